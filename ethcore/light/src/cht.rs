@@ -0,0 +1,414 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Canonical hash trie definitions and helper functions.
+//!
+//! Each CHT is a trie mapping block numbers to canonical hash and total difficulty.
+//! One is generated for every `SIZE` blocks, allowing us to discard those blocks in
+//! favor of the trie root. When the "ancient" blocks need to be accessed, we simply
+//! request an inclusion proof of a specific block number against the trie with the
+//! root hash. A correct proof implies that the claimed block is identical to the one
+//! we discarded.
+
+use std::sync::Arc;
+
+use ethcore::ids::BlockId;
+use util::{Bytes, H256, U256, HashDB, MemoryDB, OverlayDB, Mutex, RwLock, Database};
+use util::trie::{self, TrieMut, TrieDBMut, Trie, TrieDB};
+use util::trie::recorder::{Recorder, BasicRecorder};
+use rlp::{Stream, RlpStream, UntrustedRlp, View};
+
+/// The size of each CHT.
+pub const SIZE: u64 = 2048;
+
+/// Number of blocks the end of a CHT's range must lie below the best block
+/// before the range is considered final and the CHT is produced.
+pub const HISTORY: u64 = 2048;
+
+// key under which the roots of the stored CHTs are kept.
+const ROOTS_KEY: &'static [u8] = &*b"cht_roots";
+
+/// A canonical hash trie. This is generic over any database it can query.
+/// See module docs for more details.
+#[derive(Debug, Clone)]
+pub struct CHT<DB: HashDB> {
+	db: DB,
+	root: H256,
+	number: u64,
+}
+
+impl<DB: HashDB> CHT<DB> {
+	/// Get the number of this CHT.
+	pub fn number(&self) -> u64 {
+		self.number
+	}
+
+	/// Get the root of this CHT.
+	pub fn root(&self) -> H256 {
+		self.root
+	}
+
+	/// Generate an inclusion proof for the entry at a specific block.
+	/// Nodes before level `from_level` will be omitted.
+	/// Returns an error on an incomplete trie, and `Ok(None)` on an unprovable request.
+	pub fn prove(&self, num: u64, from_level: u32) -> trie::Result<Option<Vec<Bytes>>> {
+		if block_to_cht_number(num) != Some(self.number) { return Ok(None) }
+
+		let mut recorder = BasicRecorder::with_depth(from_level);
+		let t = TrieDB::new(&self.db, &self.root)?;
+		t.get_recorded(&key(num), &mut recorder)?;
+
+		Ok(Some(recorder.drain().into_iter().map(|x| x.data).collect()))
+	}
+}
+
+/// Block information necessary to build a CHT.
+pub struct BlockInfo {
+	/// The block's hash.
+	pub hash: H256,
+	/// The block's parent's hash.
+	pub parent_hash: H256,
+	/// The block's total difficulty.
+	pub total_difficulty: U256,
+}
+
+/// Build an in-memory CHT from a closure which provides necessary information
+/// about blocks. If the fetcher ever fails to provide the info, the CHT
+/// will not be generated.
+///
+/// The fetcher is queried for the last block of the CHT by number first and
+/// by parent hash afterwards, so the CHT is always built from a single chain.
+pub fn build<F>(cht_num: u64, mut fetcher: F) -> Option<CHT<MemoryDB>>
+	where F: FnMut(BlockId) -> Option<BlockInfo>
+{
+	let mut db = MemoryDB::new();
+
+	// start from the last block by number and work backwards.
+	let last_num = start_number(cht_num + 1) - 1;
+	let mut id = BlockId::Number(last_num);
+
+	let mut root = H256::default();
+
+	{
+		let mut t = TrieDBMut::new(&mut db, &mut root);
+		for blk_num in (0..SIZE).map(|n| last_num - n) {
+			let info = match fetcher(id) {
+				Some(info) => info,
+				None => return None,
+			};
+
+			id = BlockId::Hash(info.parent_hash);
+			t.insert(&key(blk_num), &value(&info.hash, &info.total_difficulty))
+				.expect("fresh in-memory database is infallible; qed");
+		}
+	}
+
+	Some(CHT {
+		db: db,
+		root: root,
+		number: cht_num,
+	})
+}
+
+/// Canonical hash tries stored in a database column, along with all of their nodes.
+///
+/// A CHT is built once, as soon as its range is final, and inclusion proofs
+/// are later generated directly from the stored nodes.
+pub struct Store {
+	roots: RwLock<Vec<H256>>,
+	building: Mutex<()>,
+	db: Arc<Database>,
+	col: Option<u32>,
+}
+
+impl Store {
+	/// Create a store, loading the roots of any CHTs previously stored in the given column.
+	pub fn new(db: Arc<Database>, col: Option<u32>) -> Result<Self, String> {
+		let roots = match db.get(col, ROOTS_KEY)? {
+			Some(roots) => UntrustedRlp::new(&roots).as_val().map_err(|e| format!("{}", e))?,
+			None => Vec::new(),
+		};
+
+		Ok(Store {
+			roots: RwLock::new(roots),
+			building: Mutex::new(()),
+			db: db,
+			col: col,
+		})
+	}
+
+	/// Get the number of CHTs stored. These are always CHTs `0..count`.
+	pub fn count(&self) -> u64 {
+		self.roots.read().len() as u64
+	}
+
+	/// Get the root of a stored CHT.
+	pub fn root(&self, cht_num: u64) -> Option<H256> {
+		self.roots.read().get(cht_num as usize).cloned()
+	}
+
+	/// Build and store up to `max` of the CHTs whose range has become final given
+	/// the number of the best block. The fetcher is queried as in `build`.
+	/// Stops at the first CHT which can't be built and returns the number of CHTs stored.
+	///
+	/// Proofs can be generated from the stored CHTs while new ones are being built.
+	pub fn update<F>(&self, best_block: u64, max: u64, mut fetcher: F) -> Result<u64, String>
+		where F: FnMut(BlockId) -> Option<BlockInfo>
+	{
+		let _building = self.building.lock();
+		let mut produced = 0;
+
+		while produced < max {
+			let cht_num = self.count();
+			let end = start_number(cht_num + 1) - 1;
+			if best_block < end + HISTORY { break }
+
+			let cht = match build(cht_num, &mut fetcher) {
+				Some(cht) => cht,
+				None => {
+					debug!(target: "cht", "Missing blocks for CHT {}", cht_num);
+					break;
+				}
+			};
+
+			let CHT { db: mut nodes, root, .. } = cht;
+			let mut overlay = OverlayDB::new(self.db.clone(), self.col);
+			for (key, (value, rc)) in nodes.drain() {
+				if rc > 0 {
+					overlay.emplace(key, value);
+				}
+			}
+
+			let mut batch = self.db.transaction();
+			overlay.commit_to_batch(&mut batch).map_err(|e| format!("{}", e))?;
+
+			let mut roots = self.roots.write();
+			roots.push(root);
+			batch.put(self.col, ROOTS_KEY, &*::rlp::encode(&*roots));
+			if let Err(e) = self.db.write(batch) {
+				roots.pop();
+				return Err(e);
+			}
+
+			debug!(target: "cht", "Stored CHT {} with root {}", cht_num, root);
+			produced += 1;
+		}
+
+		Ok(produced)
+	}
+
+	/// Generate an inclusion proof for a block from a stored CHT.
+	/// Nodes before level `from_level` will be omitted.
+	/// Returns `None` if the CHT hasn't been stored or the block isn't in its range.
+	pub fn prove(&self, cht_num: u64, block_num: u64, from_level: u32) -> Option<Vec<Bytes>> {
+		let root = match self.root(cht_num) {
+			Some(root) => root,
+			None => return None,
+		};
+
+		let cht = CHT {
+			db: OverlayDB::new(self.db.clone(), self.col),
+			root: root,
+			number: cht_num,
+		};
+
+		match cht.prove(block_num, from_level) {
+			Ok(proof) => proof,
+			Err(e) => {
+				warn!(target: "cht", "Stored CHT {} is incomplete: {}", cht_num, e);
+				None
+			}
+		}
+	}
+}
+
+/// Compute a CHT root from an iterator of (hash, td) pairs. Fails if shorter than
+/// SIZE items. The items are assumed to proceed sequentially from `start_number(cht_num)`.
+/// Discards the trie's nodes.
+pub fn compute_root<I>(cht_num: u64, iterable: I) -> Option<H256>
+	where I: IntoIterator<Item=(H256, U256)>
+{
+	let start_num = start_number(cht_num);
+
+	let v: Vec<_> = iterable.into_iter()
+		.take(SIZE as usize)
+		.enumerate()
+		.map(|(i, (hash, td))| (key(start_num + i as u64), value(&hash, &td)))
+		.collect();
+
+	if v.len() == SIZE as usize {
+		Some(::util::triehash::trie_root(v))
+	} else {
+		None
+	}
+}
+
+/// Check a proof for a CHT.
+/// Given a set of a trie nodes, a number to query, and a trie root,
+/// verify the given trie branch and extract the canonical hash and total difficulty.
+pub fn check_proof(proof: &[Bytes], num: u64, root: H256) -> Option<(H256, U256)> {
+	let mut db = MemoryDB::new();
+
+	for node in proof {
+		db.insert(&node[..]);
+	}
+
+	let res = match TrieDB::new(&db, &root) {
+		Err(_) => return None,
+		Ok(trie) => trie.get(&key(num)),
+	};
+
+	match res {
+		Ok(Some(val)) => {
+			let rlp = UntrustedRlp::new(&val);
+			match (rlp.val_at::<H256>(0), rlp.val_at::<U256>(1)) {
+				(Ok(hash), Ok(td)) => Some((hash, td)),
+				_ => None,
+			}
+		}
+		_ => None,
+	}
+}
+
+/// Convert a block number to a CHT number.
+/// Returns `None` for `block_num` == 0, `Some` otherwise.
+pub fn block_to_cht_number(block_num: u64) -> Option<u64> {
+	match block_num {
+		0 => None,
+		n => Some((n - 1) / SIZE),
+	}
+}
+
+/// Get the starting block of a given CHT.
+/// CHT 0 includes block 1...SIZE,
+/// CHT 1 includes block SIZE + 1 ... 2*SIZE
+/// More generally: CHT N includes block (1 + N*SIZE)...((N+1)*SIZE).
+/// This is because the genesis hash is assumed to be known
+/// and including it would be redundant.
+pub fn start_number(cht_num: u64) -> u64 {
+	(cht_num * SIZE) + 1
+}
+
+fn key(num: u64) -> Vec<u8> {
+	::rlp::encode(&num).to_vec()
+}
+
+fn value(hash: &H256, td: &U256) -> Vec<u8> {
+	let mut stream = RlpStream::new_list(2);
+	stream.append(hash).append(td);
+	stream.out()
+}
+
+#[cfg(test)]
+mod tests {
+	use ethcore::ids::BlockId;
+	use util::{H256, U256};
+	use super::*;
+
+	#[test]
+	fn size_is_lt_usize() {
+		// to ensure safe casting on the target platform.
+		assert!(SIZE < usize::max_value() as u64)
+	}
+
+	#[test]
+	fn block_to_cht_number_works() {
+		assert_eq!(block_to_cht_number(0), None);
+		assert_eq!(block_to_cht_number(1), Some(0));
+		assert_eq!(block_to_cht_number(SIZE + 1), Some(1));
+		assert_eq!(block_to_cht_number(SIZE), Some(0));
+	}
+
+	#[test]
+	fn start_number_works() {
+		assert_eq!(start_number(0), 1);
+		assert_eq!(start_number(1), SIZE + 1);
+		assert_eq!(start_number(2), SIZE * 2 + 1);
+	}
+
+	// fake chain where block `n` has hash `n` and total difficulty `n`.
+	fn info(id: BlockId) -> Option<BlockInfo> {
+		match id {
+			BlockId::Number(n) => Some(n),
+			BlockId::Hash(h) => Some(U256::from(h).low_u64()),
+			_ => None,
+		}.map(|n: u64| BlockInfo {
+			hash: H256::from(n),
+			parent_hash: H256::from(n - 1),
+			total_difficulty: n.into(),
+		})
+	}
+
+	#[test]
+	fn build_prove_and_check() {
+		let cht = build(1, info).unwrap();
+		let expected_root = compute_root(1, (start_number(1)..start_number(2)).map(|n| (H256::from(n), n.into())));
+		assert_eq!(Some(cht.root()), expected_root);
+
+		let block = SIZE + 100;
+		let proof = cht.prove(block, 0).unwrap().unwrap();
+		assert_eq!(check_proof(&proof, block, cht.root()), Some((H256::from(block), block.into())));
+		assert_eq!(check_proof(&proof, block + 1, cht.root()), None);
+		assert_eq!(cht.prove(1, 0).unwrap(), None);
+	}
+
+	#[test]
+	fn store_only_final_chts() {
+		use std::sync::Arc;
+		use devtools::RandomTempPath;
+		use util::{Database, DatabaseConfig};
+
+		let path = RandomTempPath::create_dir();
+		let db = Arc::new(Database::open(&DatabaseConfig::with_columns(Some(1)), path.as_str()).unwrap());
+
+		let store = Store::new(db.clone(), Some(0)).unwrap();
+		let end = start_number(1) - 1;
+		assert_eq!(store.update(end + HISTORY - 1, 10, info).unwrap(), 0);
+		assert_eq!(store.prove(0, 1, 0), None);
+
+		assert_eq!(store.update(end + HISTORY, 10, info).unwrap(), 1);
+		assert_eq!(store.update(end + HISTORY, 10, info).unwrap(), 0);
+		let root = store.root(0).unwrap();
+		assert_eq!(Some(root), build(0, info).map(|cht| cht.root()));
+
+		// proofs come from the stored nodes, also after reopening the store.
+		let store = Store::new(db, Some(0)).unwrap();
+		assert_eq!(store.count(), 1);
+		let proof = store.prove(0, 100, 0).unwrap();
+		assert_eq!(check_proof(&proof, 100, root), Some((H256::from(100), 100.into())));
+		assert_eq!(store.prove(0, SIZE + 1, 0), None);
+		assert_eq!(store.prove(1, SIZE + 1, 0), None);
+	}
+
+	#[test]
+	fn store_limits_chts_per_update() {
+		use std::sync::Arc;
+		use devtools::RandomTempPath;
+		use util::{Database, DatabaseConfig};
+
+		let path = RandomTempPath::create_dir();
+		let db = Arc::new(Database::open(&DatabaseConfig::with_columns(Some(1)), path.as_str()).unwrap());
+
+		let store = Store::new(db, Some(0)).unwrap();
+		let best = start_number(3) - 1 + HISTORY;
+		assert_eq!(store.update(best, 2, info).unwrap(), 2);
+		assert_eq!(store.count(), 2);
+		assert_eq!(store.update(best, 2, info).unwrap(), 1);
+		assert_eq!(store.count(), 3);
+		assert_eq!(store.root(2), build(2, info).map(|cht| cht.root()));
+	}
+}
@@ -0,0 +1,458 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Light client header chain.
+//!
+//! Unlike a full node's `BlockChain` this doesn't store much in the database.
+//! It stores candidates for the last 2048-4096 blocks as well as CHT roots for
+//! historical blocks all the way to the genesis.
+//!
+//! This is separate from the `BlockChain` for two reasons:
+//!   - It stores only headers (and a pruned subset of them)
+//!   - To allow for flexibility in the database layout.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use cht;
+
+use ethcore::blockchain_info::BlockChainInfo;
+use ethcore::error::BlockError;
+use ethcore::encoded;
+use ethcore::header::Header;
+use ethcore::ids::BlockId;
+
+use rlp::{self, Encodable, Decodable, Decoder, DecoderError, RlpStream, Stream, View, UntrustedRlp};
+use util::{H256, U256, RwLock, Database, DBTransaction};
+
+/// Key under which the best block number and the CHT roots are stored.
+const CURRENT_KEY: &'static [u8] = &*b"best_and_latest";
+
+/// Information about a block.
+#[derive(Debug, Clone)]
+pub struct BlockDescriptor {
+	/// The block's hash
+	pub hash: H256,
+	/// The block's number
+	pub number: u64,
+	/// The block's total difficulty.
+	pub total_difficulty: U256,
+}
+
+// candidate block description.
+#[derive(Debug, Clone, PartialEq)]
+struct Candidate {
+	hash: H256,
+	parent_hash: H256,
+	total_difficulty: U256,
+}
+
+// all candidates at a given height, along with the canonical one.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+	candidates: Vec<Candidate>,
+	canonical_hash: H256,
+}
+
+impl Encodable for Entry {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2);
+		s.begin_list(self.candidates.len());
+		for candidate in &self.candidates {
+			s.begin_list(3)
+				.append(&candidate.hash)
+				.append(&candidate.parent_hash)
+				.append(&candidate.total_difficulty);
+		}
+		s.append(&self.canonical_hash);
+	}
+}
+
+impl Decodable for Entry {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let rlp = decoder.as_rlp();
+
+		let mut candidates = Vec::new();
+		for candidate in rlp.at(0)?.iter() {
+			candidates.push(Candidate {
+				hash: candidate.val_at(0)?,
+				parent_hash: candidate.val_at(1)?,
+				total_difficulty: candidate.val_at(2)?,
+			});
+		}
+
+		if candidates.is_empty() { return Err(DecoderError::Custom("Empty candidates vector submitted.")) }
+
+		Ok(Entry {
+			candidates: candidates,
+			canonical_hash: rlp.val_at(1)?,
+		})
+	}
+}
+
+fn era_key(number: u64) -> Vec<u8> {
+	format!("candidates_{}", number).into_bytes()
+}
+
+/// Header chain. See module docs for more details.
+pub struct HeaderChain {
+	genesis_header: encoded::Header,
+	candidates: RwLock<BTreeMap<u64, Entry>>,
+	best_block: RwLock<BlockDescriptor>,
+	cht_roots: RwLock<Vec<H256>>,
+	db: Arc<Database>,
+	col: Option<u32>,
+}
+
+impl HeaderChain {
+	/// Create a header chain, loading any state previously stored in the given database column.
+	/// `genesis` should be the RLP-encoded genesis header.
+	pub fn new(db: Arc<Database>, col: Option<u32>, genesis: &[u8]) -> Result<Self, String> {
+		let genesis_header = encoded::Header::new(genesis.to_vec());
+		let mut best_block = BlockDescriptor {
+			hash: genesis_header.hash(),
+			number: 0,
+			total_difficulty: genesis_header.difficulty(),
+		};
+		let mut candidates = BTreeMap::new();
+		let mut cht_roots = Vec::new();
+
+		if let Some(current) = db.get(col, CURRENT_KEY)? {
+			let current = UntrustedRlp::new(&current);
+			let best_number: u64 = current.val_at(0).map_err(|e| format!("{}", e))?;
+			cht_roots = current.val_at(1).map_err(|e| format!("{}", e))?;
+
+			let mut number = cht::start_number(cht_roots.len() as u64);
+			while let Some(entry) = db.get(col, &era_key(number))? {
+				let entry: Entry = UntrustedRlp::new(&entry).as_val().map_err(|e| format!("{}", e))?;
+				if number == best_number {
+					let best = entry.candidates.iter().find(|c| c.hash == entry.canonical_hash)
+						.expect("canonical hash is always one of the candidates; qed");
+					best_block = BlockDescriptor {
+						hash: best.hash,
+						number: number,
+						total_difficulty: best.total_difficulty,
+					};
+				}
+				candidates.insert(number, entry);
+				number += 1;
+			}
+		}
+
+		Ok(HeaderChain {
+			genesis_header: genesis_header,
+			candidates: RwLock::new(candidates),
+			best_block: RwLock::new(best_block),
+			cht_roots: RwLock::new(cht_roots),
+			db: db,
+			col: col,
+		})
+	}
+
+	/// Insert a pre-verified header into the header chain, writing the changes
+	/// into the given transaction.
+	///
+	/// This blindly trusts that the data given to it is sensible.
+	/// Fails if the parent is not known.
+	pub fn insert(&self, batch: &mut DBTransaction, header: Header) -> Result<(), BlockError> {
+		let hash = header.hash();
+		let number = header.number();
+		let parent_hash = *header.parent_hash();
+
+		// hold candidates the whole time to guard import order.
+		let mut candidates = self.candidates.write();
+
+		// find parent details.
+		let parent_td = if number == 1 && parent_hash == self.genesis_header.hash() {
+			Some(self.genesis_header.difficulty())
+		} else {
+			candidates.get(&(number.saturating_sub(1)))
+				.and_then(|entry| entry.candidates.iter().find(|c| c.hash == parent_hash))
+				.map(|c| c.total_difficulty)
+		};
+		let parent_td = parent_td.ok_or_else(|| BlockError::UnknownParent(parent_hash))?;
+
+		let total_difficulty = parent_td + *header.difficulty();
+
+		// insert into the candidates and the database.
+		{
+			let entry = candidates.entry(number)
+				.or_insert_with(|| Entry { candidates: Vec::new(), canonical_hash: hash });
+			if entry.candidates.iter().all(|c| c.hash != hash) {
+				entry.candidates.push(Candidate {
+					hash: hash,
+					parent_hash: parent_hash,
+					total_difficulty: total_difficulty,
+				});
+			}
+			batch.put(self.col, &era_key(number), &*rlp::encode(&*entry));
+		}
+		batch.put(self.col, &hash, &*rlp::encode(&header));
+
+		// reorganize ancestors so that the new block is canonical if it's the best one.
+		let mut best_block = self.best_block.write();
+		if total_difficulty > best_block.total_difficulty {
+			let mut canon_hash = hash;
+			for (&height, entry) in candidates.iter_mut().rev().skip_while(|&(height, _)| *height > number) {
+				if height != number && entry.canonical_hash == canon_hash { break }
+
+				let parent_hash = entry.candidates.iter().find(|c| c.hash == canon_hash)
+					.map(|c| c.parent_hash)
+					.expect("blocks are only inserted if parent is present; or this is the block we just added; qed");
+
+				if entry.canonical_hash != canon_hash {
+					trace!(target: "chain", "Setting new canonical block {} for block height {}", canon_hash, height);
+					entry.canonical_hash = canon_hash;
+					batch.put(self.col, &era_key(height), &*rlp::encode(&*entry));
+				}
+				canon_hash = parent_hash;
+			}
+
+			*best_block = BlockDescriptor {
+				hash: hash,
+				number: number,
+				total_difficulty: total_difficulty,
+			};
+		}
+
+		// produce the next CHT once its range is more than `cht::HISTORY` blocks below the best block.
+		// reorganizations deeper than this are not supported.
+		let mut cht_roots = self.cht_roots.write();
+		loop {
+			let cht_num = cht_roots.len() as u64;
+			let start = cht::start_number(cht_num);
+			let end = start + cht::SIZE - 1;
+			if best_block.number < end + cht::HISTORY || candidates.keys().next() != Some(&start) { break }
+
+			let mut canonical = Vec::with_capacity(cht::SIZE as usize);
+			for height in start..(end + 1) {
+				let entry = candidates.remove(&height)
+					.expect("candidates are contiguous from the start of the current CHT to the best block; qed");
+				batch.delete(self.col, &era_key(height));
+
+				for candidate in &entry.candidates {
+					batch.delete(self.col, &candidate.hash);
+				}
+
+				let canonical_hash = entry.canonical_hash;
+				let canon = entry.candidates.into_iter().find(|c| c.hash == canonical_hash)
+					.expect("canonical hash is always one of the candidates; qed");
+				canonical.push((canon.hash, canon.total_difficulty));
+			}
+
+			let root = cht::compute_root(cht_num, canonical)
+				.expect("exactly `cht::SIZE` canonical entries collected; qed");
+			debug!(target: "chain", "Produced CHT {} with root {}", cht_num, root);
+			cht_roots.push(root);
+		}
+
+		let mut current = RlpStream::new_list(2);
+		current.append(&best_block.number).append(&*cht_roots);
+		batch.put(self.col, CURRENT_KEY, &current.out());
+
+		Ok(())
+	}
+
+	/// Get a block's hash by id. Blocks covered by a CHT are not known by number.
+	pub fn block_hash(&self, id: BlockId) -> Option<H256> {
+		match id {
+			BlockId::Earliest | BlockId::Number(0) => Some(self.genesis_hash()),
+			BlockId::Hash(hash) => Some(hash),
+			BlockId::Number(num) => {
+				if self.best_block.read().number < num { return None }
+				self.candidates.read().get(&num).map(|entry| entry.canonical_hash)
+			}
+			BlockId::Latest | BlockId::Pending => Some(self.best_block.read().hash),
+		}
+	}
+
+	/// Get a block header. In the case of query by number, only canonical blocks
+	/// which are not yet covered by a CHT will be returned.
+	pub fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+		let hash = match self.block_hash(id) {
+			Some(hash) => hash,
+			None => return None,
+		};
+
+		if hash == self.genesis_hash() {
+			return Some(self.genesis_header.clone());
+		}
+
+		match self.db.get(self.col, &hash) {
+			Ok(header) => header.map(|header| encoded::Header::new(header.to_vec())),
+			Err(e) => {
+				warn!(target: "chain", "Failed to read header {} from database: {}", hash, e);
+				None
+			}
+		}
+	}
+
+	/// Get the total difficulty of a block which is not yet covered by a CHT.
+	pub fn total_difficulty(&self, hash: &H256) -> Option<U256> {
+		if hash == &self.genesis_hash() {
+			return Some(self.genesis_header.difficulty());
+		}
+
+		self.candidates.read().values()
+			.flat_map(|entry| entry.candidates.iter())
+			.find(|c| &c.hash == hash)
+			.map(|c| c.total_difficulty)
+	}
+
+	/// Get the nth CHT root, if it has been computed.
+	///
+	/// CHT root 0 is from block `1..2048`.
+	/// CHT root 1 is from block `2049..4096`
+	/// and so on.
+	///
+	/// This is because it's assumed that the genesis hash is known,
+	/// so including it within a CHT would be redundant.
+	pub fn cht_root(&self, n: usize) -> Option<H256> {
+		self.cht_roots.read().get(n).cloned()
+	}
+
+	/// Get the genesis hash.
+	pub fn genesis_hash(&self) -> H256 {
+		self.genesis_header.hash()
+	}
+
+	/// Get the best block's data.
+	pub fn best_block(&self) -> BlockDescriptor {
+		self.best_block.read().clone()
+	}
+
+	/// Whether the given block is known to the header chain.
+	pub fn is_known(&self, hash: &H256) -> bool {
+		self.total_difficulty(hash).is_some()
+	}
+
+	/// Get the blockchain info.
+	pub fn chain_info(&self) -> BlockChainInfo {
+		let best_block = self.best_block();
+		let first_block = self.candidates.read().keys().next().cloned();
+
+		BlockChainInfo {
+			total_difficulty: best_block.total_difficulty,
+			pending_total_difficulty: best_block.total_difficulty,
+			genesis_hash: self.genesis_hash(),
+			best_block_hash: best_block.hash,
+			best_block_number: best_block.number,
+			ancient_block_hash: None,
+			ancient_block_number: None,
+			first_block_hash: first_block.and_then(|n| self.block_hash(BlockId::Number(n))),
+			first_block_number: first_block,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use super::HeaderChain;
+	use cht;
+
+	use ethcore::ids::BlockId;
+	use ethcore::header::Header;
+	use ethcore::spec::Spec;
+	use devtools::RandomTempPath;
+	use util::{U256, Database, DatabaseConfig};
+
+	fn make_db() -> (RandomTempPath, Arc<Database>) {
+		let path = RandomTempPath::create_dir();
+		let db = Database::open(&DatabaseConfig::with_columns(Some(1)), path.as_str()).unwrap();
+		(path, Arc::new(db))
+	}
+
+	fn import_chain(chain: &HeaderChain, db: &Database, parent: Header, count: u64, difficulty: U256) -> Header {
+		let mut parent = parent;
+		for _ in 0..count {
+			let mut header = Header::new();
+			header.set_parent_hash(parent.hash());
+			header.set_number(parent.number() + 1);
+			header.set_difficulty(difficulty);
+			header.set_timestamp(parent.timestamp() + 1);
+
+			let mut batch = db.transaction();
+			chain.insert(&mut batch, header.clone()).unwrap();
+			db.write(batch).unwrap();
+			parent = header;
+		}
+		parent
+	}
+
+	#[test]
+	fn basic_chain() {
+		let spec = Spec::new_test();
+		let genesis_header = spec.genesis_header();
+		let (_path, db) = make_db();
+
+		let chain = HeaderChain::new(db.clone(), Some(0), &::rlp::encode(&genesis_header)).unwrap();
+		let rolling_timestamp = genesis_header.timestamp();
+
+		let best = import_chain(&chain, &db, genesis_header.clone(), 10_000, 10.into());
+		assert!(rolling_timestamp < best.timestamp());
+
+		assert_eq!(chain.best_block().number, 10_000);
+		assert_eq!(chain.best_block().hash, best.hash());
+		assert!(chain.block_header(BlockId::Number(10)).is_none());
+		assert!(chain.block_header(BlockId::Number(9000)).is_some());
+		assert!(chain.cht_root(2).is_some());
+		assert!(chain.cht_root(3).is_none());
+	}
+
+	#[test]
+	fn reorganize() {
+		let spec = Spec::new_test();
+		let genesis_header = spec.genesis_header();
+		let (_path, db) = make_db();
+
+		let chain = HeaderChain::new(db.clone(), Some(0), &::rlp::encode(&genesis_header)).unwrap();
+
+		let fork_point = import_chain(&chain, &db, genesis_header.clone(), 5, 10.into());
+		let weak = import_chain(&chain, &db, fork_point.clone(), 5, 10.into());
+		assert_eq!(chain.best_block().hash, weak.hash());
+
+		// a shorter chain with more total difficulty wins.
+		let strong = import_chain(&chain, &db, fork_point.clone(), 3, 20.into());
+		assert_eq!(chain.best_block().hash, strong.hash());
+		assert_eq!(chain.best_block().number, 8);
+		assert_eq!(chain.block_hash(BlockId::Number(8)), Some(strong.hash()));
+		assert_eq!(chain.block_hash(BlockId::Number(5)), Some(fork_point.hash()));
+		assert!(chain.block_hash(BlockId::Number(9)).is_none());
+		assert_eq!(chain.block_header(BlockId::Hash(weak.hash())).unwrap().hash(), weak.hash());
+	}
+
+	#[test]
+	fn persistence() {
+		let spec = Spec::new_test();
+		let genesis_header = spec.genesis_header();
+		let (_path, db) = make_db();
+
+		let best = {
+			let chain = HeaderChain::new(db.clone(), Some(0), &::rlp::encode(&genesis_header)).unwrap();
+			import_chain(&chain, &db, genesis_header.clone(), cht::SIZE * 2 + 10, 10.into())
+		};
+
+		let chain = HeaderChain::new(db.clone(), Some(0), &::rlp::encode(&genesis_header)).unwrap();
+		assert_eq!(chain.best_block().hash, best.hash());
+		assert_eq!(chain.best_block().number, cht::SIZE * 2 + 10);
+		assert!(chain.cht_root(0).is_some());
+		assert!(chain.cht_root(1).is_none());
+		assert_eq!(chain.block_header(BlockId::Latest).unwrap().hash(), best.hash());
+	}
+}
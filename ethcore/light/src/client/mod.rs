@@ -0,0 +1,279 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Light client implementation. Stores data from light sync
+
+use std::sync::Arc;
+
+use ethcore::client::{EnvInfo, PartialState, PartialCall};
+use ethcore::engines::Engine;
+use ethcore::error::CallError;
+use ethcore::ids::BlockId;
+use ethcore::service::ClientIoMessage;
+use ethcore::block_import_error::BlockImportError;
+use ethcore::block_status::BlockStatus;
+use ethcore::verification::queue::{self, HeaderQueue, QueueInfo};
+use ethcore::transaction::{SignedTransaction, PendingTransaction};
+use ethcore::blockchain_info::BlockChainInfo;
+use ethcore::spec::Spec;
+use ethcore::encoded;
+
+use io::IoChannel;
+use util::hash::{H256, H256FastMap};
+use util::{Bytes, Mutex, Database, U256};
+
+use cht;
+
+use provider::Provider;
+use request;
+
+use self::header_chain::HeaderChain;
+
+pub use self::header_chain::BlockDescriptor;
+
+mod header_chain;
+
+/// Configuration for the light client.
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+	/// Verification queue config.
+	pub queue: queue::Config,
+	/// Database column the header chain is stored in.
+	pub chain_column: Option<u32>,
+}
+
+/// Light client implementation.
+///
+/// Executing calls requires the database to have the state and account bloom columns
+/// of a full client database (see `ethcore::db`), which are used as scratch space.
+pub struct Client {
+	engine: Arc<Engine>,
+	header_queue: HeaderQueue,
+	chain: HeaderChain,
+	db: Arc<Database>,
+	_message_channel: Mutex<IoChannel<ClientIoMessage>>,
+	tx_pool: Mutex<H256FastMap<SignedTransaction>>,
+}
+
+impl Client {
+	/// Create a new `Client`, loading the header chain from the given database.
+	pub fn new(config: Config, db: Arc<Database>, spec: &Spec, io_channel: IoChannel<ClientIoMessage>) -> Result<Self, String> {
+		let genesis = ::rlp::encode(&spec.genesis_header());
+
+		Ok(Client {
+			engine: spec.engine.clone(),
+			header_queue: HeaderQueue::new(config.queue, spec.engine.clone(), io_channel.clone(), true),
+			chain: HeaderChain::new(db.clone(), config.chain_column, &genesis)?,
+			db: db,
+			_message_channel: Mutex::new(io_channel),
+			tx_pool: Mutex::new(Default::default()),
+		})
+	}
+
+	/// Import a header as rlp-encoded bytes.
+	pub fn import_header(&self, bytes: Bytes) -> Result<H256, BlockImportError> {
+		let header = ::rlp::decode(&bytes);
+
+		self.header_queue.import(header).map_err(Into::into)
+	}
+
+	/// Whether the block is already known (but not necessarily part of the canonical chain)
+	pub fn is_known(&self, id: BlockId) -> bool {
+		self.status(id) == BlockStatus::InChain
+	}
+
+	/// Import a local transaction.
+	pub fn import_own_transaction(&self, tx: SignedTransaction) {
+		self.tx_pool.lock().insert(tx.hash(), tx);
+	}
+
+	/// Fetch a vector of all pending transactions.
+	pub fn pending_transactions(&self) -> Vec<SignedTransaction> {
+		self.tx_pool.lock().values().cloned().collect()
+	}
+
+	/// Inquire about the status of a given block (or header).
+	pub fn status(&self, id: BlockId) -> BlockStatus {
+		match id {
+			BlockId::Hash(hash) if self.chain.is_known(&hash) => BlockStatus::InChain,
+			BlockId::Hash(hash) => self.header_queue.status(&hash).into(),
+			id => match self.chain.block_hash(id) {
+				Some(_) => BlockStatus::InChain,
+				None => BlockStatus::Unknown,
+			},
+		}
+	}
+
+	/// Get the header queue info.
+	pub fn queue_info(&self) -> QueueInfo {
+		self.header_queue.queue_info()
+	}
+
+	/// Get the blockchain info.
+	pub fn chain_info(&self) -> BlockChainInfo {
+		self.chain.chain_info()
+	}
+
+	/// Get a block header by id. Only the genesis and blocks not yet covered
+	/// by a CHT are available.
+	pub fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+		self.chain.block_header(id)
+	}
+
+	/// Get the total difficulty of a block by hash. Only the genesis and blocks
+	/// not yet covered by a CHT are known.
+	pub fn total_difficulty(&self, hash: &H256) -> Option<U256> {
+		self.chain.total_difficulty(hash)
+	}
+
+	/// Get the best block's descriptor.
+	pub fn best_block(&self) -> BlockDescriptor {
+		self.chain.best_block()
+	}
+
+	/// Get a handle to the consensus engine.
+	pub fn engine(&self) -> &Arc<Engine> {
+		&self.engine
+	}
+
+	/// Execute a call at the given block against the known part of its state.
+	/// See `PartialState` for how to complete the state a call needs.
+	pub fn call(&self, header: &encoded::Header, state: &PartialState, t: &SignedTransaction) -> Result<PartialCall, CallError> {
+		let mut last_hashes = vec![H256::default(); 256];
+		last_hashes[0] = header.parent_hash();
+		for i in 0..255 {
+			match self.chain.block_header(BlockId::Hash(last_hashes[i])) {
+				Some(parent) => last_hashes[i + 1] = parent.parent_hash(),
+				None => break,
+			}
+		}
+
+		let env_info = EnvInfo {
+			number: header.number(),
+			author: header.author(),
+			timestamp: header.timestamp(),
+			difficulty: header.difficulty(),
+			last_hashes: Arc::new(last_hashes),
+			gas_used: U256::zero(),
+			gas_limit: U256::max_value(),
+		};
+
+		state.call(self.db.clone(), &env_info, &*self.engine, t)
+	}
+
+	/// Get the nth CHT root, if it's been computed.
+	pub fn cht_root(&self, i: usize) -> Option<H256> {
+		self.chain.cht_root(i)
+	}
+
+	/// Verify a header proof response against the locally stored CHT root,
+	/// returning the proven header along with its total difficulty.
+	pub fn verify_header_proof(&self, req: &request::HeaderProof, header: &[u8], proof: &[Bytes]) -> Option<(encoded::Header, U256)> {
+		let root = match self.chain.cht_root(req.cht_number as usize) {
+			Some(root) => root,
+			None => return None,
+		};
+
+		let header = encoded::Header::new(header.to_vec());
+		match cht::check_proof(proof, req.block_number, root) {
+			Some((hash, td)) if hash == header.hash() && header.number() == req.block_number => Some((header, td)),
+			_ => None,
+		}
+	}
+
+	/// Flush the header queue.
+	pub fn flush_queue(&self) {
+		self.header_queue.flush()
+	}
+
+	/// Import a batch of verified headers from the queue into the header chain.
+	pub fn import_verified(&self) {
+		const MAX: usize = 256;
+
+		let mut bad = Vec::new();
+		let mut good = Vec::new();
+		for verified_header in self.header_queue.drain(MAX) {
+			let (num, hash) = (verified_header.number(), verified_header.hash());
+
+			let family = match self.chain.block_header(BlockId::Hash(*verified_header.parent_hash())) {
+				Some(parent) => self.engine.verify_block_family(&verified_header, &parent.decode(), None).map_err(|e| format!("{}", e)),
+				None => Err("Unknown parent".into()),
+			};
+
+			let mut batch = self.db.transaction();
+			let res = family.and_then(|_| self.chain.insert(&mut batch, verified_header).map_err(|e| format!("{}", e)));
+			match res {
+				Ok(()) => {
+					self.db.write_buffered(batch);
+					good.push(hash);
+				}
+				Err(e) => {
+					debug!(target: "client", "Error importing header {:?}: {}", (num, hash), e);
+					bad.push(hash);
+				}
+			}
+		}
+
+		if let Err(e) = self.db.flush() {
+			warn!(target: "client", "Failed to flush header chain: {}", e);
+		}
+
+		self.header_queue.mark_as_bad(&bad);
+		self.header_queue.mark_as_good(&good);
+	}
+}
+
+impl Provider for Client {
+	fn chain_info(&self) -> BlockChainInfo {
+		Client::chain_info(self)
+	}
+
+	fn reorg_depth(&self, _a: &H256, _b: &H256) -> Option<u64> {
+		None
+	}
+
+	fn earliest_state(&self) -> Option<u64> {
+		None
+	}
+
+	fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+		Client::block_header(self, id)
+	}
+
+	fn block_body(&self, _id: BlockId) -> Option<encoded::Body> {
+		None
+	}
+
+	fn block_receipts(&self, _hash: &H256) -> Option<Bytes> {
+		None
+	}
+
+	fn state_proof(&self, _req: request::StateProof) -> Vec<Bytes> {
+		Vec::new()
+	}
+
+	fn contract_code(&self, _req: request::ContractCode) -> Bytes {
+		Vec::new()
+	}
+
+	fn header_proof(&self, _req: request::HeaderProof) -> Option<(encoded::Header, Vec<Bytes>)> {
+		None
+	}
+
+	fn ready_transactions(&self) -> Vec<PendingTransaction> {
+		Vec::new()
+	}
+}
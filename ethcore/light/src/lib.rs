@@ -31,7 +31,9 @@
 #![deny(missing_docs)]
 
 pub mod client;
+pub mod cht;
 pub mod net;
+pub mod on_demand;
 
 #[cfg(not(feature = "ipc"))]
 pub mod provider;
@@ -49,7 +51,7 @@ pub mod remote {
 
 mod types;
 
-pub use self::provider::Provider;
+pub use self::provider::{Provider, ChtProvider};
 pub use types::les_request as request;
 
 #[macro_use]
@@ -61,6 +63,10 @@ extern crate ethcore_network as network;
 extern crate ethcore_io as io;
 extern crate rlp;
 extern crate time;
+extern crate futures;
+
+#[cfg(test)]
+extern crate ethcore_devtools as devtools;
 
 #[cfg(feature = "ipc")]
 extern crate ethcore_ipc as ipc;
\ No newline at end of file
@@ -16,7 +16,9 @@
 
 //! I/O and event context generalizations.
 
+use ethcore::transaction::SignedTransaction;
 use network::{NetworkContext, PeerId, NodeId};
+use util::U256;
 
 use super::{Announcement, LightProtocol, ReqId};
 use super::error::Error;
@@ -39,6 +41,9 @@ pub trait IoContext {
 	/// Disable a peer -- this is a disconnect + a time-out.
 	fn disable_peer(&self, peer: PeerId);
 
+	/// Adjust a peer's reputation. Peers are banned once it drops below the network threshold.
+	fn report_peer(&self, peer: PeerId, weight: i32);
+
 	/// Get a peer's protocol version.
 	fn protocol_version(&self, peer: PeerId) -> Option<u8>;
 
@@ -68,6 +73,10 @@ impl<'a> IoContext for NetworkContext<'a> {
 		NetworkContext::disable_peer(self, peer);
 	}
 
+	fn report_peer(&self, peer: PeerId, weight: i32) {
+		NetworkContext::report_peer(self, peer, weight);
+	}
+
 	fn protocol_version(&self, peer: PeerId) -> Option<u8> {
 		self.protocol_version(self.subprotocol_name(), peer)
 	}
@@ -77,18 +86,21 @@ impl<'a> IoContext for NetworkContext<'a> {
 	}
 }
 
-/// Context for a protocol event.
-pub trait EventContext {
-	/// Get the peer relevant to the event e.g. message sender,
-	/// disconnected/connected peer.
-	fn peer(&self) -> PeerId;
-
+/// Basic context for the protocol.
+pub trait BasicContext {
 	/// Returns the relevant's peer persistent Id (aka NodeId).
 	fn persistent_peer_id(&self, peer: PeerId) -> Option<NodeId>;
 
 	/// Make a request from a peer.
 	fn request_from(&self, peer: PeerId, request: Request) -> Result<ReqId, Error>;
 
+	/// Estimate the credits a peer would have left after serving a request.
+	/// `None` if the peer can't serve it at all.
+	fn credits_left(&self, peer: PeerId, request: &Request) -> Option<U256>;
+
+	/// Send transactions to all peers which relay them. Returns the number of peers.
+	fn send_transactions(&self, txs: &[SignedTransaction]) -> usize;
+
 	/// Make an announcement of new capabilities to the rest of the peers.
 	// TODO: maybe just put this on a timer in LightProtocol?
 	fn make_announcement(&self, announcement: Announcement);
@@ -98,6 +110,61 @@ pub trait EventContext {
 
 	/// Disable a peer.
 	fn disable_peer(&self, peer: PeerId);
+
+	/// Adjust a peer's reputation.
+	fn report_peer(&self, peer: PeerId, weight: i32);
+}
+
+/// Context for a protocol event which has a peer ID attached.
+pub trait EventContext: BasicContext {
+	/// Get the peer relevant to the event e.g. message sender,
+	/// disconnected/connected peer.
+	fn peer(&self) -> PeerId;
+
+	/// Treat the event context as a basic context.
+	fn as_basic(&self) -> &BasicContext;
+}
+
+/// Basic context.
+pub struct TickCtx<'a> {
+	/// Io context to enable dispatch.
+	pub io: &'a IoContext,
+	/// Protocol implementation.
+	pub proto: &'a LightProtocol,
+}
+
+impl<'a> BasicContext for TickCtx<'a> {
+	fn persistent_peer_id(&self, id: PeerId) -> Option<NodeId> {
+		self.io.persistent_peer_id(id)
+	}
+
+	fn request_from(&self, peer: PeerId, request: Request) -> Result<ReqId, Error> {
+		self.proto.request_from(self.io, &peer, request)
+	}
+
+	fn credits_left(&self, peer: PeerId, request: &Request) -> Option<U256> {
+		self.proto.credits_left(peer, request)
+	}
+
+	fn send_transactions(&self, txs: &[SignedTransaction]) -> usize {
+		self.proto.send_transactions(self.io, txs)
+	}
+
+	fn make_announcement(&self, announcement: Announcement) {
+		self.proto.make_announcement(self.io, announcement);
+	}
+
+	fn disconnect_peer(&self, peer: PeerId) {
+		self.io.disconnect_peer(peer);
+	}
+
+	fn disable_peer(&self, peer: PeerId) {
+		self.io.disable_peer(peer);
+	}
+
+	fn report_peer(&self, peer: PeerId, weight: i32) {
+		self.io.report_peer(peer, weight);
+	}
 }
 
 /// Concrete implementation of `EventContext` over the light protocol struct and
@@ -111,11 +178,7 @@ pub struct Ctx<'a> {
 	pub peer: PeerId,
 }
 
-impl<'a> EventContext for Ctx<'a> {
-	fn peer(&self) -> PeerId {
-		self.peer
-	}
-
+impl<'a> BasicContext for Ctx<'a> {
 	fn persistent_peer_id(&self, id: PeerId) -> Option<NodeId> {
 		self.io.persistent_peer_id(id)
 	}
@@ -124,6 +187,14 @@ impl<'a> EventContext for Ctx<'a> {
 		self.proto.request_from(self.io, &peer, request)
 	}
 
+	fn credits_left(&self, peer: PeerId, request: &Request) -> Option<U256> {
+		self.proto.credits_left(peer, request)
+	}
+
+	fn send_transactions(&self, txs: &[SignedTransaction]) -> usize {
+		self.proto.send_transactions(self.io, txs)
+	}
+
 	fn make_announcement(&self, announcement: Announcement) {
 		self.proto.make_announcement(self.io, announcement);
 	}
@@ -135,4 +206,18 @@ impl<'a> EventContext for Ctx<'a> {
 	fn disable_peer(&self, peer: PeerId) {
 		self.io.disable_peer(peer);
 	}
+
+	fn report_peer(&self, peer: PeerId, weight: i32) {
+		self.io.report_peer(peer, weight);
+	}
+}
+
+impl<'a> EventContext for Ctx<'a> {
+	fn peer(&self) -> PeerId {
+		self.peer
+	}
+
+	fn as_basic(&self) -> &BasicContext {
+		&*self
+	}
 }
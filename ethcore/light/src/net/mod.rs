@@ -37,8 +37,8 @@ use provider::Provider;
 use request::{self, HashOrNumber, Request};
 
 use self::buffer_flow::{Buffer, FlowParams};
-use self::context::Ctx;
-use self::error::{Error, Punishment};
+use self::context::{Ctx, TickCtx};
+use self::error::Punishment;
 
 mod buffer_flow;
 mod context;
@@ -48,7 +48,8 @@ mod status;
 #[cfg(test)]
 mod tests;
 
-pub use self::context::{EventContext, IoContext};
+pub use self::context::{BasicContext, EventContext, IoContext};
+pub use self::error::Error;
 pub use self::status::{Status, Capabilities, Announcement};
 
 const TIMEOUT: TimerToken = 0;
@@ -57,6 +58,10 @@ const TIMEOUT_INTERVAL_MS: u64 = 1000;
 // minimum interval between updates.
 const UPDATE_INTERVAL_MS: i64 = 5000;
 
+// reputation weights reported to the network host when punishing peers.
+const DISCONNECT_REPUTATION: i32 = -20;
+const DISABLE_REPUTATION: i32 = -50;
+
 /// Supported protocol versions.
 pub const PROTOCOL_VERSIONS: &'static [u8] = &[1];
 
@@ -116,7 +121,7 @@ mod timeout {
 
 /// A request id.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct ReqId(usize);
+pub struct ReqId(pub usize);
 
 // A pending peer: one we've sent our status to but
 // may not have received one for.
@@ -188,6 +193,8 @@ pub trait Handler: Send + Sync {
 	/// Called when a peer responds with header proofs. Each proof is a block header coupled
 	/// with a series of trie nodes is ascending order by distance from the root.
 	fn on_header_proofs(&self, _ctx: &EventContext, _req_id: ReqId, _proofs: &[(Bytes, Vec<Bytes>)]) { }
+	/// Called to "tick" the handler periodically.
+	fn tick(&self, _ctx: &BasicContext) { }
 	/// Called on abort.
 	fn on_abort(&self) { }
 }
@@ -228,7 +235,7 @@ pub struct LightProtocol {
 	pending_requests: RwLock<HashMap<usize, Requested>>,
 	capabilities: RwLock<Capabilities>,
 	flow_params: FlowParams, // assumed static and same for every peer.
-	handlers: Vec<Box<Handler>>,
+	handlers: Vec<Arc<Handler>>,
 	req_id: AtomicUsize,
 }
 
@@ -267,6 +274,25 @@ impl LightProtocol {
 		})
 	}
 
+	/// Estimate the credits a peer would have left after serving a request.
+	/// Returns `None` if the peer isn't a server or can't afford the request.
+	pub fn credits_left(&self, peer: PeerId, request: &Request) -> Option<U256> {
+		self.peers.read().get(&peer).and_then(|peer| {
+			let mut peer = peer.lock();
+			match peer.remote_flow.as_mut() {
+				Some(&mut (ref mut buf, ref flow)) => {
+					flow.recharge(buf);
+					let cost = flow.compute_cost(request.kind(), request.amount());
+					match buf.current() {
+						current if current >= cost => Some(current - cost),
+						_ => None,
+					}
+				}
+				None => None,
+			}
+		})
+	}
+
 	/// Make a request to a peer.
 	///
 	/// Fails on: nonexistent peer, network error, peer not server,
@@ -354,15 +380,45 @@ impl LightProtocol {
 		}
 	}
 
+	/// Send transactions to every peer which relays them to the eth network.
+	/// Returns the number of peers they were sent to.
+	pub fn send_transactions(&self, io: &IoContext, txs: &[SignedTransaction]) -> usize {
+		let mut stream = RlpStream::new_list(txs.len());
+		for tx in txs {
+			stream.append(tx);
+		}
+		let payload = stream.out();
+
+		let mut sent = 0;
+		for (peer_id, peer) in self.peers.read().iter() {
+			if peer.lock().capabilities.tx_relay {
+				io.send(*peer_id, packet::SEND_TRANSACTIONS, payload.clone());
+				sent += 1;
+			}
+		}
+
+		sent
+	}
+
 	/// Add an event handler.
-	/// Ownership will be transferred to the protocol structure,
-	/// and the handler will be kept alive as long as it is.
+	/// The protocol structure keeps a shared reference to the handler,
+	/// so it will be kept alive at least as long as the protocol is.
 	/// These are intended to be added when the protocol structure
 	/// is initialized as a means of customizing its behavior.
-	pub fn add_handler(&mut self, handler: Box<Handler>) {
+	pub fn add_handler(&mut self, handler: Arc<Handler>) {
 		self.handlers.push(handler);
 	}
 
+	/// Execute the given closure with a basic context derived from the I/O context.
+	pub fn with_context<F, T>(&self, io: &IoContext, f: F) -> T
+		where F: FnOnce(&BasicContext) -> T
+	{
+		f(&TickCtx {
+			io: io,
+			proto: self,
+		})
+	}
+
 	/// Signal to handlers that network activity is being aborted
 	/// and clear peer data.
 	pub fn abort(&self) {
@@ -505,6 +561,15 @@ impl LightProtocol {
 			}
 		}
 	}
+
+	fn tick_handlers(&self, io: &IoContext) {
+		for handler in &self.handlers {
+			handler.tick(&TickCtx {
+				io: io,
+				proto: self,
+			})
+		}
+	}
 }
 
 impl LightProtocol {
@@ -1112,10 +1177,12 @@ fn punish(peer: PeerId, io: &IoContext, e: Error) {
 		Punishment::None => {}
 		Punishment::Disconnect => {
 			debug!(target: "les", "Disconnecting peer {}: {}", peer, e);
+			io.report_peer(peer, DISCONNECT_REPUTATION);
 			io.disconnect_peer(peer)
 		}
 		Punishment::Disable => {
 			debug!(target: "les", "Disabling peer {}: {}", peer, e);
+			io.report_peer(peer, DISABLE_REPUTATION);
 			io.disable_peer(peer)
 		}
 	}
@@ -1140,7 +1207,10 @@ impl NetworkProtocolHandler for LightProtocol {
 
 	fn timeout(&self, io: &NetworkContext, timer: TimerToken) {
 		match timer {
-			TIMEOUT => self.timeout_check(io),
+			TIMEOUT => {
+				self.timeout_check(io);
+				self.tick_handlers(io);
+			}
 			_ => warn!(target: "les", "received timeout on unknown token {}", timer),
 		}
 	}
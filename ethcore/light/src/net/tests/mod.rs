@@ -66,6 +66,8 @@ impl IoContext for Expect {
 		assert_eq!(self, &Expect::Punish(peer));
 	}
 
+	fn report_peer(&self, _peer: PeerId, _weight: i32) {}
+
 	fn protocol_version(&self, _peer: PeerId) -> Option<u8> {
 		Some(super::MAX_PROTOCOL_VERSION)
 	}
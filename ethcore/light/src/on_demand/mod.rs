@@ -0,0 +1,605 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! On-demand chain requests over LES. This is a major building block for RPCs.
+//! The request service is implemented using Futures. Higher level request handlers
+//! will take the raw data received here and extract meaningful results from it.
+
+use std::collections::HashMap;
+
+use ethcore::encoded;
+use ethcore::receipt::Receipt;
+
+use futures::{self, Complete, Oneshot};
+use network::PeerId;
+use time::{Duration, SteadyTime};
+use util::{Bytes, H256, U256, RwLock};
+use util::sha3::Hashable;
+
+use net::{Handler, Status, Capabilities, Announcement, EventContext, BasicContext, ReqId, Error as NetError};
+use cht;
+use request as les_request;
+
+pub mod request;
+
+pub use self::request::BasicAccount;
+
+// maximum number of times a request will be dispatched before it is dropped.
+const MAX_ATTEMPTS: usize = 5;
+
+// time in seconds after which a request with no suitable peers is dropped.
+const ORPHAN_TIMEOUT_SECS: i64 = 30;
+
+// reputation weight reported for peers giving bad responses.
+const BAD_RESPONSE_REPUTATION: i32 = -50;
+
+/// Response to an on-demand request.
+/// Resolves to `Err(Canceled)` if the request could not be fulfilled.
+pub type Response<T> = Oneshot<T>;
+
+// relevant peer info.
+struct Peer {
+	status: Status,
+	capabilities: Capabilities,
+}
+
+impl Peer {
+	// whether the peer can serve chain data (bodies, receipts) for the given block.
+	fn can_serve_chain(&self, num: u64) -> bool {
+		self.capabilities.serve_chain_since.map_or(false, |since| since <= num)
+			&& self.status.head_num >= num
+	}
+
+	// whether the peer can serve state data for the given block.
+	fn can_serve_state(&self, num: u64) -> bool {
+		self.capabilities.serve_state_since.map_or(false, |since| since <= num)
+			&& self.status.head_num >= num
+	}
+}
+
+// Which kind of request is pending and where to send the verified result.
+enum Request {
+	HeaderByNumber(request::HeaderByNumber, Complete<(encoded::Header, U256)>),
+	Block(request::Body, Complete<encoded::Block>),
+	BlockReceipts(request::BlockReceipts, Complete<Vec<Receipt>>),
+	Account(request::Account, Complete<Option<BasicAccount>>),
+	Storage(request::Storage, Complete<H256>),
+	Code(request::Code, Complete<Bytes>),
+}
+
+impl Request {
+	// whether the given peer is able to serve this request at all.
+	fn can_be_served_by(&self, peer: &Peer) -> bool {
+		match *self {
+			Request::HeaderByNumber(ref req, _) =>
+				peer.capabilities.serve_headers && peer.status.head_num >= req.num,
+			Request::Block(ref req, _) => peer.can_serve_chain(req.header.number()),
+			Request::BlockReceipts(ref req, _) => peer.can_serve_chain(req.0.number()),
+			Request::Account(ref req, _) => peer.can_serve_state(req.header.number()),
+			Request::Storage(ref req, _) => peer.can_serve_state(req.block_id.1),
+			Request::Code(ref req, _) => peer.can_serve_state(req.block_id.1),
+		}
+	}
+
+	// the raw LES request to make for this.
+	fn to_les_request(&self) -> les_request::Request {
+		match *self {
+			Request::HeaderByNumber(ref req, _) => les_request::Request::HeaderProofs(les_request::HeaderProofs {
+				requests: vec![les_request::HeaderProof {
+					cht_number: cht::block_to_cht_number(req.num).unwrap_or(0),
+					block_number: req.num,
+					from_level: 0,
+				}],
+			}),
+			Request::Block(ref req, _) => les_request::Request::Bodies(les_request::Bodies {
+				block_hashes: vec![req.hash],
+			}),
+			Request::BlockReceipts(ref req, _) => les_request::Request::Receipts(les_request::Receipts {
+				block_hashes: vec![req.0.hash()],
+			}),
+			Request::Account(ref req, _) => les_request::Request::StateProofs(les_request::StateProofs {
+				requests: vec![les_request::StateProof {
+					block: req.header.hash(),
+					key1: req.address.sha3(),
+					key2: None,
+					from_level: 0,
+				}],
+			}),
+			Request::Storage(ref req, _) => les_request::Request::StateProofs(les_request::StateProofs {
+				requests: vec![les_request::StateProof {
+					block: req.block_id.0,
+					key1: req.address.sha3(),
+					key2: Some(req.key.sha3()),
+					from_level: 0,
+				}],
+			}),
+			Request::Code(ref req, _) => les_request::Request::Codes(les_request::ContractCodes {
+				code_requests: vec![les_request::ContractCode {
+					block_hash: req.block_id.0,
+					account_key: req.address.sha3(),
+				}],
+			}),
+		}
+	}
+}
+
+// a request along with bookkeeping for retries.
+struct Pending {
+	request: Request,
+	attempts: usize,
+	created: SteadyTime,
+}
+
+/// On demand request service. See module docs for more details.
+/// Accumulates info about all peers' capabilities and dispatches
+/// requests to them accordingly. Responses are verified against
+/// locally known headers before being handed out.
+pub struct OnDemand {
+	peers: RwLock<HashMap<PeerId, Peer>>,
+	pending_requests: RwLock<HashMap<ReqId, Pending>>,
+	orphaned_requests: RwLock<Vec<Pending>>,
+}
+
+impl Default for OnDemand {
+	fn default() -> Self {
+		OnDemand {
+			peers: RwLock::new(HashMap::new()),
+			pending_requests: RwLock::new(HashMap::new()),
+			orphaned_requests: RwLock::new(Vec::new()),
+		}
+	}
+}
+
+impl OnDemand {
+	/// Request a header by block number and CHT root hash.
+	/// The header's total difficulty is proven along with it.
+	pub fn header_by_number(&self, ctx: &BasicContext, req: request::HeaderByNumber) -> Response<(encoded::Header, U256)> {
+		let (sender, receiver) = futures::oneshot();
+		self.dispatch(ctx, Request::HeaderByNumber(req, sender));
+		receiver
+	}
+
+	/// Request a block, given its header. Block bodies are requestable by hash only,
+	/// and the header is required anyway to verify and complete the block body
+	/// -- this just doesn't obscure the network query.
+	pub fn block(&self, ctx: &BasicContext, req: request::Body) -> Response<encoded::Block> {
+		let (sender, receiver) = futures::oneshot();
+		self.dispatch(ctx, Request::Block(req, sender));
+		receiver
+	}
+
+	/// Request the receipts for a block. The header serves two purposes:
+	/// provide the block hash to fetch receipts for, and for verification of the receipts root.
+	pub fn block_receipts(&self, ctx: &BasicContext, req: request::BlockReceipts) -> Response<Vec<Receipt>> {
+		let (sender, receiver) = futures::oneshot();
+		self.dispatch(ctx, Request::BlockReceipts(req, sender));
+		receiver
+	}
+
+	/// Request an account by address and block header -- which gives a hash to query and a state root
+	/// to verify against.
+	pub fn account(&self, ctx: &BasicContext, req: request::Account) -> Response<Option<BasicAccount>> {
+		let (sender, receiver) = futures::oneshot();
+		self.dispatch(ctx, Request::Account(req, sender));
+		receiver
+	}
+
+	/// Request an item of an account's storage by address, key, the account's
+	/// storage root, and block id.
+	pub fn storage(&self, ctx: &BasicContext, req: request::Storage) -> Response<H256> {
+		let (sender, receiver) = futures::oneshot();
+
+		// empty storage needs no network round-trip.
+		if req.storage_root == ::util::sha3::SHA3_NULL_RLP {
+			sender.complete(H256::new());
+		} else {
+			self.dispatch(ctx, Request::Storage(req, sender));
+		}
+
+		receiver
+	}
+
+	/// Request code by address, known code hash, and block id.
+	pub fn code(&self, ctx: &BasicContext, req: request::Code) -> Response<Bytes> {
+		let (sender, receiver) = futures::oneshot();
+
+		// empty code needs no network round-trip.
+		if req.code_hash == ::util::sha3::SHA3_EMPTY {
+			sender.complete(Vec::new());
+		} else {
+			self.dispatch(ctx, Request::Code(req, sender));
+		}
+
+		receiver
+	}
+
+	/// Number of requests currently awaiting a response or a suitable peer.
+	pub fn pending_count(&self) -> usize {
+		self.pending_requests.read().len() + self.orphaned_requests.read().len()
+	}
+
+	// dispatch a fresh request.
+	fn dispatch(&self, ctx: &BasicContext, request: Request) {
+		self.dispatch_pending(ctx, Pending {
+			request: request,
+			attempts: 0,
+			created: SteadyTime::now(),
+		})
+	}
+
+	// dispatch a request to a suitable peer, orphaning it if there are none.
+	// peers which can afford the request are tried in order of the credits
+	// they would have left, so the load is spread according to their buffers.
+	fn dispatch_pending(&self, ctx: &BasicContext, mut pending: Pending) {
+		if pending.attempts >= MAX_ATTEMPTS {
+			debug!(target: "on_demand", "Dropping request after {} failed attempts", pending.attempts);
+			return;
+		}
+
+		let les_req = pending.request.to_les_request();
+
+		// hold the lock while dispatching so a response can't be handled
+		// before the request is recorded.
+		let mut pending_requests = self.pending_requests.write();
+		let mut candidates: Vec<_> = self.peers.read().iter()
+			.filter(|&(_, peer)| pending.request.can_be_served_by(peer))
+			.filter_map(|(id, _)| ctx.credits_left(*id, &les_req).map(|credits| (credits, *id)))
+			.collect();
+		candidates.sort_by(|a, b| b.cmp(a));
+
+		for (_, id) in candidates {
+			match ctx.request_from(id, les_req.clone()) {
+				Ok(req_id) => {
+					trace!(target: "on_demand", "Assigning request to peer {}", id);
+					pending.attempts += 1;
+					pending_requests.insert(req_id, pending);
+					return;
+				}
+				Err(NetError::BufferEmpty) => continue,
+				Err(e) => trace!(target: "on_demand", "Failed to make request of peer {}: {:?}", id, e),
+			}
+		}
+
+		trace!(target: "on_demand", "No suitable peer for request");
+		self.orphaned_requests.write().push(pending);
+	}
+
+	// retry a request which failed with the peer it was assigned to.
+	fn retry(&self, ctx: &BasicContext, req_id: ReqId) {
+		let pending = self.pending_requests.write().remove(&req_id);
+		if let Some(pending) = pending {
+			self.dispatch_pending(ctx, pending);
+		}
+	}
+
+	// take a pending request, if it exists.
+	fn take(&self, req_id: ReqId) -> Option<Pending> {
+		self.pending_requests.write().remove(&req_id)
+	}
+
+	// punish a peer for a bad response and re-dispatch the request.
+	fn punish_and_retry(&self, ctx: &EventContext, pending: Pending) {
+		ctx.report_peer(ctx.peer(), BAD_RESPONSE_REPUTATION);
+		ctx.disable_peer(ctx.peer());
+		self.peers.write().remove(&ctx.peer());
+		self.dispatch_pending(ctx.as_basic(), pending);
+	}
+
+	// dispatch orphaned requests and drop those which have waited too long.
+	fn dispatch_orphaned(&self, ctx: &BasicContext) {
+		let now = SteadyTime::now();
+		let orphaned = ::std::mem::replace(&mut *self.orphaned_requests.write(), Vec::new());
+
+		for pending in orphaned {
+			if pending.created + Duration::seconds(ORPHAN_TIMEOUT_SECS) <= now {
+				debug!(target: "on_demand", "Dropping request which found no suitable peer");
+				continue;
+			}
+
+			self.dispatch_pending(ctx, pending);
+		}
+	}
+}
+
+impl Handler for OnDemand {
+	fn on_connect(&self, ctx: &EventContext, status: &Status, capabilities: &Capabilities) {
+		self.peers.write().insert(ctx.peer(), Peer { status: status.clone(), capabilities: capabilities.clone() });
+		self.dispatch_orphaned(ctx.as_basic());
+	}
+
+	fn on_disconnect(&self, ctx: &EventContext, unfulfilled: &[ReqId]) {
+		self.peers.write().remove(&ctx.peer());
+		let ctx = ctx.as_basic();
+
+		for unfulfilled in unfulfilled {
+			self.retry(ctx, *unfulfilled);
+		}
+	}
+
+	fn on_announcement(&self, ctx: &EventContext, announcement: &Announcement) {
+		{
+			let mut peers = self.peers.write();
+			if let Some(peer) = peers.get_mut(&ctx.peer()) {
+				peer.status.head_hash = announcement.head_hash;
+				peer.status.head_num = announcement.head_num;
+				peer.status.head_td = announcement.head_td;
+				peer.capabilities.update_from(announcement);
+			}
+		}
+
+		self.dispatch_orphaned(ctx.as_basic());
+	}
+
+	fn on_header_proofs(&self, ctx: &EventContext, req_id: ReqId, proofs: &[(Bytes, Vec<Bytes>)]) {
+		let pending = match self.take(req_id) {
+			Some(pending) => pending,
+			None => return,
+		};
+
+		let pending = match pending.request {
+			Request::HeaderByNumber(req, sender) => {
+				if let Some(&(ref header, ref proof)) = proofs.get(0) {
+					match req.check_response(header, proof) {
+						Ok(header_td) => return sender.complete(header_td),
+						Err(e) => debug!(target: "on_demand", "Bad header proof response from peer {}: {:?}", ctx.peer(), e),
+					}
+				}
+
+				Pending { request: Request::HeaderByNumber(req, sender), ..pending }
+			}
+			request => Pending { request: request, ..pending },
+		};
+
+		self.punish_and_retry(ctx, pending);
+	}
+
+	fn on_block_bodies(&self, ctx: &EventContext, req_id: ReqId, bodies: &[Bytes]) {
+		let pending = match self.take(req_id) {
+			Some(pending) => pending,
+			None => return,
+		};
+
+		let pending = match pending.request {
+			Request::Block(req, sender) => {
+				if let Some(body) = bodies.get(0) {
+					match req.check_response(body) {
+						Ok(block) => return sender.complete(block),
+						Err(e) => debug!(target: "on_demand", "Bad block body response from peer {}: {:?}", ctx.peer(), e),
+					}
+				}
+
+				Pending { request: Request::Block(req, sender), ..pending }
+			}
+			request => Pending { request: request, ..pending },
+		};
+
+		self.punish_and_retry(ctx, pending);
+	}
+
+	fn on_receipts(&self, ctx: &EventContext, req_id: ReqId, receipts: &[Vec<Receipt>]) {
+		let pending = match self.take(req_id) {
+			Some(pending) => pending,
+			None => return,
+		};
+
+		let pending = match pending.request {
+			Request::BlockReceipts(req, sender) => {
+				if let Some(receipts) = receipts.get(0) {
+					match req.check_response(receipts) {
+						Ok(receipts) => return sender.complete(receipts),
+						Err(e) => debug!(target: "on_demand", "Bad receipts response from peer {}: {:?}", ctx.peer(), e),
+					}
+				}
+
+				Pending { request: Request::BlockReceipts(req, sender), ..pending }
+			}
+			request => Pending { request: request, ..pending },
+		};
+
+		self.punish_and_retry(ctx, pending);
+	}
+
+	fn on_state_proofs(&self, ctx: &EventContext, req_id: ReqId, proofs: &[Vec<Bytes>]) {
+		let pending = match self.take(req_id) {
+			Some(pending) => pending,
+			None => return,
+		};
+
+		let pending = match pending.request {
+			Request::Account(req, sender) => {
+				if let Some(proof) = proofs.get(0) {
+					match req.check_response(proof) {
+						Ok(account) => return sender.complete(account),
+						Err(e) => debug!(target: "on_demand", "Bad account proof response from peer {}: {:?}", ctx.peer(), e),
+					}
+				}
+
+				Pending { request: Request::Account(req, sender), ..pending }
+			}
+			Request::Storage(req, sender) => {
+				if let Some(proof) = proofs.get(0) {
+					match req.check_response(proof) {
+						Ok(value) => return sender.complete(value),
+						Err(e) => debug!(target: "on_demand", "Bad storage proof response from peer {}: {:?}", ctx.peer(), e),
+					}
+				}
+
+				Pending { request: Request::Storage(req, sender), ..pending }
+			}
+			request => Pending { request: request, ..pending },
+		};
+
+		self.punish_and_retry(ctx, pending);
+	}
+
+	fn on_code(&self, ctx: &EventContext, req_id: ReqId, codes: &[Bytes]) {
+		let pending = match self.take(req_id) {
+			Some(pending) => pending,
+			None => return,
+		};
+
+		let pending = match pending.request {
+			Request::Code(req, sender) => {
+				if let Some(code) = codes.get(0) {
+					match req.check_response(code) {
+						Ok(()) => return sender.complete(code.clone()),
+						Err(e) => debug!(target: "on_demand", "Bad code response from peer {}: {:?}", ctx.peer(), e),
+					}
+				}
+
+				Pending { request: Request::Code(req, sender), ..pending }
+			}
+			request => Pending { request: request, ..pending },
+		};
+
+		self.punish_and_retry(ctx, pending);
+	}
+
+	fn tick(&self, ctx: &BasicContext) {
+		self.dispatch_orphaned(ctx)
+	}
+
+	fn on_abort(&self) {
+		self.peers.write().clear();
+		self.pending_requests.write().clear();
+		self.orphaned_requests.write().clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+	use net::{Announcement, BasicContext, ReqId, Error as LesError};
+	use request::Request as LesRequest;
+	use network::{PeerId, NodeId};
+	use ethcore::transaction::SignedTransaction;
+	use futures::Future;
+	use util::{H256, U256, Mutex};
+
+	struct FakeContext;
+
+	impl BasicContext for FakeContext {
+		fn persistent_peer_id(&self, _: PeerId) -> Option<NodeId> { None }
+		fn request_from(&self, _: PeerId, _: LesRequest) -> Result<ReqId, LesError> {
+			unimplemented!()
+		}
+		fn credits_left(&self, _: PeerId, _: &LesRequest) -> Option<U256> { None }
+		fn send_transactions(&self, _: &[SignedTransaction]) -> usize { 0 }
+		fn make_announcement(&self, _: Announcement) { }
+		fn disconnect_peer(&self, _: PeerId) { }
+		fn disable_peer(&self, _: PeerId) { }
+		fn report_peer(&self, _: PeerId, _: i32) { }
+	}
+
+	#[test]
+	fn detects_hangup() {
+		let on_demand = OnDemand::default();
+		let result = on_demand.header_by_number(&FakeContext, request::HeaderByNumber {
+			num: 10_000,
+			cht_root: H256::default(),
+		});
+
+		assert_eq!(on_demand.orphaned_requests.read().len(), 1);
+		drop(on_demand);
+
+		assert!(result.wait().is_err());
+	}
+
+	#[test]
+	fn empty_code_needs_no_request() {
+		let on_demand = OnDemand::default();
+		let result = on_demand.code(&FakeContext, request::Code {
+			block_id: (H256::default(), 1),
+			address: Default::default(),
+			code_hash: ::util::sha3::SHA3_EMPTY,
+		});
+
+		assert_eq!(on_demand.pending_count(), 0);
+		assert_eq!(result.wait().unwrap(), Vec::<u8>::new());
+	}
+
+	#[test]
+	fn empty_storage_needs_no_request() {
+		let on_demand = OnDemand::default();
+		let result = on_demand.storage(&FakeContext, request::Storage {
+			block_id: (H256::default(), 1),
+			address: Default::default(),
+			storage_root: ::util::sha3::SHA3_NULL_RLP,
+			key: H256::default(),
+		});
+
+		assert_eq!(on_demand.pending_count(), 0);
+		assert_eq!(result.wait().unwrap(), H256::new());
+	}
+
+	// peers with the given credits left, recording which ones were asked.
+	struct CreditContext {
+		credits: HashMap<PeerId, U256>,
+		requested: Mutex<Vec<PeerId>>,
+	}
+
+	impl BasicContext for CreditContext {
+		fn persistent_peer_id(&self, _: PeerId) -> Option<NodeId> { None }
+		fn request_from(&self, peer: PeerId, _: LesRequest) -> Result<ReqId, LesError> {
+			let mut requested = self.requested.lock();
+			requested.push(peer);
+			Ok(ReqId(requested.len()))
+		}
+		fn credits_left(&self, peer: PeerId, _: &LesRequest) -> Option<U256> {
+			self.credits.get(&peer).cloned()
+		}
+		fn send_transactions(&self, _: &[SignedTransaction]) -> usize { 0 }
+		fn make_announcement(&self, _: Announcement) { }
+		fn disconnect_peer(&self, _: PeerId) { }
+		fn disable_peer(&self, _: PeerId) { }
+		fn report_peer(&self, _: PeerId, _: i32) { }
+	}
+
+	#[test]
+	fn dispatches_to_peer_with_most_credits() {
+		let on_demand = OnDemand::default();
+		for peer in 0..4 {
+			on_demand.peers.write().insert(peer, Peer {
+				status: Status {
+					protocol_version: 1,
+					network_id: 0,
+					head_td: U256::zero(),
+					head_hash: H256::default(),
+					head_num: 1_000_000,
+					genesis_hash: H256::default(),
+					last_head: None,
+				},
+				capabilities: Default::default(),
+			});
+		}
+
+		// peer 3 can't afford the request at all.
+		let ctx = CreditContext {
+			credits: vec![(0, 100.into()), (1, 5000.into()), (2, 300.into())].into_iter().collect(),
+			requested: Mutex::new(Vec::new()),
+		};
+
+		let req = || request::HeaderByNumber { num: 10_000, cht_root: H256::default() };
+		let _first = on_demand.header_by_number(&ctx, req());
+		assert_eq!(*ctx.requested.lock(), vec![1]);
+
+		let ctx = CreditContext { credits: HashMap::new(), requested: Mutex::new(Vec::new()) };
+		let _second = on_demand.header_by_number(&ctx, req());
+		assert!(ctx.requested.lock().is_empty());
+		assert_eq!(on_demand.orphaned_requests.read().len(), 1);
+	}
+}
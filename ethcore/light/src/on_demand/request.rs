@@ -0,0 +1,428 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Request types, verification, and verification errors.
+
+use ethcore::encoded;
+use ethcore::receipt::Receipt;
+
+use rlp::{RlpStream, Stream, UntrustedRlp, View};
+use util::{Address, Bytes, HashDB, H256, U256};
+use util::memorydb::MemoryDB;
+use util::sha3::Hashable;
+use util::trie::{Trie, TrieDB, TrieError};
+
+/// Errors in verification.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+	/// RLP decoder error.
+	Decoder(::rlp::DecoderError),
+	/// Trie lookup error (result of bad proof)
+	Trie(TrieError),
+	/// Bad inclusion proof
+	BadProof,
+	/// Wrong header number.
+	WrongNumber(u64, u64),
+	/// Wrong header hash.
+	WrongHash(H256, H256),
+	/// Wrong trie root.
+	WrongTrieRoot(H256, H256),
+}
+
+impl From<::rlp::DecoderError> for Error {
+	fn from(err: ::rlp::DecoderError) -> Self {
+		Error::Decoder(err)
+	}
+}
+
+impl From<Box<TrieError>> for Error {
+	fn from(err: Box<TrieError>) -> Self {
+		Error::Trie(*err)
+	}
+}
+
+/// Request for a header by number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderByNumber {
+	/// The header's number.
+	pub num: u64,
+	/// The root of the CHT containing this header.
+	pub cht_root: H256,
+}
+
+impl HeaderByNumber {
+	/// Check a response with a header and cht proof.
+	pub fn check_response(&self, header: &[u8], proof: &[Bytes]) -> Result<(encoded::Header, U256), Error> {
+		let (expected_hash, td) = match ::cht::check_proof(proof, self.num, self.cht_root) {
+			Some((expected_hash, td)) => (expected_hash, td),
+			None => return Err(Error::BadProof),
+		};
+
+		// and compare the hash to the found header.
+		let found_hash = header.sha3();
+		match expected_hash == found_hash {
+			true => Ok((encoded::Header::new(header.to_vec()), td)),
+			false => Err(Error::WrongHash(expected_hash, found_hash)),
+		}
+	}
+}
+
+/// Request for a block, with header and precomputed hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Body {
+	/// The block's header.
+	pub header: encoded::Header,
+	/// The block's hash.
+	pub hash: H256,
+}
+
+impl Body {
+	/// Create a request for a block body from a given header.
+	pub fn new(header: encoded::Header) -> Self {
+		let hash = header.hash();
+		Body {
+			header: header,
+			hash: hash,
+		}
+	}
+
+	/// Check a response for this block body.
+	pub fn check_response(&self, body: &[u8]) -> Result<encoded::Block, Error> {
+		let body_view = UntrustedRlp::new(body);
+
+		// check the integrity of the the body against the header
+		let tx_root = ::util::triehash::ordered_trie_root(body_view.at(0)?.iter().map(|r| r.as_raw().to_vec()));
+		if tx_root != self.header.transactions_root() {
+			return Err(Error::WrongTrieRoot(self.header.transactions_root(), tx_root));
+		}
+
+		let uncles_hash = body_view.at(1)?.as_raw().sha3();
+		if uncles_hash != self.header.uncles_hash() {
+			return Err(Error::WrongHash(self.header.uncles_hash(), uncles_hash));
+		}
+
+		// concatenate the header and the body.
+		let mut stream = RlpStream::new_list(3);
+		stream.append_raw(self.header.rlp().as_raw(), 1);
+		stream.append_raw(&body_view.at(0)?.as_raw(), 1);
+		stream.append_raw(&body_view.at(1)?.as_raw(), 1);
+
+		Ok(encoded::Block::new(stream.out()))
+	}
+}
+
+/// Request for a block's receipts with header for verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockReceipts(pub encoded::Header);
+
+impl BlockReceipts {
+	/// Check a response with receipts against the stored header.
+	pub fn check_response(&self, receipts: &[Receipt]) -> Result<Vec<Receipt>, Error> {
+		let receipts_root = self.0.receipts_root();
+		let found_root = ::util::triehash::ordered_trie_root(receipts.iter().map(|r| ::rlp::encode(r).to_vec()));
+
+		match receipts_root == found_root {
+			true => Ok(receipts.to_vec()),
+			false => Err(Error::WrongTrieRoot(receipts_root, found_root)),
+		}
+	}
+}
+
+/// Basic account data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicAccount {
+	/// The account's nonce.
+	pub nonce: U256,
+	/// The account's balance.
+	pub balance: U256,
+	/// The account's storage root.
+	pub storage_root: H256,
+	/// The account's code hash.
+	pub code_hash: H256,
+}
+
+/// Request for an account structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+	/// Header for verification.
+	pub header: encoded::Header,
+	/// Address requested.
+	pub address: Address,
+}
+
+impl Account {
+	/// Check a response with an account against the stored header.
+	/// A `None` result means the account doesn't exist in the state.
+	pub fn check_response(&self, proof: &[Bytes]) -> Result<Option<BasicAccount>, Error> {
+		let state_root = self.header.state_root();
+
+		let mut db = MemoryDB::new();
+		for node in proof { db.insert(&node[..]); }
+
+		let trie = TrieDB::new(&db, &state_root)?;
+		match trie.get(&self.address.sha3())? {
+			Some(val) => {
+				let rlp = UntrustedRlp::new(&val);
+				Ok(Some(BasicAccount {
+					nonce: rlp.val_at(0)?,
+					balance: rlp.val_at(1)?,
+					storage_root: rlp.val_at(2)?,
+					code_hash: rlp.val_at(3)?,
+				}))
+			},
+			None => Ok(None),
+		}
+	}
+}
+
+/// Request for an item of an account's storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Storage {
+	/// Block hash and number to request the storage at.
+	pub block_id: (H256, u64),
+	/// Address of the account.
+	pub address: Address,
+	/// Account's storage root, for verification.
+	pub storage_root: H256,
+	/// Storage key requested.
+	pub key: H256,
+}
+
+impl Storage {
+	/// Check a response with a storage proof against the account's storage root.
+	pub fn check_response(&self, proof: &[Bytes]) -> Result<H256, Error> {
+		let mut db = MemoryDB::new();
+		for node in proof { db.insert(&node[..]); }
+
+		let trie = TrieDB::new(&db, &self.storage_root)?;
+		match trie.get(&self.key.sha3())? {
+			Some(val) => {
+				let value: U256 = UntrustedRlp::new(&val).as_val()?;
+				Ok(value.into())
+			}
+			None => Ok(H256::new()),
+		}
+	}
+}
+
+/// Request for account code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Code {
+	/// Block hash and number to request the code at.
+	pub block_id: (H256, u64),
+	/// Address of the account.
+	pub address: Address,
+	/// Account's code hash.
+	pub code_hash: H256,
+}
+
+impl Code {
+	/// Check a response with code against the code hash.
+	pub fn check_response(&self, code: &[u8]) -> Result<(), Error> {
+		let found_hash = code.sha3();
+		if found_hash == self.code_hash {
+			Ok(())
+		} else {
+			Err(Error::WrongHash(self.code_hash, found_hash))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use util::{MemoryDB, Address, H256, U256, FixedHash};
+	use util::trie::{Trie, TrieMut, TrieDB, SecTrieDBMut};
+	use util::trie::recorder::{BasicRecorder, Recorder};
+
+	use ethcore::header::Header;
+	use ethcore::encoded;
+	use ethcore::ids::BlockId;
+	use ethcore::receipt::{Receipt, TransactionOutcome};
+
+	#[test]
+	fn check_header_by_number() {
+		let mut header = Header::new();
+		header.set_number(100);
+		header.set_extra_data(b"test_header".to_vec());
+		let hash = header.hash();
+
+		// fake chain where block `n` has hash `n`, except for our header.
+		let cht = ::cht::build(0, |id| {
+			let num = match id {
+				BlockId::Number(n) => n,
+				BlockId::Hash(h) if h == hash => 100,
+				BlockId::Hash(h) => U256::from(h).low_u64(),
+				_ => return None,
+			};
+
+			Some(::cht::BlockInfo {
+				hash: if num == 100 { hash } else { H256::from(num) },
+				parent_hash: if num == 101 { hash } else { H256::from(num - 1) },
+				total_difficulty: num.into(),
+			})
+		}).unwrap();
+
+		let proof = cht.prove(100, 0).unwrap().unwrap();
+		let req = HeaderByNumber {
+			num: 100,
+			cht_root: cht.root(),
+		};
+
+		let raw_header = ::rlp::encode(&header);
+		assert!(req.check_response(&*raw_header, &proof[..]).is_ok());
+
+		let wrong = HeaderByNumber {
+			num: 101,
+			cht_root: cht.root(),
+		};
+		assert!(wrong.check_response(&*raw_header, &proof[..]).is_err());
+	}
+
+	#[test]
+	fn check_body() {
+		use rlp::{RlpStream, Stream};
+
+		let header = Header::new();
+		let mut body_stream = RlpStream::new_list(2);
+		body_stream.begin_list(0).begin_list(0);
+
+		let req = Body {
+			header: encoded::Header::new(::rlp::encode(&header).to_vec()),
+			hash: header.hash(),
+		};
+
+		assert!(req.check_response(&*body_stream.drain()).is_ok())
+	}
+
+	#[test]
+	fn check_receipts() {
+		let receipts = (0..5).map(|_| Receipt {
+			gas_used: 21_000u64.into(),
+			log_bloom: Default::default(),
+			logs: Vec::new(),
+			outcome: TransactionOutcome::StateRoot(H256::random()),
+		}).collect::<Vec<_>>();
+
+		let mut header = Header::new();
+		let receipts_root = ::util::triehash::ordered_trie_root(
+			receipts.iter().map(|x| ::rlp::encode(x).to_vec())
+		);
+
+		header.set_receipts_root(receipts_root);
+
+		let req = BlockReceipts(encoded::Header::new(::rlp::encode(&header).to_vec()));
+
+		assert!(req.check_response(&receipts).is_ok())
+	}
+
+	#[test]
+	fn check_state_proof() {
+		use rlp::{RlpStream, Stream};
+
+		let mut root = H256::default();
+		let mut db = MemoryDB::new();
+		let mut header = Header::new();
+		header.set_number(123_456);
+		header.set_extra_data(b"test_header".to_vec());
+
+		let addr = Address::random();
+		let rand_acc = || {
+			let mut stream = RlpStream::new_list(4);
+			stream.append(&2u64)
+				.append(&100_000_000u64)
+				.append(&H256::random())
+				.append(&H256::random());
+
+			stream.out()
+		};
+		{
+			let mut trie = SecTrieDBMut::new(&mut db, &mut root);
+			for _ in 0..100 {
+				let address = Address::random();
+				trie.insert(&*address, &rand_acc()).unwrap();
+			}
+
+			trie.insert(&*addr, &rand_acc()).unwrap();
+		}
+
+		let proof = {
+			let trie = TrieDB::new(&db, &root).unwrap();
+			let key = ::util::sha3::Hashable::sha3(&*addr);
+			let mut recorder = BasicRecorder::new();
+			trie.get_recorded(&*key, &mut recorder).unwrap().unwrap();
+
+			recorder.drain().into_iter().map(|r| r.data).collect::<Vec<_>>()
+		};
+
+		header.set_state_root(root.clone());
+
+		let req = Account {
+			header: encoded::Header::new(::rlp::encode(&header).to_vec()),
+			address: addr,
+		};
+
+		assert!(req.check_response(&proof[..]).unwrap().is_some());
+	}
+
+	#[test]
+	fn check_storage_proof() {
+		let mut root = H256::default();
+		let mut db = MemoryDB::new();
+		let key = H256::random();
+		{
+			let mut trie = SecTrieDBMut::new(&mut db, &mut root);
+			for _ in 0..100 {
+				trie.insert(&*H256::random(), &::rlp::encode(&U256::from(1)).to_vec()).unwrap();
+			}
+
+			trie.insert(&*key, &::rlp::encode(&U256::from(42)).to_vec()).unwrap();
+		}
+
+		let prove = |key: &H256| {
+			let trie = TrieDB::new(&db, &root).unwrap();
+			let mut recorder = BasicRecorder::new();
+			trie.get_recorded(&*::util::sha3::Hashable::sha3(key), &mut recorder).unwrap();
+
+			recorder.drain().into_iter().map(|r| r.data).collect::<Vec<_>>()
+		};
+
+		let req = Storage {
+			block_id: (Default::default(), 2),
+			address: Address::default(),
+			storage_root: root,
+			key: key,
+		};
+		assert_eq!(req.check_response(&prove(&key)[..]), Ok(H256::from(U256::from(42))));
+
+		let absent = Storage { key: H256::random(), ..req.clone() };
+		assert_eq!(absent.check_response(&prove(&absent.key)[..]), Ok(H256::new()));
+		assert!(absent.check_response(&[]).is_err());
+	}
+
+	#[test]
+	fn check_code() {
+		let code = vec![1u8; 256];
+		let req = Code {
+			block_id: (Default::default(), 2),
+			address: Address::default(),
+			code_hash: ::util::sha3::Hashable::sha3(&code),
+		};
+
+		assert!(req.check_response(&code).is_ok());
+		assert!(req.check_response(&[]).is_err());
+	}
+}
@@ -17,16 +17,26 @@
 //! A provider for the LES protocol. This is typically a full node, who can
 //! give as much data as necessary to its peers.
 
+use std::sync::Arc;
+
 use ethcore::blockchain_info::BlockChainInfo;
 use ethcore::client::{BlockChainClient, ProvingBlockChainClient};
 use ethcore::transaction::PendingTransaction;
 use ethcore::ids::BlockId;
 use ethcore::encoded;
 
-use util::{Bytes, H256};
+use io::{IoContext, IoHandler, TimerToken};
+use util::{Bytes, H256, Database};
 
+use cht;
 use request;
 
+const CHT_TIMER: TimerToken = 0;
+const CHT_TIMER_MS: u64 = 1000;
+
+// CHTs built per timer tick, limiting the load of catching up with a long chain.
+const CHTS_PER_TICK: u64 = 4;
+
 /// Defines the operations that a provider for `LES` must fulfill.
 ///
 /// These are defined at [1], but may be subject to change.
@@ -227,6 +237,7 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 	}
 
 	fn header_proof(&self, _req: request::HeaderProof) -> Option<(encoded::Header, Vec<Bytes>)> {
+		// CHTs aren't built on demand; see `ChtProvider`.
 		None
 	}
 
@@ -234,3 +245,129 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 		BlockChainClient::ready_transactions(self)
 	}
 }
+
+/// Light data provider for a full client which also serves header proofs.
+///
+/// Canonical hash tries are built and stored once their range is final, and proofs
+/// are generated from the stored tries. Header proofs for more recent ranges are refused.
+///
+/// Building happens on the IO worker the provider is registered with, a few CHTs
+/// at a time, so a node that starts with a long chain catches up gradually.
+pub struct ChtProvider<C: ?Sized> {
+	client: Arc<C>,
+	chts: cht::Store,
+}
+
+impl<C: ProvingBlockChainClient + ?Sized> ChtProvider<C> {
+	/// Create a provider for the given client, storing CHTs in the given database column.
+	pub fn new(client: Arc<C>, db: Arc<Database>, col: Option<u32>) -> Result<Self, String> {
+		Ok(ChtProvider {
+			client: client,
+			chts: cht::Store::new(db, col)?,
+		})
+	}
+
+	/// Build and store up to `max` of the CHTs whose range has become final.
+	/// Returns the number of CHTs stored.
+	pub fn update(&self, max: u64) -> u64 {
+		let best_block = BlockChainClient::chain_info(&*self.client).best_block_number;
+
+		let client = &*self.client;
+		let block_info = |id| {
+			match (BlockChainClient::block_header(client, id), client.block_total_difficulty(id)) {
+				(Some(hdr), Some(td)) => Some(cht::BlockInfo {
+					hash: hdr.hash(),
+					parent_hash: hdr.parent_hash(),
+					total_difficulty: td,
+				}),
+				_ => None,
+			}
+		};
+
+		match self.chts.update(best_block, max, block_info) {
+			Ok(produced) => {
+				let pending = (best_block.saturating_sub(cht::HISTORY) / cht::SIZE).saturating_sub(self.chts.count());
+				if produced > 0 && pending > 0 {
+					debug!(target: "les_provider", "Stored {} canonical hash tries, {} left to build", produced, pending);
+				}
+				produced
+			}
+			Err(e) => {
+				warn!(target: "les_provider", "Failed to store canonical hash tries: {}", e);
+				0
+			}
+		}
+	}
+}
+
+impl<C: ProvingBlockChainClient + ?Sized> Provider for ChtProvider<C> {
+	fn chain_info(&self) -> BlockChainInfo {
+		Provider::chain_info(&*self.client)
+	}
+
+	fn reorg_depth(&self, a: &H256, b: &H256) -> Option<u64> {
+		Provider::reorg_depth(&*self.client, a, b)
+	}
+
+	fn earliest_state(&self) -> Option<u64> {
+		Provider::earliest_state(&*self.client)
+	}
+
+	fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+		Provider::block_header(&*self.client, id)
+	}
+
+	fn block_body(&self, id: BlockId) -> Option<encoded::Body> {
+		Provider::block_body(&*self.client, id)
+	}
+
+	fn block_receipts(&self, hash: &H256) -> Option<Bytes> {
+		Provider::block_receipts(&*self.client, hash)
+	}
+
+	fn state_proof(&self, req: request::StateProof) -> Vec<Bytes> {
+		Provider::state_proof(&*self.client, req)
+	}
+
+	fn contract_code(&self, req: request::ContractCode) -> Bytes {
+		Provider::contract_code(&*self.client, req)
+	}
+
+	fn header_proof(&self, req: request::HeaderProof) -> Option<(encoded::Header, Vec<Bytes>)> {
+		if Some(req.cht_number) != cht::block_to_cht_number(req.block_number) {
+			debug!(target: "les_provider", "Requested CHT number mismatch with block number.");
+			return None;
+		}
+
+		// only CHTs with a final range are stored.
+		let proof = match self.chts.prove(req.cht_number, req.block_number, req.from_level) {
+			Some(proof) => proof,
+			None => {
+				trace!(target: "les_provider", "Requested proof from unavailable CHT {}", req.cht_number);
+				return None;
+			}
+		};
+
+		BlockChainClient::block_header(&*self.client, BlockId::Number(req.block_number))
+			.map(|header| (header, proof))
+	}
+
+	fn ready_transactions(&self) -> Vec<PendingTransaction> {
+		Provider::ready_transactions(&*self.client)
+	}
+}
+
+impl<C, M> IoHandler<M> for ChtProvider<C> where
+	C: ProvingBlockChainClient + ?Sized,
+	M: Send + Sync + Clone + 'static,
+{
+	fn initialize(&self, io: &IoContext<M>) {
+		io.register_timer(CHT_TIMER, CHT_TIMER_MS).expect("Error registering CHT timer");
+	}
+
+	fn timeout(&self, _io: &IoContext<M>, timer: TimerToken) {
+		if timer == CHT_TIMER {
+			self.update(CHTS_PER_TICK);
+		}
+	}
+}
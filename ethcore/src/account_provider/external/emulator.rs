@@ -0,0 +1,108 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Software emulator of a Ledger-style device, for testing without hardware.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use util::FixedHash;
+use ethstore::ethkey::{self, ExtendedSecret, DerivationPath, Message};
+use super::Error;
+use super::ledger::{Transport, CLA, INS_GET_ADDRESS, INS_SIGN, SW_OK, SW_REJECTED, SW_INVALID_DATA, SW_INS_NOT_SUPPORTED};
+
+/// Device holding a hierarchical deterministic wallet, answering APDUs like the real one.
+pub struct LedgerEmulator {
+	master: ExtendedSecret,
+	confirm: AtomicBool,
+}
+
+impl LedgerEmulator {
+	/// Creates a device with the wallet generated from `seed`.
+	pub fn new(seed: &[u8]) -> Result<Self, ethkey::Error> {
+		Ok(LedgerEmulator {
+			master: ExtendedSecret::from_seed(seed)?,
+			confirm: AtomicBool::new(true),
+		})
+	}
+
+	/// Sets whether the emulated user confirms or rejects signing requests.
+	pub fn set_confirm(&self, confirm: bool) {
+		self.confirm.store(confirm, Ordering::SeqCst);
+	}
+
+	fn handle(&self, ins: u8, data: &[u8]) -> Result<Vec<u8>, u16> {
+		let (path, rest) = decode_path(data).ok_or(SW_INVALID_DATA)?;
+		let key = self.master.derive_path(&path).and_then(|key| key.key_pair()).map_err(|_| SW_INVALID_DATA)?;
+
+		match ins {
+			INS_GET_ADDRESS if rest.is_empty() => {
+				let mut response = vec![65, 4];
+				response.extend_from_slice(&**key.public());
+				let address = key.address().hex();
+				response.push(address.len() as u8);
+				response.extend_from_slice(address.as_bytes());
+				Ok(response)
+			},
+			INS_SIGN if rest.len() == 32 => {
+				if !self.confirm.load(Ordering::SeqCst) {
+					return Err(SW_REJECTED);
+				}
+
+				let message = Message::from_slice(rest);
+				let signature = ethkey::sign(key.secret(), &message).map_err(|_| SW_INVALID_DATA)?;
+				let mut response = vec![signature.v() + 27];
+				response.extend_from_slice(signature.r());
+				response.extend_from_slice(signature.s());
+				Ok(response)
+			},
+			INS_GET_ADDRESS | INS_SIGN => Err(SW_INVALID_DATA),
+			_ => Err(SW_INS_NOT_SUPPORTED),
+		}
+	}
+}
+
+impl Transport for LedgerEmulator {
+	fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>, Error> {
+		if apdu.len() < 5 || apdu[0] != CLA || apdu.len() != 5 + apdu[4] as usize {
+			return Err(Error::Transport("Malformed APDU".into()));
+		}
+
+		let (mut response, status) = match self.handle(apdu[1], &apdu[5..]) {
+			Ok(response) => (response, SW_OK),
+			Err(status) => (Vec::new(), status),
+		};
+		response.push((status >> 8) as u8);
+		response.push(status as u8);
+		Ok(response)
+	}
+}
+
+/// Decode the derivation path at the start of `data`, returning it with the remaining data.
+fn decode_path(data: &[u8]) -> Option<(DerivationPath, &[u8])> {
+	let count = match data.first() {
+		Some(count) => *count as usize,
+		None => return None,
+	};
+	if data.len() < 1 + count * 4 {
+		return None;
+	}
+
+	let master = "m".parse().expect("Path of the master key is valid; qed");
+	let path = data[1..1 + count * 4].chunks(4).fold(master, |path: DerivationPath, index| {
+		path.child((index[0] as u32) << 24 | (index[1] as u32) << 16 | (index[2] as u32) << 8 | index[3] as u32)
+	});
+	Some((path, &data[1 + count * 4..]))
+}
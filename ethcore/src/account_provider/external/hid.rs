@@ -0,0 +1,135 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transport to a Ledger-style device through a raw HID node, e.g. `/dev/hidraw0`.
+//!
+//! APDUs are split into 64 byte HID reports. Each report starts with the channel
+//! `0x0101`, the tag `0x05` and the big-endian report index. The data carried by
+//! the reports is the big-endian length of the APDU followed by the APDU itself.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use util::Mutex;
+use super::Error;
+use super::ledger::Transport;
+
+const CHANNEL: u16 = 0x0101;
+const TAG_APDU: u8 = 0x05;
+const REPORT_SIZE: usize = 64;
+const HEADER_SIZE: usize = 5;
+
+/// Device connected through a raw HID node.
+pub struct HidTransport {
+	device: Mutex<File>,
+}
+
+impl HidTransport {
+	/// Open the raw HID node at `path`.
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		let device = OpenOptions::new().read(true).write(true).open(path)
+			.map_err(|e| Error::Transport(format!("{}", e)))?;
+		Ok(HidTransport {
+			device: Mutex::new(device),
+		})
+	}
+}
+
+impl Transport for HidTransport {
+	fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>, Error> {
+		let mut device = self.device.lock();
+		for report in wrap(apdu) {
+			// reports are written with a leading report number, zero for devices without numbered reports
+			let mut data = vec![0];
+			data.extend_from_slice(&report);
+			device.write_all(&data).map_err(|e| Error::Transport(format!("{}", e)))?;
+		}
+		unwrap(&mut *device)
+	}
+}
+
+/// Split `apdu` into HID reports.
+fn wrap(apdu: &[u8]) -> Vec<Vec<u8>> {
+	let mut data = vec![(apdu.len() >> 8) as u8, apdu.len() as u8];
+	data.extend_from_slice(apdu);
+
+	data.chunks(REPORT_SIZE - HEADER_SIZE).enumerate().map(|(index, chunk)| {
+		let mut report = vec![(CHANNEL >> 8) as u8, CHANNEL as u8, TAG_APDU, (index >> 8) as u8, index as u8];
+		report.extend_from_slice(chunk);
+		report.resize(REPORT_SIZE, 0);
+		report
+	}).collect()
+}
+
+/// Read HID reports from `reader` until a whole response is reassembled.
+fn unwrap<R: Read>(reader: &mut R) -> Result<Vec<u8>, Error> {
+	let mut data = Vec::new();
+	let mut index = 0usize;
+	loop {
+		let mut report = [0u8; REPORT_SIZE];
+		let read = reader.read(&mut report).map_err(|e| Error::Transport(format!("{}", e)))?;
+		if read <= HEADER_SIZE {
+			return Err(Error::InvalidResponse);
+		}
+
+		let channel = (report[0] as u16) << 8 | report[1] as u16;
+		let report_index = (report[3] as usize) << 8 | report[4] as usize;
+		if channel != CHANNEL || report[2] != TAG_APDU || report_index != index {
+			return Err(Error::InvalidResponse);
+		}
+
+		data.extend_from_slice(&report[HEADER_SIZE..read]);
+		index += 1;
+
+		if data.len() >= 2 {
+			let len = (data[0] as usize) << 8 | data[1] as usize;
+			if data.len() >= 2 + len {
+				data.truncate(2 + len);
+				return Ok(data.split_off(2));
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+	use super::{wrap, unwrap, REPORT_SIZE};
+
+	#[test]
+	fn should_split_and_reassemble_apdus() {
+		let apdu: Vec<u8> = (0..150).map(|i| i as u8).collect();
+		let reports = wrap(&apdu);
+
+		assert_eq!(reports.len(), 3);
+		assert!(reports.iter().all(|report| report.len() == REPORT_SIZE));
+		assert_eq!(&reports[0][..7], &[0x01, 0x01, 0x05, 0x00, 0x00, 0x00, 150]);
+		assert_eq!(&reports[2][..5], &[0x01, 0x01, 0x05, 0x00, 0x02]);
+
+		let mut stream = Cursor::new(reports.concat());
+		assert_eq!(unwrap(&mut stream).unwrap(), apdu);
+	}
+
+	#[test]
+	fn should_reject_reports_out_of_order() {
+		let apdu = vec![0u8; 100];
+		let mut reports = wrap(&apdu);
+		reports.swap(0, 1);
+
+		let mut stream = Cursor::new(reports.concat());
+		assert!(unwrap(&mut stream).is_err());
+	}
+}
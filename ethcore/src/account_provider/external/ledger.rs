@@ -0,0 +1,253 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Ledger-style hardware wallet speaking APDU over a `Transport`.
+//!
+//! Commands use class `0xe0`. The payload of each command starts with the
+//! derivation path: the number of indices followed by each big-endian index.
+//! - `GET_ADDRESS` returns the public key and the hex address, each prefixed with its length.
+//! - `SIGN` takes the path followed by the 32 byte hash and returns `v`, `r` and `s`.
+//!
+//! Every response ends with a two byte status word; `0x9000` means success.
+
+use std::sync::Arc;
+use std::str::{self, FromStr};
+use util::{RwLock, H256, FixedHash};
+use ethstore::ethkey::{Address, Message, Signature, DerivationPath};
+use super::{ExternalSigner, Error};
+
+/// Command class.
+pub const CLA: u8 = 0xe0;
+/// Get address command.
+pub const INS_GET_ADDRESS: u8 = 0x02;
+/// Sign hash command.
+pub const INS_SIGN: u8 = 0x04;
+
+/// Success.
+pub const SW_OK: u16 = 0x9000;
+/// Request rejected by the user.
+pub const SW_REJECTED: u16 = 0x6985;
+/// Invalid command data.
+pub const SW_INVALID_DATA: u16 = 0x6a80;
+/// Unknown command.
+pub const SW_INS_NOT_SUPPORTED: u16 = 0x6d00;
+
+/// Channel to the device, e.g. HID or an emulator.
+pub trait Transport: Send + Sync {
+	/// Sends the command `apdu` and returns the response, including the status word.
+	fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+	fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>, Error> {
+		(**self).exchange(apdu)
+	}
+}
+
+/// Build a command APDU.
+pub fn command(ins: u8, data: &[u8]) -> Vec<u8> {
+	let mut apdu = vec![CLA, ins, 0, 0, data.len() as u8];
+	apdu.extend_from_slice(data);
+	apdu
+}
+
+/// Encode derivation `path` as a command payload.
+pub fn encode_path(path: &DerivationPath) -> Vec<u8> {
+	let indices = path.indices();
+	let mut data = Vec::with_capacity(1 + indices.len() * 4);
+	data.push(indices.len() as u8);
+	for index in indices {
+		data.extend_from_slice(&[(index >> 24) as u8, (index >> 16) as u8, (index >> 8) as u8, *index as u8]);
+	}
+	data
+}
+
+/// Split the response into data and status word, failing on errors.
+fn response_data(response: &[u8]) -> Result<&[u8], Error> {
+	if response.len() < 2 {
+		return Err(Error::InvalidResponse);
+	}
+
+	let (data, status) = response.split_at(response.len() - 2);
+	match ((status[0] as u16) << 8) | status[1] as u16 {
+		SW_OK => Ok(data),
+		SW_REJECTED => Err(Error::Rejected),
+		status => Err(Error::Device(status)),
+	}
+}
+
+/// Accounts on a Ledger-style device, derived at the standard Ethereum path.
+/// The accounts are read from the device once and cached along with their paths.
+pub struct LedgerSigner<T> {
+	transport: T,
+	account_count: u32,
+	paths: RwLock<Vec<(Address, DerivationPath)>>,
+}
+
+impl<T: Transport> LedgerSigner<T> {
+	/// Exposes the first `account_count` accounts of the device connected through `transport`.
+	pub fn new(transport: T, account_count: u32) -> Self {
+		LedgerSigner {
+			transport: transport,
+			account_count: account_count,
+			paths: RwLock::new(Vec::new()),
+		}
+	}
+
+	/// Read the accounts from the device, replacing the cached ones.
+	pub fn refresh(&self) -> Result<Vec<Address>, Error> {
+		let mut paths = Vec::new();
+		for index in 0..self.account_count {
+			let path = DerivationPath::ethereum().child(index);
+			let address = self.address(&path)?;
+			paths.push((address, path));
+		}
+
+		let accounts = paths.iter().map(|&(ref address, _)| address.clone()).collect();
+		*self.paths.write() = paths;
+		Ok(accounts)
+	}
+
+	fn cached_path(&self, account: &Address) -> Option<DerivationPath> {
+		self.paths.read().iter().find(|&&(ref address, _)| address == account).map(|&(_, ref path)| path.clone())
+	}
+
+	fn address(&self, path: &DerivationPath) -> Result<Address, Error> {
+		let response = self.transport.exchange(&command(INS_GET_ADDRESS, &encode_path(path)))?;
+		let data = response_data(&response)?;
+
+		// public key and address, each prefixed with its length
+		let public_len = *data.get(0).ok_or(Error::InvalidResponse)? as usize;
+		let address_len = *data.get(1 + public_len).ok_or(Error::InvalidResponse)? as usize;
+		let start = 2 + public_len;
+		if data.len() < start + address_len {
+			return Err(Error::InvalidResponse);
+		}
+
+		let address = str::from_utf8(&data[start..start + address_len]).map_err(|_| Error::InvalidResponse)?;
+		Address::from_str(address).map_err(|_| Error::InvalidResponse)
+	}
+
+	fn path(&self, account: &Address) -> Result<DerivationPath, Error> {
+		if let Some(path) = self.cached_path(account) {
+			return Ok(path);
+		}
+
+		// the device may have been replaced
+		self.refresh()?;
+		self.cached_path(account).ok_or(Error::UnknownAccount)
+	}
+}
+
+impl<T: Transport> ExternalSigner for LedgerSigner<T> {
+	fn accounts(&self) -> Result<Vec<Address>, Error> {
+		{
+			let paths = self.paths.read();
+			if !paths.is_empty() {
+				return Ok(paths.iter().map(|&(ref address, _)| address.clone()).collect());
+			}
+		}
+		self.refresh()
+	}
+
+	fn has_account(&self, account: &Address) -> bool {
+		// only the first query goes to the device
+		if self.paths.read().is_empty() && self.refresh().is_err() {
+			return false;
+		}
+		self.cached_path(account).is_some()
+	}
+
+	fn sign(&self, account: &Address, message: &Message) -> Result<Signature, Error> {
+		let mut data = encode_path(&self.path(account)?);
+		data.extend_from_slice(&**message);
+
+		let response = self.transport.exchange(&command(INS_SIGN, &data))?;
+		let data = response_data(&response)?;
+		if data.len() != 65 || data[0] < 27 {
+			return Err(Error::InvalidResponse);
+		}
+
+		Ok(Signature::from_rsv(&H256::from_slice(&data[1..33]), &H256::from_slice(&data[33..65]), data[0] - 27))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use ethstore::ethkey::{Mnemonic, verify_address};
+	use account_provider::external::{ExternalSigner, LedgerEmulator, Error};
+	use super::{LedgerSigner, Transport};
+
+	struct CountingTransport {
+		device: Arc<LedgerEmulator>,
+		exchanges: AtomicUsize,
+	}
+
+	impl Transport for CountingTransport {
+		fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>, Error> {
+			self.exchanges.fetch_add(1, Ordering::SeqCst);
+			self.device.exchange(apdu)
+		}
+	}
+
+	fn emulator() -> Arc<LedgerEmulator> {
+		let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+		let mnemonic = Mnemonic::from_phrase(phrase).unwrap();
+		Arc::new(LedgerEmulator::new(&mnemonic.seed("")).unwrap())
+	}
+
+	#[test]
+	fn should_list_device_accounts() {
+		let signer = LedgerSigner::new(emulator(), 2);
+
+		assert_eq!(signer.accounts().unwrap(), vec![
+			"9858effd232b4033e47d90003d41ec34ecaeda94".into(),
+			"6fac4d18c912343bf86fa7049364dd4e424ab9c0".into(),
+		]);
+	}
+
+	#[test]
+	fn should_sign_on_device() {
+		let device = emulator();
+		let signer = LedgerSigner::new(device.clone(), 1);
+		let account = "9858effd232b4033e47d90003d41ec34ecaeda94".into();
+		let message = 5.into();
+
+		let signature = signer.sign(&account, &message).unwrap();
+		assert!(verify_address(&account, &signature, &message).unwrap());
+
+		device.set_confirm(false);
+		assert_eq!(signer.sign(&account, &message), Err(Error::Rejected));
+		assert_eq!(signer.sign(&6.into(), &message), Err(Error::UnknownAccount));
+	}
+
+	#[test]
+	fn should_cache_device_accounts() {
+		let transport = Arc::new(CountingTransport { device: emulator(), exchanges: AtomicUsize::new(0) });
+		let signer = LedgerSigner::new(transport.clone(), 2);
+
+		assert!(signer.has_account(&"6fac4d18c912343bf86fa7049364dd4e424ab9c0".into()));
+		assert!(!signer.has_account(&6.into()));
+		assert_eq!(signer.accounts().unwrap().len(), 2);
+		assert_eq!(transport.exchanges.load(Ordering::SeqCst), 2);
+
+		signer.refresh().unwrap();
+		assert_eq!(transport.exchanges.load(Ordering::SeqCst), 4);
+	}
+}
@@ -0,0 +1,71 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! External signers: accounts whose keys never leave a separate device.
+
+mod emulator;
+mod hid;
+mod ledger;
+
+use std::fmt;
+use ethstore::ethkey::{Address, Message, Signature};
+
+pub use self::emulator::LedgerEmulator;
+pub use self::hid::HidTransport;
+pub use self::ledger::{LedgerSigner, Transport};
+
+/// External signer errors.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+	/// Communication with the device failed.
+	Transport(String),
+	/// The account is not available on the device.
+	UnknownAccount,
+	/// The user rejected the request on the device.
+	Rejected,
+	/// The device returned a malformed response.
+	InvalidResponse,
+	/// The device returned an error status word.
+	Device(u16),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+		match *self {
+			Error::Transport(ref e) => write!(f, "Device communication failed: {}", e),
+			Error::UnknownAccount => write!(f, "Account not found on device"),
+			Error::Rejected => write!(f, "Request rejected on device"),
+			Error::InvalidResponse => write!(f, "Invalid device response"),
+			Error::Device(status) => write!(f, "Device error: 0x{:04x}", status),
+		}
+	}
+}
+
+/// Backend keeping accounts outside of the secret store, e.g. on a hardware wallet.
+pub trait ExternalSigner: Send + Sync {
+	/// Addresses of all accounts available through this signer.
+	fn accounts(&self) -> Result<Vec<Address>, Error>;
+
+	/// Whether `account` is available through this signer.
+	/// Implementations should answer without talking to the device where possible.
+	fn has_account(&self, account: &Address) -> bool {
+		self.accounts().map(|accounts| accounts.contains(account)).unwrap_or(false)
+	}
+
+	/// Signs `message` with `account`. The device may ask its user to confirm.
+	fn sign(&self, account: &Address, message: &Message) -> Result<Signature, Error>;
+}
@@ -16,9 +16,11 @@
 
 //! Account management.
 
+mod external;
 mod stores;
 
 use self::stores::{AddressBook, DappsSettingsStore, NewDappsPolicy};
+pub use self::external::{ExternalSigner, LedgerSigner, LedgerEmulator, HidTransport, Transport, Error as ExternalError};
 
 use std::fmt;
 use std::collections::HashMap;
@@ -26,7 +28,7 @@ use std::time::{Instant, Duration};
 use util::RwLock;
 use ethstore::{SimpleSecretStore, SecretStore, Error as SSError, EthStore, EthMultiStore, random_string};
 use ethstore::dir::MemoryDirectory;
-use ethstore::ethkey::{Address, Message, Public, Secret, Random, Generator};
+use ethstore::ethkey::{Address, Message, Public, Secret, Random, Generator, Mnemonic, DerivationPath};
 use ethjson::misc::AccountMeta;
 pub use ethstore::ethkey::Signature;
 
@@ -56,6 +58,8 @@ pub enum Error {
 	NotUnlocked,
 	/// Returned when signing fails.
 	SStore(SSError),
+	/// Returned when an external signer fails.
+	External(ExternalError),
 }
 
 impl fmt::Display for Error {
@@ -63,6 +67,7 @@ impl fmt::Display for Error {
 		match *self {
 			Error::NotUnlocked => write!(f, "Account is locked"),
 			Error::SStore(ref e) => write!(f, "{}", e),
+			Error::External(ref e) => write!(f, "{}", e),
 		}
 	}
 }
@@ -73,6 +78,12 @@ impl From<SSError> for Error {
 	}
 }
 
+impl From<ExternalError> for Error {
+	fn from(e: ExternalError) -> Self {
+		Error::External(e)
+	}
+}
+
 /// Dapp identifier
 pub type DappId = String;
 
@@ -92,6 +103,8 @@ pub struct AccountProvider {
 	sstore: Box<SecretStore>,
 	/// Accounts unlocked with rolling tokens
 	transient_sstore: EthMultiStore,
+	/// Accounts kept on external devices
+	external_signers: RwLock<Vec<Box<ExternalSigner>>>,
 }
 
 impl AccountProvider {
@@ -103,6 +116,7 @@ impl AccountProvider {
 			dapps_settings: RwLock::new(DappsSettingsStore::new(sstore.local_path().into())),
 			sstore: sstore,
 			transient_sstore: transient_sstore(),
+			external_signers: RwLock::new(Vec::new()),
 		}
 	}
 
@@ -114,6 +128,7 @@ impl AccountProvider {
 			dapps_settings: RwLock::new(DappsSettingsStore::transient()),
 			sstore: Box::new(EthStore::open(Box::new(MemoryDirectory::default())).expect("MemoryDirectory load always succeeds; qed")),
 			transient_sstore: transient_sstore(),
+			external_signers: RwLock::new(Vec::new()),
 		}
 	}
 
@@ -138,6 +153,20 @@ impl AccountProvider {
 		Ok(address)
 	}
 
+	/// Creates a hierarchical deterministic wallet from the mnemonic `phrase`.
+	/// Returns its first account, derived at the standard Ethereum path.
+	pub fn new_account_from_mnemonic(&self, phrase: &str, password: &str) -> Result<Address, Error> {
+		let mnemonic = Mnemonic::from_phrase(phrase).map_err(SSError::EthKey)?;
+		let address = self.sstore.insert_hd_wallet(&mnemonic.seed(""), DerivationPath::ethereum(), password)?;
+		Ok(address)
+	}
+
+	/// Derives the next account of the wallet `account` was derived from.
+	pub fn derive_account(&self, account: &Address, password: &str) -> Result<Address, Error> {
+		let address = self.sstore.derive_account(account, password)?;
+		Ok(address)
+	}
+
 	/// Import a new presale wallet.
 	pub fn import_presale(&self, presale_json: &[u8], password: &str) -> Result<Address, Error> {
 		let address = self.sstore.import_presale(presale_json, password)?;
@@ -150,12 +179,40 @@ impl AccountProvider {
 		Ok(Address::from(address).into())
 	}
 
+	/// Adds a backend of accounts kept on an external device.
+	/// Its accounts are listed along with the local ones and signing with them is routed to the device.
+	pub fn add_external_signer(&self, signer: Box<ExternalSigner>) {
+		self.external_signers.write().push(signer);
+	}
+
 	/// Returns addresses of all accounts.
 	pub fn accounts(&self) -> Result<Vec<Address>, Error> {
-		let accounts = self.sstore.accounts()?;
+		let mut accounts = self.sstore.accounts()?;
+		accounts.extend(self.external_accounts());
 		Ok(accounts)
 	}
 
+	/// Returns addresses of accounts on external devices, as cached by their signers.
+	/// Devices which can't be reached are skipped.
+	pub fn external_accounts(&self) -> Vec<Address> {
+		self.external_signers.read()
+			.iter()
+			.flat_map(|signer| signer.accounts().unwrap_or_else(|e| {
+				warn!("Cannot list accounts of external signer: {}", e);
+				Vec::new()
+			}))
+			.collect()
+	}
+
+	/// Signs `message` on the external device holding `account`.
+	/// Returns `None` if no device holds the account.
+	fn sign_external(&self, account: &Address, message: &Message) -> Option<Result<Signature, Error>> {
+		self.external_signers.read()
+			.iter()
+			.find(|signer| signer.has_account(account))
+			.map(|signer| signer.sign(account, message).map_err(Into::into))
+	}
+
 	/// Sets a whitelist of accounts exposed for unknown dapps.
 	/// `None` means that all accounts will be visible.
 	pub fn set_new_dapps_whitelist(&self, accounts: Option<Vec<Address>>) -> Result<(), Error> {
@@ -229,10 +286,14 @@ impl AccountProvider {
 
 	/// Returns each account along with name and meta.
 	pub fn accounts_info(&self) -> Result<HashMap<Address, AccountMeta>, Error> {
-		let r: HashMap<Address, AccountMeta> = self.sstore.accounts()?
+		let mut r: HashMap<Address, AccountMeta> = self.sstore.accounts()?
 			.into_iter()
 			.map(|a| (a.clone(), self.account_meta(a).ok().unwrap_or_default()))
 			.collect();
+		r.extend(self.external_accounts().into_iter().map(|a| (a, AccountMeta {
+			meta: r#"{"hardware":true}"#.into(),
+			..Default::default()
+		})));
 		Ok(r)
 	}
 
@@ -334,13 +395,23 @@ impl AccountProvider {
 	}
 
 	/// Signs the message. If password is not provided the account must be unlocked.
+	/// Accounts on external devices are signed with on the device, without a password.
 	pub fn sign(&self, account: Address, password: Option<String>, message: Message) -> Result<Signature, Error> {
+		if let Some(result) = self.sign_external(&account, &message) {
+			return result;
+		}
+
 		let password = password.map(Ok).unwrap_or_else(|| self.password(&account))?;
 		Ok(self.sstore.sign(&account, &password, &message)?)
 	}
 
 	/// Signs given message with supplied token. Returns a token to use in next signing within this session.
 	pub fn sign_with_token(&self, account: Address, token: AccountToken, message: Message) -> Result<(Signature, AccountToken), Error> {
+		if let Some(result) = self.sign_external(&account, &message) {
+			// the device does its own confirmation, the token stays valid
+			return result.map(|signature| (signature, token));
+		}
+
 		let is_std_password = self.sstore.test_password(&account, &token)?;
 
 		let new_token = random_string(16);
@@ -396,13 +467,89 @@ impl AccountProvider {
 	pub fn import_geth_accounts(&self, desired: Vec<Address>, testnet: bool) -> Result<Vec<Address>, Error> {
 		self.sstore.import_geth_accounts(desired, testnet).map_err(Into::into)
 	}
+
+	/// Create new vault.
+	pub fn create_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		self.sstore.create_vault(name, password).map_err(Into::into)
+	}
+
+	/// Open existing vault.
+	pub fn open_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		self.sstore.open_vault(name, password).map_err(Into::into)
+	}
+
+	/// Close previously opened vault.
+	pub fn close_vault(&self, name: &str) -> Result<(), Error> {
+		self.sstore.close_vault(name).map_err(Into::into)
+	}
+
+	/// Change password of an opened vault.
+	pub fn change_vault_password(&self, name: &str, new_password: &str) -> Result<(), Error> {
+		self.sstore.change_vault_password(name, new_password).map_err(Into::into)
+	}
+
+	/// List all vaults.
+	pub fn list_vaults(&self) -> Result<Vec<String>, Error> {
+		self.sstore.list_vaults().map_err(Into::into)
+	}
+
+	/// List opened vaults.
+	pub fn list_opened_vaults(&self) -> Result<Vec<String>, Error> {
+		self.sstore.list_opened_vaults().map_err(Into::into)
+	}
+
+	/// Move account to the opened vault, or out of its vault if `vault` is `None`.
+	pub fn change_vault(&self, address: Address, vault: Option<String>) -> Result<(), Error> {
+		self.sstore.change_account_vault(&address, vault).map_err(Into::into)
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use super::{AccountProvider, Unlock};
+	use super::{AccountProvider, Unlock, LedgerSigner, LedgerEmulator, Error, ExternalError};
+	use std::sync::Arc;
 	use std::time::Instant;
-	use ethstore::ethkey::{Generator, Random};
+	use ethstore::ethkey::{Generator, Random, Mnemonic, verify_address};
+
+	#[test]
+	fn derive_accounts_from_mnemonic() {
+		let ap = AccountProvider::transient_provider();
+		let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+		assert!(ap.new_account_from_mnemonic("abandon abandon abandon", "test").is_err());
+		let first = ap.new_account_from_mnemonic(phrase, "test").unwrap();
+		assert_eq!(first, "9858effd232b4033e47d90003d41ec34ecaeda94".parse().unwrap());
+		let second = ap.derive_account(&first, "test").unwrap();
+		assert_eq!(ap.accounts().unwrap().len(), 2);
+		assert!(ap.sign(second, Some("test".into()), Default::default()).is_ok());
+	}
+
+	#[test]
+	fn should_sign_with_external_signer() {
+		// given
+		let ap = AccountProvider::transient_provider();
+		let local = ap.new_account("test").unwrap();
+		let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+		let device = Arc::new(LedgerEmulator::new(&Mnemonic::from_phrase(phrase).unwrap().seed("")).unwrap());
+		let hardware = "9858effd232b4033e47d90003d41ec34ecaeda94".parse().unwrap();
+
+		// when
+		ap.add_external_signer(Box::new(LedgerSigner::new(device.clone(), 1)));
+
+		// then
+		assert_eq!(ap.accounts().unwrap(), vec![local, hardware]);
+		assert!(ap.accounts_info().unwrap().contains_key(&hardware));
+		assert!(!ap.is_unlocked(hardware));
+		let signature = ap.sign(hardware, None, 1.into()).unwrap();
+		assert!(verify_address(&hardware, &signature, &1.into()).unwrap());
+		let (_, token) = ap.sign_with_token(hardware, "token".into(), 1.into()).unwrap();
+		assert_eq!(token, "token");
+
+		device.set_confirm(false);
+		match ap.sign(hardware, None, 1.into()) {
+			Err(Error::External(ExternalError::Rejected)) => {},
+			_ => panic!("Signing should be rejected on the device."),
+		}
+	}
 
 	#[test]
 	fn unlock_account_temp() {
@@ -91,6 +91,7 @@ pub struct ExecutedBlock {
 	transactions_set: HashSet<H256>,
 	state: State,
 	traces: Option<Vec<Vec<FlatTrace>>>,
+	last_hashes: Arc<LastHashes>,
 }
 
 /// A set of references to `ExecutedBlock` fields that are publicly accessible.
@@ -106,7 +107,7 @@ pub struct BlockRefMut<'a> {
 	/// State.
 	pub state: &'a mut State,
 	/// Traces.
-	pub traces: &'a Option<Vec<Vec<FlatTrace>>>,
+	pub traces: &'a mut Option<Vec<Vec<FlatTrace>>>,
 }
 
 /// A set of immutable references to `ExecutedBlock` fields that are publicly accessible.
@@ -127,13 +128,14 @@ pub struct BlockRef<'a> {
 
 impl ExecutedBlock {
 	/// Create a new block from the given `state`.
-	fn new(state: State, tracing: bool) -> ExecutedBlock {
+	fn new(state: State, last_hashes: Arc<LastHashes>, tracing: bool) -> ExecutedBlock {
 		ExecutedBlock {
 			base: Default::default(),
 			receipts: Default::default(),
 			transactions_set: Default::default(),
 			state: state,
 			traces: if tracing {Some(Vec::new())} else {None},
+			last_hashes: last_hashes,
 		}
 	}
 
@@ -145,7 +147,7 @@ impl ExecutedBlock {
 			uncles: &self.base.uncles,
 			state: &mut self.state,
 			receipts: &self.receipts,
-			traces: &self.traces,
+			traces: &mut self.traces,
 		}
 	}
 
@@ -160,6 +162,24 @@ impl ExecutedBlock {
 			traces: &self.traces,
 		}
 	}
+
+	/// Returns true if the execution of this block is being traced.
+	pub fn tracing_enabled(&self) -> bool {
+		self.traces.is_some()
+	}
+
+	/// Get the environment info concerning this block, after all its transactions.
+	pub fn env_info(&self) -> EnvInfo {
+		EnvInfo {
+			number: self.base.header.number(),
+			author: self.base.header.author().clone(),
+			timestamp: self.base.header.timestamp(),
+			difficulty: self.base.header.difficulty().clone(),
+			last_hashes: self.last_hashes.clone(),
+			gas_used: self.receipts.last().map_or(U256::zero(), |r| r.gas_used),
+			gas_limit: self.base.header.gas_limit().clone(),
+		}
+	}
 }
 
 /// Trait for a object that is a `ExecutedBlock`.
@@ -254,7 +274,7 @@ impl<'x> OpenBlock<'x> {
 	) -> Result<Self, Error> {
 		let state = State::from_existing(db, parent.state_root().clone(), engine.account_start_nonce(), factories)?;
 		let mut r = OpenBlock {
-			block: ExecutedBlock::new(state, tracing),
+			block: ExecutedBlock::new(state, last_hashes.clone(), tracing),
 			engine: engine,
 			last_hashes: last_hashes,
 		};
@@ -323,15 +343,7 @@ impl<'x> OpenBlock<'x> {
 	/// Get the environment info concerning this block.
 	pub fn env_info(&self) -> EnvInfo {
 		// TODO: memoise.
-		EnvInfo {
-			number: self.block.base.header.number(),
-			author: self.block.base.header.author().clone(),
-			timestamp: self.block.base.header.timestamp(),
-			difficulty: self.block.base.header.difficulty().clone(),
-			last_hashes: self.last_hashes.clone(),
-			gas_used: self.block.receipts.last().map_or(U256::zero(), |r| r.gas_used),
-			gas_limit: self.block.base.header.gas_limit().clone(),
-		}
+		self.block.env_info()
 	}
 
 	/// Push a transaction into the block.
@@ -440,6 +452,11 @@ impl ClosedBlock {
 		// revert rewards (i.e. set state back at last transaction's state).
 		let mut block = self.block;
 		block.state = self.unclosed_state;
+		// drop any traces the engine added on close (e.g. rewards), keeping one per transaction.
+		let transactions = block.base.transactions.len();
+		if let Some(ref mut traces) = block.traces {
+			traces.truncate(transactions);
+		}
 		OpenBlock {
 			block: block,
 			engine: engine,
@@ -1319,7 +1319,7 @@ mod tests {
 	use util::{Database, DatabaseConfig};
 	use util::hash::*;
 	use util::sha3::Hashable;
-	use receipt::Receipt;
+	use receipt::{Receipt, TransactionOutcome};
 	use blockchain::{BlockProvider, BlockChain, Config, ImportRoute};
 	use tests::helpers::*;
 	use devtools::*;
@@ -1897,7 +1897,7 @@ mod tests {
 		let db = new_db(temp.as_str());
 		let bc = new_chain(&genesis, db.clone());
 		insert_block(&db, &bc, &b1, vec![Receipt {
-			state_root: H256::default(),
+			outcome: TransactionOutcome::StateRoot(H256::default()),
 			gas_used: 10_000.into(),
 			log_bloom: Default::default(),
 			logs: vec![
@@ -1906,7 +1906,7 @@ mod tests {
 			],
 		},
 		Receipt {
-			state_root: H256::default(),
+			outcome: TransactionOutcome::StateRoot(H256::default()),
 			gas_used: 10_000.into(),
 			log_bloom: Default::default(),
 			logs: vec![
@@ -1915,7 +1915,7 @@ mod tests {
 		}]);
 		insert_block(&db, &bc, &b2, vec![
 			Receipt {
-				state_root: H256::default(),
+				outcome: TransactionOutcome::StateRoot(H256::default()),
 				gas_used: 10_000.into(),
 				log_bloom: Default::default(),
 				logs: vec![
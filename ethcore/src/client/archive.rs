@@ -0,0 +1,237 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Background rebuild of archive state for a client with pruned state.
+//!
+//! The blocks of the client's chain are re-executed by a second client which keeps all
+//! state. Once it has caught up with the chain, the first client switches over to its
+//! database, so historical state becomes available without a restart.
+
+use std::cmp;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use client::{BlockChainClient, BlockId, BlockImportError, Client};
+use error::ImportError;
+use util::RwLock;
+
+// how many blocks to queue for re-execution at once.
+const BATCH_SIZE: u64 = 1000;
+
+// how often to report progress.
+const PROGRESS_INTERVAL_SECS: u64 = 10;
+
+// how long to wait for the chain to settle before trying to switch over again.
+const SWITCH_RETRY_MS: u64 = 100;
+
+/// Status of an archive rebuild.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebuildStatus {
+	/// Blocks are being re-executed.
+	Ongoing {
+		/// The first block re-executed.
+		first: u64,
+		/// The best block re-executed so far.
+		current: u64,
+		/// The best block of the chain being rebuilt.
+		target: u64,
+	},
+	/// The rebuild caught up at the given block and the client switched over to it.
+	Finished(u64),
+	/// The rebuild stopped with an error.
+	Failed(String),
+}
+
+/// A background rebuild of archive state. Dropping it aborts the rebuild; it can be
+/// resumed later by starting a new one with the same archive client.
+pub struct ArchiveRebuild {
+	status: Arc<RwLock<RebuildStatus>>,
+	abort: Arc<AtomicBool>,
+	thread: Option<JoinHandle<()>>,
+}
+
+impl ArchiveRebuild {
+	/// Start re-executing the chain of `source` into `archive`, which must be a client
+	/// of the same chain using `Algorithm::Archive` and its own engine instance.
+	///
+	/// Re-execution starts at block `from`, so the archive must already hold the
+	/// state of its parent: either the genesis state, a snapshot restored at block
+	/// `from - 1`, or an earlier rebuild, which is resumed from its best block.
+	///
+	/// `on_finish` is called with the best block once `source` has switched over;
+	/// `archive` can't write to the database any more by then and should be shut down.
+	pub fn start<F>(source: Arc<Client>, archive: Arc<Client>, from: u64, on_finish: F) -> Result<Self, String>
+		where F: FnOnce(u64) + Send + 'static
+	{
+		let archive_info = archive.chain_info();
+		let archive_best = archive_info.best_block_number;
+		if source.block_hash(BlockId::Number(archive_best)) != Some(archive_info.best_block_hash) {
+			return Err(format!("Archive best block #{} is not part of the chain being rebuilt.", archive_best));
+		}
+
+		let first = match archive_best + 1 {
+			next if next < from => return Err(format!(
+				"Archive is at block #{}, so re-execution from block #{} is impossible. \
+				Restore a snapshot taken at block #{} first.", archive_best, from, from - 1)),
+			next if next > from && archive_best > 0 => {
+				info!(target: "archive", "Resuming archive rebuild from block #{}", next);
+				next
+			}
+			next => next,
+		};
+
+		let target = source.chain_info().best_block_number;
+		let status = Arc::new(RwLock::new(RebuildStatus::Ongoing {
+			first: first,
+			current: archive_best,
+			target: target,
+		}));
+		let abort = Arc::new(AtomicBool::new(false));
+
+		let rebuilder = Rebuilder {
+			source: source,
+			archive: archive,
+			first: first,
+			status: status.clone(),
+			abort: abort.clone(),
+		};
+
+		let thread = thread::Builder::new()
+			.name("Archive rebuild".into())
+			.spawn(move || {
+				let result = rebuilder.run();
+				let status = match result {
+					Ok(Some(best)) => {
+						info!(target: "archive", "Archive state rebuilt up to block #{}", best);
+						on_finish(best);
+						RebuildStatus::Finished(best)
+					}
+					Ok(None) => return,
+					Err(e) => {
+						warn!(target: "archive", "Archive rebuild failed: {}", e);
+						RebuildStatus::Failed(e)
+					}
+				};
+				*rebuilder.status.write() = status;
+			})
+			.map_err(|e| format!("Unable to spawn archive rebuild thread: {}", e))?;
+
+		Ok(ArchiveRebuild {
+			status: status,
+			abort: abort,
+			thread: Some(thread),
+		})
+	}
+
+	/// Get the status of the rebuild.
+	pub fn status(&self) -> RebuildStatus {
+		self.status.read().clone()
+	}
+}
+
+impl Drop for ArchiveRebuild {
+	fn drop(&mut self) {
+		self.abort.store(true, AtomicOrdering::SeqCst);
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+// state of the rebuild thread.
+struct Rebuilder {
+	source: Arc<Client>,
+	archive: Arc<Client>,
+	first: u64,
+	status: Arc<RwLock<RebuildStatus>>,
+	abort: Arc<AtomicBool>,
+}
+
+impl Rebuilder {
+	// re-execute blocks until caught up and switched over, returning the best block,
+	// or `None` if aborted.
+	fn run(&self) -> Result<Option<u64>, String> {
+		let mut next = self.first;
+		let mut last_report = Instant::now();
+
+		loop {
+			if self.abort.load(AtomicOrdering::SeqCst) { return Ok(None) }
+
+			// step back past blocks which the source chain has since retracted.
+			next = self.common_ancestor(next - 1) + 1;
+
+			let target = self.source.chain_info().best_block_number;
+			if next > target {
+				if self.source.switch_db(&self.archive)? {
+					return Ok(Some(target));
+				}
+
+				// the source imported more blocks in the meantime.
+				thread::sleep(Duration::from_millis(SWITCH_RETRY_MS));
+				continue;
+			}
+
+			let end = cmp::min(target, next + BATCH_SIZE - 1);
+			for number in next..(end + 1) {
+				let block = match self.source.block(BlockId::Number(number)) {
+					Some(block) => block.into_inner(),
+					None => return Err(format!("Block #{} is missing from the chain.", number)),
+				};
+
+				while self.archive.queue_info().is_full() {
+					if self.abort.load(AtomicOrdering::SeqCst) { return Ok(None) }
+					thread::sleep(Duration::from_millis(SWITCH_RETRY_MS));
+				}
+
+				match self.archive.import_block(block) {
+					Ok(_) | Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {}
+					Err(e) => return Err(format!("Cannot import block #{}: {:?}", number, e)),
+				}
+			}
+
+			self.archive.flush_queue();
+			while self.archive.import_verified_blocks() > 0 {}
+
+			let current = self.archive.chain_info().best_block_number;
+			if current < end {
+				return Err(format!("Block #{} could not be re-executed.", current + 1));
+			}
+
+			*self.status.write() = RebuildStatus::Ongoing {
+				first: self.first,
+				current: current,
+				target: target,
+			};
+
+			if last_report.elapsed() >= Duration::from_secs(PROGRESS_INTERVAL_SECS) {
+				info!(target: "archive", "Rebuilding archive state: block #{} of #{}", current, target);
+				last_report = Instant::now();
+			}
+
+			next = current + 1;
+		}
+	}
+
+	// the highest block at or below `number` which both chains agree on.
+	fn common_ancestor(&self, mut number: u64) -> u64 {
+		while number > 0 && self.archive.block_hash(BlockId::Number(number)) != self.source.block_hash(BlockId::Number(number)) {
+			number -= 1;
+		}
+		number
+	}
+}
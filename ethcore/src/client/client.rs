@@ -20,7 +20,7 @@ use std::sync::{Arc, Weak};
 use std::path::{Path};
 use std::fmt;
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
-use std::time::{Instant};
+use std::time::{Instant, Duration};
 use time::precise_time_ns;
 
 // util
@@ -38,7 +38,7 @@ use header::BlockNumber;
 use state::{State, CleanupMode};
 use spec::Spec;
 use basic_types::Seal;
-use engines::Engine;
+use engines::{Engine, EquivocationProof};
 use service::ClientIoMessage;
 use env_info::LastHashes;
 use verification;
@@ -64,10 +64,10 @@ use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Databa
 use trace;
 use trace::FlatTransactionTraces;
 use evm::{Factory as EvmFactory, Schedule};
-use miner::{Miner, MinerService};
+use miner::{Miner, MinerService, PersistedTransaction};
 use snapshot::{self, io as snapshot_io};
 use factory::Factories;
-use rlp::{View, UntrustedRlp};
+use rlp::{self, View, UntrustedRlp};
 use state_db::StateDB;
 use rand::OsRng;
 use client::registry::Registry;
@@ -82,6 +82,12 @@ pub use verification::queue::QueueInfo as BlockQueueInfo;
 const MAX_TX_QUEUE_SIZE: usize = 4096;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+/// Prefix of the keys of engine-specific data in the extras column.
+const ENGINE_DATA_PREFIX: &'static [u8] = b"engine_";
+/// Key of the persisted transaction queue in the extras column.
+const TRANSACTION_QUEUE_KEY: &'static [u8] = b"transaction_queue";
+/// How often the transaction queue is written to the database.
+const TRANSACTION_QUEUE_PERSIST_PERIOD: u64 = 60;
 
 impl fmt::Display for BlockChainInfo {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -134,7 +140,7 @@ pub struct Client {
 	tracedb: RwLock<TraceDB<BlockChain>>,
 	engine: Arc<Engine>,
 	config: ClientConfig,
-	pruning: journaldb::Algorithm,
+	pruning: RwLock<journaldb::Algorithm>,
 	db: RwLock<Arc<Database>>,
 	state_db: Mutex<StateDB>,
 	block_queue: BlockQueue,
@@ -154,6 +160,7 @@ pub struct Client {
 	rng: Mutex<OsRng>,
 	on_mode_change: Mutex<Option<Box<FnMut(&Mode) + 'static + Send>>>,
 	registrar: Mutex<Option<Registry>>,
+	queue_persisted_at: Mutex<Instant>,
 }
 
 impl Client {
@@ -237,7 +244,7 @@ impl Client {
 			chain: RwLock::new(chain),
 			tracedb: tracedb,
 			engine: engine,
-			pruning: config.pruning.clone(),
+			pruning: RwLock::new(config.pruning.clone()),
 			verifier: verification::new(config.verifier_type.clone()),
 			config: config,
 			db: RwLock::new(db),
@@ -256,13 +263,16 @@ impl Client {
 			rng: Mutex::new(OsRng::new().map_err(::util::UtilError::StdIo)?),
 			on_mode_change: Mutex::new(None),
 			registrar: Mutex::new(None),
+			queue_persisted_at: Mutex::new(Instant::now()),
 		});
 		if let Some(reg_addr) = client.additional_params().get("registrar").and_then(|s| Address::from_str(s).ok()) {
 			trace!(target: "client", "Found registrar at {}", reg_addr);
 			let weak = Arc::downgrade(&client);
-			let registrar = Registry::new(reg_addr, move |a, d| weak.upgrade().ok_or("No client!".into()).and_then(|c| c.call_contract(a, d)));
+			let registrar = Registry::new(reg_addr, move |a, d| weak.upgrade().ok_or("No client!".into()).and_then(|c| c.call_contract(BlockId::Latest, a, d)));
 			*client.registrar.lock() = Some(registrar);
 		}
+		client.engine.register_client(Arc::downgrade(&client));
+		client.restore_transaction_queue();
 		Ok(client)
 	}
 
@@ -276,6 +286,63 @@ impl Client {
 		&*self.engine
 	}
 
+	/// Get engine-specific data previously stored with `set_engine_data`.
+	pub fn engine_data(&self, key: &[u8]) -> Option<Bytes> {
+		match self.db.read().get(::db::COL_EXTRA, &engine_data_key(key)) {
+			Ok(value) => value.map(|v| v.to_vec()),
+			Err(e) => {
+				warn!(target: "client", "Failed to read engine data: {}", e);
+				None
+			},
+		}
+	}
+
+	/// Store engine-specific data so that it is available after a restart.
+	/// The data is written straight to the database rather than buffered.
+	pub fn set_engine_data(&self, key: &[u8], value: &[u8]) -> Result<(), String> {
+		let db = self.db.read();
+		let mut batch = DBTransaction::new(&db);
+		batch.put(::db::COL_EXTRA, &engine_data_key(key), value);
+		db.write(batch)
+	}
+
+	/// Write the transactions which should survive a restart to the database.
+	pub fn persist_transaction_queue(&self) {
+		let transactions = self.miner.transactions_to_persist();
+		let db = self.db.read();
+		let mut batch = DBTransaction::new(&db);
+		batch.put(::db::COL_EXTRA, TRANSACTION_QUEUE_KEY, &rlp::encode(&transactions));
+		match db.write(batch) {
+			Ok(_) => trace!(target: "client", "Persisted {} queued transactions", transactions.len()),
+			Err(e) => warn!(target: "client", "Failed to persist transaction queue: {}", e),
+		}
+	}
+
+	/// Re-import the transactions persisted before the last shutdown.
+	fn restore_transaction_queue(&self) {
+		let bytes = match self.db.read().get(::db::COL_EXTRA, TRANSACTION_QUEUE_KEY) {
+			Ok(Some(bytes)) => bytes,
+			Ok(None) => return,
+			Err(e) => {
+				warn!(target: "client", "Failed to read persisted transaction queue: {}", e);
+				return;
+			},
+		};
+		let transactions: Vec<PersistedTransaction> = match UntrustedRlp::new(&bytes).as_val() {
+			Ok(transactions) => transactions,
+			Err(e) => {
+				warn!(target: "client", "Ignoring corrupted persisted transaction queue: {}", e);
+				return;
+			},
+		};
+		if transactions.is_empty() {
+			return;
+		}
+		let total = transactions.len();
+		let restored = self.miner.restore_transactions(self, transactions);
+		info!(target: "client", "Restored {} of {} transactions queued before shutdown", restored, total);
+	}
+
 	fn notify<F>(&self, f: F) where F: Fn(&ChainNotify) {
 		for np in self.notify.read().iter() {
 			if let Some(n) = np.upgrade() {
@@ -662,6 +729,36 @@ impl Client {
 		}
 	}
 
+	/// Switch over to the database of another client which has imported the same chain
+	/// with a different pruning algorithm, e.g. while rebuilding archive state.
+	/// Returns `false` without switching if the best blocks of the two clients differ.
+	///
+	/// The database is closed for `other`, so that nothing it does afterwards - timers,
+	/// its engine or dropping it - writes to the database; it should be shut down.
+	pub fn switch_db(&self, other: &Client) -> Result<bool, String> {
+		let _import_lock = self.import_lock.lock();
+		let _other_import_lock = other.import_lock.lock();
+		if other.chain_info().best_block_hash != self.chain_info().best_block_hash {
+			return Ok(false);
+		}
+
+		let mut state_db = self.state_db.lock();
+		let mut chain = self.chain.write();
+		let mut tracedb = self.tracedb.write();
+		let mut db = self.db.write();
+		let pruning = *other.pruning.read();
+		*db = Arc::new(other.db.read().reopen()?);
+
+		let cache_size = state_db.cache_size();
+		*state_db = StateDB::new(journaldb::new(db.clone(), pruning, ::db::COL_STATE), cache_size);
+		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone(), self.engine.clone()));
+		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
+		*self.pruning.write() = pruning;
+
+		info!(target: "client", "Switched over to the {} state database", pruning.as_str());
+		Ok(true)
+	}
+
 	/// Get a copy of the best block's state.
 	pub fn state(&self) -> State {
 		let header = self.best_block_header();
@@ -690,6 +787,15 @@ impl Client {
 	pub fn tick(&self) {
 		self.check_garbage();
 		self.check_snooze();
+		self.check_queue_persistence();
+	}
+
+	fn check_queue_persistence(&self) {
+		let mut persisted_at = self.queue_persisted_at.lock();
+		if persisted_at.elapsed() >= Duration::from_secs(TRANSACTION_QUEUE_PERSIST_PERIOD) {
+			self.persist_transaction_queue();
+			*persisted_at = Instant::now();
+		}
 	}
 
 	fn check_garbage(&self) {
@@ -830,7 +936,7 @@ impl snapshot::DatabaseRestore for Client {
 		db.restore(new_db)?;
 
 		let cache_size = state_db.cache_size();
-		*state_db = StateDB::new(journaldb::new(db.clone(), self.pruning, ::db::COL_STATE), cache_size);
+		*state_db = StateDB::new(journaldb::new(db.clone(), *self.pruning.read(), ::db::COL_STATE), cache_size);
 		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone(), self.engine.clone()));
 		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
 		Ok(())
@@ -864,7 +970,7 @@ impl BlockChainClient for Client {
 			// give the sender a sufficient balance
 			state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty);
 		}
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, struct_logging: analytics.struct_logging, check_nonce: false };
 		let mut ret = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, options)?;
 
 		// TODO gav move this into Executive.
@@ -884,7 +990,7 @@ impl BlockChainClient for Client {
 			return Err(CallError::TransactionNotFound);
 		}
 
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, struct_logging: analytics.struct_logging, check_nonce: false };
 		let last_hashes = self.build_last_hashes(header.hash());
 		let mut env_info = EnvInfo {
 			number: header.number(),
@@ -910,6 +1016,36 @@ impl BlockChainClient for Client {
 		Ok(ret)
 	}
 
+	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Vec<Executed>, CallError> {
+		let header = self.block_header(block).ok_or(CallError::StatePruned)?;
+		let body = self.block_body(block).ok_or(CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(block).ok_or(CallError::StatePruned)?;
+
+		let last_hashes = self.build_last_hashes(header.hash());
+		let mut env_info = EnvInfo {
+			number: header.number(),
+			author: header.author(),
+			timestamp: header.timestamp(),
+			difficulty: header.difficulty(),
+			last_hashes: last_hashes,
+			gas_used: U256::default(),
+			gas_limit: header.gas_limit(),
+		};
+
+		let mut executed = Vec::new();
+		for t in body.transactions() {
+			// each transaction gets its own tracers.
+			let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, struct_logging: analytics.struct_logging, check_nonce: false };
+			let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+			let mut ret = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(&t, options)?;
+			ret.state_diff = original_state.map(|original| state.diff_from(original));
+			env_info.gas_used = env_info.gas_used + ret.gas_used;
+			executed.push(ret);
+		}
+
+		Ok(executed)
+	}
+
 	fn keep_alive(&self) {
 		let should_wake = match *self.mode.lock() {
 			Mode::Dark(..) | Mode::Passive(..) => true,
@@ -1342,10 +1478,10 @@ impl BlockChainClient for Client {
 		}
 	}
 
-	fn call_contract(&self, address: Address, data: Bytes) -> Result<Bytes, String> {
+	fn call_contract(&self, block_id: BlockId, address: Address, data: Bytes) -> Result<Bytes, String> {
 		let from = Address::default();
 		let transaction = Transaction {
-			nonce: self.latest_nonce(&from),
+			nonce: self.nonce(&from, block_id).unwrap_or_default(),
 			action: Action::Call(address),
 			gas: U256::from(50_000_000),
 			gas_price: U256::default(),
@@ -1353,7 +1489,7 @@ impl BlockChainClient for Client {
 			data: data,
 		}.fake_sign(from);
 
-		self.call(&transaction, BlockId::Latest, Default::default())
+		self.call(&transaction, block_id, Default::default())
 			.map_err(|e| format!("{:?}", e))
 			.map(|executed| {
 				executed.output
@@ -1376,6 +1512,10 @@ impl MiningBlockChainClient for Client {
 		self.engine.schedule(&self.latest_env_info())
 	}
 
+	fn equivocation_proofs(&self) -> Vec<EquivocationProof> {
+		self.engine.equivocation_proofs()
+	}
+
 	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
 		let engine = &*self.engine;
 		let chain = self.chain.read();
@@ -1500,13 +1640,21 @@ impl ::client::ProvingBlockChainClient for Client {
 
 impl Drop for Client {
 	fn drop(&mut self) {
+		self.persist_transaction_queue();
 		self.engine.stop();
 	}
 }
 
+/// Database key of engine-specific data.
+fn engine_data_key(key: &[u8]) -> Vec<u8> {
+	let mut k = ENGINE_DATA_PREFIX.to_vec();
+	k.extend_from_slice(key);
+	k
+}
+
 /// Returns `LocalizedReceipt` given `LocalizedTransaction`
 /// and a vector of receipts from given block up to transaction index.
-fn transaction_receipt(tx: LocalizedTransaction, mut receipts: Vec<Receipt>) -> LocalizedReceipt {
+pub fn transaction_receipt(tx: LocalizedTransaction, mut receipts: Vec<Receipt>) -> LocalizedReceipt {
 	assert_eq!(receipts.len(), tx.transaction_index + 1, "All previous receipts are provided.");
 
 	let sender = tx.sender()
@@ -1543,7 +1691,7 @@ fn transaction_receipt(tx: LocalizedTransaction, mut receipts: Vec<Receipt>) ->
 			log_index: no_of_logs + i,
 		}).collect(),
 		log_bloom: receipt.log_bloom,
-		state_root: receipt.state_root,
+		outcome: receipt.outcome,
 	}
 }
 
@@ -1588,7 +1736,7 @@ mod tests {
 		use super::transaction_receipt;
 		use ethkey::KeyPair;
 		use log_entry::{LogEntry, LocalizedLogEntry};
-		use receipt::{Receipt, LocalizedReceipt};
+		use receipt::{Receipt, LocalizedReceipt, TransactionOutcome};
 		use transaction::{Transaction, LocalizedTransaction, Action};
 		use util::Hashable;
 
@@ -1598,7 +1746,7 @@ mod tests {
 
 		let block_number = 1;
 		let block_hash = 5.into();
-		let state_root = 99.into();
+		let state_root: H256 = 99.into();
 		let gas_used = 10.into();
 		let raw_tx = Transaction {
 			nonce: 0.into(),
@@ -1625,12 +1773,12 @@ mod tests {
 			data: vec![],
 		}];
 		let receipts = vec![Receipt {
-			state_root: state_root,
+			outcome: TransactionOutcome::StateRoot(state_root),
 			gas_used: 5.into(),
 			log_bloom: Default::default(),
 			logs: vec![logs[0].clone()],
 		}, Receipt {
-			state_root: state_root,
+			outcome: TransactionOutcome::StateRoot(state_root),
 			gas_used: gas_used,
 			log_bloom: Default::default(),
 			logs: logs.clone(),
@@ -1666,7 +1814,7 @@ mod tests {
 				log_index: 2,
 			}],
 			log_bloom: Default::default(),
-			state_root: state_root,
+			outcome: TransactionOutcome::StateRoot(state_root),
 		});
 	}
 }
@@ -16,16 +16,20 @@
 
 //! Blockchain database client.
 
+mod archive;
 mod registry;
 mod config;
 mod error;
 mod test_client;
 mod trace;
 mod client;
+mod partial_state;
 
+pub use self::archive::{ArchiveRebuild, RebuildStatus};
 pub use self::client::*;
 pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockChainConfig, VMType};
 pub use self::error::Error;
+pub use self::partial_state::{PartialState, PartialAccount, PartialCall};
 pub use self::test_client::{TestBlockChainClient, EachBlockWith};
 pub use self::chain_notify::ChainNotify;
 pub use self::traits::{BlockChainClient, MiningBlockChainClient};
@@ -0,0 +1,229 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Calls against a partial state.
+//!
+//! Light clients only know the parts of the state they have fetched and verified.
+//! A call is executed against those while recording every account and storage item
+//! looked up. If any of them weren't known, the caller fetches them and calls again.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use db::COL_STATE;
+use engines::Engine;
+use env_info::EnvInfo;
+use executive::{Executive, Executed, TransactOptions};
+use factory::Factories;
+use pod_account::PodAccount;
+use pod_state::PodState;
+use state::{State, CleanupMode};
+use state_db::StateDB;
+use transaction::SignedTransaction;
+use types::executed::{CallError, ExecutionError};
+
+use util::{Address, Bytes, H256, U256, Database};
+use util::journaldb::{self, Algorithm};
+use util::sha3::SHA3_NULL_RLP;
+
+// size of the account and code caches of the scratch state.
+const SCRATCH_CACHE_SIZE: usize = 1024 * 1024;
+
+/// An account known to a partial state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialAccount {
+	/// The account's nonce.
+	pub nonce: U256,
+	/// The account's balance.
+	pub balance: U256,
+	/// The account's code.
+	pub code: Bytes,
+}
+
+/// Outcome of a call against a partial state.
+#[derive(Debug, PartialEq)]
+pub enum PartialCall {
+	/// The call only looked up known accounts and storage.
+	Executed(Executed),
+	/// The call looked up unknown accounts or storage, which must be added before
+	/// calling again. Storage is only reported for accounts known to exist.
+	Missing {
+		/// Addresses of unknown accounts.
+		accounts: Vec<Address>,
+		/// Unknown storage items, by account address and key.
+		storage: Vec<(Address, H256)>,
+	},
+}
+
+/// The known parts of a state.
+#[derive(Debug, Default, Clone)]
+pub struct PartialState {
+	accounts: HashMap<Address, Option<PartialAccount>>,
+	storage: HashMap<(Address, H256), H256>,
+}
+
+impl PartialState {
+	/// Create an empty partial state.
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Add an account, or `None` if it is known not to exist.
+	pub fn insert_account(&mut self, address: Address, account: Option<PartialAccount>) {
+		self.accounts.insert(address, account);
+	}
+
+	/// Add a storage item of an account.
+	pub fn insert_storage(&mut self, address: Address, key: H256, value: H256) {
+		self.storage.insert((address, key), value);
+	}
+
+	/// Get a known account. `Some(None)` means the account is known not to exist.
+	pub fn account(&self, address: &Address) -> Option<Option<&PartialAccount>> {
+		self.accounts.get(address).map(|acc| acc.as_ref())
+	}
+
+	/// Execute a call against the known state, giving the sender enough balance
+	/// to pay for it as `BlockChainClient::call` does.
+	///
+	/// `db` provides the scratch space to execute in. It must have the state and
+	/// account bloom columns; nothing is written to it.
+	pub fn call(&self, db: Arc<Database>, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction) -> Result<PartialCall, CallError> {
+		let sender = t.sender().map_err(|e| {
+			let message = format!("Transaction malformed: {:?}", e);
+			ExecutionError::TransactionMalformed(message)
+		})?;
+
+		let factories = Factories::default();
+		let state_db = StateDB::new(journaldb::new(db, Algorithm::Archive, COL_STATE), SCRATCH_CACHE_SIZE);
+		let mut state = State::from_existing(state_db, SHA3_NULL_RLP, engine.account_start_nonce(), factories.clone())
+			.expect("the empty state root is always present; qed");
+
+		state.populate_from(self.to_pod());
+		state.commit().expect("scratch state only holds nodes it has just created; qed");
+		state.record_accesses();
+
+		let balance = state.balance(&sender);
+		let needed_balance = t.value + t.gas * t.gas_price;
+		if balance < needed_balance {
+			state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty);
+		}
+
+		let options = TransactOptions { tracing: false, vm_tracing: false, struct_logging: None, check_nonce: false };
+		let executed = Executive::new(&mut state, env_info, engine, &factories.vm).transact(t, options);
+
+		let accesses = state.accesses().expect("accesses recorded above; qed");
+		let accounts: Vec<_> = accesses.accounts.into_iter()
+			.filter(|address| !self.accounts.contains_key(address))
+			.collect();
+		let storage: Vec<_> = accesses.storage.into_iter()
+			.filter(|&(ref address, _)| self.account(address).map_or(false, |acc| acc.is_some()))
+			.filter(|item| !self.storage.contains_key(item))
+			.collect();
+
+		// results based on unknown state are meaningless, even errors.
+		if !accounts.is_empty() || !storage.is_empty() {
+			return Ok(PartialCall::Missing {
+				accounts: accounts,
+				storage: storage,
+			});
+		}
+
+		Ok(PartialCall::Executed(executed?))
+	}
+
+	fn to_pod(&self) -> PodState {
+		let mut pod = BTreeMap::new();
+		for (address, account) in &self.accounts {
+			if let Some(ref account) = *account {
+				pod.insert(address.clone(), PodAccount {
+					balance: account.balance,
+					nonce: account.nonce,
+					code: Some(account.code.clone()),
+					storage: BTreeMap::new(),
+				});
+			}
+		}
+
+		for (&(ref address, ref key), value) in &self.storage {
+			if let Some(account) = pod.get_mut(address) {
+				account.storage.insert(key.clone(), value.clone());
+			}
+		}
+
+		PodState::from(pod)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use devtools::RandomTempPath;
+	use rustc_serialize::hex::FromHex;
+	use env_info::EnvInfo;
+	use spec::Spec;
+	use transaction::{Transaction, Action};
+	use util::{Address, H256, U256, Database, DatabaseConfig};
+	use super::*;
+
+	#[test]
+	fn reports_missing_state_until_known() {
+		let path = RandomTempPath::create_dir();
+		let db = Arc::new(Database::open(&DatabaseConfig::with_columns(::db::NUM_COLUMNS), path.as_str()).unwrap());
+		let spec = Spec::new_test();
+		let env_info = EnvInfo::default();
+
+		let (sender, contract) = (Address::from(1), Address::from(2));
+		let t = Transaction {
+			nonce: 0.into(),
+			action: Action::Call(contract),
+			gas: 100_000.into(),
+			gas_price: 0.into(),
+			value: 0.into(),
+			data: Vec::new(),
+		}.fake_sign(sender);
+
+		// the block author is looked up to pay fees to.
+		let mut partial = PartialState::new();
+		partial.insert_account(env_info.author, None);
+		let call = |partial: &PartialState| partial.call(db.clone(), &env_info, &*spec.engine, &t).unwrap();
+
+		match call(&partial) {
+			PartialCall::Missing { accounts, storage } => {
+				let mut accounts = accounts;
+				accounts.sort();
+				assert_eq!(accounts, vec![sender, contract]);
+				assert!(storage.is_empty());
+			}
+			other => panic!("unexpected call outcome {:?}", other),
+		}
+
+		// the contract returns storage item 0.
+		partial.insert_account(sender, None);
+		partial.insert_account(contract, Some(PartialAccount {
+			nonce: 0.into(),
+			balance: 0.into(),
+			code: "60005460005260206000f3".from_hex().unwrap(),
+		}));
+		assert_eq!(call(&partial), PartialCall::Missing { accounts: Vec::new(), storage: vec![(contract, H256::default())] });
+
+		partial.insert_storage(contract, H256::default(), H256::from(U256::from(42)));
+		match call(&partial) {
+			PartialCall::Executed(executed) => assert_eq!(executed.output, H256::from(U256::from(42)).to_vec()),
+			other => panic!("unexpected call outcome {:?}", other),
+		}
+	}
+}
@@ -31,12 +31,13 @@ use db::{NUM_COLUMNS, COL_STATE};
 use header::{Header as BlockHeader, BlockNumber};
 use filter::Filter;
 use log_entry::LocalizedLogEntry;
-use receipt::{Receipt, LocalizedReceipt};
+use receipt::{Receipt, LocalizedReceipt, TransactionOutcome};
 use blockchain::extras::BlockReceipts;
 use error::{ImportResult};
 use evm::{Factory as EvmFactory, VMType, Schedule};
 use miner::{Miner, MinerService, TransactionImportResult};
 use spec::Spec;
+use engines::EquivocationProof;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
 
@@ -94,6 +95,10 @@ pub struct TestBlockChainClient {
 	pub traces: RwLock<Option<Vec<LocalizedTrace>>>,
 	/// Pruning history size to report.
 	pub history: RwLock<Option<u64>>,
+	/// Equivocation proofs to report.
+	pub equivocation_proofs: RwLock<Vec<EquivocationProof>>,
+	/// State trie nodes served by `state_data`.
+	pub state_nodes: RwLock<HashMap<H256, Bytes>>,
 }
 
 /// Used for generating test client blocks.
@@ -157,6 +162,8 @@ impl TestBlockChainClient {
 			first_block: RwLock::new(None),
 			traces: RwLock::new(None),
 			history: RwLock::new(None),
+			equivocation_proofs: RwLock::new(Vec::new()),
+			state_nodes: RwLock::new(HashMap::new()),
 		};
 		client.add_blocks(1, EachBlockWith::Nothing); // add genesis block
 		client.genesis_hash = client.last_hash.read().clone();
@@ -340,6 +347,10 @@ impl MiningBlockChainClient for TestBlockChainClient {
 		Schedule::new_post_eip150(24576, true, true, true)
 	}
 
+	fn equivocation_proofs(&self) -> Vec<EquivocationProof> {
+		self.equivocation_proofs.read().clone()
+	}
+
 	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
 		let engine = &*self.spec.engine;
 		let genesis_header = self.spec.genesis_header();
@@ -393,6 +404,10 @@ impl BlockChainClient for TestBlockChainClient {
 		self.execution_result.read().clone().unwrap()
 	}
 
+	fn replay_block_transactions(&self, _block: BlockId, _analytics: CallAnalytics) -> Result<Vec<Executed>, CallError> {
+		self.execution_result.read().clone().unwrap().map(|executed| vec![executed])
+	}
+
 	fn block_total_difficulty(&self, _id: BlockId) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -569,6 +584,9 @@ impl BlockChainClient for TestBlockChainClient {
 
 	// TODO: returns just hashes instead of node state rlp(?)
 	fn state_data(&self, hash: &H256) -> Option<Bytes> {
+		if let Some(node) = self.state_nodes.read().get(hash) {
+			return Some(node.clone());
+		}
 		// starts with 'f' ?
 		if *hash > H256::from("f000000000000000000000000000000000000000000000000000000000000000") {
 			let mut rlp = RlpStream::new();
@@ -582,7 +600,7 @@ impl BlockChainClient for TestBlockChainClient {
 		// starts with 'f' ?
 		if *hash > H256::from("f000000000000000000000000000000000000000000000000000000000000000") {
 			let receipt = BlockReceipts::new(vec![Receipt::new(
-				H256::zero(),
+				TransactionOutcome::StateRoot(H256::zero()),
 				U256::zero(),
 				vec![])]);
 			let mut rlp = RlpStream::new();
@@ -721,7 +739,7 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
-	fn call_contract(&self, _address: Address, _data: Bytes) -> Result<Bytes, String> { Ok(vec![]) }
+	fn call_contract(&self, _id: BlockId, _address: Address, _data: Bytes) -> Result<Bytes, String> { Ok(vec![]) }
 
 	fn registrar_address(&self) -> Option<Address> { None }
 
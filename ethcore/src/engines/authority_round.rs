@@ -37,6 +37,9 @@ use service::ClientIoMessage;
 use transaction::SignedTransaction;
 use env_info::EnvInfo;
 use builtin::Builtin;
+use client::{Client, BlockId};
+use super::validator_set::{ValidatorSet, new_validator_set};
+use super::block_reward::{self, BlockRewardContract};
 
 /// `AuthorityRound` params.
 #[derive(Debug, PartialEq)]
@@ -46,11 +49,13 @@ pub struct AuthorityRoundParams {
 	/// Time to wait before next block or authority switching.
 	pub step_duration: Duration,
 	/// Valid authorities.
-	pub authorities: Vec<Address>,
-	/// Number of authorities.
-	pub authority_n: usize,
+	pub validators: ethjson::spec::ValidatorSet,
 	/// Starting step,
 	pub start_step: Option<u64>,
+	/// Reward per block.
+	pub block_reward: U256,
+	/// Contract deciding on block rewards instead of `block_reward`.
+	pub block_reward_contract: Option<Address>,
 }
 
 impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
@@ -58,9 +63,10 @@ impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 		AuthorityRoundParams {
 			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
 			step_duration: Duration::from_secs(p.step_duration.into()),
-			authority_n: p.authorities.len(),
-			authorities: p.authorities.into_iter().map(Into::into).collect::<Vec<_>>(),
+			validators: p.validators,
 			start_step: p.start_step.map(Into::into),
+			block_reward: p.block_reward.map_or_else(U256::zero, Into::into),
+			block_reward_contract: p.block_reward_contract_address.map(Into::into),
 		}
 	}
 }
@@ -77,6 +83,8 @@ pub struct AuthorityRound {
 	proposed: AtomicBool,
 	account_provider: Mutex<Option<Arc<AccountProvider>>>,
 	password: RwLock<Option<String>>,
+	validators: Box<ValidatorSet>,
+	block_reward_contract: Option<BlockRewardContract>,
 }
 
 fn header_step(header: &Header) -> Result<usize, ::rlp::DecoderError> {
@@ -102,6 +110,8 @@ impl AuthorityRound {
 	pub fn new(params: CommonParams, our_params: AuthorityRoundParams, builtins: BTreeMap<Address, Builtin>) -> Result<Arc<Self>, Error> {
 		let should_timeout = our_params.start_step.is_none();
 		let initial_step = our_params.start_step.unwrap_or_else(|| (unix_now().as_secs() / our_params.step_duration.as_secs())) as usize;
+		let validators = new_validator_set(our_params.validators.clone());
+		let block_reward_contract = our_params.block_reward_contract.map(BlockRewardContract::new);
 		let engine = Arc::new(
 			AuthorityRound {
 				params: params,
@@ -113,6 +123,8 @@ impl AuthorityRound {
 				proposed: AtomicBool::new(false),
 				account_provider: Mutex::new(None),
 				password: RwLock::new(None),
+				validators: validators,
+				block_reward_contract: block_reward_contract,
 			});
 		// Do not initialize timeouts for tests.
 		if should_timeout {
@@ -132,13 +144,32 @@ impl AuthorityRound {
 		}
 	}
 
-	fn step_proposer(&self, step: usize) -> &Address {
-		let p = &self.our_params;
-		p.authorities.get(step % p.authority_n).expect("There are authority_n authorities; taking number modulo authority_n gives number in authority_n range; qed")
+	fn step_proposer(&self, parent: BlockId, step: usize) -> Option<Address> {
+		self.validators.get(parent, step)
 	}
 
-	fn is_step_proposer(&self, step: usize, address: &Address) -> bool {
-		self.step_proposer(step) == address
+	fn is_step_proposer(&self, parent: BlockId, step: usize, address: &Address) -> bool {
+		self.step_proposer(parent, step).map_or(false, |proposer| proposer == *address)
+	}
+
+	/// Check if the signature belongs to the proposer of the given step, according to the validator set
+	/// at the parent state. Fails with `UnavailableValidatorSet` if that set can not be read.
+	fn verify_proposer(&self, header: &Header, step: usize) -> Result<(), Error> {
+		match self.step_proposer(BlockId::Hash(*header.parent_hash()), step) {
+			Some(proposer) => {
+				let proposer_signature = header_signature(header)?;
+				if verify_address(&proposer, &proposer_signature, &header.bare_hash())? {
+					Ok(())
+				} else {
+					trace!(target: "poa", "verify_proposer: invalid seal signature");
+					Err(BlockError::InvalidSeal)?
+				}
+			},
+			None => {
+				trace!(target: "poa", "verify_proposer: validator set unavailable at {}", header.parent_hash());
+				Err(EngineError::UnavailableValidatorSet(*header.parent_hash()))?
+			},
+		}
 	}
 }
 
@@ -199,8 +230,10 @@ impl Engine for AuthorityRound {
 		]
 	}
 
-	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
-		Schedule::new_post_eip150(usize::max_value(), true, true, true)
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_post_eip150(usize::max_value(), true, true, true);
+		self.params.update_schedule(env_info.number, &mut schedule);
+		schedule
 	}
 
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
@@ -216,9 +249,13 @@ impl Engine for AuthorityRound {
 		});
 	}
 
+	/// Apply the block reward on finalisation of the block.
+	fn on_close_block(&self, block: &mut ExecutedBlock) {
+		block_reward::on_close_block(block, self, self.our_params.block_reward, self.block_reward_contract.as_ref());
+	}
+
 	fn is_sealer(&self, author: &Address) -> Option<bool> {
-		let p = &self.our_params;
-		Some(p.authorities.contains(author))
+		self.validators.contains(BlockId::Latest, author)
 	}
 
 	/// Attempt to seal the block internally.
@@ -229,7 +266,7 @@ impl Engine for AuthorityRound {
 		if self.proposed.load(AtomicOrdering::SeqCst) { return Seal::None; }
 		let header = block.header();
 		let step = self.step.load(AtomicOrdering::SeqCst);
-		if self.is_step_proposer(step, header.author()) {
+		if self.is_step_proposer(BlockId::Hash(*header.parent_hash()), step, header.author()) {
 			if let Some(ref ap) = *self.account_provider.lock() {
 				// Account should be permanently unlocked, otherwise sealing will fail.
 				if let Ok(signature) = ap.sign(*header.author(), self.password.read().clone(), header.bare_hash()) {
@@ -266,13 +303,11 @@ impl Engine for AuthorityRound {
 		let header_step = header_step(header)?;
 		// Give one step slack if step is lagging, double vote is still not possible.
 		if header_step <= self.step.load(AtomicOrdering::SeqCst) + 1 {
-			let proposer_signature = header_signature(header)?;
-			let ok_sig = verify_address(self.step_proposer(header_step), &proposer_signature, &header.bare_hash())?;
-			if ok_sig {
-				Ok(())
-			} else {
-				trace!(target: "poa", "verify_block_unordered: invalid seal signature");
-				Err(BlockError::InvalidSeal)?
+			match self.verify_proposer(header, header_step) {
+				// The parent may not be imported yet; family verification checks the proposer again and rejects the block
+				// if the validator set is still unavailable then.
+				Err(Error::Engine(EngineError::UnavailableValidatorSet(_))) => Ok(()),
+				result => result,
 			}
 		} else {
 			trace!(target: "poa", "verify_block_unordered: block from the future");
@@ -298,7 +333,9 @@ impl Engine for AuthorityRound {
 		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
 			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() })));
 		}
-		Ok(())
+
+		// Validator set may change with the parent state, which is imported now.
+		self.verify_proposer(header, step)
 	}
 
 	fn verify_transaction_basic(&self, t: &SignedTransaction, _header: &Header) -> Result<(), Error> {
@@ -334,6 +371,10 @@ impl Engine for AuthorityRound {
 	fn register_account_provider(&self, account_provider: Arc<AccountProvider>) {
 		*self.account_provider.lock() = Some(account_provider);
 	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		self.validators.register_client(client);
+	}
 }
 
 #[cfg(test)]
@@ -347,7 +388,7 @@ mod tests {
 	use tests::helpers::*;
 	use account_provider::AccountProvider;
 	use spec::Spec;
-	use engines::Seal;
+	use engines::{Seal, EngineError};
 
 	#[test]
 	fn has_valid_metadata() {
@@ -448,6 +489,33 @@ mod tests {
 		assert!(engine.verify_block_seal(&header).is_ok());
 	}
 
+	#[test]
+	fn rejects_proposer_if_validator_set_is_unavailable() {
+		let tap = AccountProvider::transient_provider();
+		let addr = tap.insert_account("0".sha3(), "0").unwrap();
+
+		// no client is registered, so the validator contract can not be read.
+		let bytes: &[u8] = include_bytes!("../../res/authority_round_validator_contract.json");
+		let engine = Spec::load(bytes).expect("invalid chain spec").engine;
+
+		let mut parent: Header = Header::default();
+		parent.set_gas_limit(0x2fefd8.into());
+		parent.set_seal(vec![encode(&0usize).to_vec(), encode(&(&*H520::default() as &[u8])).to_vec()]);
+		let mut header: Header = Header::default();
+		header.set_number(1);
+		header.set_gas_limit(0x2fefd8.into());
+		header.set_parent_hash(parent.hash());
+		header.set_author(addr);
+		let signature = tap.sign(addr, Some("0".into()), header.bare_hash()).unwrap();
+		header.set_seal(vec![encode(&1usize).to_vec(), encode(&(&*signature as &[u8])).to_vec()]);
+
+		assert!(engine.verify_block_unordered(&header, None).is_ok());
+		match engine.verify_block_family(&header, &parent, None) {
+			Err(Error::Engine(EngineError::UnavailableValidatorSet(_))) => {},
+			other => panic!("Unexpected verification result: {:?}", other),
+		}
+	}
+
 	#[test]
 	fn rejects_future_block() {
 		let mut header: Header = Header::default();
@@ -16,18 +16,22 @@
 
 //! A blockchain engine that supports a basic, non-BFT proof-of-authority.
 
+use std::sync::Weak;
 use ethkey::{recover, public_to_address};
 use account_provider::AccountProvider;
 use block::*;
 use builtin::Builtin;
 use spec::CommonParams;
-use engines::{Engine, Seal};
+use engines::{Engine, Seal, EngineError};
 use env_info::EnvInfo;
 use error::{BlockError, Error};
 use evm::Schedule;
 use ethjson;
 use header::Header;
 use transaction::SignedTransaction;
+use client::{Client, BlockId};
+use super::validator_set::{ValidatorSet, new_validator_set};
+use super::block_reward::{self, BlockRewardContract};
 
 use util::*;
 
@@ -39,7 +43,11 @@ pub struct BasicAuthorityParams {
 	/// Block duration.
 	pub duration_limit: u64,
 	/// Valid signatories.
-	pub authorities: HashSet<Address>,
+	pub validators: ethjson::spec::ValidatorSet,
+	/// Reward per block.
+	pub block_reward: U256,
+	/// Contract deciding on block rewards instead of `block_reward`.
+	pub block_reward_contract: Option<Address>,
 }
 
 impl From<ethjson::spec::BasicAuthorityParams> for BasicAuthorityParams {
@@ -47,7 +55,9 @@ impl From<ethjson::spec::BasicAuthorityParams> for BasicAuthorityParams {
 		BasicAuthorityParams {
 			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
 			duration_limit: p.duration_limit.into(),
-			authorities: p.authorities.into_iter().map(Into::into).collect::<HashSet<_>>(),
+			validators: p.validators,
+			block_reward: p.block_reward.map_or_else(U256::zero, Into::into),
+			block_reward_contract: p.block_reward_contract_address.map(Into::into),
 		}
 	}
 }
@@ -60,12 +70,16 @@ pub struct BasicAuthority {
 	builtins: BTreeMap<Address, Builtin>,
 	account_provider: Mutex<Option<Arc<AccountProvider>>>,
 	password: RwLock<Option<String>>,
+	validators: Box<ValidatorSet>,
+	block_reward_contract: Option<BlockRewardContract>,
 }
 
 impl BasicAuthority {
 	/// Create a new instance of BasicAuthority engine
 	pub fn new(params: CommonParams, our_params: BasicAuthorityParams, builtins: BTreeMap<Address, Builtin>) -> Self {
 		BasicAuthority {
+			validators: new_validator_set(our_params.validators.clone()),
+			block_reward_contract: our_params.block_reward_contract.map(BlockRewardContract::new),
 			params: params,
 			our_params: our_params,
 			builtins: builtins,
@@ -73,6 +87,24 @@ impl BasicAuthority {
 			password: RwLock::new(None),
 		}
 	}
+
+	/// Check that the block is signed by one of the validators of its parent state.
+	/// Fails with `UnavailableValidatorSet` if that set can not be read.
+	fn verify_signer(&self, header: &Header) -> result::Result<(), Error> {
+		use rlp::{UntrustedRlp, View};
+
+		// check the signature is legit.
+		let sig = UntrustedRlp::new(&header.seal()[0]).as_val::<H520>()?;
+		let signer = public_to_address(&recover(&sig.into(), &header.bare_hash())?);
+		match self.validators.contains(BlockId::Hash(*header.parent_hash()), &signer) {
+			Some(false) => Err(BlockError::InvalidSeal.into()),
+			Some(true) => Ok(()),
+			None => {
+				trace!(target: "basicauthority", "verify_signer: validator set unavailable at {}", header.parent_hash());
+				Err(EngineError::UnavailableValidatorSet(*header.parent_hash()).into())
+			},
+		}
+	}
 }
 
 impl Engine for BasicAuthority {
@@ -87,8 +119,10 @@ impl Engine for BasicAuthority {
 	/// Additional engine-specific information for the user/developer concerning `header`.
 	fn extra_info(&self, _header: &Header) -> BTreeMap<String, String> { map!["signature".to_owned() => "TODO".to_owned()] }
 
-	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
-		Schedule::new_homestead()
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_homestead();
+		self.params.update_schedule(env_info.number, &mut schedule);
+		schedule
 	}
 
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
@@ -104,8 +138,13 @@ impl Engine for BasicAuthority {
 		});
 	}
 
+	/// Apply the block reward on finalisation of the block.
+	fn on_close_block(&self, block: &mut ExecutedBlock) {
+		block_reward::on_close_block(block, self, self.our_params.block_reward, self.block_reward_contract.as_ref());
+	}
+
 	fn is_sealer(&self, author: &Address) -> Option<bool> {
-		Some(self.our_params.authorities.contains(author))
+		self.validators.contains(BlockId::Latest, author)
 	}
 
 	/// Attempt to seal the block internally.
@@ -140,15 +179,12 @@ impl Engine for BasicAuthority {
 	}
 
 	fn verify_block_unordered(&self, header: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
-		use rlp::{UntrustedRlp, View};
-
-		// check the signature is legit.
-		let sig = UntrustedRlp::new(&header.seal()[0]).as_val::<H520>()?;
-		let signer = public_to_address(&recover(&sig.into(), &header.bare_hash())?);
-		if !self.our_params.authorities.contains(&signer) {
-			return Err(BlockError::InvalidSeal)?;
+		match self.verify_signer(header) {
+			// The parent may not be imported yet; family verification checks the signer again and rejects the block
+			// if the validator set is still unavailable then.
+			Err(Error::Engine(EngineError::UnavailableValidatorSet(_))) => Ok(()),
+			result => result,
 		}
-		Ok(())
 	}
 
 	fn verify_block_family(&self, header: &Header, parent: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
@@ -167,7 +203,9 @@ impl Engine for BasicAuthority {
 		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
 			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() })));
 		}
-		Ok(())
+
+		// Validator set may change with the parent state, which is imported now.
+		self.verify_signer(header)
 	}
 
 	fn verify_transaction_basic(&self, t: &SignedTransaction, _header: &Header) -> result::Result<(), Error> {
@@ -186,6 +224,10 @@ impl Engine for BasicAuthority {
 	fn register_account_provider(&self, ap: Arc<AccountProvider>) {
 		*self.account_provider.lock() = Some(ap);
 	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		self.validators.register_client(client);
+	}
 }
 
 #[cfg(test)]
@@ -196,9 +238,12 @@ mod tests {
 	use error::{BlockError, Error};
 	use tests::helpers::*;
 	use account_provider::AccountProvider;
+	use client::{Client, BlockChainClient, MiningBlockChainClient, BlockId};
+	use ethkey::KeyPair;
 	use header::Header;
 	use spec::Spec;
-	use engines::Seal;
+	use transaction::{Transaction, Action, SignedTransaction};
+	use engines::{Seal, EngineError};
 
 	/// Create a new test chain spec with `BasicAuthority` consensus engine.
 	fn new_test_authority() -> Spec {
@@ -206,6 +251,31 @@ mod tests {
 		Spec::load(bytes).expect("invalid chain spec")
 	}
 
+	/// Create a new test chain spec with `BasicAuthority` consensus engine, which reads the validators from
+	/// a contract at 0xc0. Calling the contract with an address replaces the validator.
+	fn new_validator_contract_authority() -> Spec {
+		let bytes: &[u8] = include_bytes!("../../res/basic_authority_validator_contract.json");
+		Spec::load(bytes).expect("invalid chain spec")
+	}
+
+	// Builds a block on top of the best block of `client` and seals it with the signature of `author`.
+	fn sign_block(client: &Client, tap: &AccountProvider, author: Address, transactions: Vec<SignedTransaction>) -> Bytes {
+		let mut open_block = client.prepare_open_block(author, (3141562.into(), 31415620.into()), vec![]);
+		for transaction in transactions {
+			open_block.push_transaction(transaction, None).unwrap();
+		}
+		let locked = open_block.close_and_lock();
+		let signature = tap.sign(author, Some("".into()), locked.header().bare_hash()).unwrap();
+		locked.seal(client.engine(), vec![::rlp::encode(&(&*signature as &[u8])).to_vec()]).unwrap().rlp_bytes()
+	}
+
+	fn import(client: &Client, block: Bytes) -> H256 {
+		let hash = client.import_block(block).unwrap();
+		client.flush_queue();
+		client.import_verified_blocks();
+		hash
+	}
+
 	#[test]
 	fn has_valid_metadata() {
 		let engine = new_test_authority().engine;
@@ -273,6 +343,68 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn rejects_blocks_from_removed_validators() {
+		let tap = AccountProvider::transient_provider();
+		let removed = tap.insert_account("".sha3(), "").unwrap();
+		let added = tap.insert_account("1".sha3(), "").unwrap();
+
+		let client_result = generate_dummy_client_with_spec_and_data(new_validator_contract_authority, 0, 0, &[]);
+		let client = client_result.reference();
+
+		// the first validator hands over to the second one.
+		let change = Transaction {
+			nonce: 0x100000.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Call(Address::from(0xc0)),
+			value: 0.into(),
+			data: H256::from(added).to_vec(),
+		}.sign(KeyPair::from_secret("".sha3()).unwrap().secret(), None);
+		let handover = sign_block(client, &tap, removed, vec![change]);
+		import(client, handover.clone());
+		assert_eq!(client.chain_info().best_block_number, 1);
+		assert_eq!(client.engine().is_sealer(&removed), Some(false));
+		assert_eq!(client.engine().is_sealer(&added), Some(true));
+
+		let from_removed = sign_block(client, &tap, removed, vec![]);
+		let from_added = sign_block(client, &tap, added, vec![]);
+
+		// a fresh node learns about the change only by importing the handover block.
+		let sync_result = generate_dummy_client_with_spec_and_data(new_validator_contract_authority, 0, 0, &[]);
+		let sync_client = sync_result.reference();
+		sync_client.import_block(handover).unwrap();
+		let from_removed_hash = import(sync_client, from_removed);
+		assert_eq!(sync_client.chain_info().best_block_number, 1);
+		assert!(sync_client.block_header(BlockId::Hash(from_removed_hash)).is_none());
+
+		import(sync_client, from_added);
+		assert_eq!(sync_client.chain_info().best_block_number, 2);
+	}
+
+	#[test]
+	fn rejects_signer_if_validator_set_is_unavailable() {
+		let tap = AccountProvider::transient_provider();
+		let signer = tap.insert_account("".sha3(), "").unwrap();
+
+		// no client is registered, so the validator contract can not be read.
+		let engine = new_validator_contract_authority().engine;
+		let mut parent = Header::default();
+		parent.set_gas_limit(0x2fefd8.into());
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_gas_limit(0x2fefd8.into());
+		header.set_parent_hash(parent.hash());
+		let signature = tap.sign(signer, Some("".into()), header.bare_hash()).unwrap();
+		header.set_seal(vec![::rlp::encode(&(&*signature as &[u8])).to_vec()]);
+
+		assert!(engine.verify_block_unordered(&header, None).is_ok());
+		match engine.verify_block_family(&header, &parent, None) {
+			Err(Error::Engine(EngineError::UnavailableValidatorSet(_))) => {},
+			other => panic!("Unexpected verification result: {:?}", other),
+		}
+	}
+
 	#[test]
 	fn seals_internally() {
 		let tap = AccountProvider::transient_provider();
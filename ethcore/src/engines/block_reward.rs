@@ -0,0 +1,218 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Block rewards for authority engines: a fixed amount for the block author
+//! and an optional contract deciding on beneficiaries and amounts.
+
+use ethabi;
+use util::*;
+use block::ExecutedBlock;
+use engines::Engine;
+use state::CleanupMode;
+use trace::{Tracer, ExecutiveTracer, NoopTracer};
+use trace::trace::RewardType;
+
+/// Gas available to the block reward contract.
+const SYSTEM_CALL_GAS: u64 = 50_000_000;
+
+/// Address which is the sender of calls made by the engine itself.
+pub fn system_address() -> Address {
+	"fffffffffffffffffffffffffffffffffffffffe".into()
+}
+
+/// Kind of a benefactor passed to the block reward contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewardKind {
+	/// Author of the block.
+	Author = 0,
+	/// Author of an included uncle.
+	Uncle = 1,
+}
+
+/// Contract deciding on block rewards. It is called by the system address when a block
+/// is closed, with `reward(address[] benefactors, uint16[] kind) returns (address[], uint256[])`.
+pub struct BlockRewardContract {
+	address: Address,
+	contract: ethabi::Contract,
+}
+
+impl BlockRewardContract {
+	/// Create a new reward contract interface for the contract at `address`.
+	pub fn new(address: Address) -> Self {
+		BlockRewardContract {
+			address: address,
+			contract: ethabi::Contract::new(ethabi::Interface::load(b"[{\"constant\":false,\"inputs\":[{\"name\":\"benefactors\",\"type\":\"address[]\"},{\"name\":\"kind\",\"type\":\"uint16[]\"}],\"name\":\"reward\",\"outputs\":[{\"name\":\"\",\"type\":\"address[]\"},{\"name\":\"\",\"type\":\"uint256[]\"}],\"payable\":false,\"type\":\"function\"}]").expect("JSON is autogenerated; qed")),
+		}
+	}
+
+	fn as_string<T: fmt::Debug>(e: T) -> String { format!("{:?}", e) }
+
+	/// Ask the contract for the rewards of the given benefactors, against the state of `block`,
+	/// tracing the call with `tracer`. Returns the list of beneficiaries and the amounts they are to be given.
+	pub fn reward<T: Tracer>(&self, benefactors: &[(Address, RewardKind)], block: &mut ExecutedBlock, engine: &Engine, tracer: &mut T) -> Result<Vec<(Address, U256)>, String> {
+		let call = self.contract.function("reward".into()).map_err(Self::as_string)?;
+		let data = call.encode_call(vec![
+			ethabi::Token::Array(benefactors.iter().map(|&(ref address, _)| ethabi::Token::Address(address.0)).collect()),
+			ethabi::Token::Array(benefactors.iter().map(|&(_, kind)| ethabi::Token::Uint({ let mut r = [0u8; 32]; U256::from(kind as u64).to_big_endian(&mut r); r })).collect()),
+		]).map_err(Self::as_string)?;
+
+		let env_info = block.env_info();
+		let output = block.fields_mut().state.system_call(&env_info, engine, &system_address(), &self.address, SYSTEM_CALL_GAS.into(), data, tracer)?;
+		let output = call.decode_output(output).map_err(Self::as_string)?;
+
+		let mut result = output.into_iter();
+		let addresses = result.next().and_then(|r| r.to_array()).ok_or("Invalid return arity")?
+			.into_iter().map(|a| a.to_address().map(Address::from)).collect::<Option<Vec<_>>>().ok_or("Invalid type returned")?;
+		let values = result.next().and_then(|r| r.to_array()).ok_or("Invalid return arity")?
+			.into_iter().map(|v| v.to_uint().map(|v| U256::from(v.as_ref()))).collect::<Option<Vec<_>>>().ok_or("Invalid type returned")?;
+
+		if addresses.len() != values.len() {
+			return Err(format!("Mismatched number of beneficiaries ({}) and rewards ({})", addresses.len(), values.len()));
+		}
+		Ok(addresses.into_iter().zip(values.into_iter()).collect())
+	}
+}
+
+/// Add the given rewards to the balances of the beneficiaries and commit the state. If tracing
+/// is enabled, the rewards are recorded in `tracer` and its traces stored after the transaction traces.
+pub fn apply_block_rewards(rewards: &[(Address, U256, RewardType)], block: &mut ExecutedBlock, mut tracer: ExecutiveTracer) {
+	let tracing_enabled = block.tracing_enabled();
+	let fields = block.fields_mut();
+
+	for &(ref beneficiary, ref value, ref reward_type) in rewards {
+		fields.state.add_balance(beneficiary, value, CleanupMode::NoEmpty);
+
+		if tracing_enabled {
+			tracer.trace_reward(beneficiary.clone(), value.clone(), reward_type.clone());
+		}
+	}
+
+	if let Some(ref mut traces) = *fields.traces {
+		traces.push(tracer.traces());
+	}
+
+	// Commit state so that we can actually figure out the state root.
+	if let Err(e) = fields.state.commit() {
+		warn!("Encountered error on state commit: {}", e);
+	}
+}
+
+/// Compute and apply the rewards for closing `block`: the rewards decided by `contract`
+/// if one is given, otherwise `block_reward` for the author. If the contract call fails
+/// no rewards are given for the block, which is the same on all nodes. The state is
+/// committed either way, since the contract call may have changed it.
+pub fn on_close_block(block: &mut ExecutedBlock, engine: &Engine, block_reward: U256, contract: Option<&BlockRewardContract>) {
+	let author = *block.fields().header.author();
+	let mut tracer = ExecutiveTracer::default();
+	let rewards = match contract {
+		Some(contract) => {
+			let benefactors = [(author, RewardKind::Author)];
+			let result = match block.tracing_enabled() {
+				true => contract.reward(&benefactors, block, engine, &mut tracer),
+				false => contract.reward(&benefactors, block, engine, &mut NoopTracer),
+			};
+			match result {
+				Ok(rewards) => rewards.into_iter().map(|(address, value)| (address, value, RewardType::External)).collect(),
+				Err(e) => {
+					warn!(target: "engine", "Block reward contract call failed: {}", e);
+					Vec::new()
+				},
+			}
+		},
+		None if block_reward.is_zero() => Vec::new(),
+		None => vec![(author, block_reward, RewardType::Block)],
+	};
+	apply_block_rewards(&rewards, block, tracer);
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use block::*;
+	use tests::helpers::*;
+	use trace::trace::{Action, Reward, RewardType};
+	use spec::Spec;
+	use trace::ExecutiveTracer;
+	use super::{apply_block_rewards, on_close_block, BlockRewardContract};
+
+	fn open_block<'a>(spec: &'a Spec, author: Address) -> OpenBlock<'a> {
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db().take(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		OpenBlock::new(&*spec.engine, Default::default(), true, db, &genesis_header, last_hashes, author, (3141562.into(), 31415620.into()), vec![]).unwrap()
+	}
+
+	#[test]
+	fn applies_and_traces_rewards() {
+		let spec = Spec::new_test_round();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db().take(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let mut b = OpenBlock::new(engine, Default::default(), true, db, &genesis_header, last_hashes, Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+
+		let beneficiary = Address::from(1);
+		apply_block_rewards(&[(beneficiary, 10.into(), RewardType::External)], b.block_mut(), ExecutiveTracer::default());
+		assert_eq!(b.block_mut().fields().state.balance(&beneficiary), 10.into());
+		assert_eq!(b.block_mut().fields().traces.as_ref().map(|t| t.len()), Some(1));
+	}
+
+	#[test]
+	fn engine_gives_fixed_block_reward() {
+		let spec = Spec::load(include_bytes!("../../res/basic_authority_block_reward.json") as &[u8]).unwrap();
+		let author = Address::from(0xaa);
+		let b = open_block(&spec, author).close_and_lock();
+
+		assert_eq!(b.state().balance(&author), 0x0d.into());
+		let traces = b.traces().as_ref().unwrap().last().unwrap();
+		assert_eq!(traces.len(), 1);
+		assert_eq!(traces[0].action, Action::Reward(Reward { author: author, value: 0x0d.into(), reward_type: RewardType::Block }));
+	}
+
+	#[test]
+	fn engine_gives_rewards_decided_by_contract() {
+		let spec = Spec::load(include_bytes!("../../res/basic_authority_reward_contract.json") as &[u8]).unwrap();
+		let author = Address::from(0xaa);
+		let beneficiary = Address::from(0x0a);
+		let b = open_block(&spec, author).close_and_lock();
+
+		// the contract takes precedence over the fixed reward.
+		assert_eq!(b.state().balance(&author), 0.into());
+		assert_eq!(b.state().balance(&beneficiary), 5.into());
+		assert_eq!(b.state().storage_at(&Address::from(0xb0), &H256::zero()), H256::from(1));
+
+		// the system call is traced before the reward.
+		let traces = b.traces().as_ref().unwrap().last().unwrap();
+		assert_eq!(traces.len(), 2);
+		match traces[0].action {
+			Action::Call(ref call) => assert_eq!(call.to, Address::from(0xb0)),
+			ref other => panic!("Expected the system call trace, got {:?}", other),
+		}
+		assert_eq!(traces[1].action, Action::Reward(Reward { author: beneficiary, value: 5.into(), reward_type: RewardType::External }));
+	}
+
+	#[test]
+	fn commits_contract_changes_without_rewards() {
+		let spec = Spec::load(include_bytes!("../../res/basic_authority_reward_contract.json") as &[u8]).unwrap();
+		let mut b = open_block(&spec, Address::from(0xaa));
+		let genesis_root = *spec.genesis_header().state_root();
+
+		// this contract only writes to its storage and decides on no rewards.
+		on_close_block(b.block_mut(), &*spec.engine, 0x0d.into(), Some(&BlockRewardContract::new(Address::from(0xb1))));
+		assert!(*b.block_mut().fields().state.root() != genesis_root);
+	}
+}
@@ -21,6 +21,8 @@ mod instant_seal;
 mod basic_authority;
 mod authority_round;
 mod tendermint;
+mod validator_set;
+mod block_reward;
 
 pub use self::null_engine::NullEngine;
 pub use self::instant_seal::InstantSeal;
@@ -28,6 +30,7 @@ pub use self::basic_authority::BasicAuthority;
 pub use self::authority_round::AuthorityRound;
 pub use self::tendermint::Tendermint;
 
+use std::sync::Weak;
 use util::*;
 use account_provider::AccountProvider;
 use block::ExecutedBlock;
@@ -38,11 +41,12 @@ use spec::CommonParams;
 use evm::Schedule;
 use io::IoChannel;
 use service::ClientIoMessage;
-use header::Header;
+use header::{Header, BlockNumber};
 use transaction::SignedTransaction;
 use ethereum::ethash;
 use blockchain::extras::BlockDetails;
 use views::HeaderView;
+use client::Client;
 
 /// Voting errors.
 #[derive(Debug)]
@@ -57,6 +61,8 @@ pub enum EngineError {
 	UnexpectedMessage,
 	/// Seal field has an unexpected size.
 	BadSealFieldSize(OutOfBounds<usize>),
+	/// Validator set at the given parent block could not be read.
+	UnavailableValidatorSet(H256),
 }
 
 impl fmt::Display for EngineError {
@@ -68,12 +74,25 @@ impl fmt::Display for EngineError {
 			NotAuthorized(ref address) => format!("Signer {} is not authorized.", address),
 			UnexpectedMessage => "This Engine should not be fed messages.".into(),
 			BadSealFieldSize(ref oob) => format!("Seal field has an unexpected length: {}", oob),
+			UnavailableValidatorSet(ref parent) => format!("Validator set at {} is unavailable.", parent),
 		};
 
 		f.write_fmt(format_args!("Engine error ({})", msg))
 	}
 }
 
+/// Proof that an authority signed two conflicting consensus messages.
+/// Both messages are in the engine's own signed encoding, so the proof can be checked by anyone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquivocationProof {
+	/// Author of both messages.
+	pub author: Address,
+	/// First message received.
+	pub first: Bytes,
+	/// Message conflicting with the first one.
+	pub second: Bytes,
+}
+
 /// Seal type.
 #[derive(Debug, PartialEq, Eq)]
 pub enum Seal {
@@ -182,17 +201,12 @@ pub trait Engine : Sync + Send {
 
 	// TODO: builtin contract routing - to do this properly, it will require removing the built-in configuration-reading logic
 	// from Spec into here and removing the Spec::builtins field.
-	/// Determine whether a particular address is a builtin contract.
-	fn is_builtin(&self, a: &Address) -> bool { self.builtins().contains_key(a) }
-	/// Determine the code execution cost of the builtin contract with address `a`.
-	/// Panics if `is_builtin(a)` is not true.
-	fn cost_of_builtin(&self, a: &Address, input: &[u8]) -> U256 {
-		self.builtins().get(a).expect("queried cost of nonexistent builtin").cost(input.len())
-	}
-	/// Execution the builtin contract `a` on `input` and return `output`.
-	/// Panics if `is_builtin(a)` is not true.
-	fn execute_builtin(&self, a: &Address, input: &[u8], output: &mut BytesRef) {
-		self.builtins().get(a).expect("attempted to execute nonexistent builtin").execute(input, output);
+	/// Attempt to get a handle to a built-in contract.
+	/// Only returns references to activated built-ins.
+	fn builtin(&self, a: &Address, block_number: BlockNumber) -> Option<&Builtin> {
+		self.builtins()
+			.get(a)
+			.and_then(|b| if b.is_active(block_number) { Some(b) } else { None })
 	}
 
 	/// Check if new block should be chosen as the one  in chain.
@@ -215,6 +229,13 @@ pub trait Engine : Sync + Send {
 
 	/// Add an account provider useful for Engines that sign stuff.
 	fn register_account_provider(&self, _account_provider: Arc<AccountProvider>) {}
+
+	/// Add a client which can be used for querying the chain state.
+	fn register_client(&self, _client: Weak<Client>) {}
+
+	/// Proofs of misbehaviour of other authorities observed by this node.
+	fn equivocation_proofs(&self) -> Vec<EquivocationProof> { Vec::new() }
+
 	/// Trigger next step of the consensus engine.
 	fn step(&self) {}
 }
@@ -85,6 +85,11 @@ impl ConsensusMessage {
 		self.height == h && self.round == r && self.step == s && self.block_hash == block_hash
 	}
 
+	/// Check if the message was issued at a later consensus position than the given step.
+	pub fn is_after(&self, h: Height, r: Round, s: Step) -> bool {
+		(self.height, self.round, self.step.number()) > (h, r, s.number())
+	}
+
 	pub fn is_aligned(&self, m: &ConsensusMessage) -> bool {
 		self.is_block_hash(m.height, m.round, m.step, m.block_hash)
 	}
@@ -26,7 +26,9 @@ mod message;
 mod transition;
 mod params;
 mod vote_collector;
+mod persistence;
 
+use std::sync::Weak;
 use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use util::*;
 use error::{Error, BlockError};
@@ -39,16 +41,19 @@ use ethkey::{recover, public_to_address};
 use account_provider::AccountProvider;
 use block::*;
 use spec::CommonParams;
-use engines::{Engine, Seal, EngineError};
+use engines::{Engine, Seal, EngineError, EquivocationProof};
 use blockchain::extras::BlockDetails;
 use views::HeaderView;
 use evm::Schedule;
 use io::{IoService, IoChannel};
 use service::ClientIoMessage;
+use client::{Client, BlockId};
+use super::validator_set::{ValidatorSet, new_validator_set};
 use self::message::*;
 use self::transition::TransitionHandler;
 use self::params::TendermintParams;
 use self::vote_collector::VoteCollector;
+use self::persistence::{PersistedState, STATE_KEY};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Step {
@@ -99,6 +104,12 @@ pub struct Tendermint {
 	last_lock: AtomicUsize,
 	/// Bare hash of the proposed block, used for seal submission.
 	proposal: RwLock<Option<H256>>,
+	/// Set used to determine the current validators.
+	validators: Box<ValidatorSet>,
+	/// Last message signed by this node, used to avoid double signing.
+	last_signed: RwLock<Option<ConsensusMessage>>,
+	/// Client used to persist the consensus state.
+	client: RwLock<Option<Weak<Client>>>,
 }
 
 impl Tendermint {
@@ -106,6 +117,7 @@ impl Tendermint {
 	pub fn new(params: CommonParams, our_params: TendermintParams, builtins: BTreeMap<Address, Builtin>) -> Result<Arc<Self>, Error> {
 		let engine = Arc::new(
 			Tendermint {
+				validators: new_validator_set(our_params.validators.clone()),
 				params: params,
 				our_params: our_params,
 				builtins: builtins,
@@ -121,6 +133,8 @@ impl Tendermint {
 				lock_change: RwLock::new(None),
 				last_lock: AtomicUsize::new(0),
 				proposal: RwLock::new(None),
+				last_signed: RwLock::new(None),
+				client: RwLock::new(None),
 			});
 		let handler = TransitionHandler { engine: Arc::downgrade(&engine) };
 		engine.step_service.register_handler(Arc::new(handler))?;
@@ -162,12 +176,26 @@ impl Tendermint {
 			let h = self.height.load(AtomicOrdering::SeqCst);
 			let r = self.round.load(AtomicOrdering::SeqCst);
 			let s = self.step.read();
+			// hold the lock until the vote is persisted, so that no conflicting vote can slip in.
+			let mut last_signed = self.last_signed.write();
+			if let Some(ref last) = *last_signed {
+				if last.is_after(h, r, *s) || (last.is_step(h, r, *s) && last.block_hash != block_hash) {
+					warn!(target: "poa", "Refusing to sign a vote conflicting with previously signed {:?}.", last);
+					return None;
+				}
+			}
 			let vote_info = message_info_rlp(h, r, *s, block_hash);
 			let authority = self.authority.read();
 			match ap.sign(*authority, self.password.read().clone(), vote_info.sha3()).map(Into::into) {
 				Ok(signature) => {
 					let message_rlp = message_full_rlp(&signature, &vote_info);
 					let message = ConsensusMessage::new(signature, h, r, *s, block_hash);
+					*last_signed = Some(message.clone());
+					if let Err(e) = self.write_state(&*last_signed) {
+						warn!(target: "poa", "Not releasing {:?}, it could not be persisted: {}", message, e);
+						return None;
+					}
+					drop(last_signed);
 					self.votes.vote(message.clone(), *authority);
 					debug!(target: "poa", "Generated {:?} as {}.", message, *authority);
 					self.handle_valid_message(&message);
@@ -205,6 +233,53 @@ impl Tendermint {
 		self.height.store(new_height, AtomicOrdering::SeqCst);
 		self.round.store(0, AtomicOrdering::SeqCst);
 		*self.lock_change.write() = None;
+		self.persist_state();
+	}
+
+	/// Save the state required to resume consensus safely after a restart.
+	fn persist_state(&self) {
+		let last_signed = self.last_signed.read();
+		if let Err(e) = self.write_state(&*last_signed) {
+			warn!(target: "poa", "Could not persist consensus state: {}", e);
+		}
+	}
+
+	// write the consensus state with the given last signed message. the caller
+	// must hold the `last_signed` lock.
+	fn write_state(&self, last_signed: &Option<ConsensusMessage>) -> Result<(), String> {
+		let client = match self.client.read().as_ref().and_then(Weak::upgrade) {
+			Some(client) => client,
+			None => return Ok(()),
+		};
+		let state = PersistedState {
+			height: self.height.load(AtomicOrdering::SeqCst),
+			round: self.round.load(AtomicOrdering::SeqCst),
+			last_lock: self.last_lock.load(AtomicOrdering::SeqCst),
+			lock_change: self.lock_change.read().clone(),
+			last_signed: last_signed.clone(),
+		};
+		client.set_engine_data(STATE_KEY, &::rlp::encode(&state))
+	}
+
+	/// Restore the persisted state if it is not behind the current one.
+	fn restore_state(&self, client: &Client) {
+		let state: PersistedState = match client.engine_data(STATE_KEY).map(|raw| UntrustedRlp::new(&raw).as_val()) {
+			Some(Ok(state)) => state,
+			Some(Err(e)) => {
+				warn!(target: "poa", "Could not decode persisted consensus state: {}", e);
+				return;
+			},
+			None => return,
+		};
+		if state.height < self.height.load(AtomicOrdering::SeqCst) {
+			return;
+		}
+		debug!(target: "poa", "Restoring consensus state at height {}, round {}.", state.height, state.round);
+		self.height.store(state.height, AtomicOrdering::SeqCst);
+		self.round.store(state.round, AtomicOrdering::SeqCst);
+		self.last_lock.store(state.last_lock, AtomicOrdering::SeqCst);
+		*self.lock_change.write() = state.lock_change;
+		*self.last_signed.write() = state.last_signed;
 	}
 
 	/// Use via step_service to transition steps.
@@ -263,30 +338,87 @@ impl Tendermint {
 		}
 	}
 
+	/// Check if address is a validator for the height currently being voted on.
 	fn is_authority(&self, address: &Address) -> bool {
-		self.our_params.authorities.contains(address)
+		self.validators.contains(BlockId::Latest, address).unwrap_or(false)
+	}
+
+	/// Check if `n` votes are enough to be above the threshold of validators at `parent`.
+	fn is_above_threshold_at(&self, parent: BlockId, n: usize) -> bool {
+		self.validators.count(parent).map_or(false, |validator_n| n > validator_n * 2/3)
 	}
 
 	fn is_above_threshold(&self, n: usize) -> bool {
-		n > self.our_params.authority_n * 2/3
+		self.is_above_threshold_at(BlockId::Latest, n)
 	}
 
-	/// Check if address is a proposer for given round.
-	fn is_round_proposer(&self, height: Height, round: Round, address: &Address) -> Result<(), EngineError> {
-		let ref p = self.our_params;
+	/// Check if address is a proposer for given round, with validators taken from `parent`.
+	fn is_round_proposer(&self, parent: BlockId, height: Height, round: Round, address: &Address) -> Result<(), EngineError> {
 		let proposer_nonce = height + round;
 		trace!(target: "poa", "is_proposer: Proposer nonce: {}", proposer_nonce);
-		let proposer = p.authorities.get(proposer_nonce % p.authority_n).expect("There are authority_n authorities; taking number modulo authority_n gives number in authority_n range; qed");
-		if proposer == address {
-			Ok(())
-		} else {
-			Err(EngineError::NotProposer(Mismatch { expected: proposer.clone(), found: address.clone() }))
+		match self.validators.get(parent, proposer_nonce) {
+			Some(ref proposer) if proposer == address => Ok(()),
+			Some(proposer) => Err(EngineError::NotProposer(Mismatch { expected: proposer, found: address.clone() })),
+			None => Err(EngineError::NotAuthorized(address.clone())),
 		}
 	}
 
 	/// Check if address is the current proposer.
 	fn is_proposer(&self, address: &Address) -> Result<(), EngineError> {
-		self.is_round_proposer(self.height.load(AtomicOrdering::SeqCst), self.round.load(AtomicOrdering::SeqCst), address)
+		self.is_round_proposer(BlockId::Latest, self.height.load(AtomicOrdering::SeqCst), self.round.load(AtomicOrdering::SeqCst), address)
+	}
+
+	/// Check the proposal and commit signatures against validators of the parent state.
+	/// Passes if that set is not available; it is then checked again during family verification.
+	fn verify_seal_signatures(&self, header: &Header) -> Result<(), Error> {
+		let parent = BlockId::Hash(*header.parent_hash());
+		if self.validators.count(parent).is_none() {
+			trace!(target: "poa", "verify_seal_signatures: validator set unavailable at {}", header.parent_hash());
+			return Ok(());
+		}
+
+		let proposal = ConsensusMessage::new_proposal(header)?;
+		let proposer = proposal.verify()?;
+		if self.validators.contains(parent, &proposer) != Some(true) {
+			Err(EngineError::NotAuthorized(proposer))?
+		}
+
+		let precommit_hash = proposal.precommit_hash();
+		let ref signatures_field = header.seal()[2];
+		let mut signature_count = 0;
+		let mut origins = HashSet::new();
+		for rlp in UntrustedRlp::new(signatures_field).iter() {
+			let precommit: ConsensusMessage = ConsensusMessage::new_commit(&proposal, rlp.as_val()?);
+			let address = match self.votes.get(&precommit) {
+				Some(a) => a,
+				None => public_to_address(&recover(&precommit.signature.into(), &precommit_hash)?),
+			};
+			if self.validators.contains(parent, &address) != Some(true) {
+				Err(EngineError::NotAuthorized(address.to_owned()))?
+			}
+
+			if origins.insert(address) {
+				signature_count += 1;
+			} else {
+				warn!(target: "poa", "verify_seal_signatures: Duplicate signature from {} on the seal.", address);
+				Err(BlockError::InvalidSeal)?;
+			}
+		}
+
+		// Check if its a proposal if there is not enough precommits.
+		if !self.is_above_threshold_at(parent, signature_count) {
+			let signatures_len = signatures_field.len();
+			// Proposal has to have an empty signature list.
+			if signatures_len != 1 {
+				Err(EngineError::BadSealFieldSize(OutOfBounds {
+					min: Some(1),
+					max: Some(1),
+					found: signatures_len
+				}))?;
+			}
+			self.is_round_proposer(parent, proposal.height, proposal.round, &proposer)?;
+		}
+		Ok(())
 	}
 
 	fn is_height(&self, message: &ConsensusMessage) -> bool {
@@ -300,6 +432,7 @@ impl Tendermint {
 	fn increment_round(&self, n: Round) {
 		trace!(target: "poa", "increment_round: New round.");
 		self.round.fetch_add(n, AtomicOrdering::SeqCst);
+		self.persist_state();
 	}
 
 	fn should_unlock(&self, lock_change_round: Round) -> bool { 
@@ -339,6 +472,7 @@ impl Tendermint {
 		if lock_change {
 			trace!(target: "poa", "handle_valid_message: Lock change.");
 			*self.lock_change.write()	= Some(message.clone());
+			self.persist_state();
 		}
 		// Check if it can affect the step transition.
 		if self.is_height(message) {
@@ -396,8 +530,10 @@ impl Engine for Tendermint {
 		]
 	}
 
-	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
-		Schedule::new_post_eip150(usize::max_value(), true, true, true)
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_post_eip150(usize::max_value(), true, true, true);
+		self.params.update_schedule(env_info.number, &mut schedule);
+		schedule
 	}
 
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
@@ -415,7 +551,7 @@ impl Engine for Tendermint {
 
 	/// Should this node participate.
 	fn is_sealer(&self, address: &Address) -> Option<bool> {
-		Some(self.is_authority(address))
+		self.validators.contains(BlockId::Latest, address)
 	}
 
 	/// Attempt to seal generate a proposal seal.
@@ -491,48 +627,7 @@ impl Engine for Tendermint {
 	}
 
 	fn verify_block_unordered(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
-		let proposal = ConsensusMessage::new_proposal(header)?;	
-		let proposer = proposal.verify()?;
-		if !self.is_authority(&proposer) {
-			Err(EngineError::NotAuthorized(proposer))?
-		}
-
-		let precommit_hash = proposal.precommit_hash();
-		let ref signatures_field = header.seal()[2];
-		let mut signature_count = 0;
-		let mut origins = HashSet::new();
-		for rlp in UntrustedRlp::new(signatures_field).iter() {
-			let precommit: ConsensusMessage = ConsensusMessage::new_commit(&proposal, rlp.as_val()?);
-			let address = match self.votes.get(&precommit) {
-				Some(a) => a,
-				None => public_to_address(&recover(&precommit.signature.into(), &precommit_hash)?),
-			};
-			if !self.our_params.authorities.contains(&address) {
-				Err(EngineError::NotAuthorized(address.to_owned()))?
-			}
-
-			if origins.insert(address) {
-				signature_count += 1;
-			} else {
-				warn!(target: "poa", "verify_block_unordered: Duplicate signature from {} on the seal.", address);
-				Err(BlockError::InvalidSeal)?;
-			}
-		}
-
-		// Check if its a proposal if there is not enough precommits.
-		if !self.is_above_threshold(signature_count) {
-			let signatures_len = signatures_field.len();
-			// Proposal has to have an empty signature list.
-			if signatures_len != 1 {
-				Err(EngineError::BadSealFieldSize(OutOfBounds {
-					min: Some(1),
-					max: Some(1),
-					found: signatures_len
-				}))?;
-			}
-			self.is_round_proposer(proposal.height, proposal.round, &proposer)?;
-		}
-		Ok(())
+		self.verify_seal_signatures(header)
 	}
 
 	fn verify_block_family(&self, header: &Header, parent: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
@@ -547,7 +642,8 @@ impl Engine for Tendermint {
 			Err(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() }))?;
 		}
 
-		Ok(())
+		// Validator set may change with the parent state, which is now available.
+		self.verify_seal_signatures(header)
 	}
 
 	fn verify_transaction_basic(&self, t: &SignedTransaction, _header: &Header) -> Result<(), Error> {
@@ -651,6 +747,18 @@ impl Engine for Tendermint {
 	fn register_account_provider(&self, account_provider: Arc<AccountProvider>) {
 		*self.account_provider.lock() = Some(account_provider);
 	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		if let Some(c) = client.upgrade() {
+			self.restore_state(&c);
+		}
+		*self.client.write() = Some(client.clone());
+		self.validators.register_client(client);
+	}
+
+	fn equivocation_proofs(&self) -> Vec<EquivocationProof> {
+		self.votes.equivocation_proofs()
+	}
 }
 
 #[cfg(test)]
@@ -951,4 +1059,30 @@ mod tests {
 		assert!(first ^ second);
 		engine.stop();
 	}
+
+	#[test]
+	fn refuses_conflicting_vote_after_restart() {
+		let (spec, tap) = setup();
+		let client_result = get_test_client_with_blocks(vec![]);
+		let client = client_result.reference();
+		let v0 = insert_and_unlock(&tap, "0");
+
+		let engine = Tendermint::new(spec.params.clone(), TendermintParams::default(), BTreeMap::new()).unwrap();
+		engine.register_account_provider(tap.clone());
+		engine.register_client(Arc::downgrade(client));
+		engine.set_signer(v0, "0".into());
+		*engine.step.write() = Step::Prevote;
+		assert!(engine.generate_message(Some(1.into())).is_some());
+		engine.stop();
+
+		// A fresh engine on the same database remembers the vote.
+		let restarted = Tendermint::new(spec.params.clone(), TendermintParams::default(), BTreeMap::new()).unwrap();
+		restarted.register_account_provider(tap.clone());
+		restarted.register_client(Arc::downgrade(client));
+		restarted.set_signer(v0, "0".into());
+		*restarted.step.write() = Step::Prevote;
+		assert!(restarted.generate_message(Some(2.into())).is_none());
+		assert!(restarted.generate_message(Some(1.into())).is_some());
+		restarted.stop();
+	}
 }
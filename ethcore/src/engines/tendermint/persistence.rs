@@ -0,0 +1,91 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Tendermint consensus state kept in the client database across restarts.
+
+use rlp::*;
+use super::{Height, Round};
+use super::message::ConsensusMessage;
+
+/// Key of the persisted state in the engine data of the client.
+pub const STATE_KEY: &'static [u8] = b"tendermint_state";
+
+/// State needed to resume consensus after a restart without equivocating.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PersistedState {
+	/// Blockchain height.
+	pub height: Height,
+	/// Consensus round.
+	pub round: Round,
+	/// Last lock round.
+	pub last_lock: Round,
+	/// Message for the last PoLC.
+	pub lock_change: Option<ConsensusMessage>,
+	/// Last message signed by this node.
+	pub last_signed: Option<ConsensusMessage>,
+}
+
+impl Encodable for PersistedState {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(5)
+			.append(&self.height)
+			.append(&self.round)
+			.append(&self.last_lock)
+			.append(&self.lock_change)
+			.append(&self.last_signed);
+	}
+}
+
+impl Decodable for PersistedState {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let rlp = decoder.as_rlp();
+		Ok(PersistedState {
+			height: rlp.val_at(0)?,
+			round: rlp.val_at(1)?,
+			last_lock: rlp.val_at(2)?,
+			lock_change: rlp.val_at(3)?,
+			last_signed: rlp.val_at(4)?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use rlp::*;
+	use super::super::Step;
+	use super::super::message::ConsensusMessage;
+	use super::PersistedState;
+
+	#[test]
+	fn encode_decode() {
+		let vote = ConsensusMessage::new(H520::random(), 10, 2, Step::Prevote, Some("1".sha3()));
+		let state = PersistedState {
+			height: 10,
+			round: 3,
+			last_lock: 2,
+			lock_change: Some(vote.clone()),
+			last_signed: Some(ConsensusMessage::new(H520::random(), 10, 3, Step::Precommit, None)),
+		};
+		let decoded: PersistedState = decode(&encode(&state));
+		assert_eq!(decoded, state);
+
+		let empty = PersistedState { height: 1, round: 0, last_lock: 0, lock_change: None, last_signed: None };
+		let decoded: PersistedState = decode(&encode(&empty));
+		assert_eq!(decoded, empty);
+	}
+}
@@ -17,13 +17,19 @@
 //! Collects votes on hashes at each height and round.
 
 use util::*;
+use engines::EquivocationProof;
 use super::message::ConsensusMessage;
 use super::{Height, Round, Step};
 
+/// Maximum number of equivocation proofs kept.
+const MAX_PROOFS: usize = 1024;
+
 #[derive(Debug)]
 pub struct VoteCollector {
 	/// Storing all Proposals, Prevotes and Precommits.
 	votes: RwLock<BTreeMap<ConsensusMessage, Address>>,
+	/// Proofs of authorities voting for different blocks at the same step.
+	proofs: RwLock<VecDeque<EquivocationProof>>,
 }
 
 #[derive(Debug)]
@@ -53,12 +59,36 @@ impl VoteCollector {
 			block_hash: None
 		},
 		Address::default());
-		VoteCollector { votes: RwLock::new(collector) }
+		VoteCollector { votes: RwLock::new(collector), proofs: RwLock::new(VecDeque::new()) }
 	}
 
 	/// Insert vote if it is newer than the oldest one.
+	/// Records a proof if the voter already voted differently at the same step.
 	pub fn vote(&self, message: ConsensusMessage, voter: Address) -> Option<Address> {
-		self.votes.write().insert(message, voter)
+		let mut guard = self.votes.write();
+		let conflicting = guard.iter()
+			.skip_while(|&(m, _)| !m.is_step(message.height, message.round, message.step))
+			.take_while(|&(m, _)| m.is_step(message.height, message.round, message.step))
+			.find(|&(m, a)| *a == voter && m.block_hash != message.block_hash)
+			.map(|(m, _)| m.clone());
+		if let Some(first) = conflicting {
+			warn!(target: "poa", "vote: Authority {} has voted for different blocks at {:?}, this indicates malicious behaviour.", voter, message);
+			let mut proofs = self.proofs.write();
+			if proofs.len() == MAX_PROOFS {
+				proofs.pop_front();
+			}
+			proofs.push_back(EquivocationProof {
+				author: voter,
+				first: ::rlp::encode(&first).to_vec(),
+				second: ::rlp::encode(&message).to_vec(),
+			});
+		}
+		guard.insert(message, voter)
+	}
+
+	/// Proofs of misbehaviour collected so far.
+	pub fn equivocation_proofs(&self) -> Vec<EquivocationProof> {
+		self.proofs.read().iter().cloned().collect()
 	}
 
 	pub fn is_old_or_known(&self, message: &ConsensusMessage) -> bool {
@@ -269,4 +299,24 @@ mod tests {
 		full_vote(&collector, H520::random(), 3, 2, Step::Prevote, Some("1".sha3()), Address::default());
 		assert_eq!(collector.count_step_votes(3, 2, Step::Prevote), 1);
 	}
+
+	#[test]
+	fn equivocation_proofs() {
+		let collector = VoteCollector::new();
+		let author = H160::random();
+		full_vote(&collector, H520::random(), 3, 2, Step::Prevote, Some("0".sha3()), author);
+		// Different voter or different step is fine.
+		random_vote(&collector, H520::random(), 3, 2, Step::Prevote, Some("1".sha3()));
+		full_vote(&collector, H520::random(), 3, 2, Step::Precommit, Some("1".sha3()), author);
+		assert!(collector.equivocation_proofs().is_empty());
+
+		let second = ConsensusMessage { signature: H520::random(), height: 3, round: 2, step: Step::Prevote, block_hash: None };
+		collector.vote(second.clone(), author);
+		let proofs = collector.equivocation_proofs();
+		assert_eq!(proofs.len(), 1);
+		assert_eq!(proofs[0].author, author);
+		assert_eq!(proofs[0].second, ::rlp::encode(&second).to_vec());
+		let first: ConsensusMessage = ::rlp::decode(&proofs[0].first);
+		assert_eq!(first.block_hash, Some("0".sha3()));
+	}
 }
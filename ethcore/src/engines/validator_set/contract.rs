@@ -0,0 +1,151 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Validator set maintained in a contract.
+
+use std::sync::Weak;
+use lru_cache::LruCache;
+use util::*;
+use client::{Client, BlockChainClient, BlockId};
+use super::ValidatorSet;
+
+/// Number of per-block validator lists kept in memory.
+const MEMOIZE_CAPACITY: usize = 500;
+
+/// Validator set taken from the `getValidators` function of a contract.
+/// The list is read from the state of the parent block, so that any change
+/// made by a transaction takes effect from the next block onwards.
+pub struct ValidatorContract {
+	address: Address,
+	provider: RwLock<Option<provider::Contract>>,
+	validators: Mutex<LruCache<H256, Vec<Address>>>,
+}
+
+impl ValidatorContract {
+	/// Create a new validator set reading from the contract at `address`.
+	pub fn new(contract_address: Address) -> Self {
+		ValidatorContract {
+			address: contract_address,
+			provider: RwLock::new(None),
+			validators: Mutex::new(LruCache::new(MEMOIZE_CAPACITY)),
+		}
+	}
+
+	fn fetch(&self, parent: H256) -> Option<Vec<Address>> {
+		match *self.provider.read() {
+			Some(ref provider) => match provider.get_validators(BlockId::Hash(parent)) {
+				Ok(validators) => {
+					trace!(target: "engine", "Validators at {} are {:?}.", parent, validators);
+					Some(validators)
+				},
+				Err(s) => {
+					debug!(target: "engine", "Could not get validators at {}: {}", parent, s);
+					None
+				},
+			},
+			None => {
+				warn!(target: "engine", "Could not get validators: no client registered with the validator set contract.");
+				None
+			},
+		}
+	}
+}
+
+impl ValidatorSet for ValidatorContract {
+	fn validators(&self, parent: BlockId) -> Option<Vec<Address>> {
+		let hash = match parent {
+			BlockId::Hash(hash) => Some(hash),
+			id => self.provider.read().as_ref().and_then(|provider| provider.block_hash(id)),
+		};
+		hash.and_then(|hash| {
+			if let Some(validators) = self.validators.lock().get_mut(&hash) {
+				return Some(validators.clone());
+			}
+			self.fetch(hash).map(|validators| {
+				self.validators.lock().insert(hash, validators.clone());
+				validators
+			})
+		})
+	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		trace!(target: "engine", "Setting up contract caller.");
+		*self.provider.write() = Some(provider::Contract::new(self.address, client));
+	}
+}
+
+mod provider {
+	// Autogenerated from JSON contract definition using Rust contract convertor.
+	#![allow(unused_imports)]
+	use std::sync::Weak;
+	use std::string::String;
+	use std::result::Result;
+	use std::fmt;
+	use {util, ethabi};
+	use util::{FixedHash, Uint};
+	use client::{Client, BlockChainClient, BlockId};
+
+	pub struct Contract {
+		contract: ethabi::Contract,
+		address: util::Address,
+		client: Weak<Client>,
+	}
+
+	impl Contract {
+		pub fn new(address: util::Address, client: Weak<Client>) -> Self {
+			Contract {
+				contract: ethabi::Contract::new(ethabi::Interface::load(b"[{\"constant\":true,\"inputs\":[],\"name\":\"getValidators\",\"outputs\":[{\"name\":\"\",\"type\":\"address[]\"}],\"payable\":false,\"type\":\"function\"}]").expect("JSON is autogenerated; qed")),
+				address: address,
+				client: client,
+			}
+		}
+		fn as_string<T: fmt::Debug>(e: T) -> String { format!("{:?}", e) }
+
+		/// Hash of the block with given id, if known to the client.
+		pub fn block_hash(&self, id: BlockId) -> Option<util::H256> {
+			self.client.upgrade().and_then(|c| c.block_hash(id))
+		}
+
+		/// Auto-generated from: `{"constant":true,"inputs":[],"name":"getValidators","outputs":[{"name":"","type":"address[]"}],"payable":false,"type":"function"}`
+		#[allow(dead_code)]
+		pub fn get_validators(&self, id: BlockId) -> Result<Vec<util::Address>, String> {
+			let call = self.contract.function("getValidators".into()).map_err(Self::as_string)?;
+			let data = call.encode_call(
+				vec![]
+			).map_err(Self::as_string)?;
+			let client = self.client.upgrade().ok_or("No client!")?;
+			let output = call.decode_output(client.call_contract(id, self.address.clone(), data)?).map_err(Self::as_string)?;
+			let mut result = output.into_iter().rev().collect::<Vec<_>>();
+			Ok(({ let r = result.pop().ok_or("Invalid return arity")?; let r = r.to_array().and_then(|v| v.into_iter().map(|a| a.to_address()).collect::<Option<Vec<[u8; 20]>>>()).ok_or("Invalid type returned")?; r.into_iter().map(|a| util::Address::from(a)).collect::<Vec<_>>() }))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use client::BlockId;
+	use super::super::ValidatorSet;
+	use super::ValidatorContract;
+
+	#[test]
+	fn unavailable_without_client() {
+		let vc = ValidatorContract::new(Address::from(1));
+		assert_eq!(vc.validators(BlockId::Hash(H256::from(1))), None);
+		assert_eq!(vc.contains(BlockId::Latest, &Address::from(1)), None);
+	}
+}
@@ -0,0 +1,69 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Validator lists.
+
+mod simple_list;
+mod contract;
+
+use std::sync::Weak;
+use util::Address;
+use ethjson::spec::ValidatorSet as ValidatorSpec;
+use client::{Client, BlockId};
+use self::simple_list::SimpleList;
+use self::contract::ValidatorContract;
+
+/// Creates a validator set from spec.
+pub fn new_validator_set(spec: ValidatorSpec) -> Box<ValidatorSet> {
+	match spec {
+		ValidatorSpec::List(list) => Box::new(SimpleList::new(list.into_iter().map(Into::into).collect())),
+		ValidatorSpec::Contract(address) => Box::new(ValidatorContract::new(address.into())),
+	}
+}
+
+/// A set of authorities eligible to seal blocks.
+///
+/// All queries are made relative to the parent of the block being checked, so that
+/// a change to the set takes effect from the first block built on top of the state
+/// in which it happened. `None` is returned when the state of `parent` is not
+/// available (e.g. the block is not imported yet or during snapshot restoration).
+pub trait ValidatorSet: Send + Sync {
+	/// Validators eligible to seal blocks built on top of `parent`.
+	fn validators(&self, parent: BlockId) -> Option<Vec<Address>>;
+
+	/// Checks if a given address is a validator for blocks built on top of `parent`.
+	fn contains(&self, parent: BlockId, address: &Address) -> Option<bool> {
+		self.validators(parent).map(|validators| validators.contains(address))
+	}
+
+	/// Draws a validator nonce modulo the number of validators.
+	/// `None` if the set is not available or empty.
+	fn get(&self, parent: BlockId, nonce: usize) -> Option<Address> {
+		self.validators(parent).and_then(|validators| match validators.len() {
+			0 => None,
+			n => Some(validators[nonce % n]),
+		})
+	}
+
+	/// Returns the current number of validators.
+	fn count(&self, parent: BlockId) -> Option<usize> {
+		self.validators(parent).map(|validators| validators.len())
+	}
+
+	/// Allows blockchain state access.
+	fn register_client(&self, _client: Weak<Client>) {}
+}
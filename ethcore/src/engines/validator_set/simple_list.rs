@@ -0,0 +1,80 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Preconfigured validator list.
+
+use util::Address;
+use client::BlockId;
+use super::ValidatorSet;
+
+/// Validator set which never changes.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct SimpleList {
+	validators: Vec<Address>,
+}
+
+impl SimpleList {
+	/// Create a new list from given validators.
+	pub fn new(validators: Vec<Address>) -> Self {
+		SimpleList {
+			validators: validators,
+		}
+	}
+}
+
+impl ValidatorSet for SimpleList {
+	fn validators(&self, _parent: BlockId) -> Option<Vec<Address>> {
+		Some(self.validators.clone())
+	}
+
+	fn contains(&self, _parent: BlockId, address: &Address) -> Option<bool> {
+		Some(self.validators.contains(address))
+	}
+
+	fn get(&self, _parent: BlockId, nonce: usize) -> Option<Address> {
+		match self.validators.len() {
+			0 => None,
+			n => Some(self.validators[nonce % n]),
+		}
+	}
+
+	fn count(&self, _parent: BlockId) -> Option<usize> {
+		Some(self.validators.len())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::Address;
+	use client::BlockId;
+	use super::super::ValidatorSet;
+	use super::SimpleList;
+
+	#[test]
+	fn simple_list() {
+		let a1 = Address::from(1);
+		let a2 = Address::from(2);
+		let list = SimpleList::new(vec![a1, a2]);
+		assert_eq!(list.contains(BlockId::Latest, &a1), Some(true));
+		assert_eq!(list.contains(BlockId::Latest, &Address::from(3)), Some(false));
+		assert_eq!(list.get(BlockId::Latest, 0), Some(a1));
+		assert_eq!(list.get(BlockId::Latest, 1), Some(a2));
+		assert_eq!(list.get(BlockId::Latest, 2), Some(a1));
+		assert_eq!(list.count(BlockId::Latest), Some(2));
+		assert_eq!(SimpleList::default().get(BlockId::Latest, 0), None);
+	}
+}
@@ -20,12 +20,14 @@ use block::*;
 use builtin::Builtin;
 use env_info::EnvInfo;
 use error::{BlockError, TransactionError, Error};
-use header::Header;
+use header::{Header, BlockNumber};
 use views::HeaderView;
 use state::CleanupMode;
 use spec::CommonParams;
 use transaction::SignedTransaction;
 use engines::Engine;
+use trace::{Tracer, ExecutiveTracer};
+use trace::trace::RewardType;
 use evm::Schedule;
 use ethjson;
 use rlp::{self, UntrustedRlp, View};
@@ -42,6 +44,8 @@ pub struct EthashParams {
 	pub difficulty_bound_divisor: U256,
 	/// Difficulty increment divisor.
 	pub difficulty_increment_divisor: u64,
+	/// Difficulty increment divisor after EIP-100.
+	pub metropolis_difficulty_increment_divisor: u64,
 	/// Block duration.
 	pub duration_limit: u64,
 	/// Block reward.
@@ -78,6 +82,11 @@ pub struct EthashParams {
 	pub ecip1010_continue_transition: u64,
 	/// Maximum amount of code that can be deploying into a contract.
 	pub max_code_size: u64,
+	/// Number of first block where EIP-100 (uncle-aware difficulty adjustment) begins.
+	pub eip100b_transition: u64,
+	/// Difficulty bomb delays, keyed by the block at which each delay starts.
+	/// Delays are cumulative: the bomb sees the block number minus every delay already in effect.
+	pub difficulty_bomb_delays: BTreeMap<BlockNumber, BlockNumber>,
 }
 
 impl From<ethjson::spec::EthashParams> for EthashParams {
@@ -87,6 +96,7 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 			minimum_difficulty: p.minimum_difficulty.into(),
 			difficulty_bound_divisor: p.difficulty_bound_divisor.into(),
 			difficulty_increment_divisor: p.difficulty_increment_divisor.map_or(10, Into::into),
+			metropolis_difficulty_increment_divisor: p.metropolis_difficulty_increment_divisor.map_or(9, Into::into),
 			duration_limit: p.duration_limit.into(),
 			block_reward: p.block_reward.into(),
 			registrar: p.registrar.map_or_else(Address::new, Into::into),
@@ -105,6 +115,8 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 			ecip1010_pause_transition: p.ecip1010_pause_transition.map_or(u64::max_value(), Into::into),
 			ecip1010_continue_transition: p.ecip1010_continue_transition.map_or(u64::max_value(), Into::into),
 			max_code_size: p.max_code_size.map_or(u64::max_value(), Into::into),
+			eip100b_transition: p.eip100b_transition.map_or(u64::max_value(), Into::into),
+			difficulty_bomb_delays: p.difficulty_bomb_delays.unwrap_or_else(BTreeMap::new).into_iter().map(|(b, d)| (b.into(), d.into())).collect(),
 		}
 	}
 }
@@ -151,7 +163,7 @@ impl Engine for Ethash {
 	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
 		trace!(target: "client", "Creating schedule. fCML={}, bGCML={}", self.ethash_params.homestead_transition, self.ethash_params.eip150_transition);
 
-		if env_info.number < self.ethash_params.homestead_transition {
+		let mut schedule = if env_info.number < self.ethash_params.homestead_transition {
 			Schedule::new_frontier()
 		} else if env_info.number < self.ethash_params.eip150_transition {
 			Schedule::new_homestead()
@@ -162,7 +174,9 @@ impl Engine for Ethash {
 				env_info.number >= self.ethash_params.eip161abc_transition,
 				env_info.number >= self.ethash_params.eip161d_transition
 			)
-		}
+		};
+		self.params.update_schedule(env_info.number, &mut schedule);
+		schedule
 	}
 
 	fn signing_network_id(&self, env_info: &EnvInfo) -> Option<u64> {
@@ -216,15 +230,32 @@ impl Engine for Ethash {
 	/// This assumes that all uncles are valid uncles (i.e. of at least one generation before the current).
 	fn on_close_block(&self, block: &mut ExecutedBlock) {
 		let reward = self.ethash_params.block_reward;
+		let tracing_enabled = block.tracing_enabled();
 		let fields = block.fields_mut();
+		let mut tracer = ExecutiveTracer::default();
 
 		// Bestow block reward
-		fields.state.add_balance(fields.header.author(), &(reward + reward / U256::from(32) * U256::from(fields.uncles.len())), CleanupMode::NoEmpty);
+		let result_block_reward = reward + reward / U256::from(32) * U256::from(fields.uncles.len());
+		fields.state.add_balance(fields.header.author(), &result_block_reward, CleanupMode::NoEmpty);
+
+		if tracing_enabled {
+			tracer.trace_reward(fields.header.author().clone(), result_block_reward, RewardType::Block);
+		}
 
 		// Bestow uncle rewards
 		let current_number = fields.header.number();
 		for u in fields.uncles.iter() {
-			fields.state.add_balance(u.author(), &(reward * U256::from(8 + u.number() - current_number) / U256::from(8)), CleanupMode::NoEmpty);
+			let result_uncle_reward = reward * U256::from(8 + u.number() - current_number) / U256::from(8);
+			fields.state.add_balance(u.author(), &result_uncle_reward, CleanupMode::NoEmpty);
+
+			if tracing_enabled {
+				tracer.trace_reward(u.author().clone(), result_uncle_reward, RewardType::Uncle);
+			}
+		}
+
+		// Store the reward traces after the transaction traces.
+		if let Some(ref mut traces) = *fields.traces {
+			traces.push(tracer.traces());
 		}
 
 		// Commit state so that we can actually figure out the state root.
@@ -365,17 +396,29 @@ impl Ethash {
 		else {
 			trace!(target: "ethash", "Calculating difficulty parent.difficulty={}, header.timestamp={}, parent.timestamp={}", parent.difficulty(), header.timestamp(), parent.timestamp());
 			//block_diff = parent_diff + parent_diff // 2048 * max(1 - (block_timestamp - parent_timestamp) // 10, -99)
-			let diff_inc = (header.timestamp() - parent.timestamp()) / self.ethash_params.difficulty_increment_divisor;
-			if diff_inc <= 1 {
-				parent.difficulty().clone() + parent.difficulty().clone() / From::from(difficulty_bound_divisor) * From::from(1 - diff_inc)
+			//after EIP-100: max((2 if len(parent.uncles) else 1) - (block_timestamp - parent_timestamp) // 9, -99)
+			let (increment_divisor, threshold) = if header.number() < self.ethash_params.eip100b_transition {
+				(self.ethash_params.difficulty_increment_divisor, 1)
+			} else if parent.uncles_hash() == &SHA3_EMPTY_LIST_RLP {
+				(self.ethash_params.metropolis_difficulty_increment_divisor, 1)
 			} else {
-				parent.difficulty().clone() - parent.difficulty().clone() / From::from(difficulty_bound_divisor) * From::from(min(diff_inc - 1, 99))
+				(self.ethash_params.metropolis_difficulty_increment_divisor, 2)
+			};
+			let diff_inc = (header.timestamp() - parent.timestamp()) / increment_divisor;
+			if diff_inc <= threshold {
+				parent.difficulty().clone() + parent.difficulty().clone() / From::from(difficulty_bound_divisor) * From::from(threshold - diff_inc)
+			} else {
+				parent.difficulty().clone() - parent.difficulty().clone() / From::from(difficulty_bound_divisor) * From::from(min(diff_inc - threshold, 99))
 			}
 		};
 		target = max(min_difficulty, target);
 		if header.number() < self.ethash_params.bomb_defuse_transition {
+			// the bomb sees a "fake" block number, pushed back by every delay already in effect.
+			let bomb_number = self.ethash_params.difficulty_bomb_delays.iter()
+				.take_while(|&(block, _)| header.number() >= *block)
+				.fold(header.number(), |number, (_, delay)| number.saturating_sub(*delay));
 			if header.number() < self.ethash_params.ecip1010_pause_transition {
-				let period = ((parent.number() + 1) / EXP_DIFF_PERIOD) as usize;
+				let period = (bomb_number / EXP_DIFF_PERIOD) as usize;
 				if period > 1 {
 					target = max(min_difficulty, target + (U256::from(1) << (period - 2)));
 				}
@@ -385,9 +428,11 @@ impl Ethash {
 				target = max(min_difficulty, target + (U256::from(1) << fixed_difficulty));
 			}
 			else {
-				let period = ((parent.number() + 1) / EXP_DIFF_PERIOD) as usize;
+				let period = (bomb_number / EXP_DIFF_PERIOD) as usize;
 				let delay = ((self.ethash_params.ecip1010_continue_transition - self.ethash_params.ecip1010_pause_transition) / EXP_DIFF_PERIOD) as usize;
-				target = max(min_difficulty, target + (U256::from(1) << (period - delay - 2)));
+				if period > delay + 1 {
+					target = max(min_difficulty, target + (U256::from(1) << (period - delay - 2)));
+				}
 			}
 		}
 		target
@@ -440,6 +485,8 @@ mod tests {
 	use header::Header;
 	use super::super::{new_morden, new_homestead_test};
 	use super::{Ethash, EthashParams};
+	use trace::FlatTrace;
+	use trace::trace::{Action, Res, Reward, RewardType};
 	use rlp;
 
 	#[test]
@@ -474,6 +521,40 @@ mod tests {
 		assert_eq!(b.state().balance(&uncle_author), "3cb71f51fc558000".into());
 	}
 
+	#[test]
+	fn on_close_block_traces_rewards() {
+		let spec = new_morden();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let mut db_result = get_temp_state_db();
+		let db = spec.ensure_db_good(db_result.take(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let mut b = OpenBlock::new(engine, Default::default(), true, db, &genesis_header, last_hashes, Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let mut uncle = Header::new();
+		let uncle_author: Address = "ef2d6d194084c2de36e0dabfce45d046b37d1106".into();
+		uncle.set_author(uncle_author);
+		b.push_uncle(uncle).unwrap();
+
+		let b = b.close();
+		let reward = |author, value: &str, reward_type| FlatTrace {
+			action: Action::Reward(Reward {
+				author: author,
+				value: value.into(),
+				reward_type: reward_type,
+			}),
+			result: Res::None,
+			subtraces: 0,
+			trace_address: Default::default(),
+		};
+		assert_eq!(b.traces(), &Some(vec![vec![
+			reward(Address::zero(), "478eae0e571ba000", RewardType::Block),
+			reward(uncle_author, "3cb71f51fc558000", RewardType::Uncle),
+		]]));
+
+		let b = b.reopen(engine);
+		assert_eq!(b.traces(), &Some(vec![]));
+	}
+
 	#[test]
 	fn has_valid_metadata() {
 		let engine = new_morden().engine;
@@ -777,4 +858,88 @@ mod tests {
 			ethash.calculate_difficulty(&header, &parent_header)
 		);
 	}
+
+	#[test]
+	fn difficulty_eip100b_accounts_for_parent_uncles() {
+		let spec = new_homestead_test();
+		let ethparams = EthashParams {
+			homestead_transition: 0,
+			eip100b_transition: 0,
+			..get_default_ethash_params()
+		};
+		let ethash = Ethash::new(spec.params, ethparams, BTreeMap::new());
+
+		let mut parent_header = Header::default();
+		parent_header.set_number(1000);
+		parent_header.set_difficulty(U256::from(0x20000000));
+		parent_header.set_timestamp(1000);
+		let mut header = Header::default();
+		header.set_number(parent_header.number() + 1);
+
+		header.set_timestamp(parent_header.timestamp() + 10);
+		assert_eq!(U256::from(536870912), ethash.calculate_difficulty(&header, &parent_header));
+		header.set_timestamp(parent_header.timestamp() + 30);
+		assert_eq!(U256::from(536346624), ethash.calculate_difficulty(&header, &parent_header));
+
+		parent_header.set_uncles_hash(H256::from(1));
+		header.set_timestamp(parent_header.timestamp() + 10);
+		assert_eq!(U256::from(537133056), ethash.calculate_difficulty(&header, &parent_header));
+		header.set_timestamp(parent_header.timestamp() + 30);
+		assert_eq!(U256::from(536608768), ethash.calculate_difficulty(&header, &parent_header));
+	}
+
+	#[test]
+	fn difficulty_bomb_delays() {
+		let spec = new_homestead_test();
+		let mut parent_header = Header::default();
+		parent_header.set_number(4999999);
+		parent_header.set_difficulty(U256::from(0x20000000));
+		parent_header.set_timestamp(1000);
+		let mut header = Header::default();
+		header.set_number(parent_header.number() + 1);
+		header.set_timestamp(parent_header.timestamp() + 10);
+
+		let ethparams = EthashParams {
+			homestead_transition: 0,
+			difficulty_bomb_delays: map![0 => 3000000],
+			..get_default_ethash_params()
+		};
+		let ethash = Ethash::new(spec.params.clone(), ethparams, BTreeMap::new());
+		assert_eq!(U256::from(537133056), ethash.calculate_difficulty(&header, &parent_header));
+
+		let ethparams = EthashParams {
+			homestead_transition: 0,
+			difficulty_bomb_delays: map![5000001 => 3000000],
+			..get_default_ethash_params()
+		};
+		let ethash = Ethash::new(spec.params, ethparams, BTreeMap::new());
+		assert_eq!(U256::from(281475513581568u64), ethash.calculate_difficulty(&header, &parent_header));
+	}
+
+	#[test]
+	fn difficulty_bomb_delays_with_continue_transition() {
+		let spec = new_homestead_test();
+		let ethparams = EthashParams {
+			homestead_transition: 0,
+			ecip1010_pause_transition: 3000000,
+			ecip1010_continue_transition: 5000000,
+			difficulty_bomb_delays: map![0 => 3000000],
+			..get_default_ethash_params()
+		};
+		let ethash = Ethash::new(spec.params, ethparams, BTreeMap::new());
+
+		let mut parent_header = Header::default();
+		parent_header.set_number(4999999);
+		parent_header.set_difficulty(U256::from(0x20000000));
+		parent_header.set_timestamp(1000);
+		let mut header = Header::default();
+		header.set_number(parent_header.number() + 1);
+		header.set_timestamp(parent_header.timestamp() + 10);
+		// the delayed bomb has not caught up with the paused periods yet
+		assert_eq!(U256::from(0x20000000), ethash.calculate_difficulty(&header, &parent_header));
+
+		parent_header.set_number(6999999);
+		header.set_number(parent_header.number() + 1);
+		assert_eq!(U256::from(537133056), ethash.calculate_difficulty(&header, &parent_header));
+	}
 }
@@ -63,6 +63,9 @@ pub fn new_eip150_test() -> Spec { load(include_bytes!("../../res/ethereum/eip15
 /// Create a new Homestead-EIP150 chain spec as though it never changed from Homestead/Frontier.
 pub fn new_eip161_test() -> Spec { load(include_bytes!("../../res/ethereum/eip161_test.json")) }
 
+/// Create a new Byzantium chain spec with the EIP-196, EIP-197 and EIP-198 precompiles active from genesis.
+pub fn new_byzantium_test() -> Spec { load(include_bytes!("../../res/ethereum/byzantium_test.json")) }
+
 /// Create a new Frontier/Homestead/DAO chain spec with transition points at #5 and #8.
 pub fn new_transition_test() -> Spec { load(include_bytes!("../../res/ethereum/transition_test.json")) }
 
@@ -120,4 +123,19 @@ mod tests {
 
 		let _ = frontier.engine;
 	}
+
+	#[test]
+	fn byzantium_builtins() {
+		let byzantium = new_byzantium_test();
+		let engine = &byzantium.engine;
+
+		for i in 1..9 {
+			let address = Address::from(i as u64);
+			assert!(engine.builtin(&address, 0).is_some());
+		}
+		assert!(engine.builtin(&Address::from(9), 0).is_none());
+
+		let frontier = new_frontier_test();
+		assert!(frontier.engine.builtin(&Address::from(5), 1_000_000).is_none());
+	}
 }
@@ -17,7 +17,7 @@
 //! Interface for Evm externalities.
 
 use util::*;
-use evm::{self, Schedule};
+use evm::{self, Schedule, ReturnData};
 use env_info::*;
 use types::executed::CallType;
 
@@ -28,17 +28,23 @@ pub enum ContractCreateResult {
 	Created(Address, U256),
 	/// Returned when contract creation failed.
 	/// VM doesn't have to know the reason.
-	Failed
+	Failed,
+	/// Returned when contract creation was reverted.
+	/// Contains gas left and the return data buffer.
+	Reverted(U256, ReturnData),
 }
 
 /// Result of externalities call function.
 pub enum MessageCallResult {
 	/// Returned when message call was successfull.
-	/// Contains gas left.
-	Success(U256),
+	/// Contains gas left and the return data buffer.
+	Success(U256, ReturnData),
 	/// Returned when message call failed.
 	/// VM doesn't have to know the reason.
-	Failed
+	Failed,
+	/// Returned when message call was reverted.
+	/// Contains gas left and the return data buffer.
+	Reverted(U256, ReturnData),
 }
 
 /// Externalities interface for EVMs
@@ -47,7 +53,7 @@ pub trait Ext {
 	fn storage_at(&self, key: &H256) -> H256;
 
 	/// Stores a value for given key.
-	fn set_storage(&mut self, key: H256, value: H256);
+	fn set_storage(&mut self, key: H256, value: H256) -> evm::Result<()>;
 
 	/// Determine whether an account exists.
 	fn exists(&self, address: &Address) -> bool;
@@ -93,15 +99,15 @@ pub trait Ext {
 	fn extcodesize(&self, address: &Address) -> usize;
 
 	/// Creates log entry with given topics and data
-	fn log(&mut self, topics: Vec<H256>, data: &[u8]);
+	fn log(&mut self, topics: Vec<H256>, data: &[u8]) -> evm::Result<()>;
 
-	/// Should be called when transaction calls `RETURN` opcode.
+	/// Should be called when transaction calls `RETURN` or `REVERT` opcode.
 	/// Returns gas_left if cost of returning the data is not too high.
-	fn ret(self, gas: &U256, data: &[u8]) -> evm::Result<U256> where Self: Sized;
+	fn ret(self, gas: &U256, data: &ReturnData, apply_state: bool) -> evm::Result<U256> where Self: Sized;
 
 	/// Should be called when contract commits suicide.
 	/// Address to which funds should be refunded.
-	fn suicide(&mut self, refund_address: &Address);
+	fn suicide(&mut self, refund_address: &Address) -> evm::Result<()>;
 
 	/// Returns schedule.
 	fn schedule(&self) -> &Schedule;
@@ -118,6 +124,9 @@ pub trait Ext {
 	/// Increments sstore refunds count by 1.
 	fn inc_sstore_clears(&mut self);
 
+	/// Returns true if the current execution is in static context and must not modify the state.
+	fn is_static(&self) -> bool;
+
 	/// Prepare to trace an operation. Passthrough for the VM trace.
 	fn trace_prepare_execute(&mut self, _pc: usize, _instruction: u8, _gas_cost: &U256) -> bool { false }
 
@@ -15,6 +15,10 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use util::{U256, Uint};
+use evm::ReturnData;
+
+/// Maximal amount of unused memory kept alive by the return data buffer.
+const MAX_RETURN_WASTE_BYTES: usize = 16384;
 
 pub trait Memory {
 	/// Retrieve current size of the memory
@@ -35,6 +39,8 @@ pub trait Memory {
 	fn read_slice(&self, offset: U256, size: U256) -> &[u8];
 	/// Retrieve writeable part of memory
 	fn writeable_slice(&mut self, offset: U256, size: U256) -> &mut[u8];
+	/// Convert memory into return data.
+	fn into_return_data(self, offset: U256, size: U256) -> ReturnData where Self: Sized;
 	fn dump(&self);
 }
 
@@ -112,6 +118,21 @@ impl Memory for Vec<u8> {
 			Memory::resize(self, size)
 		}
 	}
+
+	fn into_return_data(mut self, offset: U256, size: U256) -> ReturnData {
+		let mut offset = offset.low_u64() as usize;
+		let size = size.low_u64() as usize;
+		if !is_valid_range(offset, size) {
+			return ReturnData::empty()
+		}
+		if self.len() - size > MAX_RETURN_WASTE_BYTES {
+			{ let _ = self.drain(..offset); }
+			self.truncate(size);
+			self.shrink_to_fit();
+			offset = 0;
+		}
+		ReturnData::new(self, offset, size)
+	}
 }
 
 
@@ -32,7 +32,7 @@ use std::marker::PhantomData;
 use action_params::{ActionParams, ActionValue};
 use types::executed::CallType;
 use evm::instructions::{self, Instruction, InstructionInfo};
-use evm::{self, MessageCallResult, ContractCreateResult, GasLeft, CostType};
+use evm::{self, MessageCallResult, ContractCreateResult, GasLeft, CostType, ReturnData};
 use bit_set::BitSet;
 
 use util::*;
@@ -85,8 +85,8 @@ enum InstructionResult<Gas> {
 	Ok,
 	UnusedGas(Gas),
 	JumpToPosition(U256),
-	// gas left, init_orf, init_size
-	StopExecutionNeedsReturn(Gas, U256, U256),
+	// gas left, init_orf, init_size, apply_state
+	StopExecutionNeedsReturn(Gas, U256, U256, bool),
 	StopExecution,
 }
 
@@ -95,12 +95,14 @@ enum InstructionResult<Gas> {
 pub struct Interpreter<Cost: CostType> {
 	mem: Vec<u8>,
 	cache: Arc<SharedCache>,
+	return_data: ReturnData,
 	_type: PhantomData<Cost>,
 }
 
 impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 	fn exec(&mut self, params: ActionParams, ext: &mut evm::Ext) -> evm::Result<GasLeft> {
 		self.mem.clear();
+		self.return_data = ReturnData::empty();
 
 		let mut informant = informant::EvmInformant::new(ext.depth());
 
@@ -157,9 +159,14 @@ impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 					let pos = self.verify_jump(position, &valid_jump_destinations)?;
 					reader.position = pos;
 				},
-				InstructionResult::StopExecutionNeedsReturn(gas, off, size) => {
+				InstructionResult::StopExecutionNeedsReturn(gas, off, size, apply) => {
 					informant.done();
-					return Ok(GasLeft::NeedsReturn(gas.as_u256(), self.mem.read_slice(off, size)));
+					let mem = ::std::mem::replace(&mut self.mem, Vec::new());
+					return Ok(GasLeft::NeedsReturn {
+						gas_left: gas.as_u256(),
+						data: mem.into_return_data(off, size),
+						apply_state: apply
+					});
 				},
 				InstructionResult::StopExecution => break,
 				_ => {},
@@ -176,6 +183,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 		Interpreter {
 			mem: Vec::new(),
 			cache: cache,
+			return_data: ReturnData::empty(),
 			_type: PhantomData::default(),
 		}
 	}
@@ -183,7 +191,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 	fn verify_instruction(&self, ext: &evm::Ext, instruction: Instruction, info: &InstructionInfo, stack: &Stack<U256>) -> evm::Result<()> {
 		let schedule = ext.schedule();
 
-		if !schedule.have_delegate_call && instruction == instructions::DELEGATECALL {
+		if (instruction == instructions::DELEGATECALL && !schedule.have_delegate_call) ||
+			(instruction == instructions::REVERT && !schedule.have_revert) ||
+			(instruction == instructions::STATICCALL && !schedule.have_static_call) ||
+			((instruction == instructions::RETURNDATACOPY || instruction == instructions::RETURNDATASIZE) && !schedule.have_return_data) {
 			return Err(evm::Error::BadInstruction {
 				instruction: instruction
 			});
@@ -219,10 +230,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 		match instruction {
 			instructions::MSTORE | instructions::MLOAD => Some((stack.peek(0).low_u64() as usize, 32)),
 			instructions::MSTORE8 => Some((stack.peek(0).low_u64() as usize, 1)),
-			instructions::CALLDATACOPY | instructions::CODECOPY => Some((stack.peek(0).low_u64() as usize, stack.peek(2).low_u64() as usize)),
+			instructions::CALLDATACOPY | instructions::CODECOPY | instructions::RETURNDATACOPY => Some((stack.peek(0).low_u64() as usize, stack.peek(2).low_u64() as usize)),
 			instructions::EXTCODECOPY => Some((stack.peek(1).low_u64() as usize, stack.peek(3).low_u64() as usize)),
 			instructions::CALL | instructions::CALLCODE => Some((stack.peek(5).low_u64() as usize, stack.peek(6).low_u64() as usize)),
-			instructions::DELEGATECALL => Some((stack.peek(4).low_u64() as usize, stack.peek(5).low_u64() as usize)),
+			instructions::DELEGATECALL | instructions::STATICCALL => Some((stack.peek(4).low_u64() as usize, stack.peek(5).low_u64() as usize)),
 			_ => None,
 		}
 	}
@@ -273,9 +284,16 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let init_size = stack.pop_back();
 				let create_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is `CREATE`; qed");
 
+				if ext.is_static() {
+					return Err(evm::Error::MutableCallInStaticContext);
+				}
+
 				let contract_code = self.mem.read_slice(init_off, init_size);
 				let can_create = ext.balance(&params.address) >= endowment && ext.depth() < ext.schedule().max_depth;
 
+				// clear return data buffer before creating new call frame.
+				self.return_data = ReturnData::empty();
+
 				if !can_create {
 					stack.push(U256::zero());
 					return Ok(InstructionResult::UnusedGas(create_gas));
@@ -287,21 +305,28 @@ impl<Cost: CostType> Interpreter<Cost> {
 						stack.push(address_to_u256(address));
 						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
 					},
+					ContractCreateResult::Reverted(gas_left, return_data) => {
+						stack.push(U256::zero());
+						self.return_data = return_data;
+						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
+					},
 					ContractCreateResult::Failed => {
 						stack.push(U256::zero());
 						Ok(InstructionResult::Ok)
 					}
 				};
 			},
-			instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL => {
+			instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL | instructions::STATICCALL => {
 				assert!(ext.schedule().call_value_transfer_gas > ext.schedule().call_stipend, "overflow possible");
 				stack.pop_back();
-				let call_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is one of `CALL`/`CALLCODE`/`DELEGATECALL`; qed");
+				let call_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`STATICCALL`/`CREATE`; this is one of `CALL`/`CALLCODE`/`DELEGATECALL`/`STATICCALL`; qed");
 				let code_address = stack.pop_back();
 				let code_address = u256_to_address(&code_address);
 
 				let value = if instruction == instructions::DELEGATECALL {
 					None
+				} else if instruction == instructions::STATICCALL {
+					Some(U256::zero())
 				} else {
 					Some(stack.pop_back())
 				};
@@ -320,6 +345,9 @@ impl<Cost: CostType> Interpreter<Cost> {
 				// Get sender & receive addresses, check if we have balance
 				let (sender_address, receive_address, has_balance, call_type) = match instruction {
 					instructions::CALL => {
+						if ext.is_static() && value.map_or(false, |v| !v.is_zero()) {
+							return Err(evm::Error::MutableCallInStaticContext);
+						}
 						let has_balance = ext.balance(&params.address) >= value.expect("value set for all but delegate call; qed");
 						(&params.address, &code_address, has_balance, CallType::Call)
 					},
@@ -328,9 +356,13 @@ impl<Cost: CostType> Interpreter<Cost> {
 						(&params.address, &params.address, has_balance, CallType::CallCode)
 					},
 					instructions::DELEGATECALL => (&params.sender, &params.address, true, CallType::DelegateCall),
+					instructions::STATICCALL => (&params.address, &code_address, true, CallType::StaticCall),
 					_ => panic!(format!("Unexpected instruction {} in CALL branch.", instruction))
 				};
 
+				// clear return data buffer before creating new call frame.
+				self.return_data = ReturnData::empty();
+
 				let can_call = has_balance && ext.depth() < ext.schedule().max_depth;
 				if !can_call {
 					stack.push(U256::zero());
@@ -346,8 +378,14 @@ impl<Cost: CostType> Interpreter<Cost> {
 				};
 
 				return match call_result {
-					MessageCallResult::Success(gas_left) => {
+					MessageCallResult::Success(gas_left, data) => {
 						stack.push(U256::one());
+						self.return_data = data;
+						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater then current one")))
+					},
+					MessageCallResult::Reverted(gas_left, data) => {
+						stack.push(U256::zero());
+						self.return_data = data;
 						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater then current one")))
 					},
 					MessageCallResult::Failed  => {
@@ -360,14 +398,20 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let init_off = stack.pop_back();
 				let init_size = stack.pop_back();
 
-				return Ok(InstructionResult::StopExecutionNeedsReturn(gas, init_off, init_size))
+				return Ok(InstructionResult::StopExecutionNeedsReturn(gas, init_off, init_size, true))
+			},
+			instructions::REVERT => {
+				let init_off = stack.pop_back();
+				let init_size = stack.pop_back();
+
+				return Ok(InstructionResult::StopExecutionNeedsReturn(gas, init_off, init_size, false))
 			},
 			instructions::STOP => {
 				return Ok(InstructionResult::StopExecution);
 			},
 			instructions::SUICIDE => {
 				let address = stack.pop_back();
-				ext.suicide(&u256_to_address(&address));
+				ext.suicide(&u256_to_address(&address))?;
 				return Ok(InstructionResult::StopExecution);
 			},
 			instructions::LOG0...instructions::LOG4 => {
@@ -379,7 +423,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 					.iter()
 					.map(H256::from)
 					.collect();
-				ext.log(topics, self.mem.read_slice(offset, size));
+				ext.log(topics, self.mem.read_slice(offset, size))?;
 			},
 			instructions::PUSH1...instructions::PUSH32 => {
 				let bytes = instructions::get_push_bytes(instruction);
@@ -423,7 +467,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 				if !self.is_zero(&current_val) && self.is_zero(&val) {
 					ext.inc_sstore_clears();
 				}
-				ext.set_storage(address, H256::from(&val));
+				ext.set_storage(address, H256::from(&val))?;
 			},
 			instructions::PC => {
 				stack.push(U256::from(code.position - 1));
@@ -478,16 +522,30 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let len = ext.extcodesize(&address);
 				stack.push(U256::from(len));
 			},
+			instructions::RETURNDATASIZE => {
+				stack.push(U256::from(self.return_data.len()))
+			},
 			instructions::CALLDATACOPY => {
-				self.copy_data_to_memory(stack, params.data.as_ref().map_or_else(|| &[] as &[u8], |d| &*d as &[u8]));
+				Self::copy_data_to_memory(&mut self.mem, stack, params.data.as_ref().map_or_else(|| &[] as &[u8], |d| &*d as &[u8]));
+			},
+			instructions::RETURNDATACOPY => {
+				{
+					let source_offset = stack.peek(1);
+					let size = stack.peek(2);
+					let (end, overflow) = source_offset.overflowing_add(*size);
+					if overflow || end > U256::from(self.return_data.len()) {
+						return Err(evm::Error::OutOfBounds);
+					}
+				}
+				Self::copy_data_to_memory(&mut self.mem, stack, &*self.return_data);
 			},
 			instructions::CODECOPY => {
-				self.copy_data_to_memory(stack, params.code.as_ref().map_or_else(|| &[] as &[u8], |c| &**c as &[u8]));
+				Self::copy_data_to_memory(&mut self.mem, stack, params.code.as_ref().map_or_else(|| &[] as &[u8], |c| &**c as &[u8]));
 			},
 			instructions::EXTCODECOPY => {
 				let address = u256_to_address(&stack.pop_back());
 				let code = ext.extcode(&address);
-				self.copy_data_to_memory(stack, &code);
+				Self::copy_data_to_memory(&mut self.mem, stack, &code);
 			},
 			instructions::GASPRICE => {
 				stack.push(params.gas_price.clone());
@@ -519,7 +577,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 		Ok(InstructionResult::Ok)
 	}
 
-	fn copy_data_to_memory(&mut self, stack: &mut Stack<U256>, source: &[u8]) {
+	fn copy_data_to_memory(mem: &mut Vec<u8>, stack: &mut Stack<U256>, source: &[u8]) {
 		let dest_offset = stack.pop_back();
 		let source_offset = stack.pop_back();
 		let size = stack.pop_back();
@@ -528,9 +586,9 @@ impl<Cost: CostType> Interpreter<Cost> {
 		let output_end = match source_offset > source_size || size > source_size || source_offset + size > source_size {
 			true => {
 				let zero_slice = if source_offset > source_size {
-					self.mem.writeable_slice(dest_offset, size)
+					mem.writeable_slice(dest_offset, size)
 				} else {
-					self.mem.writeable_slice(dest_offset + source_size - source_offset, source_offset + size - source_size)
+					mem.writeable_slice(dest_offset + source_size - source_offset, source_offset + size - source_size)
 				};
 				for i in zero_slice.iter_mut() {
 					*i = 0;
@@ -542,7 +600,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 
 		if source_offset < source_size {
 			let output_begin = source_offset.low_u64() as usize;
-			self.mem.write_slice(dest_offset, &source[output_begin..output_end]);
+			mem.write_slice(dest_offset, &source[output_begin..output_end]);
 		}
 	}
 
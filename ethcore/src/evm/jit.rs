@@ -17,7 +17,7 @@
 //! Just in time compiler execution environment.
 use util::*;
 use evmjit;
-use evm::{self, GasLeft};
+use evm::{self, GasLeft, ReturnData};
 use types::executed::CallType;
 
 /// Should be used to convert jit types to ethcore
@@ -142,7 +142,7 @@ impl<'a> evmjit::Ext for ExtAdapter<'a> {
 		if !old_value.is_zero() && value.is_zero() {
 			self.ext.inc_sstore_clears();
 		}
-		self.ext.set_storage(key, value);
+		self.ext.set_storage(key, value).expect("JIT does not support STATICCALL, so the context is never static; qed");
 	}
 
 	fn balance(&self, address: *const evmjit::H256, out_value: *mut evmjit::I256) {
@@ -182,6 +182,10 @@ impl<'a> evmjit::Ext for ExtAdapter<'a> {
 				evm::ContractCreateResult::Failed => unsafe {
 					*address = Address::new().into_jit();
 					*io_gas = 0;
+				},
+				evm::ContractCreateResult::Reverted(gas_left, _) => unsafe {
+					*address = Address::new().into_jit();
+					*io_gas = gas_left.low_u64();
 				}
 			}
 		} else {
@@ -260,10 +264,14 @@ impl<'a> evmjit::Ext for ExtAdapter<'a> {
 					  unsafe { slice::from_raw_parts_mut(out_beg, out_size as usize) },
 					  call_type,
 					  ) {
-			evm::MessageCallResult::Success(gas_left) => unsafe {
+			evm::MessageCallResult::Success(gas_left, _) => unsafe {
 				*io_gas = (gas + gas_left).low_u64();
 				true
 			},
+			evm::MessageCallResult::Reverted(gas_left, _) => unsafe {
+				*io_gas = (gas + gas_left).low_u64();
+				false
+			},
 			evm::MessageCallResult::Failed => unsafe {
 				*io_gas = gas.low_u64();
 				false
@@ -298,7 +306,7 @@ impl<'a> evmjit::Ext for ExtAdapter<'a> {
 			}
 
 			let bytes_ref: &[u8] = slice::from_raw_parts(beg, size as usize);
-			self.ext.log(topics, bytes_ref);
+			self.ext.log(topics, bytes_ref).expect("JIT does not support STATICCALL, so the context is never static; qed");
 		}
 	}
 
@@ -364,10 +372,17 @@ impl evm::Evm for JitEvm {
 
 		match res {
 			evmjit::ReturnCode::Stop => Ok(GasLeft::Known(U256::from(context.gas_left()))),
-			evmjit::ReturnCode::Return =>
-				Ok(GasLeft::NeedsReturn(U256::from(context.gas_left()), context.output_data())),
+			evmjit::ReturnCode::Return => {
+				let output = context.output_data().to_vec();
+				let size = output.len();
+				Ok(GasLeft::NeedsReturn {
+					gas_left: U256::from(context.gas_left()),
+					data: ReturnData::new(output, 0, size),
+					apply_state: true,
+				})
+			},
 			evmjit::ReturnCode::Suicide => {
-				ext.suicide(&Address::from_jit(&context.suicide_refund_address()));
+				ext.suicide(&Address::from_jit(&context.suicide_refund_address()))?;
 				Ok(GasLeft::Known(U256::from(context.gas_left())))
 			},
 			evmjit::ReturnCode::OutOfGas => Err(evm::Error::OutOfGas),
@@ -31,8 +31,15 @@ mod tests;
 #[cfg(all(feature="benches", test))]
 mod benches;
 
-pub use self::evm::{Evm, Error, Finalize, GasLeft, Result, CostType};
+pub use self::evm::{Evm, Error, Finalize, FinalizationResult, GasLeft, Result, CostType, ReturnData};
 pub use self::ext::{Ext, ContractCreateResult, MessageCallResult};
 pub use self::factory::{Factory, VMType};
 pub use self::schedule::Schedule;
 pub use types::executed::CallType;
+pub use self::instructions::SLOAD;
+
+/// Returns the mnemonic of given instruction and the number of stack items it consumes.
+pub fn instruction_info(instruction: u8) -> (&'static str, usize) {
+	let info = &instructions::INSTRUCTIONS[instruction as usize];
+	(info.name, info.args)
+}
@@ -22,6 +22,12 @@ pub struct Schedule {
 	pub exceptional_failed_code_deposit: bool,
 	/// Does it have a delegate cal
 	pub have_delegate_call: bool,
+	/// Does it have a REVERT instruction
+	pub have_revert: bool,
+	/// Does it have a RETURNDATASIZE and RETURNDATACOPY instructions
+	pub have_return_data: bool,
+	/// Does it have a STATICCALL instruction
+	pub have_static_call: bool,
 	/// VM stack limit
 	pub stack_limit: usize,
 	/// Max number of nested calls/creates
@@ -117,6 +123,9 @@ impl Schedule {
 		Schedule {
 			exceptional_failed_code_deposit: true,
 			have_delegate_call: true,
+			have_revert: false,
+			have_return_data: false,
+			have_static_call: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -162,6 +171,9 @@ impl Schedule {
 		Schedule {
 			exceptional_failed_code_deposit: efcd,
 			have_delegate_call: hdc,
+			have_revert: false,
+			have_return_data: false,
+			have_static_call: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -18,7 +18,7 @@ use util::*;
 use action_params::{ActionParams, ActionValue};
 use env_info::EnvInfo;
 use types::executed::CallType;
-use evm::{self, Ext, Schedule, Factory, GasLeft, VMType, ContractCreateResult, MessageCallResult};
+use evm::{self, Ext, Schedule, Factory, GasLeft, VMType, ContractCreateResult, MessageCallResult, ReturnData};
 use std::fmt::Debug;
 
 pub struct FakeLogEntry {
@@ -58,13 +58,14 @@ pub struct FakeExt {
 	schedule: Schedule,
 	balances: HashMap<Address, U256>,
 	calls: HashSet<FakeCall>,
+	is_static: bool,
 }
 
 // similar to the normal `finalize` function, but ignoring NeedsReturn.
 fn test_finalize(res: Result<GasLeft, evm::Error>) -> Result<U256, evm::Error> {
 	match res {
 		Ok(GasLeft::Known(gas)) => Ok(gas),
-		Ok(GasLeft::NeedsReturn(_, _)) => unimplemented!(), // since ret is unimplemented.
+		Ok(GasLeft::NeedsReturn { .. }) => unimplemented!(), // since ret is unimplemented.
 		Err(e) => Err(e),
 	}
 }
@@ -86,8 +87,12 @@ impl Ext for FakeExt {
 		self.store.get(key).unwrap_or(&H256::new()).clone()
 	}
 
-	fn set_storage(&mut self, key: H256, value: H256) {
+	fn set_storage(&mut self, key: H256, value: H256) -> evm::Result<()> {
+		if self.is_static {
+			return Err(evm::Error::MutableCallInStaticContext);
+		}
 		self.store.insert(key, value);
+		Ok(())
 	}
 
 	fn exists(&self, address: &Address) -> bool {
@@ -143,7 +148,7 @@ impl Ext for FakeExt {
 			data: data.to_vec(),
 			code_address: Some(code_address.clone())
 		});
-		MessageCallResult::Success(*gas)
+		MessageCallResult::Success(*gas, ReturnData::empty())
 	}
 
 	fn extcode(&self, address: &Address) -> Arc<Bytes> {
@@ -154,18 +159,22 @@ impl Ext for FakeExt {
 		self.codes.get(address).map_or(0, |c| c.len())
 	}
 
-	fn log(&mut self, topics: Vec<H256>, data: &[u8]) {
+	fn log(&mut self, topics: Vec<H256>, data: &[u8]) -> evm::Result<()> {
+		if self.is_static {
+			return Err(evm::Error::MutableCallInStaticContext);
+		}
 		self.logs.push(FakeLogEntry {
 			topics: topics,
 			data: data.to_vec()
 		});
+		Ok(())
 	}
 
-	fn ret(self, _gas: &U256, _data: &[u8]) -> evm::Result<U256> {
+	fn ret(self, _gas: &U256, _data: &ReturnData, _apply_state: bool) -> evm::Result<U256> {
 		unimplemented!();
 	}
 
-	fn suicide(&mut self, _refund_address: &Address) {
+	fn suicide(&mut self, _refund_address: &Address) -> evm::Result<()> {
 		unimplemented!();
 	}
 
@@ -184,6 +193,10 @@ impl Ext for FakeExt {
 	fn inc_sstore_clears(&mut self) {
 		self.sstore_clears += 1;
 	}
+
+	fn is_static(&self) -> bool {
+		self.is_static
+	}
 }
 
 #[test]
@@ -954,6 +967,134 @@ fn test_calls(factory: super::Factory) {
 	assert_eq!(ext.calls.len(), 2);
 }
 
+#[test]
+fn test_byzantium_instructions_disabled_by_default() {
+	let address = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+	// RETURNDATASIZE, RETURNDATACOPY, STATICCALL, REVERT
+	for instruction in &[0x3du8, 0x3e, 0xfa, 0xfd] {
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(vec![*instruction]));
+		let mut ext = FakeExt::new();
+
+		let err = {
+			let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(super::interpreter::SharedCache::default())));
+			test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+		};
+
+		match err {
+			evm::Error::BadInstruction { instruction: i } => assert_eq!(i, *instruction),
+			_ => panic!("Expected BadInstruction"),
+		}
+	}
+}
+
+#[test]
+fn test_revert() {
+	let address = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+	let code = "600160005260206000fd".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.address = address.clone();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule.have_revert = true;
+
+	let res = {
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(super::interpreter::SharedCache::default())));
+		vm.exec(params, &mut ext).unwrap()
+	};
+
+	match res {
+		GasLeft::NeedsReturn { gas_left, data, apply_state } => {
+			assert_eq!(gas_left, U256::from(99_982));
+			assert_eq!(&*data, &*"0000000000000000000000000000000000000000000000000000000000000001".from_hex().unwrap());
+			assert!(!apply_state);
+		},
+		_ => panic!("Expected NeedsReturn"),
+	}
+}
+
+#[test]
+fn test_returndatacopy_out_of_bounds() {
+	let address = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+	let code = "6001600060003e".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.address = address.clone();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule.have_return_data = true;
+
+	let err = {
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(super::interpreter::SharedCache::default())));
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	match err {
+		evm::Error::OutOfBounds => {},
+		_ => panic!("Expected OutOfBounds"),
+	}
+}
+
+#[test]
+fn test_static_call() {
+	let address = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+	let code_address = Address::from(1);
+	let code = "60006000600060006001611000fa".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.address = address.clone();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule.have_static_call = true;
+
+	let gas_left = {
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(super::interpreter::SharedCache::default())));
+		test_finalize(vm.exec(params, &mut ext)).unwrap()
+	};
+
+	assert_set_contains(&ext.calls, &FakeCall {
+		call_type: FakeCallType::Call,
+		gas: U256::from(0x1000),
+		sender_address: Some(address.clone()),
+		receive_address: Some(code_address.clone()),
+		value: Some(U256::zero()),
+		data: vec!(),
+		code_address: Some(code_address.clone())
+	});
+	assert_eq!(gas_left, U256::from(99_942));
+	assert_eq!(ext.calls.len(), 1);
+}
+
+#[test]
+fn test_sstore_in_static_context() {
+	let address = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+	let code = "6001600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.address = address.clone();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.is_static = true;
+
+	let err = {
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(super::interpreter::SharedCache::default())));
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	match err {
+		evm::Error::MutableCallInStaticContext => {},
+		_ => panic!("Expected MutableCallInStaticContext"),
+	}
+	assert!(ext.store.is_empty());
+}
+
 fn assert_set_contains<T : Debug + Eq + PartialEq + Hash>(set: &HashSet<T>, val: &T) {
 	let contains = set.contains(val);
 	if !contains {
@@ -22,9 +22,9 @@ use engines::Engine;
 use types::executed::CallType;
 use env_info::EnvInfo;
 use error::ExecutionError;
-use evm::{self, Ext, Factory, Finalize};
+use evm::{self, Ext, Factory, Finalize, FinalizationResult, ReturnData};
 use externalities::*;
-use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer, VMTrace, VMTracer, ExecutiveVMTracer, NoopVMTracer};
+use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer, VMTrace, VMTracer, ExecutiveVMTracer, NoopVMTracer, TraceError, StructLogger, StructLogOptions};
 use transaction::{Action, SignedTransaction};
 use crossbeam;
 pub use types::executed::{Executed, ExecutionResult};
@@ -51,6 +51,8 @@ pub struct TransactOptions {
 	pub tracing: bool,
 	/// Enable VM tracing.
 	pub vm_tracing: bool,
+	/// Record struct logs with given options. Takes precedence over `vm_tracing`.
+	pub struct_logging: Option<StructLogOptions>,
 	/// Check transaction nonce before execution.
 	pub check_nonce: bool,
 }
@@ -62,6 +64,7 @@ pub struct Executive<'a> {
 	engine: &'a Engine,
 	vm_factory: &'a Factory,
 	depth: usize,
+	static_flag: bool,
 }
 
 impl<'a> Executive<'a> {
@@ -73,17 +76,19 @@ impl<'a> Executive<'a> {
 			engine: engine,
 			vm_factory: vm_factory,
 			depth: 0,
+			static_flag: false,
 		}
 	}
 
 	/// Populates executive from parent properties. Increments executive depth.
-	pub fn from_parent(state: &'a mut State, info: &'a EnvInfo, engine: &'a Engine, vm_factory: &'a Factory, parent_depth: usize) -> Self {
+	pub fn from_parent(state: &'a mut State, info: &'a EnvInfo, engine: &'a Engine, vm_factory: &'a Factory, parent_depth: usize, static_flag: bool) -> Self {
 		Executive {
 			state: state,
 			info: info,
 			engine: engine,
 			vm_factory: vm_factory,
 			depth: parent_depth + 1,
+			static_flag: static_flag,
 		}
 	}
 
@@ -94,14 +99,23 @@ impl<'a> Executive<'a> {
 		substate: &'any mut Substate,
 		output: OutputPolicy<'any, 'any>,
 		tracer: &'any mut T,
-		vm_tracer: &'any mut V
+		vm_tracer: &'any mut V,
+		static_call: bool,
 	) -> Externalities<'any, T, V> where T: Tracer, V: VMTracer {
-		Externalities::new(self.state, self.info, self.engine, self.vm_factory, self.depth, origin_info, substate, output, tracer, vm_tracer)
+		let is_static = self.static_flag || static_call;
+		Externalities::new(self.state, self.info, self.engine, self.vm_factory, self.depth, origin_info, substate, output, tracer, vm_tracer, is_static)
 	}
 
 	/// This function should be used to execute transaction.
 	pub fn transact(&'a mut self, t: &SignedTransaction, options: TransactOptions) -> Result<Executed, ExecutionError> {
 		let check = options.check_nonce;
+		if let Some(struct_log_options) = options.struct_logging {
+			let struct_logger = StructLogger::toplevel(struct_log_options);
+			return match options.tracing {
+				true => self.transact_with_struct_logger(t, check, ExecutiveTracer::default(), struct_logger),
+				false => self.transact_with_struct_logger(t, check, NoopTracer, struct_logger),
+			};
+		}
 		match options.tracing {
 			true => match options.vm_tracing {
 				true => self.transact_with_tracer(t, check, ExecutiveTracer::default(), ExecutiveVMTracer::toplevel()),
@@ -122,6 +136,35 @@ impl<'a> Executive<'a> {
 		mut tracer: T,
 		mut vm_tracer: V
 	) -> Result<Executed, ExecutionError> where T: Tracer, V: VMTracer {
+		let (substate, result, output) = self.execute_transaction(t, check_nonce, &mut tracer, &mut vm_tracer)?;
+
+		// finalize here!
+		Ok(self.finalize(t, substate, result, output, tracer.traces(), vm_tracer.drain())?)
+	}
+
+	/// Execute transaction/call recording the VM state before every executed instruction.
+	pub fn transact_with_struct_logger<T>(
+		&'a mut self,
+		t: &SignedTransaction,
+		check_nonce: bool,
+		mut tracer: T,
+		mut struct_logger: StructLogger
+	) -> Result<Executed, ExecutionError> where T: Tracer {
+		let (substate, result, output) = self.execute_transaction(t, check_nonce, &mut tracer, &mut struct_logger)?;
+
+		let mut executed = self.finalize(t, substate, result, output, tracer.traces(), None)?;
+		executed.struct_logs = Some(struct_logger.logs());
+		Ok(executed)
+	}
+
+	/// Validates the transaction and executes it without finalizing.
+	fn execute_transaction<T, V>(
+		&mut self,
+		t: &SignedTransaction,
+		check_nonce: bool,
+		tracer: &mut T,
+		vm_tracer: &mut V
+	) -> Result<(Substate, evm::Result<FinalizationResult>, Bytes), ExecutionError> where T: Tracer, V: VMTracer {
 		let sender = t.sender().map_err(|e| {
 			let message = format!("Transaction malformed: {:?}", e);
 			ExecutionError::TransactionMalformed(message)
@@ -168,7 +211,7 @@ impl<'a> Executive<'a> {
 
 		let mut substate = Substate::new();
 
-		let (gas_left, output) = match t.action {
+		let (result, output) = match t.action {
 			Action::Create => {
 				let new_address = contract_address(&sender, &nonce);
 				let params = ActionParams {
@@ -184,7 +227,7 @@ impl<'a> Executive<'a> {
 					data: None,
 					call_type: CallType::None,
 				};
-				(self.create(params, &mut substate, &mut tracer, &mut vm_tracer), vec![])
+				(self.create(params, &mut substate, tracer, vm_tracer), vec![])
 			},
 			Action::Call(ref address) => {
 				let params = ActionParams {
@@ -201,12 +244,11 @@ impl<'a> Executive<'a> {
 					call_type: CallType::Call,
 				};
 				let mut out = vec![];
-				(self.call(params, &mut substate, BytesRef::Flexible(&mut out), &mut tracer, &mut vm_tracer), out)
+				(self.call(params, &mut substate, BytesRef::Flexible(&mut out), tracer, vm_tracer), out)
 			}
 		};
 
-		// finalize here!
-		Ok(self.finalize(t, substate, gas_left, output, tracer.traces(), vm_tracer.drain())?)
+		Ok((substate, result, output))
 	}
 
 	fn exec_vm<T, V>(
@@ -216,14 +258,16 @@ impl<'a> Executive<'a> {
 		output_policy: OutputPolicy,
 		tracer: &mut T,
 		vm_tracer: &mut V
-	) -> evm::Result<U256> where T: Tracer, V: VMTracer {
+	) -> evm::Result<FinalizationResult> where T: Tracer, V: VMTracer {
 
 		let depth_threshold = ::io::LOCAL_STACK_SIZE.with(|sz| sz.get() / STACK_SIZE_PER_DEPTH);
 
+		let static_call = params.call_type == CallType::StaticCall;
+
 		// Ordinary execution - keep VM in same thread
 		if (self.depth + 1) % depth_threshold != 0 {
 			let vm_factory = self.vm_factory;
-			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer);
+			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer, static_call);
 			trace!(target: "executive", "ext.schedule.have_delegate_call: {}", ext.schedule().have_delegate_call);
 			return vm_factory.create(params.gas).exec(params, &mut ext).finalize(ext);
 		}
@@ -233,7 +277,7 @@ impl<'a> Executive<'a> {
 		// https://github.com/aturon/crossbeam/issues/16
 		crossbeam::scope(|scope| {
 			let vm_factory = self.vm_factory;
-			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer);
+			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer, static_call);
 
 			scope.spawn(move || {
 				vm_factory.create(params.gas).exec(params, &mut ext).finalize(ext)
@@ -252,7 +296,7 @@ impl<'a> Executive<'a> {
 		mut output: BytesRef,
 		tracer: &mut T,
 		vm_tracer: &mut V
-	) -> evm::Result<U256> where T: Tracer, V: VMTracer {
+	) -> evm::Result<FinalizationResult> where T: Tracer, V: VMTracer {
 		// backup used in case of running out of gas
 		self.state.checkpoint();
 
@@ -264,35 +308,57 @@ impl<'a> Executive<'a> {
 		}
 		trace!("Executive::call(params={:?}) self.env_info={:?}", params, self.info);
 
-		if self.engine.is_builtin(&params.code_address) {
-			// if destination is builtin, try to execute it
+		let builtin = self.engine.builtin(&params.code_address, self.info.number);
+		if let Some(builtin) = builtin {
+			// Engines aren't supposed to return builtins until activation, but
+			// prefer to fail rather than silently break consensus.
+			if !builtin.is_active(self.info.number) {
+				panic!("Consensus failure: engine implementation prematurely enabled built-in at {}", params.code_address);
+			}
 
 			let default = [];
 			let data = if let Some(ref d) = params.data { d as &[u8] } else { &default as &[u8] };
 
 			let trace_info = tracer.prepare_trace_call(&params);
 
-			let cost = self.engine.cost_of_builtin(&params.code_address, data);
+			let cost = builtin.cost(data);
 			if cost <= params.gas {
-				self.engine.execute_builtin(&params.code_address, data, &mut output);
-				self.state.discard_checkpoint();
-
-				// trace only top level calls to builtins to avoid DDoS attacks
-				if self.depth == 0 {
-					let mut trace_output = tracer.prepare_trace_output();
-					if let Some(mut out) = trace_output.as_mut() {
-						*out = output.to_owned();
+				let mut builtin_out_buffer = Vec::new();
+				let result = {
+					let mut builtin_output = BytesRef::Flexible(&mut builtin_out_buffer);
+					builtin.execute(data, &mut builtin_output)
+				};
+				if let Err(e) = result {
+					self.state.revert_to_checkpoint();
+					let evm_err: evm::Error = e.into();
+					tracer.trace_failed_call(trace_info, vec![], evm_err.into());
+					Err(evm_err)
+				} else {
+					self.state.discard_checkpoint();
+					output.write(0, &builtin_out_buffer);
+
+					// trace only top level calls to builtins to avoid DDoS attacks
+					if self.depth == 0 {
+						let mut trace_output = tracer.prepare_trace_output();
+						if let Some(mut out) = trace_output.as_mut() {
+							*out = output.to_owned();
+						}
+
+						tracer.trace_call(
+							trace_info,
+							cost,
+							trace_output,
+							vec![]
+						);
 					}
 
-					tracer.trace_call(
-						trace_info,
-						cost,
-						trace_output,
-						vec![]
-					);
+					let out_len = builtin_out_buffer.len();
+					Ok(FinalizationResult {
+						gas_left: params.gas - cost,
+						return_data: ReturnData::new(builtin_out_buffer, 0, out_len),
+						apply_state: true,
+					})
 				}
-
-				Ok(params.gas - cost)
 			} else {
 				// just drain the whole gas
 				self.state.revert_to_checkpoint();
@@ -325,12 +391,13 @@ impl<'a> Executive<'a> {
 
 				let traces = subtracer.traces();
 				match res {
-					Ok(gas_left) => tracer.trace_call(
+					Ok(ref res) if res.apply_state => tracer.trace_call(
 						trace_info,
-						gas - gas_left,
+						gas - res.gas_left,
 						trace_output,
 						traces
 					),
+					Ok(_) => tracer.trace_failed_call(trace_info, traces, evm::Error::Reverted.into()),
 					Err(e) => tracer.trace_failed_call(trace_info, traces, e.into()),
 				};
 
@@ -344,7 +411,11 @@ impl<'a> Executive<'a> {
 				self.state.discard_checkpoint();
 
 				tracer.trace_call(trace_info, U256::zero(), trace_output, vec![]);
-				Ok(params.gas)
+				Ok(FinalizationResult {
+					gas_left: params.gas,
+					return_data: ReturnData::empty(),
+					apply_state: true,
+				})
 			}
 		}
 	}
@@ -358,7 +429,7 @@ impl<'a> Executive<'a> {
 		substate: &mut Substate,
 		tracer: &mut T,
 		vm_tracer: &mut V
-	) -> evm::Result<U256> where T: Tracer, V: VMTracer {
+	) -> evm::Result<FinalizationResult> where T: Tracer, V: VMTracer {
 		// backup used in case of running out of gas
 		self.state.checkpoint();
 
@@ -391,13 +462,14 @@ impl<'a> Executive<'a> {
 		vm_tracer.done_subtrace(subvmtracer);
 
 		match res {
-			Ok(gas_left) => tracer.trace_create(
+			Ok(ref res) if res.apply_state => tracer.trace_create(
 				trace_info,
-				gas - gas_left,
+				gas - res.gas_left,
 				trace_output,
 				created,
 				subtracer.traces()
 			),
+			Ok(_) => tracer.trace_failed_create(trace_info, subtracer.traces(), evm::Error::Reverted.into()),
 			Err(e) => tracer.trace_failed_create(trace_info, subtracer.traces(), e.into())
 		};
 
@@ -410,7 +482,7 @@ impl<'a> Executive<'a> {
 		&mut self,
 		t: &SignedTransaction,
 		mut substate: Substate,
-		result: evm::Result<U256>,
+		result: evm::Result<FinalizationResult>,
 		output: Bytes,
 		trace: Vec<FlatTrace>,
 		vm_trace: Option<VMTrace>
@@ -424,7 +496,7 @@ impl<'a> Executive<'a> {
 		let refunds_bound = sstore_refunds + suicide_refunds;
 
 		// real ammount to refund
-		let gas_left_prerefund = match result { Ok(x) => x, _ => 0.into() };
+		let gas_left_prerefund = match result { Ok(FinalizationResult{ gas_left, .. }) => gas_left, _ => 0.into() };
 		let refunded = cmp::min(refunds_bound, (t.gas - gas_left_prerefund) >> 1);
 		let gas_left = gas_left_prerefund + refunded;
 
@@ -463,8 +535,9 @@ impl<'a> Executive<'a> {
 
 		match result {
 			Err(evm::Error::Internal) => Err(ExecutionError::Internal),
-			Err(_) => {
+			Err(exception) => {
 				Ok(Executed {
+					exception: Some(exception.into()),
 					gas: t.gas,
 					gas_used: t.gas,
 					refunded: U256::zero(),
@@ -475,10 +548,12 @@ impl<'a> Executive<'a> {
 					trace: trace,
 					vm_trace: vm_trace,
 					state_diff: None,
+					struct_logs: None,
 				})
 			},
-			_ => {
+			Ok(r) => {
 				Ok(Executed {
+					exception: if r.apply_state { None } else { Some(TraceError::Reverted) },
 					gas: t.gas,
 					gas_used: gas_used,
 					refunded: refunded,
@@ -489,18 +564,24 @@ impl<'a> Executive<'a> {
 					trace: trace,
 					vm_trace: vm_trace,
 					state_diff: None,
+					struct_logs: None,
 				})
 			},
 		}
 	}
 
-	fn enact_result(&mut self, result: &evm::Result<U256>, substate: &mut Substate, un_substate: Substate) {
+	fn enact_result(&mut self, result: &evm::Result<FinalizationResult>, substate: &mut Substate, un_substate: Substate) {
 		match *result {
 			Err(evm::Error::OutOfGas)
 				| Err(evm::Error::BadJumpDestination {..})
 				| Err(evm::Error::BadInstruction {.. })
 				| Err(evm::Error::StackUnderflow {..})
-				| Err(evm::Error::OutOfStack {..}) => {
+				| Err(evm::Error::BuiltIn {..})
+				| Err(evm::Error::OutOfStack {..})
+				| Err(evm::Error::MutableCallInStaticContext)
+				| Err(evm::Error::OutOfBounds)
+				| Err(evm::Error::Reverted)
+				| Ok(FinalizationResult { apply_state: false, .. }) => {
 					self.state.revert_to_checkpoint();
 			},
 			Ok(_) | Err(evm::Error::Internal) => {
@@ -521,13 +602,14 @@ mod tests {
 	use util::bytes::BytesRef;
 	use action_params::{ActionParams, ActionValue};
 	use env_info::EnvInfo;
-	use evm::{Factory, VMType};
+	use evm::{Factory, VMType, FinalizationResult};
 	use error::ExecutionError;
 	use state::{Substate, CleanupMode};
 	use tests::helpers::*;
 	use trace::trace;
 	use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer};
 	use trace::{VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff, VMTracer, NoopVMTracer, ExecutiveVMTracer};
+	use trace::{StructLog, StructLogOptions, StructLogger};
 	use transaction::{Action, Transaction};
 
 	use types::executed::CallType;
@@ -557,7 +639,7 @@ mod tests {
 		let engine = TestEngine::new(0);
 		let mut substate = Substate::new();
 
-		let gas_left = {
+		let FinalizationResult { gas_left, .. } = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
 			ex.create(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap()
 		};
@@ -616,7 +698,7 @@ mod tests {
 		let engine = TestEngine::new(0);
 		let mut substate = Substate::new();
 
-		let gas_left = {
+		let FinalizationResult { gas_left, .. } = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
 			ex.create(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap()
 		};
@@ -677,7 +759,7 @@ mod tests {
 		let mut tracer = ExecutiveTracer::default();
 		let mut vm_tracer = ExecutiveVMTracer::toplevel();
 
-		let gas_left = {
+		let FinalizationResult { gas_left, .. } = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
 			let output = BytesRef::Fixed(&mut[0u8;0]);
 			ex.call(params, &mut substate, output, &mut tracer, &mut vm_tracer).unwrap()
@@ -788,7 +870,7 @@ mod tests {
 		let mut tracer = ExecutiveTracer::default();
 		let mut vm_tracer = ExecutiveVMTracer::toplevel();
 
-		let gas_left = {
+		let FinalizationResult { gas_left, .. } = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
 			ex.create(params.clone(), &mut substate, &mut tracer, &mut vm_tracer).unwrap()
 		};
@@ -830,6 +912,61 @@ mod tests {
 		assert_eq!(vm_tracer.drain().unwrap(), expected_vm_trace);
 	}
 
+	#[test]
+	fn test_create_contract_struct_logs() {
+		// Tracing is not supported in JIT
+		let factory = Factory::new(VMType::Interpreter, 1024 * 32);
+		// same code as in `test_create_contract`
+		let code = "601080600c6000396000f3006000355415600957005b60203560003555".from_hex().unwrap();
+
+		let sender = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+		let address = contract_address(&sender, &U256::zero());
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code));
+		params.value = ActionValue::Transfer(100.into());
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.add_balance(&sender, &U256::from(100), CleanupMode::NoEmpty);
+		let info = EnvInfo::default();
+		let engine = TestEngine::new(5);
+		let mut substate = Substate::new();
+		let mut vm_tracer = StructLogger::toplevel(StructLogOptions { disable_storage: true, ..Default::default() });
+
+		let FinalizationResult { gas_left, .. } = {
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			ex.create(params.clone(), &mut substate, &mut NoopTracer, &mut vm_tracer).unwrap()
+		};
+
+		assert_eq!(gas_left, U256::from(96_776));
+
+		let log = |pc: usize, op: &str, gas: u64, gas_cost: u64, stack: Vec<u64>, memory: Vec<u8>| StructLog {
+			pc: pc,
+			op: op.into(),
+			gas: gas.into(),
+			gas_cost: gas_cost.into(),
+			depth: 1,
+			stack: Some(stack.into_iter().map(Into::into).collect()),
+			memory: Some(memory),
+			storage: None,
+		};
+		let mut copied = vec![96, 0, 53, 84, 21, 96, 9, 87, 0, 91, 96, 32, 53, 96, 0, 53];
+		copied.resize(32, 0);
+
+		assert_eq!(vm_tracer.logs(), vec![
+			log(0, "PUSH1", 100000, 3, vec![], vec![]),
+			log(2, "DUP1", 99997, 3, vec![16], vec![]),
+			log(3, "PUSH1", 99994, 3, vec![16, 16], vec![]),
+			log(5, "PUSH1", 99991, 3, vec![16, 16, 12], vec![]),
+			log(7, "CODECOPY", 99988, 9, vec![16, 16, 12, 0], vec![]),
+			log(8, "PUSH1", 99979, 3, vec![16], copied.clone()),
+			log(10, "RETURN", 99976, 0, vec![16, 0], copied),
+		]);
+	}
+
 	evm_test!{test_create_contract_value_too_high: test_create_contract_value_too_high_jit, test_create_contract_value_too_high_int}
 	fn test_create_contract_value_too_high(factory: Factory) {
 		// code:
@@ -874,7 +1011,7 @@ mod tests {
 		let engine = TestEngine::new(0);
 		let mut substate = Substate::new();
 
-		let gas_left = {
+		let FinalizationResult { gas_left, .. } = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
 			ex.create(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap()
 		};
@@ -987,7 +1124,7 @@ mod tests {
 		let engine = TestEngine::new(0);
 		let mut substate = Substate::new();
 
-		let gas_left = {
+		let FinalizationResult { gas_left, .. } = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
 			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap()
 		};
@@ -1032,7 +1169,7 @@ mod tests {
 		let engine = TestEngine::new(0);
 		let mut substate = Substate::new();
 
-		let gas_left = {
+		let FinalizationResult { gas_left, .. } = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
 			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap()
 		};
@@ -1067,7 +1204,7 @@ mod tests {
 
 		let executed = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, struct_logging: None };
 			ex.transact(&t, opts).unwrap()
 		};
 
@@ -1101,7 +1238,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, struct_logging: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1133,7 +1270,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, struct_logging: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1167,7 +1304,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, struct_logging: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1201,7 +1338,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, struct_logging: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1247,4 +1384,70 @@ mod tests {
 			}
 		}
 	}
+
+	#[test]
+	fn test_revert() {
+		// 60 01 - push 1
+		// 60 00 - push 0
+		// 55 - sstore
+		// 60 02 - push 2
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 20 - push 32
+		// 60 00 - push 0
+		// fd - revert
+		let code = "6001600055600260005260206000fd".from_hex().unwrap();
+
+		let address = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+		let sender = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.code_address = address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code.clone()));
+		params.value = ActionValue::Transfer(U256::zero());
+		params.call_type = CallType::Call;
+
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.init_code(&address, code);
+		state.add_balance(&sender, &U256::from(100_000), CleanupMode::NoEmpty);
+
+		let info = EnvInfo::default();
+		let engine = ::ethereum::new_byzantium_test().engine;
+		let factory = Factory::new(VMType::Interpreter, 1024 * 32);
+		let mut substate = Substate::new();
+		let mut tracer = ExecutiveTracer::default();
+		let mut output = vec![];
+
+		let FinalizationResult { gas_left, apply_state, return_data } = {
+			let mut ex = Executive::new(&mut state, &info, &*engine, &factory);
+			ex.call(params, &mut substate, BytesRef::Flexible(&mut output), &mut tracer, &mut NoopVMTracer).unwrap()
+		};
+
+		let expected_output = "0000000000000000000000000000000000000000000000000000000000000002".from_hex().unwrap();
+		assert_eq!(gas_left, U256::from(79_976));
+		assert!(!apply_state);
+		assert_eq!(&*return_data, &expected_output[..]);
+		assert_eq!(output, expected_output);
+		assert_eq!(state.storage_at(&address, &H256::new()), H256::new());
+
+		let expected_trace = vec![FlatTrace {
+			trace_address: Default::default(),
+			subtraces: 0,
+			action: trace::Action::Call(trace::Call {
+				from: sender,
+				to: address,
+				value: 0.into(),
+				gas: 100_000.into(),
+				input: vec![],
+				call_type: CallType::Call,
+			}),
+			result: trace::Res::FailedCall(TraceError::Reverted),
+		}];
+		assert_eq!(tracer.traces(), expected_trace);
+	}
 }
@@ -0,0 +1,54 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::test_common::*;
+use ethjson;
+use header::Header;
+
+fn do_json_test(json_data: &[u8]) -> Vec<String> {
+	let tests = ethjson::difficulty::Test::load(json_data).unwrap();
+	let spec = ::ethereum::new_byzantium_test();
+	let engine = &*spec.engine;
+	let mut failed = Vec::new();
+
+	for (name, test) in tests.into_iter() {
+		flush!("   - {}...", name);
+
+		let block_number: u64 = test.current_block_number.into();
+		let mut parent_header = Header::new();
+		parent_header.set_number(block_number - 1);
+		parent_header.set_gas_limit(0x20000.into());
+		parent_header.set_timestamp(test.parent_timestamp.into());
+		parent_header.set_difficulty(test.parent_difficulty.into());
+		parent_header.set_uncles_hash(test.parent_uncles.into());
+		let mut header = Header::new();
+		header.set_number(block_number);
+		header.set_timestamp(test.current_timestamp.into());
+		engine.populate_from_parent(&mut header, &parent_header, 0x20000.into(), 0x20000.into());
+
+		let expected_difficulty: U256 = test.current_difficulty.into();
+		if header.difficulty() == &expected_difficulty {
+			flushln!("ok");
+		} else {
+			flushln!("FAIL (got: {}, expect: {})", header.difficulty(), expected_difficulty);
+			failed.push(name);
+		}
+	}
+
+	failed
+}
+
+declare_test!{BasicTests_difficultyByzantium, "BasicTests/difficultyByzantium"}
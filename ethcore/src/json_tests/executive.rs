@@ -21,7 +21,7 @@ use executive::*;
 use engines::Engine;
 use env_info::EnvInfo;
 use evm;
-use evm::{Schedule, Ext, Factory, Finalize, VMType, ContractCreateResult, MessageCallResult};
+use evm::{Schedule, Ext, Factory, Finalize, VMType, ContractCreateResult, MessageCallResult, ReturnData};
 use externalities::*;
 use types::executed::CallType;
 use tests::helpers::*;
@@ -73,7 +73,7 @@ impl<'a, T, V> TestExt<'a, T, V> where T: 'a + Tracer, V: 'a + VMTracer {
 	) -> Self {
 		TestExt {
 			contract_address: contract_address(&address, &state.nonce(&address)),
-			ext: Externalities::new(state, info, engine, vm_factory, depth, origin_info, substate, output, tracer, vm_tracer),
+			ext: Externalities::new(state, info, engine, vm_factory, depth, origin_info, substate, output, tracer, vm_tracer, false),
 			callcreates: vec![]
 		}
 	}
@@ -84,7 +84,7 @@ impl<'a, T, V> Ext for TestExt<'a, T, V> where T: Tracer, V: VMTracer {
 		self.ext.storage_at(key)
 	}
 
-	fn set_storage(&mut self, key: H256, value: H256) {
+	fn set_storage(&mut self, key: H256, value: H256) -> evm::Result<()> {
 		self.ext.set_storage(key, value)
 	}
 
@@ -134,7 +134,7 @@ impl<'a, T, V> Ext for TestExt<'a, T, V> where T: Tracer, V: VMTracer {
 			gas_limit: *gas,
 			value: value.unwrap()
 		});
-		MessageCallResult::Success(*gas)
+		MessageCallResult::Success(*gas, ReturnData::empty())
 	}
 
 	fn extcode(&self, address: &Address) -> Arc<Bytes>  {
@@ -145,15 +145,15 @@ impl<'a, T, V> Ext for TestExt<'a, T, V> where T: Tracer, V: VMTracer {
 		self.ext.extcodesize(address)
 	}
 
-	fn log(&mut self, topics: Vec<H256>, data: &[u8]) {
+	fn log(&mut self, topics: Vec<H256>, data: &[u8]) -> evm::Result<()> {
 		self.ext.log(topics, data)
 	}
 
-	fn ret(self, gas: &U256, data: &[u8]) -> Result<U256, evm::Error> {
-		self.ext.ret(gas, data)
+	fn ret(self, gas: &U256, data: &ReturnData, apply_state: bool) -> Result<U256, evm::Error> {
+		self.ext.ret(gas, data, apply_state)
 	}
 
-	fn suicide(&mut self, refund_address: &Address) {
+	fn suicide(&mut self, refund_address: &Address) -> evm::Result<()> {
 		self.ext.suicide(refund_address)
 	}
 
@@ -172,6 +172,10 @@ impl<'a, T, V> Ext for TestExt<'a, T, V> where T: Tracer, V: VMTracer {
 	fn inc_sstore_clears(&mut self) {
 		self.ext.inc_sstore_clears()
 	}
+
+	fn is_static(&self) -> bool {
+		self.ext.is_static()
+	}
 }
 
 fn do_json_test(json_data: &[u8]) -> Vec<String> {
@@ -233,7 +237,7 @@ fn do_json_test_for(vm_type: &VMType, json_data: &[u8]) -> Vec<String> {
 
 		match res {
 			Err(_) => fail_unless(out_of_gas, "didn't expect to run out of gas."),
-			Ok(gas_left) => {
+			Ok(evm::FinalizationResult { gas_left, .. }) => {
 				fail_unless(!out_of_gas, "expected to run out of gas.");
 				fail_unless(Some(gas_left) == vm.gas_left.map(Into::into), "gas_left is incorrect");
 				let vm_output: Option<Vec<u8>> = vm.output.map(Into::into);
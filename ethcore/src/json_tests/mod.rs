@@ -26,3 +26,4 @@ mod homestead_chain;
 mod eip150_state;
 mod eip161_state;
 mod trie;
+mod difficulty;
@@ -93,6 +93,7 @@ extern crate rayon;
 extern crate hyper;
 extern crate ethash;
 extern crate ethkey;
+extern crate ethcrypto;
 extern crate semver;
 extern crate ethcore_ipc_nano as nanoipc;
 extern crate ethcore_devtools as devtools;
@@ -103,6 +104,9 @@ extern crate ethcore_bloom_journal as bloom_journal;
 extern crate byteorder;
 extern crate transient_hashmap;
 extern crate linked_hash_map;
+extern crate num;
+extern crate bn;
+extern crate ethcore_stratum;
 
 #[macro_use]
 extern crate log;
@@ -140,6 +144,7 @@ pub mod snapshot;
 pub mod action_params;
 pub mod db;
 pub mod verification;
+pub mod private_transactions;
 #[macro_use] pub mod evm;
 
 mod cache_manager;
@@ -30,9 +30,9 @@ use transaction::{Action, SignedTransaction, PendingTransaction};
 use receipt::{Receipt, RichReceipt};
 use spec::Spec;
 use engines::{Engine, Seal};
-use miner::{MinerService, MinerStatus, TransactionQueue, PrioritizationStrategy, AccountDetails, TransactionOrigin};
+use miner::{MinerService, MinerStatus, TransactionQueue, PrioritizationStrategy, AccountDetails, TransactionOrigin, PersistedTransaction};
 use miner::banning_queue::{BanningTransactionQueue, Threshold};
-use miner::work_notify::WorkPoster;
+use miner::work_notify::{WorkPoster, NotifyWork};
 use miner::price_info::PriceInfo;
 use miner::local_transactions::{Status as LocalTransactionStatus};
 use header::BlockNumber;
@@ -105,6 +105,8 @@ pub struct MinerOptions {
 	pub tx_queue_gas_limit: GasLimit,
 	/// Banning settings
 	pub tx_queue_banning: Banning,
+	/// Persist the whole transaction queue across restarts, not only local transactions.
+	pub tx_queue_persist_all: bool,
 }
 
 impl Default for MinerOptions {
@@ -123,6 +125,7 @@ impl Default for MinerOptions {
 			work_queue_size: 20,
 			enable_resubmission: true,
 			tx_queue_banning: Banning::Disabled,
+			tx_queue_persist_all: false,
 		}
 	}
 }
@@ -223,17 +226,18 @@ pub struct Miner {
 	engine: Arc<Engine>,
 
 	accounts: Option<Arc<AccountProvider>>,
-	work_poster: Option<WorkPoster>,
+	notifiers: RwLock<Vec<Box<NotifyWork>>>,
 	gas_pricer: Mutex<GasPricer>,
+	transaction_listener: RwLock<Vec<Box<Fn(&[H256]) + Send + Sync>>>,
 }
 
 impl Miner {
 	/// Creates new instance of miner.
 	fn new_raw(options: MinerOptions, gas_pricer: GasPricer, spec: &Spec, accounts: Option<Arc<AccountProvider>>) -> Miner {
-		let work_poster = match options.new_work_notify.is_empty() {
-			true => None,
-			false => Some(WorkPoster::new(&options.new_work_notify))
-		};
+		let mut notifiers: Vec<Box<NotifyWork>> = Vec::new();
+		if !options.new_work_notify.is_empty() {
+			notifiers.push(Box::new(WorkPoster::new(&options.new_work_notify)));
+		}
 		let gas_limit = match options.tx_queue_gas_limit {
 			GasLimit::Fixed(ref limit) => *limit,
 			_ => !U256::zero(),
@@ -265,11 +269,23 @@ impl Miner {
 			options: options,
 			accounts: accounts,
 			engine: spec.engine.clone(),
-			work_poster: work_poster,
+			notifiers: RwLock::new(notifiers),
 			gas_pricer: Mutex::new(gas_pricer),
+			transaction_listener: RwLock::new(vec![]),
 		}
 	}
 
+	/// Set a callback to be notified about imported transactions' hashes.
+	pub fn add_transactions_listener(&self, f: Box<Fn(&[H256]) + Send + Sync>) {
+		self.transaction_listener.write().push(f);
+	}
+
+	/// Push a notifier that will get new work packages pushed to it and enable sealing.
+	pub fn push_notifier(&self, notifier: Box<NotifyWork>) {
+		self.notifiers.write().push(notifier);
+		self.sealing_work.lock().enabled = true;
+	}
+
 	/// Creates new instance of miner with accounts and with given spec.
 	pub fn with_spec_and_accounts(spec: &Spec, accounts: Option<Arc<AccountProvider>>) -> Miner {
 		Miner::new_raw(Default::default(), GasPricer::new_fixed(20_000_000_000u64.into()), spec, accounts)
@@ -286,7 +302,7 @@ impl Miner {
 	}
 
 	fn forced_sealing(&self) -> bool {
-		self.options.force_sealing || !self.options.new_work_notify.is_empty()
+		self.options.force_sealing || !self.notifiers.read().is_empty()
 	}
 
 	/// Clear all pending block states
@@ -304,6 +320,36 @@ impl Miner {
 		self.sealing_work.lock().queue.peek_last_ref().map(|b| b.base().clone())
 	}
 
+	/// Transactions from the queue which should survive a restart. These are the local ones
+	/// unless the miner is configured to persist the whole queue.
+	pub fn transactions_to_persist(&self) -> Vec<PersistedTransaction> {
+		let persist_all = self.options.tx_queue_persist_all;
+		self.transaction_queue.lock().queued_transactions()
+			.into_iter()
+			.map(|(pending, origin)| PersistedTransaction { pending: pending, local: origin == TransactionOrigin::Local })
+			.filter(|t| persist_all || t.local)
+			.collect()
+	}
+
+	/// Re-imports previously persisted transactions, validating them against the current state.
+	/// Returns the number of transactions which made it back to the queue.
+	pub fn restore_transactions(&self, chain: &MiningBlockChainClient, transactions: Vec<PersistedTransaction>) -> usize {
+		let mut restored = 0;
+		for t in transactions {
+			let hash = t.pending.transaction.hash();
+			let result = match t.local {
+				true => self.import_own_transaction(chain, t.pending),
+				false => self.import_external_transactions(chain, vec![t.pending.transaction]).pop()
+					.expect("one result returned per added transaction; one added => one result; qed"),
+			};
+			match result {
+				Ok(_) => restored += 1,
+				Err(e) => debug!(target: "miner", "Dropping persisted transaction {:?}: {:?}", hash, e),
+			}
+		}
+		restored
+	}
+
 	#[cfg_attr(feature="dev", allow(match_same_arms))]
 	/// Prepares new block for sealing including top transactions from queue.
 	fn prepare_block(&self, chain: &MiningBlockChainClient) -> (ClosedBlock, Option<H256>) {
@@ -518,7 +564,7 @@ impl Miner {
 				let is_new = original_work_hash.map_or(true, |h| block.block().fields().header.hash() != h);
 				sealing_work.queue.push(block);
 				// If push notifications are enabled we assume all work items are used.
-				if self.work_poster.is_some() && is_new {
+				if !self.notifiers.read().is_empty() && is_new {
 					sealing_work.queue.use_last_ref();
 				}
 				(Some((pow_hash, difficulty, number)), is_new)
@@ -529,7 +575,11 @@ impl Miner {
 			(work, is_new)
 		};
 		if is_new {
-			work.map(|(pow_hash, difficulty, number)| self.work_poster.as_ref().map(|p| p.notify(pow_hash, difficulty, number)));
+			if let Some((pow_hash, difficulty, number)) = work {
+				for notifier in self.notifiers.read().iter() {
+					notifier.notify(pow_hash, difficulty, number);
+				}
+			}
 		}
 	}
 
@@ -597,10 +647,12 @@ impl Miner {
 		let schedule = chain.latest_schedule();
 		let gas_required = |tx: &SignedTransaction| tx.gas_required(&schedule).into();
 		let best_block_header = chain.best_block_header().decode();
-		transactions.into_iter()
+		let mut inserted = Vec::with_capacity(transactions.len());
+		let results: Vec<_> = transactions.into_iter()
 			.map(|tx| {
-				if chain.transaction_block(TransactionId::Hash(tx.hash())).is_some() {
-					debug!(target: "miner", "Rejected tx {:?}: already in the blockchain", tx.hash());
+				let hash = tx.hash();
+				if chain.transaction_block(TransactionId::Hash(hash)).is_some() {
+					debug!(target: "miner", "Rejected tx {:?}: already in the blockchain", hash);
 					return Err(Error::Transaction(TransactionError::AlreadyImported));
 				}
 				match self.engine.verify_transaction_basic(&tx, &best_block_header) {
@@ -616,18 +668,30 @@ impl Miner {
 							})
 						}).unwrap_or(default_origin);
 
-						match origin {
+						let res = match origin {
 							TransactionOrigin::Local | TransactionOrigin::RetractedBlock => {
 								transaction_queue.add(tx, origin, min_block, &fetch_account, &gas_required)
 							},
 							TransactionOrigin::External => {
 								transaction_queue.add_with_banlist(tx, &fetch_account, &gas_required)
 							}
+						};
+						if res.is_ok() {
+							inserted.push(hash);
 						}
+						res
 					},
 				}
 			})
-			.collect()
+			.collect();
+
+		if !inserted.is_empty() {
+			for listener in &*self.transaction_listener.read() {
+				listener(&inserted);
+			}
+		}
+
+		results
 	}
 
 	/// Are we allowed to do a non-mandatory reseal?
@@ -706,7 +770,7 @@ impl MinerService for Miner {
 					// give the sender a sufficient balance
 					state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty);
 				}
-				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, struct_logging: analytics.struct_logging, check_nonce: false };
 				let mut ret = Executive::new(&mut state, &env_info, &*self.engine, chain.vm_factory()).transact(t, options)?;
 
 				// TODO gav move this into Executive.
@@ -1015,7 +1079,7 @@ impl MinerService for Miner {
 							},
 							logs: receipt.logs.clone(),
 							log_bloom: receipt.log_bloom,
-							state_root: receipt.state_root,
+							outcome: receipt.outcome.clone(),
 						}
 					})
 			}
@@ -1219,6 +1283,7 @@ mod tests {
 				work_queue_size: 5,
 				enable_resubmission: true,
 				tx_queue_banning: Banning::Disabled,
+				tx_queue_persist_all: false,
 			},
 			GasPricer::new_fixed(0u64.into()),
 			&Spec::new_test(),
@@ -1258,6 +1323,25 @@ mod tests {
 		assert!(!miner.prepare_work_sealing(&client));
 	}
 
+	#[test]
+	fn should_restore_persisted_local_transactions() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		miner.import_own_transaction(&client, PendingTransaction::new(transaction(), None)).unwrap();
+		miner.import_external_transactions(&client, vec![transaction()]).pop().unwrap().unwrap();
+
+		// when
+		let persisted = miner.transactions_to_persist();
+		let restarted = miner();
+		let restored = restarted.restore_transactions(&client, persisted);
+
+		// then
+		assert_eq!(restored, 1);
+		assert_eq!(restarted.pending_transactions().len(), 1);
+		assert_eq!(restarted.local_transactions().len(), 1);
+	}
+
 	#[test]
 	fn should_not_use_pending_block_if_best_block_is_higher() {
 		// given
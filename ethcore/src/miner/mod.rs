@@ -45,14 +45,20 @@ mod banning_queue;
 mod external;
 mod local_transactions;
 mod miner;
+mod persistence;
 mod price_info;
+mod stratum;
 mod transaction_queue;
 mod work_notify;
 
 pub use self::external::{ExternalMiner, ExternalMinerService};
 pub use self::miner::{Miner, MinerOptions, Banning, PendingSet, GasPricer, GasPriceCalibratorOptions, GasLimit};
+pub use self::persistence::PersistedTransaction;
 pub use self::transaction_queue::{TransactionQueue, PrioritizationStrategy, AccountDetails, TransactionOrigin};
 pub use self::local_transactions::{Status as LocalTransactionStatus};
+pub use self::stratum::{Stratum, Error as StratumError, Options as StratumOptions};
+pub use self::work_notify::NotifyWork;
+pub use ethcore_stratum::VardiffConfig;
 pub use client::TransactionImportResult;
 
 use std::collections::BTreeMap;
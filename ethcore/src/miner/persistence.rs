@@ -0,0 +1,77 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction queue contents kept in the client database across restarts.
+
+use rlp::*;
+use transaction::PendingTransaction;
+
+/// Queued transaction to be re-imported after a restart.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PersistedTransaction {
+	/// Transaction together with its activation block.
+	pub pending: PendingTransaction,
+	/// Whether the transaction was submitted locally.
+	pub local: bool,
+}
+
+impl Encodable for PersistedTransaction {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(3)
+			.append(&self.pending.transaction)
+			.append(&self.pending.min_block)
+			.append(&self.local);
+	}
+}
+
+impl Decodable for PersistedTransaction {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let rlp = decoder.as_rlp();
+		Ok(PersistedTransaction {
+			pending: PendingTransaction::new(rlp.val_at(0)?, rlp.val_at(1)?),
+			local: rlp.val_at(2)?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rlp::*;
+	use util::{U256, FromHex};
+	use ethkey::{Generator, Random};
+	use transaction::{Transaction, PendingTransaction, Action};
+	use super::PersistedTransaction;
+
+	#[test]
+	fn encode_decode() {
+		let keypair = Random.generate().unwrap();
+		let transaction = Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: "3331600055".from_hex().unwrap(),
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(keypair.secret(), None);
+
+		let persisted = vec![
+			PersistedTransaction { pending: PendingTransaction::new(transaction.clone(), None), local: true },
+			PersistedTransaction { pending: PendingTransaction::new(transaction, Some(100)), local: false },
+		];
+		let decoded: Vec<PersistedTransaction> = decode(&encode(&persisted));
+		assert_eq!(decoded, persisted);
+	}
+}
@@ -0,0 +1,420 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Client-side stratum job dispatcher and mining notifier handler
+
+use ethcore_stratum::{
+	JobDispatcher, PushWorkHandler, VardiffConfig,
+	Stratum as StratumService, Error as StratumServiceError,
+};
+
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::net::{SocketAddr, AddrParseError};
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+use util::{H64, H256, U256, Mutex, FromHex};
+use ethash::{EthashManager, SeedHashCompute};
+use ethereum::ethash::Ethash;
+use client::Client;
+use miner::{self, Miner, MinerService};
+use rlp;
+
+/// How often the stratum server hands out queued jobs and retargets worker difficulties.
+const MAINTAIN_INTERVAL_MS: u64 = 1000;
+
+/// Configures stratum server options.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Options {
+	/// Network address
+	pub listen_addr: String,
+	/// Port
+	pub port: u16,
+	/// Secret for peers
+	pub secret: Option<H256>,
+	/// Variable share difficulty settings, if enabled
+	pub vardiff: Option<VardiffConfig>,
+}
+
+fn clean_0x(s: &str) -> &str {
+	if s.starts_with("0x") {
+		&s[2..]
+	} else {
+		s
+	}
+}
+
+struct SubmitPayload {
+	nonce: H64,
+	pow_hash: H256,
+	mix_hash: H256,
+}
+
+impl SubmitPayload {
+	fn from_args(job_id: &str, payload: &[String]) -> Result<Self, PayloadError> {
+		if payload.len() != 2 {
+			return Err(PayloadError::ArgumentsAmountUnexpected(payload.len()));
+		}
+
+		let nonce = match clean_0x(&payload[0]).from_hex() {
+			Ok(ref bytes) if bytes.len() == 8 => H64::from_slice(bytes),
+			_ => return Err(PayloadError::InvalidNonce(payload[0].clone())),
+		};
+
+		let pow_hash = match clean_0x(job_id).from_hex() {
+			Ok(ref bytes) if bytes.len() == 32 => H256::from_slice(bytes),
+			_ => return Err(PayloadError::InvalidPowHash(job_id.to_owned())),
+		};
+
+		let mix_hash = match clean_0x(&payload[1]).from_hex() {
+			Ok(ref bytes) if bytes.len() == 32 => H256::from_slice(bytes),
+			_ => return Err(PayloadError::InvalidMixHash(payload[1].clone())),
+		};
+
+		Ok(SubmitPayload {
+			nonce: nonce,
+			pow_hash: pow_hash,
+			mix_hash: mix_hash,
+		})
+	}
+}
+
+#[derive(Debug)]
+enum PayloadError {
+	ArgumentsAmountUnexpected(usize),
+	InvalidNonce(String),
+	InvalidPowHash(String),
+	InvalidMixHash(String),
+}
+
+impl fmt::Display for PayloadError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Debug::fmt(&self, f)
+	}
+}
+
+/// Job dispatcher for stratum service
+pub struct StratumJobDispatcher {
+	seed_compute: Mutex<SeedHashCompute>,
+	pow: EthashManager,
+	client: Weak<Client>,
+	miner: Weak<Miner>,
+}
+
+impl JobDispatcher for StratumJobDispatcher {
+	fn initial(&self) -> Option<String> {
+		// initial payload may contain additional data, not in this case
+		self.current_job()
+	}
+
+	fn job(&self, _worker_id: String) -> Option<String> {
+		self.current_job()
+	}
+
+	/// Checks a share of the job `job_id` (the pow hash) against the worker's target and
+	/// submits the seal to the miner if it also solves the block.
+	/// `payload` is `[nonce, mix_hash]`, both hex-encoded.
+	fn submit(&self, worker_id: String, job_id: String, difficulty: Option<u64>, payload: Vec<String>) -> Result<(), StratumServiceError> {
+		let payload = SubmitPayload::from_args(&job_id, &payload)
+			.map_err(|e| StratumServiceError::InvalidShare(e.to_string()))?;
+
+		trace!(
+			target: "stratum",
+			"submit_work ({}): Decoded: nonce={}, pow_hash={}, mix_hash={}",
+			worker_id,
+			payload.nonce,
+			payload.pow_hash,
+			payload.mix_hash
+		);
+
+		let (client, miner) = match (self.client.upgrade(), self.miner.upgrade()) {
+			(Some(client), Some(miner)) => (client, miner),
+			_ => return Err(StratumServiceError::NoWork),
+		};
+
+		let work = miner.map_sealing_work(&*client, |b| (b.hash(), b.block().header().number(), *b.block().header().difficulty()));
+		let (number, block_difficulty) = match work {
+			Some((pow_hash, number, block_difficulty)) if pow_hash == payload.pow_hash => (number, block_difficulty),
+			_ => return Err(StratumServiceError::StaleShare),
+		};
+
+		let result = self.pow.compute_light(number, &payload.pow_hash.0, payload.nonce.low_u64());
+		let mix_hash = H256(result.mix_hash);
+		if mix_hash != payload.mix_hash {
+			return Err(StratumServiceError::InvalidShare(format!("mix hash mismatch: expected {}, got {}", mix_hash, payload.mix_hash)));
+		}
+
+		let share_difficulty = Ethash::boundary_to_difficulty(&H256(result.value));
+		let target = difficulty.map_or(block_difficulty, U256::from);
+		if share_difficulty < target {
+			return Err(StratumServiceError::InvalidShare(format!("share difficulty {} below target {}", share_difficulty, target)));
+		}
+
+		if share_difficulty >= block_difficulty {
+			let seal = vec![rlp::encode(&payload.mix_hash).to_vec(), rlp::encode(&payload.nonce).to_vec()];
+			if let Err(e) = miner.submit_seal(&*client, payload.pow_hash, seal) {
+				warn!(target: "stratum", "Block solution from worker {} rejected: {:?}", worker_id, e);
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl StratumJobDispatcher {
+	/// New stratum job dispatcher given the miner and client
+	fn new(miner: Weak<Miner>, client: Weak<Client>) -> StratumJobDispatcher {
+		StratumJobDispatcher {
+			seed_compute: Mutex::new(SeedHashCompute::new()),
+			pow: EthashManager::new(),
+			client: client,
+			miner: miner,
+		}
+	}
+
+	/// Serializes payload for stratum service
+	fn payload(&self, pow_hash: H256, difficulty: U256, number: u64) -> String {
+		// TODO: move this to engine
+		let target = Ethash::difficulty_to_boundary(&difficulty);
+		let seed_hash = &self.seed_compute.lock().get_seedhash(number);
+		let seed_hash = H256::from_slice(&seed_hash[..]);
+		format!(
+			r#"["0x{}","0x{}","0x{}","0x{:x}"]"#,
+			pow_hash.hex(), seed_hash.hex(), target.hex(), number
+		)
+	}
+
+	fn current_job(&self) -> Option<String> {
+		match (self.client.upgrade(), self.miner.upgrade()) {
+			(Some(client), Some(miner)) => miner.map_sealing_work(&*client, |b| {
+				let pow_hash = b.hash();
+				let number = b.block().header().number();
+				let difficulty = b.block().header().difficulty();
+
+				self.payload(pow_hash, *difficulty, number)
+			}),
+			_ => None,
+		}
+	}
+}
+
+/// Wrapper for dedicated stratum service
+pub struct Stratum {
+	dispatcher: Arc<StratumJobDispatcher>,
+	service: Arc<StratumService>,
+	stop: Arc<AtomicBool>,
+}
+
+#[derive(Debug)]
+/// Stratum error
+pub enum Error {
+	/// TCP server error
+	Service(String),
+	/// Invalid network address
+	Address(AddrParseError),
+}
+
+impl From<AddrParseError> for Error {
+	fn from(err: AddrParseError) -> Error { Error::Address(err) }
+}
+
+impl super::work_notify::NotifyWork for Stratum {
+	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64) {
+		trace!(target: "stratum", "Notify work");
+
+		self.service.push_work_all(
+			self.dispatcher.payload(pow_hash, difficulty, number)
+		).unwrap_or_else(
+			|e| warn!(target: "stratum", "Error while pushing work: {:?}", e)
+		);
+	}
+}
+
+impl Stratum {
+	/// New stratum job dispatcher, given the miner, client and dedicated stratum service
+	pub fn start(options: &Options, miner: Weak<Miner>, client: Weak<Client>) -> Result<Stratum, Error> {
+		use std::net::IpAddr;
+
+		let dispatcher = Arc::new(StratumJobDispatcher::new(miner, client));
+
+		let service = StratumService::start(
+			&SocketAddr::new(options.listen_addr.parse::<IpAddr>()?, options.port),
+			dispatcher.clone(),
+			options.secret.clone(),
+			options.vardiff.clone(),
+		).map_err(|e| Error::Service(format!("{:?}", e)))?;
+
+		let stop = Arc::new(AtomicBool::new(false));
+		{
+			let service = service.clone();
+			let stop = stop.clone();
+			thread::Builder::new().name("Stratum".into()).spawn(move || {
+				while !stop.load(Ordering::Relaxed) {
+					service.maintain();
+					thread::park_timeout(Duration::from_millis(MAINTAIN_INTERVAL_MS));
+				}
+			}).expect("Error creating stratum maintenance thread");
+		}
+
+		Ok(Stratum { dispatcher: dispatcher, service: service, stop: stop })
+	}
+
+	/// Start STRATUM job dispatcher and register it in the miner
+	pub fn register(options: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<(), Error> {
+		let stratum = Stratum::start(options, Arc::downgrade(&miner), client)?;
+		miner.push_notifier(Box::new(stratum) as Box<miner::NotifyWork>);
+		Ok(())
+	}
+}
+
+impl Drop for Stratum {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{Read, Write};
+	use std::net::{TcpStream, SocketAddr};
+	use std::str::FromStr;
+	use std::sync::Arc;
+	use std::time::{Duration, Instant};
+
+	use util::{H256, U256, DatabaseConfig};
+	use devtools::RandomTempPath;
+	use io::IoChannel;
+	use ethash::EthashManager;
+	use ethcore_stratum::{JobDispatcher, Error as StratumServiceError};
+	use client::{BlockChainClient, Client, ClientConfig};
+	use db::NUM_COLUMNS;
+	use miner::{Miner, MinerService, NotifyWork};
+	use spec::Spec;
+	use super::{Options, Stratum, StratumJobDispatcher};
+
+	fn low_difficulty_client(path: &RandomTempPath) -> Arc<Client> {
+		let spec = Spec::load(include_bytes!("../../res/ethash_low_difficulty.json") as &[u8]).unwrap();
+		Client::new(
+			ClientConfig::default(),
+			&spec,
+			path.as_path(),
+			Arc::new(Miner::with_spec(&spec)),
+			IoChannel::disconnected(),
+			&DatabaseConfig::with_columns(NUM_COLUMNS),
+		).unwrap()
+	}
+
+	fn request(stream: &mut TcpStream, id: u64, request: &str) -> String {
+		stream.write_all(request.as_bytes()).unwrap();
+		read_until(stream, &format!(r#""id":{}}}"#, id))
+	}
+
+	// reads from the stream until `pattern` shows up, the server may push jobs in between.
+	fn read_until(stream: &mut TcpStream, pattern: &str) -> String {
+		let deadline = Instant::now() + Duration::from_secs(30);
+		let mut buf = [0u8; 4096];
+		let mut received = String::new();
+		while !received.contains(pattern) && Instant::now() < deadline {
+			match stream.read(&mut buf) {
+				Ok(0) => break,
+				Ok(read) => received.push_str(&String::from_utf8_lossy(&buf[..read])),
+				Err(_) => {},
+			}
+		}
+		received
+	}
+
+	struct Solution {
+		pow_hash: H256,
+		difficulty: U256,
+		number: u64,
+		nonce: String,
+		mix_hash: String,
+	}
+
+	// solves the current sealing work of the client's miner.
+	fn solve(client: &Arc<Client>) -> Solution {
+		let miner = client.miner();
+		let (pow_hash, difficulty, number) = miner.map_sealing_work(&**client, |b| {
+			(b.hash(), *b.block().header().difficulty(), b.block().header().number())
+		}).unwrap();
+		let nonce = 1u64;
+		let pow = EthashManager::new().compute_light(number, &pow_hash.0, nonce);
+		Solution {
+			pow_hash: pow_hash,
+			difficulty: difficulty,
+			number: number,
+			nonce: format!("0x{:016x}", nonce),
+			mix_hash: format!("0x{}", H256(pow.mix_hash).hex()),
+		}
+	}
+
+	#[test]
+	fn imports_blocks_solved_by_workers() {
+		let path = RandomTempPath::new();
+		let client = low_difficulty_client(&path);
+		let solution = solve(&client);
+		let options = Options { listen_addr: "127.0.0.1".into(), port: 19970, secret: None, vardiff: None };
+		let stratum = Stratum::start(&options, Arc::downgrade(&client.miner()), Arc::downgrade(&client)).unwrap();
+
+		let mut stream = TcpStream::connect(&SocketAddr::from_str("127.0.0.1:19970").unwrap()).unwrap();
+		stream.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+
+		let subscribed = request(&mut stream, 1, r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 1}"#);
+		assert!(subscribed.contains(&format!("0x{}", solution.pow_hash.hex())));
+		let authorized = request(&mut stream, 2, r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 2}"#);
+		assert!(authorized.contains(r#""result":true"#));
+
+		stratum.notify(solution.pow_hash, solution.difficulty, solution.number);
+		let job = read_until(&mut stream, &format!("0x{}", solution.pow_hash.hex()));
+		assert!(job.contains(&format!("0x{}", solution.pow_hash.hex())));
+
+		let rejected = request(&mut stream, 3, &format!(
+			r#"{{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "0x{}", "{}", "0x{}"], "id": 3}}"#,
+			solution.pow_hash.hex(), solution.nonce, H256::from(1).hex()
+		));
+		assert!(rejected.contains(r#""result":false"#));
+		assert_eq!(client.chain_info().best_block_number, 0);
+
+		let accepted = request(&mut stream, 4, &format!(
+			r#"{{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "0x{}", "{}", "{}"], "id": 4}}"#,
+			solution.pow_hash.hex(), solution.nonce, solution.mix_hash
+		));
+		assert!(accepted.contains(r#""result":true"#));
+		assert_eq!(client.chain_info().best_block_number, 1);
+	}
+
+	#[test]
+	fn rejects_shares_below_worker_difficulty() {
+		let path = RandomTempPath::new();
+		let client = low_difficulty_client(&path);
+		let dispatcher = StratumJobDispatcher::new(Arc::downgrade(&client.miner()), Arc::downgrade(&client));
+
+		let solution = solve(&client);
+		let job_id = format!("0x{}", solution.pow_hash.hex());
+		let payload = vec![solution.nonce.clone(), solution.mix_hash.clone()];
+		match dispatcher.submit("miner1".into(), job_id.clone(), Some(u64::max_value()), payload.clone()) {
+			Err(StratumServiceError::InvalidShare(_)) => {},
+			other => panic!("Unexpected submit result: {:?}", other),
+		}
+		assert_eq!(client.chain_info().best_block_number, 0);
+
+		assert!(dispatcher.submit("miner1".into(), job_id, Some(1), payload).is_ok());
+		assert_eq!(client.chain_info().best_block_number, 1);
+	}
+}
@@ -1019,6 +1019,16 @@ impl TransactionQueue {
 			.collect()
 	}
 
+	/// Returns all transactions in the queue (both current and future) together with their origin,
+	/// ordered by sender and nonce.
+	pub fn queued_transactions(&self) -> Vec<(PendingTransaction, TransactionOrigin)> {
+		let mut transactions = self.by_hash.values()
+			.map(|t| (PendingTransaction::new(t.transaction.clone(), t.min_block), t.origin))
+			.collect::<Vec<_>>();
+		transactions.sort_by_key(|&(ref t, _)| (t.transaction.sender().expect("Sender is verified when transaction is added; qed"), t.transaction.nonce));
+		transactions
+	}
+
 	/// Returns local transactions (some of them might not be part of the queue anymore).
 	pub fn local_transactions(&self) -> &LinkedHashMap<H256, LocalTransactionStatus> {
 		self.local_transactions.all_transactions()
@@ -1288,7 +1298,7 @@ mod test {
 	use super::{TransactionSet, TransactionOrder, VerifiedTransaction};
 	use miner::local_transactions::LocalTransactionsList;
 	use client::TransactionImportResult;
-	use transaction::{SignedTransaction, Transaction, Action};
+	use transaction::{SignedTransaction, PendingTransaction, Transaction, Action};
 
 	fn unwrap_tx_err(err: Result<TransactionImportResult, Error>) -> TransactionError {
 		match err.unwrap_err() {
@@ -2015,6 +2025,24 @@ mod test {
 		assert_eq!(top.len(), 2);
 	}
 
+	#[test]
+	fn should_return_queued_transactions_with_origin() {
+		// given
+		let mut txq = TransactionQueue::default();
+
+		let (tx, tx2) = new_tx_pair_default(2.into(), 0.into());
+
+		// when
+		txq.add(tx2.clone(), TransactionOrigin::External, Some(10), &default_account_details, &gas_estimator).unwrap();
+		txq.add(tx.clone(), TransactionOrigin::Local, None, &default_account_details, &gas_estimator).unwrap();
+
+		// then
+		let queued = txq.queued_transactions();
+		assert_eq!(queued.len(), 2);
+		assert_eq!(queued[0], (PendingTransaction::new(tx, None), TransactionOrigin::Local));
+		assert_eq!(queued[1], (PendingTransaction::new(tx2, Some(10)), TransactionOrigin::External));
+	}
+
 	#[test]
 	fn should_put_transaction_to_futures_if_gap_detected() {
 		// given
@@ -0,0 +1,111 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Messages exchanged between the parties of a private contract.
+
+use rlp::*;
+use util::{Bytes, Address, H256, H520, Hashable};
+use ethkey::Signature;
+
+/// Private transaction encrypted for the parties of a private contract.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PrivateTransaction {
+	/// Address of the private contract.
+	pub contract: Address,
+	/// Signed transaction encrypted for each of the parties, validators first, in the order of the contract.
+	pub encrypted: Vec<Bytes>,
+}
+
+impl PrivateTransaction {
+	/// Hash of the message, identifying the private transaction.
+	pub fn hash(&self) -> H256 {
+		encode(self).sha3()
+	}
+}
+
+impl Encodable for PrivateTransaction {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2)
+			.append(&self.contract)
+			.append(&self.encrypted);
+	}
+}
+
+impl Decodable for PrivateTransaction {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let rlp = decoder.as_rlp();
+		Ok(PrivateTransaction {
+			contract: rlp.val_at(0)?,
+			encrypted: rlp.val_at(1)?,
+		})
+	}
+}
+
+/// Private state resulting from a private transaction, signed by one of the validators.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SignedPrivateTransaction {
+	/// Hash of the private transaction message.
+	pub private_transaction_hash: H256,
+	/// Root of the private state after executing the transaction.
+	pub state: H256,
+	/// Validator's signature of the state change, see `state_change_hash`.
+	pub signature: Signature,
+}
+
+impl Encodable for SignedPrivateTransaction {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(3)
+			.append(&self.private_transaction_hash)
+			.append(&self.state)
+			.append(&H520::from(self.signature.clone()));
+	}
+}
+
+impl Decodable for SignedPrivateTransaction {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let rlp = decoder.as_rlp();
+		Ok(SignedPrivateTransaction {
+			private_transaction_hash: rlp.val_at(0)?,
+			state: rlp.val_at(1)?,
+			signature: rlp.val_at::<H520>(2)?.into(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rlp::*;
+	use util::{H256, H520, FixedHash};
+	use super::{PrivateTransaction, SignedPrivateTransaction};
+
+	#[test]
+	fn encode_decode() {
+		let transaction = PrivateTransaction {
+			contract: 5.into(),
+			encrypted: vec![vec![1, 2, 3], vec![4, 5]],
+		};
+		let decoded: PrivateTransaction = decode(&encode(&transaction));
+		assert_eq!(decoded, transaction);
+
+		let signed = SignedPrivateTransaction {
+			private_transaction_hash: transaction.hash(),
+			state: H256::random(),
+			signature: H520::random().into(),
+		};
+		let decoded: SignedPrivateTransaction = decode(&encode(&signed));
+		assert_eq!(decoded, signed);
+	}
+}
@@ -0,0 +1,678 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions.
+//!
+//! A private contract lives in a private state kept beside the public state, known only to
+//! the parties of the contract. Transactions to it are encrypted for each of its parties, who
+//! re-execute them against the private state to follow its changes. Validators among them sign
+//! `sha3(contract ‖ previous state root ‖ new state root ‖ nonce)`. Once all validators have signed,
+//! a public marker transaction anchors the new root in the contract's public counterpart by calling
+//! `setState(bytes32 prevState, bytes32 newState, uint8[] v, bytes32[] r, bytes32[] s)`.
+//!
+//! The counterpart exposes the anchored root as `state() returns (bytes32)` and the number of
+//! changes anchored so far as `nonce() returns (uint256)`. It only accepts a change if `prevState`
+//! is the anchored root and the signatures cover its current nonce, which it then increments,
+//! so that signed changes can't be replayed.
+
+mod messages;
+
+pub use self::messages::{PrivateTransaction, SignedPrivateTransaction};
+
+use std::fmt;
+use std::path::Path;
+use std::collections::HashMap;
+use ethabi;
+use ethcrypto;
+use ethkey::{Public, Signature, public_to_address, recover};
+use rlp::*;
+use util::*;
+use util::journaldb::{self, Algorithm};
+use util::kvdb::{Database, DatabaseConfig};
+use account_provider::{AccountProvider, Error as AccountError};
+use client::{BlockId, MiningBlockChainClient, EnvInfo, Executed, Executive, TransactOptions};
+use engines::Engine;
+use error::ExecutionError;
+use factory::Factories;
+use miner::MinerService;
+use state::State;
+use state_db::StateDB;
+use transaction::{Transaction, SignedTransaction, PendingTransaction, Action};
+
+/// Size of the account cache of the private state.
+const STATE_CACHE_SIZE: usize = 1024 * 1024;
+/// Gas given to the public marker transaction.
+const MARKER_GAS: u64 = 500_000;
+
+/// Private transactions errors.
+#[derive(Debug)]
+pub enum Error {
+	/// Contract is not registered as private on this node.
+	UnknownContract(Address),
+	/// Transaction does not call a contract.
+	NotContractCall,
+	/// None of the local accounts is a party of the contract.
+	NotParty,
+	/// No private transaction with given hash is awaiting signatures.
+	UnknownTransaction(H256),
+	/// Signature is not from a validator or is for a different state.
+	InvalidSignature,
+	/// Private state with given root is not available locally.
+	StateUnavailable(H256),
+	/// No account is configured to send public marker transactions.
+	NoSigner,
+	/// Execution of the transaction against the private state failed.
+	Execution(ExecutionError),
+	/// Encryption or decryption failed.
+	Crypto(ethcrypto::Error),
+	/// Validator or marker signing failed.
+	Account(AccountError),
+	/// Message is malformed.
+	Decoder(DecoderError),
+	/// Public counterpart of the contract can't be read or called.
+	Anchor(String),
+	/// Private state database error.
+	Database(String),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+		match *self {
+			Error::UnknownContract(ref address) => write!(f, "Contract {} is not a known private contract", address),
+			Error::NotContractCall => write!(f, "Private transaction must call a private contract"),
+			Error::NotParty => write!(f, "No local account is a party of the contract"),
+			Error::UnknownTransaction(ref hash) => write!(f, "Private transaction {} is not awaiting signatures", hash),
+			Error::InvalidSignature => write!(f, "Signature is not from a validator of the resulting state"),
+			Error::StateUnavailable(ref root) => write!(f, "Private state {} is not available", root),
+			Error::NoSigner => write!(f, "No account configured to send marker transactions"),
+			Error::Execution(ref e) => write!(f, "Private transaction execution failed: {}", e),
+			Error::Crypto(ref e) => write!(f, "{}", e),
+			Error::Account(ref e) => write!(f, "{}", e),
+			Error::Decoder(ref e) => write!(f, "Malformed message: {}", e),
+			Error::Anchor(ref e) => write!(f, "Public contract error: {}", e),
+			Error::Database(ref e) => write!(f, "Private state database error: {}", e),
+		}
+	}
+}
+
+impl From<ExecutionError> for Error {
+	fn from(e: ExecutionError) -> Self {
+		Error::Execution(e)
+	}
+}
+
+impl From<ethcrypto::Error> for Error {
+	fn from(e: ethcrypto::Error) -> Self {
+		Error::Crypto(e)
+	}
+}
+
+impl From<AccountError> for Error {
+	fn from(e: AccountError) -> Self {
+		Error::Account(e)
+	}
+}
+
+impl From<DecoderError> for Error {
+	fn from(e: DecoderError) -> Self {
+		Error::Decoder(e)
+	}
+}
+
+/// Contract whose state is only known to its parties.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PrivateContract {
+	/// Address of the contract, both in the private state and on the public chain.
+	pub address: Address,
+	/// Public keys of the validators, which have to sign every state change.
+	pub validators: Vec<Public>,
+	/// Public keys of the other parties, which follow the state changes without signing them.
+	pub parties: Vec<Public>,
+	/// Root of the private state the contract was registered with.
+	pub initial_state: H256,
+}
+
+impl PrivateContract {
+	/// Public keys of all parties, validators first; private transactions are encrypted for each in this order.
+	pub fn all_parties(&self) -> Vec<Public> {
+		self.validators.iter().chain(self.parties.iter()).cloned().collect()
+	}
+}
+
+impl Encodable for PrivateContract {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(4)
+			.append(&self.address)
+			.append(&self.validators)
+			.append(&self.parties)
+			.append(&self.initial_state);
+	}
+}
+
+impl Decodable for PrivateContract {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let rlp = decoder.as_rlp();
+		Ok(PrivateContract {
+			address: rlp.val_at(0)?,
+			validators: rlp.val_at(1)?,
+			parties: rlp.val_at(2)?,
+			initial_state: rlp.val_at(3)?,
+		})
+	}
+}
+
+/// Outcome of sending a private transaction.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Receipt {
+	/// Hash of the private transaction message.
+	pub hash: H256,
+	/// Address of the private contract.
+	pub contract: Address,
+	/// Encoded `PrivateTransaction` to be delivered to all parties of the contract.
+	pub message: Bytes,
+	/// Hash of the public marker transaction if the local validators were enough to sign the new state.
+	pub marker: Option<H256>,
+}
+
+/// Private transaction sent by this node, awaiting validators' signatures.
+struct Signing {
+	contract: Address,
+	prev_state: H256,
+	state: H256,
+	nonce: U256,
+	signatures: Vec<Option<Signature>>,
+}
+
+impl Signing {
+	fn hash(&self) -> H256 {
+		state_change_hash(&self.contract, &self.prev_state, &self.state, &self.nonce)
+	}
+}
+
+/// Hash signed by validators to allow changing the anchored state of `contract` from
+/// `prev_state` to `state` when the counterpart is at `nonce`.
+pub fn state_change_hash(contract: &Address, prev_state: &H256, state: &H256, nonce: &U256) -> H256 {
+	let mut nonce_bytes = [0u8; 32];
+	nonce.to_big_endian(&mut nonce_bytes);
+
+	let mut data = contract.to_vec();
+	data.extend_from_slice(prev_state);
+	data.extend_from_slice(state);
+	data.extend_from_slice(&nonce_bytes);
+	data.sha3()
+}
+
+/// Executes private transactions, collects validators' signatures and anchors private
+/// states on the public chain.
+pub struct Provider {
+	db: Arc<Database>,
+	engine: Arc<Engine>,
+	accounts: Arc<AccountProvider>,
+	signer: Option<Address>,
+	anchor: ethabi::Contract,
+	contracts: RwLock<HashMap<Address, PrivateContract>>,
+	signing: Mutex<HashMap<H256, Signing>>,
+}
+
+impl Provider {
+	/// Open the private state database at `path`. Marker transactions are sent from `signer`.
+	pub fn new(path: &Path, engine: Arc<Engine>, accounts: Arc<AccountProvider>, signer: Option<Address>) -> Result<Self, Error> {
+		let config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+		let db = Database::open(&config, &path.to_str().expect("DB path could not be converted to string."))
+			.map_err(Error::Database)?;
+
+		let mut contracts = HashMap::new();
+		for (_, value) in db.iter(::db::COL_EXTRA) {
+			let contract: PrivateContract = UntrustedRlp::new(&value).as_val()?;
+			contracts.insert(contract.address, contract);
+		}
+
+		Ok(Provider {
+			db: Arc::new(db),
+			engine: engine,
+			accounts: accounts,
+			signer: signer,
+			anchor: ethabi::Contract::new(ethabi::Interface::load(b"[{\"constant\":true,\"inputs\":[],\"name\":\"state\",\"outputs\":[{\"name\":\"\",\"type\":\"bytes32\"}],\"payable\":false,\"type\":\"function\"},{\"constant\":true,\"inputs\":[],\"name\":\"nonce\",\"outputs\":[{\"name\":\"\",\"type\":\"uint256\"}],\"payable\":false,\"type\":\"function\"},{\"constant\":false,\"inputs\":[{\"name\":\"prevState\",\"type\":\"bytes32\"},{\"name\":\"newState\",\"type\":\"bytes32\"},{\"name\":\"v\",\"type\":\"uint8[]\"},{\"name\":\"r\",\"type\":\"bytes32[]\"},{\"name\":\"s\",\"type\":\"bytes32[]\"}],\"name\":\"setState\",\"outputs\":[],\"payable\":false,\"type\":\"function\"}]").expect("JSON is autogenerated; qed")),
+			contracts: RwLock::new(contracts),
+			signing: Mutex::new(HashMap::new()),
+		})
+	}
+
+	/// Register a private contract with the given runtime `code`. All parties have to register
+	/// the contract with the same arguments to start from the same private state.
+	/// Returns the root of the initial private state.
+	pub fn register_contract(&self, address: Address, code: Bytes, validators: Vec<Public>, parties: Vec<Public>) -> Result<H256, Error> {
+		let mut state = State::new(self.state_db(), self.engine.account_start_nonce(), Factories::default());
+		state.new_contract(&address, U256::zero(), U256::zero());
+		state.init_code(&address, code);
+		state.commit().map_err(|e| Error::Database(format!("{}", e)))?;
+		let (root, state_db) = state.drop();
+
+		let contract = PrivateContract {
+			address: address,
+			validators: validators,
+			parties: parties,
+			initial_state: root,
+		};
+
+		let mut batch = DBTransaction::new(&self.db);
+		self.journal(&mut batch, state_db, &root)?;
+		batch.put(::db::COL_EXTRA, &address, &encode(&contract));
+		self.db.write(batch).map_err(Error::Database)?;
+
+		self.contracts.write().insert(address, contract);
+		Ok(root)
+	}
+
+	/// Addresses of the registered private contracts.
+	pub fn contracts(&self) -> Vec<Address> {
+		self.contracts.read().keys().cloned().collect()
+	}
+
+	/// Execute a signed transaction to a private contract and encrypt it for all of its parties.
+	/// Local validator accounts which are unlocked sign the resulting state straight away.
+	pub fn send_transaction(&self, chain: &MiningBlockChainClient, miner: &MinerService, transaction: SignedTransaction) -> Result<Receipt, Error> {
+		let contract = self.contract_for(&transaction)?;
+		let (state, nonce) = self.anchored_state(chain, &contract)?;
+		let (new_state, _) = self.execute(state, &transaction, true)?;
+
+		let plain = encode(&transaction);
+		let encrypted = contract.all_parties().iter()
+			.map(|public| ethcrypto::ecies::encrypt(public, &[], &plain))
+			.collect::<Result<Vec<_>, _>>()?;
+		let private_transaction = PrivateTransaction {
+			contract: contract.address,
+			encrypted: encrypted,
+		};
+		let hash = private_transaction.hash();
+
+		let mut signing = Signing {
+			contract: contract.address,
+			prev_state: state,
+			state: new_state,
+			nonce: nonce,
+			signatures: vec![None; contract.validators.len()],
+		};
+		let change_hash = signing.hash();
+		for (public, signature) in contract.validators.iter().zip(signing.signatures.iter_mut()) {
+			let validator = public_to_address(public);
+			if self.is_local(&validator) {
+				*signature = self.accounts.sign(validator, None, change_hash).map_err(|e| {
+					debug!(target: "privatetx", "Local validator {} can't sign private state: {}", validator, e);
+				}).ok();
+			}
+		}
+
+		let marker = match signing.signatures.iter().all(Option::is_some) {
+			true => Some(self.publish(chain, miner, &signing)?),
+			false => {
+				self.signing.lock().insert(hash, signing);
+				None
+			},
+		};
+
+		Ok(Receipt {
+			hash: hash,
+			contract: contract.address,
+			message: encode(&private_transaction).to_vec(),
+			marker: marker,
+		})
+	}
+
+	/// Import a private transaction as one of the contract's parties: decrypt and re-execute it,
+	/// storing the resulting state. Validators also sign the state change; the returned message
+	/// goes back to the sender.
+	pub fn import_private_transaction(&self, chain: &MiningBlockChainClient, message: &[u8]) -> Result<Option<SignedPrivateTransaction>, Error> {
+		let private_transaction: PrivateTransaction = UntrustedRlp::new(message).as_val()?;
+		let contract = self.contracts.read().get(&private_transaction.contract).cloned()
+			.ok_or(Error::UnknownContract(private_transaction.contract))?;
+
+		let (index, party, encrypted) = contract.all_parties().iter()
+			.map(public_to_address)
+			.zip(private_transaction.encrypted.iter())
+			.enumerate()
+			.find(|&(_, (ref party, _))| self.is_local(party))
+			.map(|(index, (party, encrypted))| (index, party, encrypted))
+			.ok_or(Error::NotParty)?;
+
+		let plain = self.accounts.decrypt(party, None, &[], encrypted)?;
+		let transaction: SignedTransaction = UntrustedRlp::new(&plain).as_val()?;
+		if transaction.action != Action::Call(contract.address) {
+			return Err(Error::NotContractCall);
+		}
+
+		let (state, nonce) = self.anchored_state(chain, &contract)?;
+		let (new_state, _) = self.execute(state, &transaction, true)?;
+		if index >= contract.validators.len() {
+			return Ok(None);
+		}
+
+		let signature = self.accounts.sign(party, None, state_change_hash(&contract.address, &state, &new_state, &nonce))?;
+		Ok(Some(SignedPrivateTransaction {
+			private_transaction_hash: private_transaction.hash(),
+			state: new_state,
+			signature: signature,
+		}))
+	}
+
+	/// Add a validator's signature to a private transaction sent by this node. Once all
+	/// validators have signed, the marker transaction is sent and its hash returned.
+	pub fn import_signature(&self, chain: &MiningBlockChainClient, miner: &MinerService, message: &[u8]) -> Result<Option<H256>, Error> {
+		let signed: SignedPrivateTransaction = UntrustedRlp::new(message).as_val()?;
+		let hash = signed.private_transaction_hash;
+
+		let complete = {
+			let mut signing = self.signing.lock();
+			let complete = {
+				let pending = signing.get_mut(&hash).ok_or(Error::UnknownTransaction(hash))?;
+				if pending.state != signed.state {
+					return Err(Error::InvalidSignature);
+				}
+				// signatures of the same change for another anchored state or nonce don't recover to a validator
+				let public = recover(&signed.signature, &pending.hash()).map_err(|_| Error::InvalidSignature)?;
+				let contract = self.contracts.read().get(&pending.contract).cloned()
+					.ok_or(Error::UnknownContract(pending.contract))?;
+				let index = contract.validators.iter().position(|v| *v == public).ok_or(Error::InvalidSignature)?;
+				pending.signatures[index] = Some(signed.signature);
+				pending.signatures.iter().all(Option::is_some)
+			};
+			match complete {
+				true => signing.remove(&hash),
+				false => None,
+			}
+		};
+
+		match complete {
+			Some(signing) => self.publish(chain, miner, &signing).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	/// Execute a call against the current private state of a contract without changing it.
+	pub fn call(&self, chain: &MiningBlockChainClient, transaction: &SignedTransaction) -> Result<Executed, Error> {
+		let contract = self.contract_for(transaction)?;
+		let (state, _) = self.anchored_state(chain, &contract)?;
+		self.execute(state, transaction, false).map(|(_, executed)| executed)
+	}
+
+	fn contract_for(&self, transaction: &SignedTransaction) -> Result<PrivateContract, Error> {
+		match transaction.action {
+			Action::Call(ref address) => self.contracts.read().get(address).cloned().ok_or(Error::UnknownContract(*address)),
+			Action::Create => Err(Error::NotContractCall),
+		}
+	}
+
+	fn is_local(&self, address: &Address) -> bool {
+		self.accounts.accounts().map(|accounts| accounts.contains(address)).unwrap_or(false)
+	}
+
+	fn state_db(&self) -> StateDB {
+		StateDB::new(journaldb::new(self.db.clone(), Algorithm::Archive, ::db::COL_STATE), STATE_CACHE_SIZE)
+	}
+
+	fn journal(&self, batch: &mut DBTransaction, mut state_db: StateDB, root: &H256) -> Result<(), Error> {
+		state_db.journal_under(batch, 0, root).map(|_| ()).map_err(|e| Error::Database(format!("{}", e)))
+	}
+
+	/// Private state currently anchored by the public counterpart of the contract, and its nonce.
+	/// Falls back to the initial state until the first marker transaction is mined.
+	fn anchored_state(&self, chain: &MiningBlockChainClient, contract: &PrivateContract) -> Result<(H256, U256), Error> {
+		let anchored = H256::from_slice(&self.call_anchor(chain, contract, "state")?);
+		let nonce = U256::from(&self.call_anchor(chain, contract, "nonce")?[..]);
+
+		let state = match anchored.is_zero() {
+			true => contract.initial_state,
+			false => anchored,
+		};
+		match self.state_db().journal_db().contains(&state) {
+			true => Ok((state, nonce)),
+			false => Err(Error::StateUnavailable(state)),
+		}
+	}
+
+	/// Call the constant function `name` of the public counterpart, returning its 32-byte result.
+	/// Returns zero until the counterpart is deployed.
+	fn call_anchor(&self, chain: &MiningBlockChainClient, contract: &PrivateContract, name: &str) -> Result<[u8; 32], Error> {
+		let function = self.anchor.function(name.into()).map_err(|e| Error::Anchor(format!("{:?}", e)))?;
+		let data = function.encode_call(vec![]).map_err(|e| Error::Anchor(format!("{:?}", e)))?;
+		let output = chain.call_contract(BlockId::Latest, contract.address, data).map_err(Error::Anchor)?;
+		if output.is_empty() {
+			return Ok([0u8; 32]);
+		}
+
+		let token = function.decode_output(output).map_err(|e| Error::Anchor(format!("{:?}", e)))?.into_iter().next();
+		match token {
+			Some(ethabi::Token::Uint(word)) => Ok(word),
+			Some(ethabi::Token::FixedBytes(ref bytes)) if bytes.len() == 32 => {
+				let mut word = [0u8; 32];
+				word.copy_from_slice(bytes);
+				Ok(word)
+			},
+			_ => Err(Error::Anchor(format!("Invalid {} returned", name))),
+		}
+	}
+
+	/// Execute `transaction` on top of the private state `root`, returning the new root.
+	/// With `commit` the new state is stored and nonces are checked.
+	fn execute(&self, root: H256, transaction: &SignedTransaction, commit: bool) -> Result<(H256, Executed), Error> {
+		let factories = Factories::default();
+		let mut state = State::from_existing(self.state_db(), root, self.engine.account_start_nonce(), factories.clone())
+			.map_err(|_| Error::StateUnavailable(root))?;
+
+		// private contracts see no public block data, so every validator executes in the same environment.
+		let env_info = EnvInfo {
+			gas_limit: U256::max_value(),
+			..Default::default()
+		};
+		let options = TransactOptions { tracing: false, vm_tracing: false, struct_logging: None, check_nonce: commit };
+		let executed = Executive::new(&mut state, &env_info, &*self.engine, &factories.vm).transact(transaction, options)?;
+		state.commit().map_err(|e| Error::Database(format!("{}", e)))?;
+		let (new_root, state_db) = state.drop();
+
+		if commit {
+			let mut batch = DBTransaction::new(&self.db);
+			self.journal(&mut batch, state_db, &new_root)?;
+			self.db.write(batch).map_err(Error::Database)?;
+		}
+		Ok((new_root, executed))
+	}
+
+	/// Send the public marker transaction anchoring a fully signed private state.
+	fn publish(&self, chain: &MiningBlockChainClient, miner: &MinerService, signing: &Signing) -> Result<H256, Error> {
+		let signer = self.signer.ok_or(Error::NoSigner)?;
+		let signatures = signing.signatures.iter()
+			.map(|s| s.as_ref().expect("marker is published once all validators signed; qed"))
+			.collect::<Vec<_>>();
+
+		let function = self.anchor.function("setState".into()).map_err(|e| Error::Anchor(format!("{:?}", e)))?;
+		let data = function.encode_call(vec![
+			ethabi::Token::FixedBytes(signing.prev_state.to_vec()),
+			ethabi::Token::FixedBytes(signing.state.to_vec()),
+			ethabi::Token::Array(signatures.iter().map(|s| ethabi::Token::Uint({ let mut r = [0u8; 32]; U256::from(s.v() as u64 + 27).to_big_endian(&mut r); r })).collect()),
+			ethabi::Token::Array(signatures.iter().map(|s| ethabi::Token::FixedBytes(s.r().to_vec())).collect()),
+			ethabi::Token::Array(signatures.iter().map(|s| ethabi::Token::FixedBytes(s.s().to_vec())).collect()),
+		]).map_err(|e| Error::Anchor(format!("{:?}", e)))?;
+
+		let network_id = chain.signing_network_id();
+		let transaction = Transaction {
+			nonce: miner.last_nonce(&signer).map(|n| n + U256::one()).unwrap_or_else(|| chain.latest_nonce(&signer)),
+			action: Action::Call(signing.contract),
+			gas: MARKER_GAS.into(),
+			gas_price: miner.sensible_gas_price(),
+			value: U256::zero(),
+			data: data,
+		};
+		let signature = self.accounts.sign(signer, None, transaction.hash(network_id))?;
+		let transaction = transaction.with_signature(signature, network_id);
+		let hash = transaction.hash();
+
+		miner.import_own_transaction(chain, PendingTransaction::new(transaction, None))
+			.map_err(|e| Error::Anchor(format!("{:?}", e)))?;
+		trace!(target: "privatetx", "Anchoring private state {} of {} with {}", signing.state, signing.contract, hash);
+		Ok(hash)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use rlp::*;
+	use util::*;
+	use devtools::RandomTempPath;
+	use ethkey::{Generator, Random, KeyPair, sign};
+	use account_provider::AccountProvider;
+	use client::TestBlockChainClient;
+	use miner::{Miner, MinerService};
+	use spec::Spec;
+	use transaction::{Transaction, Action};
+	use super::{Provider, SignedPrivateTransaction, state_change_hash};
+
+	/// Returns the stored word for empty calldata, otherwise stores the first calldata word.
+	const STORE_CODE: &'static str = "36600f5760005460005260206000f35b60003560005500";
+
+	fn provider(path: &RandomTempPath, key: Option<&KeyPair>, signer: Option<&KeyPair>) -> Provider {
+		let accounts = Arc::new(AccountProvider::transient_provider());
+		for k in key.into_iter().chain(signer) {
+			let address = accounts.insert_account(k.secret().clone(), "").unwrap();
+			accounts.unlock_account_permanently(address, "".into()).unwrap();
+		}
+		Provider::new(path.as_path(), Spec::new_test().engine, accounts, signer.map(|s| s.address())).unwrap()
+	}
+
+	fn store(sender: &KeyPair, contract: Address, nonce: u64, value: u64) -> ::transaction::SignedTransaction {
+		Transaction {
+			nonce: nonce.into(),
+			action: Action::Call(contract),
+			gas: 100_000.into(),
+			gas_price: U256::zero(),
+			value: U256::zero(),
+			data: H256::from(value).to_vec(),
+		}.sign(sender.secret(), None)
+	}
+
+	#[test]
+	fn should_anchor_state_signed_by_local_validator() {
+		let path = RandomTempPath::new();
+		let validator = Random.generate().unwrap();
+		let provider = provider(&path, Some(&validator), Some(&validator));
+		let client = TestBlockChainClient::default();
+		client.set_balance(validator.address(), U256::from(1_000_000_000_000_000_000u64));
+		let miner = Miner::with_spec(&Spec::new_test());
+
+		let contract = Address::from(0x1234);
+		let initial = provider.register_contract(contract, STORE_CODE.from_hex().unwrap(), vec![validator.public().clone()], vec![]).unwrap();
+
+		let sender = Random.generate().unwrap();
+		let receipt = provider.send_transaction(&client, &miner, store(&sender, contract, 0, 5)).unwrap();
+		let marker = receipt.marker.expect("the only validator is local; qed");
+
+		let pending = miner.pending_transactions();
+		assert_eq!(pending.len(), 1);
+		assert_eq!(pending[0].transaction.hash(), marker);
+		assert_eq!(pending[0].transaction.action, Action::Call(contract));
+		assert_eq!(H256::from_slice(&pending[0].transaction.data[4..36]), initial);
+		let new_state = H256::from_slice(&pending[0].transaction.data[36..68]);
+		assert!(new_state != initial);
+
+		let read = Transaction {
+			nonce: 0.into(),
+			action: Action::Call(contract),
+			gas: 100_000.into(),
+			gas_price: U256::zero(),
+			value: U256::zero(),
+			data: vec![],
+		}.fake_sign(sender.address());
+		assert_eq!(provider.execute(new_state, &read, false).unwrap().1.output, H256::from(5).to_vec());
+		assert_eq!(provider.execute(initial, &read, false).unwrap().1.output, H256::zero().to_vec());
+	}
+
+	#[test]
+	fn should_collect_signatures_of_remote_validators() {
+		let (sender_path, validator_path, party_path) = (RandomTempPath::new(), RandomTempPath::new(), RandomTempPath::new());
+		let (validator, party, signer) = (Random.generate().unwrap(), Random.generate().unwrap(), Random.generate().unwrap());
+		let sender_provider = provider(&sender_path, None, Some(&signer));
+		let validator_provider = provider(&validator_path, Some(&validator), None);
+		let party_provider = provider(&party_path, Some(&party), None);
+		let client = TestBlockChainClient::default();
+		client.set_balance(signer.address(), U256::from(1_000_000_000_000_000_000u64));
+		let miner = Miner::with_spec(&Spec::new_test());
+
+		let contract = Address::from(0x1234);
+		let code = STORE_CODE.from_hex().unwrap();
+		let (validators, parties) = (vec![validator.public().clone()], vec![party.public().clone()]);
+		let initial = sender_provider.register_contract(contract, code.clone(), validators.clone(), parties.clone()).unwrap();
+		assert_eq!(validator_provider.register_contract(contract, code.clone(), validators.clone(), parties.clone()).unwrap(), initial);
+		assert_eq!(party_provider.register_contract(contract, code, validators, parties).unwrap(), initial);
+
+		let sender = Random.generate().unwrap();
+		let receipt = sender_provider.send_transaction(&client, &miner, store(&sender, contract, 0, 7)).unwrap();
+		assert_eq!(receipt.marker, None);
+
+		let signed = validator_provider.import_private_transaction(&client, &receipt.message).unwrap().unwrap();
+		assert_eq!(signed.private_transaction_hash, receipt.hash);
+
+		// other parties follow the state without signing it.
+		assert_eq!(party_provider.import_private_transaction(&client, &receipt.message).unwrap(), None);
+		let read = Transaction {
+			nonce: 0.into(),
+			action: Action::Call(contract),
+			gas: 100_000.into(),
+			gas_price: U256::zero(),
+			value: U256::zero(),
+			data: vec![],
+		}.fake_sign(sender.address());
+		assert_eq!(party_provider.execute(signed.state, &read, false).unwrap().1.output, H256::from(7).to_vec());
+
+		let marker = sender_provider.import_signature(&client, &miner, &encode(&signed)).unwrap();
+		assert!(marker.is_some());
+		assert_eq!(miner.pending_transactions().len(), 1);
+		assert!(sender_provider.import_signature(&client, &miner, &encode(&signed)).is_err());
+	}
+
+	#[test]
+	fn should_reject_signatures_replayed_from_other_state_changes() {
+		let (sender_path, validator_path) = (RandomTempPath::new(), RandomTempPath::new());
+		let (validator, signer) = (Random.generate().unwrap(), Random.generate().unwrap());
+		let sender_provider = provider(&sender_path, None, Some(&signer));
+		let validator_provider = provider(&validator_path, Some(&validator), None);
+		let client = TestBlockChainClient::default();
+		client.set_balance(signer.address(), U256::from(1_000_000_000_000_000_000u64));
+		let miner = Miner::with_spec(&Spec::new_test());
+
+		let contract = Address::from(0x1234);
+		let code = STORE_CODE.from_hex().unwrap();
+		let validators = vec![validator.public().clone()];
+		let initial = sender_provider.register_contract(contract, code.clone(), validators.clone(), vec![]).unwrap();
+		validator_provider.register_contract(contract, code, validators, vec![]).unwrap();
+
+		let sender = Random.generate().unwrap();
+		let receipt = sender_provider.send_transaction(&client, &miner, store(&sender, contract, 0, 7)).unwrap();
+		let signed = validator_provider.import_private_transaction(&client, &receipt.message).unwrap().unwrap();
+
+		// the same change signed at another nonce or from another anchored state, as in an earlier marker.
+		for &(prev_state, nonce) in &[(initial, U256::one()), (H256::from(1), U256::zero())] {
+			let replayed = SignedPrivateTransaction {
+				signature: sign(validator.secret(), &state_change_hash(&contract, &prev_state, &signed.state, &nonce)).unwrap(),
+				..signed.clone()
+			};
+			assert!(sender_provider.import_signature(&client, &miner, &encode(&replayed)).is_err());
+		}
+		assert_eq!(miner.pending_transactions().len(), 0);
+
+		assert!(sender_provider.import_signature(&client, &miner, &encode(&signed)).unwrap().is_some());
+		assert_eq!(miner.pending_transactions().len(), 1);
+	}
+}
@@ -44,6 +44,8 @@ pub enum ClientIoMessage {
 	FeedStateChunk(H256, Bytes),
 	/// Feed a block chunk to the snapshot service
 	FeedBlockChunk(H256, Bytes),
+	/// Feed state trie nodes to the snapshot service
+	FeedStateNodes(Vec<Bytes>),
 	/// Take a snapshot for the block with given number.
 	TakeSnapshot(u64),
 	/// Trigger sealing update (useful for internal sealing).
@@ -206,6 +208,7 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 				}
 			}
 			ClientIoMessage::FeedStateChunk(ref hash, ref chunk) => self.snapshot.feed_state_chunk(*hash, chunk),
+			ClientIoMessage::FeedStateNodes(ref nodes) => self.snapshot.feed_state_nodes(nodes),
 			ClientIoMessage::FeedBlockChunk(ref hash, ref chunk) => self.snapshot.feed_block_chunk(*hash, chunk),
 			ClientIoMessage::TakeSnapshot(num) => {
 				let client = self.client.clone();
@@ -20,7 +20,7 @@ use std::fmt;
 
 use ids::BlockId;
 
-use util::H256;
+use util::{H256, U256};
 use util::trie::TrieError;
 use rlp::DecoderError;
 
@@ -47,6 +47,8 @@ pub enum Error {
 	UnrecognizedCodeState(u8),
 	/// Restoration aborted.
 	RestorationAborted,
+	/// Pivot block total difficulty lower than the block's own difficulty.
+	InvalidTotalDifficulty(U256),
 	/// Trie error.
 	Trie(TrieError),
 	/// Decoder error.
@@ -70,6 +72,7 @@ impl fmt::Display for Error {
 			Error::MissingCode(ref missing) => write!(f, "Incomplete snapshot: {} contract codes not found.", missing.len()),
 			Error::UnrecognizedCodeState(state) => write!(f, "Unrecognized code encoding ({})", state),
 			Error::RestorationAborted => write!(f, "Snapshot restoration aborted."),
+			Error::InvalidTotalDifficulty(ref td) => write!(f, "Pivot block has invalid total difficulty {}", td),
 			Error::Io(ref err) => err.fmt(f),
 			Error::Decoder(ref err) => err.fmt(f),
 			Error::Trie(ref err) => err.fmt(f),
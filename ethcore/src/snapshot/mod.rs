@@ -52,6 +52,7 @@ use rand::{Rng, OsRng};
 
 pub use self::error::Error;
 
+pub use self::nodes::NodeRebuilder;
 pub use self::service::{Service, DatabaseRestore};
 pub use self::traits::SnapshotService;
 pub use self::watcher::Watcher;
@@ -64,6 +65,7 @@ pub mod service;
 mod account;
 mod block;
 mod error;
+mod nodes;
 mod watcher;
 
 #[cfg(test)]
@@ -0,0 +1,268 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State restoration from individual trie nodes.
+//!
+//! Rather than rebuilding the state from snapshot chunks, the `NodeRebuilder` walks
+//! the account trie down from a known state root, asking for every trie node and
+//! piece of contract code by hash until nothing is left missing.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use account_db::{AccountDB, AccountDBMut};
+use state_db::StateDB;
+
+use util::{Bytes, DBValue, Hashable, HashDB};
+use util::hash::{FixedHash, H256};
+use util::journaldb::{self, Algorithm, JournalDB};
+use util::kvdb::Database;
+use util::nibbleslice::NibbleSlice;
+use util::sha3::{SHA3_EMPTY, SHA3_NULL_RLP};
+use rlp::{DecoderError, Prototype, UntrustedRlp, View};
+use bloom_journal::Bloom;
+
+/// Where a node lives in the state. Determines the key it is stored under.
+#[derive(Debug, Clone, PartialEq)]
+enum NodeKind {
+	/// Account trie node, along with the nibble path leading to it.
+	Account(Vec<u8>),
+	/// Storage trie node of the account with the given address hash.
+	Storage(H256),
+	/// Code of the account with the given address hash.
+	Code(H256),
+}
+
+impl NodeKind {
+	// the position of a child reached through the given nibbles.
+	fn child<I: Iterator<Item=u8>>(&self, nibbles: I) -> NodeKind {
+		match *self {
+			NodeKind::Account(ref path) => {
+				let mut path = path.clone();
+				path.extend(nibbles);
+				NodeKind::Account(path)
+			}
+			ref other => other.clone(),
+		}
+	}
+}
+
+/// Rebuilds the state from trie nodes and code fed in arbitrary order.
+///
+/// Only nodes reachable from the state root are accepted. Nodes already present
+/// in the database, e.g. from a download towards an earlier state root, are reused.
+pub struct NodeRebuilder {
+	db: Box<JournalDB>,
+	bloom: Bloom,
+	state_root: H256,
+	missing: HashMap<H256, Vec<NodeKind>>,
+	queue: Vec<H256>, // missing hashes in discovery order. may contain stale entries.
+	fed_nodes: usize,
+}
+
+impl NodeRebuilder {
+	/// Create a new node rebuilder to write into the given backing DB.
+	pub fn new(db: Arc<Database>, pruning: Algorithm, state_root: H256) -> Result<Self, ::error::Error> {
+		let mut rebuilder = NodeRebuilder {
+			db: journaldb::new(db.clone(), pruning, ::db::COL_STATE),
+			bloom: StateDB::load_bloom(&*db),
+			state_root: SHA3_NULL_RLP,
+			missing: HashMap::new(),
+			queue: Vec::new(),
+			fed_nodes: 0,
+		};
+		rebuilder.reset(state_root)?;
+		Ok(rebuilder)
+	}
+
+	/// Start over towards a different state root, keeping all nodes written so far.
+	pub fn reset(&mut self, state_root: H256) -> Result<(), ::error::Error> {
+		self.missing.clear();
+		self.queue.clear();
+		self.state_root = state_root;
+		if state_root != SHA3_NULL_RLP {
+			self.require(state_root, NodeKind::Account(Vec::new()))?;
+		}
+		self.flush()
+	}
+
+	/// Get up to `max` hashes of nodes which are still missing, most recently discovered first.
+	pub fn missing(&mut self, max: usize) -> Vec<H256> {
+		while self.queue.last().map_or(false, |h| !self.missing.contains_key(h)) {
+			self.queue.pop();
+		}
+
+		let missing = &self.missing;
+		let mut seen = HashSet::new();
+		self.queue.iter().rev()
+			.filter(|h| missing.contains_key(h))
+			.filter(|h| seen.insert(**h))
+			.take(max)
+			.cloned()
+			.collect()
+	}
+
+	/// Feed the rebuilder raw trie nodes or code. Data nobody asked for is ignored.
+	/// Returns the number of nodes accepted.
+	pub fn feed(&mut self, nodes: &[Bytes]) -> Result<usize, ::error::Error> {
+		let mut accepted = 0;
+		for data in nodes {
+			let hash = data.sha3();
+			if let Some(kinds) = self.missing.remove(&hash) {
+				for kind in kinds {
+					self.insert(hash, kind, data)?;
+				}
+				accepted += 1;
+			}
+		}
+
+		if self.queue.len() > 2 * self.missing.len() {
+			let missing = &self.missing;
+			self.queue.retain(|h| missing.contains_key(h));
+		}
+
+		self.fed_nodes += accepted;
+		self.flush()?;
+		trace!(target: "snapshot", "fed {} state nodes, {} missing", accepted, self.missing.len());
+		Ok(accepted)
+	}
+
+	/// Whether the whole state below the root is present.
+	pub fn is_done(&self) -> bool { self.missing.is_empty() }
+
+	/// Number of nodes known to be missing.
+	pub fn missing_nodes(&self) -> usize { self.missing.len() }
+
+	/// Number of nodes fed so far.
+	pub fn fed_nodes(&self) -> usize { self.fed_nodes }
+
+	/// Get the state root of the rebuilder.
+	pub fn state_root(&self) -> H256 { self.state_root }
+
+	// note a node as needed, walking it straight away if it's already in the database.
+	fn require(&mut self, hash: H256, kind: NodeKind) -> Result<(), ::error::Error> {
+		let local = match kind {
+			NodeKind::Account(_) => self.db.as_hashdb().get(&hash),
+			NodeKind::Storage(addr_hash) | NodeKind::Code(addr_hash) =>
+				AccountDB::from_hash(self.db.as_hashdb(), addr_hash).get(&hash),
+		};
+
+		match local {
+			Some(data) => self.walk(&data, &kind),
+			None => {
+				let kinds = self.missing.entry(hash).or_insert_with(Vec::new);
+				if kinds.is_empty() {
+					self.queue.push(hash);
+				}
+				if !kinds.contains(&kind) {
+					kinds.push(kind);
+				}
+				Ok(())
+			}
+		}
+	}
+
+	// write a node under the key for its position and require its children.
+	fn insert(&mut self, hash: H256, kind: NodeKind, data: &[u8]) -> Result<(), ::error::Error> {
+		match kind {
+			NodeKind::Account(_) => self.db.as_hashdb_mut().emplace(hash, DBValue::from_slice(data)),
+			NodeKind::Storage(addr_hash) | NodeKind::Code(addr_hash) =>
+				AccountDBMut::from_hash(self.db.as_hashdb_mut(), addr_hash).emplace(hash, DBValue::from_slice(data)),
+		}
+
+		self.walk(data, &kind)
+	}
+
+	fn walk(&mut self, data: &[u8], kind: &NodeKind) -> Result<(), ::error::Error> {
+		if let NodeKind::Code(_) = *kind { return Ok(()) }
+
+		let mut children = Vec::new();
+		walk_node(UntrustedRlp::new(data), kind, &mut self.bloom, &mut children)?;
+		for (hash, kind) in children {
+			self.require(hash, kind)?;
+		}
+		Ok(())
+	}
+
+	fn flush(&mut self) -> Result<(), ::error::Error> {
+		let backing = self.db.backing().clone();
+		let mut batch = backing.transaction();
+		StateDB::commit_bloom(&mut batch, self.bloom.drain_journal())?;
+		self.db.inject(&mut batch)?;
+		backing.write_buffered(batch);
+		Ok(())
+	}
+}
+
+// collect the hashes referenced by a trie node: child nodes, and for accounts
+// their storage roots and code. inline nodes are walked in place.
+fn walk_node(node: UntrustedRlp, kind: &NodeKind, bloom: &mut Bloom, out: &mut Vec<(H256, NodeKind)>) -> Result<(), DecoderError> {
+	match node.prototype()? {
+		Prototype::List(2) => {
+			let encoded = node.at(0)?.data()?;
+			if encoded.is_empty() { return Err(DecoderError::RlpIsTooShort) }
+
+			let (partial, is_leaf) = NibbleSlice::from_encoded(encoded);
+			let kind = kind.child(partial.iter());
+			match (is_leaf, kind) {
+				(true, NodeKind::Account(path)) => walk_account(&path, UntrustedRlp::new(node.at(1)?.data()?), bloom, out),
+				(true, _) => Ok(()),
+				(false, kind) => walk_child(node.at(1)?, &kind, bloom, out),
+			}
+		}
+		Prototype::List(17) => {
+			for i in 0..16 {
+				walk_child(node.at(i)?, &kind.child(Some(i as u8).into_iter()), bloom, out)?;
+			}
+			Ok(())
+		}
+		Prototype::Data(0) => Ok(()),
+		_ => Err(DecoderError::Custom("Invalid trie node")),
+	}
+}
+
+fn walk_child(child: UntrustedRlp, kind: &NodeKind, bloom: &mut Bloom, out: &mut Vec<(H256, NodeKind)>) -> Result<(), DecoderError> {
+	if child.is_empty() { return Ok(()) }
+
+	match child.is_list() {
+		true => walk_node(child, kind, bloom, out),
+		false => {
+			out.push((child.as_val()?, kind.clone()));
+			Ok(())
+		}
+	}
+}
+
+fn walk_account(path: &[u8], account: UntrustedRlp, bloom: &mut Bloom, out: &mut Vec<(H256, NodeKind)>) -> Result<(), DecoderError> {
+	if path.len() != 64 { return Err(DecoderError::Custom("Account at invalid trie depth")) }
+
+	let mut addr_hash = H256::new();
+	for (i, byte) in addr_hash.iter_mut().enumerate() {
+		*byte = (path[2 * i] << 4) | path[2 * i + 1];
+	}
+
+	let storage_root: H256 = account.val_at(2)?;
+	let code_hash: H256 = account.val_at(3)?;
+
+	bloom.set(&*addr_hash);
+	if storage_root != SHA3_NULL_RLP {
+		out.push((storage_root, NodeKind::Storage(addr_hash)));
+	}
+	if code_hash != SHA3_EMPTY {
+		out.push((code_hash, NodeKind::Code(addr_hash)));
+	}
+	Ok(())
+}
@@ -23,22 +23,26 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-use super::{ManifestData, StateRebuilder, BlockRebuilder, RestorationStatus, SnapshotService};
+use super::{ManifestData, StateRebuilder, BlockRebuilder, NodeRebuilder, RestorationStatus, SnapshotService};
 use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
 
 use blockchain::BlockChain;
 use client::{BlockChainClient, Client};
 use engines::Engine;
 use error::Error;
+use header::Header;
 use ids::BlockId;
+use receipt::Receipt;
 use service::ClientIoMessage;
 
 use io::IoChannel;
 
-use util::{Bytes, H256, Mutex, RwLock, RwLockReadGuard, UtilError};
+use util::{Bytes, Hashable, H256, U256, Mutex, RwLock, RwLockReadGuard, UtilError};
 use util::journaldb::Algorithm;
 use util::kvdb::{Database, DatabaseConfig};
 use util::snappy;
+use rlp::{UntrustedRlp, View};
+use rand::OsRng;
 
 /// Helper for removing directories in case of error.
 struct Guard(bool, PathBuf);
@@ -186,6 +190,45 @@ impl Restoration {
 	}
 }
 
+/// State download manager. Rebuilds the state from individual trie nodes
+/// and imports the pivot block on top of it once the state is complete.
+struct NodeRestoration {
+	nodes: NodeRebuilder,
+	block: Bytes,
+	receipts: Bytes,
+	total_difficulty: U256,
+	guard: Guard,
+	db: Arc<Database>,
+}
+
+impl NodeRestoration {
+	// finish up the state download by importing the pivot block.
+	fn finalize(self, genesis: &[u8], engine: Arc<Engine>) -> Result<(), Error> {
+		let header: Header = UntrustedRlp::new(&self.block).val_at(0)?;
+		let receipts: Vec<Receipt> = UntrustedRlp::new(&self.receipts).as_val()?;
+
+		if header.state_root() != &self.nodes.state_root() {
+			return Err(super::Error::WrongStateRoot(self.nodes.state_root(), *header.state_root()).into());
+		}
+
+		if self.total_difficulty < *header.difficulty() {
+			return Err(super::Error::InvalidTotalDifficulty(self.total_difficulty).into());
+		}
+
+		let chain = BlockChain::new(Default::default(), genesis, self.db.clone(), engine.clone());
+		super::verify_old_block(&mut OsRng::new()?, &header, &*engine, &chain, Some(&self.block), true)?;
+
+		let mut batch = self.db.transaction();
+		chain.insert_unordered_block(&mut batch, &self.block, receipts, Some(self.total_difficulty - *header.difficulty()), true, false);
+		self.db.write_buffered(batch);
+		chain.commit();
+		self.db.flush().map_err(UtilError::SimpleString)?;
+
+		self.guard.disarm();
+		Ok(())
+	}
+}
+
 /// Type alias for client io channel.
 pub type Channel = IoChannel<ClientIoMessage>;
 
@@ -212,6 +255,8 @@ pub struct ServiceParams {
 /// This controls taking snapshots and restoring from them.
 pub struct Service {
 	restoration: Mutex<Option<Restoration>>,
+	node_restoration: Mutex<Option<NodeRestoration>>,
+	queued_nodes: Mutex<HashSet<H256>>,
 	snapshot_root: PathBuf,
 	db_config: DatabaseConfig,
 	io_channel: Mutex<Channel>,
@@ -226,6 +271,7 @@ pub struct Service {
 	progress: super::Progress,
 	taking_snapshot: AtomicBool,
 	restoring_snapshot: AtomicBool,
+	state_download_complete: AtomicBool,
 }
 
 impl Service {
@@ -233,6 +279,8 @@ impl Service {
 	pub fn new(params: ServiceParams) -> Result<Self, Error> {
 		let mut service = Service {
 			restoration: Mutex::new(None),
+			node_restoration: Mutex::new(None),
+			queued_nodes: Mutex::new(HashSet::new()),
 			snapshot_root: params.snapshot_root,
 			db_config: params.db_config,
 			io_channel: Mutex::new(params.channel),
@@ -247,6 +295,7 @@ impl Service {
 			progress: Default::default(),
 			taking_snapshot: AtomicBool::new(false),
 			restoring_snapshot: AtomicBool::new(false),
+			state_download_complete: AtomicBool::new(false),
 		};
 
 		// create the root snapshot dir if it doesn't exist.
@@ -394,6 +443,9 @@ impl Service {
 	pub fn init_restore(&self, manifest: ManifestData, recover: bool) -> Result<(), Error> {
 		let rest_dir = self.restoration_dir();
 
+		// tear down any state download.
+		*self.node_restoration.lock() = None;
+
 		let mut res = self.restoration.lock();
 
 		self.state_chunks.store(0, Ordering::SeqCst);
@@ -480,6 +532,137 @@ impl Service {
 		Ok(())
 	}
 
+	/// Initialize a state download synchronously, or move the one in progress
+	/// over to a new pivot block.
+	pub fn init_state_download(&self, block: Bytes, receipts: Bytes, total_difficulty: U256) -> Result<(), Error> {
+		let header: Header = UntrustedRlp::new(&block).val_at(0)?;
+
+		// tear down any snapshot restoration.
+		*self.restoration.lock() = None;
+		self.state_download_complete.store(false, Ordering::SeqCst);
+
+		let mut res = self.node_restoration.lock();
+
+		if res.is_some() {
+			let rest = res.as_mut().expect("checked to be some above; qed");
+			trace!(target: "snapshot", "moving state download to pivot #{}", header.number());
+			rest.nodes.reset(*header.state_root())?;
+			rest.block = block;
+			rest.receipts = receipts;
+			rest.total_difficulty = total_difficulty;
+		} else {
+			trace!(target: "snapshot", "starting state download at pivot #{}", header.number());
+			let rest_dir = self.restoration_dir();
+
+			if let Err(e) = fs::remove_dir_all(&rest_dir) {
+				match e.kind() {
+					ErrorKind::NotFound => {},
+					_ => return Err(e.into()),
+				}
+			}
+
+			fs::create_dir_all(&rest_dir)?;
+
+			let db = Arc::new(Database::open(&self.db_config, &*self.restoration_db().to_string_lossy())
+				.map_err(UtilError::SimpleString)?);
+
+			*res = Some(NodeRestoration {
+				nodes: NodeRebuilder::new(db.clone(), self.pruning, *header.state_root())?,
+				block: block,
+				receipts: receipts,
+				total_difficulty: total_difficulty,
+				guard: Guard::new(rest_dir),
+				db: db,
+			});
+		}
+
+		let is_done = {
+			let rest = res.as_ref().expect("node restoration initialized above; qed");
+			self.note_node_progress(&rest.nodes);
+			rest.nodes.is_done()
+		};
+		self.restoring_snapshot.store(true, Ordering::SeqCst);
+
+		match is_done {
+			true => self.finalize_state_download(&mut *res),
+			false => Ok(()),
+		}
+	}
+
+	// reflect the state download progress in the restoration status.
+	fn note_node_progress(&self, nodes: &NodeRebuilder) {
+		self.state_chunks.store(nodes.fed_nodes(), Ordering::SeqCst);
+		self.block_chunks.store(0, Ordering::SeqCst);
+
+		*self.status.lock() = RestorationStatus::Ongoing {
+			state_chunks: (nodes.fed_nodes() + nodes.missing_nodes()) as u32,
+			block_chunks: 1,
+			state_chunks_done: nodes.fed_nodes() as u32,
+			block_chunks_done: 0,
+		};
+	}
+
+	// finalize the state download. this accepts an already-locked
+	// node restoration as an argument -- so acquiring it again _will_
+	// lead to deadlock.
+	fn finalize_state_download(&self, rest: &mut Option<NodeRestoration>) -> Result<(), Error> {
+		trace!(target: "snapshot", "finalizing state download");
+
+		if let Some(rest) = rest.take() {
+			rest.finalize(&self.genesis_block, self.engine.clone())?;
+		}
+		self.replace_client_db()?;
+
+		let _ = fs::remove_dir_all(self.restoration_dir());
+		*self.status.lock() = RestorationStatus::Inactive;
+		self.state_download_complete.store(true, Ordering::SeqCst);
+
+		Ok(())
+	}
+
+	// feed state trie nodes to the state download, finalizing it once complete.
+	fn feed_nodes(&self, nodes: &[Bytes]) -> Result<(), Error> {
+		let mut res = self.node_restoration.lock();
+
+		let is_done = match *res {
+			Some(ref mut rest) => {
+				if !self.restoring_snapshot.load(Ordering::SeqCst) {
+					return Err(super::Error::RestorationAborted.into());
+				}
+
+				rest.nodes.feed(nodes)?;
+				rest.db.flush().map_err(UtilError::SimpleString)?;
+				self.note_node_progress(&rest.nodes);
+				rest.nodes.is_done()
+			}
+			None => return Ok(()),
+		};
+
+		match is_done {
+			true => self.finalize_state_download(&mut *res),
+			false => Ok(()),
+		}
+	}
+
+	/// Feed state trie nodes or code to be processed synchronously.
+	pub fn feed_state_nodes(&self, nodes: &[Bytes]) {
+		match self.feed_nodes(nodes) {
+			Ok(()) => (),
+			Err(e) => {
+				warn!("Encountered error during state download: {}", e);
+				*self.node_restoration.lock() = None;
+				*self.status.lock() = RestorationStatus::Failed;
+				let _ = fs::remove_dir_all(self.restoration_dir());
+			}
+		}
+
+		// the nodes are no longer in flight, whatever became of them.
+		let mut queued = self.queued_nodes.lock();
+		for node in nodes {
+			queued.remove(&node.sha3());
+		}
+	}
+
 	/// Feed a chunk of either kind. no-op if no restoration or status is wrong.
 	fn feed_chunk(&self, hash: H256, chunk: &[u8], is_state: bool) -> Result<(), Error> {
 		// TODO: be able to process block chunks and state chunks at same time?
@@ -581,6 +764,9 @@ impl SnapshotService for Service {
 	fn abort_restore(&self) {
 		self.restoring_snapshot.store(false, Ordering::SeqCst);
 		*self.restoration.lock() = None;
+		*self.node_restoration.lock() = None;
+		self.queued_nodes.lock().clear();
+		self.state_download_complete.store(false, Ordering::SeqCst);
 		*self.status.lock() = RestorationStatus::Inactive;
 	}
 
@@ -603,6 +789,43 @@ impl SnapshotService for Service {
 			rest.note_canonical(canonical);
 		}
 	}
+
+	fn begin_state_download(&self, block: Bytes, receipts: Bytes, total_difficulty: U256) {
+		if let Err(e) = self.init_state_download(block, receipts, total_difficulty) {
+			warn!("Failed to begin state download: {}", e);
+			*self.node_restoration.lock() = None;
+			*self.status.lock() = RestorationStatus::Failed;
+			let _ = fs::remove_dir_all(self.restoration_dir());
+		}
+	}
+
+	fn missing_state_nodes(&self, max: usize) -> Vec<H256> {
+		// nodes waiting in the IO queue shouldn't be asked for again.
+		let queued = self.queued_nodes.lock();
+		self.node_restoration.lock().as_mut().map_or_else(Vec::new, |rest| {
+			rest.nodes.missing(max + queued.len()).into_iter()
+				.filter(|hash| !queued.contains(hash))
+				.take(max)
+				.collect()
+		})
+	}
+
+	fn restore_state_nodes(&self, nodes: Vec<Bytes>) {
+		let hashes: Vec<H256> = nodes.iter().map(|node| node.sha3()).collect();
+		self.queued_nodes.lock().extend(hashes.iter().cloned());
+
+		if let Err(e) = self.io_channel.lock().send(ClientIoMessage::FeedStateNodes(nodes)) {
+			trace!("Error sending snapshot service message: {:?}", e);
+			let mut queued = self.queued_nodes.lock();
+			for hash in &hashes {
+				queued.remove(hash);
+			}
+		}
+	}
+
+	fn state_download_complete(&self) -> bool {
+		self.state_download_complete.load(Ordering::SeqCst)
+	}
 }
 
 impl Drop for Service {
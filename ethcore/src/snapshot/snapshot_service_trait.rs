@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::{ManifestData, RestorationStatus};
-use util::{Bytes, H256};
+use util::{Bytes, H256, U256};
 use ipc::IpcConfig;
 
 /// The interface for a snapshot network service.
@@ -52,6 +52,23 @@ pub trait SnapshotService : Sync + Send {
 	/// Give the restoration in-progress some canonical block hashes for
 	/// extra verification (performed at the end)
 	fn provide_canon_hashes(&self, canonical: &[(u64, H256)]);
+
+	/// Begin restoring the state by downloading individual trie nodes, with the
+	/// given block as the pivot. `receipts` is the RLP list of the block's receipts.
+	/// If a state download is in progress, it moves over to the new pivot while keeping
+	/// the nodes fetched so far. Any snapshot restoration is torn down.
+	fn begin_state_download(&self, block: Bytes, receipts: Bytes, total_difficulty: U256);
+
+	/// Get up to `max` hashes of state trie nodes or code the state download still needs.
+	fn missing_state_nodes(&self, max: usize) -> Vec<H256>;
+
+	/// Feed raw state trie nodes or code to the service to be processed asynchronously.
+	/// Anything the state download did not ask for is ignored.
+	fn restore_state_nodes(&self, nodes: Vec<Bytes>);
+
+	/// Whether the last state download ran to completion and its pivot is now the best block.
+	/// An aborted or failed download leaves this unset.
+	fn state_download_complete(&self) -> bool;
 }
 
 impl IpcConfig for SnapshotService { }
@@ -17,6 +17,7 @@
 //! Snapshot tests.
 
 mod blocks;
+mod nodes;
 mod state;
 mod service;
 
@@ -0,0 +1,145 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for state restoration from individual trie nodes.
+
+use snapshot::NodeRebuilder;
+use super::helpers::{compare_dbs, StateProducer};
+
+use account_db::AccountDBMut;
+use rand::{XorShiftRng, SeedableRng};
+use rlp::{RlpStream, Stream};
+use util::{Bytes, Hashable, HashDB, U256};
+use util::hash::{FixedHash, H256};
+use util::journaldb::{self, Algorithm};
+use util::kvdb::{Database, DatabaseConfig};
+use util::memorydb::MemoryDB;
+use util::sha3::SHA3_NULL_RLP;
+use util::trie::{TrieDBMut, TrieMut};
+use devtools::RandomTempPath;
+
+use std::sync::Arc;
+
+// look up a node by hash, whether it's stored under a plain or an account-mangled key.
+fn find_node(db: &MemoryDB, hash: &H256) -> Option<Bytes> {
+	let keys = db.keys();
+	let found = keys.keys()
+		.filter(|k| k[..12] == hash[..12])
+		.filter_map(|k| db.get(k))
+		.find(|v| &(&**v).sha3() == hash)
+		.map(|v| v.to_vec());
+	found
+}
+
+// serve the rebuilder from the given database until it has everything.
+fn restore(rebuilder: &mut NodeRebuilder, db: &MemoryDB) {
+	while !rebuilder.is_done() {
+		let nodes: Vec<Bytes> = rebuilder.missing(64).iter().map(|h| find_node(db, h).unwrap()).collect();
+		assert!(!nodes.is_empty());
+		assert_eq!(rebuilder.feed(&nodes).unwrap(), nodes.len());
+	}
+}
+
+fn new_db(path: &RandomTempPath) -> Arc<Database> {
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	Arc::new(Database::open(&db_cfg, &path.as_path().to_string_lossy()).unwrap())
+}
+
+#[test]
+fn restore_from_nodes() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+	let mut old_db = MemoryDB::new();
+
+	for _ in 0..150 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let path = RandomTempPath::create_dir();
+	let db = new_db(&path);
+	let mut rebuilder = NodeRebuilder::new(db.clone(), Algorithm::Archive, producer.state_root()).unwrap();
+	restore(&mut rebuilder, &old_db);
+
+	// data nobody asked for is ignored.
+	assert_eq!(rebuilder.feed(&[b"not a trie node".to_vec()]).unwrap(), 0);
+	assert_eq!(rebuilder.state_root(), producer.state_root());
+
+	let new_db = journaldb::new(db, Algorithm::Archive, ::db::COL_STATE);
+	compare_dbs(&old_db, new_db.as_hashdb());
+}
+
+#[test]
+fn reuses_nodes_after_reset() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+	let mut old_db = MemoryDB::new();
+
+	for _ in 0..150 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let path = RandomTempPath::create_dir();
+	let db = new_db(&path);
+	let mut rebuilder = NodeRebuilder::new(db.clone(), Algorithm::Archive, producer.state_root()).unwrap();
+	restore(&mut rebuilder, &old_db);
+	let first = rebuilder.fed_nodes();
+
+	for _ in 0..5 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	rebuilder.reset(producer.state_root()).unwrap();
+	assert!(!rebuilder.is_done());
+	restore(&mut rebuilder, &old_db);
+	assert!(rebuilder.fed_nodes() - first < first);
+
+	let new_db = journaldb::new(db, Algorithm::Archive, ::db::COL_STATE);
+	compare_dbs(&old_db, new_db.as_hashdb());
+}
+
+#[test]
+fn restores_shared_code() {
+	let code = b"this is definitely code";
+	let accounts = vec![H256::random(), H256::random()];
+	let mut old_db = MemoryDB::new();
+
+	let mut acc_stream = RlpStream::new_list(4);
+	acc_stream.append(&U256::default())
+		.append(&U256::default())
+		.append(&SHA3_NULL_RLP)
+		.append(&code.sha3());
+	let thin_rlp = acc_stream.out();
+
+	for addr_hash in &accounts {
+		AccountDBMut::from_hash(&mut old_db, *addr_hash).insert(&code[..]);
+	}
+
+	let mut root = SHA3_NULL_RLP;
+	{
+		let mut trie = TrieDBMut::new(&mut old_db, &mut root);
+		for addr_hash in &accounts {
+			trie.insert(&addr_hash[..], &thin_rlp).unwrap();
+		}
+	}
+
+	let path = RandomTempPath::create_dir();
+	let db = new_db(&path);
+	let mut rebuilder = NodeRebuilder::new(db.clone(), Algorithm::Archive, root).unwrap();
+	restore(&mut rebuilder, &old_db);
+
+	let new_db = journaldb::new(db, Algorithm::Archive, ::db::COL_STATE);
+	compare_dbs(&old_db, new_db.as_hashdb());
+}
@@ -140,4 +140,78 @@ fn guards_delete_folders() {
 
 	drop(service);
 	assert!(!path.exists());
-}
\ No newline at end of file
+}
+#[test]
+fn state_download_is_equivalent() {
+	use util::U256;
+	use views::BlockView;
+
+	const NUM_BLOCKS: u32 = 100;
+	const TX_PER: usize = 5;
+
+	let gas_prices = vec![1.into(), 2.into(), 3.into(), 999.into()];
+
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, TX_PER, &gas_prices);
+
+	let path = RandomTempPath::create_dir();
+	let mut path = path.as_path().clone();
+	let mut client_db = path.clone();
+
+	client_db.push("client_db");
+	path.push("snapshot");
+
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	let spec = Spec::new_null();
+	let client2 = Client::new(
+		Default::default(),
+		&spec,
+		&client_db,
+		Arc::new(::miner::Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+		&db_config,
+	).unwrap();
+
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: db_config,
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path,
+		db_restore: client2.clone(),
+	};
+
+	let service = Service::new(service_params).unwrap();
+
+	let pivot = client.block(BlockId::Number(NUM_BLOCKS as u64)).unwrap();
+	let pivot_hash = BlockView::new(&pivot).header_view().sha3();
+	let receipts = client.block_receipts(&pivot_hash).unwrap();
+	let total_difficulty: U256 = client.block_total_difficulty(BlockId::Number(NUM_BLOCKS as u64)).unwrap();
+
+	// an aborted download is inactive but not complete.
+	service.init_state_download(pivot.clone(), receipts.clone(), total_difficulty).unwrap();
+	service.abort_restore();
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+	assert!(!service.state_download_complete());
+
+	service.init_state_download(pivot.clone(), receipts, total_difficulty).unwrap();
+	assert!(!service.missing_state_nodes(16).is_empty());
+
+	while let ::snapshot::RestorationStatus::Ongoing { .. } = service.status() {
+		let nodes = service.missing_state_nodes(16).into_iter()
+			.map(|hash| client.state_data(&hash).unwrap())
+			.collect::<Vec<_>>();
+		service.feed_state_nodes(&nodes);
+	}
+
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+	assert!(service.state_download_complete());
+	assert_eq!(client2.chain_info().best_block_number, NUM_BLOCKS as u64);
+	assert_eq!(client2.chain_info().best_block_hash, pivot_hash);
+	assert_eq!(client2.block(BlockId::Latest).unwrap(), pivot);
+
+	let author = BlockView::new(&pivot).header_view().author();
+	assert_eq!(client2.balance(&author, BlockId::Latest), client.balance(&author, BlockId::Latest));
+	assert_eq!(client2.nonce(&author, BlockId::Latest), client.nonce(&author, BlockId::Latest));
+}
@@ -29,6 +29,7 @@ use env_info::EnvInfo;
 use pod_state::*;
 use account_db::*;
 use header::{BlockNumber, Header};
+use evm::Schedule;
 use state_db::StateDB;
 use super::genesis::Genesis;
 use super::seal::Generic as GenericSeal;
@@ -53,6 +54,23 @@ pub struct CommonParams {
 	pub min_gas_limit: U256,
 	/// Fork block to check.
 	pub fork_block: Option<(BlockNumber, H256)>,
+	/// Number of first block where EIP-140 (REVERT opcode) rules begin.
+	pub eip140_transition: BlockNumber,
+	/// Number of first block where EIP-211 (RETURNDATASIZE/RETURNDATACOPY) rules begin.
+	pub eip211_transition: BlockNumber,
+	/// Number of first block where EIP-214 (STATICCALL) rules begin.
+	pub eip214_transition: BlockNumber,
+	/// Number of first block where EIP-658 (transaction status code in receipts) rules begin.
+	pub eip658_transition: BlockNumber,
+}
+
+impl CommonParams {
+	/// Apply common spec config parameters to the schedule.
+	pub fn update_schedule(&self, block_number: u64, schedule: &mut Schedule) {
+		schedule.have_revert = block_number >= self.eip140_transition;
+		schedule.have_return_data = block_number >= self.eip211_transition;
+		schedule.have_static_call = block_number >= self.eip214_transition;
+	}
 }
 
 impl From<ethjson::spec::Params> for CommonParams {
@@ -65,6 +83,10 @@ impl From<ethjson::spec::Params> for CommonParams {
 			subprotocol_name: p.subprotocol_name.unwrap_or_else(|| "eth".to_owned()),
 			min_gas_limit: p.min_gas_limit.into(),
 			fork_block: if let (Some(n), Some(h)) = (p.fork_block, p.fork_hash) { Some((n.into(), h.into())) } else { None },
+			eip140_transition: p.eip140_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip211_transition: p.eip211_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip214_transition: p.eip214_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip658_transition: p.eip658_transition.map_or(BlockNumber::max_value(), Into::into),
 		}
 	}
 }
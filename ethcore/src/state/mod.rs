@@ -17,17 +17,19 @@
 use std::cell::{RefCell, RefMut};
 use std::collections::hash_map::Entry;
 
-use receipt::Receipt;
+use receipt::{Receipt, TransactionOutcome};
+use action_params::{ActionParams, ActionValue};
 use engines::Engine;
 use env_info::EnvInfo;
 use error::Error;
 use executive::{Executive, TransactOptions};
 use factory::Factories;
-use trace::FlatTrace;
+use trace::{FlatTrace, Tracer, NoopTracer, NoopVMTracer};
 use pod_account::*;
 use pod_state::{self, PodState};
 use types::state_diff::StateDiff;
 use transaction::SignedTransaction;
+use types::executed::CallType;
 use state_db::StateDB;
 
 use util::*;
@@ -51,6 +53,15 @@ pub struct ApplyOutcome {
 /// Result type for the execution ("application") of a transaction.
 pub type ApplyResult = Result<ApplyOutcome, Error>;
 
+/// Accounts and storage items looked up through a `State`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Accesses {
+	/// Addresses of the accounts looked up.
+	pub accounts: HashSet<Address>,
+	/// Storage keys looked up, with the address of the account they belong to.
+	pub storage: HashSet<(Address, H256)>,
+}
+
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
 /// Account modification state. Used to check if the account was
 /// Modified in between commits and overall.
@@ -194,6 +205,7 @@ pub struct State {
 	checkpoints: RefCell<Vec<HashMap<Address, Option<AccountEntry>>>>,
 	account_start_nonce: U256,
 	factories: Factories,
+	accesses: RefCell<Option<Accesses>>,
 }
 
 #[derive(Copy, Clone)]
@@ -230,6 +242,7 @@ impl State {
 			checkpoints: RefCell::new(Vec::new()),
 			account_start_nonce: account_start_nonce,
 			factories: factories,
+			accesses: RefCell::new(None),
 		}
 	}
 
@@ -245,12 +258,32 @@ impl State {
 			cache: RefCell::new(HashMap::new()),
 			checkpoints: RefCell::new(Vec::new()),
 			account_start_nonce: account_start_nonce,
-			factories: factories
+			factories: factories,
+			accesses: RefCell::new(None),
 		};
 
 		Ok(state)
 	}
 
+	/// Start recording every account and storage item looked up from now on.
+	pub fn record_accesses(&mut self) {
+		*self.accesses.get_mut() = Some(Accesses::default());
+	}
+
+	/// Get the accounts and storage items looked up since `record_accesses` was called.
+	pub fn accesses(&self) -> Option<Accesses> {
+		self.accesses.borrow().clone()
+	}
+
+	fn note_access(&self, address: &Address, key: Option<&H256>) {
+		if let Some(ref mut accesses) = *self.accesses.borrow_mut() {
+			accesses.accounts.insert(address.clone());
+			if let Some(key) = key {
+				accesses.storage.insert((address.clone(), key.clone()));
+			}
+		}
+	}
+
 	/// Create a recoverable checkpoint of this state.
 	pub fn checkpoint(&mut self) {
 		self.checkpoints.get_mut().push(HashMap::new());
@@ -381,6 +414,8 @@ impl State {
 
 	/// Mutate storage of account `address` so that it is `value` for `key`.
 	pub fn storage_at(&self, address: &Address, key: &H256) -> H256 {
+		self.note_access(address, Some(key));
+
 		// Storage key search and update works like this:
 		// 1. If there's an entry for the account in the local cache check for the key and return it if found.
 		// 2. If there's an entry for the account in the global cache check for the key or load it into that account.
@@ -513,18 +548,56 @@ impl State {
 	pub fn apply(&mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool) -> ApplyResult {
 //		let old = self.to_pod();
 
-		let options = TransactOptions { tracing: tracing, vm_tracing: false, check_nonce: true };
+		let options = TransactOptions { tracing: tracing, vm_tracing: false, struct_logging: None, check_nonce: true };
 		let vm_factory = self.factories.vm.clone();
 		let e = Executive::new(self, env_info, engine, &vm_factory).transact(t, options)?;
 
 		// TODO uncomment once to_pod() works correctly.
 //		trace!("Applied transaction. Diff:\n{}\n", state_diff::diff_pod(&old, &self.to_pod()));
 		self.commit()?;
-		let receipt = Receipt::new(self.root().clone(), e.cumulative_gas_used, e.logs);
+		let outcome = if env_info.number >= engine.params().eip658_transition {
+			TransactionOutcome::StatusCode(if e.exception.is_some() { 0 } else { 1 })
+		} else {
+			TransactionOutcome::StateRoot(self.root().clone())
+		};
+		let receipt = Receipt::new(outcome, e.cumulative_gas_used, e.logs);
 		trace!(target: "state", "Transaction receipt: {:?}", receipt);
 		Ok(ApplyOutcome{receipt: receipt, trace: e.trace})
 	}
 
+	/// Call `contract` on behalf of `sender` without a transaction: no nonce, fee or
+	/// signature is involved and no receipt is produced. Used by engines to call
+	/// consensus contracts when closing a block. The call is traced with `tracer`.
+	/// Returns the output of the call.
+	pub fn system_call<T>(&mut self, env_info: &EnvInfo, engine: &Engine, sender: &Address, contract: &Address, gas: U256, data: Bytes, tracer: &mut T) -> Result<Bytes, String>
+		where T: Tracer
+	{
+		let params = ActionParams {
+			code_address: contract.clone(),
+			address: contract.clone(),
+			sender: sender.clone(),
+			origin: sender.clone(),
+			gas: gas,
+			gas_price: U256::zero(),
+			value: ActionValue::Transfer(U256::zero()),
+			code: self.code(contract),
+			code_hash: self.code_hash(contract),
+			data: Some(data),
+			call_type: CallType::Call,
+		};
+		let vm_factory = self.factories.vm.clone();
+		let mut substate = Substate::new();
+		let mut output = Vec::new();
+		let result = Executive::new(self, env_info, engine, &vm_factory)
+			.call(params, &mut substate, BytesRef::Flexible(&mut output), tracer, &mut NoopVMTracer);
+
+		match result {
+			Ok(ref res) if res.apply_state => Ok(output),
+			Ok(_) => Err(format!("System call to {} was reverted", contract)),
+			Err(e) => Err(format!("System call to {} failed: {:?}", contract, e)),
+		}
+	}
+
 	/// Commit accounts to SecTrieDBMut. This is similar to cpp-ethereum's dev::eth::commit.
 	/// `accounts` is mutable because we may need to commit the code or storage and record that.
 	#[cfg_attr(feature="dev", allow(match_ref_pats))]
@@ -665,6 +738,8 @@ impl State {
 	/// Populates local cache if nothing found.
 	fn ensure_cached<F, U>(&self, a: &Address, require: RequireCache, check_bloom: bool, f: F) -> U
 		where F: Fn(Option<&Account>) -> U {
+		self.note_access(a, None);
+
 		// check local cache first
 		if let Some(ref mut maybe_acc) = self.cache.borrow_mut().get_mut(a) {
 			if let Some(ref mut account) = maybe_acc.account {
@@ -715,6 +790,8 @@ impl State {
 	fn require_or_from<'a, F: FnOnce() -> Account, G: FnOnce(&mut Account)>(&'a self, a: &Address, require_code: bool, default: F, not_default: G)
 		-> RefMut<'a, Account>
 	{
+		self.note_access(a, None);
+
 		let contains_key = self.cache.borrow().contains_key(a);
 		if !contains_key {
 			match self.db.get_cached_account(a) {
@@ -834,6 +911,7 @@ impl Clone for State {
 			checkpoints: RefCell::new(Vec::new()),
 			account_start_nonce: self.account_start_nonce.clone(),
 			factories: self.factories.clone(),
+			accesses: RefCell::new(self.accesses.borrow().clone()),
 		}
 	}
 }
@@ -952,6 +1030,43 @@ mod tests {
 		assert_eq!(result.trace, expected_trace);
 	}
 
+	#[test]
+	fn should_report_status_code_after_eip658() {
+		init_log();
+
+		let temp = RandomTempPath::new();
+		let mut state = get_temp_state_in(temp.as_path());
+
+		let mut info = EnvInfo::default();
+		info.gas_limit = 1_000_000.into();
+		let spec = ::ethereum::new_byzantium_test();
+		let engine = &*spec.engine;
+
+		let ok = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Call(0xa.into()),
+			value: 0.into(),
+			data: vec![],
+		}.sign(&"".sha3(), None);
+
+		let failed = Transaction {
+			nonce: 1.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 0.into(),
+			data: FromHex::from_hex("5b600056").unwrap(),
+		}.sign(&"".sha3(), None);
+
+		let result = state.apply(&info, engine, &ok, false).unwrap();
+		assert_eq!(result.receipt.outcome, TransactionOutcome::StatusCode(1));
+
+		let result = state.apply(&info, engine, &failed, false).unwrap();
+		assert_eq!(result.receipt.outcome, TransactionOutcome::StatusCode(0));
+	}
+
 	#[test]
 	fn should_trace_call_transaction() {
 		init_log();
@@ -1944,4 +2059,21 @@ mod tests {
 		new_state.diff_from(state);
 	}
 
+	#[test]
+	fn records_accesses() {
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let (a, b, c) = (Address::from(1), Address::from(2), Address::from(3));
+		state.add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty);
+		assert_eq!(state.accesses(), None);
+
+		state.record_accesses();
+		state.balance(&b);
+		state.storage_at(&c, &H256::from(4));
+		state.add_balance(&a, &U256::from(1u64), CleanupMode::NoEmpty);
+
+		let accesses = state.accesses().unwrap();
+		assert_eq!(accesses.accounts, vec![a, b, c].into_iter().collect());
+		assert_eq!(accesses.storage, vec![(c, H256::from(4))].into_iter().collect());
+	}
 }
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use io::IoChannel;
-use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId};
+use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId, TransactionId, CallAnalytics, ArchiveRebuild, RebuildStatus};
 use state::CleanupMode;
 use ethereum;
 use block::IsBlock;
@@ -225,6 +225,25 @@ fn can_generate_gas_price_histogram() {
 	assert_eq!(hist, correct_hist);
 }
 
+#[test]
+fn replays_block_transactions_like_single_transactions() {
+	let client_result = generate_dummy_client_with_data(2, 3, slice_into![1, 2, 3]);
+	let client = client_result.reference();
+	let analytics = CallAnalytics { transaction_tracing: true, ..Default::default() };
+
+	let hashes = client.block_body(BlockId::Number(2)).unwrap().transaction_hashes();
+	let executed = client.replay_block_transactions(BlockId::Number(2), analytics).unwrap();
+	assert!(!hashes.is_empty());
+	assert_eq!(executed.len(), hashes.len());
+
+	for (block_executed, hash) in executed.into_iter().zip(hashes) {
+		let single = client.replay(TransactionId::Hash(hash), analytics).unwrap();
+		assert_eq!(block_executed.gas_used, single.gas_used);
+		assert_eq!(block_executed.output, single.output);
+		assert_eq!(block_executed.trace, single.trace);
+	}
+}
+
 #[test]
 fn empty_gas_price_histogram() {
 	let client_result = generate_dummy_client_with_data(20, 0, slice_into![]);
@@ -320,3 +339,42 @@ fn does_not_propagate_delayed_transactions() {
 	assert_eq!(2, client.ready_transactions().len());
 	assert_eq!(2, client.miner().pending_transactions().len());
 }
+
+#[test]
+fn rebuilds_archive_state() {
+	let client_result = generate_dummy_client_with_data(40, 1, &[1.into()]);
+	let client = client_result.reference();
+	let sender = KeyPair::from_secret("".sha3()).unwrap().address();
+
+	// every block sends a transaction, but old states are pruned.
+	assert_eq!(client.nonce(&sender, BlockId::Latest), Some(40.into()));
+	assert_eq!(client.nonce(&sender, BlockId::Number(10)), None);
+	assert!(client.pruning_info().earliest_state > 10);
+
+	let dir = RandomTempPath::new();
+	let spec = Spec::new_null();
+	let archive = Client::new(
+		ClientConfig { pruning: ::util::journaldb::Algorithm::Archive, ..Default::default() },
+		&spec,
+		dir.as_path(),
+		Arc::new(Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+		&DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+	).unwrap();
+
+	let finished = Arc::new(Mutex::new(None));
+	let f = finished.clone();
+	let rebuild = ArchiveRebuild::start(client.clone(), archive.clone(), 1, move |best| *f.lock() = Some(best)).unwrap();
+	while let RebuildStatus::Ongoing { .. } = rebuild.status() {
+		::std::thread::sleep(::std::time::Duration::from_millis(10));
+	}
+
+	assert_eq!(rebuild.status(), RebuildStatus::Finished(40));
+	assert_eq!(*finished.lock(), Some(40));
+	assert_eq!(client.nonce(&sender, BlockId::Number(10)), Some(10.into()));
+	assert_eq!(client.nonce(&sender, BlockId::Number(1)), Some(1.into()));
+	assert_eq!(client.nonce(&sender, BlockId::Latest), Some(40.into()));
+	assert_eq!(client.pruning_info().earliest_state, 0);
+	// the archive client no longer writes to the database now used by `client`.
+	assert!(archive.set_engine_data(b"key", b"value").is_err());
+}
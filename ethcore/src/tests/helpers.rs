@@ -428,6 +428,7 @@ pub fn get_default_ethash_params() -> EthashParams{
 		minimum_difficulty: U256::from(131072),
 		difficulty_bound_divisor: U256::from(2048),
 		difficulty_increment_divisor: 10,
+		metropolis_difficulty_increment_divisor: 9,
 		duration_limit: 13,
 		block_reward: U256::from(0),
 		registrar: "0000000000000000000000000000000000000001".into(),
@@ -446,5 +447,7 @@ pub fn get_default_ethash_params() -> EthashParams{
 		ecip1010_pause_transition: u64::max_value(),
 		ecip1010_continue_transition: u64::max_value(),
 		max_code_size: u64::max_value(),
+		eip100b_transition: u64::max_value(),
+		difficulty_bomb_delays: BTreeMap::new(),
 	}
 }
@@ -191,6 +191,16 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		self.traces(block_hash).map(Into::into)
 	}
 
+	/// Returns the transaction number and hash for the traces at given position within the block.
+	/// Traces placed after the last transaction (e.g. block rewards) are not produced by
+	/// any transaction, so both are `None` for them.
+	fn transaction_position(&self, block_number: BlockNumber, tx_position: usize) -> (Option<usize>, Option<H256>) {
+		match self.extras.transaction_hash(block_number, tx_position) {
+			Some(hash) => (Some(tx_position), Some(hash)),
+			None => (None, None),
+		}
+	}
+
 	fn matching_block_traces(
 		&self,
 		filter: &Filter,
@@ -215,8 +225,7 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		block_number: BlockNumber,
 		tx_number: usize
 	) -> Vec<LocalizedTrace> {
-		let tx_hash = self.extras.transaction_hash(block_number, tx_number)
-			.expect("Expected to find transaction hash. Database is probably corrupted");
+		let (trace_tx_number, trace_tx_hash) = self.transaction_position(block_number, tx_number);
 
 		let flat_traces: Vec<FlatTrace> = traces.into();
 		flat_traces.into_iter()
@@ -227,8 +236,8 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 						result: trace.result,
 						subtraces: trace.subtraces,
 						trace_address: trace.trace_address.into_iter().collect(),
-						transaction_number: tx_number,
-						transaction_hash: tx_hash.clone(),
+						transaction_number: trace_tx_number,
+						transaction_hash: trace_tx_hash.clone(),
 						block_number: block_number,
 						block_hash: block_hash
 					}),
@@ -313,16 +322,15 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 				// this may and should be optimized
 				.and_then(|traces| traces.into_iter().find(|trace| trace.trace_address == trace_position_deq))
 				.map(|trace| {
-					let tx_hash = self.extras.transaction_hash(block_number, tx_position)
-						.expect("Expected to find transaction hash. Database is probably corrupted");
+					let (trace_tx_number, trace_tx_hash) = self.transaction_position(block_number, tx_position);
 
 					LocalizedTrace {
 						action: trace.action,
 						result: trace.result,
 						subtraces: trace.subtraces,
 						trace_address: trace.trace_address.into_iter().collect(),
-						transaction_number: tx_position,
-						transaction_hash: tx_hash,
+						transaction_number: trace_tx_number,
+						transaction_hash: trace_tx_hash,
 						block_number: block_number,
 						block_hash: block_hash,
 					}
@@ -336,8 +344,7 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 				.and_then(|traces| traces.into_iter().nth(tx_position))
 				.map(Into::<Vec<FlatTrace>>::into)
 				.map(|traces| {
-					let tx_hash = self.extras.transaction_hash(block_number, tx_position)
-						.expect("Expected to find transaction hash. Database is probably corrupted");
+					let (trace_tx_number, trace_tx_hash) = self.transaction_position(block_number, tx_position);
 
 					traces.into_iter()
 					.map(|trace| LocalizedTrace {
@@ -345,8 +352,8 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 						result: trace.result,
 						subtraces: trace.subtraces,
 						trace_address: trace.trace_address.into_iter().collect(),
-						transaction_number: tx_position,
-						transaction_hash: tx_hash.clone(),
+						transaction_number: trace_tx_number,
+						transaction_hash: trace_tx_hash.clone(),
 						block_number: block_number,
 						block_hash: block_hash
 					})
@@ -363,8 +370,7 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 						.map(Into::<Vec<FlatTrace>>::into)
 						.enumerate()
 						.flat_map(|(tx_position, traces)| {
-							let tx_hash = self.extras.transaction_hash(block_number, tx_position)
-								.expect("Expected to find transaction hash. Database is probably corrupted");
+							let (trace_tx_number, trace_tx_hash) = self.transaction_position(block_number, tx_position);
 
 							traces.into_iter()
 								.map(|trace| LocalizedTrace {
@@ -372,8 +378,8 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 									result: trace.result,
 									subtraces: trace.subtraces,
 									trace_address: trace.trace_address.into_iter().collect(),
-									transaction_number: tx_position,
-									transaction_hash: tx_hash.clone(),
+									transaction_number: trace_tx_number,
+									transaction_hash: trace_tx_hash.clone(),
 									block_number: block_number,
 									block_hash: block_hash,
 								})
@@ -409,7 +415,7 @@ mod tests {
 	use header::BlockNumber;
 	use trace::{Config, TraceDB, Database as TraceDatabase, DatabaseExtras, ImportRequest};
 	use trace::{Filter, LocalizedTrace, AddressesFilter, TraceError};
-	use trace::trace::{Call, Action, Res};
+	use trace::trace::{Call, Action, Res, Reward, RewardType};
 	use trace::flat::{FlatTrace, FlatBlockTraces, FlatTransactionTraces};
 	use types::executed::CallType;
 
@@ -546,8 +552,8 @@ mod tests {
 			result: Res::FailedCall(TraceError::OutOfGas),
 			trace_address: vec![],
 			subtraces: 0,
-			transaction_number: 0,
-			transaction_hash: tx_hash,
+			transaction_number: Some(0),
+			transaction_hash: Some(tx_hash),
 			block_number: block_number,
 			block_hash: block_hash,
 		}
@@ -662,6 +668,68 @@ mod tests {
 		assert_eq!(tracedb.trace(2, 0, vec![]).unwrap(), create_simple_localized_trace(2, block_2.clone(), tx_2.clone()));
 	}
 
+	#[test]
+	fn test_import_reward_traces() {
+		let temp = RandomTempPath::new();
+		let db = new_db(temp.as_str());
+		let mut config = Config::default();
+		config.enabled = true;
+		let block_1 = H256::from(0xa1);
+		let tx_1 = H256::from(0xff);
+		let author = Address::from(0xaa);
+
+		let mut extras = Extras::default();
+		extras.block_hashes.insert(0, H256::default());
+		extras.block_hashes.insert(1, block_1.clone());
+		extras.transaction_hashes.insert(1, vec![tx_1.clone()]);
+
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(extras));
+
+		// import block 1 with a single transaction followed by the block reward.
+		let mut request = create_simple_import_request(1, block_1.clone());
+		let mut traces: Vec<FlatTransactionTraces> = request.traces.into();
+		traces.push(FlatTransactionTraces::from(vec![FlatTrace {
+			trace_address: Default::default(),
+			subtraces: 0,
+			action: Action::Reward(Reward {
+				author: author.clone(),
+				value: 10.into(),
+				reward_type: RewardType::Block,
+			}),
+			result: Res::None,
+		}]));
+		request.traces = traces.into();
+		let mut batch = DBTransaction::new(&db);
+		tracedb.import(&mut batch, request);
+		db.write(batch).unwrap();
+
+		let reward_trace = LocalizedTrace {
+			action: Action::Reward(Reward {
+				author: author.clone(),
+				value: 10.into(),
+				reward_type: RewardType::Block,
+			}),
+			result: Res::None,
+			trace_address: vec![],
+			subtraces: 0,
+			transaction_number: None,
+			transaction_hash: None,
+			block_number: 1,
+			block_hash: block_1.clone(),
+		};
+
+		let traces = tracedb.block_traces(1).unwrap();
+		assert_eq!(traces, vec![create_simple_localized_trace(1, block_1.clone(), tx_1.clone()), reward_trace.clone()]);
+
+		let filter = Filter {
+			range: (1..1),
+			from_address: AddressesFilter::from(vec![]),
+			to_address: AddressesFilter::from(vec![author]),
+		};
+
+		assert_eq!(tracedb.filter(&filter), vec![reward_trace]);
+	}
+
 	#[test]
 	fn query_trace_after_reopen() {
 		let temp = RandomTempPath::new();
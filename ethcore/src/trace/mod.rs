@@ -23,6 +23,7 @@ mod error;
 mod executive_tracer;
 mod import;
 mod noop_tracer;
+mod struct_logger;
 
 pub use types::trace_types::{filter, flat, localized, trace};
 pub use types::trace_types::error::Error as TraceError;
@@ -33,11 +34,13 @@ pub use types::trace_types::trace::{VMTrace, VMOperation, VMExecutedOperation, M
 pub use types::trace_types::flat::{FlatTrace, FlatTransactionTraces, FlatBlockTraces};
 pub use self::noop_tracer::{NoopTracer, NoopVMTracer};
 pub use self::executive_tracer::{ExecutiveTracer, ExecutiveVMTracer};
+pub use self::struct_logger::StructLogger;
+pub use types::trace_types::struct_log::{StructLog, StructLogOptions};
 pub use types::trace_types::filter::{Filter, AddressesFilter};
 pub use self::import::ImportRequest;
 pub use self::localized::LocalizedTrace;
 use util::{Bytes, Address, U256, H256, DBTransaction};
-use self::trace::{Call, Create};
+use self::trace::{Call, Create, RewardType};
 use action_params::ActionParams;
 use header::BlockNumber;
 
@@ -80,6 +83,9 @@ pub trait Tracer: Send {
 	/// Stores suicide info.
 	fn trace_suicide(&mut self, address: Address, balance: U256, refund_address: Address);
 
+	/// Stores reward info.
+	fn trace_reward(&mut self, author: Address, value: U256, reward_type: RewardType);
+
 	/// Spawn subtracer which will be used to trace deeper levels of execution.
 	fn subtracer(&self) -> Self where Self: Sized;
 
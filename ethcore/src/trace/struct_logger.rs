@@ -0,0 +1,128 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! VM tracer producing struct logs.
+
+use std::collections::BTreeMap;
+use util::{U256, H256};
+use evm;
+use trace::{VMTracer, VMTrace};
+use types::trace_types::struct_log::{StructLog, StructLogOptions};
+
+/// VM tracer which records the VM state right before every executed instruction.
+///
+/// The stack, memory and storage are reconstructed from the diffs reported by the VM,
+/// so memory which was only expanded, but never written to, is not included.
+pub struct StructLogger {
+	options: StructLogOptions,
+	depth: usize,
+	stack: Vec<U256>,
+	memory: Vec<u8>,
+	storage: BTreeMap<H256, H256>,
+	gas_left: Option<U256>,
+	/// Index of the last prepared instruction in `logs` together with the instruction itself.
+	last: Option<(usize, u8)>,
+	logs: Vec<StructLog>,
+}
+
+impl StructLogger {
+	/// Create a new top-level instance.
+	pub fn toplevel(options: StructLogOptions) -> Self {
+		StructLogger::new(options, 0)
+	}
+
+	fn new(options: StructLogOptions, depth: usize) -> Self {
+		StructLogger {
+			options: options,
+			depth: depth,
+			stack: Vec::new(),
+			memory: Vec::new(),
+			storage: BTreeMap::new(),
+			gas_left: None,
+			last: None,
+			logs: Vec::new(),
+		}
+	}
+
+	/// Consumes self and returns the recorded logs in execution order.
+	pub fn logs(self) -> Vec<StructLog> {
+		self.logs
+	}
+}
+
+impl VMTracer for StructLogger {
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: &U256) -> bool {
+		let log = StructLog {
+			pc: pc,
+			op: evm::instruction_info(instruction).0.to_owned(),
+			gas: self.gas_left.unwrap_or_else(U256::zero),
+			gas_cost: *gas_cost,
+			depth: self.depth,
+			stack: if self.options.disable_stack { None } else { Some(self.stack.clone()) },
+			memory: if self.options.disable_memory { None } else { Some(self.memory.clone()) },
+			storage: if self.options.disable_storage { None } else { Some(self.storage.clone()) },
+		};
+		self.last = Some((self.logs.len(), instruction));
+		self.logs.push(log);
+		true
+	}
+
+	fn trace_executed(&mut self, gas_left: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		let (index, instruction) = self.last.take().expect("trace_executed is always called after a trace_prepare_execute; qed");
+		if self.gas_left.is_none() {
+			// the gas available to the very first instruction is not known up front.
+			let log = &mut self.logs[index];
+			log.gas = gas_left + log.gas_cost;
+		}
+		self.gas_left = Some(gas_left);
+
+		if instruction == evm::SLOAD {
+			if let (Some(key), Some(value)) = (self.stack.last(), stack_push.first()) {
+				self.storage.insert(key.into(), value.into());
+			}
+		}
+		if let Some((key, value)) = store_diff {
+			self.storage.insert(key.into(), value.into());
+		}
+
+		let consumed = evm::instruction_info(instruction).1;
+		let len = self.stack.len();
+		self.stack.truncate(len.saturating_sub(consumed));
+		self.stack.extend_from_slice(stack_push);
+
+		match mem_diff {
+			Some((offset, data)) if !self.options.disable_memory && !data.is_empty() => {
+				let end = offset + data.len();
+				if self.memory.len() < end {
+					self.memory.resize((end + 31) / 32 * 32, 0);
+				}
+				self.memory[offset..end].copy_from_slice(data);
+			},
+			_ => {},
+		}
+	}
+
+	fn prepare_subtrace(&self, _code: &[u8]) -> Self {
+		StructLogger::new(self.options, self.depth + 1)
+	}
+
+	fn done_subtrace(&mut self, sub: Self) {
+		self.logs.extend(sub.logs);
+	}
+
+	fn drain(self) -> Option<VMTrace> { None }
+}
@@ -16,6 +16,8 @@
 
 //! Call analytics related types
 
+use types::trace_types::struct_log::StructLogOptions;
+
 /// Options concerning what analytics we run on the call.
 #[derive(Eq, PartialEq, Default, Clone, Copy, Debug)]
 #[cfg_attr(feature = "ipc", binary)]
@@ -26,4 +28,6 @@ pub struct CallAnalytics {
 	pub vm_tracing: bool,
 	/// Make a diff.
 	pub state_diffing: bool,
+	/// Record struct logs with given options.
+	pub struct_logging: Option<StructLogOptions>,
 }
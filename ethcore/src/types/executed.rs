@@ -18,7 +18,7 @@
 
 use util::{Bytes, U256, Address, U512};
 use rlp::*;
-use trace::{VMTrace, FlatTrace};
+use trace::{VMTrace, FlatTrace, TraceError, StructLog};
 use types::log_entry::LogEntry;
 use types::state_diff::StateDiff;
 use std::fmt;
@@ -35,6 +35,8 @@ pub enum CallType {
 	CallCode,
 	/// DELEGATECALL.
 	DelegateCall,
+	/// STATICCALL
+	StaticCall,
 }
 
 impl Encodable for CallType {
@@ -44,6 +46,7 @@ impl Encodable for CallType {
 			CallType::Call => 1,
 			CallType::CallCode => 2,
 			CallType::DelegateCall => 3,
+			CallType::StaticCall => 4,
 		};
 		s.append(&v);
 	}
@@ -56,6 +59,7 @@ impl Decodable for CallType {
 			1 => CallType::Call,
 			2 => CallType::CallCode,
 			3 => CallType::DelegateCall,
+			4 => CallType::StaticCall,
 			_ => return Err(DecoderError::Custom("Invalid value of CallType item")),
 		}))
 	}
@@ -65,6 +69,9 @@ impl Decodable for CallType {
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "ipc", binary)]
 pub struct Executed {
+	/// True if the outer call/create resulted in an exceptional exit.
+	pub exception: Option<TraceError>,
+
 	/// Gas paid up front for execution of transaction.
 	pub gas: U256,
 
@@ -100,6 +107,8 @@ pub struct Executed {
 	pub vm_trace: Option<VMTrace>,
 	/// The state diff, if we traced it.
 	pub state_diff: Option<StateDiff>,
+	/// The struct logs, if we recorded them.
+	pub struct_logs: Option<Vec<StructLog>>,
 }
 
 /// Result of executing the transaction.
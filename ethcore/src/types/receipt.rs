@@ -24,28 +24,44 @@ use basic_types::LogBloom;
 use header::BlockNumber;
 use log_entry::{LogEntry, LocalizedLogEntry};
 
+/// Transaction outcome store in the receipt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "ipc", binary)]
+pub enum TransactionOutcome {
+	/// State root is known. Pre-EIP-658 rules.
+	StateRoot(H256),
+	/// Status code is known. EIP-658 rules.
+	StatusCode(u8),
+}
+
+impl Default for TransactionOutcome {
+	fn default() -> Self {
+		TransactionOutcome::StateRoot(H256::default())
+	}
+}
+
 /// Information describing execution of a transaction.
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "ipc", binary)]
 pub struct Receipt {
-	/// The state root after executing the transaction.
-	pub state_root: H256,
 	/// The total gas used in the block following execution of the transaction.
 	pub gas_used: U256,
 	/// The OR-wide combination of all logs' blooms for this transaction.
 	pub log_bloom: LogBloom,
 	/// The logs stemming from this transaction.
 	pub logs: Vec<LogEntry>,
+	/// Transaction outcome.
+	pub outcome: TransactionOutcome,
 }
 
 impl Receipt {
 	/// Create a new receipt.
-	pub fn new(state_root: H256, gas_used: U256, logs: Vec<LogEntry>) -> Receipt {
+	pub fn new(outcome: TransactionOutcome, gas_used: U256, logs: Vec<LogEntry>) -> Receipt {
 		Receipt {
-			state_root: state_root,
 			gas_used: gas_used,
 			log_bloom: logs.iter().fold(LogBloom::default(), |mut b, l| { b = &b | &l.bloom(); b }), //TODO: use |= operator
 			logs: logs,
+			outcome: outcome,
 		}
 	}
 }
@@ -53,7 +69,10 @@ impl Receipt {
 impl Encodable for Receipt {
 	fn rlp_append(&self, s: &mut RlpStream) {
 		s.begin_list(4);
-		s.append(&self.state_root);
+		match self.outcome {
+			TransactionOutcome::StateRoot(ref root) => { s.append(root); },
+			TransactionOutcome::StatusCode(ref status_code) => { s.append(status_code); },
+		}
 		s.append(&self.gas_used);
 		s.append(&self.log_bloom);
 		s.append(&self.logs);
@@ -63,11 +82,18 @@ impl Encodable for Receipt {
 impl Decodable for Receipt {
 	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
 		let d = decoder.as_rlp();
+		let first = d.at(0)?;
+		// state root is always 32 bytes long, status code fits into a single byte.
+		let outcome = if first.is_data() && first.data()?.len() <= 1 {
+			TransactionOutcome::StatusCode(first.as_val()?)
+		} else {
+			TransactionOutcome::StateRoot(first.as_val()?)
+		};
 		let receipt = Receipt {
-			state_root: d.val_at(0)?,
 			gas_used: d.val_at(1)?,
 			log_bloom: d.val_at(2)?,
 			logs: d.val_at(3)?,
+			outcome: outcome,
 		};
 		Ok(receipt)
 	}
@@ -97,8 +123,8 @@ pub struct RichReceipt {
 	pub logs: Vec<LogEntry>,
 	/// Logs bloom
 	pub log_bloom: LogBloom,
-	/// State root
-	pub state_root: H256,
+	/// Transaction outcome.
+	pub outcome: TransactionOutcome,
 }
 
 /// Receipt with additional info.
@@ -123,15 +149,38 @@ pub struct LocalizedReceipt {
 	pub logs: Vec<LocalizedLogEntry>,
 	/// Logs bloom
 	pub log_bloom: LogBloom,
-	/// State root
-	pub state_root: H256,
+	/// Transaction outcome.
+	pub outcome: TransactionOutcome,
+}
+
+#[test]
+fn test_no_state_root() {
+	let r = Receipt::new(
+		TransactionOutcome::StatusCode(1),
+		0x40cae.into(),
+		vec![LogEntry {
+			address: "dcf421d093428b096ca501a7cd1a740855a7976f".into(),
+			topics: vec![],
+			data: vec![0u8; 32]
+		}]
+	);
+	let encoded = encode(&r);
+	assert_eq!(UntrustedRlp::new(&encoded).at(0).unwrap().as_raw(), &[0x01]);
+	assert_eq!(decode::<Receipt>(&encoded), r);
+}
+
+#[test]
+fn test_failed_status_code() {
+	let r = Receipt::new(TransactionOutcome::StatusCode(0), 0x40cae.into(), vec![]);
+	let encoded = encode(&r);
+	assert_eq!(decode::<Receipt>(&encoded), r);
 }
 
 #[test]
 fn test_basic() {
 	let expected = ::rustc_serialize::hex::FromHex::from_hex("f90162a02f697d671e9ae4ee24a43c4b0d7e15f1cb4ba6de1561120d43b9a4e8c4a8a6ee83040caeb9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000000f838f794dcf421d093428b096ca501a7cd1a740855a7976fc0a00000000000000000000000000000000000000000000000000000000000000000").unwrap();
 	let r = Receipt::new(
-		"2f697d671e9ae4ee24a43c4b0d7e15f1cb4ba6de1561120d43b9a4e8c4a8a6ee".into(),
+		TransactionOutcome::StateRoot("2f697d671e9ae4ee24a43c4b0d7e15f1cb4ba6de1561120d43b9a4e8c4a8a6ee".into()),
 		0x40cae.into(),
 		vec![LogEntry {
 			address: "dcf421d093428b096ca501a7cd1a740855a7976f".into(),
@@ -139,5 +188,7 @@ fn test_basic() {
 			data: vec![0u8; 32]
 		}]
 	);
-	assert_eq!(&encode(&r)[..], &expected[..]);
+	let encoded = encode(&r);
+	assert_eq!(&encoded[..], &expected[..]);
+	assert_eq!(decode::<Receipt>(&encoded), r);
 }
@@ -35,6 +35,14 @@ pub enum Error {
 	StackUnderflow,
 	/// When execution would exceed defined Stack Limit
 	OutOfStack,
+	/// When builtin contract failed on input data
+	BuiltIn,
+	/// Returned on trying to modify state in static context
+	MutableCallInStaticContext,
+	/// Returned when RETURNDATACOPY reads out of bounds
+	OutOfBounds,
+	/// Execution has been reverted with REVERT instruction.
+	Reverted,
 	/// Returned on evm internal error. Should never be ignored during development.
 	/// Likely to cause consensus issues.
 	Internal,
@@ -48,6 +56,10 @@ impl From<EvmError> for Error {
 			EvmError::BadInstruction { .. } => Error::BadInstruction,
 			EvmError::StackUnderflow { .. } => Error::StackUnderflow,
 			EvmError::OutOfStack { .. } => Error::OutOfStack,
+			EvmError::BuiltIn { .. } => Error::BuiltIn,
+			EvmError::MutableCallInStaticContext => Error::MutableCallInStaticContext,
+			EvmError::OutOfBounds => Error::OutOfBounds,
+			EvmError::Reverted => Error::Reverted,
 			EvmError::Internal => Error::Internal,
 		}
 	}
@@ -62,6 +74,10 @@ impl fmt::Display for Error {
 			BadInstruction => "Bad instruction",
 			StackUnderflow => "Stack underflow",
 			OutOfStack => "Out of stack",
+			BuiltIn => "Built-in failed",
+			MutableCallInStaticContext => "Mutable call in static context",
+			OutOfBounds => "Out of bounds",
+			Reverted => "Reverted",
 			Internal => "Internal error",
 		};
 		message.fmt(f)
@@ -78,6 +94,10 @@ impl Encodable for Error {
 			StackUnderflow => 3,
 			OutOfStack => 4,
 			Internal => 5,
+			BuiltIn => 6,
+			MutableCallInStaticContext => 7,
+			OutOfBounds => 8,
+			Reverted => 9,
 		};
 		s.append(&value);
 	}
@@ -94,6 +114,10 @@ impl Decodable for Error {
 			3 => Ok(StackUnderflow),
 			4 => Ok(OutOfStack),
 			5 => Ok(Internal),
+			6 => Ok(BuiltIn),
+			7 => Ok(MutableCallInStaticContext),
+			8 => Ok(OutOfBounds),
+			9 => Ok(Reverted),
 			_ => Err(DecoderError::Custom("Invalid error type")),
 		}
 	}
@@ -130,6 +130,12 @@ impl Filter {
 				let from_matches = self.from_address.matches(&suicide.address);
 				let to_matches = self.to_address.matches(&suicide.refund_address);
 				from_matches && to_matches
+			},
+			Action::Reward(ref reward) => {
+				// rewards have no sender, so they only match a filter which accepts any sender.
+				let from_matches = self.from_address.matches_all();
+				let to_matches = self.to_address.matches(&reward.author);
+				from_matches && to_matches
 			}
 		}
 	}
@@ -140,7 +146,7 @@ mod tests {
 	use util::Address;
 	use util::sha3::Hashable;
 	use util::bloom::Bloomable;
-	use trace::trace::{Action, Call, Res, Create, CreateResult, Suicide};
+	use trace::trace::{Action, Call, Res, Create, CreateResult, Suicide, Reward, RewardType};
 	use trace::flat::FlatTrace;
 	use trace::{Filter, AddressesFilter, TraceError};
 	use types::executed::CallType;
@@ -343,5 +349,24 @@ mod tests {
 		assert!(f4.matches(&trace));
 		assert!(f5.matches(&trace));
 		assert!(!f6.matches(&trace));
+
+		let trace = FlatTrace {
+			action: Action::Reward(Reward {
+				author: 2.into(),
+				value: 100.into(),
+				reward_type: RewardType::Block,
+			}),
+			result: Res::None,
+			trace_address: vec![].into_iter().collect(),
+			subtraces: 0
+		};
+
+		assert!(!f0.matches(&trace));
+		assert!(!f1.matches(&trace));
+		assert!(f2.matches(&trace));
+		assert!(f3.matches(&trace));
+		assert!(f4.matches(&trace));
+		assert!(!f5.matches(&trace));
+		assert!(!f6.matches(&trace));
 	}
 }
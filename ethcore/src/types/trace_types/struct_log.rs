@@ -0,0 +1,55 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Struct logs produced by step-by-step transaction replay.
+
+use std::collections::BTreeMap;
+use util::{U256, H256, Bytes};
+
+/// Options concerning which parts of the VM state are recorded for every step.
+#[derive(Eq, PartialEq, Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct StructLogOptions {
+	/// Don't record the stack.
+	pub disable_stack: bool,
+	/// Don't record the memory.
+	pub disable_memory: bool,
+	/// Don't record the storage.
+	pub disable_storage: bool,
+}
+
+/// A single executed instruction together with the VM state right before its execution.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// Name of the instruction.
+	pub op: String,
+	/// Gas left before executing the instruction.
+	pub gas: U256,
+	/// Cost of the instruction.
+	pub gas_cost: U256,
+	/// Call depth, starting at 1 for the outermost call/create.
+	pub depth: usize,
+	/// Stack, bottom first. `None` if disabled.
+	pub stack: Option<Vec<U256>>,
+	/// Memory contents. `None` if disabled.
+	pub memory: Option<Bytes>,
+	/// Storage entries of the executing contract read or written so far. `None` if disabled.
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
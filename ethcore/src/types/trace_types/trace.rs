@@ -264,8 +264,83 @@ impl Decodable for Suicide {
 	}
 }
 
+/// Kind of a reward bestowed by the engine.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", binary)]
+pub enum RewardType {
+	/// Reward for the author of the block.
+	Block,
+	/// Reward for the author of an included uncle.
+	Uncle,
+	/// Reward decided by a block reward contract.
+	External,
+}
+
+impl Encodable for RewardType {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		let v = match *self {
+			RewardType::Block => 0u32,
+			RewardType::Uncle => 1,
+			RewardType::External => 2,
+		};
+		s.append(&v);
+	}
+}
+
+impl Decodable for RewardType {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		decoder.as_rlp().as_val().and_then(|v| Ok(match v {
+			0u32 => RewardType::Block,
+			1 => RewardType::Uncle,
+			2 => RewardType::External,
+			_ => return Err(DecoderError::Custom("Invalid value of RewardType item")),
+		}))
+	}
+}
+
+/// Reward action.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct Reward {
+	/// Author's address.
+	pub author: Address,
+	/// Reward amount.
+	pub value: U256,
+	/// Reward type.
+	pub reward_type: RewardType,
+}
+
+impl Reward {
+	/// Return reward action bloom.
+	/// The bloom contains only the author address.
+	pub fn bloom(&self) -> LogBloom {
+		LogBloom::from_bloomed(&self.author.sha3())
+	}
+}
+
+impl Encodable for Reward {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(3);
+		s.append(&self.author);
+		s.append(&self.value);
+		s.append(&self.reward_type);
+	}
+}
 
-/// Description of an action that we trace; will be either a call or a create.
+impl Decodable for Reward {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let d = decoder.as_rlp();
+		let res = Reward {
+			author: d.val_at(0)?,
+			value: d.val_at(1)?,
+			reward_type: d.val_at(2)?,
+		};
+
+		Ok(res)
+	}
+}
+
+/// Description of an action that we trace; will be either a call, a create, a suicide or a reward.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "ipc", binary)]
 pub enum Action {
@@ -275,6 +350,8 @@ pub enum Action {
 	Create(Create),
 	/// Suicide.
 	Suicide(Suicide),
+	/// Block or uncle reward.
+	Reward(Reward),
 }
 
 impl Encodable for Action {
@@ -292,6 +369,10 @@ impl Encodable for Action {
 			Action::Suicide(ref suicide) => {
 				s.append(&2u8);
 				s.append(suicide);
+			},
+			Action::Reward(ref reward) => {
+				s.append(&3u8);
+				s.append(reward);
 			}
 		}
 	}
@@ -305,6 +386,7 @@ impl Decodable for Action {
 			0 => d.val_at(1).map(Action::Call),
 			1 => d.val_at(1).map(Action::Create),
 			2 => d.val_at(1).map(Action::Suicide),
+			3 => d.val_at(1).map(Action::Reward),
 			_ => Err(DecoderError::Custom("Invalid action type.")),
 		}
 	}
@@ -317,6 +399,7 @@ impl Action {
 			Action::Call(ref call) => call.bloom(),
 			Action::Create(ref create) => create.bloom(),
 			Action::Suicide(ref suicide) => suicide.bloom(),
+			Action::Reward(ref reward) => reward.bloom(),
 		}
 	}
 }
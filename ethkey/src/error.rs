@@ -29,6 +29,10 @@ pub enum Error {
 	InvalidSignature,
 	/// Invalid AES message
 	InvalidMessage,
+	/// Invalid mnemonic phrase
+	InvalidMnemonic,
+	/// Invalid key derivation path
+	InvalidDerivationPath,
 	/// IO Error
 	Io(::std::io::Error),
 	/// Custom
@@ -43,6 +47,8 @@ impl fmt::Display for Error {
 			Error::InvalidAddress => "Invalid address".into(),
 			Error::InvalidSignature => "Invalid EC signature".into(),
 			Error::InvalidMessage => "Invalid AES message".into(),
+			Error::InvalidMnemonic => "Invalid mnemonic phrase".into(),
+			Error::InvalidDerivationPath => "Invalid derivation path".into(),
 			Error::Io(ref err) => format!("I/O error: {}", err),
 			Error::Custom(ref s) => s.clone(),
 		};
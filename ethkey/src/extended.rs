@@ -0,0 +1,212 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! BIP32 hierarchical deterministic keys.
+
+use std::fmt;
+use std::str::FromStr;
+use rcrypto::hmac::Hmac;
+use rcrypto::mac::Mac;
+use rcrypto::sha2::Sha512;
+use secp256k1::key;
+use bigint::hash::H256;
+use super::{Secret, KeyPair, SECP256K1, Error};
+
+/// Child indices starting from this one are hardened.
+pub const HARDENED: u32 = 0x80000000;
+
+/// Path from a master key to one of its descendants, e.g. `m/44'/60'/0'/0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+	/// Path of BIP44 Ethereum accounts; account `n` is its child `n`.
+	pub fn ethereum() -> Self {
+		DerivationPath(vec![44 | HARDENED, 60 | HARDENED, HARDENED, 0])
+	}
+
+	/// Child indices along the path.
+	pub fn indices(&self) -> &[u32] {
+		&self.0
+	}
+
+	/// Path to the child `index` of the key at this path.
+	pub fn child(&self, index: u32) -> Self {
+		let mut indices = self.0.clone();
+		indices.push(index);
+		DerivationPath(indices)
+	}
+}
+
+impl FromStr for DerivationPath {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Error> {
+		let mut parts = s.split('/');
+		if parts.next() != Some("m") {
+			return Err(Error::InvalidDerivationPath);
+		}
+
+		parts.map(|part| {
+			let (number, offset) = match part.ends_with('\'') {
+				true => (&part[..part.len() - 1], HARDENED),
+				false => (part, 0),
+			};
+			match number.parse::<u32>() {
+				Ok(n) if n < HARDENED => Ok(n | offset),
+				_ => Err(Error::InvalidDerivationPath),
+			}
+		}).collect::<Result<Vec<_>, _>>().map(DerivationPath)
+	}
+}
+
+impl fmt::Display for DerivationPath {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "m")?;
+		for index in &self.0 {
+			match *index >= HARDENED {
+				true => write!(f, "/{}'", index - HARDENED)?,
+				false => write!(f, "/{}", index)?,
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Secret key extended with the chain code needed to derive its children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendedSecret {
+	secret: Secret,
+	chain_code: H256,
+}
+
+impl ExtendedSecret {
+	/// Master key of the tree generated from `seed`.
+	pub fn from_seed(seed: &[u8]) -> Result<Self, Error> {
+		let i = hmac_sha512(b"Bitcoin seed", seed);
+		ExtendedSecret::from_hmac(&i, None)
+	}
+
+	/// The plain secret key, usable for signing.
+	pub fn secret(&self) -> &Secret {
+		&self.secret
+	}
+
+	/// Chain code mixed into the derivation of this key's children.
+	pub fn chain_code(&self) -> &H256 {
+		&self.chain_code
+	}
+
+	/// Derive the child key `index`; indices from `HARDENED` on produce hardened keys.
+	pub fn derive(&self, index: u32) -> Result<Self, Error> {
+		let context = &SECP256K1;
+		let parent = key::SecretKey::from_slice(context, &self.secret)?;
+
+		let mut data = Vec::with_capacity(37);
+		if index >= HARDENED {
+			data.push(0);
+			data.extend_from_slice(&self.secret);
+		} else {
+			let public = key::PublicKey::from_secret_key(context, &parent)?;
+			data.extend_from_slice(&public.serialize_vec(context, true));
+		}
+		data.extend_from_slice(&[(index >> 24) as u8, (index >> 16) as u8, (index >> 8) as u8, index as u8]);
+
+		let i = hmac_sha512(&self.chain_code, &data);
+		ExtendedSecret::from_hmac(&i, Some(&parent))
+	}
+
+	/// Derive the descendant at `path`, relative to this key.
+	pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, Error> {
+		path.indices().iter().fold(Ok(self.clone()), |key, index| key.and_then(|k| k.derive(*index)))
+	}
+
+	/// Key pair of this key, without the chain code.
+	pub fn key_pair(&self) -> Result<KeyPair, Error> {
+		KeyPair::from_secret(self.secret.clone())
+	}
+
+	/// Key from the output of HMAC-SHA512, tweaked by the `parent` secret for child keys.
+	fn from_hmac(i: &[u8; 64], parent: Option<&key::SecretKey>) -> Result<Self, Error> {
+		let context = &SECP256K1;
+		let mut child = key::SecretKey::from_slice(context, &i[..32])?;
+		if let Some(parent) = parent {
+			child.add_assign(context, parent)?;
+		}
+
+		let mut secret = Secret::default();
+		secret.copy_from_slice(&child[0..32]);
+		let mut chain_code = H256::default();
+		chain_code.copy_from_slice(&i[32..]);
+
+		Ok(ExtendedSecret {
+			secret: secret,
+			chain_code: chain_code,
+		})
+	}
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+	let mut mac = Hmac::new(Sha512::new(), key);
+	mac.input(data);
+	let mut result = [0u8; 64];
+	mac.raw_result(&mut result);
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use rustc_serialize::hex::{FromHex, ToHex};
+	use {Address, Mnemonic, Secret};
+	use super::{ExtendedSecret, DerivationPath, HARDENED};
+
+	#[test]
+	fn should_derive_bip32_test_vector() {
+		let seed = "000102030405060708090a0b0c0d0e0f".from_hex().unwrap();
+		let master = ExtendedSecret::from_seed(&seed).unwrap();
+		assert_eq!(master.secret(), &Secret::from_str("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35").unwrap());
+		assert_eq!(master.chain_code().to_hex(), "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508");
+
+		let hardened = master.derive(HARDENED).unwrap();
+		assert_eq!(hardened.secret(), &Secret::from_str("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea").unwrap());
+		assert_eq!(hardened.chain_code().to_hex(), "47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141");
+
+		let normal = master.derive_path(&"m/0'/1".parse().unwrap()).unwrap();
+		assert_eq!(normal.secret(), &Secret::from_str("3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368").unwrap());
+		assert_eq!(normal.chain_code().to_hex(), "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19");
+	}
+
+	#[test]
+	fn should_derive_ethereum_account_from_mnemonic() {
+		let mnemonic = Mnemonic::from_phrase("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+		let master = ExtendedSecret::from_seed(&mnemonic.seed("")).unwrap();
+		let account = master.derive_path(&DerivationPath::ethereum().child(0)).unwrap();
+		assert_eq!(account.key_pair().unwrap().address(), Address::from_str("9858effd232b4033e47d90003d41ec34ecaeda94").unwrap());
+	}
+
+	#[test]
+	fn should_parse_and_display_paths() {
+		let path: DerivationPath = "m/44'/60'/0'/0".parse().unwrap();
+		assert_eq!(path, DerivationPath::ethereum());
+		assert_eq!(path.to_string(), "m/44'/60'/0'/0");
+		assert_eq!(DerivationPath::from_str("m").unwrap().indices().len(), 0);
+		assert!(DerivationPath::from_str("44'/60'").is_err());
+		assert!(DerivationPath::from_str("m/x").is_err());
+		assert!(DerivationPath::from_str("m/2147483648").is_err());
+	}
+}
@@ -20,12 +20,15 @@ extern crate lazy_static;
 extern crate tiny_keccak;
 extern crate secp256k1;
 extern crate rustc_serialize;
+extern crate crypto as rcrypto;
 extern crate ethcore_bigint as bigint;
 
 mod brain;
 mod error;
+mod extended;
 mod keypair;
 mod keccak;
+mod mnemonic;
 mod prefix;
 mod random;
 mod signature;
@@ -42,7 +45,9 @@ pub trait Generator {
 
 pub use self::brain::Brain;
 pub use self::error::Error;
+pub use self::extended::{ExtendedSecret, DerivationPath, HARDENED};
 pub use self::keypair::{KeyPair, public_to_address};
+pub use self::mnemonic::Mnemonic;
 pub use self::prefix::Prefix;
 pub use self::random::Random;
 pub use self::signature::{sign, verify_public, verify_address, recover, Signature};
@@ -0,0 +1,170 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! BIP39 mnemonic phrases.
+
+use rand::Rng;
+use rand::os::OsRng;
+use rcrypto::digest::Digest;
+use rcrypto::sha2::{Sha256, Sha512};
+use rcrypto::hmac::Hmac;
+use rcrypto::pbkdf2::pbkdf2;
+use super::Error;
+
+/// Number of PBKDF2 rounds used to stretch a phrase into a seed.
+const SEED_ROUNDS: u32 = 2048;
+
+lazy_static! {
+	static ref WORDS: Vec<&'static str> = include_str!("../res/bip39_english.txt").lines().collect();
+}
+
+/// Mnemonic phrase encoding entropy together with its checksum, using the english wordlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mnemonic {
+	phrase: String,
+	entropy: Vec<u8>,
+}
+
+impl Mnemonic {
+	/// Generate a random phrase of `words` words; must be one of 12, 15, 18, 21 or 24.
+	pub fn random(words: usize) -> Result<Mnemonic, Error> {
+		if !is_valid_length(words) {
+			return Err(Error::InvalidMnemonic);
+		}
+		let mut entropy = vec![0u8; words / 3 * 4];
+		let mut rng = OsRng::new()?;
+		rng.fill_bytes(&mut entropy);
+		Mnemonic::from_entropy(&entropy)
+	}
+
+	/// Encode 16 to 32 bytes of entropy (a multiple of 4) as a phrase.
+	pub fn from_entropy(entropy: &[u8]) -> Result<Mnemonic, Error> {
+		if entropy.len() % 4 != 0 || entropy.len() < 16 || entropy.len() > 32 {
+			return Err(Error::InvalidMnemonic);
+		}
+
+		let mut hash = [0u8; 32];
+		let mut hasher = Sha256::new();
+		hasher.input(entropy);
+		hasher.result(&mut hash);
+
+		// entropy followed by `len / 4` bits of its hash, split into 11-bit word indices
+		let mut bits: Vec<bool> = entropy.iter().flat_map(|byte| (0..8).map(move |i| byte & (0x80 >> i) != 0)).collect();
+		bits.extend((0..entropy.len() / 4).map(|i| hash[0] & (0x80 >> i) != 0));
+		let phrase = bits.chunks(11)
+			.map(|chunk| WORDS[chunk.iter().fold(0, |acc, &bit| acc << 1 | bit as usize)])
+			.collect::<Vec<_>>()
+			.join(" ");
+
+		Ok(Mnemonic {
+			phrase: phrase,
+			entropy: entropy.to_vec(),
+		})
+	}
+
+	/// Parse a phrase, checking that all words are known and the checksum matches.
+	pub fn from_phrase(phrase: &str) -> Result<Mnemonic, Error> {
+		let words: Vec<&str> = phrase.split_whitespace().collect();
+		if !is_valid_length(words.len()) {
+			return Err(Error::InvalidMnemonic);
+		}
+
+		let mut bits = Vec::with_capacity(words.len() * 11);
+		for word in &words {
+			let index = WORDS.binary_search_by(|probe| probe.as_bytes().cmp(word.as_bytes())).map_err(|_| Error::InvalidMnemonic)?;
+			bits.extend((0..11).map(|i| index & (0x400 >> i) != 0));
+		}
+		let entropy: Vec<u8> = bits[..bits.len() * 32 / 33].chunks(8)
+			.map(|chunk| chunk.iter().fold(0, |acc, &bit| acc << 1 | bit as u8))
+			.collect();
+
+		let mnemonic = Mnemonic::from_entropy(&entropy)?;
+		match mnemonic.phrase == words.join(" ") {
+			true => Ok(mnemonic),
+			false => Err(Error::InvalidMnemonic),
+		}
+	}
+
+	/// Words of the phrase separated by single spaces.
+	pub fn phrase(&self) -> &str {
+		&self.phrase
+	}
+
+	/// Entropy encoded by the phrase.
+	pub fn entropy(&self) -> &[u8] {
+		&self.entropy
+	}
+
+	/// 64-byte seed for hierarchical key derivation, protected by an optional `passphrase`.
+	/// Passphrases are used as given, without unicode normalization.
+	pub fn seed(&self, passphrase: &str) -> Vec<u8> {
+		let mut mac = Hmac::new(Sha512::new(), self.phrase.as_bytes());
+		let salt = format!("mnemonic{}", passphrase);
+		let mut seed = vec![0u8; 64];
+		pbkdf2(&mut mac, salt.as_bytes(), SEED_ROUNDS, &mut seed);
+		seed
+	}
+}
+
+fn is_valid_length(words: usize) -> bool {
+	words % 3 == 0 && words >= 12 && words <= 24
+}
+
+#[cfg(test)]
+mod tests {
+	use rustc_serialize::hex::{FromHex, ToHex};
+	use super::Mnemonic;
+
+	#[test]
+	fn should_encode_entropy() {
+		let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+		assert_eq!(mnemonic.phrase(), "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about");
+		assert_eq!(
+			mnemonic.seed("TREZOR").to_hex(),
+			"c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+		);
+
+		let entropy = "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f".from_hex().unwrap();
+		let mnemonic = Mnemonic::from_entropy(&entropy).unwrap();
+		assert_eq!(mnemonic.phrase(), "legal winner thank year wave sausage worth useful legal winner thank yellow");
+	}
+
+	#[test]
+	fn should_parse_phrase() {
+		let mnemonic = Mnemonic::from_phrase("legal winner thank year  wave sausage worth useful legal winner thank yellow").unwrap();
+		assert_eq!(mnemonic.entropy().to_hex(), "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f");
+		assert_eq!(mnemonic.phrase(), "legal winner thank year wave sausage worth useful legal winner thank yellow");
+	}
+
+	#[test]
+	fn should_reject_invalid_phrases() {
+		// bad checksum
+		assert!(Mnemonic::from_phrase("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon").is_err());
+		// unknown word
+		assert!(Mnemonic::from_phrase("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon sparta").is_err());
+		// wrong length
+		assert!(Mnemonic::from_phrase("abandon abandon abandon").is_err());
+	}
+
+	#[test]
+	fn random_phrases_should_be_valid() {
+		let mnemonic = Mnemonic::random(24).unwrap();
+		assert_eq!(mnemonic.entropy().len(), 32);
+		assert_eq!(Mnemonic::from_phrase(mnemonic.phrase()).unwrap(), mnemonic);
+		assert!(Mnemonic::random(13).is_err());
+	}
+}
@@ -0,0 +1,218 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethkey::{KeyPair, Address, Secret, ExtendedSecret, DerivationPath};
+use {json, Error};
+use account::{Crypto, KdfParams};
+
+/// Account derived from the seed of a wallet.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DerivedAccount {
+	/// Index of the account's key among the children of the wallet's parent key.
+	pub index: u32,
+	pub address: Address,
+	pub name: String,
+	pub meta: String,
+}
+
+/// Hierarchical deterministic wallet, whose crypto holds the seed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HdWallet {
+	pub id: [u8; 16],
+	pub crypto: Crypto,
+	/// Path of the parent key of all accounts.
+	pub path: DerivationPath,
+	/// Accounts derived so far; removed accounts are dropped, the others keep their index.
+	pub accounts: Vec<DerivedAccount>,
+	pub filename: Option<String>,
+}
+
+impl From<DerivedAccount> for json::WalletAccount {
+	fn from(account: DerivedAccount) -> Self {
+		json::WalletAccount {
+			index: account.index,
+			address: account.address.into(),
+			name: account.name,
+			meta: account.meta,
+		}
+	}
+}
+
+impl Into<json::WalletFile> for HdWallet {
+	fn into(self) -> json::WalletFile {
+		json::WalletFile {
+			id: From::from(self.id),
+			crypto: self.crypto.into(),
+			path: self.path.to_string(),
+			accounts: self.accounts.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+impl HdWallet {
+	/// Create a wallet from `seed`, with its first account derived.
+	/// Accounts are children of the key at `path`.
+	pub fn create(seed: &[u8], path: DerivationPath, id: [u8; 16], password: &str, iterations: u32) -> Result<Self, Error> {
+		let address = derive_address(seed, &path, 0)?;
+		Ok(HdWallet {
+			id: id,
+			crypto: Crypto::with_plain(seed, password, iterations),
+			path: path,
+			accounts: vec![DerivedAccount {
+				index: 0,
+				address: address,
+				name: String::new(),
+				meta: "{}".to_owned(),
+			}],
+			filename: None,
+		})
+	}
+
+	/// Create a new `HdWallet` from the given `json`, read from `filename` if it's `Some`.
+	pub fn from_file(json: json::WalletFile, filename: Option<String>) -> Result<Self, Error> {
+		let path = json.path.parse().map_err(|_| Error::InvalidKeyFile(format!("Invalid derivation path: {}", json.path)))?;
+		Ok(HdWallet {
+			id: json.id.into(),
+			crypto: json.crypto.into(),
+			path: path,
+			accounts: json.accounts.into_iter().map(|account| DerivedAccount {
+				index: account.index,
+				address: account.address.into(),
+				name: account.name,
+				meta: account.meta,
+			}).collect(),
+			filename: filename,
+		})
+	}
+
+	/// Account of this wallet at `address`.
+	pub fn account(&self, address: &Address) -> Option<&DerivedAccount> {
+		self.accounts.iter().find(|account| &account.address == address)
+	}
+
+	/// Secret of the account at `address`, derived from the decrypted seed.
+	pub fn secret(&self, address: &Address, password: &str) -> Result<Secret, Error> {
+		let index = self.account(address).ok_or(Error::InvalidAccount)?.index;
+		let seed = self.crypto.decrypt(password)?;
+		let child = ExtendedSecret::from_seed(&seed)?.derive_path(&self.path.child(index))?;
+		Ok(child.secret().clone())
+	}
+
+	/// Derive the next account. Returns the updated wallet and the new account's address.
+	pub fn derive_next(&self, password: &str) -> Result<(Self, Address), Error> {
+		let seed = self.crypto.decrypt(password)?;
+		let index = self.accounts.iter().map(|account| account.index + 1).max().unwrap_or(0);
+		let address = derive_address(&seed, &self.path, index)?;
+
+		let mut wallet = self.clone();
+		wallet.accounts.push(DerivedAccount {
+			index: index,
+			address: address,
+			name: String::new(),
+			meta: "{}".to_owned(),
+		});
+		Ok((wallet, address))
+	}
+
+	/// Remove the account at `address`, keeping the seed.
+	/// Returns `None` if it was the last account of the wallet.
+	pub fn remove_account(&self, address: &Address) -> Result<Option<Self>, Error> {
+		if self.account(address).is_none() {
+			return Err(Error::InvalidAccount);
+		}
+
+		let mut wallet = self.clone();
+		wallet.accounts.retain(|account| &account.address != address);
+		match wallet.accounts.is_empty() {
+			true => Ok(None),
+			false => Ok(Some(wallet)),
+		}
+	}
+
+	/// Change the metadata of the account at `address` only.
+	pub fn with_account_info<F>(&self, address: &Address, update: F) -> Result<Self, Error> where F: FnOnce(&mut DerivedAccount) {
+		let mut wallet = self.clone();
+		update(wallet.accounts.iter_mut().find(|account| &account.address == address).ok_or(Error::InvalidAccount)?);
+		Ok(wallet)
+	}
+
+	pub fn change_password(&self, old_password: &str, new_password: &str, iterations: u32) -> Result<Self, Error> {
+		let seed = self.crypto.decrypt(old_password)?;
+		let mut wallet = self.clone();
+		wallet.crypto = Crypto::with_plain(&seed, new_password, iterations);
+		Ok(wallet)
+	}
+
+	/// Re-encrypt the seed with the same password, deriving the key as described by `params`.
+	pub fn with_kdf(&self, password: &str, params: KdfParams) -> Result<Self, Error> {
+		let seed = self.crypto.decrypt(password)?;
+		let mut wallet = self.clone();
+		wallet.crypto = Crypto::with_plain_kdf(&seed, password, params)?;
+		Ok(wallet)
+	}
+
+	pub fn check_password(&self, password: &str) -> bool {
+		self.crypto.decrypt(password).is_ok()
+	}
+}
+
+fn derive_address(seed: &[u8], path: &DerivationPath, index: u32) -> Result<Address, Error> {
+	let child = ExtendedSecret::from_seed(seed)?.derive_path(&path.child(index))?;
+	Ok(KeyPair::from_secret(child.secret().clone())?.address())
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{KeyPair, Mnemonic, DerivationPath, ExtendedSecret};
+	use super::HdWallet;
+
+	#[test]
+	fn derived_accounts() {
+		let seed = Mnemonic::from_entropy(&[0u8; 16]).unwrap().seed("");
+		let path = DerivationPath::ethereum();
+		let wallet = HdWallet::create(&seed, path.clone(), [0u8; 16], "test", 10240).unwrap();
+		let (wallet, second) = wallet.derive_next("test").unwrap();
+		assert_eq!(wallet.accounts[1].address, second);
+
+		let master = ExtendedSecret::from_seed(&seed).unwrap();
+		for (i, account) in wallet.accounts.iter().enumerate() {
+			let secret = wallet.secret(&account.address, "test").unwrap();
+			assert_eq!(&secret, master.derive_path(&path.child(i as u32)).unwrap().secret());
+			assert_eq!(KeyPair::from_secret(secret).unwrap().address(), account.address);
+		}
+		assert!(wallet.secret(&second, "x").is_err());
+	}
+
+	#[test]
+	fn removed_accounts_keep_other_indices() {
+		let seed = Mnemonic::from_entropy(&[0u8; 16]).unwrap().seed("");
+		let wallet = HdWallet::create(&seed, DerivationPath::ethereum(), [0u8; 16], "test", 10240).unwrap();
+		let first = wallet.accounts[0].address;
+		let (wallet, second) = wallet.derive_next("test").unwrap();
+
+		let wallet = wallet.remove_account(&first).unwrap().unwrap();
+		assert_eq!(wallet.account(&second).unwrap().index, 1);
+		let (wallet, third) = wallet.derive_next("test").unwrap();
+		assert_eq!(wallet.account(&third).unwrap().index, 2);
+
+		let wallet = wallet.with_account_info(&third, |account| account.name = "Third".into()).unwrap();
+		assert_eq!(wallet.account(&second).unwrap().name, "");
+		assert_eq!(wallet.account(&third).unwrap().name, "Third");
+
+		let wallet = wallet.remove_account(&second).unwrap().unwrap();
+		assert_eq!(wallet.remove_account(&third).unwrap(), None);
+	}
+}
@@ -44,6 +44,32 @@ pub enum Kdf {
 	Scrypt(Scrypt),
 }
 
+/// Key derivation to use when encrypting a key; the salt is chosen randomly.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum KdfParams {
+	/// PBKDF2 with HMAC-SHA256 and the given number of iterations.
+	Pbkdf2 {
+		iterations: u32,
+	},
+	/// Scrypt with cost `n` (a power of 2), block size `r` and parallelization `p`.
+	Scrypt {
+		n: u32,
+		r: u32,
+		p: u32,
+	},
+}
+
+impl KdfParams {
+	/// Scrypt with the standard parameters of other Ethereum clients.
+	pub fn scrypt() -> Self {
+		KdfParams::Scrypt {
+			n: 262144,
+			r: 8,
+			p: 1,
+		}
+	}
+}
+
 impl From<json::Prf> for Prf {
 	fn from(json: json::Prf) -> Self {
 		match json {
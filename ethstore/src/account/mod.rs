@@ -15,11 +15,13 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 mod cipher;
+mod hd_wallet;
 mod kdf;
 mod safe_account;
 mod version;
 
 pub use self::cipher::{Cipher, Aes128Ctr};
-pub use self::kdf::{Kdf, Pbkdf2, Scrypt, Prf};
+pub use self::hd_wallet::{HdWallet, DerivedAccount};
+pub use self::kdf::{Kdf, KdfParams, Pbkdf2, Scrypt, Prf};
 pub use self::safe_account::{SafeAccount, Crypto};
 pub use self::version::Version;
@@ -18,7 +18,7 @@ use ethkey::{KeyPair, sign, Address, Secret, Signature, Message, Public};
 use {json, Error, crypto};
 use crypto::Keccak256;
 use random::Random;
-use account::{Version, Cipher, Kdf, Aes128Ctr, Pbkdf2, Prf};
+use account::{Version, Cipher, Kdf, KdfParams, Aes128Ctr, Pbkdf2, Scrypt, Prf};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Crypto {
@@ -37,6 +37,8 @@ pub struct SafeAccount {
 	pub filename: Option<String>,
 	pub name: String,
 	pub meta: String,
+	/// Name of the vault holding this account, if any.
+	pub vault: Option<String>,
 }
 
 impl From<json::Crypto> for Crypto {
@@ -76,34 +78,54 @@ impl Into<json::KeyFile> for SafeAccount {
 
 impl Crypto {
 	pub fn create(secret: &Secret, password: &str, iterations: u32) -> Self {
+		Crypto::with_plain(&**secret, password, iterations)
+	}
+
+	/// Encrypt arbitrary `plain` data, e.g. a wallet seed.
+	pub fn with_plain(plain: &[u8], password: &str, iterations: u32) -> Self {
+		Crypto::with_plain_kdf(plain, password, KdfParams::Pbkdf2 { iterations: iterations })
+			.expect("PBKDF2 key derivation never fails; qed")
+	}
+
+	/// Encrypt arbitrary `plain` data, deriving the key as described by `params`.
+	pub fn with_plain_kdf(plain: &[u8], password: &str, params: KdfParams) -> Result<Self, Error> {
 		let salt: [u8; 32] = Random::random();
 		let iv: [u8; 16] = Random::random();
 
 		// two parts of derived key
 		// DK = [ DK[0..15] DK[16..31] ] = [derived_left_bits, derived_right_bits]
-		let (derived_left_bits, derived_right_bits) = crypto::derive_key_iterations(password, &salt, iterations);
+		let ((derived_left_bits, derived_right_bits), kdf) = match params {
+			KdfParams::Pbkdf2 { iterations } => (crypto::derive_key_iterations(password, &salt, iterations), Kdf::Pbkdf2(Pbkdf2 {
+				dklen: crypto::KEY_LENGTH as u32,
+				salt: salt,
+				c: iterations,
+				prf: Prf::HmacSha256,
+			})),
+			KdfParams::Scrypt { n, r, p } => (crypto::derive_key_scrypt(password, &salt, n, p, r)?, Kdf::Scrypt(Scrypt {
+				dklen: crypto::KEY_LENGTH as u32,
+				salt: salt,
+				n: n,
+				r: r,
+				p: p,
+			})),
+		};
 
-		let mut ciphertext = [0u8; 32];
+		let mut ciphertext = vec![0u8; plain.len()];
 
 		// aes-128-ctr with initial vector of iv
-		crypto::aes::encrypt(&derived_left_bits, &iv, &**secret, &mut ciphertext);
+		crypto::aes::encrypt(&derived_left_bits, &iv, plain, &mut ciphertext);
 
 		// KECCAK(DK[16..31] ++ <ciphertext>), where DK[16..31] - derived_right_bits
 		let mac = crypto::derive_mac(&derived_right_bits, &ciphertext).keccak256();
 
-		Crypto {
+		Ok(Crypto {
 			cipher: Cipher::Aes128Ctr(Aes128Ctr {
 				iv: iv,
 			}),
-			ciphertext: ciphertext.to_vec(),
-			kdf: Kdf::Pbkdf2(Pbkdf2 {
-				dklen: crypto::KEY_LENGTH as u32,
-				salt: salt,
-				c: iterations,
-				prf: Prf::HmacSha256,
-			}),
+			ciphertext: ciphertext,
+			kdf: kdf,
 			mac: mac,
-		}
+		})
 	}
 
 	pub fn secret(&self, password: &str) -> Result<Secret, Error> {
@@ -111,6 +133,15 @@ impl Crypto {
 			return Err(Error::InvalidSecret);
 		}
 
+		let plain = self.decrypt(password)?;
+		let mut secret = Secret::default();
+		let from = 32 - plain.len();
+		(&mut *secret)[from..].copy_from_slice(&plain);
+		Ok(secret)
+	}
+
+	/// Decrypt the data without interpreting it.
+	pub fn decrypt(&self, password: &str) -> Result<Vec<u8>, Error> {
 		let (derived_left_bits, derived_right_bits) = match self.kdf {
 			Kdf::Pbkdf2(ref params) => crypto::derive_key_iterations(password, &params.salt, params.c),
 			Kdf::Scrypt(ref params) => crypto::derive_key_scrypt(password, &params.salt, params.n, params.p, params.r)?,
@@ -122,16 +153,15 @@ impl Crypto {
 			return Err(Error::InvalidPassword);
 		}
 
-		let mut secret = Secret::default();
+		let mut plain = vec![0u8; self.ciphertext.len()];
 
 		match self.cipher {
 			Cipher::Aes128Ctr(ref params) => {
-				let from = 32 - self.ciphertext.len();
-				crypto::aes::decrypt(&derived_left_bits, &params.iv, &self.ciphertext, &mut (&mut *secret)[from..])
+				crypto::aes::decrypt(&derived_left_bits, &params.iv, &self.ciphertext, &mut plain)
 			},
 		}
 
-		Ok(secret)
+		Ok(plain)
 	}
 }
 
@@ -152,6 +182,7 @@ impl SafeAccount {
 			filename: None,
 			name: name,
 			meta: meta,
+			vault: None,
 		}
 	}
 
@@ -167,46 +198,62 @@ impl SafeAccount {
 			filename: filename,
 			name: json.name.unwrap_or(String::new()),
 			meta: json.meta.unwrap_or("{}".to_owned()),
+			vault: None,
 		}
 	}
 
+	pub fn secret(&self, password: &str) -> Result<Secret, Error> {
+		self.crypto.secret(password)
+	}
+
 	pub fn sign(&self, password: &str, message: &Message) -> Result<Signature, Error> {
-		let secret = self.crypto.secret(password)?;
+		let secret = self.secret(password)?;
 		sign(&secret, message).map_err(From::from)
 	}
 
 	pub fn decrypt(&self, password: &str, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
-		let secret = self.crypto.secret(password)?;
+		let secret = self.secret(password)?;
 		crypto::ecies::decrypt(&secret, shared_mac, message).map_err(From::from)
 	}
 
 	pub fn public(&self, password: &str) -> Result<Public, Error> {
-		let secret = self.crypto.secret(password)?;
+		let secret = self.secret(password)?;
 		Ok(KeyPair::from_secret(secret)?.public().clone())
 	}
 
 	pub fn change_password(&self, old_password: &str, new_password: &str, iterations: u32) -> Result<Self, Error> {
-		let secret = self.crypto.secret(old_password)?;
+		let plain = self.crypto.decrypt(old_password)?;
 		let result = SafeAccount {
 			id: self.id.clone(),
 			version: self.version.clone(),
-			crypto: Crypto::create(&secret, new_password, iterations),
+			crypto: Crypto::with_plain(&plain, new_password, iterations),
 			address: self.address.clone(),
 			filename: self.filename.clone(),
 			name: self.name.clone(),
 			meta: self.meta.clone(),
+			vault: self.vault.clone(),
 		};
 		Ok(result)
 	}
 
+	/// Re-encrypt with the same password, deriving the key as described by `params`.
+	/// Everything else, including the UUID and file name, is preserved.
+	pub fn with_kdf(&self, password: &str, params: KdfParams) -> Result<Self, Error> {
+		let plain = self.crypto.decrypt(password)?;
+		let mut account = self.clone();
+		account.crypto = Crypto::with_plain_kdf(&plain, password, params)?;
+		Ok(account)
+	}
+
 	pub fn check_password(&self, password: &str) -> bool {
-		self.crypto.secret(password).is_ok()
+		self.crypto.decrypt(password).is_ok()
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use ethkey::{Generator, Random, verify_public, Message};
+	use account::{Kdf, KdfParams};
 	use super::{Crypto, SafeAccount};
 
 	#[test]
@@ -249,4 +296,20 @@ mod tests {
 		assert!(new_account.sign(first_password, &message).is_err());
 		assert!(new_account.sign(sec_password, &message).is_ok());
 	}
+
+	#[test]
+	fn upgrade_kdf() {
+		let keypair = Random.generate().unwrap();
+		let password = "hello world";
+		let message = Message::default();
+		let account = SafeAccount::create(&keypair, [1u8; 16], password, 1024, "Test".to_owned(), "{}".to_owned());
+		let upgraded = account.with_kdf(password, KdfParams::Scrypt { n: 1024, r: 8, p: 1 }).unwrap();
+		match upgraded.crypto.kdf {
+			Kdf::Scrypt(ref params) => assert_eq!(params.n, 1024),
+			_ => panic!("Key should be derived with scrypt."),
+		}
+		assert_eq!(upgraded.id, account.id);
+		assert!(upgraded.sign(password, &message).is_ok());
+		assert!(account.with_kdf("wrong", KdfParams::Pbkdf2 { iterations: 2048 }).is_err());
+	}
 }
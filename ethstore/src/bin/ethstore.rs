@@ -23,7 +23,7 @@ use std::io::Read;
 use docopt::Docopt;
 use ethstore::ethkey::Address;
 use ethstore::dir::{KeyDirectory, ParityDirectory, DiskDirectory, GethDirectory, DirectoryType};
-use ethstore::{EthStore, SecretStore, import_accounts, Error, PresaleWallet};
+use ethstore::{EthStore, SecretStore, import_accounts, export_key_file, export_secret, upgrade_kdf, Error, PresaleWallet, KdfParams};
 
 pub const USAGE: &'static str = r#"
 Ethereum key management.
@@ -38,6 +38,15 @@ Usage:
     ethstore remove <address> <password> [--dir DIR]
     ethstore sign <address> <password> <message> [--dir DIR]
     ethstore public <address> <password>
+    ethstore export <address> [--dir DIR]
+    ethstore export-secret <address> <password> [--dir DIR]
+    ethstore upgrade-kdf <password> [--kdf KDF] [--iterations N] [--dir DIR]
+    ethstore vault create <vault> <password> [--dir DIR]
+    ethstore vault change-pwd <vault> <old-pwd> <new-pwd> [--dir DIR]
+    ethstore vault list [--dir DIR]
+    ethstore vault accounts <vault> <password> [--dir DIR]
+    ethstore vault move-in <address> <vault> <password> [--dir DIR]
+    ethstore vault move-out <address> <vault> <password> [--dir DIR]
     ethstore [-h | --help]
 
 Options:
@@ -48,6 +57,9 @@ Options:
     --src DIR          Specify import source. It may be either
                        parity, parity-test, get, geth-test
                        or a path [default: geth].
+    --kdf KDF          Key derivation to re-encrypt keys with. It may be
+                       either scrypt or pbkdf2 [default: scrypt].
+    --iterations N     Number of PBKDF2 iterations [default: 262144].
 
 Commands:
     insert             Save account with password.
@@ -58,6 +70,18 @@ Commands:
     remove             Remove account.
     sign               Sign message.
     public             Displays public key for an address.
+    export             Print key file of an account, not in a vault.
+                       Accounts of HD wallets can't be exported.
+    export-secret      Print unencrypted secret of an account, not in a vault.
+    upgrade-kdf        Re-encrypt all keys with a stronger key derivation.
+                       Password file may contain a password per line.
+                       Keys in vaults are left untouched.
+    vault create       Create new password-protected vault.
+    vault change-pwd   Change vault password.
+    vault list         List vaults.
+    vault accounts     List accounts in vault.
+    vault move-in      Move account to vault.
+    vault move-out     Move account out of vault.
 "#;
 
 #[derive(Debug, RustcDecodable)]
@@ -70,6 +94,14 @@ struct Args {
 	cmd_remove: bool,
 	cmd_sign: bool,
 	cmd_public: bool,
+	cmd_export: bool,
+	cmd_export_secret: bool,
+	cmd_upgrade_kdf: bool,
+	cmd_vault: bool,
+	cmd_create: bool,
+	cmd_accounts: bool,
+	cmd_move_in: bool,
+	cmd_move_out: bool,
 	arg_secret: String,
 	arg_password: String,
 	arg_old_pwd: String,
@@ -77,8 +109,11 @@ struct Args {
 	arg_address: String,
 	arg_message: String,
 	arg_path: String,
+	arg_vault: String,
 	flag_src: String,
 	flag_dir: String,
+	flag_kdf: String,
+	flag_iterations: u32,
 }
 
 fn main() {
@@ -111,6 +146,13 @@ fn format_accounts(accounts: &[Address]) -> String {
 		.join("\n")
 }
 
+fn load_passwords(path: &str) -> Result<Vec<String>, Error> {
+	let mut file = fs::File::open(path)?;
+	let mut passwords = String::new();
+	file.read_to_string(&mut passwords)?;
+	Ok(passwords.lines().map(ToOwned::to_owned).collect())
+}
+
 fn load_password(path: &str) -> Result<String, Error> {
 	let mut file = fs::File::open(path)?;
 	let mut password = String::new();
@@ -127,7 +169,9 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 
 	let store = EthStore::open(key_dir(&args.flag_dir)?)?;
 
-	return if args.cmd_insert {
+	return if args.cmd_vault {
+		execute_vault(&store, &args)
+	} else if args.cmd_insert {
 		let secret = args.arg_secret.parse().map_err(|_| Error::InvalidSecret)?;
 		let password = load_password(&args.arg_password)?;
 		let address = store.insert_account(secret, &password)?;
@@ -168,8 +212,64 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 		let password = load_password(&args.arg_password)?;
 		let public = store.public(&address, &password)?;
 		Ok(format!("0x{:?}", public))
+	} else if args.cmd_export {
+		let address = args.arg_address.parse().map_err(|_| Error::InvalidAccount)?;
+		let dir = key_dir(&args.flag_dir)?;
+		export_key_file(&*dir, &address)
+	} else if args.cmd_export_secret {
+		let address = args.arg_address.parse().map_err(|_| Error::InvalidAccount)?;
+		let password = load_password(&args.arg_password)?;
+		let dir = key_dir(&args.flag_dir)?;
+		let secret = export_secret(&*dir, &address, &password)?;
+		Ok(format!("0x{:?}", secret))
+	} else if args.cmd_upgrade_kdf {
+		let params = match args.flag_kdf.as_str() {
+			"scrypt" => KdfParams::scrypt(),
+			"pbkdf2" => KdfParams::Pbkdf2 { iterations: args.flag_iterations },
+			kdf => return Err(Error::Custom(format!("Unknown key derivation: {}", kdf))),
+		};
+		let passwords = load_passwords(&args.arg_password)?;
+		let dir = key_dir(&args.flag_dir)?;
+		let result = upgrade_kdf(&*dir, &passwords, params)?;
+		let mut output = format!("Upgraded {} account(s)", result.upgraded.len());
+		if !result.skipped.is_empty() {
+			output = format!("{}, skipped (no matching password):\n{}", output, format_accounts(&result.skipped));
+		}
+		Ok(output)
 	} else {
 		Ok(format!("{}", USAGE))
 	}
 }
 
+fn execute_vault(store: &EthStore, args: &Args) -> Result<String, Error> {
+	if args.cmd_create {
+		let password = load_password(&args.arg_password)?;
+		store.create_vault(&args.arg_vault, &password)?;
+		Ok(format!("{}", true))
+	} else if args.cmd_change_pwd {
+		let old_pwd = load_password(&args.arg_old_pwd)?;
+		let new_pwd = load_password(&args.arg_new_pwd)?;
+		store.open_vault(&args.arg_vault, &old_pwd)?;
+		let ok = store.change_vault_password(&args.arg_vault, &new_pwd).is_ok();
+		Ok(format!("{}", ok))
+	} else if args.cmd_list {
+		let vaults = store.list_vaults()?;
+		Ok(vaults.join("\n"))
+	} else if args.cmd_accounts {
+		let password = load_password(&args.arg_password)?;
+		let others = store.accounts()?;
+		store.open_vault(&args.arg_vault, &password)?;
+		let accounts = store.accounts()?
+			.into_iter()
+			.filter(|a| !others.contains(a))
+			.collect::<Vec<_>>();
+		Ok(format_accounts(&accounts))
+	} else {
+		let address = args.arg_address.parse().map_err(|_| Error::InvalidAccount)?;
+		let password = load_password(&args.arg_password)?;
+		store.open_vault(&args.arg_vault, &password)?;
+		let vault = if args.cmd_move_in { Some(args.arg_vault.clone()) } else { None };
+		let ok = store.change_account_vault(&address, vault).is_ok();
+		Ok(format!("{}", ok))
+	}
+}
@@ -20,9 +20,14 @@ use std::collections::HashMap;
 use time;
 use {json, SafeAccount, Error};
 use json::Uuid;
-use super::KeyDirectory;
+use account::HdWallet;
+use super::{KeyDirectory, VaultKeyDirectory, VaultKeyDirectoryProvider, VaultDiskDirectory, WalletDirectory};
+use super::vault::VAULT_FILE_NAME;
 
-const IGNORED_FILES: &'static [&'static str] = &["thumbs.db", "address_book.json", "dapps_policy.json"];
+/// Sub-directory holding the hierarchical deterministic wallets.
+const WALLETS_DIR_NAME: &'static str = "wallets";
+
+const IGNORED_FILES: &'static [&'static str] = &["thumbs.db", "address_book.json", "dapps_policy.json", VAULT_FILE_NAME];
 
 #[cfg(not(windows))]
 fn restrict_permissions_to_owner(file_path: &Path) -> Result<(), i32>  {
@@ -60,36 +65,21 @@ impl DiskDirectory {
 
 	/// all accounts found in keys directory
 	fn files(&self) -> Result<HashMap<PathBuf, SafeAccount>, Error> {
-		// it's not done using one iterator cause
-		// there is an issue with rustc and it takes tooo much time to compile
-		let paths = fs::read_dir(&self.path)?
-			.flat_map(Result::ok)
-			.filter(|entry| {
-				let metadata = entry.metadata().ok();
-				let file_name = entry.file_name();
-				let name = file_name.to_string_lossy();
-				// filter directories
-				metadata.map_or(false, |m| !m.is_dir()) &&
-				// hidden files
-				!name.starts_with(".") &&
-				// other ignored files
-				!IGNORED_FILES.contains(&&*name)
-			})
-			.map(|entry| entry.path())
-			.collect::<Vec<PathBuf>>();
+		let paths = key_file_paths(&self.path)?;
 
 		Ok(paths
 			.iter()
 			.map(|p| (
 				fs::File::open(p)
 					.map_err(Error::from)
-					.and_then(|r| json::KeyFile::load(r).map_err(|e| Error::Custom(format!("{:?}", e)))),
+					.and_then(|r| json::KeyFile::load(r).map_err(|e| Error::Custom(format!("{:?}", e))))
+					.map(|file| SafeAccount::from_file(
+						file, Some(p.file_name().and_then(|n| n.to_str()).expect("Keys have valid UTF8 names only.").to_owned())
+					)),
 				p
 			))
-			.filter_map(|(file, path)| match file {
-				Ok(file) => Some((path.clone(), SafeAccount::from_file(
-					file, Some(path.file_name().and_then(|n| n.to_str()).expect("Keys have valid UTF8 names only.").to_owned())
-				))),
+			.filter_map(|(account, path)| match account {
+				Ok(account) => Some((path.clone(), account)),
 				Err(err) => {
 					warn!("Invalid key file: {:?} ({})", path, err);
 					None
@@ -117,33 +107,7 @@ impl KeyDirectory for DiskDirectory {
 	fn insert(&self, account: SafeAccount) -> Result<SafeAccount, Error> {
 		// transform account into key file
 		let keyfile: json::KeyFile = account.clone().into();
-
-		// build file path
-		let filename = account.filename.as_ref().cloned().unwrap_or_else(|| {
-			let timestamp = time::strftime("%Y-%m-%dT%H-%M-%S", &time::now_utc()).expect("Time-format string is valid.");
-			format!("UTC--{}Z--{}", timestamp, Uuid::from(account.id))
-		});
-
-		// update account filename
-		let mut account = account;
-		account.filename = Some(filename.clone());
-
-		{
-			// Path to keyfile
-			let mut keyfile_path = self.path.clone();
-			keyfile_path.push(filename.as_str());
-
-			// save the file
-			let mut file = fs::File::create(&keyfile_path)?;
-			keyfile.write(&mut file).map_err(|e| Error::Custom(format!("{:?}", e)))?;
-
-			if let Err(_) = restrict_permissions_to_owner(keyfile_path.as_path()) {
-				fs::remove_file(keyfile_path).expect("Expected to remove recently created file");
-				return Err(Error::Io(io::Error::last_os_error()));
-			}
-		}
-
-		Ok(account)
+		write_key_file(&self.path, account, |file| keyfile.write(file).map_err(|e| Error::Custom(format!("{:?}", e))))
 	}
 
 	fn remove(&self, account: &SafeAccount) -> Result<(), Error> {
@@ -161,6 +125,144 @@ impl KeyDirectory for DiskDirectory {
 	}
 
 	fn path(&self) -> Option<&PathBuf> { Some(&self.path) }
+
+	fn as_vault_provider(&self) -> Option<&VaultKeyDirectoryProvider> { Some(self) }
+
+	fn as_wallet_directory(&self) -> Option<&WalletDirectory> { Some(self) }
+}
+
+impl WalletDirectory for DiskDirectory {
+	fn load_wallets(&self) -> Result<Vec<HdWallet>, Error> {
+		let path = self.path.join(WALLETS_DIR_NAME);
+		if !path.is_dir() {
+			return Ok(Vec::new());
+		}
+
+		Ok(key_file_paths(&path)?
+			.into_iter()
+			.filter_map(|path| match read_wallet_file(&path) {
+				Ok(wallet) => Some(wallet),
+				Err(err) => {
+					warn!("Invalid wallet file: {:?} ({})", path, err);
+					None
+				},
+			})
+			.collect()
+		)
+	}
+
+	fn save_wallet(&self, wallet: HdWallet) -> Result<HdWallet, Error> {
+		let path = self.path.join(WALLETS_DIR_NAME);
+		fs::create_dir_all(&path)?;
+
+		let mut wallet = wallet;
+		let filename = wallet.filename.clone().unwrap_or_else(|| key_file_name(&wallet.id));
+		let wallet_file: json::WalletFile = wallet.clone().into();
+		// the seed is only in this file, so it must never be left half-written.
+		replace_file(&path.join(&filename), |file| wallet_file.write(file).map_err(|e| Error::Custom(format!("{:?}", e))))?;
+		wallet.filename = Some(filename);
+		Ok(wallet)
+	}
+
+	fn remove_wallet(&self, wallet: &HdWallet) -> Result<(), Error> {
+		let filename = wallet.filename.as_ref().ok_or(Error::InvalidAccount)?;
+		fs::remove_file(self.path.join(WALLETS_DIR_NAME).join(filename)).map_err(From::from)
+	}
+}
+
+fn read_wallet_file(path: &Path) -> Result<HdWallet, Error> {
+	let file = fs::File::open(path)?;
+	let wallet_file = json::WalletFile::load(file).map_err(|e| Error::InvalidKeyFile(format!("{:?}", e)))?;
+	let filename = path.file_name().and_then(|n| n.to_str()).expect("Keys have valid UTF8 names only.").to_owned();
+	HdWallet::from_file(wallet_file, Some(filename))
+}
+
+impl VaultKeyDirectoryProvider for DiskDirectory {
+	fn create(&self, name: &str, password: &str, iterations: u32) -> Result<Box<VaultKeyDirectory>, Error> {
+		let vault = VaultDiskDirectory::create(&self.path, name, password, iterations)?;
+		Ok(Box::new(vault))
+	}
+
+	fn open(&self, name: &str, password: &str, iterations: u32) -> Result<Box<VaultKeyDirectory>, Error> {
+		let vault = VaultDiskDirectory::at(&self.path, name, password, iterations)?;
+		Ok(Box::new(vault))
+	}
+
+	fn list_vaults(&self) -> Result<Vec<String>, Error> {
+		Ok(fs::read_dir(&self.path)?
+			.flat_map(Result::ok)
+			.filter(|entry| entry.path().join(VAULT_FILE_NAME).is_file())
+			.filter_map(|entry| entry.file_name().to_str().map(ToOwned::to_owned))
+			.collect())
+	}
+}
+
+/// Paths of all files in `path` which may be key files.
+pub fn key_file_paths(path: &Path) -> Result<Vec<PathBuf>, Error> {
+	// it's not done using one iterator cause
+	// there is an issue with rustc and it takes tooo much time to compile
+	let paths = fs::read_dir(path)?
+		.flat_map(Result::ok)
+		.filter(|entry| {
+			let metadata = entry.metadata().ok();
+			let file_name = entry.file_name();
+			let name = file_name.to_string_lossy();
+			// filter directories
+			metadata.map_or(false, |m| !m.is_dir()) &&
+			// hidden files
+			!name.starts_with(".") &&
+			// other ignored files
+			!IGNORED_FILES.contains(&&*name)
+		})
+		.map(|entry| entry.path())
+		.collect::<Vec<PathBuf>>();
+
+	Ok(paths)
+}
+
+/// Save the key file of `account` in `path`, with contents produced by `write`.
+/// An existing file is replaced atomically. Returns the account with its file name set.
+pub fn write_key_file<F>(path: &Path, account: SafeAccount, write: F) -> Result<SafeAccount, Error>
+	where F: FnOnce(&mut fs::File) -> Result<(), Error>
+{
+	// build file path
+	let filename = account.filename.as_ref().cloned().unwrap_or_else(|| key_file_name(&account.id));
+
+	// update account filename
+	let mut account = account;
+	account.filename = Some(filename.clone());
+
+	// save the file
+	replace_file(&path.join(filename.as_str()), write)?;
+	Ok(account)
+}
+
+/// Name of a new key file for the key `id`.
+fn key_file_name(id: &[u8; 16]) -> String {
+	let timestamp = time::strftime("%Y-%m-%dT%H-%M-%S", &time::now_utc()).expect("Time-format string is valid.");
+	format!("UTC--{}Z--{}", timestamp, Uuid::from(*id))
+}
+
+/// Replace the file at `path` with contents produced by `write`.
+/// They're written to a hidden file which is then renamed over `path`,
+/// so a crash never leaves a half-written file behind.
+pub fn replace_file<F>(path: &Path, write: F) -> Result<(), Error>
+	where F: FnOnce(&mut fs::File) -> Result<(), Error>
+{
+	let name = path.file_name().and_then(|n| n.to_str()).expect("Keys have valid UTF8 names only.");
+	let temp_path = path.with_file_name(format!(".{}.tmp", name));
+
+	let written = fs::File::create(&temp_path)
+		.map_err(Error::from)
+		.and_then(|mut file| write(&mut file).and_then(|_| file.sync_all().map_err(Error::from)))
+		.and_then(|_| restrict_permissions_to_owner(&temp_path).map_err(|_| Error::Io(io::Error::last_os_error())));
+
+	if let Err(err) = written {
+		let _ = fs::remove_file(&temp_path);
+		return Err(err);
+	}
+
+	fs::rename(&temp_path, path).map_err(From::from)
 }
 
 
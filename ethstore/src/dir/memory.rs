@@ -20,11 +20,14 @@ use itertools::Itertools;
 use ethkey::Address;
 
 use {SafeAccount, Error};
-use super::KeyDirectory;
+use account::HdWallet;
+use json::Uuid;
+use super::{KeyDirectory, WalletDirectory};
 
 #[derive(Default)]
 pub struct MemoryDirectory {
 	accounts: RwLock<HashMap<Address, Vec<SafeAccount>>>,
+	wallets: RwLock<Vec<HdWallet>>,
 }
 
 impl KeyDirectory for MemoryDirectory {
@@ -63,5 +66,30 @@ impl KeyDirectory for MemoryDirectory {
 		}
 		Ok(())
 	}
+
+	fn as_wallet_directory(&self) -> Option<&WalletDirectory> { Some(self) }
+}
+
+impl WalletDirectory for MemoryDirectory {
+	fn load_wallets(&self) -> Result<Vec<HdWallet>, Error> {
+		Ok(self.wallets.read().clone())
+	}
+
+	fn save_wallet(&self, wallet: HdWallet) -> Result<HdWallet, Error> {
+		let mut wallet = wallet;
+		if wallet.filename.is_none() {
+			wallet.filename = Some(format!("{}", Uuid::from(wallet.id)));
+		}
+
+		let mut wallets = self.wallets.write();
+		wallets.retain(|w| w.filename != wallet.filename);
+		wallets.push(wallet.clone());
+		Ok(wallet)
+	}
+
+	fn remove_wallet(&self, wallet: &HdWallet) -> Result<(), Error> {
+		self.wallets.write().retain(|w| w.filename != wallet.filename);
+		Ok(())
+	}
 }
 
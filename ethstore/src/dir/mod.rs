@@ -16,11 +16,13 @@
 
 use std::path::{PathBuf};
 use {SafeAccount, Error};
+use account::HdWallet;
 
 mod disk;
 mod geth;
 mod memory;
 mod parity;
+mod vault;
 
 pub enum DirectoryType {
 	Testnet,
@@ -33,9 +35,45 @@ pub trait KeyDirectory: Send + Sync {
 	fn update(&self, account: SafeAccount) -> Result<SafeAccount, Error>;
 	fn remove(&self, account: &SafeAccount) -> Result<(), Error>;
 	fn path(&self) -> Option<&PathBuf> { None }
+	/// Vaults kept alongside this directory, if supported.
+	fn as_vault_provider(&self) -> Option<&VaultKeyDirectoryProvider> { None }
+	/// Hierarchical deterministic wallets kept alongside this directory, if supported.
+	fn as_wallet_directory(&self) -> Option<&WalletDirectory> { None }
+}
+
+/// Stores hierarchical deterministic wallets, apart from the key files.
+pub trait WalletDirectory: Send + Sync {
+	/// All stored wallets.
+	fn load_wallets(&self) -> Result<Vec<HdWallet>, Error>;
+	/// Save `wallet`, replacing the stored one with the same file name.
+	/// Returns the wallet with its file name set.
+	fn save_wallet(&self, wallet: HdWallet) -> Result<HdWallet, Error>;
+	/// Remove a stored wallet.
+	fn remove_wallet(&self, wallet: &HdWallet) -> Result<(), Error>;
+}
+
+/// Creates and opens vaults: groups of keys additionally protected by a vault password.
+pub trait VaultKeyDirectoryProvider {
+	/// Create a new vault `name` protected by `password`.
+	fn create(&self, name: &str, password: &str, iterations: u32) -> Result<Box<VaultKeyDirectory>, Error>;
+	/// Open the existing vault `name`, failing if `password` is invalid.
+	fn open(&self, name: &str, password: &str, iterations: u32) -> Result<Box<VaultKeyDirectory>, Error>;
+	/// Names of all vaults, opened or not.
+	fn list_vaults(&self) -> Result<Vec<String>, Error>;
+}
+
+/// Key directory of an opened vault.
+pub trait VaultKeyDirectory: KeyDirectory {
+	/// Cast to the plain key directory.
+	fn as_key_directory(&self) -> &KeyDirectory;
+	/// Name of the vault.
+	fn name(&self) -> &str;
+	/// Re-encrypt the vault and all of its keys with `new_password`.
+	fn set_password(&mut self, new_password: &str) -> Result<(), Error>;
 }
 
 pub use self::disk::DiskDirectory;
 pub use self::geth::GethDirectory;
 pub use self::memory::MemoryDirectory;
 pub use self::parity::ParityDirectory;
+pub use self::vault::VaultDiskDirectory;
@@ -17,7 +17,7 @@
 use std::env;
 use std::path::PathBuf;
 use {SafeAccount, Error};
-use super::{KeyDirectory, DiskDirectory, DirectoryType};
+use super::{KeyDirectory, DiskDirectory, DirectoryType, VaultKeyDirectoryProvider, WalletDirectory};
 
 fn parity_dir_path() -> PathBuf {
 	let mut home = env::home_dir().expect("Failed to get home dir");
@@ -74,4 +74,8 @@ impl KeyDirectory for ParityDirectory {
 	fn remove(&self, account: &SafeAccount) -> Result<(), Error> {
 		self.dir.remove(account)
 	}
+
+	fn as_vault_provider(&self) -> Option<&VaultKeyDirectoryProvider> { self.dir.as_vault_provider() }
+
+	fn as_wallet_directory(&self) -> Option<&WalletDirectory> { self.dir.as_wallet_directory() }
 }
@@ -0,0 +1,334 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fs;
+use std::path::{PathBuf, Path};
+use {json, SafeAccount, Error};
+use account::Crypto;
+use random::Random;
+use super::{KeyDirectory, VaultKeyDirectory};
+use super::disk::{key_file_paths, write_key_file, replace_file};
+
+/// Name of the file describing a vault, kept in the vault directory.
+pub const VAULT_FILE_NAME: &'static str = "vault.json";
+/// Hidden directory the key files are re-encrypted into when the vault password changes.
+const REENCRYPT_DIR_NAME: &'static str = ".reencrypt";
+/// Marks the re-encrypted files as complete; they're moved into the vault only once it's written.
+const REENCRYPT_DONE_FILE_NAME: &'static str = ".done";
+
+/// Vault kept in a sub-directory of a disk key directory.
+/// Key files are stored whole, encrypted with the vault password.
+pub struct VaultDiskDirectory {
+	path: PathBuf,
+	name: String,
+	password: String,
+	iterations: u32,
+}
+
+impl VaultDiskDirectory {
+	/// Create a new vault `name` in the key directory at `root`.
+	pub fn create<P>(root: P, name: &str, password: &str, iterations: u32) -> Result<Self, Error> where P: AsRef<Path> {
+		let path = vault_path(root, name)?;
+		if path.exists() {
+			return Err(Error::VaultExists);
+		}
+
+		fs::create_dir_all(&path)?;
+		let vault = VaultDiskDirectory::new(path, name, password, iterations);
+		if let Err(err) = vault.write_vault_file() {
+			let _ = fs::remove_dir_all(&vault.path);
+			return Err(err);
+		}
+		Ok(vault)
+	}
+
+	/// Open the existing vault `name` in the key directory at `root`.
+	pub fn at<P>(root: P, name: &str, password: &str, iterations: u32) -> Result<Self, Error> where P: AsRef<Path> {
+		let path = vault_path(root, name)?;
+		// finish or roll back a password change interrupted by a crash
+		complete_reencrypt(&path)?;
+		let file = fs::File::open(path.join(VAULT_FILE_NAME)).map_err(|_| Error::VaultNotFound)?;
+		let vault_file = json::VaultFile::load(file).map_err(|e| Error::InvalidKeyFile(format!("{:?}", e)))?;
+		// fails if the password is invalid
+		Crypto::from(vault_file.crypto).decrypt(password)?;
+		Ok(VaultDiskDirectory::new(path, name, password, iterations))
+	}
+
+	fn new(path: PathBuf, name: &str, password: &str, iterations: u32) -> Self {
+		VaultDiskDirectory {
+			path: path,
+			name: name.to_owned(),
+			password: password.to_owned(),
+			iterations: iterations,
+		}
+	}
+
+	fn write_vault_file(&self) -> Result<(), Error> {
+		self.write_vault_file_to(&self.path, &self.password)
+	}
+
+	fn write_vault_file_to(&self, dir: &Path, password: &str) -> Result<(), Error> {
+		let check: [u8; 32] = Random::random();
+		let vault_file = json::VaultFile {
+			crypto: Crypto::with_plain(&check, password, self.iterations).into(),
+		};
+		replace_file(&dir.join(VAULT_FILE_NAME), |file| vault_file.write(file).map_err(|e| Error::Custom(format!("{:?}", e))))
+	}
+
+	/// Write the key file of `account` to `dir`, encrypted with `password`.
+	fn write_account(&self, dir: &Path, account: SafeAccount, password: &str) -> Result<SafeAccount, Error> {
+		let mut account = account;
+		account.vault = Some(self.name.clone());
+
+		let key_file: json::KeyFile = account.clone().into();
+		let mut plain = Vec::new();
+		key_file.write(&mut plain).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+		let vault_key_file = json::VaultKeyFile {
+			crypto: Crypto::with_plain(&plain, password, self.iterations).into(),
+		};
+
+		write_key_file(dir, account, |file| vault_key_file.write(file).map_err(|e| Error::Custom(format!("{:?}", e))))
+	}
+
+	/// Re-encrypt all key files and the vault file into `dir` with `password`,
+	/// then mark them as complete.
+	fn reencrypt_to(&self, dir: &Path, password: &str) -> Result<(), Error> {
+		for account in self.load()? {
+			self.write_account(dir, account, password)?;
+		}
+		self.write_vault_file_to(dir, password)?;
+
+		let done = fs::File::create(dir.join(REENCRYPT_DONE_FILE_NAME))?;
+		done.sync_all().map_err(From::from)
+	}
+
+	/// Re-encrypt everything aside with `new_password`, leaving the vault itself untouched.
+	fn prepare_reencrypt(&self, new_password: &str) -> Result<(), Error> {
+		let temp_dir = self.path.join(REENCRYPT_DIR_NAME);
+		let _ = fs::remove_dir_all(&temp_dir);
+		fs::create_dir_all(&temp_dir)?;
+
+		if let Err(err) = self.reencrypt_to(&temp_dir, new_password) {
+			let _ = fs::remove_dir_all(&temp_dir);
+			return Err(err);
+		}
+		Ok(())
+	}
+
+	/// All accounts in the vault with paths of their files.
+	fn files(&self) -> Result<Vec<(PathBuf, SafeAccount)>, Error> {
+		Ok(key_file_paths(&self.path)?
+			.into_iter()
+			.filter_map(|path| match self.read_key_file(&path) {
+				Ok(account) => Some((path, account)),
+				Err(err) => {
+					warn!("Invalid vault key file: {:?} ({})", path, err);
+					None
+				},
+			})
+			.collect()
+		)
+	}
+
+	fn read_key_file(&self, path: &Path) -> Result<SafeAccount, Error> {
+		let file = fs::File::open(path)?;
+		let vault_key_file = json::VaultKeyFile::load(file).map_err(|e| Error::InvalidKeyFile(format!("{:?}", e)))?;
+		let plain = Crypto::from(vault_key_file.crypto).decrypt(&self.password)?;
+		let key_file = json::KeyFile::load(&plain[..]).map_err(|e| Error::InvalidKeyFile(format!("{:?}", e)))?;
+		let filename = path.file_name().and_then(|n| n.to_str()).expect("Keys have valid UTF8 names only.").to_owned();
+
+		let mut account = SafeAccount::from_file(key_file, Some(filename));
+		account.vault = Some(self.name.clone());
+		Ok(account)
+	}
+}
+
+impl KeyDirectory for VaultDiskDirectory {
+	fn load(&self) -> Result<Vec<SafeAccount>, Error> {
+		Ok(self.files()?.into_iter().map(|(_, account)| account).collect())
+	}
+
+	fn update(&self, account: SafeAccount) -> Result<SafeAccount, Error> {
+		// files are replaced iff filename is the same
+		self.insert(account)
+	}
+
+	fn insert(&self, account: SafeAccount) -> Result<SafeAccount, Error> {
+		self.write_account(&self.path, account, &self.password)
+	}
+
+	fn remove(&self, account: &SafeAccount) -> Result<(), Error> {
+		let to_remove = self.files()?
+			.into_iter()
+			.find(|&(_, ref acc)| acc == account);
+
+		match to_remove {
+			None => Err(Error::InvalidAccount),
+			Some((path, _)) => fs::remove_file(path).map_err(From::from)
+		}
+	}
+
+	fn path(&self) -> Option<&PathBuf> { Some(&self.path) }
+}
+
+impl VaultKeyDirectory for VaultDiskDirectory {
+	fn as_key_directory(&self) -> &KeyDirectory {
+		self
+	}
+
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn set_password(&mut self, new_password: &str) -> Result<(), Error> {
+		// everything is re-encrypted aside first, so that a failure leaves
+		// the vault untouched and still opened by the old password
+		self.prepare_reencrypt(new_password)?;
+		// from now on the change is committed: if moving the files is interrupted,
+		// it's finished when the vault is opened next
+		complete_reencrypt(&self.path)?;
+
+		self.password = new_password.to_owned();
+		Ok(())
+	}
+}
+
+/// Finish a password change of the vault at `path`, if there's one pending.
+/// Files re-encrypted completely are moved over the old ones, the vault file last;
+/// incomplete ones are discarded, keeping the old password.
+fn complete_reencrypt(path: &Path) -> Result<(), Error> {
+	let temp_dir = path.join(REENCRYPT_DIR_NAME);
+	if !temp_dir.is_dir() {
+		return Ok(());
+	}
+
+	if !temp_dir.join(REENCRYPT_DONE_FILE_NAME).exists() {
+		return fs::remove_dir_all(&temp_dir).map_err(From::from);
+	}
+
+	for key_path in key_file_paths(&temp_dir)? {
+		let filename = key_path.file_name().expect("key_file_paths returns paths of files; qed");
+		fs::rename(&key_path, path.join(filename))?;
+	}
+	// already moved if the previous attempt was interrupted after it
+	if temp_dir.join(VAULT_FILE_NAME).exists() {
+		fs::rename(temp_dir.join(VAULT_FILE_NAME), path.join(VAULT_FILE_NAME))?;
+	}
+	fs::remove_dir_all(&temp_dir).map_err(From::from)
+}
+
+/// Path of the vault `name`; names may only contain alphanumerics, `-` and `_`.
+fn vault_path<P>(root: P, name: &str) -> Result<PathBuf, Error> where P: AsRef<Path> {
+	if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+		return Err(Error::InvalidVaultName);
+	}
+	Ok(root.as_ref().join(name))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{env, fs};
+	use dir::{KeyDirectory, VaultKeyDirectory};
+	use account::SafeAccount;
+	use ethkey::{Random, Generator};
+	use Error;
+	use super::VaultDiskDirectory;
+
+	#[test]
+	fn should_create_open_and_reencrypt_vault() {
+		// given
+		let mut dir = env::temp_dir();
+		dir.push("ethstore_should_create_open_and_reencrypt_vault");
+		let _ = fs::remove_dir_all(&dir);
+		let keypair = Random.generate().unwrap();
+		let account = SafeAccount::create(&keypair, [0u8; 16], "account", 1024, "Test".to_owned(), "{}".to_owned());
+
+		// when
+		let mut vault = VaultDiskDirectory::create(&dir, "vault", "password", 1024).unwrap();
+		let inserted = vault.insert(account).unwrap();
+		assert_eq!(inserted.vault, Some("vault".to_owned()));
+		assert!(VaultDiskDirectory::create(&dir, "vault", "password", 1024).is_err(), "Should not overwrite a vault.");
+		vault.set_password("new password").unwrap();
+
+		// then
+		assert!(!dir.join("vault").join(".reencrypt").exists(), "Re-encrypted files should be moved into the vault.");
+		assert_eq!(fs::read_dir(dir.join("vault")).unwrap().count(), 2);
+		match VaultDiskDirectory::at(&dir, "vault", "password", 1024) {
+			Err(Error::InvalidPassword) => (),
+			_ => panic!("Old password should not open the vault."),
+		}
+		let reopened = VaultDiskDirectory::at(&dir, "vault", "new password", 1024).unwrap();
+		let accounts = reopened.load().unwrap();
+		assert_eq!(accounts, vec![inserted.clone()]);
+		assert!(accounts[0].check_password("account"));
+
+		reopened.remove(&inserted).unwrap();
+		assert_eq!(reopened.load().unwrap().len(), 0);
+
+		// cleanup
+		let _ = fs::remove_dir_all(dir);
+	}
+
+	#[test]
+	fn should_complete_or_roll_back_interrupted_password_change() {
+		// given
+		let mut dir = env::temp_dir();
+		dir.push("ethstore_should_complete_or_roll_back_interrupted_password_change");
+		let _ = fs::remove_dir_all(&dir);
+		let keypair = Random.generate().unwrap();
+		let account = SafeAccount::create(&keypair, [0u8; 16], "account", 1024, "Test".to_owned(), "{}".to_owned());
+		let vault = VaultDiskDirectory::create(&dir, "vault", "password", 1024).unwrap();
+		let inserted = vault.insert(account).unwrap();
+
+		// when: interrupted before the re-encrypted files are complete
+		vault.prepare_reencrypt("new password").unwrap();
+		fs::remove_file(dir.join("vault").join(".reencrypt").join(".done")).unwrap();
+
+		// then
+		let reopened = VaultDiskDirectory::at(&dir, "vault", "password", 1024).unwrap();
+		assert!(!dir.join("vault").join(".reencrypt").exists());
+		assert_eq!(reopened.load().unwrap(), vec![inserted.clone()]);
+
+		// when: interrupted while moving the complete files
+		reopened.prepare_reencrypt("new password").unwrap();
+		let key_file = inserted.filename.clone().unwrap();
+		fs::rename(dir.join("vault").join(".reencrypt").join(&key_file), dir.join("vault").join(&key_file)).unwrap();
+
+		// then
+		match VaultDiskDirectory::at(&dir, "vault", "password", 1024) {
+			Err(Error::InvalidPassword) => (),
+			_ => panic!("Password change should be completed."),
+		}
+		assert!(!dir.join("vault").join(".reencrypt").exists());
+		let reopened = VaultDiskDirectory::at(&dir, "vault", "new password", 1024).unwrap();
+		assert_eq!(reopened.load().unwrap(), vec![inserted]);
+
+		// cleanup
+		let _ = fs::remove_dir_all(dir);
+	}
+
+	#[test]
+	fn should_reject_invalid_vault_names() {
+		let dir = env::temp_dir();
+		for name in &["", "..", "a/b", "vault name"] {
+			match VaultDiskDirectory::create(&dir, name, "password", 1024) {
+				Err(Error::InvalidVaultName) => (),
+				_ => panic!("Vault name {:?} should be rejected.", name),
+			}
+		}
+	}
+}
@@ -28,6 +28,11 @@ pub enum Error {
 	InvalidMessage,
 	InvalidKeyFile(String),
 	CreationFailed,
+	VaultsNotSupported,
+	InvalidVaultName,
+	VaultNotFound,
+	VaultExists,
+	WalletsNotSupported,
 	EthKey(EthKeyError),
 	EthCrypto(EthCryptoError),
 	Custom(String),
@@ -43,6 +48,11 @@ impl fmt::Display for Error {
 			Error::InvalidMessage => "Invalid message".into(),
 			Error::InvalidKeyFile(ref reason) => format!("Invalid key file: {}", reason),
 			Error::CreationFailed => "Account creation failed".into(),
+			Error::VaultsNotSupported => "Vaults are not supported".into(),
+			Error::InvalidVaultName => "Invalid vault name".into(),
+			Error::VaultNotFound => "Vault not found".into(),
+			Error::VaultExists => "Vault already exists".into(),
+			Error::WalletsNotSupported => "HD wallets are not supported".into(),
 			Error::EthKey(ref err) => err.to_string(),
 			Error::EthCrypto(ref err) => err.to_string(),
 			Error::Custom(ref s) => s.clone(),
@@ -14,15 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::mem;
 use parking_lot::RwLock;
 
-use crypto::KEY_ITERATIONS;
+use crypto::{self, KEY_ITERATIONS};
 use random::Random;
-use ethkey::{Signature, Address, Message, Secret, Public, KeyPair};
-use dir::KeyDirectory;
-use account::SafeAccount;
+use ethkey::{Signature, Address, Message, Secret, Public, KeyPair, DerivationPath, sign};
+use dir::{KeyDirectory, VaultKeyDirectory, VaultKeyDirectoryProvider, WalletDirectory};
+use account::{SafeAccount, HdWallet};
 use presale::PresaleWallet;
 use json::{self, Uuid};
 use {import, Error, SimpleSecretStore, SecretStore};
@@ -42,17 +42,85 @@ impl EthStore {
 		})
 	}
 
-	fn get(&self, address: &Address) -> Result<SafeAccount, Error> {
+	fn get(&self, address: &Address) -> Result<StoredAccount, Error> {
 		let mut accounts = self.store.get(address)?.into_iter();
 		accounts.next().ok_or(Error::InvalidAccount)
 	}
 }
 
+/// Account as kept by the store: a key file, or an account of a hierarchical deterministic wallet.
+#[derive(Debug, PartialEq, Clone)]
+enum StoredAccount {
+	Key(SafeAccount),
+	Derived(HdWallet, Address),
+}
+
+impl StoredAccount {
+	fn secret(&self, password: &str) -> Result<Secret, Error> {
+		match *self {
+			StoredAccount::Key(ref account) => account.secret(password),
+			StoredAccount::Derived(ref wallet, ref address) => wallet.secret(address, password),
+		}
+	}
+
+	fn check_password(&self, password: &str) -> bool {
+		match *self {
+			StoredAccount::Key(ref account) => account.check_password(password),
+			StoredAccount::Derived(ref wallet, _) => wallet.check_password(password),
+		}
+	}
+
+	fn sign(&self, password: &str, message: &Message) -> Result<Signature, Error> {
+		let secret = self.secret(password)?;
+		sign(&secret, message).map_err(From::from)
+	}
+
+	fn decrypt(&self, password: &str, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
+		let secret = self.secret(password)?;
+		crypto::ecies::decrypt(&secret, shared_mac, message).map_err(From::from)
+	}
+
+	fn public(&self, password: &str) -> Result<Public, Error> {
+		let secret = self.secret(password)?;
+		Ok(KeyPair::from_secret(secret)?.public().clone())
+	}
+
+	/// UUID of the key file, or of the whole wallet for derived accounts.
+	fn id(&self) -> [u8; 16] {
+		match *self {
+			StoredAccount::Key(ref account) => account.id,
+			StoredAccount::Derived(ref wallet, _) => wallet.id,
+		}
+	}
+
+	fn name(&self) -> String {
+		match *self {
+			StoredAccount::Key(ref account) => account.name.clone(),
+			StoredAccount::Derived(ref wallet, ref address) => wallet.account(address).map(|a| a.name.clone()).unwrap_or_default(),
+		}
+	}
+
+	fn meta(&self) -> String {
+		match *self {
+			StoredAccount::Key(ref account) => account.meta.clone(),
+			StoredAccount::Derived(ref wallet, ref address) => wallet.account(address).map(|a| a.meta.clone()).unwrap_or_default(),
+		}
+	}
+}
+
 impl SimpleSecretStore for EthStore {
 	fn insert_account(&self, secret: Secret, password: &str) -> Result<Address, Error> {
 		self.store.insert_account(secret, password)
 	}
 
+	fn insert_hd_wallet(&self, seed: &[u8], path: DerivationPath, password: &str) -> Result<Address, Error> {
+		self.store.insert_hd_wallet(seed, path, password)
+	}
+
+	fn derive_account(&self, account: &Address, password: &str) -> Result<Address, Error> {
+		self.store.derive_account(account, password)
+	}
+
 	fn accounts(&self) -> Result<Vec<Address>, Error> {
 		self.store.accounts()
 	}
@@ -87,7 +155,7 @@ impl SecretStore for EthStore {
 	fn import_wallet(&self, json: &[u8], password: &str) -> Result<Address, Error> {
 		let json_keyfile = json::KeyFile::load(json).map_err(|_| Error::InvalidKeyFile("Invalid JSON format".to_owned()))?;
 		let mut safe_account = SafeAccount::from_file(json_keyfile, None);
-		let secret = safe_account.crypto.secret(password).map_err(|_| Error::InvalidPassword)?;
+		let secret = safe_account.secret(password).map_err(|_| Error::InvalidPassword)?;
 		safe_account.address = KeyPair::from_secret(secret)?.address();
 		let address = safe_account.address.clone();
 		self.store.import(safe_account)?;
@@ -101,7 +169,7 @@ impl SecretStore for EthStore {
 
 	fn copy_account(&self, new_store: &SimpleSecretStore, address: &Address, password: &str, new_password: &str) -> Result<(), Error> {
 		let account = self.get(address)?;
-		let secret = account.crypto.secret(password)?;
+		let secret = account.secret(password)?;
 		new_store.insert_account(secret, new_password)?;
 		Ok(())
 	}
@@ -113,35 +181,47 @@ impl SecretStore for EthStore {
 
 	fn uuid(&self, address: &Address) -> Result<Uuid, Error> {
 		let account = self.get(address)?;
-		Ok(account.id.into())
+		Ok(account.id().into())
 	}
 
 	fn name(&self, address: &Address) -> Result<String, Error> {
 		let account = self.get(address)?;
-		Ok(account.name.clone())
+		Ok(account.name())
 	}
 
 	fn meta(&self, address: &Address) -> Result<String, Error> {
 		let account = self.get(address)?;
-		Ok(account.meta.clone())
+		Ok(account.meta())
 	}
 
 	fn set_name(&self, address: &Address, name: String) -> Result<(), Error> {
-		let old = self.get(address)?;
-		let mut account = old.clone();
-		account.name = name;
-
-		// save to file
-		self.store.update(old, account)
+		match self.get(address)? {
+			StoredAccount::Key(old) => {
+				let mut account = old.clone();
+				account.name = name;
+
+				// save to file
+				self.store.update(old, account)
+			},
+			StoredAccount::Derived(wallet, address) => {
+				self.store.save_wallet(wallet.with_account_info(&address, |account| account.name = name)?)
+			},
+		}
 	}
 
 	fn set_meta(&self, address: &Address, meta: String) -> Result<(), Error> {
-		let old = self.get(address)?;
-		let mut account = old.clone();
-		account.meta = meta;
-
-		// save to file
-		self.store.update(old, account)
+		match self.get(address)? {
+			StoredAccount::Key(old) => {
+				let mut account = old.clone();
+				account.meta = meta;
+
+				// save to file
+				self.store.update(old, account)
+			},
+			StoredAccount::Derived(wallet, address) => {
+				self.store.save_wallet(wallet.with_account_info(&address, |account| account.meta = meta)?)
+			},
+		}
 	}
 
 	fn local_path(&self) -> String {
@@ -155,6 +235,34 @@ impl SecretStore for EthStore {
 	fn import_geth_accounts(&self, desired: Vec<Address>, testnet: bool) -> Result<Vec<Address>, Error> {
 		import::import_geth_accounts(&*self.store.dir, desired.into_iter().collect(), testnet)
 	}
+
+	fn create_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		self.store.create_vault(name, password)
+	}
+
+	fn open_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		self.store.open_vault(name, password)
+	}
+
+	fn close_vault(&self, name: &str) -> Result<(), Error> {
+		self.store.close_vault(name)
+	}
+
+	fn change_vault_password(&self, name: &str, new_password: &str) -> Result<(), Error> {
+		self.store.change_vault_password(name, new_password)
+	}
+
+	fn list_vaults(&self) -> Result<Vec<String>, Error> {
+		self.store.list_vaults()
+	}
+
+	fn list_opened_vaults(&self) -> Result<Vec<String>, Error> {
+		self.store.list_opened_vaults()
+	}
+
+	fn change_account_vault(&self, address: &Address, vault: Option<String>) -> Result<(), Error> {
+		self.store.change_account_vault(address, vault)
+	}
 }
 
 /// Similar to `EthStore` but may store many accounts (with different passwords) for the same `Address`
@@ -162,6 +270,8 @@ pub struct EthMultiStore {
 	dir: Box<KeyDirectory>,
 	iterations: u32,
 	cache: RwLock<BTreeMap<Address, Vec<SafeAccount>>>,
+	wallets: RwLock<Vec<HdWallet>>,
+	vaults: RwLock<HashMap<String, Box<VaultKeyDirectory>>>,
 }
 
 impl EthMultiStore {
@@ -175,6 +285,8 @@ impl EthMultiStore {
 			dir: directory,
 			iterations: iterations,
 			cache: Default::default(),
+			wallets: Default::default(),
+			vaults: Default::default(),
 		};
 		store.reload_accounts()?;
 		Ok(store)
@@ -182,7 +294,10 @@ impl EthMultiStore {
 
 	fn reload_accounts(&self) -> Result<(), Error> {
 		let mut cache = self.cache.write();
-		let accounts = self.dir.load()?;
+		let mut accounts = self.dir.load()?;
+		for vault in self.vaults.read().values() {
+			accounts.extend(vault.load()?);
+		}
 
 		let mut new_accounts = BTreeMap::new();
 		for account in accounts {
@@ -190,22 +305,26 @@ impl EthMultiStore {
 			entry.push(account);
 		}
 		mem::replace(&mut *cache, new_accounts);
+		self.reload_wallets()
+	}
+
+	fn reload_wallets(&self) -> Result<(), Error> {
+		let wallets = match self.dir.as_wallet_directory() {
+			Some(dir) => dir.load_wallets()?,
+			None => Vec::new(),
+		};
+		mem::replace(&mut *self.wallets.write(), wallets);
 		Ok(())
 	}
 
-	fn get(&self, address: &Address) -> Result<Vec<SafeAccount>, Error> {
-		{
-			let cache = self.cache.read();
-			if let Some(accounts) = cache.get(address) {
-				if !accounts.is_empty() {
-					return Ok(accounts.clone())
-				}
-			}
+	fn get(&self, address: &Address) -> Result<Vec<StoredAccount>, Error> {
+		let accounts = self.cached(address);
+		if !accounts.is_empty() {
+			return Ok(accounts);
 		}
 
 		self.reload_accounts()?;
-		let cache = self.cache.read();
-		let accounts = cache.get(address).cloned().ok_or(Error::InvalidAccount)?;
+		let accounts = self.cached(address);
 		if accounts.is_empty() {
 			Err(Error::InvalidAccount)
 		} else {
@@ -213,9 +332,19 @@ impl EthMultiStore {
 		}
 	}
 
+	/// Cached key files and wallet accounts with `address`.
+	fn cached(&self, address: &Address) -> Vec<StoredAccount> {
+		let mut accounts: Vec<_> = self.cache.read().get(address)
+			.map_or_else(Vec::new, |accounts| accounts.iter().cloned().map(StoredAccount::Key).collect());
+		accounts.extend(self.wallets.read().iter()
+			.filter(|wallet| wallet.account(address).is_some())
+			.map(|wallet| StoredAccount::Derived(wallet.clone(), address.clone())));
+		accounts
+	}
+
 	fn import(&self, account: SafeAccount) -> Result<(), Error> {
 		// save to file
-		let account = self.dir.insert(account)?;
+		let account = self.with_directory(&account.vault, |dir| dir.insert(account))?;
 
 		// update cache
 		let mut cache = self.cache.write();
@@ -226,7 +355,7 @@ impl EthMultiStore {
 
 	fn update(&self, old: SafeAccount, new: SafeAccount) -> Result<(), Error> {
 		// save to file
-		let account = self.dir.update(new)?;
+		let account = self.with_directory(&new.vault, |dir| dir.update(new))?;
 
 		// update cache
 		let mut cache = self.cache.write();
@@ -239,6 +368,98 @@ impl EthMultiStore {
 
 	}
 
+	fn wallet_directory(&self) -> Result<&WalletDirectory, Error> {
+		self.dir.as_wallet_directory().ok_or(Error::WalletsNotSupported)
+	}
+
+	fn save_wallet(&self, wallet: HdWallet) -> Result<(), Error> {
+		self.wallet_directory()?.save_wallet(wallet)?;
+		self.reload_wallets()
+	}
+
+	fn remove_wallet(&self, wallet: &HdWallet) -> Result<(), Error> {
+		self.wallet_directory()?.remove_wallet(wallet)?;
+		self.reload_wallets()
+	}
+
+	/// Run `f` on the directory of `vault`, or on the main directory if it's `None`.
+	fn with_directory<F, T>(&self, vault: &Option<String>, f: F) -> Result<T, Error> where F: FnOnce(&KeyDirectory) -> Result<T, Error> {
+		match *vault {
+			Some(ref name) => {
+				let vaults = self.vaults.read();
+				let vault = vaults.get(name).ok_or(Error::VaultNotFound)?;
+				f(vault.as_key_directory())
+			},
+			None => f(&*self.dir),
+		}
+	}
+
+	fn vault_provider(&self) -> Result<&VaultKeyDirectoryProvider, Error> {
+		self.dir.as_vault_provider().ok_or(Error::VaultsNotSupported)
+	}
+
+	/// Create a new vault and open it.
+	pub fn create_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		let vault = self.vault_provider()?.create(name, password, self.iterations)?;
+		self.vaults.write().insert(name.to_owned(), vault);
+		Ok(())
+	}
+
+	/// Open an existing vault, making its accounts available.
+	pub fn open_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		let vault = self.vault_provider()?.open(name, password, self.iterations)?;
+		self.vaults.write().insert(name.to_owned(), vault);
+		self.reload_accounts()
+	}
+
+	/// Close an opened vault; its accounts are no longer available.
+	pub fn close_vault(&self, name: &str) -> Result<(), Error> {
+		if self.vaults.write().remove(name).is_none() {
+			return Err(Error::VaultNotFound);
+		}
+		self.reload_accounts()
+	}
+
+	/// Change the password of an opened vault.
+	pub fn change_vault_password(&self, name: &str, new_password: &str) -> Result<(), Error> {
+		let mut vaults = self.vaults.write();
+		let vault = vaults.get_mut(name).ok_or(Error::VaultNotFound)?;
+		vault.set_password(new_password)
+	}
+
+	/// Names of all vaults, opened or not.
+	pub fn list_vaults(&self) -> Result<Vec<String>, Error> {
+		self.vault_provider()?.list_vaults()
+	}
+
+	/// Names of the opened vaults.
+	pub fn list_opened_vaults(&self) -> Result<Vec<String>, Error> {
+		Ok(self.vaults.read().keys().cloned().collect())
+	}
+
+	/// Move all accounts with `address` to `vault`, or out of any vault if it's `None`.
+	/// Accounts of wallets are kept outside of vaults and can't be moved.
+	pub fn change_account_vault(&self, address: &Address, vault: Option<String>) -> Result<(), Error> {
+		let mut accounts = Vec::new();
+		for account in self.get(address)? {
+			match account {
+				StoredAccount::Key(account) => accounts.push(account),
+				StoredAccount::Derived(..) => return Err(Error::WalletsNotSupported),
+			}
+		}
+
+		for stored in accounts {
+			if stored.vault == vault {
+				continue;
+			}
+
+			let mut moved = stored.clone();
+			moved.vault = vault.clone();
+			self.with_directory(&vault, |dir| dir.insert(moved))?;
+			self.with_directory(&stored.vault, |dir| dir.remove(&stored))?;
+		}
+		self.reload_accounts()
+	}
 }
 
 impl SimpleSecretStore for EthMultiStore {
@@ -251,9 +472,35 @@ impl SimpleSecretStore for EthMultiStore {
 		Ok(address)
 	}
 
+	fn insert_hd_wallet(&self, seed: &[u8], path: DerivationPath, password: &str) -> Result<Address, Error> {
+		let id: [u8; 16] = Random::random();
+		let wallet = HdWallet::create(seed, path, id, password, self.iterations)?;
+		let address = wallet.accounts[0].address.clone();
+		self.save_wallet(wallet)?;
+		Ok(address)
+	}
+
+	fn derive_account(&self, account: &Address, password: &str) -> Result<Address, Error> {
+		let accounts = self.get(account)?;
+		for account in accounts {
+			if let StoredAccount::Derived(wallet, _) = account {
+				if wallet.check_password(password) {
+					let (wallet, address) = wallet.derive_next(password)?;
+					self.save_wallet(wallet)?;
+					return Ok(address);
+				}
+			}
+		}
+		Err(Error::InvalidPassword)
+	}
+
 	fn accounts(&self) -> Result<Vec<Address>, Error> {
 		self.reload_accounts()?;
-		Ok(self.cache.read().keys().cloned().collect())
+		let mut accounts: BTreeSet<Address> = self.cache.read().keys().cloned().collect();
+		for wallet in self.wallets.read().iter() {
+			accounts.extend(wallet.accounts.iter().map(|account| account.address.clone()));
+		}
+		Ok(accounts.into_iter().collect())
 	}
 
 	fn remove_account(&self, address: &Address, password: &str) -> Result<(), Error> {
@@ -265,8 +512,19 @@ impl SimpleSecretStore for EthMultiStore {
 				continue;
 			}
 
+			let account = match account {
+				StoredAccount::Key(account) => account,
+				StoredAccount::Derived(wallet, address) => {
+					// the seed is only removed together with the last account of the wallet
+					return match wallet.remove_account(&address)? {
+						Some(remaining) => self.save_wallet(remaining),
+						None => self.remove_wallet(&wallet),
+					};
+				},
+			};
+
 			// Remove from dir
-			self.dir.remove(&account)?;
+			self.with_directory(&account.vault, |dir| dir.remove(&account))?;
 
 			// Remove from cache
 			let mut cache = self.cache.write();
@@ -291,8 +549,15 @@ impl SimpleSecretStore for EthMultiStore {
 		let accounts = self.get(address)?;
 		for account in accounts {
 			// Change password
-			let new_account = account.change_password(old_password, new_password, self.iterations)?;
-			self.update(account, new_account)?;
+			match account {
+				StoredAccount::Key(account) => {
+					let new_account = account.change_password(old_password, new_password, self.iterations)?;
+					self.update(account, new_account)?;
+				},
+				StoredAccount::Derived(wallet, _) => {
+					self.save_wallet(wallet.change_password(old_password, new_password, self.iterations)?)?;
+				},
+			}
 		}
 		Ok(())
 	}
@@ -323,7 +588,7 @@ impl SimpleSecretStore for EthMultiStore {
 mod tests {
 
 	use dir::MemoryDirectory;
-	use ethkey::{Random, Generator, KeyPair};
+	use ethkey::{Random, Generator, KeyPair, Mnemonic, ExtendedSecret, DerivationPath};
 	use secret_store::{SimpleSecretStore, SecretStore};
 	use super::{EthStore, EthMultiStore};
 
@@ -437,4 +702,45 @@ mod tests {
 		assert_eq!(multi_store.accounts().unwrap().len(), 1);
 	}
 
+	#[test]
+	fn should_derive_hd_accounts() {
+		// given
+		let store = store();
+		let seed = Mnemonic::from_entropy(&[0u8; 16]).unwrap().seed("");
+		let master = ExtendedSecret::from_seed(&seed).unwrap();
+		let first = store.insert_hd_wallet(&seed, DerivationPath::ethereum(), "test").unwrap();
+		assert_eq!(first, master.derive_path(&DerivationPath::ethereum().child(0)).unwrap().key_pair().unwrap().address());
+
+		// when
+		assert!(store.derive_account(&first, "x").is_err(), "Should not derive with invalid password.");
+		let second = store.derive_account(&first, "test").unwrap();
+		store.change_password(&second, "test", "xyz").unwrap();
+
+		// then
+		let expected = master.derive_path(&DerivationPath::ethereum().child(1)).unwrap().key_pair().unwrap();
+		assert_eq!(second, expected.address());
+		assert_eq!(store.accounts().unwrap(), { let mut a = vec![first, second]; a.sort(); a });
+		assert_eq!(store.public(&second, "xyz").unwrap(), *expected.public());
+		assert!(store.sign(&first, "xyz", &Default::default()).is_ok(), "Password of the whole wallet should change.");
+		store.set_name(&second, "Second".to_owned()).unwrap();
+		assert_eq!(store.name(&second).unwrap(), "Second");
+		assert_eq!(store.name(&first).unwrap(), "", "Should keep names of the other accounts.");
+
+		store.remove_account(&first, "xyz").unwrap();
+		assert_eq!(store.accounts().unwrap(), vec![second], "Should keep the other accounts of the wallet.");
+		assert_eq!(store.public(&second, "xyz").unwrap(), *expected.public());
+		let third = store.derive_account(&second, "xyz").unwrap();
+		assert_eq!(third, master.derive_path(&DerivationPath::ethereum().child(2)).unwrap().key_pair().unwrap().address());
+
+		store.remove_account(&second, "xyz").unwrap();
+		store.remove_account(&third, "xyz").unwrap();
+		assert_eq!(store.accounts().unwrap().len(), 0, "Should remove the wallet with its last account.");
+	}
+
+	#[test]
+	fn should_not_derive_plain_accounts() {
+		let store = store();
+		let address = store.insert_account(keypair().secret().clone(), "test").unwrap();
+		assert!(store.derive_account(&address, "test").is_err());
+	}
 }
@@ -0,0 +1,187 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Exporting keys and re-encrypting key directories.
+//!
+//! Only the accounts kept directly in a directory are handled; accounts in vaults
+//! are encrypted with the vault password as well and are left out.
+
+use ethkey::{Address, Secret};
+use account::{SafeAccount, HdWallet, KdfParams};
+use dir::KeyDirectory;
+use json;
+use Error;
+
+/// Outcome of re-encrypting a key directory.
+#[derive(Debug, PartialEq)]
+pub struct KdfUpgrade {
+	/// Accounts re-encrypted with the new key derivation.
+	pub upgraded: Vec<Address>,
+	/// Accounts left untouched because none of the passwords matched.
+	pub skipped: Vec<Address>,
+}
+
+/// Key file of `address` in `dir`.
+fn find_account(dir: &KeyDirectory, address: &Address) -> Result<SafeAccount, Error> {
+	dir.load()?
+		.into_iter()
+		.find(|account| &account.address == address)
+		.ok_or(Error::InvalidAccount)
+}
+
+/// Wallet in `dir` which `address` was derived from.
+fn find_wallet(dir: &KeyDirectory, address: &Address) -> Result<Option<HdWallet>, Error> {
+	let wallets = match dir.as_wallet_directory() {
+		Some(wallets) => wallets.load_wallets()?,
+		None => return Ok(None),
+	};
+	Ok(wallets.into_iter().find(|wallet| wallet.account(address).is_some()))
+}
+
+/// Key file of `address` as JSON, encrypted just like in `dir`.
+/// Accounts of HD wallets have no key file of their own; exporting them is refused.
+pub fn export_key_file(dir: &KeyDirectory, address: &Address) -> Result<String, Error> {
+	if find_wallet(dir, address)?.is_some() {
+		return Err(Error::Custom("Account belongs to an HD wallet; only its secret may be exported".into()));
+	}
+
+	let key_file: json::KeyFile = find_account(dir, address)?.into();
+	let mut out = Vec::new();
+	key_file.write(&mut out).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+	String::from_utf8(out).map_err(|e| Error::Custom(format!("{:?}", e)))
+}
+
+/// Unencrypted secret of `address`.
+/// For accounts of HD wallets that's the secret of the account only, never the wallet's seed.
+pub fn export_secret(dir: &KeyDirectory, address: &Address, password: &str) -> Result<Secret, Error> {
+	match find_wallet(dir, address)? {
+		Some(wallet) => wallet.secret(address, password),
+		None => find_account(dir, address)?.secret(password),
+	}
+}
+
+/// Re-encrypt all accounts and HD wallets in `dir`, deriving their keys as described by `params`.
+/// Each one is unlocked with the first of `passwords` that matches; UUIDs and file names are preserved.
+/// Files are replaced atomically, so an interrupted upgrade leaves every file either old or upgraded.
+pub fn upgrade_kdf(dir: &KeyDirectory, passwords: &[String], params: KdfParams) -> Result<KdfUpgrade, Error> {
+	let mut result = KdfUpgrade {
+		upgraded: Vec::new(),
+		skipped: Vec::new(),
+	};
+
+	for account in dir.load()? {
+		match passwords.iter().find(|password| account.check_password(password)) {
+			Some(password) => {
+				let upgraded = account.with_kdf(password, params)?;
+				result.upgraded.push(dir.update(upgraded)?.address);
+			},
+			None => result.skipped.push(account.address),
+		}
+	}
+
+	if let Some(wallets) = dir.as_wallet_directory() {
+		for wallet in wallets.load_wallets()? {
+			let addresses = wallet.accounts.iter().map(|account| account.address.clone());
+			match passwords.iter().find(|password| wallet.check_password(password)) {
+				Some(password) => {
+					wallets.save_wallet(wallet.with_kdf(password, params)?)?;
+					result.upgraded.extend(addresses);
+				},
+				None => result.skipped.extend(addresses),
+			}
+		}
+	}
+
+	Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+	use dir::{KeyDirectory, MemoryDirectory};
+	use ethkey::{Random, Generator, KeyPair, Mnemonic, DerivationPath};
+	use account::{SafeAccount, HdWallet, Kdf, KdfParams};
+	use super::{export_key_file, export_secret, upgrade_kdf, KdfUpgrade};
+
+	#[test]
+	fn should_export_key_file_and_secret() {
+		// given
+		let dir = MemoryDirectory::default();
+		let keypair = Random.generate().unwrap();
+		let account = SafeAccount::create(&keypair, [1u8; 16], "password", 1024, "Test".to_owned(), "{}".to_owned());
+		dir.insert(account).unwrap();
+
+		// then
+		assert!(export_key_file(&dir, &keypair.address()).unwrap().contains(&format!("{:?}", keypair.address())));
+		assert_eq!(&export_secret(&dir, &keypair.address(), "password").unwrap(), keypair.secret());
+		assert!(export_secret(&dir, &keypair.address(), "wrong").is_err());
+		assert!(export_key_file(&dir, &1.into()).is_err());
+	}
+
+	#[test]
+	fn should_upgrade_kdf_preserving_uuids() {
+		// given
+		let dir = MemoryDirectory::default();
+		let first = Random.generate().unwrap();
+		let second = Random.generate().unwrap();
+		dir.insert(SafeAccount::create(&first, [1u8; 16], "first", 1024, "".to_owned(), "{}".to_owned())).unwrap();
+		dir.insert(SafeAccount::create(&second, [2u8; 16], "second", 1024, "".to_owned(), "{}".to_owned())).unwrap();
+
+		// when
+		let result = upgrade_kdf(&dir, &["first".to_owned()], KdfParams::Pbkdf2 { iterations: 2048 }).unwrap();
+
+		// then
+		assert_eq!(result, KdfUpgrade {
+			upgraded: vec![first.address()],
+			skipped: vec![second.address()],
+		});
+		let accounts = dir.load().unwrap();
+		let upgraded = accounts.iter().find(|a| a.address == first.address()).unwrap();
+		assert_eq!(upgraded.id, [1u8; 16]);
+		assert!(upgraded.check_password("first"));
+		match upgraded.crypto.kdf {
+			Kdf::Pbkdf2(ref params) => assert_eq!(params.c, 2048),
+			_ => panic!("Key should be derived with PBKDF2."),
+		}
+	}
+
+	#[test]
+	fn should_export_only_derived_secrets_of_wallets() {
+		// given
+		let dir = MemoryDirectory::default();
+		let seed = Mnemonic::from_entropy(&[0u8; 16]).unwrap().seed("");
+		let wallet = HdWallet::create(&seed, DerivationPath::ethereum(), [1u8; 16], "password", 1024).unwrap();
+		let address = wallet.accounts[0].address;
+		dir.as_wallet_directory().unwrap().save_wallet(wallet).unwrap();
+
+		// then
+		assert!(export_key_file(&dir, &address).is_err(), "Should not export the wallet's seed.");
+		let secret = export_secret(&dir, &address, "password").unwrap();
+		assert_eq!(KeyPair::from_secret(secret).unwrap().address(), address);
+
+		// when
+		let result = upgrade_kdf(&dir, &["password".to_owned()], KdfParams::Pbkdf2 { iterations: 2048 }).unwrap();
+
+		// then
+		assert_eq!(result.upgraded, vec![address]);
+		let wallets = dir.as_wallet_directory().unwrap().load_wallets().unwrap();
+		assert_eq!(wallets.len(), 1);
+		match wallets[0].crypto.kdf {
+			Kdf::Pbkdf2(ref params) => assert_eq!(params.c, 2048),
+			_ => panic!("Seed should be encrypted with PBKDF2."),
+		}
+	}
+}
@@ -0,0 +1,112 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Hierarchical deterministic wallet files.
+
+use std::io::{Read, Write};
+use serde_json;
+use super::{Uuid, Crypto, H160};
+
+/// Hierarchical deterministic wallet: an encrypted seed and the accounts derived from it.
+/// Kept apart from key files, which hold a single secret each.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct WalletFile {
+	pub id: Uuid,
+	/// Seed encrypted with the wallet password.
+	pub crypto: Crypto,
+	/// Path of the parent key of all accounts, e.g. `m/44'/60'/0'/0`.
+	pub path: String,
+	/// Accounts derived so far.
+	pub accounts: Vec<WalletAccount>,
+}
+
+/// Account of a wallet, with metadata of its own.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct WalletAccount {
+	/// Index of the account's key among the children of the parent key.
+	pub index: u32,
+	pub address: H160,
+	pub name: String,
+	pub meta: String,
+}
+
+impl WalletFile {
+	pub fn load<R>(reader: R) -> Result<Self, serde_json::Error> where R: Read {
+		serde_json::from_reader(reader)
+	}
+
+	pub fn write<W>(&self, writer: &mut W) -> Result<(), serde_json::Error> where W: Write {
+		serde_json::to_writer(writer, self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use json::{WalletFile, WalletAccount, Crypto, Cipher, Aes128Ctr, Kdf, Pbkdf2, Prf};
+
+	#[test]
+	fn wallet_file_deserialization() {
+		let s = r#"{
+			"id": "8777d9f6-7860-4b9b-88b7-0b57ee6b3a73",
+			"crypto": {
+				"cipher": "aes-128-ctr",
+				"ciphertext": "7203da0676d141b138cd7f8e1a4365f59cc1aa6978dc5443f364ca943d7cb4bc",
+				"cipherparams": {
+					"iv": "b5a7ec855ec9e2c405371356855fec83"
+				},
+				"kdf": "pbkdf2",
+				"kdfparams": {
+					"c": 10240,
+					"dklen": 32,
+					"prf": "hmac-sha256",
+					"salt": "1e8642fdf1f87172492c1412fc62f8db75d796cdfa9c53c3f2b11e44a2a1b209"
+				},
+				"mac": "46325c5d4e8c991ad2683d525c7854da387138b6ca45068985aa4959fa2b8c8f"
+			},
+			"path": "m/44'/60'/0'/0",
+			"accounts": [
+				{ "index": 0, "address": "9858effd232b4033e47d90003d41ec34ecaeda94", "name": "Main", "meta": "{}" }
+			]
+		}"#;
+
+		let wallet: WalletFile = serde_json::from_str(s).unwrap();
+		assert_eq!(wallet, WalletFile {
+			id: "8777d9f6-7860-4b9b-88b7-0b57ee6b3a73".into(),
+			crypto: Crypto {
+				cipher: Cipher::Aes128Ctr(Aes128Ctr {
+					iv: "b5a7ec855ec9e2c405371356855fec83".into(),
+				}),
+				ciphertext: "7203da0676d141b138cd7f8e1a4365f59cc1aa6978dc5443f364ca943d7cb4bc".into(),
+				kdf: Kdf::Pbkdf2(Pbkdf2 {
+					c: 10240,
+					dklen: 32,
+					prf: Prf::HmacSha256,
+					salt: "1e8642fdf1f87172492c1412fc62f8db75d796cdfa9c53c3f2b11e44a2a1b209".into(),
+				}),
+				mac: "46325c5d4e8c991ad2683d525c7854da387138b6ca45068985aa4959fa2b8c8f".into(),
+			},
+			path: "m/44'/60'/0'/0".into(),
+			accounts: vec![WalletAccount {
+				index: 0,
+				address: "9858effd232b4033e47d90003d41ec34ecaeda94".into(),
+				name: "Main".into(),
+				meta: "{}".into(),
+			}],
+		});
+	}
+}
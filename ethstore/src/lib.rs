@@ -46,14 +46,16 @@ mod json;
 
 mod error;
 mod ethstore;
+mod export;
 mod import;
 mod presale;
 mod random;
 mod secret_store;
 
-pub use self::account::SafeAccount;
+pub use self::account::{SafeAccount, KdfParams};
 pub use self::error::Error;
 pub use self::ethstore::{EthStore, EthMultiStore};
+pub use self::export::{export_key_file, export_secret, upgrade_kdf, KdfUpgrade};
 pub use self::import::{import_accounts, read_geth_accounts};
 pub use self::presale::PresaleWallet;
 pub use self::secret_store::{SimpleSecretStore, SecretStore};
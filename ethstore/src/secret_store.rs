@@ -14,12 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethkey::{Address, Message, Signature, Secret, Public};
+use ethkey::{Address, Message, Signature, Secret, Public, DerivationPath};
 use Error;
 use json::Uuid;
 
 pub trait SimpleSecretStore: Send + Sync {
 	fn insert_account(&self, secret: Secret, password: &str) -> Result<Address, Error>;
+	/// Insert a hierarchical deterministic wallet from `seed`, returning its first account.
+	/// Accounts are derived as children of the key at `path`.
+	fn insert_hd_wallet(&self, seed: &[u8], path: DerivationPath, password: &str) -> Result<Address, Error>;
+	/// Derive the next account of the wallet `account` belongs to.
+	fn derive_account(&self, account: &Address, password: &str) -> Result<Address, Error>;
 	fn change_password(&self, account: &Address, old_password: &str, new_password: &str) -> Result<(), Error>;
 	fn remove_account(&self, account: &Address, password: &str) -> Result<(), Error>;
 
@@ -47,5 +52,20 @@ pub trait SecretStore: SimpleSecretStore {
 	fn local_path(&self) -> String;
 	fn list_geth_accounts(&self, testnet: bool) -> Vec<Address>;
 	fn import_geth_accounts(&self, desired: Vec<Address>, testnet: bool) -> Result<Vec<Address>, Error>;
+
+	/// Create a new vault protected by `password` and open it.
+	fn create_vault(&self, name: &str, password: &str) -> Result<(), Error>;
+	/// Open an existing vault, making its accounts available.
+	fn open_vault(&self, name: &str, password: &str) -> Result<(), Error>;
+	/// Close an opened vault.
+	fn close_vault(&self, name: &str) -> Result<(), Error>;
+	/// Change the password of an opened vault.
+	fn change_vault_password(&self, name: &str, new_password: &str) -> Result<(), Error>;
+	/// Names of all vaults, opened or not.
+	fn list_vaults(&self) -> Result<Vec<String>, Error>;
+	/// Names of the opened vaults.
+	fn list_opened_vaults(&self) -> Result<Vec<String>, Error>;
+	/// Move an account into the opened `vault`, or out of its vault if `vault` is `None`.
+	fn change_account_vault(&self, address: &Address, vault: Option<String>) -> Result<(), Error>;
 }
 
@@ -19,7 +19,7 @@ extern crate ethstore;
 
 mod util;
 
-use ethstore::{EthStore, SimpleSecretStore};
+use ethstore::{EthStore, SimpleSecretStore, SecretStore};
 use ethstore::ethkey::{Random, Generator, Secret, KeyPair, verify_address};
 use ethstore::dir::DiskDirectory;
 use util::TransientDir;
@@ -88,6 +88,36 @@ fn secret_store_remove_account() {
 	assert!(store.remove_account(&accounts[0], "").is_err());
 }
 
+#[test]
+fn secret_store_move_account_to_vault() {
+	let dir = TransientDir::create().unwrap();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	let address = store.insert_account(random_secret(), "").unwrap();
+	assert!(store.create_vault("vault", "vault password").is_ok());
+	assert_eq!(store.list_vaults().unwrap(), vec!["vault".to_owned()]);
+	assert_eq!(store.list_opened_vaults().unwrap(), vec!["vault".to_owned()]);
+
+	// moved account is only available while the vault is open
+	assert!(store.change_account_vault(&address, Some("vault".into())).is_ok());
+	assert!(store.sign(&address, "", &Default::default()).is_ok());
+	assert!(store.close_vault("vault").is_ok());
+	assert_eq!(store.accounts().unwrap().len(), 0);
+
+	assert!(store.open_vault("vault", "wrong password").is_err());
+	assert!(store.open_vault("vault", "vault password").is_ok());
+	assert!(store.change_vault_password("vault", "new password").is_ok());
+	assert!(store.close_vault("vault").is_ok());
+	assert!(store.open_vault("vault", "vault password").is_err());
+	assert!(store.open_vault("vault", "new password").is_ok());
+	assert_eq!(store.accounts().unwrap(), vec![address]);
+
+	// and moved back out
+	assert!(store.change_account_vault(&address, None).is_ok());
+	assert!(store.close_vault("vault").is_ok());
+	assert_eq!(store.accounts().unwrap(), vec![address]);
+	assert!(store.sign(&address, "", &Default::default()).is_ok());
+}
+
 fn test_path() -> &'static str {
 	match ::std::fs::metadata("ethstore") {
 		Ok(_) => "ethstore/tests/res/geth_keystore",
@@ -17,7 +17,7 @@
 use std::path::PathBuf;
 use std::{env, fs};
 use rand::{Rng, OsRng};
-use ethstore::dir::{KeyDirectory, DiskDirectory};
+use ethstore::dir::{KeyDirectory, DiskDirectory, VaultKeyDirectoryProvider};
 use ethstore::{Error, SafeAccount};
 
 pub fn random_dir() -> PathBuf {
@@ -74,4 +74,12 @@ impl KeyDirectory for TransientDir {
 	fn remove(&self, account: &SafeAccount) -> Result<(), Error> {
 		self.dir.remove(account)
 	}
+
+	fn path(&self) -> Option<&PathBuf> {
+		self.dir.path()
+	}
+
+	fn as_vault_provider(&self) -> Option<&VaultKeyDirectoryProvider> {
+		self.dir.as_vault_provider()
+	}
 }
@@ -20,7 +20,7 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use util::{U256, H256, Address, Bytes, FixedHash};
 use ethcore::client::EnvInfo;
-use ethcore::evm::{self, Ext, ContractCreateResult, MessageCallResult, Schedule, CallType};
+use ethcore::evm::{self, Ext, ContractCreateResult, MessageCallResult, Schedule, CallType, ReturnData};
 
 pub struct FakeExt {
 	schedule: Schedule,
@@ -43,8 +43,9 @@ impl Ext for FakeExt {
 		self.store.get(key).unwrap_or(&H256::new()).clone()
 	}
 
-	fn set_storage(&mut self, key: H256, value: H256) {
+	fn set_storage(&mut self, key: H256, value: H256) -> evm::Result<()> {
 		self.store.insert(key, value);
+		Ok(())
 	}
 
 	fn exists(&self, _address: &Address) -> bool {
@@ -91,15 +92,15 @@ impl Ext for FakeExt {
 		unimplemented!();
 	}
 
-	fn log(&mut self, _topics: Vec<H256>, _data: &[u8]) {
+	fn log(&mut self, _topics: Vec<H256>, _data: &[u8]) -> evm::Result<()> {
 		unimplemented!();
 	}
 
-	fn ret(self, gas: &U256, _data: &[u8]) -> evm::Result<U256> {
+	fn ret(self, gas: &U256, _data: &ReturnData, _apply_state: bool) -> evm::Result<U256> {
 		Ok(*gas)
 	}
 
-	fn suicide(&mut self, _refund_address: &Address) {
+	fn suicide(&mut self, _refund_address: &Address) -> evm::Result<()> {
 		unimplemented!();
 	}
 
@@ -119,4 +120,8 @@ impl Ext for FakeExt {
 		unimplemented!();
 		// self.sstore_clears += 1;
 	}
+
+	fn is_static(&self) -> bool {
+		false
+	}
 }
@@ -76,11 +76,11 @@ pub fn run_vm(params: ActionParams) -> Result<Success, Failure> {
 	let mut ext = ext::FakeExt::default();
 
 	let start = Instant::now();
-	let gas_left = vm.exec(params, &mut ext).finalize(ext);
+	let result = vm.exec(params, &mut ext).finalize(ext);
 	let duration = start.elapsed();
 
-	match gas_left {
-		Ok(gas_left) => Ok(Success {
+	match result {
+		Ok(evm::FinalizationResult { gas_left, .. }) => Ok(Success {
 			gas_used: initial_gas - gas_left,
 			// TODO [ToDr] get output from ext
 			output: Vec::new(),
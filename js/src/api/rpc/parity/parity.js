@@ -55,16 +55,36 @@ export default class Parity {
       .execute('parity_changePassword', inAddress(account), password, newPassword);
   }
 
+  changeVault (account, vaultName) {
+    return this._transport
+      .execute('parity_changeVault', inAddress(account), vaultName);
+  }
+
+  changeVaultPassword (vaultName, password) {
+    return this._transport
+      .execute('parity_changeVaultPassword', vaultName, password);
+  }
+
   checkRequest (requestId) {
     return this._transport
       .execute('parity_checkRequest', inNumber16(requestId));
   }
 
+  closeVault (vaultName) {
+    return this._transport
+      .execute('parity_closeVault', vaultName);
+  }
+
   consensusCapability () {
     return this._transport
       .execute('parity_consensusCapability');
   }
 
+  consensusEvidence () {
+    return this._transport
+      .execute('parity_consensusEvidence');
+  }
+
   dappsPort () {
     return this._transport
       .execute('parity_dappsPort')
@@ -81,6 +101,12 @@ export default class Parity {
       .execute('parity_defaultExtraData');
   }
 
+  deriveAddress (account, password) {
+    return this._transport
+      .execute('parity_deriveAddress', inAddress(account), password)
+      .then(outAddress);
+  }
+
   devLogs () {
     return this._transport
       .execute('parity_devLogs');
@@ -172,6 +198,16 @@ export default class Parity {
       .then(outAddresses);
   }
 
+  listOpenedVaults () {
+    return this._transport
+      .execute('parity_listOpenedVaults');
+  }
+
+  listVaults () {
+    return this._transport
+      .execute('parity_listVaults');
+  }
+
   localTransactions () {
     return this._transport
       .execute('parity_localTransactions')
@@ -219,6 +255,12 @@ export default class Parity {
       .then(outNumber);
   }
 
+  newAccountFromMnemonic (phrase, password) {
+    return this._transport
+      .execute('parity_newAccountFromMnemonic', phrase, password)
+      .then(outAddress);
+  }
+
   newAccountFromPhrase (phrase, password) {
     return this._transport
       .execute('parity_newAccountFromPhrase', phrase, password)
@@ -237,6 +279,11 @@ export default class Parity {
       .then(outAddress);
   }
 
+  newVault (vaultName, password) {
+    return this._transport
+      .execute('parity_newVault', vaultName, password);
+  }
+
   nextNonce (account) {
     return this._transport
       .execute('parity_nextNonce', inAddress(account))
@@ -248,6 +295,11 @@ export default class Parity {
       .execute('parity_nodeName');
   }
 
+  openVault (vaultName, password) {
+    return this._transport
+      .execute('parity_openVault', vaultName, password);
+  }
+
   pendingTransactions () {
     return this._transport
       .execute('parity_pendingTransactions')
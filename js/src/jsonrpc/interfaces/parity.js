@@ -78,6 +78,25 @@ export default {
     }
   },
 
+  bannedPeers: {
+    desc: 'Returns peers banned for misbehaviour.',
+    params: [],
+    returns: {
+      type: Array,
+      desc: 'Banned peers',
+      details: {
+        id: {
+          type: String,
+          desc: 'Node id'
+        },
+        until: {
+          type: Quantity,
+          desc: 'Unix timestamp the ban expires at'
+        }
+      }
+    }
+  },
+
   chainStatus: {
     desc: 'Returns the information on warp sync blocks',
     params: [],
@@ -94,6 +113,42 @@ export default {
     }
   },
 
+  changeVault: {
+    desc: 'Moves an account to the opened vault, or out of its vault if the vault name is empty',
+    params: [
+      {
+        type: Address,
+        desc: 'Address of the account'
+      },
+      {
+        type: String,
+        desc: 'Vault name'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'true on success'
+    }
+  },
+
+  changeVaultPassword: {
+    desc: 'Changes the password of an opened vault',
+    params: [
+      {
+        type: String,
+        desc: 'Vault name'
+      },
+      {
+        type: String,
+        desc: 'New vault password'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'true on success'
+    }
+  },
+
   checkRequest: {
     desc: 'Returns the transactionhash of the requestId (received from parity_postTransaction) if the request was confirmed',
     params: [
@@ -108,6 +163,29 @@ export default {
     }
   },
 
+  clearBannedPeers: {
+    desc: 'Lifts all peer bans.',
+    params: [],
+    returns: {
+      type: Boolean,
+      desc: '`true` on success'
+    }
+  },
+
+  closeVault: {
+    desc: 'Closes an opened vault, its accounts are no longer available',
+    params: [
+      {
+        type: String,
+        desc: 'Vault name'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'true on success'
+    }
+  },
+
   consensusCapability: {
     desc: 'Returns an object or string detailing the state of parity capability of maintaining consensus',
     params: [],
@@ -117,6 +195,15 @@ export default {
     }
   },
 
+  consensusEvidence: {
+    desc: 'Returns proofs of validators signing conflicting consensus messages, observed by the engine',
+    params: [],
+    returns: {
+      type: Array,
+      desc: 'Array of objects with the offending validator `author` and the two signed messages `first` and `second`'
+    }
+  },
+
   dappsPort: {
     desc: 'Returns the port the dapps are running on, error if not enabled',
     params: [],
@@ -144,6 +231,24 @@ export default {
     }
   },
 
+  deriveAddress: {
+    desc: 'Derives and stores the next account of the HD wallet the given account belongs to',
+    params: [
+      {
+        type: Address,
+        desc: 'Account of the wallet'
+      },
+      {
+        type: String,
+        desc: 'Password'
+      }
+    ],
+    returns: {
+      type: Address,
+      desc: 'The derived address'
+    }
+  },
+
   devLogs: {
     desc: 'Returns latest logs of your node',
     params: [],
@@ -337,6 +442,24 @@ export default {
     }
   },
 
+  listOpenedVaults: {
+    desc: 'Returns a list of the opened vaults',
+    params: [],
+    returns: {
+      type: Array,
+      desc: 'Array of vault names'
+    }
+  },
+
+  listVaults: {
+    desc: 'Returns a list of all vaults',
+    params: [],
+    returns: {
+      type: Array,
+      desc: 'Array of vault names'
+    }
+  },
+
   localTransactions: {
     desc: 'Returns an object of current and past local transactions.',
     params: [],
@@ -401,6 +524,24 @@ export default {
     }
   },
 
+  newAccountFromMnemonic: {
+    desc: 'Creates a new HD wallet from a BIP39 mnemonic phrase',
+    params: [
+      {
+        type: String,
+        desc: 'Mnemonic phrase'
+      },
+      {
+        type: String,
+        desc: 'Password'
+      }
+    ],
+    returns: {
+      type: Address,
+      desc: 'The first address of the wallet'
+    }
+  },
+
   newAccountFromPhrase: {
     desc: 'Creates a new account from a recovery passphrase',
     params: [
@@ -455,6 +596,24 @@ export default {
     }
   },
 
+  newVault: {
+    desc: 'Creates a new vault protected by a password and opens it',
+    params: [
+      {
+        type: String,
+        desc: 'Vault name'
+      },
+      {
+        type: String,
+        desc: 'Vault password'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'true on success'
+    }
+  },
+
   nextNonce: {
     desc: 'Returns next available nonce for transaction from given account. Includes pending block and transaction queue.',
     params: [
@@ -478,6 +637,24 @@ export default {
     }
   },
 
+  openVault: {
+    desc: 'Opens an existing vault, making its accounts available',
+    params: [
+      {
+        type: String,
+        desc: 'Vault name'
+      },
+      {
+        type: String,
+        desc: 'Vault password'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'true on success'
+    }
+  },
+
   pendingTransactions: {
     desc: 'Returns a list of transactions currently in the queue.',
     params: [],
@@ -666,6 +843,24 @@ export default {
     }
   },
 
+  setIpFilter: {
+    desc: 'Replaces the IP filter applied to peer connections and discovery.',
+    params: [
+      {
+        type: String,
+        desc: 'Allowed networks in CIDR notation and/or one of `all`, `public`, `private`, `none`'
+      },
+      {
+        type: String,
+        desc: 'Denied networks in CIDR notation'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: '`true` on success'
+    }
+  },
+
   setMinGasPrice: {
     desc: 'Changes minimal gas price for transaction to be accepted to the queue.',
     params: [
@@ -743,6 +938,20 @@ export default {
     }
   },
 
+  unbanPeer: {
+    desc: 'Lifts the ban on a peer banned for misbehaviour.',
+    params: [
+      {
+        type: String,
+        desc: 'Node id or enode'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: '`true` if the peer was banned'
+    }
+  },
+
   unsignedTransactionsCount: {
     desc: 'Returns number of unsigned transactions when running with Trusted Signer. Error otherwise',
     params: [],
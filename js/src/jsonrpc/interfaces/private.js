@@ -0,0 +1,101 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+import { Address, Data, Hash } from '../types';
+
+export default {
+  call: {
+    desc: 'Executes a call against the latest anchored state of a private contract without sending anything to the network.',
+    params: [
+      {
+        type: Object,
+        desc: 'The call object, see eth_call'
+      }
+    ],
+    returns: {
+      type: Data,
+      desc: 'The return value of the executed contract'
+    }
+  },
+
+  importSignature: {
+    desc: 'Imports a state signature produced by a validator of a private contract. Once all validators have signed, the node publishes the public anchor transaction.',
+    params: [
+      {
+        type: Data,
+        desc: 'RLP-encoded signed private transaction, as returned by private_importTransaction'
+      }
+    ],
+    returns: {
+      type: Hash,
+      desc: 'Hash of the published anchor transaction or null if signatures are still missing'
+    }
+  },
+
+  importTransaction: {
+    desc: 'Imports an encrypted private transaction as a party of the contract and re-executes it. Validators sign the resulting state change with the local validator account.',
+    params: [
+      {
+        type: Data,
+        desc: 'RLP-encoded encrypted private transaction'
+      }
+    ],
+    returns: {
+      type: Data,
+      desc: 'RLP-encoded signed private transaction to be relayed to the sender or null if the local account is not a validator'
+    }
+  },
+
+  registerContract: {
+    desc: 'Registers a private contract, its code and the public keys of its validators and other parties. The public contract at the given address anchors the private state.',
+    params: [
+      {
+        type: Address,
+        desc: '20 Bytes - The address of the public anchor contract'
+      },
+      {
+        type: Data,
+        desc: 'Code of the private contract'
+      },
+      {
+        type: Array,
+        desc: '64 Bytes public keys of the validators'
+      },
+      {
+        type: Array,
+        desc: '64 Bytes public keys of the other parties, which follow the private state without signing it'
+      }
+    ],
+    returns: {
+      type: Hash,
+      desc: 'The initial private state root'
+    }
+  },
+
+  sendTransaction: {
+    desc: 'Executes a signed transaction against a private contract, encrypts it for every party and collects their state signatures.',
+    params: [
+      {
+        type: Data,
+        desc: 'RLP-encoded signed transaction'
+      }
+    ],
+    returns: {
+      type: Object,
+      desc: 'Receipt with `hash`, `contractAddress`, encrypted `message` to relay to the other parties and `publicTransactionHash` once the state was anchored'
+    }
+  }
+};
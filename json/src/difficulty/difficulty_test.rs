@@ -0,0 +1,62 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Difficulty test deserialization.
+
+use uint::Uint;
+use hash::H256;
+
+/// Difficulty test deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct DifficultyTest {
+	/// Parent timestamp.
+	#[serde(rename="parentTimestamp")]
+	pub parent_timestamp: Uint,
+	/// Parent difficulty.
+	#[serde(rename="parentDifficulty")]
+	pub parent_difficulty: Uint,
+	/// Parent uncles hash.
+	#[serde(rename="parentUncles")]
+	pub parent_uncles: H256,
+	/// Current timestamp.
+	#[serde(rename="currentTimestamp")]
+	pub current_timestamp: Uint,
+	/// Current difficulty.
+	#[serde(rename="currentDifficulty")]
+	pub current_difficulty: Uint,
+	/// Current block number.
+	#[serde(rename="currentBlockNumber")]
+	pub current_block_number: Uint,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use difficulty::DifficultyTest;
+
+	#[test]
+	fn difficulty_test_deserialization() {
+		let s = r#"{
+			"parentTimestamp" : "0x2a",
+			"parentDifficulty" : "0x20000",
+			"parentUncles" : "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+			"currentTimestamp" : "0x32",
+			"currentDifficulty" : "0x20000",
+			"currentBlockNumber" : "0x1"
+		}"#;
+		let _deserialized: DifficultyTest = serde_json::from_str(s).unwrap();
+	}
+}
@@ -18,6 +18,7 @@
 
 use uint::Uint;
 use hash::Address;
+use spec::ValidatorSet;
 
 /// Authority params deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -29,11 +30,17 @@ pub struct AuthorityRoundParams {
 	#[serde(rename="stepDuration")]
 	pub step_duration: Uint,
 	/// Valid authorities
-	pub authorities: Vec<Address>,
+	pub validators: ValidatorSet,
 	/// Starting step. Determined automatically if not specified.
 	/// To be used for testing only.
 	#[serde(rename="startStep")]
 	pub start_step: Option<Uint>,
+	/// Reward per block in wei.
+	#[serde(rename="blockReward")]
+	pub block_reward: Option<Uint>,
+	/// Address of the contract deciding on block rewards, takes precedence over `blockReward`.
+	#[serde(rename="blockRewardContractAddress")]
+	pub block_reward_contract_address: Option<Address>,
 }
 
 /// Authority engine deserialization.
@@ -54,8 +61,11 @@ mod tests {
 			"params": {
 				"gasLimitBoundDivisor": "0x0400",
 				"stepDuration": "0x02",
-				"authorities" : ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"],
-				"startStep" : 24
+				"validators": {
+					"list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				},
+				"startStep" : 24,
+				"blockReward": "0x0d"
 			}
 		}"#;
 
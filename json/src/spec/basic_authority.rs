@@ -18,6 +18,7 @@
 
 use uint::Uint;
 use hash::Address;
+use spec::ValidatorSet;
 
 /// Authority params deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -29,7 +30,13 @@ pub struct BasicAuthorityParams {
 	#[serde(rename="durationLimit")]
 	pub duration_limit: Uint,
 	/// Valid authorities
-	pub authorities: Vec<Address>,
+	pub validators: ValidatorSet,
+	/// Reward per block in wei.
+	#[serde(rename="blockReward")]
+	pub block_reward: Option<Uint>,
+	/// Address of the contract deciding on block rewards, takes precedence over `blockReward`.
+	#[serde(rename="blockRewardContractAddress")]
+	pub block_reward_contract_address: Option<Address>,
 }
 
 /// Authority engine deserialization.
@@ -42,6 +49,10 @@ pub struct BasicAuthority {
 #[cfg(test)]
 mod tests {
 	use serde_json;
+	use std::str::FromStr;
+	use util::{U256, H160 as Hash160};
+	use uint::Uint;
+	use hash::Address;
 	use spec::basic_authority::BasicAuthority;
 
 	#[test]
@@ -50,10 +61,16 @@ mod tests {
 			"params": {
 				"gasLimitBoundDivisor": "0x0400",
 				"durationLimit": "0x0d",
-				"authorities" : ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				"validators": {
+					"list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				},
+				"blockReward": "0x0d",
+				"blockRewardContractAddress": "0x0000000000000000000000000000000000000042"
 			}
 		}"#;
 
-		let _deserialized: BasicAuthority = serde_json::from_str(s).unwrap();
+		let deserialized: BasicAuthority = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.block_reward, Some(Uint(U256::from(0x0d))));
+		assert_eq!(deserialized.params.block_reward_contract_address, Some(Address(Hash160::from_str("0000000000000000000000000000000000000042").unwrap())));
 	}
 }
@@ -16,6 +16,7 @@
 
 //! Ethash params deserialization.
 
+use std::collections::BTreeMap;
 use uint::Uint;
 use hash::Address;
 
@@ -35,6 +36,9 @@ pub struct EthashParams {
 	#[serde(rename="difficultyIncrementDivisor")]
 	pub difficulty_increment_divisor: Option<Uint>,
 	/// See main EthashParams docs.
+	#[serde(rename="metropolisDifficultyIncrementDivisor")]
+	pub metropolis_difficulty_increment_divisor: Option<Uint>,
+	/// See main EthashParams docs.
 	#[serde(rename="durationLimit")]
 	pub duration_limit: Uint,
 	/// See main EthashParams docs.
@@ -97,6 +101,12 @@ pub struct EthashParams {
 	#[serde(rename="maxCodeSize")]
 	pub max_code_size: Option<Uint>,
 
+	/// See main EthashParams docs.
+	#[serde(rename="eip100bTransition")]
+	pub eip100b_transition: Option<Uint>,
+	/// See main EthashParams docs.
+	#[serde(rename="difficultyBombDelays")]
+	pub difficulty_bomb_delays: Option<BTreeMap<Uint, Uint>>,
 }
 
 /// Ethash engine deserialization.
@@ -108,7 +118,9 @@ pub struct Ethash {
 
 #[cfg(test)]
 mod tests {
+	use std::collections::BTreeMap;
 	use serde_json;
+	use uint::Uint;
 	use spec::ethash::Ethash;
 
 	#[test]
@@ -153,11 +165,19 @@ mod tests {
 				"eip155Transition": "0x42",
 				"eip160Transition": "0x42",
 				"eip161abcTransition": "0x42",
-				"eip161dTransition": "0x42"
+				"eip161dTransition": "0x42",
+				"eip100bTransition": "0x42",
+				"difficultyBombDelays": {
+					"0x42": "0x2dc6c0"
+				}
 			}
 		}"#;
 
-		let _deserialized: Ethash = serde_json::from_str(s).unwrap();
+		let deserialized: Ethash = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.eip100b_transition, Some(Uint(0x42u64.into())));
+		let mut delays = BTreeMap::new();
+		delays.insert(Uint(0x42u64.into()), Uint(3_000_000u64.into()));
+		assert_eq!(deserialized.params.difficulty_bomb_delays, Some(delays));
 	}
 
 	#[test]
@@ -28,9 +28,10 @@ pub mod ethash;
 pub mod basic_authority;
 pub mod authority_round;
 pub mod tendermint;
+pub mod validator_set;
 
 pub use self::account::Account;
-pub use self::builtin::{Builtin, Pricing, Linear};
+pub use self::builtin::{Builtin, Pricing, Linear, Modexp, AltBn128Pairing};
 pub use self::genesis::Genesis;
 pub use self::params::Params;
 pub use self::spec::Spec;
@@ -41,3 +42,4 @@ pub use self::ethash::{Ethash, EthashParams};
 pub use self::basic_authority::{BasicAuthority, BasicAuthorityParams};
 pub use self::authority_round::{AuthorityRound, AuthorityRoundParams};
 pub use self::tendermint::{Tendermint, TendermintParams};
+pub use self::validator_set::ValidatorSet;
@@ -49,11 +49,26 @@ pub struct Params {
 	/// Expected fork block hash.
 	#[serde(rename="forkCanonHash")]
 	pub fork_hash: Option<H256>,
+
+	/// See `CommonParams` docs.
+	#[serde(rename="eip140Transition")]
+	pub eip140_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip211Transition")]
+	pub eip211_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip214Transition")]
+	pub eip214_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip658Transition")]
+	pub eip658_transition: Option<Uint>,
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
+	use util::U256;
+	use uint::Uint;
 	use spec::params::Params;
 
 	#[test]
@@ -65,10 +80,13 @@ mod tests {
 			"chainID" : "0x15",
 			"subprotocolName" : "exp",
 			"minGasLimit": "0x1388",
-			"accountStartNonce": "0x00"
+			"accountStartNonce": "0x00",
+			"eip140Transition": "0x10"
 		}"#;
 
-		let _deserialized: Params = serde_json::from_str(s).unwrap();
+		let deserialized: Params = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.eip140_transition, Some(Uint(U256::from(0x10))));
+		assert_eq!(deserialized.eip211_transition, None);
 		// TODO: validate all fields
 	}
 }
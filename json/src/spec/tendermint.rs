@@ -17,7 +17,7 @@
 //! Tendermint params deserialization.
 
 use uint::Uint;
-use hash::Address;
+use spec::ValidatorSet;
 
 /// Tendermint params deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -26,7 +26,7 @@ pub struct TendermintParams {
 	#[serde(rename="gasLimitBoundDivisor")]
 	pub gas_limit_bound_divisor: Uint,
 	/// Valid authorities
-	pub authorities: Vec<Address>,
+	pub validators: ValidatorSet,
 	/// Propose step timeout in milliseconds.
 	#[serde(rename="timeoutPropose")]
 	pub timeout_propose: Option<Uint>,
@@ -58,7 +58,9 @@ mod tests {
 		let s = r#"{
 			"params": {
 				"gasLimitBoundDivisor": "0x0400",
-				"authorities" : ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				"validators": {
+					"list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				}
 			}
 		}"#;
 
@@ -14,11 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::fs::File;
+use std::io::Write;
 use std::path::PathBuf;
-use ethcore::ethstore::{EthStore, SecretStore, import_accounts, read_geth_accounts};
+use util::Address;
+use ethcore::ethstore::{EthStore, SecretStore, KdfParams, import_accounts, read_geth_accounts, export_key_file, export_secret, upgrade_kdf};
 use ethcore::ethstore::dir::DiskDirectory;
 use ethcore::account_provider::AccountProvider;
-use helpers::{password_prompt, password_from_file};
+use helpers::{password_prompt, password_entry_prompt, confirm_prompt, password_from_file, passwords_from_files};
 use params::SpecType;
 
 #[derive(Debug, PartialEq)]
@@ -26,7 +29,10 @@ pub enum AccountCmd {
 	New(NewAccount),
 	List(ListAccounts),
 	Import(ImportAccounts),
-	ImportFromGeth(ImportFromGethAccounts)
+	ImportFromGeth(ImportFromGethAccounts),
+	Export(ExportAccount),
+	ExportSecret(ExportSecret),
+	UpgradeKdf(UpgradeKdf),
 }
 
 #[derive(Debug, PartialEq)]
@@ -60,12 +66,45 @@ pub struct ImportFromGethAccounts {
 	pub spec: SpecType,
 }
 
+/// Parameters for exporting a key file
+#[derive(Debug, PartialEq)]
+pub struct ExportAccount {
+	pub path: String,
+	pub spec: SpecType,
+	pub address: Address,
+	/// file to write the key file to; printed if `None`
+	pub file: Option<String>,
+}
+
+/// Parameters for exporting an unencrypted secret
+#[derive(Debug, PartialEq)]
+pub struct ExportSecret {
+	pub path: String,
+	pub spec: SpecType,
+	pub address: Address,
+	pub password_file: Option<String>,
+}
+
+/// Parameters for re-encrypting all accounts
+#[derive(Debug, PartialEq)]
+pub struct UpgradeKdf {
+	pub path: String,
+	pub spec: SpecType,
+	/// key derivation to re-encrypt with
+	pub kdf: KdfParams,
+	/// files with passwords of the accounts, one per line
+	pub password_files: Vec<String>,
+}
+
 pub fn execute(cmd: AccountCmd) -> Result<String, String> {
 	match cmd {
 		AccountCmd::New(new_cmd) => new(new_cmd),
 		AccountCmd::List(list_cmd) => list(list_cmd),
 		AccountCmd::Import(import_cmd) => import(import_cmd),
-		AccountCmd::ImportFromGeth(import_geth_cmd) => import_geth(import_geth_cmd)
+		AccountCmd::ImportFromGeth(import_geth_cmd) => import_geth(import_geth_cmd),
+		AccountCmd::Export(export_cmd) => export(export_cmd),
+		AccountCmd::ExportSecret(export_secret_cmd) => export_raw_secret(export_secret_cmd),
+		AccountCmd::UpgradeKdf(upgrade_cmd) => upgrade(upgrade_cmd),
 	}
 }
 
@@ -132,3 +171,47 @@ fn import_geth(i: ImportFromGethAccounts) -> Result<String, String> {
 		Err(err) => Err(format!("Import geth accounts failed. {}", err))
 	}
 }
+
+fn export(e: ExportAccount) -> Result<String, String> {
+	let dir = keys_dir(e.path, e.spec)?;
+	let key_file = export_key_file(&dir, &e.address).map_err(|e| format!("Could not export account: {}", e))?;
+	match e.file {
+		Some(path) => {
+			let mut file = File::create(&path).map_err(|e| format!("Could not create {}: {}", path, e))?;
+			file.write_all(key_file.as_bytes()).map_err(|e| format!("Could not write {}: {}", path, e))?;
+			Ok(format!("Key file written to {}", path))
+		},
+		None => Ok(key_file),
+	}
+}
+
+fn export_raw_secret(e: ExportSecret) -> Result<String, String> {
+	let password = match e.password_file {
+		Some(file) => password_from_file(file)?,
+		None => password_entry_prompt()?,
+	};
+
+	let dir = keys_dir(e.path, e.spec)?;
+	let secret = export_secret(&dir, &e.address, &password).map_err(|e| format!("Could not export secret: {}", e))?;
+
+	if !confirm_prompt("Anyone who sees the unencrypted secret can spend the funds of the account. Print it?")? {
+		return Err("Export aborted.".into());
+	}
+	Ok(format!("{:?}", secret))
+}
+
+fn upgrade(u: UpgradeKdf) -> Result<String, String> {
+	if u.password_files.is_empty() {
+		return Err("Provide the passwords of the accounts with --password.".into());
+	}
+
+	let passwords = passwords_from_files(&u.password_files)?;
+	let dir = keys_dir(u.path, u.spec)?;
+	let result = upgrade_kdf(&dir, &passwords, u.kdf).map_err(|e| format!("Could not upgrade accounts: {}", e))?;
+
+	let mut output = format!("{} account(s) upgraded", result.upgraded.len());
+	for address in &result.skipped {
+		output = format!("{}\nSkipped {:?}: no matching password", output, address);
+	}
+	Ok(output)
+}
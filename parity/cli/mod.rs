@@ -41,12 +41,18 @@ usage! {
 		cmd_hash: bool,
 		cmd_kill: bool,
 		cmd_db: bool,
+		cmd_rebuild_archive: bool,
+		cmd_export_secret: bool,
+		cmd_upgrade_kdf: bool,
+		cmd_scrypt: bool,
+		cmd_pbkdf2: bool,
 
 		// Arguments
 		arg_pid_file: String,
 		arg_file: Option<String>,
 		arg_path: Vec<String>,
 		arg_id: Option<usize>,
+		arg_address: Option<String>,
 
 		// Flags
 		// -- Legacy Options
@@ -102,6 +108,10 @@ usage! {
 			or |c: &Config| otry!(c.account).password.clone(),
 		flag_keys_iterations: u32 = 10240u32,
 			or |c: &Config| otry!(c.account).keys_iterations.clone(),
+		flag_ledger_device: Option<String> = None,
+			or |c: &Config| otry!(c.account).ledger_device.clone().map(Some),
+		flag_ledger_accounts: u32 = 1u32,
+			or |c: &Config| otry!(c.account).ledger_accounts.clone(),
 
 		flag_force_ui: bool = false,
 			or |c: &Config| otry!(c.ui).force.clone(),
@@ -119,6 +129,12 @@ usage! {
 		// -- Networking Options
 		flag_warp: bool = false,
 			or |c: &Config| otry!(c.network).warp.clone(),
+		flag_fast_sync: bool = false,
+			or |c: &Config| otry!(c.network).fast_sync.clone(),
+		flag_light: bool = false,
+			or |c: &Config| otry!(c.network).light.clone(),
+		flag_serve_light: bool = false,
+			or |c: &Config| otry!(c.network).serve_light.clone(),
 		flag_port: u16 = 30303u16,
 			or |c: &Config| otry!(c.network).port.clone(),
 		flag_min_peers: u16 = 25u16,
@@ -129,16 +145,22 @@ usage! {
 			or |c: &Config| otry!(c.network).max_pending_peers.clone(),
 		flag_snapshot_peers: u16 = 0u16,
 			or |c: &Config| otry!(c.network).snapshot_peers.clone(),
+		flag_ban_duration: u64 = 3600u64,
+			or |c: &Config| otry!(c.network).ban_duration.clone(),
 		flag_nat: String = "any",
 			or |c: &Config| otry!(c.network).nat.clone(),
 		flag_allow_ips: String = "all",
 			or |c: &Config| otry!(c.network).allow_ips.clone(),
+		flag_deny_ips: Option<String> = None,
+			or |c: &Config| otry!(c.network).deny_ips.clone().map(Some),
 		flag_network_id: Option<u64> = None,
 			or |c: &Config| otry!(c.network).id.clone().map(Some),
 		flag_bootnodes: Option<String> = None,
 			or |c: &Config| otry!(c.network).bootnodes.clone().map(|vec| Some(vec.join(","))),
 		flag_no_discovery: bool = false,
 			or |c: &Config| otry!(c.network).discovery.map(|d| !d).clone(),
+		flag_discovery_v5: bool = false,
+			or |c: &Config| otry!(c.network).discovery_v5.clone(),
 		flag_node_key: Option<String> = None,
 			or |c: &Config| otry!(c.network).node_key.clone().map(Some),
 		flag_reserved_peers: Option<String> = None,
@@ -162,6 +184,18 @@ usage! {
 		flag_jsonrpc_hosts: String = "none",
 			or |c: &Config| otry!(c.rpc).hosts.clone().map(|vec| vec.join(",")),
 
+		// WS
+		flag_no_ws: bool = false,
+			or |c: &Config| otry!(c.websockets).disable.clone(),
+		flag_ws_port: u16 = 8546u16,
+			or |c: &Config| otry!(c.websockets).port.clone(),
+		flag_ws_interface: String  = "local",
+			or |c: &Config| otry!(c.websockets).interface.clone(),
+		flag_ws_apis: String = "web3,eth,net,parity,traces,rpc",
+			or |c: &Config| otry!(c.websockets).apis.clone().map(|vec| vec.join(",")),
+		flag_ws_origins: String = "none",
+			or |c: &Config| otry!(c.websockets).origins.clone().map(|vec| vec.join(",")),
+
 		// IPC
 		flag_no_ipc: bool = false,
 			or |c: &Config| otry!(c.ipc).disable.clone(),
@@ -191,6 +225,10 @@ usage! {
 			or |c: &Config| otry!(c.mining).author.clone().map(Some),
 		flag_engine_signer: Option<String> = None,
 			or |c: &Config| otry!(c.mining).engine_signer.clone().map(Some),
+		flag_private_signer: Option<String> = None,
+			or |c: &Config| otry!(c.mining).private_signer.clone().map(Some),
+		flag_private_validators: Option<String> = None,
+			or |c: &Config| otry!(c.mining).private_validators.clone().map(Some),
 		flag_force_sealing: bool = false,
 			or |c: &Config| otry!(c.mining).force_sealing.clone(),
 		flag_reseal_on_txs: String = "own",
@@ -227,11 +265,37 @@ usage! {
 			or |c: &Config| otry!(c.mining).tx_queue_ban_count.clone(),
 		flag_tx_queue_ban_time: u16 = 180u16,
 			or |c: &Config| otry!(c.mining).tx_queue_ban_time.clone(),
+		flag_tx_queue_persist_all: bool = false,
+			or |c: &Config| otry!(c.mining).tx_queue_persist_all.clone(),
 		flag_remove_solved: bool = false,
 			or |c: &Config| otry!(c.mining).remove_solved.clone(),
 		flag_notify_work: Option<String> = None,
 			or |c: &Config| otry!(c.mining).notify_work.clone().map(|vec| Some(vec.join(","))),
 
+		// -- Stratum Options
+		flag_stratum: bool = false,
+			or |c: &Config| otry!(c.stratum).enable.clone(),
+		flag_stratum_interface: String = "local",
+			or |c: &Config| otry!(c.stratum).interface.clone(),
+		flag_stratum_port: u16 = 8008u16,
+			or |c: &Config| otry!(c.stratum).port.clone(),
+		flag_stratum_secret: Option<String> = None,
+			or |c: &Config| otry!(c.stratum).secret.clone().map(Some),
+		flag_stratum_vardiff: bool = false,
+			or |c: &Config| otry!(c.stratum).vardiff.clone(),
+		flag_vardiff_initial: u64 = 4294967296u64,
+			or |c: &Config| otry!(c.stratum).vardiff_initial.clone(),
+		flag_vardiff_min: u64 = 16777216u64,
+			or |c: &Config| otry!(c.stratum).vardiff_min.clone(),
+		flag_vardiff_max: u64 = 281474976710656u64,
+			or |c: &Config| otry!(c.stratum).vardiff_max.clone(),
+		flag_vardiff_target_time: u64 = 10u64,
+			or |c: &Config| otry!(c.stratum).vardiff_target_time.clone(),
+		flag_vardiff_retarget_time: u64 = 60u64,
+			or |c: &Config| otry!(c.stratum).vardiff_retarget_time.clone(),
+		flag_vardiff_variance: u64 = 30u64,
+			or |c: &Config| otry!(c.stratum).vardiff_variance.clone(),
+
 		// -- Footprint Options
 		flag_tracing: String = "auto",
 			or |c: &Config| otry!(c.footprint).tracing.clone(),
@@ -298,9 +362,11 @@ struct Config {
 	ui: Option<Ui>,
 	network: Option<Network>,
 	rpc: Option<Rpc>,
+	websockets: Option<Ws>,
 	ipc: Option<Ipc>,
 	dapps: Option<Dapps>,
 	mining: Option<Mining>,
+	stratum: Option<Stratum>,
 	footprint: Option<Footprint>,
 	snapshots: Option<Snapshots>,
 	vm: Option<VM>,
@@ -328,6 +394,8 @@ struct Account {
 	unlock: Option<Vec<String>>,
 	password: Option<Vec<String>>,
 	keys_iterations: Option<u32>,
+	ledger_device: Option<String>,
+	ledger_accounts: Option<u32>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -343,16 +411,22 @@ struct Ui {
 struct Network {
 	disable: Option<bool>,
 	warp: Option<bool>,
+	fast_sync: Option<bool>,
+	light: Option<bool>,
+	serve_light: Option<bool>,
 	port: Option<u16>,
 	min_peers: Option<u16>,
 	max_peers: Option<u16>,
 	snapshot_peers: Option<u16>,
 	max_pending_peers: Option<u16>,
+	ban_duration: Option<u64>,
 	nat: Option<String>,
 	allow_ips: Option<String>,
+	deny_ips: Option<String>,
 	id: Option<u64>,
 	bootnodes: Option<Vec<String>>,
 	discovery: Option<bool>,
+	discovery_v5: Option<bool>,
 	node_key: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
@@ -368,6 +442,15 @@ struct Rpc {
 	hosts: Option<Vec<String>>,
 }
 
+#[derive(Default, Debug, PartialEq, RustcDecodable)]
+struct Ws {
+	disable: Option<bool>,
+	port: Option<u16>,
+	interface: Option<String>,
+	apis: Option<Vec<String>>,
+	origins: Option<Vec<String>>,
+}
+
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Ipc {
 	disable: Option<bool>,
@@ -390,6 +473,8 @@ struct Dapps {
 struct Mining {
 	author: Option<String>,
 	engine_signer: Option<String>,
+	private_signer: Option<String>,
+	private_validators: Option<String>,
 	force_sealing: Option<bool>,
 	reseal_on_txs: Option<String>,
 	reseal_min_period: Option<u64>,
@@ -408,10 +493,26 @@ struct Mining {
 	tx_queue_strategy: Option<String>,
 	tx_queue_ban_count: Option<u16>,
 	tx_queue_ban_time: Option<u16>,
+	tx_queue_persist_all: Option<bool>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
 }
 
+#[derive(Default, Debug, PartialEq, RustcDecodable)]
+struct Stratum {
+	enable: Option<bool>,
+	interface: Option<String>,
+	port: Option<u16>,
+	secret: Option<String>,
+	vardiff: Option<bool>,
+	vardiff_initial: Option<u64>,
+	vardiff_min: Option<u64>,
+	vardiff_max: Option<u64>,
+	vardiff_target_time: Option<u64>,
+	vardiff_retarget_time: Option<u64>,
+	vardiff_variance: Option<u64>,
+}
+
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Footprint {
 	tracing: Option<String>,
@@ -529,12 +630,18 @@ mod tests {
 			cmd_hash: false,
 			cmd_db: false,
 			cmd_kill: false,
+			cmd_rebuild_archive: false,
+			cmd_export_secret: false,
+			cmd_upgrade_kdf: false,
+			cmd_scrypt: false,
+			cmd_pbkdf2: false,
 
 			// Arguments
 			arg_pid_file: "".into(),
 			arg_file: None,
 			arg_id: None,
 			arg_path: vec![],
+			arg_address: None,
 
 			// -- Operating Options
 			flag_mode: "last".into(),
@@ -554,6 +661,8 @@ mod tests {
 			flag_unlock: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
 			flag_password: vec!["~/.safe/password.file".into()],
 			flag_keys_iterations: 10240u32,
+			flag_ledger_device: None,
+			flag_ledger_accounts: 1u32,
 
 			flag_force_ui: false,
 			flag_no_ui: false,
@@ -564,16 +673,22 @@ mod tests {
 
 			// -- Networking Options
 			flag_warp: true,
+			flag_fast_sync: false,
+			flag_light: false,
+			flag_serve_light: true,
 			flag_port: 30303u16,
 			flag_min_peers: 25u16,
 			flag_max_peers: 50u16,
 			flag_max_pending_peers: 64u16,
 			flag_snapshot_peers: 0u16,
+			flag_ban_duration: 3600u64,
 			flag_allow_ips: "all".into(),
+			flag_deny_ips: None,
 			flag_nat: "any".into(),
 			flag_network_id: Some(1),
 			flag_bootnodes: Some("".into()),
 			flag_no_discovery: false,
+			flag_discovery_v5: false,
 			flag_node_key: None,
 			flag_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
@@ -588,6 +703,13 @@ mod tests {
 			flag_jsonrpc_apis: "web3,eth,net,parity,traces,rpc".into(),
 			flag_jsonrpc_hosts: "none".into(),
 
+			// WS
+			flag_no_ws: false,
+			flag_ws_port: 8546u16,
+			flag_ws_interface: "local".into(),
+			flag_ws_apis: "web3,eth,net,parity,traces,rpc".into(),
+			flag_ws_origins: "none".into(),
+
 			// IPC
 			flag_no_ipc: false,
 			flag_ipc_path: "$HOME/.parity/jsonrpc.ipc".into(),
@@ -605,6 +727,8 @@ mod tests {
 			// -- Sealing/Mining Options
 			flag_author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 			flag_engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+			flag_private_signer: None,
+			flag_private_validators: None,
 			flag_force_sealing: true,
 			flag_reseal_on_txs: "all".into(),
 			flag_reseal_min_period: 4000u64,
@@ -623,9 +747,23 @@ mod tests {
 			flag_tx_queue_strategy: "gas_factor".into(),
 			flag_tx_queue_ban_count: 1u16,
 			flag_tx_queue_ban_time: 180u16,
+			flag_tx_queue_persist_all: false,
 			flag_remove_solved: false,
 			flag_notify_work: Some("http://localhost:3001".into()),
 
+			// -- Stratum Options
+			flag_stratum: false,
+			flag_stratum_interface: "local".into(),
+			flag_stratum_port: 8008u16,
+			flag_stratum_secret: None,
+			flag_stratum_vardiff: false,
+			flag_vardiff_initial: 4294967296u64,
+			flag_vardiff_min: 16777216u64,
+			flag_vardiff_max: 281474976710656u64,
+			flag_vardiff_target_time: 10u64,
+			flag_vardiff_retarget_time: 60u64,
+			flag_vardiff_variance: 30u64,
+
 			// -- Footprint Options
 			flag_tracing: "auto".into(),
 			flag_pruning: "auto".into(),
@@ -732,6 +870,8 @@ mod tests {
 				unlock: Some(vec!["0x1".into(), "0x2".into(), "0x3".into()]),
 				password: Some(vec!["passwdfile path".into()]),
 				keys_iterations: None,
+				ledger_device: None,
+				ledger_accounts: None,
 			}),
 			ui: Some(Ui {
 				force: None,
@@ -743,16 +883,22 @@ mod tests {
 			network: Some(Network {
 				disable: Some(false),
 				warp: Some(false),
+				fast_sync: None,
+				light: None,
+				serve_light: None,
 				port: None,
 				min_peers: Some(10),
 				max_peers: Some(20),
 				max_pending_peers: Some(30),
 				snapshot_peers: Some(40),
+				ban_duration: None,
 				allow_ips: Some("public".into()),
+				deny_ips: None,
 				nat: Some("any".into()),
 				id: None,
 				bootnodes: None,
 				discovery: Some(true),
+				discovery_v5: None,
 				node_key: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
@@ -765,6 +911,7 @@ mod tests {
 				apis: None,
 				hosts: None,
 			}),
+			websockets: None,
 			ipc: Some(Ipc {
 				disable: None,
 				path: None,
@@ -782,6 +929,8 @@ mod tests {
 			mining: Some(Mining {
 				author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 				engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+				private_signer: None,
+				private_validators: None,
 				force_sealing: Some(true),
 				reseal_on_txs: Some("all".into()),
 				reseal_min_period: Some(4000),
@@ -797,12 +946,14 @@ mod tests {
 				tx_queue_strategy: None,
 				tx_queue_ban_count: None,
 				tx_queue_ban_time: None,
+				tx_queue_persist_all: None,
 				tx_gas_limit: None,
 				tx_time_limit: None,
 				extra_data: None,
 				remove_solved: None,
 				notify_work: None,
 			}),
+			stratum: None,
 			footprint: Some(Footprint {
 				tracing: Some("on".into()),
 				pruning: Some("fast".into()),
@@ -20,14 +20,15 @@ use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::cmp::max;
 use cli::{Args, ArgsError};
-use util::{Hashable, U256, Uint, Bytes, version_data, Secret, Address};
+use util::{Hashable, H256, U256, Uint, Bytes, version_data, Secret, Address};
 use util::log::Colour;
-use ethsync::{NetworkConfiguration, is_valid_node_url, AllowIP};
+use ethsync::{NetworkConfiguration, is_valid_node_url};
 use ethcore::client::{VMType};
-use ethcore::miner::{MinerOptions, Banning};
+use ethcore::miner::{MinerOptions, Banning, StratumOptions, VardiffConfig};
 use ethcore::verification::queue::VerifierSettings;
+use ethcore::ethstore::KdfParams;
 
-use rpc::{IpcConfiguration, HttpConfiguration};
+use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration};
 use ethcore_rpc::NetworkSettings;
 use cache::CacheConfig;
 use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, replace_home,
@@ -41,7 +42,7 @@ use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
 use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
 use presale::ImportWallet;
-use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
+use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts, ExportAccount, ExportSecret, UpgradeKdf};
 use snapshot::{self, SnapshotCommand};
 
 const AUTHCODE_FILENAME: &'static str = "authcodes";
@@ -104,6 +105,7 @@ impl Configuration {
 		let miner_options = self.miner_options()?;
 		let logger_config = self.logger_config();
 		let http_conf = self.http_config()?;
+		let ws_conf = self.ws_config()?;
 		let ipc_conf = self.ipc_config()?;
 		let net_conf = self.net_config()?;
 		let network_id = self.network_id();
@@ -114,6 +116,9 @@ impl Configuration {
 		let compaction = self.args.flag_db_compaction.parse()?;
 		let wal = !self.args.flag_fast_and_loose;
 		let warp_sync = self.args.flag_warp;
+		let fast_sync = self.args.flag_fast_sync;
+		let light = self.args.flag_light;
+		let serve_light = self.args.flag_serve_light;
 		let geth_compatibility = self.args.flag_geth;
 		let ui_address = self.ui_port().map(|port| (self.ui_interface(), port));
 		let dapps_conf = self.dapps_config();
@@ -182,6 +187,34 @@ impl Configuration {
 					spec: spec,
 				};
 				AccountCmd::Import(import_acc)
+			} else if self.args.cmd_export {
+				let export_acc = ExportAccount {
+					path: dirs.keys,
+					spec: spec,
+					address: to_address(self.args.arg_address.clone())?,
+					file: self.args.arg_file.clone(),
+				};
+				AccountCmd::Export(export_acc)
+			} else if self.args.cmd_export_secret {
+				let export_secret = ExportSecret {
+					path: dirs.keys,
+					spec: spec,
+					address: to_address(self.args.arg_address.clone())?,
+					password_file: self.args.flag_password.first().cloned(),
+				};
+				AccountCmd::ExportSecret(export_secret)
+			} else if self.args.cmd_upgrade_kdf {
+				let upgrade_kdf = UpgradeKdf {
+					path: dirs.keys,
+					spec: spec,
+					kdf: if self.args.cmd_scrypt {
+						KdfParams::scrypt()
+					} else {
+						KdfParams::Pbkdf2 { iterations: self.args.flag_keys_iterations }
+					},
+					password_files: self.args.flag_password.clone(),
+				};
+				AccountCmd::UpgradeKdf(upgrade_kdf)
 			} else {
 				unreachable!();
 			};
@@ -306,6 +339,13 @@ impl Configuration {
 
 			let verifier_settings = self.verifier_settings();
 
+			// `db rebuild-archive` runs the node, rebuilding archive state in the background.
+			let rebuild_archive_from = if self.args.cmd_db && self.args.cmd_rebuild_archive {
+				Some(self.args.flag_from.parse().map_err(|_| format!("Invalid block number: {}", self.args.flag_from))?)
+			} else {
+				None
+			};
+
 			let run_cmd = RunCmd {
 				cache_config: cache_config,
 				dirs: dirs,
@@ -316,12 +356,14 @@ impl Configuration {
 				logger_config: logger_config.clone(),
 				miner_options: miner_options,
 				http_conf: http_conf,
+				ws_conf: ws_conf,
 				ipc_conf: ipc_conf,
 				net_conf: net_conf,
 				network_id: network_id,
 				acc_conf: self.accounts_config()?,
 				gas_pricer: self.gas_pricer_config()?,
 				miner_extras: self.miner_extras()?,
+				stratum: self.stratum_options()?,
 				update_policy: update_policy,
 				mode: mode,
 				tracing: tracing,
@@ -330,6 +372,9 @@ impl Configuration {
 				wal: wal,
 				vm_type: vm_type,
 				warp_sync: warp_sync,
+				fast_sync: fast_sync,
+				light: light,
+				serve_light: serve_light,
 				geth_compatibility: geth_compatibility,
 				ui_address: ui_address,
 				net_settings: self.network_settings(),
@@ -342,6 +387,8 @@ impl Configuration {
 				check_seal: !self.args.flag_no_seal_check,
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
 				verifier_settings: verifier_settings,
+				rebuild_archive_from: rebuild_archive_from,
+				rebuild_archive_snapshot: rebuild_archive_from.and_then(|_| self.args.arg_file.clone()),
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -368,6 +415,8 @@ impl Configuration {
 			gas_ceil_target: to_u256(&self.args.flag_gas_cap)?,
 			transactions_limit: self.args.flag_tx_queue_size,
 			engine_signer: self.engine_signer()?,
+			private_signer: self.private_signer()?,
+			private_validators: to_addresses(&self.args.flag_private_validators)?,
 		};
 
 		Ok(extras)
@@ -381,6 +430,10 @@ impl Configuration {
 		to_address(self.args.flag_engine_signer.clone())
 	}
 
+	fn private_signer(&self) -> Result<Address, String> {
+		to_address(self.args.flag_private_signer.clone())
+	}
+
 	fn format(&self) -> Result<Option<DataFormat>, String> {
 		match self.args.flag_format {
 			Some(ref f) => Ok(Some(f.parse()?)),
@@ -421,15 +474,6 @@ impl Configuration {
 		max(self.min_peers(), peers)
 	}
 
-	fn allow_ips(&self) -> Result<AllowIP, String> {
-		match self.args.flag_allow_ips.as_str() {
-			"all" => Ok(AllowIP::All),
-			"public" => Ok(AllowIP::Public),
-			"private" => Ok(AllowIP::Private),
-			_ => Err("Invalid IP filter value".to_owned()),
-		}
-	}
-
 	fn min_peers(&self) -> u32 {
 		self.args.flag_peers.unwrap_or(self.args.flag_min_peers) as u32
 	}
@@ -452,11 +496,53 @@ impl Configuration {
 			testnet: self.args.flag_testnet,
 			password_files: self.args.flag_password.clone(),
 			unlocked_accounts: to_addresses(&self.args.flag_unlock)?,
+			ledger_device: self.args.flag_ledger_device.clone(),
+			ledger_accounts: self.args.flag_ledger_accounts,
 		};
 
 		Ok(cfg)
 	}
 
+	fn stratum_options(&self) -> Result<Option<StratumOptions>, String> {
+		if !self.args.flag_stratum {
+			return Ok(None);
+		}
+
+		Ok(Some(StratumOptions {
+			listen_addr: self.stratum_interface(),
+			port: self.args.flag_stratum_port,
+			secret: self.args.flag_stratum_secret.as_ref().map(|s| s.parse::<H256>().unwrap_or_else(|_| s.sha3())),
+			vardiff: self.vardiff_config()?,
+		}))
+	}
+
+	fn vardiff_config(&self) -> Result<Option<VardiffConfig>, String> {
+		if !self.args.flag_stratum_vardiff {
+			return Ok(None);
+		}
+
+		let config = VardiffConfig {
+			initial_difficulty: self.args.flag_vardiff_initial,
+			min_difficulty: self.args.flag_vardiff_min,
+			max_difficulty: self.args.flag_vardiff_max,
+			target_share_secs: self.args.flag_vardiff_target_time,
+			retarget_secs: self.args.flag_vardiff_retarget_time,
+			variance_percent: self.args.flag_vardiff_variance,
+		};
+
+		if config.min_difficulty > config.max_difficulty {
+			return Err("--vardiff-min must not be greater than --vardiff-max".into());
+		}
+		if config.initial_difficulty < config.min_difficulty || config.initial_difficulty > config.max_difficulty {
+			return Err("--vardiff-initial must be between --vardiff-min and --vardiff-max".into());
+		}
+		if config.target_share_secs == 0 {
+			return Err("--vardiff-target-time must be greater than zero".into());
+		}
+
+		Ok(Some(config))
+	}
+
 	fn miner_options(&self) -> Result<MinerOptions, String> {
 		let reseal = self.args.flag_reseal_on_txs.parse::<ResealPolicy>()?;
 
@@ -483,7 +569,8 @@ impl Configuration {
 					ban_duration: Duration::from_secs(self.args.flag_tx_queue_ban_time as u64),
 				},
 				None => Banning::Disabled,
-			}
+			},
+			tx_queue_persist_all: self.args.flag_tx_queue_persist_all,
 		};
 
 		Ok(options)
@@ -586,11 +673,14 @@ impl Configuration {
 		ret.public_address = public.map(|p| format!("{}", p));
 		ret.use_secret = self.args.flag_node_key.as_ref().map(|s| s.parse::<Secret>().unwrap_or_else(|_| s.sha3()));
 		ret.discovery_enabled = !self.args.flag_no_discovery && !self.args.flag_nodiscover;
+		ret.discovery_v5 = self.args.flag_discovery_v5;
 		ret.max_peers = self.max_peers();
 		ret.min_peers = self.min_peers();
 		ret.snapshot_peers = self.snapshot_peers();
-		ret.allow_ips = self.allow_ips()?;
+		ret.set_ip_filter(&self.args.flag_allow_ips, self.args.flag_deny_ips.as_ref().map_or("", |d| d.as_str()))
+			.map_err(|e| format!("Invalid IP filter value: {}", e))?;
 		ret.max_pending_peers = self.max_pending_peers();
+		ret.ban_duration = self.args.flag_ban_duration;
 		let mut net_path = PathBuf::from(self.directories().base);
 		net_path.push("network");
 		ret.config_path = Some(net_path.to_str().unwrap().to_owned());
@@ -629,6 +719,16 @@ impl Configuration {
 		Some(hosts)
 	}
 
+	fn ws_origins(&self) -> Option<Vec<String>> {
+		match self.args.flag_ws_origins.as_ref() {
+			"none" => return Some(Vec::new()),
+			"all" => return None,
+			_ => {}
+		}
+		let origins = self.args.flag_ws_origins.split(',').map(|o| o.into()).collect();
+		Some(origins)
+	}
+
 	fn dapps_hosts(&self) -> Option<Vec<String>> {
 		match self.args.flag_dapps_hosts.as_ref() {
 			"none" => return Some(Vec::new()),
@@ -671,6 +771,18 @@ impl Configuration {
 		Ok(conf)
 	}
 
+	fn ws_config(&self) -> Result<WsConfiguration, String> {
+		let conf = WsConfiguration {
+			enabled: !self.args.flag_no_ws,
+			interface: self.ws_interface(),
+			port: self.args.flag_ws_port,
+			apis: self.args.flag_ws_apis.parse()?,
+			origins: self.ws_origins(),
+		};
+
+		Ok(conf)
+	}
+
 	fn network_settings(&self) -> NetworkSettings {
 		NetworkSettings {
 			name: self.args.flag_identity.clone(),
@@ -769,6 +881,22 @@ impl Configuration {
 		}.into()
 	}
 
+	fn ws_interface(&self) -> String {
+		match self.args.flag_ws_interface.as_str() {
+			"all" => "0.0.0.0",
+			"local" => "127.0.0.1",
+			x => x,
+		}.into()
+	}
+
+	fn stratum_interface(&self) -> String {
+		match self.args.flag_stratum_interface.as_str() {
+			"all" => "0.0.0.0",
+			"local" => "127.0.0.1",
+			x => x,
+		}.into()
+	}
+
 	fn dapps_interface(&self) -> String {
 		match self.args.flag_dapps_interface.as_str() {
 			"local" => "127.0.0.1",
@@ -809,7 +937,7 @@ mod tests {
 	use cli::Args;
 	use ethcore_rpc::NetworkSettings;
 	use ethcore::client::{VMType, BlockId};
-	use ethcore::miner::{MinerOptions, PrioritizationStrategy};
+	use ethcore::miner::{MinerOptions, PrioritizationStrategy, StratumOptions, VardiffConfig};
 	use helpers::{default_network_config};
 	use run::RunCmd;
 	use dir::{Directories, default_hypervisor_path};
@@ -817,8 +945,10 @@ mod tests {
 	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState};
 	use presale::ImportWallet;
 	use params::SpecType;
-	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
+	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts, ExportAccount, UpgradeKdf};
+	use ethcore::ethstore::KdfParams;
 	use devtools::{RandomTempPath};
+	use util::Hashable;
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 	use std::io::Write;
 	use std::fs::{File, create_dir};
@@ -874,6 +1004,30 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_account_export() {
+		let args = vec!["parity", "account", "export", "0x0000000000000000000000000000000000000001", "key.json"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::Export(ExportAccount {
+			path: Directories::default().keys,
+			spec: SpecType::default(),
+			address: 1.into(),
+			file: Some("key.json".into()),
+		})));
+	}
+
+	#[test]
+	fn test_command_account_upgrade_kdf() {
+		let args = vec!["parity", "account", "upgrade-kdf", "pbkdf2", "--keys-iterations", "20480", "--password", "pwd"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::UpgradeKdf(UpgradeKdf {
+			path: Directories::default().keys,
+			spec: SpecType::default(),
+			kdf: KdfParams::Pbkdf2 { iterations: 20480 },
+			password_files: vec!["pwd".into()],
+		})));
+	}
+
 	#[test]
 	fn test_command_wallet_import() {
 		let args = vec!["parity", "wallet", "import", "my_wallet.json", "--password", "pwd"];
@@ -1006,13 +1160,18 @@ mod tests {
 			logger_config: Default::default(),
 			miner_options: Default::default(),
 			http_conf: Default::default(),
+			ws_conf: Default::default(),
 			ipc_conf: Default::default(),
 			net_conf: default_network_config(),
 			network_id: None,
 			warp_sync: false,
+			fast_sync: false,
+			light: false,
+			serve_light: false,
 			acc_conf: Default::default(),
 			gas_pricer: Default::default(),
 			miner_extras: Default::default(),
+			stratum: None,
 			update_policy: UpdatePolicy { enable_downloading: true, require_consensus: true, filter: UpdateFilter::Critical, track: ReleaseTrack::Unknown, path: default_hypervisor_path() },
 			mode: Default::default(),
 			tracing: Default::default(),
@@ -1032,9 +1191,24 @@ mod tests {
 			check_seal: true,
 			download_old_blocks: true,
 			verifier_settings: Default::default(),
+			rebuild_archive_from: None,
+			rebuild_archive_snapshot: None,
 		}));
 	}
 
+	#[test]
+	fn test_command_db_rebuild_archive() {
+		let args = vec!["parity", "db", "rebuild-archive", "--from", "4000000", "snapshot.bin"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Run(cmd) => {
+				assert_eq!(cmd.rebuild_archive_from, Some(4000000));
+				assert_eq!(cmd.rebuild_archive_snapshot, Some("snapshot.bin".into()));
+			}
+			cmd => panic!("unexpected command: {:?}", cmd),
+		}
+	}
+
 	#[test]
 	fn should_parse_mining_options() {
 		// given
@@ -1071,6 +1245,25 @@ mod tests {
 		assert!(conf3.update_policy().is_err());
 	}
 
+	#[test]
+	fn should_parse_ip_filter() {
+		use ethsync::AllowIP;
+
+		let conf = parse(&["parity", "--allow-ips", "10.0.0.0/8,192.168.1.0/24", "--deny-ips", "10.1.0.0/16"]);
+		let net = conf.net_config().unwrap();
+		assert_eq!(net.allow_ips, AllowIP::None);
+		assert_eq!(net.allow_networks, vec!["10.0.0.0/8".to_owned(), "192.168.1.0/24".to_owned()]);
+		assert_eq!(net.deny_networks, vec!["10.1.0.0/16".to_owned()]);
+
+		let conf = parse(&["parity", "--allow-ips", "public"]);
+		let net = conf.net_config().unwrap();
+		assert_eq!(net.allow_ips, AllowIP::Public);
+		assert!(net.deny_networks.is_empty());
+
+		let conf = parse(&["parity", "--deny-ips", "10.0.0.0/40"]);
+		assert!(conf.net_config().is_err());
+	}
+
 	#[test]
 	fn should_parse_network_settings() {
 		// given
@@ -1219,6 +1412,39 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_parse_stratum_options() {
+		// given
+		let disabled = parse(&["parity"]);
+		let fixed = parse(&["parity", "--stratum", "--stratum-port", "3333", "--stratum-secret", "pass"]);
+		let vardiff = parse(&["parity", "--stratum", "--stratum-interface", "all", "--stratum-vardiff",
+			"--vardiff-initial", "1000", "--vardiff-min", "10", "--vardiff-max", "100000", "--vardiff-target-time", "5"]);
+		let invalid = parse(&["parity", "--stratum", "--stratum-vardiff", "--vardiff-min", "1000", "--vardiff-max", "10"]);
+
+		// then
+		assert_eq!(disabled.stratum_options(), Ok(None));
+		assert_eq!(fixed.stratum_options(), Ok(Some(StratumOptions {
+			listen_addr: "127.0.0.1".into(),
+			port: 3333,
+			secret: Some("pass".sha3()),
+			vardiff: None,
+		})));
+		assert_eq!(vardiff.stratum_options(), Ok(Some(StratumOptions {
+			listen_addr: "0.0.0.0".into(),
+			port: 8008,
+			secret: None,
+			vardiff: Some(VardiffConfig {
+				initial_difficulty: 1000,
+				min_difficulty: 10,
+				max_difficulty: 100000,
+				target_share_secs: 5,
+				retarget_secs: 60,
+				variance_percent: 30,
+			}),
+		})));
+		assert!(invalid.stratum_options().is_err());
+	}
+
 	#[test]
 	fn should_not_bail_on_empty_line_in_reserved_peers() {
 		let temp = RandomTempPath::new();
@@ -196,6 +196,13 @@ impl DatabaseDirectories {
 		dir
 	}
 
+	/// Get the path for the database of canonical hash tries served to light clients.
+	pub fn cht_path(&self) -> PathBuf {
+		let mut dir = self.db_root_path();
+		dir.push("cht");
+		dir
+	}
+
 	/// Get the path for the network directory.
 	pub fn network_path(&self) -> PathBuf {
 		let mut dir = self.spec_root_path();
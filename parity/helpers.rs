@@ -195,6 +195,7 @@ pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 		udp_port: None,
 		nat_enabled: true,
 		discovery_enabled: true,
+		discovery_v5: false,
 		boot_nodes: Vec::new(),
 		use_secret: None,
 		max_peers: 50,
@@ -202,8 +203,11 @@ pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 		snapshot_peers: 0,
 		max_pending_peers: 64,
 		allow_ips: AllowIP::All,
+		allow_networks: Vec::new(),
+		deny_networks: Vec::new(),
 		reserved_nodes: Vec::new(),
 		allow_non_reserved: true,
+		ban_duration: 3600,
 	}
 }
 
@@ -302,6 +306,26 @@ pub fn password_prompt() -> Result<String, String> {
 	Ok(password)
 }
 
+/// Ask for the password of an existing account.
+pub fn password_entry_prompt() -> Result<String, String> {
+	use rpassword::read_password;
+
+	print!("Type password: ");
+	flush_stdout();
+
+	read_password().map_err(|_| "Unable to ask for password on non-interactive terminal.".to_owned())
+}
+
+/// Ask the user to confirm `question` by typing `yes`.
+pub fn confirm_prompt(question: &str) -> Result<bool, String> {
+	print!("{} Type 'yes' to confirm: ", question);
+	flush_stdout();
+
+	let mut answer = String::new();
+	io::stdin().read_line(&mut answer).map_err(|_| "Unable to read confirmation.".to_owned())?;
+	Ok(answer.trim() == "yes")
+}
+
 /// Read a password from password file.
 pub fn password_from_file(path: String) -> Result<String, String> {
 	let passwords = passwords_from_files(&[path])?;
@@ -160,6 +160,8 @@ pub struct AccountsConfig {
 	pub testnet: bool,
 	pub password_files: Vec<String>,
 	pub unlocked_accounts: Vec<Address>,
+	pub ledger_device: Option<String>,
+	pub ledger_accounts: u32,
 }
 
 impl Default for AccountsConfig {
@@ -169,6 +171,8 @@ impl Default for AccountsConfig {
 			testnet: false,
 			password_files: Vec::new(),
 			unlocked_accounts: Vec::new(),
+			ledger_device: None,
+			ledger_accounts: 1,
 		}
 	}
 }
@@ -213,6 +217,8 @@ pub struct MinerExtras {
 	pub gas_ceil_target: U256,
 	pub transactions_limit: usize,
 	pub engine_signer: Address,
+	pub private_signer: Address,
+	pub private_validators: Vec<Address>,
 }
 
 impl Default for MinerExtras {
@@ -224,6 +230,8 @@ impl Default for MinerExtras {
 			gas_ceil_target: U256::from(6_283_184),
 			transactions_limit: 1024,
 			engine_signer: Default::default(),
+			private_signer: Default::default(),
+			private_validators: Vec::new(),
 		}
 	}
 }
@@ -19,13 +19,13 @@ use std::sync::Arc;
 use std::net::SocketAddr;
 use std::io;
 use io::PanicHandler;
-use ethcore_rpc::{RpcServerError, RpcServer as Server, IpcServerError};
+use ethcore_rpc::{RpcServerError, RpcServer as Server, IpcServerError, WsServerError};
 use rpc_apis;
 use rpc_apis::ApiSet;
 use helpers::parity_ipc_path;
 use dir::default_data_path;
 
-pub use ethcore_rpc::{IpcServer, Server as HttpServer};
+pub use ethcore_rpc::{IpcServer, WsServer, Server as HttpServer};
 
 #[derive(Debug, PartialEq)]
 pub struct HttpConfiguration {
@@ -50,6 +50,27 @@ impl Default for HttpConfiguration {
 	}
 }
 
+#[derive(Debug, PartialEq)]
+pub struct WsConfiguration {
+	pub enabled: bool,
+	pub interface: String,
+	pub port: u16,
+	pub apis: ApiSet,
+	pub origins: Option<Vec<String>>,
+}
+
+impl Default for WsConfiguration {
+	fn default() -> Self {
+		WsConfiguration {
+			enabled: true,
+			interface: "127.0.0.1".into(),
+			port: 8546,
+			apis: ApiSet::UnsafeContext,
+			origins: Some(Vec::new()),
+		}
+	}
+}
+
 #[derive(Debug, PartialEq)]
 pub struct IpcConfiguration {
 	pub enabled: bool,
@@ -98,6 +119,17 @@ fn setup_rpc_server(apis: ApiSet, deps: &Dependencies) -> Result<Server, String>
 	Ok(rpc_apis::setup_rpc(server, deps.apis.clone(), apis))
 }
 
+pub fn new_light_http(conf: HttpConfiguration, deps: Arc<rpc_apis::LightDependencies>, panic_handler: Arc<PanicHandler>) -> Result<Option<HttpServer>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let url = format!("{}:{}", conf.interface, conf.port);
+	let addr = url.parse().map_err(|_| format!("Invalid JSONRPC listen host/port given: {}", url))?;
+	let server = rpc_apis::setup_light_rpc(Server::new(), deps, conf.apis);
+	Ok(Some(start_http(server, &addr, conf.cors, conf.hosts, panic_handler)?))
+}
+
 pub fn setup_http_rpc_server(
 	dependencies: &Dependencies,
 	url: &SocketAddr,
@@ -106,8 +138,17 @@ pub fn setup_http_rpc_server(
 	apis: ApiSet
 ) -> Result<HttpServer, String> {
 	let server = setup_rpc_server(apis, dependencies)?;
-	let ph = dependencies.panic_handler.clone();
-	let start_result = server.start_http(url, cors_domains, allowed_hosts, ph);
+	start_http(server, url, cors_domains, allowed_hosts, dependencies.panic_handler.clone())
+}
+
+fn start_http(
+	server: Server,
+	url: &SocketAddr,
+	cors_domains: Option<Vec<String>>,
+	allowed_hosts: Option<Vec<String>>,
+	panic_handler: Arc<PanicHandler>
+) -> Result<HttpServer, String> {
+	let start_result = server.start_http(url, cors_domains, allowed_hosts, panic_handler);
 	match start_result {
 		Err(RpcServerError::IoError(err)) => match err.kind() {
 			io::ErrorKind::AddrInUse => Err(format!("RPC address {} is already in use, make sure that another instance of an Ethereum client is not running or change the address using the --jsonrpc-port and --jsonrpc-interface options.", url)),
@@ -118,6 +159,30 @@ pub fn setup_http_rpc_server(
 	}
 }
 
+pub fn new_ws(conf: WsConfiguration, deps: &Dependencies) -> Result<Option<WsServer>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let url = format!("{}:{}", conf.interface, conf.port);
+	let addr = url.parse().map_err(|_| format!("Invalid WebSockets listen host/port given: {}", url))?;
+	Ok(Some(setup_ws_rpc_server(deps, &addr, conf.origins, conf.apis)?))
+}
+
+pub fn setup_ws_rpc_server(
+	dependencies: &Dependencies,
+	url: &SocketAddr,
+	allowed_origins: Option<Vec<String>>,
+	apis: ApiSet
+) -> Result<WsServer, String> {
+	let server = setup_rpc_server(apis, dependencies)?;
+	match server.start_ws(url, allowed_origins) {
+		Err(WsServerError::IoError(err)) => Err(format!("WebSockets io error: {}", err)),
+		Err(e) => Err(format!("WebSockets error: {:?}", e)),
+		Ok(server) => Ok(server),
+	}
+}
+
 pub fn new_ipc(conf: IpcConfiguration, deps: &Dependencies) -> Result<Option<IpcServer>, String> {
 	if !conf.enabled { return Ok(None); }
 	Ok(Some(setup_ipc_rpc_server(deps, &conf.socket_addr, conf.apis)?))
@@ -125,9 +190,18 @@ pub fn new_ipc(conf: IpcConfiguration, deps: &Dependencies) -> Result<Option<Ipc
 
 pub fn setup_ipc_rpc_server(dependencies: &Dependencies, addr: &str, apis: ApiSet) -> Result<IpcServer, String> {
 	let server = setup_rpc_server(apis, dependencies)?;
-	match server.start_ipc(addr) {
-		Err(IpcServerError::Io(io_error)) => Err(format!("RPC io error: {}", io_error)),
-		Err(any_error) => Err(format!("Rpc error: {:?}", any_error)),
-		Ok(server) => Ok(server)
+	server.start_ipc(addr).map_err(ipc_error)
+}
+
+#[cfg(not(windows))]
+fn ipc_error(io_error: IpcServerError) -> String {
+	format!("RPC io error: {}", io_error)
+}
+
+#[cfg(windows)]
+fn ipc_error(error: IpcServerError) -> String {
+	match error {
+		IpcServerError::Io(io_error) => format!("RPC io error: {}", io_error),
+		any_error => format!("Rpc error: {:?}", any_error),
 	}
 }
@@ -22,13 +22,17 @@ use std::sync::Arc;
 use util::RotatingLogger;
 use ethcore::miner::{Miner, ExternalMiner};
 use ethcore::client::Client;
+use ethcore_rpc::v1::EthPubSubClient;
 use ethcore::account_provider::AccountProvider;
 use ethcore::snapshot::SnapshotService;
-use ethsync::{ManageNetwork, SyncProvider};
+use ethcore::private_transactions::Provider as PrivateTxProvider;
+use ethsync::{ManageNetwork, SyncProvider, LightSync};
 use ethcore_rpc::{Extendable, NetworkSettings};
 pub use ethcore_rpc::SignerService;
 use updater::Updater;
 use hash_fetch::fetch::Client as FetchClient;
+use light::client::Client as LightClient;
+use light::on_demand::OnDemand;
 use parity_reactor::Remote;
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
@@ -51,8 +55,12 @@ pub enum Api {
 	ParitySet,
 	/// Traces (Safe)
 	Traces,
+	/// Geth-compatible "debug" API (Expensive: replays whole transactions and blocks)
+	Debug,
 	/// Rpc (Safe)
 	Rpc,
+	/// Private transactions (UNSAFE: Side Effects (signing and sending anchor transactions))
+	Private,
 }
 
 impl FromStr for Api {
@@ -71,7 +79,9 @@ impl FromStr for Api {
 			"parity_accounts" => Ok(ParityAccounts),
 			"parity_set" => Ok(ParitySet),
 			"traces" => Ok(Traces),
+			"debug" => Ok(Debug),
 			"rpc" => Ok(Rpc),
+			"private" => Ok(Private),
 			api => Err(format!("Unknown api: {}", api))
 		}
 	}
@@ -126,6 +136,16 @@ pub struct Dependencies {
 	pub dapps_port: Option<u16>,
 	pub fetch: FetchClient,
 	pub remote: Remote,
+	pub pubsub: Arc<EthPubSubClient<Client, SyncProvider>>,
+	pub private_tx: Option<Arc<PrivateTxProvider>>,
+}
+
+/// Dependencies of the APIs served in light client mode.
+pub struct LightDependencies {
+	pub client: Arc<LightClient>,
+	pub sync: Arc<LightSync>,
+	pub on_demand: Arc<OnDemand>,
+	pub secret_store: Arc<AccountProvider>,
 }
 
 fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
@@ -141,7 +161,9 @@ fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
 			Api::ParityAccounts => ("parity_accounts", "1.0"),
 			Api::ParitySet => ("parity_set", "1.0"),
 			Api::Traces => ("traces", "1.0"),
+			Api::Debug => ("debug", "1.0"),
 			Api::Rpc => ("rpc", "1.0"),
+			Api::Private => ("private", "1.0"),
 		};
 		modules.insert(name.into(), version.into());
 	}
@@ -157,10 +179,12 @@ impl ApiSet {
 			ApiSet::UnsafeContext => safe_list,
 			ApiSet::IpcContext => {
 				safe_list.insert(Api::ParityAccounts);
+				safe_list.insert(Api::Debug);
 				safe_list
 			},
 			ApiSet::SafeContext => {
 				safe_list.insert(Api::ParityAccounts);
+				safe_list.insert(Api::Debug);
 				safe_list.insert(Api::ParitySet);
 				safe_list.insert(Api::Signer);
 				safe_list
@@ -211,6 +235,7 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 
 				let filter_client = EthFilterClient::new(&deps.client, &deps.miner);
 				server.add_delegate(filter_client.to_delegate());
+				server.set_pubsub(deps.pubsub.clone());
 
 				add_signing_methods!(EthSigning, server, deps);
 			},
@@ -258,10 +283,39 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 			Api::Traces => {
 				server.add_delegate(TracesClient::new(&deps.client, &deps.miner).to_delegate())
 			},
+			Api::Debug => {
+				server.add_delegate(DebugClient::new(&deps.client, &deps.miner).to_delegate())
+			},
 			Api::Rpc => {
 				let modules = to_modules(&apis);
 				server.add_delegate(RpcClient::new(modules).to_delegate());
-			}
+			},
+			Api::Private => {
+				match deps.private_tx {
+					Some(ref private_tx) => server.add_delegate(PrivateClient::new(private_tx, &deps.client, &deps.miner).to_delegate()),
+					None => warn!(target: "rpc", "Private transactions API is disabled: no --private-signer or --private-validators configured."),
+				}
+			},
+		}
+	}
+	server
+}
+
+pub fn setup_light_rpc<T: Extendable>(server: T, deps: Arc<LightDependencies>, apis: ApiSet) -> T {
+	use ethcore_rpc::v1::{Web3, Eth, Web3Client};
+	use ethcore_rpc::v1::light::EthClient;
+
+	// only these APIs can be served without a full client so far.
+	for api in apis.list_apis() {
+		match api {
+			Api::Web3 => {
+				server.add_delegate(Web3Client::new().to_delegate());
+			},
+			Api::Eth => {
+				let client = EthClient::new(&deps.sync, &deps.client, &deps.on_demand, &deps.secret_store);
+				server.add_delegate(client.to_delegate());
+			},
+			api => debug!(target: "rpc", "{:?} API is not available in light client mode", api),
 		}
 	}
 	server
@@ -282,7 +336,9 @@ mod test {
 		assert_eq!(Api::ParityAccounts, "parity_accounts".parse().unwrap());
 		assert_eq!(Api::ParitySet, "parity_set".parse().unwrap());
 		assert_eq!(Api::Traces, "traces".parse().unwrap());
+		assert_eq!(Api::Debug, "debug".parse().unwrap());
 		assert_eq!(Api::Rpc, "rpc".parse().unwrap());
+		assert_eq!(Api::Private, "private".parse().unwrap());
 		assert!("rp".parse::<Api>().is_err());
 	}
 
@@ -311,7 +367,7 @@ mod test {
 			// safe
 			Api::Web3, Api::Net, Api::Eth, Api::Parity, Api::Traces, Api::Rpc,
 			// semi-safe
-			Api::ParityAccounts
+			Api::ParityAccounts, Api::Debug,
 		].into_iter().collect();
 		assert_eq!(ApiSet::IpcContext.list_apis(), expected);
 	}
@@ -322,7 +378,7 @@ mod test {
 			// safe
 			Api::Web3, Api::Net, Api::Eth, Api::Parity, Api::Traces, Api::Rpc,
 			// semi-safe
-			Api::ParityAccounts,
+			Api::ParityAccounts, Api::Debug,
 			// Unsafe
 			Api::ParitySet, Api::Signer,
 		].into_iter().collect();
@@ -14,28 +14,36 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::fs;
 use std::sync::Arc;
+use std::path::Path;
 use std::net::{TcpListener};
 use ctrlc::CtrlC;
 use fdlimit::raise_fd_limit;
 use ethcore_rpc::{NetworkSettings, is_major_importing};
+use ethcore_rpc::v1::EthPubSubClient;
 use ethsync::NetworkConfiguration;
-use util::{Colour, version, RotatingLogger, Mutex, Condvar};
+use util::{Colour, version, RotatingLogger, Mutex, Condvar, Database, DatabaseConfig};
+use util::journaldb::Algorithm;
 use io::{MayPanic, ForwardPanic, PanicHandler};
 use ethcore_logger::{Config as LogConfig};
-use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockChainClient};
+use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockChainClient, ArchiveRebuild};
 use ethcore::service::ClientService;
 use ethcore::account_provider::AccountProvider;
-use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions};
+use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions, Stratum, StratumOptions};
+use ethcore::private_transactions::Provider as PrivateTxProvider;
 use ethcore::snapshot;
+use ethcore::snapshot::io::{SnapshotReader, PackedReader};
 use ethcore::verification::queue::VerifierSettings;
+use ethcore::ids::BlockId;
 use ethsync::SyncConfig;
+use light::{ChtProvider, Provider as LightProvider};
 use informant::Informant;
 use updater::{UpdatePolicy, Updater};
 use parity_reactor::{EventLoop, EventLoopHandle};
 use hash_fetch::fetch::{Fetch, Client as FetchClient};
 
-use rpc::{HttpServer, IpcServer, HttpConfiguration, IpcConfiguration};
+use rpc::{HttpServer, IpcServer, WsServer, HttpConfiguration, IpcConfiguration, WsConfiguration};
 use signer::SignerServer;
 use dapps::WebappServer;
 use params::{
@@ -43,6 +51,7 @@ use params::{
 	tracing_switch_to_bool, fatdb_switch_to_bool, mode_switch_to_bool
 };
 use helpers::{to_client_config, execute_upgrades, passwords_from_files};
+use snapshot::restore_using;
 use upgrade::upgrade_key_location;
 use dir::Directories;
 use cache::CacheConfig;
@@ -72,13 +81,18 @@ pub struct RunCmd {
 	pub logger_config: LogConfig,
 	pub miner_options: MinerOptions,
 	pub http_conf: HttpConfiguration,
+	pub ws_conf: WsConfiguration,
 	pub ipc_conf: IpcConfiguration,
 	pub net_conf: NetworkConfiguration,
 	pub network_id: Option<u64>,
 	pub warp_sync: bool,
+	pub fast_sync: bool,
+	pub light: bool,
+	pub serve_light: bool,
 	pub acc_conf: AccountsConfig,
 	pub gas_pricer: GasPricerConfig,
 	pub miner_extras: MinerExtras,
+	pub stratum: Option<StratumOptions>,
 	pub update_policy: UpdatePolicy,
 	pub mode: Option<Mode>,
 	pub tracing: Switch,
@@ -98,6 +112,10 @@ pub struct RunCmd {
 	pub check_seal: bool,
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
+	/// First block to re-execute when rebuilding archive state in the background.
+	pub rebuild_archive_from: Option<u64>,
+	/// Snapshot to seed the archive rebuild from.
+	pub rebuild_archive_snapshot: Option<String>,
 }
 
 pub fn open_ui(dapps_conf: &dapps::Configuration, signer_conf: &signer::Configuration) -> Result<(), String> {
@@ -126,6 +144,10 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		}
 	}
 
+	if cmd.light {
+		return execute_light(cmd).map(|_| false);
+	}
+
 	// set up panic handler
 	let panic_handler = PanicHandler::new_in_arc();
 
@@ -150,6 +172,10 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	// select pruning algorithm
 	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
 
+	if cmd.rebuild_archive_from.is_some() && algorithm == Algorithm::Archive {
+		return Err("The database is already an archive; there is nothing to rebuild.".into());
+	}
+
 	// check if tracing is on
 	let tracing = tracing_switch_to_bool(cmd.tracing, &user_defaults)?;
 
@@ -167,6 +193,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	// prepare client and snapshot paths.
 	let client_path = db_dirs.client_path(algorithm);
 	let snapshot_path = db_dirs.snapshot_path();
+	let cht_path = db_dirs.cht_path();
 
 	// execute upgrades
 	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
@@ -212,7 +239,9 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	}
 	sync_config.fork_block = spec.fork_block();
 	sync_config.warp_sync = cmd.warp_sync;
+	sync_config.fast_sync = cmd.fast_sync;
 	sync_config.download_old_blocks = cmd.download_old_blocks;
+	sync_config.serve_light = cmd.serve_light;
 
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
 
@@ -229,6 +258,21 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	miner.set_gas_ceil_target(cmd.miner_extras.gas_ceil_target);
 	miner.set_extra_data(cmd.miner_extras.extra_data);
 	miner.set_transactions_limit(cmd.miner_extras.transactions_limit);
+	let private_signer = match cmd.miner_extras.private_signer {
+		signer if signer == Default::default() => None,
+		signer => {
+			if !passwords.iter().any(|p| account_provider.unlock_account_permanently(signer, p.clone()).is_ok()) {
+				return Err(format!("No password found for the private transactions signer {}. Make sure valid password is present in files passed using `--password`.", signer));
+			}
+			Some(signer)
+		},
+	};
+	let private_validators = cmd.miner_extras.private_validators;
+	for validator in &private_validators {
+		if !passwords.iter().any(|p| account_provider.unlock_account_permanently(*validator, p.clone()).is_ok()) {
+			return Err(format!("No password found for the private transactions validator {}. Make sure valid password is present in files passed using `--password`.", validator));
+		}
+	}
 	let engine_signer = cmd.miner_extras.engine_signer;
 	if engine_signer != Default::default() {
 		if !passwords.into_iter().any(|p| miner.set_engine_signer(engine_signer, p).is_ok()) {
@@ -236,7 +280,33 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		}
 	}
 
+	// open the private transactions state only if this node takes part in private transactions
+	let private_tx_provider = match private_signer.is_some() || !private_validators.is_empty() {
+		true => Some(Arc::new(PrivateTxProvider::new(
+			&db_dirs.db_root_path().join("private"),
+			spec.engine.clone(),
+			account_provider.clone(),
+			private_signer,
+		).map_err(|e| format!("Error opening private transactions database: {}", e))?)),
+		false => None,
+	};
+
 	// create client config
+	// the archive being rebuilt gets the same settings, save for pruning.
+	let archive_config = cmd.rebuild_archive_from.map(|_| to_client_config(
+		&cmd.cache_config,
+		mode.clone(),
+		tracing,
+		fat_db,
+		cmd.compaction.clone(),
+		cmd.wal,
+		cmd.vm_type.clone(),
+		cmd.name.clone(),
+		Algorithm::Archive,
+		cmd.pruning_history,
+		cmd.check_seal,
+	));
+
 	let mut client_config = to_client_config(
 		&cmd.cache_config,
 		mode.clone(),
@@ -285,9 +355,28 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let client = service.client();
 	let snapshot_service = service.snapshot_service();
 
+	// start stratum
+	if let Some(ref stratum_config) = cmd.stratum {
+		Stratum::register(stratum_config, miner.clone(), Arc::downgrade(&client))
+			.map_err(|e| format!("Stratum start error: {:?}", e))?;
+	}
+
 	// create external miner
 	let external_miner = Arc::new(ExternalMiner::default());
 
+	// create light data provider. when serving light peers, canonical hash tries
+	// are built in the background for header proofs.
+	let light_provider: Arc<LightProvider> = match sync_config.serve_light {
+		true => {
+			let cht_db = Database::open(&DatabaseConfig::default(), &cht_path.to_string_lossy())
+				.map_err(|e| format!("Error opening CHT database: {}", e))?;
+			let provider = Arc::new(ChtProvider::new(client.clone(), Arc::new(cht_db), None)?);
+			service.register_io_handler(provider.clone()).map_err(|_| "Unable to register CHT handler".to_owned())?;
+			provider as Arc<LightProvider>
+		},
+		false => client.clone() as Arc<LightProvider>,
+	};
+
 	// create sync object
 	let (sync_provider, manage_network, chain_notify) = modules::sync(
 		&mut hypervisor,
@@ -295,7 +384,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		net_conf.into(),
 		client.clone(),
 		snapshot_service.clone(),
-		client.clone(),
+		light_provider.clone(),
 		&cmd.logger_config,
 	).map_err(|e| format!("Sync error: {}", e))?;
 
@@ -322,6 +411,16 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	);
 	service.add_notify(updater.clone());
 
+	// subscriptions
+	let pubsub = Arc::new(EthPubSubClient::new(&client, &sync_provider));
+	service.add_notify(pubsub.clone());
+	let weak_pubsub = Arc::downgrade(&pubsub);
+	miner.add_transactions_listener(Box::new(move |hashes| {
+		if let Some(pubsub) = weak_pubsub.upgrade() {
+			pubsub.notify_pending_transactions(hashes);
+		}
+	}));
+
 
 	// set up dependencies for rpc servers
 	let signer_path = cmd.signer_conf.signer_path.clone();
@@ -351,6 +450,8 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		},
 		fetch: fetch.clone(),
 		remote: event_loop.remote(),
+		pubsub: pubsub.clone(),
+		private_tx: private_tx_provider.clone(),
 	});
 
 	let dependencies = rpc::Dependencies {
@@ -360,6 +461,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 
 	// start rpc servers
 	let http_server = rpc::new_http(cmd.http_conf, &dependencies)?;
+	let ws_server = rpc::new_ws(cmd.ws_conf, &dependencies)?;
 	let ipc_server = rpc::new_ipc(cmd.ipc_conf, &dependencies)?;
 
 	// the dapps server
@@ -399,12 +501,77 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	user_defaults.mode = mode;
 	user_defaults.save(&user_defaults_path)?;
 
+	let user_defaults = Arc::new(Mutex::new(user_defaults));
+
 	// tell client how to save the default mode if it gets changed.
+	let mode_defaults = (user_defaults.clone(), user_defaults_path.clone());
 	client.on_mode_change(move |mode: &Mode| {
+		let mut user_defaults = mode_defaults.0.lock();
 		user_defaults.mode = mode.clone();
-		let _ = user_defaults.save(&user_defaults_path);	// discard failures - there's nothing we can do
+		let _ = user_defaults.save(&mode_defaults.1);	// discard failures - there's nothing we can do
 	});
 
+	// rebuild archive state in the background; the rebuild must be kept alive.
+	let _archive_rebuild = match (cmd.rebuild_archive_from, archive_config) {
+		(Some(from), Some(archive_config)) => {
+			let archive_spec = cmd.spec.spec()?;
+			let archive_service = ClientService::start(
+				archive_config,
+				&archive_spec,
+				&db_dirs.client_path(Algorithm::Archive),
+				&db_dirs.db_path(Algorithm::Archive).join("snapshot"),
+				&cmd.dirs.ipc_path(),
+				Arc::new(Miner::with_spec(&archive_spec)),
+			).map_err(|e| format!("Archive client service error: {:?}", e))?;
+			drop(archive_spec);
+			panic_handler.forward_from(&archive_service);
+
+			let archive = archive_service.client();
+			if let Some(ref file) = cmd.rebuild_archive_snapshot {
+				if from > 1 && archive.chain_info().best_block_number == 0 {
+					let reader = PackedReader::new(Path::new(file))
+						.map_err(|e| format!("Couldn't open snapshot file: {}", e))
+						.and_then(|x| x.ok_or("Snapshot file has invalid format.".into()));
+					let reader = reader?;
+
+					let manifest = reader.manifest().clone();
+					if manifest.block_number + 1 != from {
+						return Err(format!("Snapshot is at block #{}, but re-execution from block #{} needs one at block #{}.",
+							manifest.block_number, from, from - 1));
+					}
+					if client.block_hash(BlockId::Number(manifest.block_number)) != Some(manifest.block_hash) {
+						return Err(format!("Snapshot block #{} is not part of the local chain.", manifest.block_number));
+					}
+
+					restore_using(archive_service.snapshot_service(), &reader, true)?;
+				}
+			}
+
+			let finish_defaults = (user_defaults.clone(), user_defaults_path.clone());
+			let pruned_path = db_dirs.client_path(algorithm);
+			// the archive service is owned by the rebuild, so it's shut down once the client
+			// has switched over, and only after the rebuild has stopped otherwise.
+			let rebuild = ArchiveRebuild::start(client.clone(), archive, from, move |_| {
+				drop(archive_service);
+
+				let mut user_defaults = finish_defaults.0.lock();
+				user_defaults.pruning = Algorithm::Archive;
+				if let Err(e) = user_defaults.save(&finish_defaults.1) {
+					warn!("Failed to save archive pruning as the default: {}", e);
+				}
+
+				// the pruned database is closed and no longer needed.
+				match fs::remove_dir_all(&pruned_path) {
+					Ok(_) => info!("Removed the {} database at {}", algorithm.as_str(), pruned_path.display()),
+					Err(e) => warn!("Failed to remove the {} database at {}: {}", algorithm.as_str(), pruned_path.display(), e),
+				}
+			})?;
+
+			Some(rebuild)
+		},
+		_ => None,
+	};
+
 	// the watcher must be kept alive.
 	let _watcher = match cmd.no_periodic_snapshot {
 		true => None,
@@ -432,6 +599,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let restart = wait_for_exit(
 		panic_handler,
 		http_server,
+		ws_server,
 		ipc_server,
 		dapps_server,
 		signer_server,
@@ -454,6 +622,96 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	Ok(restart)
 }
 
+// run as a light client, serving the web3 and eth APIs over HTTP.
+// the light client doesn't sync headers yet, so only the genesis block and headers
+// imported into its database can be queried.
+fn execute_light(cmd: RunCmd) -> Result<(), String> {
+	use ethcore::db::{NUM_COLUMNS, COL_HEADERS};
+	use ethsync::{LightSync, LightSyncParams, ManageNetwork};
+	use io::IoChannel;
+	use light::client::{Client as LightClient, Config as LightConfig};
+	use light::on_demand::OnDemand;
+
+	// set up panic handler
+	let panic_handler = PanicHandler::new_in_arc();
+
+	// increase max number of open files
+	raise_fd_limit();
+
+	// load spec
+	let spec = cmd.spec.spec()?;
+
+	// database paths
+	let db_dirs = cmd.dirs.database(spec.genesis_header().hash(), cmd.spec.legacy_fork_name(), spec.data_dir.clone());
+
+	// create dirs used by parity
+	cmd.dirs.create_dirs(false, false)?;
+
+	info!("Starting {} in light client mode", Colour::White.bold().paint(version()));
+
+	// calls are executed in the state columns, so the database needs all of a full client's.
+	let db_path = db_dirs.db_root_path().join("light");
+	let db = Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), &db_path.to_string_lossy())
+		.map_err(|e| format!("Error opening light client database: {}", e))?;
+	let config = LightConfig {
+		chain_column: COL_HEADERS,
+		..Default::default()
+	};
+	let client = Arc::new(LightClient::new(config, Arc::new(db), &spec, IoChannel::disconnected())?);
+
+	// the on-demand request service, attached to the light protocol.
+	let on_demand = Arc::new(OnDemand::default());
+
+	// set up bootnodes and network path.
+	let mut net_conf = cmd.net_conf;
+	if !cmd.custom_bootnodes {
+		net_conf.boot_nodes = spec.nodes.clone();
+	}
+	net_conf.net_config_path = Some(db_dirs.network_path().to_string_lossy().into_owned());
+
+	let sync = Arc::new(LightSync::new(LightSyncParams {
+		network_config: net_conf.into_basic().map_err(|e| format!("Network configuration error: {:?}", e))?,
+		client: client.clone(),
+		network_id: cmd.network_id.unwrap_or_else(|| spec.network_id()),
+		subprotocol_name: SyncConfig::default().light_subprotocol_name,
+		handlers: vec![on_demand.clone()],
+	}).map_err(|e| format!("Light sync error: {:?}", e))?);
+
+	// prepare account provider
+	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
+	let account_provider = Arc::new(prepare_account_provider(&cmd.dirs, &spec.data_dir, cmd.acc_conf, &passwords)?);
+
+	// drop the spec to free up genesis state.
+	drop(spec);
+
+	sync.start_network();
+
+	let deps = Arc::new(rpc_apis::LightDependencies {
+		client: client,
+		sync: sync.clone(),
+		on_demand: on_demand,
+		secret_store: account_provider,
+	});
+	let _http_server = rpc::new_light_http(cmd.http_conf, deps, panic_handler.clone())?;
+
+	// Handle exit
+	let exit = Arc::new((Mutex::new(()), Condvar::new()));
+
+	let e = exit.clone();
+	CtrlC::set_handler(move || { e.1.notify_all(); });
+
+	let e = exit.clone();
+	panic_handler.on_panic(move |_reason| { e.1.notify_all(); });
+
+	let mut l = exit.0.lock();
+	let _ = exit.1.wait(&mut l);
+
+	info!("Finishing work, please wait...");
+	sync.stop_network();
+
+	Ok(())
+}
+
 #[cfg(not(windows))]
 fn daemonize(pid_file: String) -> Result<(), String> {
 	extern crate daemonize;
@@ -474,6 +732,7 @@ fn daemonize(_pid_file: String) -> Result<(), String> {
 fn prepare_account_provider(dirs: &Directories, data_dir: &str, cfg: AccountsConfig, passwords: &[String]) -> Result<AccountProvider, String> {
 	use ethcore::ethstore::EthStore;
 	use ethcore::ethstore::dir::DiskDirectory;
+	use ethcore::account_provider::{HidTransport, LedgerSigner};
 
 	let path = dirs.keys_path(data_dir);
 	upgrade_key_location(&dirs.legacy_keys_path(cfg.testnet), &path);
@@ -488,12 +747,18 @@ fn prepare_account_provider(dirs: &Directories, data_dir: &str, cfg: AccountsCon
 		}
 	}
 
+	if let Some(ref device) = cfg.ledger_device {
+		let transport = HidTransport::open(device).map_err(|e| format!("Could not open Ledger device {}: {}", device, e))?;
+		account_service.add_external_signer(Box::new(LedgerSigner::new(transport, cfg.ledger_accounts)));
+	}
+
 	Ok(account_service)
 }
 
 fn wait_for_exit(
 	panic_handler: Arc<PanicHandler>,
 	_http_server: Option<HttpServer>,
+	_ws_server: Option<WsServer>,
 	_ipc_server: Option<IpcServer>,
 	_dapps_server: Option<WebappServer>,
 	_signer_server: Option<SignerServer>,
@@ -42,7 +42,8 @@ pub fn main() {
 			&SocketAddr::from_str(&service_config.listen_addr)
 				.unwrap_or_else(|e| panic!("Fatal: invalid listen address ({:?})", e)),
 			job_dispatcher.service().clone(),
-			service_config.secret
+			service_config.secret,
+			service_config.vardiff,
 		).unwrap_or_else(
 			|e| panic!("Fatal: cannot start stratum server({:?})", e)
 		);
@@ -52,6 +53,7 @@ pub fn main() {
 	let _ = boot::register(STRATUM_MODULE_ID);
 
 	while !stop.load(::std::sync::atomic::Ordering::Relaxed) {
+		server.maintain();
 		thread::park_timeout(std::time::Duration::from_millis(1000));
 	}
 }
@@ -32,7 +32,9 @@ extern crate ethkey;
 extern crate ethcrypto as crypto;
 extern crate ethstore;
 extern crate ethsync;
+extern crate ethcore_light as light;
 extern crate transient_hashmap;
+#[cfg(windows)]
 extern crate jsonrpc_ipc_server as ipc;
 extern crate ethcore_ipc;
 extern crate time;
@@ -42,6 +44,7 @@ extern crate futures;
 extern crate rand;
 extern crate parity_updater as updater;
 extern crate parity_reactor;
+extern crate ws;
 
 #[macro_use]
 extern crate log;
@@ -59,10 +62,18 @@ use std::sync::Arc;
 use std::net::SocketAddr;
 use io::PanicHandler;
 use jsonrpc_core::{IoHandler, IoDelegate};
+use util::Mutex;
 
+#[cfg(windows)]
 pub use ipc::{Server as IpcServer, Error as IpcServerError};
+#[cfg(not(windows))]
+pub use pubsub::ipc::Server as IpcServer;
+#[cfg(not(windows))]
+pub use std::io::Error as IpcServerError;
 pub use jsonrpc_http_server::{ServerBuilder, Server, RpcServerError};
+pub use pubsub::ws::{Server as WsServer, ServerError as WsServerError};
 pub mod v1;
+pub mod pubsub;
 pub use v1::{SigningQueue, SignerService, ConfirmationsQueue, NetworkSettings};
 pub use v1::block_import::is_major_importing;
 
@@ -70,11 +81,16 @@ pub use v1::block_import::is_major_importing;
 pub trait Extendable {
 	/// Add `Delegate` to this object.
 	fn add_delegate<D: Send + Sync + 'static>(&self, delegate: IoDelegate<D>);
+
+	/// Set pub-sub implementation handling subscriptions.
+	/// Ignored by objects that can't push notifications.
+	fn set_pubsub(&self, _pubsub: Arc<v1::EthPubSub>) {}
 }
 
 /// Http server.
 pub struct RpcServer {
 	handler: Arc<IoHandler>,
+	pubsub: Mutex<Option<Arc<v1::EthPubSub>>>,
 }
 
 impl Extendable for RpcServer {
@@ -82,6 +98,10 @@ impl Extendable for RpcServer {
 	fn add_delegate<D: Send + Sync + 'static>(&self, delegate: IoDelegate<D>) {
 		self.handler.add_delegate(delegate);
 	}
+
+	fn set_pubsub(&self, pubsub: Arc<v1::EthPubSub>) {
+		*self.pubsub.lock() = Some(pubsub);
+	}
 }
 
 impl RpcServer {
@@ -89,9 +109,14 @@ impl RpcServer {
 	pub fn new() -> RpcServer {
 		RpcServer {
 			handler: Arc::new(IoHandler::new()),
+			pubsub: Mutex::new(None),
 		}
 	}
 
+	fn pubsub_handler(&self) -> Arc<pubsub::PubSubHandler> {
+		Arc::new(pubsub::PubSubHandler::new(self.handler.clone(), self.pubsub.lock().clone()))
+	}
+
 	/// Start http server asynchronously and returns result with `Server` handle on success or an error.
 	pub fn start_http(
 		&self,
@@ -120,7 +145,21 @@ impl RpcServer {
 			.start_http(addr)
 	}
 
+	/// Start `WebSockets` server asynchronously and returns result with `Server` handle on success or an error.
+	pub fn start_ws(&self, addr: &SocketAddr, allowed_origins: Option<Vec<String>>) -> Result<WsServer, WsServerError> {
+		WsServer::start(addr, self.pubsub_handler(), allowed_origins)
+	}
+
+	/// Start ipc server asynchronously and returns result with `Server` handle on success or an error.
+	#[cfg(not(windows))]
+	pub fn start_ipc(&self, addr: &str) -> Result<IpcServer, IpcServerError> {
+		IpcServer::start(addr, self.pubsub_handler())
+	}
+
 	/// Start ipc server asynchronously and returns result with `Server` handle on success or an error.
+	///
+	/// Subscriptions are not supported over named pipes.
+	#[cfg(windows)]
 	pub fn start_ipc(&self, addr: &str) -> Result<ipc::Server, ipc::Error> {
 		let server = ipc::Server::new(addr, &self.handler)?;
 		server.run_async()?;
@@ -0,0 +1,262 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JSON-RPC over Unix domain sockets with subscriptions support.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::ops::Drop;
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use v1::Session;
+use super::PubSubHandler;
+
+/// Maximal size of a single request; connections sending larger ones are dropped.
+const MAX_REQUEST_SIZE: usize = 5 * 1024 * 1024;
+/// Maximal number of connections served at the same time.
+const MAX_CONNECTIONS: usize = 64;
+/// Maximal number of messages waiting to be written to a connection;
+/// clients which fall further behind are disconnected.
+const MAX_PENDING_MESSAGES: usize = 1024;
+
+/// IPC JSON-RPC server.
+pub struct Server {
+	path: PathBuf,
+	running: Arc<AtomicBool>,
+	handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Server {
+	/// Starts listening on given socket path in a separate thread.
+	/// Returns a `Server` handle which closes the server when dropped.
+	pub fn start(path: &str, handler: Arc<PubSubHandler>) -> io::Result<Server> {
+		let path = PathBuf::from(path);
+		// Remove socket file left behind by an instance that wasn't shut down cleanly.
+		if path.exists() {
+			fs::remove_file(&path)?;
+		}
+
+		let listener = UnixListener::bind(&path)?;
+		let running = Arc::new(AtomicBool::new(true));
+		let is_running = running.clone();
+		let connections = Arc::new(AtomicUsize::new(0));
+		let handle = thread::Builder::new().name("ipc-server".into()).spawn(move || {
+			for stream in listener.incoming() {
+				if !is_running.load(Ordering::SeqCst) {
+					break;
+				}
+
+				match stream {
+					Ok(_) if connections.load(Ordering::SeqCst) >= MAX_CONNECTIONS => {
+						warn!(target: "rpc", "IPC: Too many connections, dropping a new one.");
+					},
+					Ok(stream) => {
+						let handler = handler.clone();
+						let active = connections.clone();
+						connections.fetch_add(1, Ordering::SeqCst);
+						let spawned = thread::Builder::new().name("ipc-connection".into()).spawn(move || {
+							handle_connection(stream, handler);
+							active.fetch_sub(1, Ordering::SeqCst);
+						});
+						if let Err(e) = spawned {
+							connections.fetch_sub(1, Ordering::SeqCst);
+							warn!(target: "rpc", "IPC: Unable to spawn connection thread: {:?}", e);
+						}
+					},
+					Err(e) => warn!(target: "rpc", "IPC: Error accepting connection: {:?}", e),
+				}
+			}
+		})?;
+
+		Ok(Server {
+			path: path,
+			running: running,
+			handle: Some(handle),
+		})
+	}
+}
+
+impl Drop for Server {
+	fn drop(&mut self) {
+		self.running.store(false, Ordering::SeqCst);
+		// Wake up the listener thread so that it notices the flag.
+		let _ = UnixStream::connect(&self.path);
+		self.handle.take().expect("Handle is always set until the server is dropped; qed").join().unwrap();
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+fn handle_connection(stream: UnixStream, handler: Arc<PubSubHandler>) {
+	let (mut writer, closer) = match (stream.try_clone(), stream.try_clone()) {
+		(Ok(writer), Ok(closer)) => (writer, closer),
+		(Err(e), _) | (_, Err(e)) => {
+			warn!(target: "rpc", "IPC: Unable to set up connection: {:?}", e);
+			return;
+		},
+	};
+
+	// Responses and notifications are written by a separate thread,
+	// so that notifying a slow client never blocks the notifier.
+	let (tx, rx) = mpsc::sync_channel::<String>(MAX_PENDING_MESSAGES);
+	thread::spawn(move || {
+		for message in rx {
+			if writer.write_all(message.as_bytes()).and_then(|_| writer.write_all(b"\n")).is_err() {
+				break;
+			}
+		}
+	});
+
+	let session = Arc::new(Session::new(move |message| match tx.try_send(message) {
+		Ok(()) => true,
+		Err(mpsc::TrySendError::Full(_)) => {
+			warn!(target: "rpc", "IPC: Client doesn't keep up with {} pending messages, closing the connection.", MAX_PENDING_MESSAGES);
+			let _ = closer.shutdown(Shutdown::Both);
+			false
+		},
+		Err(mpsc::TrySendError::Disconnected(_)) => false,
+	}));
+	let mut reader = stream;
+	let mut splitter = RequestSplitter::new(MAX_REQUEST_SIZE);
+	let mut buf = [0u8; 4096];
+	loop {
+		let read = match reader.read(&mut buf) {
+			Ok(0) | Err(_) => break,
+			Ok(read) => read,
+		};
+
+		let requests = match splitter.push(&buf[..read]) {
+			Ok(requests) => requests,
+			Err(RequestTooLarge) => {
+				warn!(target: "rpc", "IPC: Request exceeds {} bytes, closing the connection.", MAX_REQUEST_SIZE);
+				break;
+			},
+		};
+
+		for request in requests {
+			let request = match String::from_utf8(request) {
+				Ok(request) => request,
+				Err(_) => {
+					warn!(target: "rpc", "IPC: Received a request which is not valid UTF-8.");
+					continue;
+				},
+			};
+
+			let session_ref = session.clone();
+			handler.handle_request(&session, &request, move |response| {
+				if let Some(response) = response {
+					session_ref.send(response);
+				}
+			});
+		}
+	}
+}
+
+/// A request didn't fit into the splitter buffer.
+#[derive(Debug, PartialEq)]
+struct RequestTooLarge;
+
+/// Splits a stream of bytes into separate JSON values.
+struct RequestSplitter {
+	buffer: Vec<u8>,
+	limit: usize,
+	depth: usize,
+	in_string: bool,
+	escaped: bool,
+}
+
+impl RequestSplitter {
+	/// Creates a splitter accepting requests of at most `limit` bytes.
+	fn new(limit: usize) -> Self {
+		RequestSplitter {
+			buffer: Vec::new(),
+			limit: limit,
+			depth: 0,
+			in_string: false,
+			escaped: false,
+		}
+	}
+
+	/// Consume given bytes and return all requests completed by them.
+	fn push(&mut self, bytes: &[u8]) -> Result<Vec<Vec<u8>>, RequestTooLarge> {
+		let mut requests = Vec::new();
+		for &byte in bytes {
+			// skip whitespace between requests
+			if self.depth == 0 && byte != b'{' && byte != b'[' {
+				continue;
+			}
+
+			if self.buffer.len() >= self.limit {
+				return Err(RequestTooLarge);
+			}
+			self.buffer.push(byte);
+			if self.in_string {
+				match byte {
+					_ if self.escaped => self.escaped = false,
+					b'\\' => self.escaped = true,
+					b'"' => self.in_string = false,
+					_ => {},
+				}
+				continue;
+			}
+
+			match byte {
+				b'"' => self.in_string = true,
+				b'{' | b'[' => self.depth += 1,
+				b'}' | b']' => {
+					self.depth -= 1;
+					if self.depth == 0 {
+						requests.push(mem::replace(&mut self.buffer, Vec::new()));
+					}
+				},
+				_ => {},
+			}
+		}
+		Ok(requests)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{RequestSplitter, RequestTooLarge};
+
+	#[test]
+	fn should_split_requests() {
+		let mut splitter = RequestSplitter::new(1024);
+
+		let requests = splitter.push(br#"{"method":"eth_subscribe","params":["newHeads"]} [{"a":"}\"{"#);
+		assert_eq!(requests, Ok(vec![br#"{"method":"eth_subscribe","params":["newHeads"]}"#.to_vec()]));
+
+		let requests = splitter.push(br#"}]{"id""#);
+		assert_eq!(requests, Ok(vec![br#"[{"a":"}\"{"}]"#.to_vec()]));
+
+		let requests = splitter.push(br#":1}"#);
+		assert_eq!(requests, Ok(vec![br#"{"id":1}"#.to_vec()]));
+	}
+
+	#[test]
+	fn should_reject_oversized_requests() {
+		let mut splitter = RequestSplitter::new(8);
+
+		assert_eq!(splitter.push(br#"{"id":1}"#), Ok(vec![br#"{"id":1}"#.to_vec()]));
+		assert_eq!(splitter.push(br#"{"id":10}"#), Err(RequestTooLarge));
+	}
+}
@@ -0,0 +1,135 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pub-Sub transports.
+//!
+//! Regular JSON-RPC transports are request-response only. Transports in this module
+//! keep a `Session` per connection, so that subscriptions can push notifications back.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use jsonrpc_core::{IoHandler, GenericIoHandler, Error, ErrorCode, Value};
+use serde_json;
+use v1::{EthPubSub, Session};
+use v1::types::{PubSubKind, PubSubParams};
+
+pub mod ws;
+#[cfg(not(windows))]
+pub mod ipc;
+
+/// JSON-RPC request handler aware of pub-sub sessions.
+///
+/// Routes `eth_subscribe` and `eth_unsubscribe` calls to the pub-sub implementation
+/// and all other requests to the regular `IoHandler`.
+pub struct PubSubHandler {
+	handler: Arc<IoHandler>,
+	pubsub: Option<Arc<EthPubSub>>,
+}
+
+impl PubSubHandler {
+	/// Creates new handler. Subscriptions are rejected as unknown methods if `pubsub` is `None`.
+	pub fn new(handler: Arc<IoHandler>, pubsub: Option<Arc<EthPubSub>>) -> Self {
+		PubSubHandler {
+			handler: handler,
+			pubsub: pubsub,
+		}
+	}
+
+	/// Handle a request received within given session.
+	pub fn handle_request<F>(&self, session: &Arc<Session>, request: &str, on_response: F) where
+		F: Fn(Option<String>) + Send + 'static,
+	{
+		match self.handle_pubsub(session, request) {
+			Some(response) => on_response(Some(response)),
+			None => self.handler.handle_request(request, on_response),
+		}
+	}
+
+	fn handle_pubsub(&self, session: &Arc<Session>, request: &str) -> Option<String> {
+		let pubsub = match self.pubsub {
+			Some(ref pubsub) => pubsub,
+			None => return None,
+		};
+
+		// Batches and malformed requests are left to the regular handler.
+		let call = match serde_json::from_str::<Value>(request) {
+			Ok(Value::Object(call)) => call,
+			_ => return None,
+		};
+
+		let is_subscribe = match call.get("method") {
+			Some(&Value::String(ref method)) if method == "eth_subscribe" => true,
+			Some(&Value::String(ref method)) if method == "eth_unsubscribe" => false,
+			_ => return None,
+		};
+
+		let id = call.get("id").cloned().unwrap_or(Value::Null);
+		let params = match call.get("params") {
+			Some(&Value::Array(ref params)) => params.clone(),
+			None => Vec::new(),
+			Some(_) => return Some(response(id, Err(invalid_params("Expected an array of parameters.")))),
+		};
+
+		let result = match is_subscribe {
+			true => subscribe(&**pubsub, session, params).map(Value::String),
+			false => unsubscribe(&**pubsub, session, params).map(Value::Bool),
+		};
+		Some(response(id, result))
+	}
+}
+
+fn subscribe(pubsub: &EthPubSub, session: &Arc<Session>, params: Vec<Value>) -> Result<String, Error> {
+	let mut params = params.into_iter();
+	let kind: PubSubKind = match params.next() {
+		Some(kind) => serde_json::from_value(kind).map_err(|e| invalid_params(&format!("Invalid subscription kind: {}", e)))?,
+		None => return Err(invalid_params("Expected subscription kind.")),
+	};
+	let params = match params.next() {
+		Some(Value::Null) | None => PubSubParams::None,
+		Some(filter) => PubSubParams::Logs(
+			serde_json::from_value(filter).map_err(|e| invalid_params(&format!("Invalid filter: {}", e)))?
+		),
+	};
+
+	pubsub.subscribe(session, kind, params)
+}
+
+fn unsubscribe(pubsub: &EthPubSub, session: &Arc<Session>, params: Vec<Value>) -> Result<bool, Error> {
+	match params.into_iter().next() {
+		Some(Value::String(id)) => pubsub.unsubscribe(session, id),
+		_ => Err(invalid_params("Expected subscription id.")),
+	}
+}
+
+fn invalid_params(details: &str) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: "Couldn't parse parameters.".into(),
+		data: Some(Value::String(details.into())),
+	}
+}
+
+fn response(id: Value, result: Result<Value, Error>) -> String {
+	let mut response = BTreeMap::new();
+	response.insert("jsonrpc".to_owned(), Value::String("2.0".into()));
+	match result {
+		Ok(result) => response.insert("result".to_owned(), result),
+		Err(error) => response.insert("error".to_owned(), serde_json::to_value(&error)),
+	};
+	response.insert("id".to_owned(), id);
+
+	serde_json::to_string(&Value::Object(response)).expect("Serialization of a Value cannot fail; qed")
+}
@@ -0,0 +1,186 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JSON-RPC over `WebSockets` with subscriptions support.
+
+use std;
+use std::thread;
+use std::ops::Drop;
+use std::sync::Arc;
+use std::net::SocketAddr;
+use io::{PanicHandler, OnPanicListener, MayPanic};
+use ws;
+use v1::Session;
+use super::PubSubHandler;
+
+/// `WebSockets` server startup error
+#[derive(Debug)]
+pub enum ServerError {
+	/// Wrapped `std::io::Error`
+	IoError(std::io::Error),
+	/// Other `ws-rs` error
+	WebSocket(ws::Error)
+}
+
+impl From<ws::Error> for ServerError {
+	fn from(err: ws::Error) -> Self {
+		match err.kind {
+			ws::ErrorKind::Io(e) => ServerError::IoError(e),
+			_ => ServerError::WebSocket(err),
+		}
+	}
+}
+
+struct Connection {
+	session: Arc<Session>,
+	handler: Arc<PubSubHandler>,
+	allowed_origins: Option<Vec<String>>,
+}
+
+impl ws::Handler for Connection {
+	fn on_request(&mut self, req: &ws::Request) -> ws::Result<ws::Response> {
+		if let Some(ref allowed) = self.allowed_origins {
+			let origin = req.header("origin").or_else(|| req.header("Origin"));
+			let is_valid = match origin {
+				// Non-browser clients don't send the header.
+				None => true,
+				Some(origin) => allowed.iter().any(|allowed| allowed.as_bytes() == &origin[..]),
+			};
+
+			if !is_valid {
+				warn!(target: "rpc", "Blocked WebSockets connection from untrusted origin.");
+				return Ok(ws::Response::forbidden("Origin not allowed.".into()));
+			}
+		}
+
+		ws::Response::from_request(req)
+	}
+
+	fn on_message(&mut self, msg: ws::Message) -> ws::Result<()> {
+		let req = msg.as_text()?;
+		let session = self.session.clone();
+		self.handler.handle_request(&self.session, req, move |response| {
+			if let Some(result) = response {
+				if !session.send(result) {
+					warn!(target: "rpc", "Error while sending WebSockets response.");
+				}
+			}
+		});
+		Ok(())
+	}
+}
+
+struct Factory {
+	handler: Arc<PubSubHandler>,
+	allowed_origins: Option<Vec<String>>,
+}
+
+impl ws::Factory for Factory {
+	type Handler = Connection;
+
+	fn connection_made(&mut self, sender: ws::Sender) -> Self::Handler {
+		Connection {
+			session: Arc::new(Session::new(move |msg| sender.send(msg).is_ok())),
+			handler: self.handler.clone(),
+			allowed_origins: self.allowed_origins.clone(),
+		}
+	}
+}
+
+/// `WebSockets` JSON-RPC server.
+pub struct Server {
+	handle: Option<thread::JoinHandle<ws::WebSocket<Factory>>>,
+	broadcaster: ws::Sender,
+	panic_handler: Arc<PanicHandler>,
+	addr: SocketAddr,
+}
+
+impl Server {
+	/// Starts a new `WebSockets` server in separate thread.
+	/// Returns a `Server` handle which closes the server when dropped.
+	///
+	/// Connections with `Origin` header not present in `allowed_origins` are rejected,
+	/// `None` allows all origins.
+	pub fn start(addr: &SocketAddr, handler: Arc<PubSubHandler>, allowed_origins: Option<Vec<String>>) -> Result<Server, ServerError> {
+		let config = {
+			let mut config = ws::Settings::default();
+			// accept only handshakes beginning with GET
+			config.method_strict = true;
+			// Was shutting down server when suspending on linux:
+			config.shutdown_on_interrupt = false;
+			config
+		};
+
+		let ws = ws::Builder::new().with_settings(config).build(Factory {
+			handler: handler,
+			allowed_origins: allowed_origins,
+		})?;
+
+		let panic_handler = PanicHandler::new_in_arc();
+		let ph = panic_handler.clone();
+		let broadcaster = ws.broadcaster();
+		let addr = *addr;
+
+		// Spawn a thread with event loop
+		let handle = thread::spawn(move || {
+			ph.catch_panic(move || {
+				match ws.listen(addr).map_err(ServerError::from) {
+					Err(ServerError::IoError(io)) => die(format!(
+						"WebSockets: Could not start listening on specified address. Make sure that no other instance is running or change the address using the --ws-port and --ws-interface options. Details: {:?}",
+						io
+					)),
+					Err(any_error) => die(format!(
+						"WebSockets: Unknown error occurred when starting the server. Details: {:?}",
+						any_error
+					)),
+					Ok(server) => server,
+				}
+			}).unwrap()
+		});
+
+		Ok(Server {
+			handle: Some(handle),
+			broadcaster: broadcaster,
+			panic_handler: panic_handler,
+			addr: addr,
+		})
+	}
+
+	/// Returns the address this server is listening on
+	pub fn addr(&self) -> &SocketAddr {
+		&self.addr
+	}
+}
+
+impl MayPanic for Server {
+	fn on_panic<F>(&self, closure: F) where F: OnPanicListener {
+		self.panic_handler.on_panic(closure);
+	}
+}
+
+impl Drop for Server {
+	fn drop(&mut self) {
+		if let Err(e) = self.broadcaster.shutdown() {
+			warn!(target: "rpc", "WebSockets server was not closed cleanly. Details: {:?}", e);
+		}
+		self.handle.take().expect("Handle is always set until the server is dropped; qed").join().unwrap();
+	}
+}
+
+fn die(msg: String) -> ! {
+	println!("ERROR: {}", msg);
+	std::process::exit(1);
+}
@@ -48,6 +48,7 @@ mod codes {
 	pub const COMPILATION_ERROR: i64 = -32050;
 	pub const ENCRYPTION_ERROR: i64 = -32055;
 	pub const FETCH_ERROR: i64 = -32060;
+	pub const PRIVATE_TRANSACTION_ERROR: i64 = -32070;
 }
 
 pub fn unimplemented(details: Option<String>) -> Error {
@@ -90,6 +91,14 @@ pub fn account<T: fmt::Debug>(error: &str, details: T) -> Error {
 	}
 }
 
+pub fn private_transaction<T: fmt::Debug>(error: T) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::PRIVATE_TRANSACTION_ERROR),
+		message: "Private transaction error.".into(),
+		data: Some(Value::String(format!("{:?}", error))),
+	}
+}
+
 pub fn compilation<T: fmt::Debug>(error: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::COMPILATION_ERROR),
@@ -291,6 +300,22 @@ pub fn from_call_error(error: CallError) -> Error {
 	}
 }
 
+pub fn no_light_peers() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "No light peers who can serve data".into(),
+		data: None,
+	}
+}
+
+pub fn request_timeout() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Timed out waiting for light peers to serve data".into(),
+		data: None,
+	}
+}
+
 pub fn unknown_block() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
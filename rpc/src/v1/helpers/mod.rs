@@ -26,6 +26,8 @@ mod requests;
 mod signer;
 mod signing_queue;
 mod network_settings;
+mod session;
+mod subscribers;
 
 pub use self::poll_manager::PollManager;
 pub use self::poll_filter::{PollFilter, limit_logs};
@@ -33,3 +35,5 @@ pub use self::requests::{TransactionRequest, FilledTransactionRequest, Confirmat
 pub use self::signing_queue::{ConfirmationsQueue, ConfirmationPromise, ConfirmationResult, SigningQueue, QueueEvent};
 pub use self::signer::SignerService;
 pub use self::network_settings::NetworkSettings;
+pub use self::session::Session;
+pub use self::subscribers::{Subscribers, Id as SubscriptionId};
@@ -0,0 +1,47 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pub-Sub session.
+
+use util::Mutex;
+
+/// A connected pub-sub client.
+///
+/// Notifications are pushed through the sink given by the transport.
+/// Subscriptions hold only weak references to sessions, so dropping the
+/// session when the connection closes also ends all its subscriptions.
+pub struct Session {
+	sink: Mutex<Box<FnMut(String) -> bool + Send>>,
+}
+
+impl Session {
+	/// Creates a new session using given sink.
+	///
+	/// The sink should return `false` if the message couldn't be delivered.
+	pub fn new<F>(sink: F) -> Self where
+		F: FnMut(String) -> bool + Send + 'static,
+	{
+		Session {
+			sink: Mutex::new(Box::new(sink)),
+		}
+	}
+
+	/// Sends a message to the client. Returns `false` if the message couldn't be delivered.
+	pub fn send(&self, message: String) -> bool {
+		let mut sink = self.sink.lock();
+		(&mut **sink)(message)
+	}
+}
@@ -0,0 +1,175 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A map of subscribers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Weak};
+use rand::{Rng, StdRng};
+use serde::Serialize;
+use serde_json;
+use jsonrpc_core::Value;
+use v1::helpers::Session;
+
+/// Subscription identifier.
+pub type Id = String;
+
+struct Subscriber<T> {
+	session: Weak<Session>,
+	data: T,
+}
+
+/// Keeps track of subscriptions together with sessions they belong to.
+pub struct Subscribers<T> {
+	rand: StdRng,
+	subscriptions: HashMap<Id, Subscriber<T>>,
+}
+
+impl<T> Default for Subscribers<T> {
+	fn default() -> Self {
+		Subscribers {
+			rand: StdRng::new().expect("Not able to operate without random source."),
+			subscriptions: HashMap::new(),
+		}
+	}
+}
+
+impl<T> Subscribers<T> {
+	fn next_id(&mut self) -> Id {
+		loop {
+			let id = format!("0x{:016x}", self.rand.gen::<u64>());
+			if !self.subscriptions.contains_key(&id) {
+				return id;
+			}
+		}
+	}
+
+	/// Insert new subscription and return assigned id.
+	pub fn push(&mut self, session: &Arc<Session>, data: T) -> Id {
+		self.prune();
+		let id = self.next_id();
+		self.subscriptions.insert(id.clone(), Subscriber {
+			session: Arc::downgrade(session),
+			data: data,
+		});
+		id
+	}
+
+	/// Removes subscription with given id if it belongs to `session`.
+	/// Returns `true` if it was removed.
+	pub fn remove(&mut self, id: &Id, session: &Arc<Session>) -> bool {
+		let owned = self.subscriptions.get(id)
+			.and_then(|subscriber| subscriber.session.upgrade())
+			.map_or(false, |owner| &*owner as *const Session == &**session as *const Session);
+		owned && self.subscriptions.remove(id).is_some()
+	}
+
+	/// Returns `true` if there are no subscribers.
+	pub fn is_empty(&self) -> bool {
+		self.subscriptions.is_empty()
+	}
+
+	/// Returns number of subscribers.
+	pub fn len(&self) -> usize {
+		self.subscriptions.len()
+	}
+
+	/// Sends a notification to every subscriber for which `f` produced a result.
+	///
+	/// Subscriptions of sessions that are no longer alive are removed.
+	pub fn notify<F, R>(&mut self, mut f: F) where
+		F: FnMut(&T) -> Vec<R>,
+		R: Serialize,
+	{
+		let mut closed = Vec::new();
+		for (id, subscriber) in &self.subscriptions {
+			let session = match subscriber.session.upgrade() {
+				Some(session) => session,
+				None => {
+					closed.push(id.clone());
+					continue;
+				},
+			};
+
+			for result in f(&subscriber.data) {
+				if !session.send(notification(id, &result)) {
+					closed.push(id.clone());
+					break;
+				}
+			}
+		}
+
+		for id in closed {
+			self.subscriptions.remove(&id);
+		}
+	}
+
+	fn prune(&mut self) {
+		let closed = self.subscriptions.iter()
+			.filter(|&(_, subscriber)| subscriber.session.upgrade().is_none())
+			.map(|(id, _)| id.clone())
+			.collect::<Vec<_>>();
+
+		for id in closed {
+			self.subscriptions.remove(&id);
+		}
+	}
+}
+
+/// Serializes a subscription notification.
+pub fn notification<R: Serialize>(id: &Id, result: &R) -> String {
+	let mut params = BTreeMap::new();
+	params.insert("subscription".to_owned(), Value::String(id.clone()));
+	params.insert("result".to_owned(), serde_json::to_value(result));
+
+	let mut message = BTreeMap::new();
+	message.insert("jsonrpc".to_owned(), Value::String("2.0".into()));
+	message.insert("method".to_owned(), Value::String("eth_subscription".into()));
+	message.insert("params".to_owned(), Value::Object(params));
+
+	serde_json::to_string(&Value::Object(message)).expect("Serialization of a Value cannot fail; qed")
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::mpsc;
+	use v1::helpers::Session;
+	use super::Subscribers;
+
+	#[test]
+	fn should_notify_subscribers_and_prune_closed_sessions() {
+		// given
+		let (tx, rx) = mpsc::channel();
+		let session = Arc::new(Session::new(move |msg| tx.send(msg).is_ok()));
+		let closed = Arc::new(Session::new(|_| true));
+		let mut subscribers = Subscribers::default();
+		let id = subscribers.push(&session, 5u64);
+		subscribers.push(&closed, 6u64);
+		drop(closed);
+
+		// when
+		subscribers.notify(|data| vec![*data]);
+
+		// then
+		assert_eq!(rx.try_recv().unwrap(), format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":5,"subscription":"{}"}}}}"#, id));
+		assert!(rx.try_recv().is_err());
+		assert_eq!(subscribers.len(), 1);
+		assert!(!subscribers.remove(&id, &Arc::new(Session::new(|_| true))), "Should not remove subscription of another session.");
+		assert!(subscribers.remove(&id, &session));
+		assert!(subscribers.is_empty());
+	}
+}
@@ -0,0 +1,111 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Debug rpc implementation.
+
+use std::sync::{Weak, Arc};
+
+use ethcore::client::{BlockChainClient, CallAnalytics, TransactionId, BlockId};
+use ethcore::error::CallError;
+use ethcore::miner::MinerService;
+use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
+
+use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
+use v1::traits::Debug;
+use v1::helpers::{errors, CallRequest as CRequest};
+use v1::types::{BlockNumber, CallRequest, DebugTrace, H256, TraceOptions};
+
+fn to_call_analytics(options: TraceOptions) -> CallAnalytics {
+	CallAnalytics {
+		transaction_tracing: false,
+		vm_tracing: false,
+		state_diffing: false,
+		struct_logging: Some(options.into()),
+	}
+}
+
+/// Debug rpc implementation.
+pub struct DebugClient<C, M> where C: BlockChainClient, M: MinerService {
+	client: Weak<C>,
+	miner: Weak<M>,
+}
+
+impl<C, M> DebugClient<C, M> where C: BlockChainClient, M: MinerService {
+	/// Creates new Debug client.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+		DebugClient {
+			client: Arc::downgrade(client),
+			miner: Arc::downgrade(miner),
+		}
+	}
+
+	// TODO: share with eth.rs
+	fn sign_call(&self, request: CRequest) -> Result<SignedTransaction, Error> {
+		let client = take_weak!(self.client);
+		let miner = take_weak!(self.miner);
+		let from = request.from.unwrap_or(0.into());
+		Ok(EthTransaction {
+			nonce: request.nonce.unwrap_or_else(|| client.latest_nonce(&from)),
+			action: request.to.map_or(Action::Create, Action::Call),
+			gas: request.gas.unwrap_or(50_000_000.into()),
+			gas_price: request.gas_price.unwrap_or_else(|| miner.sensible_gas_price()),
+			value: request.value.unwrap_or(0.into()),
+			data: request.data.map_or_else(Vec::new, |d| d.to_vec())
+		}.fake_sign(from))
+	}
+
+	fn active(&self) -> Result<(), Error> {
+		// TODO: only call every 30s at most.
+		take_weak!(self.client).keep_alive();
+		Ok(())
+	}
+}
+
+impl<C, M> Debug for DebugClient<C, M> where C: BlockChainClient + 'static, M: MinerService + 'static {
+	fn trace_transaction(&self, transaction_hash: H256, options: Trailing<TraceOptions>) -> Result<Option<DebugTrace>, Error> {
+		self.active()?;
+
+		match take_weak!(self.client).replay(TransactionId::Hash(transaction_hash.into()), to_call_analytics(options.0)) {
+			Ok(e) => Ok(Some(DebugTrace::from(e))),
+			Err(CallError::TransactionNotFound) => Ok(None),
+			Err(e) => Err(errors::from_call_error(e)),
+		}
+	}
+
+	fn trace_call(&self, request: CallRequest, block: BlockNumber, options: Trailing<TraceOptions>) -> Result<DebugTrace, Error> {
+		self.active()?;
+
+		let request = CallRequest::into(request);
+		let signed = self.sign_call(request)?;
+		take_weak!(self.client).call(&signed, block.into(), to_call_analytics(options.0))
+			.map(DebugTrace::from)
+			.map_err(errors::from_call_error)
+	}
+
+	fn trace_block_by_number(&self, block: BlockNumber, options: Trailing<TraceOptions>) -> Result<Vec<DebugTrace>, Error> {
+		self.active()?;
+
+		let client = take_weak!(self.client);
+		let id: BlockId = block.into();
+		client.block_header(id).ok_or_else(errors::unknown_block)?;
+
+		client.replay_block_transactions(id, to_call_analytics(options.0))
+			.map(|executed| executed.into_iter().map(DebugTrace::from).collect())
+			.map_err(errors::from_call_error)
+	}
+}
@@ -0,0 +1,244 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Eth PUB-SUB rpc implementation.
+
+use std::sync::{Arc, Weak};
+
+use jsonrpc_core::*;
+use ethsync::SyncProvider;
+use ethcore::client::{BlockChainClient, BlockId, ChainNotify};
+use ethcore::filter::Filter as EthFilter;
+use ethcore::log_entry::LocalizedLogEntry;
+use ethcore::receipt::Receipt;
+use util::{Bytes, H256, Mutex};
+use rlp;
+
+use v1::helpers::{errors, Session, Subscribers, SubscriptionId};
+use v1::helpers::block_import::is_major_importing;
+use v1::traits::EthPubSub;
+use v1::types::{PubSubKind, PubSubParams, PubSubResult, Log, SyncStatus, SyncInfo, Filter};
+
+/// Eth PubSub implementation.
+pub struct EthPubSubClient<C, S: ?Sized> where
+	C: BlockChainClient,
+	S: SyncProvider,
+{
+	client: Weak<C>,
+	sync: Weak<S>,
+	heads_subscribers: Mutex<Subscribers<()>>,
+	logs_subscribers: Mutex<Subscribers<EthFilter>>,
+	transactions_subscribers: Mutex<Subscribers<()>>,
+	sync_subscribers: Mutex<Subscribers<()>>,
+	is_syncing: Mutex<bool>,
+}
+
+impl<C, S: ?Sized> EthPubSubClient<C, S> where
+	C: BlockChainClient,
+	S: SyncProvider,
+{
+	/// Creates new `EthPubSubClient`.
+	pub fn new(client: &Arc<C>, sync: &Arc<S>) -> Self {
+		EthPubSubClient {
+			client: Arc::downgrade(client),
+			sync: Arc::downgrade(sync),
+			heads_subscribers: Default::default(),
+			logs_subscribers: Default::default(),
+			transactions_subscribers: Default::default(),
+			sync_subscribers: Default::default(),
+			is_syncing: Mutex::new(false),
+		}
+	}
+
+	/// Notify all `newPendingTransactions` subscribers about transactions imported to the queue.
+	pub fn notify_pending_transactions(&self, hashes: &[H256]) {
+		let mut subscribers = self.transactions_subscribers.lock();
+		if subscribers.is_empty() {
+			return;
+		}
+
+		let results = hashes.iter()
+			.map(|hash| PubSubResult::TransactionHash((*hash).into()))
+			.collect::<Vec<_>>();
+		subscribers.notify(|_| results.iter().collect());
+	}
+
+	fn notify_heads(&self, client: &C, enacted: &[H256]) {
+		let mut subscribers = self.heads_subscribers.lock();
+		if subscribers.is_empty() {
+			return;
+		}
+
+		let results = enacted.iter()
+			.filter_map(|hash| client.block_header(BlockId::Hash(*hash)))
+			.map(|header| PubSubResult::Header(header.decode().into()))
+			.collect::<Vec<_>>();
+		subscribers.notify(|_| results.iter().collect());
+	}
+
+	fn notify_logs(&self, client: &C, enacted: &[H256], retracted: &[H256]) {
+		let mut subscribers = self.logs_subscribers.lock();
+		if subscribers.is_empty() {
+			return;
+		}
+
+		// logs from retracted blocks go first, so that subscribers can revert them
+		// before applying logs from the new canonical chain.
+		let logs = retracted.iter()
+			.flat_map(|hash| block_logs(client, hash).into_iter().map(|log| (log, true)))
+			.chain(enacted.iter().flat_map(|hash| block_logs(client, hash).into_iter().map(|log| (log, false))))
+			.collect::<Vec<_>>();
+
+		if logs.is_empty() {
+			return;
+		}
+
+		subscribers.notify(|filter| logs.iter()
+			.filter(|&&(ref log, _)| filter.matches(&log.entry))
+			.map(|&(ref log, removed)| {
+				let mut log: Log = log.clone().into();
+				log.removed = removed;
+				PubSubResult::Log(log)
+			})
+			.collect()
+		);
+	}
+
+	fn notify_syncing(&self, client: &C, sync: &S) {
+		let mut subscribers = self.sync_subscribers.lock();
+		let status = sync_status(client, sync);
+		let is_syncing = status != SyncStatus::None;
+
+		{
+			let mut was_syncing = self.is_syncing.lock();
+			if *was_syncing == is_syncing {
+				return;
+			}
+			*was_syncing = is_syncing;
+		}
+
+		let result = PubSubResult::SyncState(status);
+		subscribers.notify(|_| vec![&result]);
+	}
+}
+
+fn sync_status<C: BlockChainClient, S: SyncProvider + ?Sized>(client: &C, sync: &S) -> SyncStatus {
+	let status = sync.status();
+	if !is_major_importing(Some(status.state), client.queue_info()) {
+		return SyncStatus::None;
+	}
+
+	let current_block = client.chain_info().best_block_number;
+	let highest_block = status.highest_block_number.unwrap_or(status.start_block_number);
+	SyncStatus::Info(SyncInfo {
+		starting_block: status.start_block_number.into(),
+		current_block: current_block.into(),
+		highest_block: highest_block.into(),
+		warp_chunks_amount: None,
+		warp_chunks_processed: None,
+	})
+}
+
+/// Returns all logs of given block, regardless of whether it's in the canonical chain.
+fn block_logs<C: BlockChainClient>(client: &C, hash: &H256) -> Vec<LocalizedLogEntry> {
+	let block = match client.block(BlockId::Hash(*hash)) {
+		Some(block) => block,
+		None => return Vec::new(),
+	};
+	let receipts: Vec<Receipt> = match client.block_receipts(hash) {
+		Some(receipts) => rlp::decode(&receipts),
+		None => return Vec::new(),
+	};
+
+	let block_number = block.number();
+	let mut logs = Vec::new();
+	let mut log_index = 0;
+	for (transaction_index, (transaction_hash, receipt)) in block.transaction_hashes().into_iter().zip(receipts).enumerate() {
+		for (transaction_log_index, entry) in receipt.logs.into_iter().enumerate() {
+			logs.push(LocalizedLogEntry {
+				entry: entry,
+				block_hash: *hash,
+				block_number: block_number,
+				transaction_hash: transaction_hash,
+				transaction_index: transaction_index,
+				log_index: log_index,
+				transaction_log_index: transaction_log_index,
+			});
+			log_index += 1;
+		}
+	}
+	logs
+}
+
+impl<C, S: ?Sized> ChainNotify for EthPubSubClient<C, S> where
+	C: BlockChainClient + 'static,
+	S: SyncProvider + 'static,
+{
+	fn new_blocks(
+		&self,
+		_imported: Vec<H256>,
+		_invalid: Vec<H256>,
+		enacted: Vec<H256>,
+		retracted: Vec<H256>,
+		_sealed: Vec<H256>,
+		_proposed: Vec<Bytes>,
+		_duration: u64,
+	) {
+		let (client, sync) = match (self.client.upgrade(), self.sync.upgrade()) {
+			(Some(client), Some(sync)) => (client, sync),
+			_ => return,
+		};
+
+		self.notify_heads(&*client, &enacted);
+		self.notify_logs(&*client, &enacted, &retracted);
+		self.notify_syncing(&*client, &*sync);
+	}
+}
+
+impl<C, S: ?Sized> EthPubSub for EthPubSubClient<C, S> where
+	C: BlockChainClient + 'static,
+	S: SyncProvider + 'static,
+{
+	fn subscribe(&self, session: &Arc<Session>, kind: PubSubKind, params: PubSubParams) -> Result<SubscriptionId, Error> {
+		match (kind, params) {
+			(PubSubKind::NewHeads, PubSubParams::None) => {
+				Ok(self.heads_subscribers.lock().push(session, ()))
+			},
+			(PubSubKind::Logs, PubSubParams::Logs(filter)) => {
+				Ok(self.logs_subscribers.lock().push(session, filter.into()))
+			},
+			(PubSubKind::NewPendingTransactions, PubSubParams::None) => {
+				Ok(self.transactions_subscribers.lock().push(session, ()))
+			},
+			(PubSubKind::Syncing, PubSubParams::None) => {
+				Ok(self.sync_subscribers.lock().push(session, ()))
+			},
+			(PubSubKind::Logs, PubSubParams::None) => {
+				Ok(self.logs_subscribers.lock().push(session, Filter::default().into()))
+			},
+			(_, PubSubParams::Logs(_)) => Err(errors::invalid_params("params", "Expected no parameters.")),
+		}
+	}
+
+	fn unsubscribe(&self, session: &Arc<Session>, id: SubscriptionId) -> Result<bool, Error> {
+		let res = self.heads_subscribers.lock().remove(&id, session)
+			|| self.logs_subscribers.lock().remove(&id, session)
+			|| self.transactions_subscribers.lock().remove(&id, session)
+			|| self.sync_subscribers.lock().remove(&id, session);
+
+		Ok(res)
+	}
+}
@@ -0,0 +1,577 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Eth RPC interface for the light client.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::Duration;
+
+use jsonrpc_core::*;
+use jsonrpc_macros::Trailing;
+
+use light::client::Client as LightClient;
+use light::cht;
+use light::on_demand::{request, OnDemand, BasicAccount, Response};
+
+use ethcore::account_provider::AccountProvider;
+use ethcore::client::{Executed, PartialState, PartialAccount, PartialCall};
+use ethcore::encoded;
+use ethcore::header::BlockNumber as EthBlockNumber;
+use ethcore::ids::BlockId;
+use ethcore::receipt::Receipt as EthReceipt;
+use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
+use ethsync::LightNetworkDispatcher;
+use futures::{self, Future};
+use rlp::{self, UntrustedRlp, View};
+use util::{Address, H256, U256, Mutex};
+
+use v1::helpers::{CallRequest as CRequest, errors};
+use v1::traits::Eth;
+use v1::types::{
+	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, Transaction, CallRequest, Index,
+	Filter, Log, Receipt, Work, DappId,
+	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
+};
+
+// how long to wait for a peer to answer a request.
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+// how many times a call may be executed before all the state it touches is known.
+// every round fetches at least one new item.
+const MAX_CALL_ROUNDS: usize = 64;
+
+// how many of the latest blocks are searched for a transaction's receipt.
+const RECEIPT_SEARCH_BLOCKS: u64 = 64;
+
+// wait for a response, failing if no peer answers in time.
+fn wait_response<T>(response: Response<T>) -> Result<T, Error> {
+	let (timeout, timed_out) = futures::oneshot();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+		timeout.complete(());
+	});
+
+	match response.map(Some).select(timed_out.map(|_| None)).wait() {
+		Ok((Some(item), _)) => Ok(item),
+		Ok((None, _)) => Err(errors::request_timeout()),
+		Err(_) => Err(errors::no_light_peers()),
+	}
+}
+
+/// Light client `ETH` RPC.
+pub struct EthClient<S: LightNetworkDispatcher + 'static> {
+	sync: Weak<S>,
+	client: Weak<LightClient>,
+	on_demand: Weak<OnDemand>,
+	accounts: Weak<AccountProvider>,
+	// transactions sent through this client and the best block when they were sent.
+	sent: Mutex<HashMap<H256, EthBlockNumber>>,
+}
+
+impl<S: LightNetworkDispatcher + 'static> EthClient<S> {
+	/// Create a new `EthClient` with a handle to the light sync instance, client,
+	/// and on-demand request service, which is assumed to be attached as a handler.
+	pub fn new(sync: &Arc<S>, client: &Arc<LightClient>, on_demand: &Arc<OnDemand>, accounts: &Arc<AccountProvider>) -> Self {
+		EthClient {
+			sync: Arc::downgrade(sync),
+			client: Arc::downgrade(client),
+			on_demand: Arc::downgrade(on_demand),
+			accounts: Arc::downgrade(accounts),
+			sent: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Get a block header from the on demand service or client, or error.
+	fn header(&self, id: BlockId) -> Result<Option<encoded::Header>, Error> {
+		let client = take_weak!(self.client);
+		if let Some(h) = client.block_header(id) {
+			return Ok(Some(h));
+		}
+
+		// headers which have been pruned into a CHT must be fetched with a proof.
+		let num = match id {
+			BlockId::Number(num) => num,
+			_ => return Ok(None),
+		};
+
+		self.header_proof(num).map(|proved| proved.map(|(header, _)| header))
+	}
+
+	/// Fetch a header covered by a CHT along with its total difficulty.
+	fn header_proof(&self, num: EthBlockNumber) -> Result<Option<(encoded::Header, U256)>, Error> {
+		let client = take_weak!(self.client);
+		let cht_root = match cht::block_to_cht_number(num).and_then(|cn| client.cht_root(cn as usize)) {
+			Some(root) => root,
+			None => return Ok(None),
+		};
+
+		let (sync, on_demand) = (take_weak!(self.sync), take_weak!(self.on_demand));
+		let req = request::HeaderByNumber {
+			num: num,
+			cht_root: cht_root,
+		};
+
+		match sync.with_context(|ctx| on_demand.header_by_number(ctx, req)) {
+			Some(response) => wait_response(response).map(Some),
+			None => Err(errors::network_disabled()),
+		}
+	}
+
+	/// Get the total difficulty of a known header.
+	fn total_difficulty(&self, header: &encoded::Header) -> Result<U256, Error> {
+		if let Some(td) = take_weak!(self.client).total_difficulty(&header.hash()) {
+			return Ok(td);
+		}
+
+		// headers pruned into a CHT have their total difficulty proven with them.
+		match self.header_proof(header.number())? {
+			Some((ref proved, td)) if proved.hash() == header.hash() => Ok(td),
+			_ => Err(errors::unknown_block()),
+		}
+	}
+
+	/// Get a header, failing with `unknown_block` if it can't be found.
+	fn known_header(&self, id: BlockId) -> Result<encoded::Header, Error> {
+		self.header(id)?.ok_or_else(errors::unknown_block)
+	}
+
+	/// Helper for getting account info at a given block.
+	fn account(&self, address: Address, header: encoded::Header) -> Result<Option<BasicAccount>, Error> {
+		let (sync, on_demand) = (take_weak!(self.sync), take_weak!(self.on_demand));
+		let req = request::Account {
+			header: header,
+			address: address,
+		};
+
+		match sync.with_context(|ctx| on_demand.account(ctx, req)) {
+			Some(response) => wait_response(response),
+			None => Err(errors::network_disabled()),
+		}
+	}
+
+	/// Helper for getting a full block, if the header is known.
+	fn block(&self, id: BlockId) -> Result<Option<encoded::Block>, Error> {
+		let header = match self.header(id)? {
+			Some(header) => header,
+			None => return Ok(None),
+		};
+
+		let (sync, on_demand) = (take_weak!(self.sync), take_weak!(self.on_demand));
+		match sync.with_context(|ctx| on_demand.block(ctx, request::Body::new(header))) {
+			Some(response) => wait_response(response).map(Some),
+			None => Err(errors::network_disabled()),
+		}
+	}
+
+	/// Helper for getting the receipts of a block with a known header.
+	fn receipts(&self, header: encoded::Header) -> Result<Vec<EthReceipt>, Error> {
+		let (sync, on_demand) = (take_weak!(self.sync), take_weak!(self.on_demand));
+		match sync.with_context(|ctx| on_demand.block_receipts(ctx, request::BlockReceipts(header))) {
+			Some(response) => wait_response(response),
+			None => Err(errors::network_disabled()),
+		}
+	}
+
+	/// Build a rich block out of a full block.
+	fn rich_block(&self, block: encoded::Block, include_txs: bool) -> Result<RichBlock, Error> {
+		let client = take_weak!(self.client);
+		let header = encoded::Header::new(block.rlp().at(0).as_raw().to_vec());
+		let total_difficulty = self.total_difficulty(&header)?;
+		let view = block.header_view();
+
+		Ok(RichBlock {
+			block: Block {
+				hash: Some(view.sha3().into()),
+				size: Some(block.rlp().as_raw().len().into()),
+				parent_hash: view.parent_hash().into(),
+				uncles_hash: view.uncles_hash().into(),
+				author: view.author().into(),
+				miner: view.author().into(),
+				state_root: view.state_root().into(),
+				transactions_root: view.transactions_root().into(),
+				receipts_root: view.receipts_root().into(),
+				number: Some(view.number().into()),
+				gas_used: view.gas_used().into(),
+				gas_limit: view.gas_limit().into(),
+				logs_bloom: view.log_bloom().into(),
+				timestamp: view.timestamp().into(),
+				difficulty: view.difficulty().into(),
+				total_difficulty: total_difficulty.into(),
+				seal_fields: view.seal().into_iter().map(|f| rlp::decode(&f)).map(Bytes::new).collect(),
+				uncles: block.uncle_hashes().into_iter().map(Into::into).collect(),
+				transactions: match include_txs {
+					true => BlockTransactions::Full(block.view().localized_transactions().into_iter().map(Into::into).collect()),
+					false => BlockTransactions::Hashes(block.transaction_hashes().into_iter().map(Into::into).collect()),
+				},
+				extra_data: Bytes::new(view.extra_data()),
+			},
+			extra_info: client.engine().extra_info(&header.decode()),
+		})
+	}
+
+	/// Helper for getting the code of an account with a known code hash.
+	fn code(&self, address: Address, code_hash: H256, header: &encoded::Header) -> Result<Vec<u8>, Error> {
+		let (sync, on_demand) = (take_weak!(self.sync), take_weak!(self.on_demand));
+		let req = request::Code {
+			block_id: (header.hash(), header.number()),
+			address: address,
+			code_hash: code_hash,
+		};
+
+		match sync.with_context(|ctx| on_demand.code(ctx, req)) {
+			Some(response) => wait_response(response),
+			None => Err(errors::network_disabled()),
+		}
+	}
+
+	/// Fetch an account and its code into a partial state, noting its storage root.
+	fn fetch_account(&self, address: Address, header: &encoded::Header, state: &mut PartialState, storage_roots: &mut HashMap<Address, H256>) -> Result<(), Error> {
+		let account = match self.account(address, header.clone())? {
+			Some(acc) => {
+				let code = self.code(address, acc.code_hash, header)?;
+				storage_roots.insert(address, acc.storage_root);
+				Some(PartialAccount {
+					nonce: acc.nonce,
+					balance: acc.balance,
+					code: code,
+				})
+			}
+			None => None,
+		};
+
+		state.insert_account(address, account);
+		Ok(())
+	}
+
+	/// Helper for getting an item of an account's storage with a known storage root.
+	fn storage(&self, address: Address, key: H256, storage_root: H256, header: &encoded::Header) -> Result<H256, Error> {
+		let (sync, on_demand) = (take_weak!(self.sync), take_weak!(self.on_demand));
+		let req = request::Storage {
+			block_id: (header.hash(), header.number()),
+			address: address,
+			storage_root: storage_root,
+			key: key,
+		};
+
+		match sync.with_context(|ctx| on_demand.storage(ctx, req)) {
+			Some(response) => wait_response(response),
+			None => Err(errors::network_disabled()),
+		}
+	}
+
+	/// Fetch an item of a known account's storage into a partial state.
+	fn fetch_storage(&self, address: Address, key: H256, header: &encoded::Header, state: &mut PartialState, storage_roots: &HashMap<Address, H256>) -> Result<(), Error> {
+		let storage_root = *storage_roots.get(&address)
+			.expect("storage is only reported missing for fetched accounts which exist; qed");
+
+		let value = self.storage(address, key, storage_root, header)?;
+		state.insert_storage(address, key, value);
+		Ok(())
+	}
+
+	/// Find a transaction in a block and build its receipt.
+	fn receipt_in(&self, hash: &H256, header: encoded::Header) -> Result<Option<Receipt>, Error> {
+		let block = match self.block(BlockId::Hash(header.hash()))? {
+			Some(block) => block,
+			None => return Ok(None),
+		};
+
+		let index = match block.transaction_hashes().into_iter().position(|h| &h == hash) {
+			Some(index) => index,
+			None => return Ok(None),
+		};
+
+		let mut receipts = self.receipts(header)?;
+		receipts.truncate(index + 1);
+
+		let tx = block.view().localized_transaction_at(index)
+			.expect("index is the position of a transaction in this block; qed");
+		Ok(Some(::ethcore::client::transaction_receipt(tx, receipts).into()))
+	}
+
+	/// Execute a call at the given block, fetching the state it touches from the network.
+	fn execute(&self, req: CRequest, num: BlockNumber) -> Result<Executed, Error> {
+		let client = take_weak!(self.client);
+		let header = self.known_header(to_block_id(num))?;
+		let from = req.from.unwrap_or(Address::zero());
+
+		let mut state = PartialState::new();
+		let mut storage_roots = HashMap::new();
+		self.fetch_account(from, &header, &mut state, &mut storage_roots)?;
+
+		let nonce = match (req.nonce, state.account(&from)) {
+			(Some(nonce), _) => nonce,
+			(None, Some(Some(acc))) => acc.nonce,
+			(None, _) => client.engine().account_start_nonce(),
+		};
+
+		let tx = EthTransaction {
+			nonce: nonce,
+			action: req.to.map_or(Action::Create, Action::Call),
+			gas: req.gas.unwrap_or(U256::from(50_000_000)),
+			gas_price: req.gas_price.unwrap_or_else(U256::zero),
+			value: req.value.unwrap_or_else(U256::zero),
+			data: req.data.unwrap_or_else(Vec::new),
+		}.fake_sign(from);
+
+		for _ in 0..MAX_CALL_ROUNDS {
+			match client.call(&header, &state, &tx).map_err(errors::from_call_error)? {
+				PartialCall::Executed(executed) => return Ok(executed),
+				PartialCall::Missing { accounts, storage } => {
+					for address in accounts {
+						self.fetch_account(address, &header, &mut state, &mut storage_roots)?;
+					}
+					for (address, key) in storage {
+						self.fetch_storage(address, key, &header, &mut state, &storage_roots)?;
+					}
+				}
+			}
+		}
+
+		Err(errors::internal("Call touches too much state to fetch.", MAX_CALL_ROUNDS))
+	}
+}
+
+// light clients have no pending block, so treat it as the latest one.
+fn to_block_id(num: BlockNumber) -> BlockId {
+	match num {
+		BlockNumber::Pending => BlockId::Latest,
+		num => num.into(),
+	}
+}
+
+impl<S: LightNetworkDispatcher + 'static> Eth for EthClient<S> {
+	fn protocol_version(&self) -> Result<String, Error> {
+		Ok(format!("{}", ::light::net::MAX_PROTOCOL_VERSION))
+	}
+
+	fn syncing(&self) -> Result<SyncStatus, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn author(&self) -> Result<RpcH160, Error> {
+		Ok(Default::default())
+	}
+
+	fn is_mining(&self) -> Result<bool, Error> {
+		Ok(false)
+	}
+
+	fn hashrate(&self) -> Result<RpcU256, Error> {
+		Ok(Default::default())
+	}
+
+	fn gas_price(&self) -> Result<RpcU256, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn accounts(&self, id: Trailing<DappId>) -> Result<Vec<RpcH160>, Error> {
+		let dapp = id.0;
+
+		let store = take_weak!(self.accounts);
+		let accounts = store
+			.note_dapp_used(dapp.clone().into())
+			.and_then(|_| store.dapps_addresses(dapp.into()))
+			.map_err(|e| errors::internal("Could not fetch accounts.", e))?;
+
+		Ok(accounts.into_iter().map(Into::into).collect())
+	}
+
+	fn block_number(&self) -> Result<RpcU256, Error> {
+		Ok(take_weak!(self.client).chain_info().best_block_number.into())
+	}
+
+	fn balance(&self, address: RpcH160, num: Trailing<BlockNumber>) -> Result<RpcU256, Error> {
+		let header = self.known_header(to_block_id(num.0))?;
+		self.account(address.into(), header)
+			.map(|acc| acc.map_or(U256::zero(), |a| a.balance).into())
+	}
+
+	fn storage_at(&self, address: RpcH160, key: RpcU256, num: Trailing<BlockNumber>) -> Result<RpcH256, Error> {
+		let address: Address = address.into();
+		let key: U256 = key.into();
+		let header = self.known_header(to_block_id(num.0))?;
+		match self.account(address, header.clone())? {
+			Some(acc) => self.storage(address, H256::from(key), acc.storage_root, &header).map(Into::into),
+			None => Ok(RpcH256::default()),
+		}
+	}
+
+	fn block_by_hash(&self, hash: RpcH256, include_txs: bool) -> Result<Option<RichBlock>, Error> {
+		match self.block(BlockId::Hash(hash.into()))? {
+			Some(block) => self.rich_block(block, include_txs).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	fn block_by_number(&self, num: BlockNumber, include_txs: bool) -> Result<Option<RichBlock>, Error> {
+		match self.block(to_block_id(num))? {
+			Some(block) => self.rich_block(block, include_txs).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	fn transaction_count(&self, address: RpcH160, num: Trailing<BlockNumber>) -> Result<RpcU256, Error> {
+		let header = self.known_header(to_block_id(num.0))?;
+		self.account(address.into(), header)
+			.map(|acc| acc.map_or(U256::zero(), |a| a.nonce).into())
+	}
+
+	fn block_transaction_count_by_hash(&self, hash: RpcH256) -> Result<Option<RpcU256>, Error> {
+		self.block(BlockId::Hash(hash.into()))
+			.map(|block| block.map(|block| block.transactions_count().into()))
+	}
+
+	fn block_transaction_count_by_number(&self, num: BlockNumber) -> Result<Option<RpcU256>, Error> {
+		self.block(to_block_id(num))
+			.map(|block| block.map(|block| block.transactions_count().into()))
+	}
+
+	fn block_uncles_count_by_hash(&self, hash: RpcH256) -> Result<Option<RpcU256>, Error> {
+		self.block(BlockId::Hash(hash.into()))
+			.map(|block| block.map(|block| block.uncles_count().into()))
+	}
+
+	fn block_uncles_count_by_number(&self, num: BlockNumber) -> Result<Option<RpcU256>, Error> {
+		self.block(to_block_id(num))
+			.map(|block| block.map(|block| block.uncles_count().into()))
+	}
+
+	fn code_at(&self, address: RpcH160, num: Trailing<BlockNumber>) -> Result<Bytes, Error> {
+		let address: Address = address.into();
+		let header = self.known_header(to_block_id(num.0))?;
+		match self.account(address, header.clone())? {
+			Some(acc) => self.code(address, acc.code_hash, &header).map(Bytes::new),
+			None => Ok(Bytes::default()),
+		}
+	}
+
+	fn send_raw_transaction(&self, raw: Bytes) -> Result<RpcH256, Error> {
+		let raw = raw.to_vec();
+		let tx: SignedTransaction = UntrustedRlp::new(&raw).as_val().map_err(errors::from_rlp_error)?;
+		tx.sender().map_err(|e| errors::invalid_params("transaction", e))?;
+
+		let client = take_weak!(self.client);
+		let hash = tx.hash();
+		let sent = match take_weak!(self.sync).with_context(|ctx| ctx.send_transactions(&[tx.clone()])) {
+			Some(sent) => sent,
+			None => return Err(errors::network_disabled()),
+		};
+
+		if sent == 0 {
+			return Err(errors::no_light_peers());
+		}
+
+		client.import_own_transaction(tx);
+		self.sent.lock().insert(hash, client.chain_info().best_block_number);
+		Ok(hash.into())
+	}
+
+	fn submit_transaction(&self, raw: Bytes) -> Result<RpcH256, Error> {
+		self.send_raw_transaction(raw)
+	}
+
+	fn call(&self, req: CallRequest, num: Trailing<BlockNumber>) -> Result<Bytes, Error> {
+		self.execute(req.into(), num.0).map(|executed| executed.output.into())
+	}
+
+	fn estimate_gas(&self, req: CallRequest, num: Trailing<BlockNumber>) -> Result<RpcU256, Error> {
+		self.execute(req.into(), num.0).map(|executed| (executed.gas_used + executed.refunded).into())
+	}
+
+	fn transaction_by_hash(&self, _hash: RpcH256) -> Result<Option<Transaction>, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn transaction_by_block_hash_and_index(&self, _hash: RpcH256, _idx: Index) -> Result<Option<Transaction>, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn transaction_by_block_number_and_index(&self, _num: BlockNumber, _idx: Index) -> Result<Option<Transaction>, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn transaction_receipt(&self, hash: RpcH256) -> Result<Option<Receipt>, Error> {
+		let hash: H256 = hash.into();
+		let best = take_weak!(self.client).chain_info().best_block_number;
+
+		// transactions sent through this client can't be in blocks before they were sent.
+		let oldest = best.saturating_sub(RECEIPT_SEARCH_BLOCKS);
+		let oldest = match self.sent.lock().get(&hash) {
+			Some(&sent_at) if sent_at > oldest => sent_at,
+			_ => oldest,
+		};
+
+		for num in oldest..(best + 1) {
+			let header = match self.header(BlockId::Number(num))? {
+				Some(header) => header,
+				None => continue,
+			};
+
+			// receipts aren't requested for blocks with no transactions.
+			if header.transactions_root() == ::util::sha3::SHA3_NULL_RLP { continue }
+
+			if let Some(receipt) = self.receipt_in(&hash, header)? {
+				self.sent.lock().remove(&hash);
+				return Ok(Some(receipt));
+			}
+		}
+
+		Ok(None)
+	}
+
+	fn uncle_by_block_hash_and_index(&self, _hash: RpcH256, _idx: Index) -> Result<Option<RichBlock>, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn uncle_by_block_number_and_index(&self, _num: BlockNumber, _idx: Index) -> Result<Option<RichBlock>, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn compilers(&self) -> Result<Vec<String>, Error> {
+		Ok(Vec::new())
+	}
+
+	fn compile_lll(&self, _: String) -> Result<Bytes, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn compile_serpent(&self, _: String) -> Result<Bytes, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn compile_solidity(&self, _: String) -> Result<Bytes, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn logs(&self, _filter: Filter) -> Result<Vec<Log>, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn work(&self, _timeout: Trailing<u64>) -> Result<Work, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn submit_work(&self, _nonce: RpcH64, _pow_hash: RpcH256, _mix_hash: RpcH256) -> Result<bool, Error> {
+		rpc_unimplemented!()
+	}
+
+	fn submit_hashrate(&self, _rate: RpcU256, _id: RpcH256) -> Result<bool, Error> {
+		rpc_unimplemented!()
+	}
+}
@@ -25,13 +25,16 @@ macro_rules! take_weak {
 	}
 }
 
+mod debug;
 mod eth;
 mod eth_filter;
+mod eth_pubsub;
 mod net;
 mod parity;
 mod parity_accounts;
 mod parity_set;
 mod personal;
+mod private;
 mod signer;
 mod signing;
 mod signing_unsafe;
@@ -39,14 +42,19 @@ mod rpc;
 mod traces;
 mod web3;
 
+pub mod light;
+
 pub use self::web3::Web3Client;
+pub use self::debug::DebugClient;
 pub use self::eth::{EthClient, EthClientOptions};
 pub use self::eth_filter::EthFilterClient;
+pub use self::eth_pubsub::EthPubSubClient;
 pub use self::net::NetClient;
 pub use self::parity::ParityClient;
 pub use self::parity_accounts::ParityAccountsClient;
 pub use self::parity_set::ParitySetClient;
 pub use self::personal::PersonalClient;
+pub use self::private::PrivateClient;
 pub use self::signer::SignerClient;
 pub use self::signing::SigningQueueClient;
 pub use self::signing_unsafe::SigningUnsafeClient;
@@ -37,10 +37,11 @@ use jsonrpc_macros::Trailing;
 use v1::traits::Parity;
 use v1::types::{
 	Bytes, U256, H160, H256, H512,
-	Peers, Transaction, RpcSettings, Histogram,
+	Peers, BannedPeer, Transaction, RpcSettings, Histogram,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, DappId, ChainStatus,
+	EquivocationProof,
 };
 use v1::helpers::{errors, SigningQueue, SignerService, NetworkSettings};
 use v1::helpers::dispatch::DEFAULT_MAC;
@@ -207,6 +208,12 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		})
 	}
 
+	fn banned_peers(&self) -> Result<Vec<BannedPeer>, Error> {
+		self.active()?;
+
+		Ok(take_weak!(self.net).banned_peers().into_iter().map(Into::into).collect())
+	}
+
 	fn net_port(&self) -> Result<u16, Error> {
 		self.active()?;
 
@@ -409,4 +416,10 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 			block_gap: gap.map(|(x, y)| (x.into(), y.into())),
 		})
 	}
+
+	fn consensus_evidence(&self) -> Result<Vec<EquivocationProof>, Error> {
+		self.active()?;
+
+		Ok(take_weak!(self.client).equivocation_proofs().into_iter().map(Into::into).collect())
+	}
 }
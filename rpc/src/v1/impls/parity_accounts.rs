@@ -78,6 +78,25 @@ impl<C: 'static> ParityAccounts for ParityAccountsClient<C> where C: MiningBlock
 			.map_err(|e| errors::account("Could not create account.", e))
 	}
 
+	fn new_account_from_mnemonic(&self, phrase: String, pass: String) -> Result<RpcH160, Error> {
+		self.active()?;
+		let store = take_weak!(self.accounts);
+
+		store.new_account_from_mnemonic(&phrase, &pass)
+			.map(Into::into)
+			.map_err(|e| errors::account("Could not create account.", e))
+	}
+
+	fn derive_address(&self, account: RpcH160, pass: String) -> Result<RpcH160, Error> {
+		self.active()?;
+		let account: Address = account.into();
+
+		take_weak!(self.accounts)
+			.derive_account(&account, &pass)
+			.map(Into::into)
+			.map_err(|e| errors::account("Could not derive account.", e))
+	}
+
 	fn new_account_from_wallet(&self, json: String, pass: String) -> Result<RpcH160, Error> {
 		self.active()?;
 		let store = take_weak!(self.accounts);
@@ -216,6 +235,68 @@ impl<C: 'static> ParityAccounts for ParityAccountsClient<C> where C: MiningBlock
 
 		Ok(into_vec(store.list_geth_accounts(false)))
 	}
+
+	fn create_vault(&self, name: String, password: String) -> Result<bool, Error> {
+		self.active()?;
+
+		take_weak!(self.accounts)
+			.create_vault(&name, &password)
+			.map_err(|e| errors::account("Could not create vault.", e))
+			.map(|_| true)
+	}
+
+	fn open_vault(&self, name: String, password: String) -> Result<bool, Error> {
+		self.active()?;
+
+		take_weak!(self.accounts)
+			.open_vault(&name, &password)
+			.map_err(|e| errors::account("Could not open vault.", e))
+			.map(|_| true)
+	}
+
+	fn close_vault(&self, name: String) -> Result<bool, Error> {
+		self.active()?;
+
+		take_weak!(self.accounts)
+			.close_vault(&name)
+			.map_err(|e| errors::account("Could not close vault.", e))
+			.map(|_| true)
+	}
+
+	fn change_vault_password(&self, name: String, new_password: String) -> Result<bool, Error> {
+		self.active()?;
+
+		take_weak!(self.accounts)
+			.change_vault_password(&name, &new_password)
+			.map_err(|e| errors::account("Could not change vault password.", e))
+			.map(|_| true)
+	}
+
+	fn list_vaults(&self) -> Result<Vec<String>, Error> {
+		self.active()?;
+
+		take_weak!(self.accounts)
+			.list_vaults()
+			.map_err(|e| errors::account("Could not list vaults.", e))
+	}
+
+	fn list_opened_vaults(&self) -> Result<Vec<String>, Error> {
+		self.active()?;
+
+		take_weak!(self.accounts)
+			.list_opened_vaults()
+			.map_err(|e| errors::account("Could not list opened vaults.", e))
+	}
+
+	fn change_vault(&self, address: RpcH160, vault: String) -> Result<bool, Error> {
+		self.active()?;
+		let vault = if vault.is_empty() { None } else { Some(vault) };
+
+		take_weak!(self.accounts)
+			.change_vault(address.into(), vault)
+			.map_err(|e| errors::account("Could not change vault.", e))
+			.map(|_| true)
+	}
 }
 
 fn into_vec<A, B>(a: Vec<A>) -> Vec<B> where
@@ -168,6 +168,28 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn unban_peer(&self, peer: String) -> Result<bool, Error> {
+		self.active()?;
+
+		take_weak!(self.net).unban_peer(peer).map_err(|e| errors::invalid_params("Peer id", e))
+	}
+
+	fn clear_banned_peers(&self) -> Result<bool, Error> {
+		self.active()?;
+
+		take_weak!(self.net).clear_banned_peers();
+		Ok(true)
+	}
+
+	fn set_ip_filter(&self, allow: String, deny: String) -> Result<bool, Error> {
+		self.active()?;
+
+		match take_weak!(self.net).set_ip_filter(allow, deny) {
+			Ok(()) => Ok(true),
+			Err(e) => Err(errors::invalid_params("IP filter", e)),
+		}
+	}
+
 	fn start_network(&self) -> Result<bool, Error> {
 		take_weak!(self.net).start_network();
 		Ok(true)
@@ -0,0 +1,121 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions rpc implementation.
+
+use std::sync::{Weak, Arc};
+
+use rlp::{UntrustedRlp, View, encode};
+use ethcore::client::MiningBlockChainClient;
+use ethcore::miner::MinerService;
+use ethcore::private_transactions::Provider;
+use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
+
+use jsonrpc_core::Error;
+use v1::traits::Private;
+use v1::helpers::{errors, CallRequest as CRequest};
+use v1::types::{Bytes, CallRequest, PrivateTransactionReceipt, H160, H256, H512};
+
+/// Private transactions rpc implementation.
+pub struct PrivateClient<C, M> where C: MiningBlockChainClient, M: MinerService {
+	provider: Weak<Provider>,
+	client: Weak<C>,
+	miner: Weak<M>,
+}
+
+impl<C, M> PrivateClient<C, M> where C: MiningBlockChainClient, M: MinerService {
+	/// Creates new Private client.
+	pub fn new(provider: &Arc<Provider>, client: &Arc<C>, miner: &Arc<M>) -> Self {
+		PrivateClient {
+			provider: Arc::downgrade(provider),
+			client: Arc::downgrade(client),
+			miner: Arc::downgrade(miner),
+		}
+	}
+
+	fn sign_call(&self, request: CRequest) -> Result<SignedTransaction, Error> {
+		let from = request.from.unwrap_or(0.into());
+		Ok(EthTransaction {
+			nonce: request.nonce.unwrap_or_else(Default::default),
+			action: request.to.map_or(Action::Create, Action::Call),
+			gas: request.gas.unwrap_or(50_000_000.into()),
+			gas_price: request.gas_price.unwrap_or_else(Default::default),
+			value: request.value.unwrap_or(0.into()),
+			data: request.data.map_or_else(Vec::new, |d| d.to_vec())
+		}.fake_sign(from))
+	}
+
+	fn active(&self) -> Result<(), Error> {
+		// TODO: only call every 30s at most.
+		take_weak!(self.client).keep_alive();
+		Ok(())
+	}
+}
+
+impl<C, M> Private for PrivateClient<C, M> where C: MiningBlockChainClient + 'static, M: MinerService + 'static {
+	fn register_contract(&self, address: H160, code: Bytes, validators: Vec<H512>, parties: Vec<H512>) -> Result<H256, Error> {
+		self.active()?;
+
+		let provider = take_weak!(self.provider);
+		provider.register_contract(
+			address.into(),
+			code.to_vec(),
+			validators.into_iter().map(Into::into).collect(),
+			parties.into_iter().map(Into::into).collect(),
+		)
+			.map(Into::into)
+			.map_err(errors::private_transaction)
+	}
+
+	fn send_transaction(&self, raw: Bytes) -> Result<PrivateTransactionReceipt, Error> {
+		self.active()?;
+
+		let raw_transaction = raw.to_vec();
+		let signed: SignedTransaction = UntrustedRlp::new(&raw_transaction).as_val().map_err(errors::from_rlp_error)?;
+		let provider = take_weak!(self.provider);
+		provider.send_transaction(&*take_weak!(self.client), &*take_weak!(self.miner), signed)
+			.map(Into::into)
+			.map_err(errors::private_transaction)
+	}
+
+	fn import_transaction(&self, message: Bytes) -> Result<Option<Bytes>, Error> {
+		self.active()?;
+
+		let provider = take_weak!(self.provider);
+		provider.import_private_transaction(&*take_weak!(self.client), &message.to_vec())
+			.map(|signed| signed.map(|signed| encode(&signed).to_vec().into()))
+			.map_err(errors::private_transaction)
+	}
+
+	fn import_signature(&self, message: Bytes) -> Result<Option<H256>, Error> {
+		self.active()?;
+
+		let provider = take_weak!(self.provider);
+		provider.import_signature(&*take_weak!(self.client), &*take_weak!(self.miner), &message.to_vec())
+			.map(|hash| hash.map(Into::into))
+			.map_err(errors::private_transaction)
+	}
+
+	fn call(&self, request: CallRequest) -> Result<Bytes, Error> {
+		self.active()?;
+
+		let signed = self.sign_call(CallRequest::into(request))?;
+		let provider = take_weak!(self.provider);
+		provider.call(&*take_weak!(self.client), &signed)
+			.map(|executed| executed.output.into())
+			.map_err(errors::private_transaction)
+	}
+}
@@ -34,6 +34,7 @@ fn to_call_analytics(flags: Vec<String>) -> CallAnalytics {
 		transaction_tracing: flags.contains(&("trace".to_owned())),
 		vm_tracing: flags.contains(&("vmTrace".to_owned())),
 		state_diffing: flags.contains(&("stateDiff".to_owned())),
+		struct_logging: None,
 	}
 }
 
@@ -231,7 +231,7 @@ impl MinerService for TestMinerService {
 				contract_address: None,
 				logs: r.logs.clone(),
 				log_bloom: r.log_bloom,
-				state_root: r.state_root,
+				outcome: r.outcome.clone(),
 			}
 		)
 	}
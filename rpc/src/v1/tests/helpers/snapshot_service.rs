@@ -16,7 +16,7 @@
 
 use ethcore::snapshot::{ManifestData, RestorationStatus, SnapshotService};
 
-use util::{Bytes, Mutex};
+use util::{Bytes, Mutex, U256};
 use util::hash::H256;
 
 /// Mocked snapshot service (used for sync info extensions).
@@ -48,4 +48,8 @@ impl SnapshotService for TestSnapshotService {
 	fn restore_state_chunk(&self, _hash: H256, _chunk: Bytes) { }
 	fn restore_block_chunk(&self, _hash: H256, _chunk: Bytes) { }
 	fn provide_canon_hashes(&self, _hashes: &[(u64, H256)]) { }
+	fn begin_state_download(&self, _block: Bytes, _receipts: Bytes, _total_difficulty: U256) { }
+	fn missing_state_nodes(&self, _max: usize) -> Vec<H256> { Vec::new() }
+	fn restore_state_nodes(&self, _nodes: Vec<Bytes>) { }
+	fn state_download_complete(&self) -> bool { false }
 }
\ No newline at end of file
@@ -0,0 +1,102 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use ethcore::executed::Executed;
+use ethcore::trace::StructLog;
+use ethcore::client::TestBlockChainClient;
+
+use jsonrpc_core::{IoHandler, GenericIoHandler};
+use v1::tests::helpers::TestMinerService;
+use v1::{Debug, DebugClient};
+
+struct Tester {
+	_client: Arc<TestBlockChainClient>,
+	_miner: Arc<TestMinerService>,
+	io: IoHandler,
+}
+
+fn io() -> Tester {
+	let client = Arc::new(TestBlockChainClient::new());
+	let mut storage = BTreeMap::new();
+	storage.insert(1.into(), 2.into());
+	*client.execution_result.write() = Some(Ok(Executed {
+		exception: None,
+		gas: 20_000.into(),
+		gas_used: 10_000.into(),
+		refunded: 0.into(),
+		cumulative_gas_used: 10_000.into(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![1, 2, 3],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+		struct_logs: Some(vec![StructLog {
+			pc: 0,
+			op: "PUSH1".into(),
+			gas: 100.into(),
+			gas_cost: 3.into(),
+			depth: 1,
+			stack: Some(vec![1.into()]),
+			memory: Some(vec![0; 32]),
+			storage: Some(storage),
+		}]),
+	}));
+	let miner = Arc::new(TestMinerService::default());
+	let debug = DebugClient::new(&client, &miner);
+	let io = IoHandler::new();
+	io.add_delegate(debug.to_delegate());
+
+	Tester {
+		_client: client,
+		_miner: miner,
+		io: io,
+	}
+}
+
+#[test]
+fn rpc_debug_trace_transaction() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"failed":false,"gas":"0x2710","returnValue":"0x010203","structLogs":[{"depth":1,"gas":"0x64","gasCost":"0x3","memory":["0x0000000000000000000000000000000000000000000000000000000000000000"],"op":"PUSH1","pc":0,"stack":["0x1"],"storage":{"0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000002"}}]},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_block_by_number() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceBlockByNumber","params":["latest"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"failed":false,"gas":"0x2710","returnValue":"0x010203","structLogs":[{"depth":1,"gas":"0x64","gasCost":"0x3","memory":["0x0000000000000000000000000000000000000000000000000000000000000000"],"op":"PUSH1","pc":0,"stack":["0x1"],"storage":{"0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000002"}}]}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_call() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceCall","params":[{}, "latest"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"failed":false,"gas":"0x2710","returnValue":"0x010203","structLogs":[{"depth":1,"gas":"0x64","gasCost":"0x3","memory":["0x0000000000000000000000000000000000000000000000000000000000000000"],"op":"PUSH1","pc":0,"stack":["0x1"],"storage":{"0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000002"}}]},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
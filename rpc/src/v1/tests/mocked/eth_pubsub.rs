@@ -0,0 +1,112 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::sync::mpsc;
+
+use ethcore::client::{TestBlockChainClient, EachBlockWith, BlockId, ChainNotify};
+use jsonrpc_core::IoHandler;
+use util::H256;
+
+use pubsub::PubSubHandler;
+use v1::{EthPubSub, EthPubSubClient, Session};
+use v1::types::PubSubKind;
+use v1::tests::helpers::{TestSyncProvider, Config};
+
+fn pubsub_tester() -> (Arc<TestBlockChainClient>, Arc<EthPubSubClient<TestBlockChainClient, TestSyncProvider>>) {
+	let client = Arc::new(TestBlockChainClient::new());
+	let sync = Arc::new(TestSyncProvider::new(Config {
+		network_id: 3,
+		num_peers: 120,
+	}));
+	let pubsub = Arc::new(EthPubSubClient::new(&client, &sync));
+	(client, pubsub)
+}
+
+fn new_blocks(pubsub: &EthPubSubClient<TestBlockChainClient, TestSyncProvider>, enacted: Vec<H256>) {
+	pubsub.new_blocks(vec![], vec![], enacted, vec![], vec![], vec![], 0);
+}
+
+#[test]
+fn should_subscribe_to_new_heads() {
+	// given
+	let (client, pubsub) = pubsub_tester();
+	let handler = PubSubHandler::new(Arc::new(IoHandler::new()), Some(pubsub.clone() as Arc<EthPubSub>));
+	let (tx, rx) = mpsc::channel();
+	let session = Arc::new(Session::new(move |msg| tx.send(msg).is_ok()));
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newHeads"], "id": 1}"#;
+	let s = session.clone();
+	handler.handle_request(&session, request, move |response| {
+		s.send(response.unwrap());
+	});
+	let response = rx.try_recv().unwrap();
+	let id = response.split('"').nth(9).unwrap().to_owned();
+	assert_eq!(response, format!(r#"{{"id":1,"jsonrpc":"2.0","result":"{}"}}"#, id));
+
+	client.add_blocks(1, EachBlockWith::Nothing);
+	let hash = client.block_hash(BlockId::Number(1)).unwrap();
+	new_blocks(&pubsub, vec![hash]);
+
+	// then
+	let notification = rx.try_recv().unwrap();
+	assert!(notification.starts_with(r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"#));
+	assert!(notification.contains(&format!(r#""hash":"0x{:?}""#, hash)));
+	assert!(notification.ends_with(&format!(r#""subscription":"{}"}}}}"#, id)));
+
+	// and when
+	let other_session = Arc::new(Session::new(|_| true));
+	assert!(!pubsub.unsubscribe(&other_session, id.clone()).unwrap(), "Other sessions can't unsubscribe.");
+	assert!(pubsub.unsubscribe(&session, id.clone()).unwrap());
+	assert!(!pubsub.unsubscribe(&session, id).unwrap());
+	new_blocks(&pubsub, vec![hash]);
+
+	// then
+	assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn should_notify_about_pending_transactions() {
+	// given
+	let (_client, pubsub) = pubsub_tester();
+	let (tx, rx) = mpsc::channel();
+	let session = Arc::new(Session::new(move |msg| tx.send(msg).is_ok()));
+	let id = pubsub.subscribe(&session, PubSubKind::NewPendingTransactions, Default::default()).unwrap();
+
+	// when
+	pubsub.notify_pending_transactions(&[H256::from(5)]);
+
+	// then
+	assert_eq!(rx.try_recv().unwrap(), format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":"0x0000000000000000000000000000000000000000000000000000000000000005","subscription":"{}"}}}}"#, id));
+}
+
+#[test]
+fn should_subscribe_to_all_logs_without_filter() {
+	let (_client, pubsub) = pubsub_tester();
+	let handler = PubSubHandler::new(Arc::new(IoHandler::new()), Some(pubsub as Arc<EthPubSub>));
+	let session = Arc::new(Session::new(|_| true));
+	let (tx, rx) = mpsc::channel();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["logs"], "id": 1}"#;
+	handler.handle_request(&session, request, move |response| {
+		tx.send(response).unwrap();
+	});
+
+	let response = rx.try_recv().unwrap().unwrap();
+	let id = response.split('"').nth(9).unwrap().to_owned();
+	assert_eq!(response, format!(r#"{{"id":1,"jsonrpc":"2.0","result":"{}"}}"#, id));
+}
@@ -0,0 +1,316 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Light client `ETH` RPC tests, served by a full client over a fake network.
+
+use std::sync::Arc;
+
+use devtools::RandomTempPath;
+use ethcore::account_provider::AccountProvider;
+use ethcore::client::{BlockChainClient, Client, ClientConfig};
+use ethcore::db::{COL_HEADERS, NUM_COLUMNS};
+use ethcore::ids::BlockId;
+use ethcore::miner::Miner;
+use ethcore::spec::Spec;
+use ethcore::transaction::{Transaction, SignedTransaction, Action};
+use ethkey::{Generator, Random};
+use ethsync::LightNetworkDispatcher;
+use io::IoChannel;
+use jsonrpc_core::{IoHandler, GenericIoHandler};
+use light::client::{Client as LightClient, Config as LightConfig};
+use light::net::{BasicContext, EventContext, Handler, Status, Capabilities, Announcement, ReqId, Error as LesError};
+use light::on_demand::OnDemand;
+use light::request::Request as LesRequest;
+use light::Provider;
+use rustc_serialize::hex::ToHex;
+use util::{Address, H512, Mutex, U256};
+use util::kvdb::{Database, DatabaseConfig};
+
+use v1::Eth;
+use v1::types::U256 as RpcU256;
+use v1::impls::light::EthClient as LightEthClient;
+
+const ACCOUNT: &'static str = "0x000000000000000000000000000000000000000a";
+const CONTRACT: &'static str = "0x000000000000000000000000000000000000000c";
+const PROXY: &'static str = "0x000000000000000000000000000000000000000d";
+
+// the contract returns its storage at slot 0; the proxy calls the contract and returns its output.
+const GENESIS_STATE: &'static str = r#"{
+	"0x000000000000000000000000000000000000000a": {
+		"balance": "0x1000", "code": "0x", "nonce": "0x01", "storage": {}
+	},
+	"0x000000000000000000000000000000000000000c": {
+		"balance": "0x00", "code": "0x60005460005260206000f3", "nonce": "0x00", "storage": { "0x00": "0x2a" }
+	},
+	"0x000000000000000000000000000000000000000d": {
+		"balance": "0x00",
+		"code": "0x6020600060006000600073000000000000000000000000000000000000000c5af15060206000f3",
+		"nonce": "0x00",
+		"storage": {}
+	}
+}"#;
+
+// a network of one peer: the full client. requests are queued while the on-demand
+// service holds its locks and answered once it's done dispatching.
+struct TestNetwork {
+	on_demand: Arc<OnDemand>,
+	full: Arc<Client>,
+	queued: Mutex<Vec<(ReqId, LesRequest)>>,
+	next_id: Mutex<usize>,
+	relayed: Mutex<Vec<SignedTransaction>>,
+}
+
+impl TestNetwork {
+	fn answer(&self) {
+		loop {
+			let queued = ::std::mem::replace(&mut *self.queued.lock(), Vec::new());
+			if queued.is_empty() { break }
+
+			for (req_id, request) in queued {
+				match request {
+					LesRequest::StateProofs(req) => {
+						let proofs: Vec<_> = req.requests.into_iter()
+							.map(|req| Provider::state_proof(&*self.full, req))
+							.collect();
+						self.on_demand.on_state_proofs(self, req_id, &proofs);
+					}
+					LesRequest::Codes(req) => {
+						let codes = Provider::contract_codes(&*self.full, req);
+						self.on_demand.on_code(self, req_id, &codes);
+					}
+					LesRequest::Bodies(req) => {
+						let bodies: Vec<_> = Provider::block_bodies(&*self.full, req).into_iter()
+							.map(|body| body.expect("only known blocks are requested; qed").into_inner())
+							.collect();
+						self.on_demand.on_block_bodies(self, req_id, &bodies);
+					}
+					LesRequest::Receipts(req) => {
+						let receipts: Vec<_> = Provider::receipts(&*self.full, req).iter()
+							.map(|receipts| ::rlp::decode(receipts))
+							.collect();
+						self.on_demand.on_receipts(self, req_id, &receipts);
+					}
+					_ => panic!("only state proofs, code, bodies and receipts are requested"),
+				}
+			}
+		}
+	}
+}
+
+impl BasicContext for TestNetwork {
+	fn persistent_peer_id(&self, _: usize) -> Option<H512> { None }
+	fn request_from(&self, _: usize, request: LesRequest) -> Result<ReqId, LesError> {
+		let mut next_id = self.next_id.lock();
+		*next_id += 1;
+		self.queued.lock().push((ReqId(*next_id), request));
+		Ok(ReqId(*next_id))
+	}
+	fn credits_left(&self, _: usize, _: &LesRequest) -> Option<U256> { Some(U256::max_value()) }
+	fn send_transactions(&self, txs: &[SignedTransaction]) -> usize {
+		self.relayed.lock().extend(txs.iter().cloned());
+		1
+	}
+	fn make_announcement(&self, _: Announcement) { }
+	fn disconnect_peer(&self, _: usize) { }
+	fn disable_peer(&self, _: usize) { }
+	fn report_peer(&self, _: usize, _: i32) { }
+}
+
+impl EventContext for TestNetwork {
+	fn peer(&self) -> usize { 0 }
+	fn as_basic(&self) -> &BasicContext { self }
+}
+
+impl LightNetworkDispatcher for TestNetwork {
+	fn with_context<F, T>(&self, f: F) -> Option<T> where F: FnOnce(&BasicContext) -> T {
+		let res = f(self);
+		self.answer();
+		Some(res)
+	}
+}
+
+struct Tester {
+	full: Arc<Client>,
+	network: Arc<TestNetwork>,
+	io: IoHandler,
+	_full_dir: RandomTempPath,
+	_light_dir: RandomTempPath,
+}
+
+impl Tester {
+	fn new() -> Self {
+		let mut spec = Spec::new_test();
+		let state: ::ethjson::blockchain::State = ::serde_json::from_str(GENESIS_STATE).unwrap();
+		spec.set_genesis_state(state.into());
+
+		let full_dir = RandomTempPath::new();
+		let full = Client::new(
+			ClientConfig::default(),
+			&spec,
+			full_dir.as_path(),
+			Arc::new(Miner::with_spec(&spec)),
+			IoChannel::disconnected(),
+			&DatabaseConfig::with_columns(NUM_COLUMNS),
+		).unwrap();
+
+		let light_dir = RandomTempPath::new();
+		let light_db = Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), light_dir.as_str()).unwrap();
+		let light_config = LightConfig { chain_column: COL_HEADERS, ..Default::default() };
+		let light = Arc::new(LightClient::new(light_config, Arc::new(light_db), &spec, IoChannel::disconnected()).unwrap());
+
+		let on_demand = Arc::new(OnDemand::default());
+		let network = Arc::new(TestNetwork {
+			on_demand: on_demand.clone(),
+			full: full.clone(),
+			queued: Mutex::new(Vec::new()),
+			next_id: Mutex::new(0),
+			relayed: Mutex::new(Vec::new()),
+		});
+
+		let chain_info = BlockChainClient::chain_info(&*full);
+		let status = Status {
+			protocol_version: 1,
+			network_id: 0,
+			head_td: chain_info.total_difficulty,
+			head_hash: chain_info.best_block_hash,
+			head_num: chain_info.best_block_number,
+			genesis_hash: chain_info.genesis_hash,
+			last_head: None,
+		};
+		let capabilities = Capabilities {
+			serve_chain_since: Some(0),
+			serve_state_since: Some(0),
+			..Default::default()
+		};
+		on_demand.on_connect(&*network, &status, &capabilities);
+
+		let accounts = Arc::new(AccountProvider::transient_provider());
+		let io = IoHandler::new();
+		io.add_delegate(LightEthClient::new(&network, &light, &on_demand, &accounts).to_delegate());
+
+		Tester {
+			full: full,
+			network: network,
+			io: io,
+			_full_dir: full_dir,
+			_light_dir: light_dir,
+		}
+	}
+}
+
+#[test]
+fn rpc_light_eth_balance() {
+	let tester = Tester::new();
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getBalance", "params": ["{}", "earliest"], "id": 1}}"#, ACCOUNT);
+	let response = r#"{"jsonrpc":"2.0","result":"0x1000","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getBalance", "params": ["0x00000000000000000000000000000000000000ff", "earliest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_light_eth_code() {
+	let tester = Tester::new();
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getCode", "params": ["{}", "earliest"], "id": 1}}"#, CONTRACT);
+	let response = r#"{"jsonrpc":"2.0","result":"0x60005460005260206000f3","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getCode", "params": ["{}", "earliest"], "id": 1}}"#, ACCOUNT);
+	let response = r#"{"jsonrpc":"2.0","result":"0x","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_light_eth_call() {
+	let tester = Tester::new();
+
+	// the proxy's call touches the sender, the proxy, the contract and its storage.
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_call", "params": [{{"from": "{}", "to": "{}"}}, "earliest"], "id": 1}}"#, ACCOUNT, PROXY);
+	let response = r#"{"jsonrpc":"2.0","result":"0x000000000000000000000000000000000000000000000000000000000000002a","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_light_eth_estimate_gas() {
+	let tester = Tester::new();
+
+	let from: Address = ACCOUNT.parse().unwrap();
+	let tx = Transaction {
+		nonce: U256::one(),
+		action: Action::Call(PROXY.parse().unwrap()),
+		gas: U256::from(50_000_000),
+		gas_price: U256::zero(),
+		value: U256::zero(),
+		data: Vec::new(),
+	}.fake_sign(from);
+	let executed = tester.full.call(&tx, BlockId::Earliest, Default::default()).unwrap();
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_estimateGas", "params": [{{"from": "{}", "to": "{}"}}, "earliest"], "id": 1}}"#, ACCOUNT, PROXY);
+	let gas = ::serde_json::to_string(&RpcU256::from(executed.gas_used + executed.refunded)).unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","result":{},"id":1}}"#, gas);
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response));
+}
+
+#[test]
+fn rpc_light_eth_storage_at() {
+	let tester = Tester::new();
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getStorageAt", "params": ["{}", "0x0", "earliest"], "id": 1}}"#, CONTRACT);
+	let response = r#"{"jsonrpc":"2.0","result":"0x000000000000000000000000000000000000000000000000000000000000002a","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getStorageAt", "params": ["{}", "0x0", "earliest"], "id": 1}}"#, ACCOUNT);
+	let response = r#"{"jsonrpc":"2.0","result":"0x0000000000000000000000000000000000000000000000000000000000000000","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_light_eth_block_by_number() {
+	let tester = Tester::new();
+
+	let genesis = BlockChainClient::chain_info(&*tester.full).genesis_hash;
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["earliest", false], "id": 1}"#;
+	let response = tester.io.handle_request_sync(request).unwrap();
+	assert!(response.contains(&format!(r#""hash":"0x{}""#, genesis.hex())));
+	assert!(response.contains(r#""transactions":[]"#));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getBlockByHash", "params": ["0x{}", false], "id": 1}}"#, genesis.hex());
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response));
+}
+
+#[test]
+fn rpc_light_eth_send_raw_transaction() {
+	let tester = Tester::new();
+
+	let secret = Random.generate().unwrap().secret().clone();
+	let tx = Transaction {
+		nonce: U256::zero(),
+		action: Action::Call(PROXY.parse().unwrap()),
+		gas: U256::from(100_000),
+		gas_price: U256::zero(),
+		value: U256::zero(),
+		data: Vec::new(),
+	}.sign(&secret, None);
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_sendRawTransaction", "params": ["0x{}"], "id": 1}}"#, ::rlp::encode(&tx).to_vec().to_hex());
+	let response = format!(r#"{{"jsonrpc":"2.0","result":"0x{}","id":1}}"#, tx.hash().hex());
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response));
+	assert_eq!(*tester.network.relayed.lock(), vec![tx]);
+}
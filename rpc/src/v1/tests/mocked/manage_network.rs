@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethsync::{ManageNetwork, NetworkConfiguration};
+use ethsync::{ManageNetwork, NetworkConfiguration, BannedPeer};
 
 pub struct TestManageNetwork;
 
@@ -24,6 +24,10 @@ impl ManageNetwork for TestManageNetwork {
 	fn deny_unreserved_peers(&self) { }
 	fn remove_reserved_peer(&self, _peer: String) -> Result<(), String> { Ok(()) }
 	fn add_reserved_peer(&self, _peer: String) -> Result<(), String> { Ok(()) }
+	fn banned_peers(&self) -> Vec<BannedPeer> { Vec::new() }
+	fn unban_peer(&self, _peer: String) -> Result<bool, String> { Ok(false) }
+	fn clear_banned_peers(&self) {}
+	fn set_ip_filter(&self, _allow: String, _deny: String) -> Result<(), String> { Ok(()) }
 	fn start_network(&self) {}
 	fn stop_network(&self) {}
 	fn network_config(&self) -> NetworkConfiguration { NetworkConfiguration::new_local() }
@@ -17,13 +17,17 @@
 //! RPC mocked tests. Most of these test that the RPC server is serializing and forwarding
 //! method calls properly.
 
+mod debug;
 mod eth;
+mod eth_pubsub;
+mod light_eth;
 mod manage_network;
 mod net;
 mod parity;
 mod parity_accounts;
 mod parity_set;
 mod personal;
+mod private;
 mod rpc;
 mod signer;
 mod signing;
@@ -270,6 +270,17 @@ fn rpc_parity_net_peers() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_banned_peers() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_bannedPeers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_net_port() {
 	let deps = Dependencies::new();
@@ -460,3 +471,24 @@ fn rpc_parity_chain_status() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_consensus_evidence() {
+	use ethcore::engines::EquivocationProof;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_consensusEvidence", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	deps.client.equivocation_proofs.write().push(EquivocationProof {
+		author: Address::from(1),
+		first: vec![1, 2],
+		second: vec![3, 4],
+	});
+
+	let response = r#"{"jsonrpc":"2.0","result":[{"author":"0x0000000000000000000000000000000000000001","first":"0x0102","second":"0x0304"}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
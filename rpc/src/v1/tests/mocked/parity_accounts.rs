@@ -18,6 +18,9 @@ use std::sync::Arc;
 
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::TestBlockChainClient;
+use ethstore::EthStore;
+use ethstore::dir::DiskDirectory;
+use devtools::RandomTempPath;
 
 use jsonrpc_core::{IoHandler, GenericIoHandler};
 use v1::{ParityAccounts, ParityAccountsClient};
@@ -40,7 +43,16 @@ fn accounts_provider() -> Arc<AccountProvider> {
 }
 
 fn setup() -> ParityAccountsTester {
-	let accounts = accounts_provider();
+	setup_with_accounts(accounts_provider())
+}
+
+fn setup_with_vaults_support(temp_path: &RandomTempPath) -> ParityAccountsTester {
+	let dir = DiskDirectory::create(temp_path.as_path()).unwrap();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	setup_with_accounts(Arc::new(AccountProvider::new(Box::new(store))))
+}
+
+fn setup_with_accounts(accounts: Arc<AccountProvider>) -> ParityAccountsTester {
 	let client = blockchain_client();
 	let parity_accounts = ParityAccountsClient::new(&accounts, &client);
 
@@ -227,3 +239,54 @@ fn should_be_able_to_remove_address() {
 	let response = r#"{"jsonrpc":"2.0","result":{},"id":4}"#;
 	assert_eq!(res, Some(response.into()));
 }
+
+#[test]
+fn should_be_able_to_derive_addresses_from_mnemonic() {
+	let tester = setup();
+	let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_newAccountFromMnemonic", "params": ["{}", "password"], "id": 1}}"#, phrase);
+	let response = r#"{"jsonrpc":"2.0","result":"0x9858effd232b4033e47d90003d41ec34ecaeda94","id":1}"#;
+	let res = tester.io.handle_request_sync(&request);
+	assert_eq!(res, Some(response.into()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_deriveAddress", "params": ["0x9858effd232b4033e47d90003d41ec34ecaeda94", "password"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x6fac4d18c912343bf86fa7049364dd4e424ab9c0","id":1}"#;
+	let res = tester.io.handle_request_sync(request);
+	assert_eq!(res, Some(response.into()));
+
+	assert_eq!(tester.accounts.accounts().unwrap().len(), 2);
+}
+
+#[test]
+fn should_be_able_to_move_account_to_vault() {
+	let temp_path = RandomTempPath::new();
+	let tester = setup_with_vaults_support(&temp_path);
+	let address = tester.accounts.new_account("password").unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_newVault", "params": ["vault1", "vault_password"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.into()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_listVaults", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["vault1"],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.into()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_changeVault", "params": ["0x{:?}", "vault1"], "id": 1}}"#, address);
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.into()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_closeVault", "params": ["vault1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.into()));
+	assert!(tester.accounts.accounts().unwrap().is_empty());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_listOpenedVaults", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.into()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_openVault", "params": ["vault1", "vault_password"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.into()));
+	assert_eq!(tester.accounts.accounts().unwrap(), vec![address]);
+}
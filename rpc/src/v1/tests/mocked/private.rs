@@ -0,0 +1,117 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::sync::Arc;
+
+use rustc_serialize::hex::ToHex;
+use ethcore::account_provider::AccountProvider;
+use ethcore::client::TestBlockChainClient;
+use ethcore::private_transactions::Provider;
+use ethcore::spec::Spec;
+use ethcore::transaction::{Transaction, Action};
+use ethkey::{Generator, Random, KeyPair};
+use util::{Address, U256, H256};
+use devtools::RandomTempPath;
+
+use jsonrpc_core::{IoHandler, GenericIoHandler};
+use v1::{Private, PrivateClient};
+use v1::tests::helpers::TestMinerService;
+
+/// Returns the stored word for empty calldata, otherwise stores the first calldata word.
+const STORE_CODE: &'static str = "36600f5760005460005260206000f35b60003560005500";
+
+struct PrivateTester {
+	validator: KeyPair,
+	miner: Arc<TestMinerService>,
+	io: IoHandler,
+	// these unused fields are necessary to keep the data alive
+	// as the handler has only weak pointers.
+	_provider: Arc<Provider>,
+	_client: Arc<TestBlockChainClient>,
+	_path: RandomTempPath,
+}
+
+fn setup() -> PrivateTester {
+	let path = RandomTempPath::new();
+	let validator = Random.generate().unwrap();
+	let accounts = Arc::new(AccountProvider::transient_provider());
+	let address = accounts.insert_account(validator.secret().clone(), "").unwrap();
+	accounts.unlock_account_permanently(address, "".into()).unwrap();
+
+	let provider = Arc::new(Provider::new(path.as_path(), Spec::new_test().engine, accounts, Some(address)).unwrap());
+	let client = Arc::new(TestBlockChainClient::default());
+	client.set_balance(address, U256::from(1_000_000_000_000_000_000u64));
+	let miner = Arc::new(TestMinerService::default());
+
+	let io = IoHandler::new();
+	io.add_delegate(PrivateClient::new(&provider, &client, &miner).to_delegate());
+
+	PrivateTester {
+		validator: validator,
+		miner: miner,
+		io: io,
+		_provider: provider,
+		_client: client,
+		_path: path,
+	}
+}
+
+fn register(tester: &PrivateTester, contract: &str) -> String {
+	let request = r#"{"jsonrpc": "2.0", "method": "private_registerContract", "params": [""#.to_owned()
+		+ contract + r#"", "0x"# + STORE_CODE + r#"", ["0x"# + &format!("{:?}", tester.validator.public()) + r#""], []], "id": 1}"#;
+	tester.io.handle_request_sync(&request).unwrap()
+}
+
+#[test]
+fn rpc_private_register_contract() {
+	let tester = setup();
+	let response = register(&tester, "0x0000000000000000000000000000000000001234");
+	assert!(response.starts_with(r#"{"jsonrpc":"2.0","result":"0x"#));
+
+	// registering the same contract again is rejected.
+	let response = register(&tester, "0x0000000000000000000000000000000000001234");
+	assert!(response.contains(r#""code":-32070"#));
+}
+
+#[test]
+fn rpc_private_send_transaction_and_call() {
+	let tester = setup();
+	register(&tester, "0x0000000000000000000000000000000000001234");
+
+	let sender = Random.generate().unwrap();
+	let transaction = Transaction {
+		nonce: 0.into(),
+		action: Action::Call(Address::from(0x1234)),
+		gas: 100_000.into(),
+		gas_price: U256::zero(),
+		value: U256::zero(),
+		data: H256::from(5).to_vec(),
+	}.sign(sender.secret(), None);
+	let request = r#"{"jsonrpc": "2.0", "method": "private_sendTransaction", "params": ["0x"#.to_owned()
+		+ &::rlp::encode(&transaction).to_hex() + r#""], "id": 1}"#;
+	let response = tester.io.handle_request_sync(&request).unwrap();
+
+	let imported = tester.miner.imported_transactions.lock();
+	assert_eq!(imported.len(), 1);
+	assert!(response.contains(&format!(r#""publicTransactionHash":"0x{:?}""#, imported[0].hash())));
+	assert!(response.contains(r#""contractAddress":"0x0000000000000000000000000000000000001234""#));
+
+	// the anchor is not mined yet, so calls still see the initial state.
+	let request = r#"{"jsonrpc": "2.0", "method": "private_call", "params": [{"to": "0x0000000000000000000000000000000000001234"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x"#.to_owned() + &::std::iter::repeat("00").take(32).collect::<String>() + r#"","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response));
+}
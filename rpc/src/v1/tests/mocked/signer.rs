@@ -18,7 +18,8 @@ use std::sync::Arc;
 use std::str::FromStr;
 use util::{U256, Uint, Address, ToPretty};
 
-use ethcore::account_provider::AccountProvider;
+use ethcore::account_provider::{AccountProvider, LedgerSigner, LedgerEmulator};
+use ethstore::ethkey::Mnemonic;
 use ethcore::client::TestBlockChainClient;
 use ethcore::transaction::{Transaction, Action};
 use rlp::encode;
@@ -214,6 +215,52 @@ fn should_confirm_transaction_and_dispatch() {
 	assert_eq!(tester.miner.imported_transactions.lock().len(), 1);
 }
 
+#[test]
+fn should_confirm_transaction_on_hardware_wallet() {
+	// given
+	let tester = signer_tester();
+	let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+	let device = Arc::new(LedgerEmulator::new(&Mnemonic::from_phrase(phrase).unwrap().seed("")).unwrap());
+	tester.accounts.add_external_signer(Box::new(LedgerSigner::new(device.clone(), 1)));
+	let address = Address::from_str("9858effd232b4033e47d90003d41ec34ecaeda94").unwrap();
+	let recipient = Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap();
+	tester.signer.add_request(ConfirmationPayload::SendTransaction(FilledTransactionRequest {
+		from: address,
+		to: Some(recipient),
+		gas_price: U256::from(10_000),
+		gas: U256::from(10_000_000),
+		value: U256::from(1),
+		data: vec![],
+		nonce: None,
+		min_block: None,
+	})).unwrap();
+
+	let t = Transaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(0x1000),
+		gas: U256::from(10_000_000),
+		action: Action::Call(recipient),
+		value: U256::from(0x1),
+		data: vec![]
+	};
+	let signature = tester.accounts.sign(address, None, t.hash(None)).unwrap();
+	let t = t.with_signature(signature, None);
+
+	// when
+	let request = r#"{
+		"jsonrpc":"2.0",
+		"method":"signer_confirmRequest",
+		"params":["0x1", {"gasPrice":"0x1000"}, ""],
+		"id":1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:?}", t.hash()).as_ref() + r#"","id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(tester.signer.requests().len(), 0);
+	assert_eq!(tester.miner.imported_transactions.lock().len(), 1);
+}
+
 #[test]
 fn should_confirm_transaction_with_token() {
 	// given
@@ -45,12 +45,13 @@ fn io() -> Tester {
 		result: Res::None,
 		subtraces: 0,
 		trace_address: vec![0],
-		transaction_number: 0,
-		transaction_hash: 5.into(),
+		transaction_number: Some(0),
+		transaction_hash: Some(5.into()),
 		block_number: 10,
 		block_hash: 10.into(),
 	}]);
 	*client.execution_result.write() = Some(Ok(Executed {
+		exception: None,
 		gas: 20_000.into(),
 		gas_used: 10_000.into(),
 		refunded: 0.into(),
@@ -61,6 +62,7 @@ fn io() -> Tester {
 		trace: vec![],
 		vm_trace: None,
 		state_diff: None,
+		struct_logs: None,
 	}));
 	let miner = Arc::new(TestMinerService::default());
 	let traces = TracesClient::new(&client, &miner);
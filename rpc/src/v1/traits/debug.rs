@@ -0,0 +1,39 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Debug rpc interface.
+
+use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
+use v1::types::{BlockNumber, CallRequest, DebugTrace, H256, TraceOptions};
+
+build_rpc_trait! {
+	/// Debug rpc interface.
+	pub trait Debug {
+		/// Replays the transaction with the given hash and returns the VM state before every executed instruction.
+		#[rpc(name = "debug_traceTransaction")]
+		fn trace_transaction(&self, H256, Trailing<TraceOptions>) -> Result<Option<DebugTrace>, Error>;
+
+		/// Executes the given call on top of the given block and returns the VM state before every executed instruction.
+		#[rpc(name = "debug_traceCall")]
+		fn trace_call(&self, CallRequest, BlockNumber, Trailing<TraceOptions>) -> Result<DebugTrace, Error>;
+
+		/// Replays all transactions of the given block and returns their struct logs.
+		#[rpc(name = "debug_traceBlockByNumber")]
+		fn trace_block_by_number(&self, BlockNumber, Trailing<TraceOptions>) -> Result<Vec<DebugTrace>, Error>;
+	}
+}
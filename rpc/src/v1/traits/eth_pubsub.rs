@@ -0,0 +1,35 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Eth PUB-SUB rpc interface.
+
+use std::sync::Arc;
+use jsonrpc_core::Error;
+
+use v1::helpers::{Session, SubscriptionId};
+use v1::types::{PubSubKind, PubSubParams};
+
+/// Eth PUB-SUB rpc interface.
+///
+/// Subscriptions are bound to a session, so unlike other interfaces this one
+/// is only available over transports that can push messages (WebSockets and IPC).
+pub trait EthPubSub: Send + Sync + 'static {
+	/// Subscribe to Eth subscription.
+	fn subscribe(&self, session: &Arc<Session>, kind: PubSubKind, params: PubSubParams) -> Result<SubscriptionId, Error>;
+
+	/// Unsubscribe from existing Eth subscription created within the same session.
+	fn unsubscribe(&self, session: &Arc<Session>, id: SubscriptionId) -> Result<bool, Error>;
+}
@@ -23,10 +23,11 @@ use jsonrpc_macros::Trailing;
 
 use v1::types::{
 	H160, H256, H512, U256, Bytes,
-	Peers, Transaction, RpcSettings, Histogram,
+	Peers, BannedPeer, Transaction, RpcSettings, Histogram,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, DappId, ChainStatus,
+	EquivocationProof,
 };
 
 build_rpc_trait! {
@@ -72,6 +73,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_netPeers")]
 		fn net_peers(&self) -> Result<Peers, Error>;
 
+		/// Returns peers banned for misbehaviour along with ban expiry times
+		#[rpc(name = "parity_bannedPeers")]
+		fn banned_peers(&self) -> Result<Vec<BannedPeer>, Error>;
+
 		/// Returns network port
 		#[rpc(name = "parity_netPort")]
 		fn net_port(&self) -> Result<u16, Error>;
@@ -178,5 +183,9 @@ build_rpc_trait! {
 		/// Get the current chain status.
 		#[rpc(name = "parity_chainStatus")]
 		fn chain_status(&self) -> Result<ChainStatus, Error>;
+
+		/// Get proofs of validator misbehaviour observed by the consensus engine.
+		#[rpc(name = "parity_consensusEvidence")]
+		fn consensus_evidence(&self) -> Result<Vec<EquivocationProof>, Error>;
 	}
 }
@@ -32,6 +32,16 @@ build_rpc_trait! {
 		#[rpc(name = "parity_newAccountFromPhrase")]
 		fn new_account_from_phrase(&self, String, String) -> Result<H160, Error>;
 
+		/// Creates new hierarchical deterministic wallet from the given BIP39 mnemonic phrase
+		/// and returns its first account. Second parameter is password for the new wallet.
+		#[rpc(name = "parity_newAccountFromMnemonic")]
+		fn new_account_from_mnemonic(&self, String, String) -> Result<H160, Error>;
+
+		/// Derives and stores the next account of the wallet the given account belongs to.
+		/// Arguments: `account`, `password`.
+		#[rpc(name = "parity_deriveAddress")]
+		fn derive_address(&self, H160, String) -> Result<H160, Error>;
+
 		/// Creates new account from the given JSON wallet.
 		/// Second parameter is password for the wallet and the new account.
 		#[rpc(name = "parity_newAccountFromWallet")]
@@ -103,5 +113,34 @@ build_rpc_trait! {
 		/// Returns the accounts available for importing from Geth.
 		#[rpc(name = "parity_listGethAccounts")]
 		fn geth_accounts(&self) -> Result<Vec<H160>, Error>;
+
+		/// Create new vault.
+		#[rpc(name = "parity_newVault")]
+		fn create_vault(&self, String, String) -> Result<bool, Error>;
+
+		/// Open existing vault.
+		#[rpc(name = "parity_openVault")]
+		fn open_vault(&self, String, String) -> Result<bool, Error>;
+
+		/// Close previously opened vault.
+		#[rpc(name = "parity_closeVault")]
+		fn close_vault(&self, String) -> Result<bool, Error>;
+
+		/// Change password of an opened vault.
+		#[rpc(name = "parity_changeVaultPassword")]
+		fn change_vault_password(&self, String, String) -> Result<bool, Error>;
+
+		/// Lists all vaults.
+		#[rpc(name = "parity_listVaults")]
+		fn list_vaults(&self) -> Result<Vec<String>, Error>;
+
+		/// Lists opened vaults.
+		#[rpc(name = "parity_listOpenedVaults")]
+		fn list_opened_vaults(&self) -> Result<Vec<String>, Error>;
+
+		/// Moves account to the opened vault.
+		/// Empty vault name moves the account out of its vault.
+		#[rpc(name = "parity_changeVault")]
+		fn change_vault(&self, H160, String) -> Result<bool, Error>;
 	}
 }
@@ -72,6 +72,19 @@ build_rpc_trait! {
 		#[rpc(name = "parity_acceptNonReservedPeers")]
 		fn accept_non_reserved_peers(&self) -> Result<bool, Error>;
 
+		/// Lift the ban on a peer banned for misbehaviour.
+		#[rpc(name = "parity_unbanPeer")]
+		fn unban_peer(&self, String) -> Result<bool, Error>;
+
+		/// Lift all peer bans.
+		#[rpc(name = "parity_clearBannedPeers")]
+		fn clear_banned_peers(&self) -> Result<bool, Error>;
+
+		/// Replace the IP filter applied to peer connections and discovery.
+		/// Takes the allow and deny lists in the `--allow-ips` and `--deny-ips` format.
+		#[rpc(name = "parity_setIpFilter")]
+		fn set_ip_filter(&self, String, String) -> Result<bool, Error>;
+
 		/// Start the network.
 		///
 		/// Deprecated. Use `set_mode("active")` instead.
@@ -0,0 +1,49 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions rpc interface.
+
+use jsonrpc_core::Error;
+use v1::types::{Bytes, CallRequest, PrivateTransactionReceipt, H160, H256, H512};
+
+build_rpc_trait! {
+	/// Private transactions rpc interface.
+	pub trait Private {
+		/// Registers a private contract with given runtime code, validators' public keys
+		/// and public keys of the other parties. Returns the root of the initial private state.
+		#[rpc(name = "private_registerContract")]
+		fn register_contract(&self, H160, Bytes, Vec<H512>, Vec<H512>) -> Result<H256, Error>;
+
+		/// Sends a signed transaction to a private contract. Returns the encrypted message
+		/// to be delivered to all parties of the contract.
+		#[rpc(name = "private_sendTransaction")]
+		fn send_transaction(&self, Bytes) -> Result<PrivateTransactionReceipt, Error>;
+
+		/// Re-executes an encrypted private transaction as a party of the contract. Validators
+		/// return the signed resulting state, to be delivered back to the sender.
+		#[rpc(name = "private_importTransaction")]
+		fn import_transaction(&self, Bytes) -> Result<Option<Bytes>, Error>;
+
+		/// Imports a validator's signature of a private transaction sent by this node.
+		/// Returns the hash of the public marker transaction once all validators signed.
+		#[rpc(name = "private_importSignature")]
+		fn import_signature(&self, Bytes) -> Result<Option<H256>, Error>;
+
+		/// Executes a call against the private state of a contract.
+		#[rpc(name = "private_call")]
+		fn call(&self, CallRequest) -> Result<Bytes, Error>;
+	}
+}
@@ -17,6 +17,8 @@
 use std::ops::Deref;
 use std::collections::BTreeMap;
 use serde::{Serialize, Serializer};
+use ethcore::header::Header as EthHeader;
+use rlp::{self, Encodable};
 use v1::types::{Bytes, Transaction, H160, H256, H2048, U256};
 
 /// Block Transactions
@@ -95,6 +97,80 @@ pub struct Block {
 	pub size: Option<U256>,
 }
 
+/// Block header representation.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Header {
+	/// Hash of the block
+	pub hash: Option<H256>,
+	/// Hash of the parent
+	#[serde(rename="parentHash")]
+	pub parent_hash: H256,
+	/// Hash of the uncles
+	#[serde(rename="sha3Uncles")]
+	pub uncles_hash: H256,
+	/// Authors address
+	pub author: H160,
+	// TODO: get rid of this one
+	/// ?
+	pub miner: H160,
+	/// State root hash
+	#[serde(rename="stateRoot")]
+	pub state_root: H256,
+	/// Transactions root hash
+	#[serde(rename="transactionsRoot")]
+	pub transactions_root: H256,
+	/// Transactions receipts root hash
+	#[serde(rename="receiptsRoot")]
+	pub receipts_root: H256,
+	/// Block number
+	pub number: Option<U256>,
+	/// Gas Used
+	#[serde(rename="gasUsed")]
+	pub gas_used: U256,
+	/// Gas Limit
+	#[serde(rename="gasLimit")]
+	pub gas_limit: U256,
+	/// Extra data
+	#[serde(rename="extraData")]
+	pub extra_data: Bytes,
+	/// Logs bloom
+	#[serde(rename="logsBloom")]
+	pub logs_bloom: H2048,
+	/// Timestamp
+	pub timestamp: U256,
+	/// Difficulty
+	pub difficulty: U256,
+	/// Seal fields
+	#[serde(rename="sealFields")]
+	pub seal_fields: Vec<Bytes>,
+	/// Size in bytes
+	pub size: Option<U256>,
+}
+
+impl From<EthHeader> for Header {
+	fn from(h: EthHeader) -> Self {
+		Header {
+			hash: Some(h.hash().into()),
+			size: Some(h.rlp_bytes().len().into()),
+			parent_hash: (*h.parent_hash()).into(),
+			uncles_hash: (*h.uncles_hash()).into(),
+			author: (*h.author()).into(),
+			miner: (*h.author()).into(),
+			state_root: (*h.state_root()).into(),
+			transactions_root: (*h.transactions_root()).into(),
+			receipts_root: (*h.receipts_root()).into(),
+			number: Some(h.number().into()),
+			gas_used: (*h.gas_used()).into(),
+			gas_limit: (*h.gas_limit()).into(),
+			logs_bloom: (*h.log_bloom()).into(),
+			timestamp: h.timestamp().into(),
+			difficulty: (*h.difficulty()).into(),
+			seal_fields: h.seal().into_iter().map(|f| rlp::decode(f)).map(Bytes::new).collect(),
+			extra_data: h.extra_data().clone().into(),
+		}
+	}
+}
+
 /// Block representation with additional info
 #[derive(Debug)]
 pub struct RichBlock {
@@ -133,7 +209,7 @@ mod tests {
 	use std::collections::BTreeMap;
 	use serde_json;
 	use v1::types::{Transaction, H64, H160, H256, H2048, Bytes, U256};
-	use super::{Block, RichBlock, BlockTransactions};
+	use super::{Block, RichBlock, BlockTransactions, Header};
 
 	#[test]
 	fn test_serialize_block_transactions() {
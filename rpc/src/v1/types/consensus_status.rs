@@ -15,7 +15,8 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use semver;
-use v1::types::{H160, H256};
+use ethcore::engines;
+use v1::types::{H160, H256, Bytes};
 use updater::{self, CapState};
 
 /// Capability info
@@ -167,3 +168,24 @@ impl Into<OperationsInfo> for updater::OperationsInfo {
 		}
 	}
 }
+
+/// Proof that a validator signed two conflicting consensus messages.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EquivocationProof {
+	/// Validator which signed both messages.
+	pub author: H160,
+	/// First signed message.
+	pub first: Bytes,
+	/// Second signed message, conflicting with the first.
+	pub second: Bytes,
+}
+
+impl From<engines::EquivocationProof> for EquivocationProof {
+	fn from(p: engines::EquivocationProof) -> Self {
+		EquivocationProof {
+			author: p.author.into(),
+			first: p.first.into(),
+			second: p.second.into(),
+		}
+	}
+}
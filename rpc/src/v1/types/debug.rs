@@ -0,0 +1,155 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Debug namespace types.
+
+use std::collections::BTreeMap;
+use ethcore::client::Executed;
+use ethcore::trace::{StructLog as EthStructLog, StructLogOptions};
+use v1::types::{Bytes, H256, U256};
+
+/// Options of `debug_trace*` calls.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TraceOptions {
+	/// Don't report the stack.
+	#[serde(default, rename="disableStack")]
+	pub disable_stack: bool,
+	/// Don't report the memory.
+	#[serde(default, rename="disableMemory")]
+	pub disable_memory: bool,
+	/// Don't report the storage.
+	#[serde(default, rename="disableStorage")]
+	pub disable_storage: bool,
+}
+
+impl Into<StructLogOptions> for TraceOptions {
+	fn into(self) -> StructLogOptions {
+		StructLogOptions {
+			disable_stack: self.disable_stack,
+			disable_memory: self.disable_memory,
+			disable_storage: self.disable_storage,
+		}
+	}
+}
+
+/// VM state right before execution of a single instruction.
+#[derive(Debug, Serialize)]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// Instruction name.
+	pub op: String,
+	/// Gas left before execution.
+	pub gas: U256,
+	/// Cost of the instruction.
+	#[serde(rename="gasCost")]
+	pub gas_cost: U256,
+	/// Call depth.
+	pub depth: usize,
+	/// Stack, bottom first.
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// Memory split into 32-byte words.
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub memory: Option<Vec<H256>>,
+	/// Storage entries of the executing contract seen so far.
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+impl From<EthStructLog> for StructLog {
+	fn from(l: EthStructLog) -> Self {
+		StructLog {
+			pc: l.pc,
+			op: l.op,
+			gas: l.gas.into(),
+			gas_cost: l.gas_cost.into(),
+			depth: l.depth,
+			stack: l.stack.map(|s| s.into_iter().map(Into::into).collect()),
+			memory: l.memory.map(|m| m.chunks(32).map(H256::from).collect()),
+			storage: l.storage.map(|s| s.into_iter().map(|(k, v)| (k.into(), v.into())).collect()),
+		}
+	}
+}
+
+/// Result of a `debug_trace*` call.
+#[derive(Debug, Serialize)]
+pub struct DebugTrace {
+	/// Gas used by the transaction.
+	pub gas: U256,
+	/// True if the transaction resulted in an exceptional exit or was reverted.
+	pub failed: bool,
+	/// Output of the transaction.
+	#[serde(rename="returnValue")]
+	pub return_value: Bytes,
+	/// Executed instructions.
+	#[serde(rename="structLogs")]
+	pub struct_logs: Vec<StructLog>,
+}
+
+impl From<Executed> for DebugTrace {
+	fn from(e: Executed) -> Self {
+		DebugTrace {
+			gas: e.gas_used.into(),
+			failed: e.exception.is_some(),
+			return_value: e.output.into(),
+			struct_logs: e.struct_logs.unwrap_or_else(Vec::new).into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{TraceOptions, StructLog, DebugTrace};
+
+	#[test]
+	fn trace_options_deserialization() {
+		let s = r#"{"disableStack":true,"disableStorage":true}"#;
+		let deserialized: TraceOptions = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, TraceOptions {
+			disable_stack: true,
+			disable_memory: false,
+			disable_storage: true,
+		});
+
+		let deserialized: TraceOptions = serde_json::from_str("{}").unwrap();
+		assert_eq!(deserialized, TraceOptions::default());
+	}
+
+	#[test]
+	fn debug_trace_serialization() {
+		let t = DebugTrace {
+			gas: 3.into(),
+			failed: false,
+			return_value: vec![].into(),
+			struct_logs: vec![StructLog {
+				pc: 0,
+				op: "PUSH1".into(),
+				gas: 100.into(),
+				gas_cost: 3.into(),
+				depth: 1,
+				stack: Some(vec![]),
+				memory: None,
+				storage: None,
+			}],
+		};
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"gas":"0x3","failed":false,"returnValue":"0x","structLogs":[{"pc":0,"op":"PUSH1","gas":"0x64","gasCost":"0x3","depth":1,"stack":[]}]}"#);
+	}
+}
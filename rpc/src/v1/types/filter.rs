@@ -53,7 +53,7 @@ pub type FilterAddress = VariadicValue<H160>;
 pub type Topic = VariadicValue<H256>;
 
 /// Filter
-#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[derive(Debug, Default, PartialEq, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Filter {
 	/// From Block
@@ -0,0 +1,62 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::private_transactions::Receipt;
+use v1::types::{H160, H256, Bytes};
+
+/// Result of sending a private transaction.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PrivateTransactionReceipt {
+	/// Hash of the private transaction message.
+	pub hash: H256,
+	/// Address of the private contract.
+	#[serde(rename="contractAddress")]
+	pub contract_address: H160,
+	/// Encrypted transaction to be delivered to the validators.
+	pub message: Bytes,
+	/// Hash of the public marker transaction, if the state has already been signed by all validators.
+	#[serde(rename="publicTransactionHash")]
+	pub public_transaction_hash: Option<H256>,
+}
+
+impl From<Receipt> for PrivateTransactionReceipt {
+	fn from(r: Receipt) -> Self {
+		PrivateTransactionReceipt {
+			hash: r.hash.into(),
+			contract_address: r.contract.into(),
+			message: r.message.into(),
+			public_transaction_hash: r.marker.map(Into::into),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::PrivateTransactionReceipt;
+
+	#[test]
+	fn receipt_serialization() {
+		let receipt = PrivateTransactionReceipt {
+			hash: 1.into(),
+			contract_address: 2.into(),
+			message: vec![0xab].into(),
+			public_transaction_hash: None,
+		};
+		let serialized = serde_json::to_string(&receipt).unwrap();
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","contractAddress":"0x0000000000000000000000000000000000000002","message":"0xab","publicTransactionHash":null}"#);
+	}
+}
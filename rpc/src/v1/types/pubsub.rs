@@ -0,0 +1,126 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pub-Sub types.
+
+use serde::{Deserialize, Deserializer, Error, Serialize, Serializer};
+use serde::de::Visitor;
+use v1::types::{Filter, Header, Log, SyncStatus, H256};
+
+/// Subscription kind.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Kind {
+	/// New block headers subscription.
+	NewHeads,
+	/// Logs subscription.
+	Logs,
+	/// New Pending Transactions subscription.
+	NewPendingTransactions,
+	/// Node syncing status subscription.
+	Syncing,
+}
+
+impl Deserialize for Kind {
+	fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<Kind, D::Error>
+	where D: Deserializer {
+		deserializer.deserialize(KindVisitor)
+	}
+}
+
+struct KindVisitor;
+
+impl Visitor for KindVisitor {
+	type Value = Kind;
+
+	fn visit_str<E>(&mut self, value: &str) -> ::std::result::Result<Self::Value, E> where E: Error {
+		match value {
+			"newHeads" => Ok(Kind::NewHeads),
+			"logs" => Ok(Kind::Logs),
+			"newPendingTransactions" => Ok(Kind::NewPendingTransactions),
+			"syncing" => Ok(Kind::Syncing),
+			_ => Err(Error::custom(format!("unknown subscription kind: {}", value))),
+		}
+	}
+
+	fn visit_string<E>(&mut self, value: String) -> ::std::result::Result<Self::Value, E> where E: Error {
+		self.visit_str(value.as_ref())
+	}
+}
+
+/// Subscription kind-specific parameters.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Params {
+	/// No parameters passed.
+	None,
+	/// Log parameters.
+	Logs(Filter),
+}
+
+impl Default for Params {
+	fn default() -> Self {
+		Params::None
+	}
+}
+
+/// Subscription result.
+#[derive(Debug, PartialEq)]
+pub enum Result {
+	/// New block header.
+	Header(Header),
+	/// Log
+	Log(Log),
+	/// Transaction hash
+	TransactionHash(H256),
+	/// Syncing status.
+	SyncState(SyncStatus),
+}
+
+impl Serialize for Result {
+	fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+	where S: Serializer {
+		match *self {
+			Result::Header(ref header) => header.serialize(serializer),
+			Result::Log(ref log) => log.serialize(serializer),
+			Result::TransactionHash(ref hash) => hash.serialize(serializer),
+			Result::SyncState(ref sync) => sync.serialize(serializer),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::{H256, SyncStatus};
+	use super::{Kind, Result};
+
+	#[test]
+	fn should_deserialize_kind() {
+		let s = r#"["newHeads", "logs", "newPendingTransactions", "syncing"]"#;
+		let deserialized: Vec<Kind> = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, vec![Kind::NewHeads, Kind::Logs, Kind::NewPendingTransactions, Kind::Syncing]);
+
+		assert!(serde_json::from_str::<Kind>(r#""unknown""#).is_err());
+	}
+
+	#[test]
+	fn should_serialize_result() {
+		let hash = Result::TransactionHash(H256::default());
+		assert_eq!(serde_json::to_string(&hash).unwrap(), r#""0x0000000000000000000000000000000000000000000000000000000000000000""#);
+
+		let sync = Result::SyncState(SyncStatus::None);
+		assert_eq!(serde_json::to_string(&sync).unwrap(), "false");
+	}
+}
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeMap;
-use ethsync::{PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
+use ethsync::{PeerInfo as SyncPeerInfo, BannedPeer as SyncBannedPeer, TransactionStats as SyncTransactionStats};
 use serde::{Serialize, Serializer};
 use v1::types::{U256, H512};
 
@@ -96,6 +96,15 @@ pub struct PeerEthereumProtocolInfo {
 	pub head: String,
 }
 
+/// Peer banned for misbehaviour
+#[derive(Default, Debug, Serialize)]
+pub struct BannedPeer {
+	/// Public node id
+	pub id: String,
+	/// Unix timestamp the ban expires at
+	pub until: u64,
+}
+
 /// Sync status
 #[derive(Debug, PartialEq)]
 pub enum SyncStatus {
@@ -147,6 +156,15 @@ impl From<SyncPeerInfo> for PeerInfo {
 	}
 }
 
+impl From<SyncBannedPeer> for BannedPeer {
+	fn from(p: SyncBannedPeer) -> Self {
+		BannedPeer {
+			id: p.id,
+			until: p.until,
+		}
+	}
+}
+
 impl From<SyncTransactionStats> for TransactionStats {
 	fn from(s: SyncTransactionStats) -> Self {
 		TransactionStats {
@@ -171,7 +189,7 @@ pub struct ChainStatus {
 mod tests {
 	use serde_json;
 	use std::collections::BTreeMap;
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus};
+	use super::{SyncInfo, SyncStatus, Peers, BannedPeer, TransactionStats, ChainStatus};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -187,6 +205,13 @@ mod tests {
 		assert_eq!(serialized, r#"{"active":0,"connected":0,"max":0,"peers":[]}"#);
 	}
 
+	#[test]
+	fn test_serialize_banned_peer() {
+		let t = BannedPeer { id: "node1".into(), until: 1500000000 };
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"id":"node1","until":1500000000}"#);
+	}
+
 	#[test]
 	fn test_serialize_sync_status() {
 		let t = SyncStatus::None;
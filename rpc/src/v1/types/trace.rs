@@ -251,6 +251,9 @@ pub enum CallType {
 	/// Delegate call
 	#[serde(rename="delegatecall")]
 	DelegateCall,
+	/// Static call
+	#[serde(rename="staticcall")]
+	StaticCall,
 }
 
 impl From<executed::CallType> for CallType {
@@ -260,6 +263,7 @@ impl From<executed::CallType> for CallType {
 			executed::CallType::Call => CallType::Call,
 			executed::CallType::CallCode => CallType::CallCode,
 			executed::CallType::DelegateCall => CallType::DelegateCall,
+			executed::CallType::StaticCall => CallType::StaticCall,
 		}
 	}
 }
@@ -317,6 +321,52 @@ impl From<trace::Suicide> for Suicide {
 	}
 }
 
+/// Reward type.
+#[derive(Debug, Serialize)]
+pub enum RewardType {
+	/// Block
+	#[serde(rename="block")]
+	Block,
+	/// Uncle
+	#[serde(rename="uncle")]
+	Uncle,
+	/// Decided by a reward contract
+	#[serde(rename="external")]
+	External,
+}
+
+impl From<trace::RewardType> for RewardType {
+	fn from(c: trace::RewardType) -> Self {
+		match c {
+			trace::RewardType::Block => RewardType::Block,
+			trace::RewardType::Uncle => RewardType::Uncle,
+			trace::RewardType::External => RewardType::External,
+		}
+	}
+}
+
+/// Reward action
+#[derive(Debug, Serialize)]
+pub struct Reward {
+	/// Author's address.
+	pub author: H160,
+	/// Reward amount.
+	pub value: U256,
+	/// Reward type.
+	#[serde(rename="rewardType")]
+	pub reward_type: RewardType,
+}
+
+impl From<trace::Reward> for Reward {
+	fn from(r: trace::Reward) -> Self {
+		Reward {
+			author: r.author.into(),
+			value: r.value.into(),
+			reward_type: r.reward_type.into(),
+		}
+	}
+}
+
 /// Action
 #[derive(Debug)]
 pub enum Action {
@@ -326,6 +376,8 @@ pub enum Action {
 	Create(Create),
 	/// Suicide
 	Suicide(Suicide),
+	/// Reward
+	Reward(Reward),
 }
 
 impl From<trace::Action> for Action {
@@ -334,6 +386,7 @@ impl From<trace::Action> for Action {
 			trace::Action::Call(call) => Action::Call(call.into()),
 			trace::Action::Create(create) => Action::Create(create.into()),
 			trace::Action::Suicide(suicide) => Action::Suicide(suicide.into()),
+			trace::Action::Reward(reward) => Action::Reward(reward.into()),
 		}
 	}
 }
@@ -418,9 +471,9 @@ pub struct LocalizedTrace {
 	/// Subtraces
 	subtraces: usize,
 	/// Transaction position
-	transaction_position: usize,
+	transaction_position: Option<usize>,
 	/// Transaction hash
-	transaction_hash: H256,
+	transaction_hash: Option<H256>,
 	/// Block Number
 	block_number: u64,
 	/// Block Hash
@@ -445,6 +498,10 @@ impl Serialize for LocalizedTrace {
 				serializer.serialize_struct_elt(&mut state, "type", "suicide")?;
 				serializer.serialize_struct_elt(&mut state, "action", suicide)?;
 			},
+			Action::Reward(ref reward) => {
+				serializer.serialize_struct_elt(&mut state, "type", "reward")?;
+				serializer.serialize_struct_elt(&mut state, "action", reward)?;
+			},
 		}
 
 		match self.result {
@@ -473,8 +530,8 @@ impl From<EthLocalizedTrace> for LocalizedTrace {
 			result: t.result.into(),
 			trace_address: t.trace_address.into_iter().map(Into::into).collect(),
 			subtraces: t.subtraces.into(),
-			transaction_position: t.transaction_number.into(),
-			transaction_hash: t.transaction_hash.into(),
+			transaction_position: t.transaction_number.map(Into::into),
+			transaction_hash: t.transaction_hash.map(Into::into),
 			block_number: t.block_number.into(),
 			block_hash: t.block_hash.into(),
 		}
@@ -512,6 +569,10 @@ impl Serialize for Trace {
 				serializer.serialize_struct_elt(&mut state, "type", "suicide")?;
 				serializer.serialize_struct_elt(&mut state, "action", suicide)?;
 			},
+			Action::Reward(ref reward) => {
+				serializer.serialize_struct_elt(&mut state, "type", "reward")?;
+				serializer.serialize_struct_elt(&mut state, "action", reward)?;
+			},
 		}
 
 		match self.result {
@@ -603,8 +664,8 @@ mod tests {
 			}),
 			trace_address: vec![10],
 			subtraces: 1,
-			transaction_position: 11,
-			transaction_hash: 12.into(),
+			transaction_position: Some(11),
+			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
 		};
@@ -626,8 +687,8 @@ mod tests {
 			result: Res::FailedCall(TraceError::OutOfGas),
 			trace_address: vec![10],
 			subtraces: 1,
-			transaction_position: 11,
-			transaction_hash: 12.into(),
+			transaction_position: Some(11),
+			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
 		};
@@ -651,8 +712,8 @@ mod tests {
 			}),
 			trace_address: vec![10],
 			subtraces: 1,
-			transaction_position: 11,
-			transaction_hash: 12.into(),
+			transaction_position: Some(11),
+			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
 		};
@@ -672,8 +733,8 @@ mod tests {
 			result: Res::FailedCreate(TraceError::OutOfGas),
 			trace_address: vec![10],
 			subtraces: 1,
-			transaction_position: 11,
-			transaction_hash: 12.into(),
+			transaction_position: Some(11),
+			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
 		};
@@ -692,8 +753,8 @@ mod tests {
 			result: Res::None,
 			trace_address: vec![10],
 			subtraces: 1,
-			transaction_position: 11,
-			transaction_hash: 12.into(),
+			transaction_position: Some(11),
+			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
 		};
@@ -701,6 +762,26 @@ mod tests {
 		assert_eq!(serialized, r#"{"type":"suicide","action":{"address":"0x0000000000000000000000000000000000000004","refundAddress":"0x0000000000000000000000000000000000000006","balance":"0x7"},"result":null,"traceAddress":[10],"subtraces":1,"transactionPosition":11,"transactionHash":"0x000000000000000000000000000000000000000000000000000000000000000c","blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#);
 	}
 
+	#[test]
+	fn test_trace_reward_serialize() {
+		let t = LocalizedTrace {
+			action: Action::Reward(Reward {
+				author: 4.into(),
+				value: 6.into(),
+				reward_type: RewardType::Block,
+			}),
+			result: Res::None,
+			trace_address: vec![],
+			subtraces: 0,
+			transaction_position: None,
+			transaction_hash: None,
+			block_number: 13,
+			block_hash: 14.into(),
+		};
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"type":"reward","action":{"author":"0x0000000000000000000000000000000000000004","value":"0x6","rewardType":"block"},"result":null,"traceAddress":[],"subtraces":0,"transactionPosition":null,"transactionHash":null,"blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#);
+	}
+
 	#[test]
 	fn test_vmtrace_serialize() {
 		let t = VMTrace {
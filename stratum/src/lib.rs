@@ -39,10 +39,13 @@ mod traits {
 	include!(concat!(env!("OUT_DIR"), "/traits.rs"));
 }
 
+mod shares;
+
 pub use traits::{
-	JobDispatcher, PushWorkHandler, Error, ServiceConfiguration,
+	JobDispatcher, PushWorkHandler, Error, ServiceConfiguration, VardiffConfig,
 	RemoteWorkHandler, RemoteJobDispatcher,
 };
+pub use shares::ShareStats;
 
 use jsonrpc_tcp_server::Server as JsonRpcServer;
 use jsonrpc_core::{IoHandler, Params, IoDelegate, to_value, from_params};
@@ -50,7 +53,9 @@ use std::sync::Arc;
 
 use std::net::SocketAddr;
 use std::collections::{HashSet, HashMap};
+use std::time::Instant;
 use util::{H256, Hashable, RwLock, RwLockReadGuard};
+use shares::{Worker, JobHistory, JobStatus};
 
 pub struct Stratum {
 	rpc_server: JsonRpcServer,
@@ -61,10 +66,14 @@ pub struct Stratum {
 	job_que: RwLock<HashSet<SocketAddr>>,
 	/// Payload manager
 	dispatcher: Arc<JobDispatcher>,
-	/// Authorized workers (socket - worker_id)
-	workers: Arc<RwLock<HashMap<SocketAddr, String>>>,
+	/// Authorized workers (socket - worker)
+	workers: Arc<RwLock<HashMap<SocketAddr, Worker>>>,
 	/// Secret if any
 	secret: Option<H256>,
+	/// Variable difficulty settings, if enabled
+	vardiff: Option<VardiffConfig>,
+	/// Jobs handed out to the workers
+	jobs: RwLock<JobHistory>,
 }
 
 impl Stratum {
@@ -72,6 +81,7 @@ impl Stratum {
 		addr: &SocketAddr,
 		dispatcher: Arc<JobDispatcher>,
 		secret: Option<H256>,
+		vardiff: Option<VardiffConfig>,
 	) -> Result<Arc<Stratum>, jsonrpc_tcp_server::Error> {
 		let handler = Arc::new(IoHandler::new());
 		let server = JsonRpcServer::new(addr, &handler)?;
@@ -83,11 +93,16 @@ impl Stratum {
 			dispatcher: dispatcher,
 			workers: Arc::new(RwLock::new(HashMap::new())),
 			secret: secret,
+			vardiff: vardiff,
+			jobs: RwLock::new(JobHistory::default()),
 		});
 
 		let mut delegate = IoDelegate::<Stratum>::new(stratum.clone());
 		delegate.add_method("miner.subscribe", Stratum::subscribe);
 		delegate.add_method("miner.authorize", Stratum::authorize);
+		delegate.add_method("mining.subscribe", Stratum::subscribe);
+		delegate.add_method("mining.authorize", Stratum::authorize);
+		delegate.add_method("mining.submit", Stratum::submit);
 		stratum.handler.add_delegate(delegate);
 
 		stratum.rpc_server.run_async()?;
@@ -124,7 +139,11 @@ impl Stratum {
 				}
 			}
 			if let Some(context) = self.rpc_server.request_context() {
-				self.workers.write().insert(context.socket_addr, worker_id);
+				let worker = Worker::new(worker_id, self.vardiff.as_ref());
+				if let Some(difficulty) = worker.difficulty {
+					self.push_difficulty(&context.socket_addr, difficulty);
+				}
+				self.workers.write().insert(context.socket_addr, worker);
 				to_value(&true)
 			}
 			else {
@@ -134,19 +153,94 @@ impl Stratum {
 		})
 	}
 
+	fn submit(&self, params: Params) -> std::result::Result<jsonrpc_core::Value, jsonrpc_core::Error> {
+		// first two elements are service messages (worker_id & job_id), the rest is pow-specific
+		let mut values = from_params::<Vec<String>>(params)?;
+		if values.len() < 2 {
+			trace!(target: "stratum", "Invalid submit work format {:?}", values);
+			return Ok(to_value(&false));
+		}
+		let payload = values.split_off(2);
+		let job_id = values.pop().expect("values has exactly 2 elements after split_off(2); qed");
+
+		let socket_addr = match self.rpc_server.request_context() {
+			Some(context) => context.socket_addr,
+			None => {
+				warn!(target: "stratum", "Submit without valid context received!");
+				return Ok(to_value(&false));
+			}
+		};
+
+		let (worker_id, difficulty) = match self.workers.read().get(&socket_addr) {
+			Some(worker) => (worker.id.clone(), worker.difficulty),
+			None => {
+				trace!(target: "stratum", "Share submitted by unauthorized worker ('{:?}')", socket_addr);
+				return Ok(to_value(&false));
+			}
+		};
+
+		let job_status = self.jobs.write().submit(&job_id, &payload);
+		let result = match job_status {
+			JobStatus::Current => self.dispatcher.submit(worker_id, job_id, difficulty, payload),
+			JobStatus::Stale => Err(Error::StaleShare),
+			JobStatus::Unknown => Err(Error::InvalidShare(format!("unknown job {}", job_id))),
+			JobStatus::Duplicate => Err(Error::InvalidShare(format!("duplicate share for job {}", job_id))),
+		};
+
+		let mut workers = self.workers.write();
+		let worker = match workers.get_mut(&socket_addr) {
+			Some(worker) => worker,
+			None => return Ok(to_value(&result.is_ok())),
+		};
+
+		match result {
+			Ok(()) => {
+				if let Some(difficulty) = worker.on_accepted(self.vardiff.as_ref(), Instant::now()) {
+					self.push_difficulty(&socket_addr, difficulty);
+				}
+				Ok(to_value(&true))
+			},
+			Err(Error::StaleShare) => {
+				trace!(target: "stratum", "Stale share from worker {}", worker.id);
+				worker.stats.stale += 1;
+				Ok(to_value(&false))
+			},
+			Err(e) => {
+				debug!(target: "stratum", "Share from worker {} rejected: {:?}", worker.id, e);
+				worker.stats.rejected += 1;
+				Ok(to_value(&false))
+			},
+		}
+	}
+
+	fn push_difficulty(&self, socket_addr: &SocketAddr, difficulty: u64) {
+		let message = format!(r#"{{"id":null,"method":"mining.set_difficulty","params":[{}]}}"#, difficulty);
+		if let Err(e) = self.rpc_server.push_message(socket_addr, message.as_bytes()) {
+			debug!(target: "stratum", "Error pushing difficulty to {:?}: {:?}", socket_addr, e);
+		}
+	}
+
 	pub fn subscribers(&self) -> RwLockReadGuard<Vec<SocketAddr>> {
 		self.subscribers.read()
 	}
 
+	/// Share counters of all authorized workers.
+	pub fn share_stats(&self) -> Vec<(String, ShareStats)> {
+		self.workers.read().values().map(|worker| (worker.id.clone(), worker.stats.clone())).collect()
+	}
+
 	pub fn maintain(&self) {
 		let mut job_que = self.job_que.write();
-		let workers = self.workers.read();
+		let mut workers = self.workers.write();
 		for socket_addr in job_que.drain() {
-			if let Some(worker_id) = workers.get(&socket_addr) {
-				let job_payload = self.dispatcher.job(worker_id.to_owned());
-				job_payload.map(
-					|json| self.rpc_server.push_message(&socket_addr, json.as_bytes())
-				);
+			if let Some(worker) = workers.get(&socket_addr) {
+				let job_payload = self.dispatcher.job(worker.id.to_owned());
+				job_payload.map(|json| {
+					if let Some(id) = job_id(&json) {
+						self.jobs.write().add_job(id);
+					}
+					self.rpc_server.push_message(&socket_addr, json.as_bytes())
+				});
 			}
 			else {
 				trace!(
@@ -155,11 +249,39 @@ impl Stratum {
 				);
 			}
 		}
+
+		// lower the difficulty of workers which can't find shares.
+		if let Some(ref vardiff) = self.vardiff {
+			let now = Instant::now();
+			for (socket_addr, worker) in workers.iter_mut() {
+				if let Some(difficulty) = worker.retarget(vardiff, now) {
+					self.push_difficulty(socket_addr, difficulty);
+				}
+			}
+		}
+	}
+}
+
+// Extract the job id from a work payload, which is expected to be a json array
+// with the job id (pow hash) first.
+fn job_id(payload: &str) -> Option<String> {
+	use std::str::FromStr;
+
+	match jsonrpc_core::Value::from_str(payload) {
+		Ok(jsonrpc_core::Value::Array(values)) => match values.into_iter().next() {
+			Some(jsonrpc_core::Value::String(id)) => Some(id),
+			_ => None,
+		},
+		_ => None,
 	}
 }
 
 impl PushWorkHandler for Stratum {
 	fn push_work_all(&self, payload: String) -> Result<(), Error> {
+		if let Some(id) = job_id(&payload) {
+			self.jobs.write().new_jobs(Some(id));
+		}
+
 		let workers = self.workers.read();
 		println!("pushing work for {} workers", workers.len());
 		for (ref addr, _) in workers.iter() {
@@ -172,6 +294,8 @@ impl PushWorkHandler for Stratum {
 		if !payloads.len() > 0 {
 			return Err(Error::NoWork);
 		}
+		self.jobs.write().new_jobs(payloads.iter().filter_map(|payload| job_id(payload)));
+
 		let workers = self.workers.read();
 		let addrs = workers.keys().collect::<Vec<&SocketAddr>>();
 		if !workers.len() > 0 {
@@ -292,14 +416,14 @@ mod tests {
 
 	#[test]
 	fn can_be_started() {
-		let stratum = Stratum::start(&SocketAddr::from_str("0.0.0.0:19980").unwrap(), Arc::new(VoidManager), None);
+		let stratum = Stratum::start(&SocketAddr::from_str("0.0.0.0:19980").unwrap(), Arc::new(VoidManager), None, None);
 		assert!(stratum.is_ok());
 	}
 
 	#[test]
 	fn records_subscriber() {
 		let addr = SocketAddr::from_str("0.0.0.0:19985").unwrap();
-		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None).unwrap();
+		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None, None).unwrap();
 		let request = r#"{"jsonrpc": "2.0", "method": "miner.subscribe", "params": [], "id": 1}"#;
 		dummy_request(&addr, request.as_bytes());
 		assert_eq!(1, stratum.subscribers.read().len());
@@ -333,7 +457,7 @@ mod tests {
 	#[test]
 	fn receives_initial_paylaod() {
 		let addr = SocketAddr::from_str("0.0.0.0:19975").unwrap();
-		Stratum::start(&addr, DummyManager::new(), None).unwrap();
+		Stratum::start(&addr, DummyManager::new(), None, None).unwrap();
 		let request = r#"{"jsonrpc": "2.0", "method": "miner.subscribe", "params": [], "id": 1}"#;
 
 		let response = String::from_utf8(dummy_request(&addr, request.as_bytes())).unwrap();
@@ -347,7 +471,8 @@ mod tests {
 		let stratum = Stratum::start(
 			&addr,
 			Arc::new(DummyManager::build().of_initial(r#"["dummy autorize payload"]"#)),
-			None
+			None,
+			None,
 		).unwrap();
 
 		let request = r#"{"jsonrpc": "2.0", "method": "miner.authorize", "params": ["miner1", ""], "id": 1}"#;
@@ -366,7 +491,8 @@ mod tests {
 		let stratum = Stratum::start(
 			&addr,
 			Arc::new(DummyManager::build().of_initial(r#"["dummy push request payload"]"#)),
-			None
+			None,
+			None,
 		).unwrap();
 
 		let result = Arc::new(RwLock::new(Vec::<String>::new()));
@@ -384,4 +510,73 @@ mod tests {
 
 		assert_eq!(2, result.read().unwrap().len());
 	}
+
+	#[derive(Default)]
+	struct SubmitManager {
+		submissions: RwLock<Vec<(String, String, Option<u64>, Vec<String>)>>,
+	}
+
+	impl JobDispatcher for SubmitManager {
+		fn submit(&self, worker_id: String, job_id: String, difficulty: Option<u64>, payload: Vec<String>) -> Result<(), Error> {
+			self.submissions.write().unwrap().push((worker_id, job_id, difficulty, payload));
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn can_submit_and_rejects_stale_shares() {
+		init_log();
+
+		let addr = SocketAddr::from_str("0.0.0.0:19960").unwrap();
+		let manager = Arc::new(SubmitManager::default());
+		let stratum = Stratum::start(&addr, manager.clone(), None, Some(VardiffConfig::default())).unwrap();
+
+		stratum.push_work_all(r#"["0x01", "0x00", "0xff"]"#.to_owned()).unwrap();
+		stratum.push_work_all(r#"["0x02", "0x00", "0xff"]"#.to_owned()).unwrap();
+
+		let result = Arc::new(RwLock::new(Vec::<String>::new()));
+		let _stop = dummy_async_waiter(
+			&addr,
+			vec![
+				r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 1}"#.to_owned(),
+				r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "0x02", "0x0000000000000001"], "id": 2}"#.to_owned(),
+				r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "0x01", "0x0000000000000002"], "id": 3}"#.to_owned(),
+			],
+			result.clone(),
+		);
+		::std::thread::park_timeout(::std::time::Duration::from_millis(500));
+
+		let submissions = manager.submissions.read().unwrap();
+		assert_eq!(
+			*submissions,
+			vec![("miner1".to_owned(), "0x02".to_owned(), Some(VardiffConfig::default().initial_difficulty), vec!["0x0000000000000001".to_owned()])]
+		);
+		assert_eq!(stratum.share_stats(), vec![("miner1".to_owned(), ShareStats { accepted: 1, rejected: 0, stale: 1 })]);
+	}
+
+	#[test]
+	fn rejects_resubmitted_shares() {
+		init_log();
+
+		let addr = SocketAddr::from_str("0.0.0.0:19955").unwrap();
+		let manager = Arc::new(SubmitManager::default());
+		let stratum = Stratum::start(&addr, manager.clone(), None, None).unwrap();
+
+		stratum.push_work_all(r#"["0x01", "0x00", "0xff"]"#.to_owned()).unwrap();
+
+		let result = Arc::new(RwLock::new(Vec::<String>::new()));
+		let _stop = dummy_async_waiter(
+			&addr,
+			vec![
+				r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 1}"#.to_owned(),
+				r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "0x01", "0x0000000000000001"], "id": 2}"#.to_owned(),
+				r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "0x01", "0x0000000000000001"], "id": 3}"#.to_owned(),
+			],
+			result.clone(),
+		);
+		::std::thread::park_timeout(::std::time::Duration::from_millis(500));
+
+		assert_eq!(manager.submissions.read().unwrap().len(), 1);
+		assert_eq!(stratum.share_stats(), vec![("miner1".to_owned(), ShareStats { accepted: 1, rejected: 1, stale: 0 })]);
+	}
 }
@@ -0,0 +1,308 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-worker share accounting, variable difficulty and job tracking.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use util::{U256, Uint};
+use traits::VardiffConfig;
+
+/// Number of superseded jobs remembered for stale-share detection.
+const MAX_STALE_JOBS: usize = 16;
+
+/// Difficulty is never changed by more than this factor in one step.
+const MAX_RETARGET_FACTOR: u64 = 4;
+
+/// Share counters of a single worker.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShareStats {
+	/// Shares accepted by the dispatcher.
+	pub accepted: u64,
+	/// Shares rejected as invalid.
+	pub rejected: u64,
+	/// Shares submitted for superseded jobs.
+	pub stale: u64,
+}
+
+/// Authorized worker state.
+#[derive(Debug)]
+pub struct Worker {
+	/// Worker id given on authorization.
+	pub id: String,
+	/// Current share difficulty, `None` if vardiff is disabled.
+	pub difficulty: Option<u64>,
+	/// Share counters.
+	pub stats: ShareStats,
+	last_retarget: Instant,
+	shares_since_retarget: u64,
+}
+
+impl Worker {
+	/// New worker, with initial difficulty taken from the vardiff config.
+	pub fn new(id: String, vardiff: Option<&VardiffConfig>) -> Self {
+		Worker {
+			id: id,
+			difficulty: vardiff.map(|config| clamp(config.initial_difficulty, config)),
+			stats: ShareStats::default(),
+			last_retarget: Instant::now(),
+			shares_since_retarget: 0,
+		}
+	}
+
+	/// Note an accepted share. Returns the new difficulty if it should be changed.
+	pub fn on_accepted(&mut self, vardiff: Option<&VardiffConfig>, now: Instant) -> Option<u64> {
+		self.stats.accepted += 1;
+		self.shares_since_retarget += 1;
+		vardiff.and_then(|config| self.retarget(config, now))
+	}
+
+	/// Re-evaluate the worker difficulty based on the share rate since the last adjustment.
+	/// Workers which didn't submit anything for a whole retarget period get their difficulty lowered.
+	pub fn retarget(&mut self, config: &VardiffConfig, now: Instant) -> Option<u64> {
+		let difficulty = match self.difficulty {
+			Some(difficulty) => difficulty,
+			None => return None,
+		};
+
+		let elapsed = now.duration_since(self.last_retarget);
+		if elapsed < Duration::from_secs(config.retarget_secs) {
+			return None;
+		}
+
+		let elapsed_ms = millis(elapsed);
+		let target_ms = config.target_share_secs.saturating_mul(1000);
+		let tolerance_ms = target_ms.saturating_mul(config.variance_percent) / 100;
+
+		// new_difficulty = difficulty * target_time / actual_time, limited per step.
+		let new_difficulty = if self.shares_since_retarget == 0 {
+			difficulty / MAX_RETARGET_FACTOR
+		} else {
+			let actual_ms = elapsed_ms / self.shares_since_retarget;
+			if actual_ms + tolerance_ms >= target_ms && actual_ms <= target_ms + tolerance_ms {
+				self.reset_window(now);
+				return None;
+			}
+
+			let upper = difficulty.saturating_mul(MAX_RETARGET_FACTOR);
+			let scaled = U256::from(difficulty) * U256::from(target_ms) / U256::from(::std::cmp::max(actual_ms, 1));
+			let scaled = if scaled > U256::from(upper) { upper } else { scaled.low_u64() };
+			::std::cmp::max(scaled, difficulty / MAX_RETARGET_FACTOR)
+		};
+
+		self.reset_window(now);
+		let new_difficulty = clamp(new_difficulty, config);
+		if new_difficulty == difficulty {
+			return None;
+		}
+
+		trace!(target: "stratum", "Worker {} difficulty {} -> {}", self.id, difficulty, new_difficulty);
+		self.difficulty = Some(new_difficulty);
+		Some(new_difficulty)
+	}
+
+	fn reset_window(&mut self, now: Instant) {
+		self.last_retarget = now;
+		self.shares_since_retarget = 0;
+	}
+}
+
+fn clamp(difficulty: u64, config: &VardiffConfig) -> u64 {
+	::std::cmp::min(::std::cmp::max(difficulty, config.min_difficulty), config.max_difficulty)
+}
+
+fn millis(duration: Duration) -> u64 {
+	duration.as_secs().saturating_mul(1000) + (duration.subsec_nanos() / 1_000_000) as u64
+}
+
+/// Status of a job a share is submitted for.
+#[derive(Debug, PartialEq)]
+pub enum JobStatus {
+	/// Job is current (or jobs are not tracked at all).
+	Current,
+	/// Job was superseded by newer work.
+	Stale,
+	/// Job was never handed out.
+	Unknown,
+	/// The same share was already submitted for the job.
+	Duplicate,
+}
+
+/// Tracks ids of jobs pushed to the workers and the shares submitted for them.
+#[derive(Debug, Default)]
+pub struct JobHistory {
+	current: HashSet<String>,
+	stale: VecDeque<String>,
+	shares: HashMap<String, HashSet<Vec<String>>>,
+}
+
+impl JobHistory {
+	/// Replace the current jobs, marking the previous ones as stale.
+	pub fn new_jobs<I: IntoIterator<Item=String>>(&mut self, ids: I) {
+		let ids: HashSet<String> = ids.into_iter().collect();
+		if ids.is_empty() { return }
+
+		for old in ::std::mem::replace(&mut self.current, ids) {
+			self.shares.remove(&old);
+			self.stale.push_back(old);
+		}
+
+		while self.stale.len() > MAX_STALE_JOBS {
+			self.stale.pop_front();
+		}
+	}
+
+	/// Add a job to the current set.
+	pub fn add_job(&mut self, id: String) {
+		self.current.insert(id);
+	}
+
+	/// Get the status of a job. If no jobs could be tracked, every job is considered current
+	/// and validation is left to the dispatcher.
+	pub fn status(&self, id: &str) -> JobStatus {
+		if self.current.is_empty() {
+			JobStatus::Current
+		} else if self.current.contains(id) {
+			JobStatus::Current
+		} else if self.stale.iter().any(|job| job == id) {
+			JobStatus::Stale
+		} else {
+			JobStatus::Unknown
+		}
+	}
+
+	/// Get the status of a job a share is submitted for, recording the share if the job
+	/// is current. Shares are compared case-insensitively and only deduplicated for tracked
+	/// jobs; they are forgotten once the job goes stale.
+	pub fn submit(&mut self, id: &str, share: &[String]) -> JobStatus {
+		match self.status(id) {
+			JobStatus::Current if !self.current.is_empty() => {
+				let share = share.iter().map(|value| value.to_lowercase()).collect();
+				match self.shares.entry(id.to_owned()).or_insert_with(HashSet::new).insert(share) {
+					true => JobStatus::Current,
+					false => JobStatus::Duplicate,
+				}
+			}
+			status => status,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::{Duration, Instant};
+	use traits::VardiffConfig;
+
+	fn config() -> VardiffConfig {
+		VardiffConfig {
+			initial_difficulty: 1000,
+			min_difficulty: 100,
+			max_difficulty: 100_000,
+			target_share_secs: 10,
+			retarget_secs: 60,
+			variance_percent: 30,
+		}
+	}
+
+	#[test]
+	fn raises_difficulty_for_fast_workers() {
+		let config = config();
+		let mut worker = Worker::new("miner1".into(), Some(&config));
+		let start = worker.last_retarget;
+
+		// no adjustments before the retarget period has passed.
+		for i in 1..12 {
+			assert_eq!(worker.on_accepted(Some(&config), start + Duration::from_secs(i)), None);
+		}
+		// 60 shares in 60 seconds is ten times the target rate, but the step is limited.
+		for _ in 12..60 {
+			worker.on_accepted(Some(&config), start + Duration::from_secs(30));
+		}
+		assert_eq!(worker.on_accepted(Some(&config), start + Duration::from_secs(60)), Some(4000));
+		assert_eq!(worker.difficulty, Some(4000));
+		assert_eq!(worker.stats.accepted, 60);
+	}
+
+	#[test]
+	fn keeps_difficulty_within_variance() {
+		let config = config();
+		let mut worker = Worker::new("miner1".into(), Some(&config));
+		let start = worker.last_retarget;
+
+		for i in 1..6 {
+			worker.on_accepted(Some(&config), start + Duration::from_secs(i * 10));
+		}
+		assert_eq!(worker.on_accepted(Some(&config), start + Duration::from_secs(61)), None);
+		assert_eq!(worker.difficulty, Some(1000));
+	}
+
+	#[test]
+	fn lowers_difficulty_for_idle_workers() {
+		let config = config();
+		let mut worker = Worker::new("miner1".into(), Some(&config));
+		let start = worker.last_retarget;
+
+		assert_eq!(worker.retarget(&config, start + Duration::from_secs(61)), Some(250));
+		assert_eq!(worker.retarget(&config, start + Duration::from_secs(122)), Some(100));
+		assert_eq!(worker.retarget(&config, start + Duration::from_secs(183)), None);
+	}
+
+	#[test]
+	fn no_difficulty_without_vardiff() {
+		let mut worker = Worker::new("miner1".into(), None);
+		assert_eq!(worker.difficulty, None);
+		assert_eq!(worker.on_accepted(None, Instant::now()), None);
+		assert_eq!(worker.stats.accepted, 1);
+	}
+
+	#[test]
+	fn tracks_stale_jobs() {
+		let mut history = JobHistory::default();
+		assert_eq!(history.status("0x01"), JobStatus::Current);
+
+		history.new_jobs(vec!["0x01".to_owned()]);
+		assert_eq!(history.status("0x01"), JobStatus::Current);
+		assert_eq!(history.status("0x02"), JobStatus::Unknown);
+
+		history.new_jobs(vec!["0x02".to_owned()]);
+		assert_eq!(history.status("0x01"), JobStatus::Stale);
+		assert_eq!(history.status("0x02"), JobStatus::Current);
+
+		for i in 3..(MAX_STALE_JOBS + 4) {
+			history.new_jobs(vec![format!("0x{:02x}", i)]);
+		}
+		assert_eq!(history.status("0x01"), JobStatus::Unknown);
+	}
+
+	#[test]
+	fn rejects_duplicate_shares() {
+		let mut history = JobHistory::default();
+		let share = vec!["0x0000000000000001".to_owned()];
+		history.new_jobs(vec!["0x01".to_owned(), "0x02".to_owned()]);
+
+		assert_eq!(history.submit("0x01", &share), JobStatus::Current);
+		assert_eq!(history.submit("0x01", &share), JobStatus::Duplicate);
+		assert_eq!(history.submit("0x01", &["0x000000000000000A".to_owned()]), JobStatus::Current);
+		assert_eq!(history.submit("0x01", &["0x000000000000000a".to_owned()]), JobStatus::Duplicate);
+		assert_eq!(history.submit("0x02", &share), JobStatus::Current);
+
+		history.new_jobs(vec!["0x03".to_owned()]);
+		assert_eq!(history.submit("0x01", &share), JobStatus::Stale);
+		assert!(history.shares.is_empty());
+	}
+}
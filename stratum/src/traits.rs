@@ -25,6 +25,12 @@ pub enum Error {
 	NoWork,
 	NoWorkers,
 	Io(String),
+	/// Share was submitted for a job which is no longer current.
+	StaleShare,
+	/// Share was rejected by the dispatcher (bad solution, insufficient difficulty etc.).
+	InvalidShare(String),
+	/// Dispatcher does not support the operation.
+	Unsupported,
 }
 
 impl From<std::io::Error> for Error {
@@ -42,6 +48,12 @@ pub trait JobDispatcher: Send + Sync {
 	fn difficulty(&self) -> Option<String> { None }
 	// json for job update given worker_id (payload manager should split job!)
 	fn job(&self, _worker_id: String) -> Option<String> { None }
+	// submit a share from the given worker for the job `job_id`. `difficulty` is the share
+	// difficulty assigned to the worker (`None` if it should be checked against the job's
+	// own target) and `payload` is the pow-specific part of the `mining.submit` parameters.
+	fn submit(&self, _worker_id: String, _job_id: String, _difficulty: Option<u64>, _payload: Vec<String>) -> Result<(), Error> {
+		Err(Error::Unsupported)
+	}
 }
 
 #[ipc(client_ident="RemoteWorkHandler")]
@@ -54,10 +66,42 @@ pub trait PushWorkHandler: Send + Sync {
 	fn push_work(&self, payloads: Vec<String>) -> Result<(), Error>;
 }
 
+/// Variable share difficulty parameters.
+#[derive(Debug, Clone, PartialEq)]
+#[binary]
+pub struct VardiffConfig {
+	/// Difficulty assigned to freshly authorized workers.
+	pub initial_difficulty: u64,
+	/// Lower bound of the per-worker difficulty.
+	pub min_difficulty: u64,
+	/// Upper bound of the per-worker difficulty.
+	pub max_difficulty: u64,
+	/// Desired average time between shares of a single worker, in seconds.
+	pub target_share_secs: u64,
+	/// Minimal time between two difficulty adjustments of a worker, in seconds.
+	pub retarget_secs: u64,
+	/// Allowed deviation from the target share time before retargeting, in percent.
+	pub variance_percent: u64,
+}
+
+impl Default for VardiffConfig {
+	fn default() -> Self {
+		VardiffConfig {
+			initial_difficulty: 1 << 32,
+			min_difficulty: 1 << 24,
+			max_difficulty: 1 << 48,
+			target_share_secs: 10,
+			retarget_secs: 60,
+			variance_percent: 30,
+		}
+	}
+}
+
 #[binary]
 pub struct ServiceConfiguration {
 	pub listen_addr: String,
 	pub secret: Option<H256>,
+	pub vardiff: Option<VardiffConfig>,
 }
 
 impl IpcConfig for PushWorkHandler { }
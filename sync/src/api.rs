@@ -20,7 +20,7 @@ use std::io;
 use util::Bytes;
 use network::{NetworkProtocolHandler, NetworkService, NetworkContext, PeerId, ProtocolId,
 	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, NetworkError,
-	AllowIP as NetworkAllowIP};
+	AllowIP as NetworkAllowIP, IpFilter, capability_topic};
 use util::{U256, H256, H512};
 use io::{TimerToken};
 use ethcore::client::{BlockChainClient, ChainNotify};
@@ -28,12 +28,12 @@ use ethcore::snapshot::SnapshotService;
 use ethcore::header::BlockNumber;
 use sync_io::NetSyncIo;
 use chain::{ChainSync, SyncStatus};
-use std::net::{SocketAddr, AddrParseError};
+use std::net::SocketAddr;
 use ipc::{BinaryConvertable, BinaryConvertError, IpcConfig};
 use std::str::FromStr;
 use parking_lot::RwLock;
 use chain::{ETH_PACKET_COUNT, SNAPSHOT_SYNC_PACKET_COUNT};
-use light::net::{LightProtocol, Params as LightParams, Capabilities, Handler as LightHandler, EventContext};
+use light::net::{LightProtocol, Params as LightParams, Capabilities, Handler as LightHandler, EventContext, BasicContext};
 
 /// Parity sync protocol
 pub const WARP_SYNC_PROTOCOL_ID: ProtocolId = *b"par";
@@ -59,6 +59,8 @@ pub struct SyncConfig {
 	pub fork_block: Option<(BlockNumber, H256)>,
 	/// Enable snapshot sync
 	pub warp_sync: bool,
+	/// Enable state download via `GET_NODE_DATA` when no snapshot is available
+	pub fast_sync: bool,
 	/// Enable light client server.
 	pub serve_light: bool,
 }
@@ -73,6 +75,7 @@ impl Default for SyncConfig {
 			light_subprotocol_name: LES_PROTOCOL,
 			fork_block: None,
 			warp_sync: false,
+			fast_sync: false,
 			serve_light: false,
 		}
 	}
@@ -128,6 +131,16 @@ pub struct PeerInfo {
 	pub eth_difficulty: Option<U256>,
 }
 
+/// Banned peer information
+#[derive(Debug)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct BannedPeer {
+	/// Public node id
+	pub id: String,
+	/// Unix timestamp the ban expires at
+	pub until: u64,
+}
+
 /// EthSync initialization parameters.
 #[cfg_attr(feature = "ipc", derive(Binary))]
 pub struct Params {
@@ -176,14 +189,24 @@ impl EthSync {
 				};
 
 				let mut light_proto = LightProtocol::new(params.provider, light_params);
-				light_proto.add_handler(Box::new(TxRelay(params.chain.clone())));
+				light_proto.add_handler(Arc::new(TxRelay(params.chain.clone())));
 
 				Arc::new(light_proto)
 			})
 		};
 
+		let mut network_config = params.network_config.clone().into_basic()?;
+		if network_config.discovery_v5 {
+			let genesis = params.chain.chain_info().genesis_hash;
+			network_config.advertised_topics.push(capability_topic(params.config.subprotocol_name, &genesis));
+			network_config.searched_topics.push(capability_topic(params.config.subprotocol_name, &genesis));
+			if light_proto.is_some() {
+				network_config.advertised_topics.push(capability_topic(params.config.light_subprotocol_name, &genesis));
+			}
+		}
+
 		let chain_sync = ChainSync::new(params.config, &*params.chain);
-		let service = NetworkService::new(params.network_config.clone().into_basic()?)?;
+		let service = NetworkService::new(network_config)?;
 
 		let sync = Arc::new(EthSync {
 			network: service,
@@ -380,6 +403,14 @@ pub trait ManageNetwork : Send + Sync {
 	fn remove_reserved_peer(&self, peer: String) -> Result<(), String>;
 	/// Add reserved peer
 	fn add_reserved_peer(&self, peer: String) -> Result<(), String>;
+	/// List peers banned for misbehaviour
+	fn banned_peers(&self) -> Vec<BannedPeer>;
+	/// Lift the ban on a peer. Returns `false` if the peer was not banned
+	fn unban_peer(&self, peer: String) -> Result<bool, String>;
+	/// Lift all peer bans
+	fn clear_banned_peers(&self);
+	/// Replace the IP filter. Takes allow and deny lists in the `--allow-ips`/`--deny-ips` format
+	fn set_ip_filter(&self, allow: String, deny: String) -> Result<(), String>;
 	/// Start network
 	fn start_network(&self);
 	/// Stop network
@@ -407,6 +438,24 @@ impl ManageNetwork for EthSync {
 		self.network.add_reserved_peer(&peer).map_err(|e| format!("{:?}", e))
 	}
 
+	fn banned_peers(&self) -> Vec<BannedPeer> {
+		self.network.banned_peers().into_iter().map(|(id, until)| BannedPeer { id: id.hex(), until: until }).collect()
+	}
+
+	fn unban_peer(&self, peer: String) -> Result<bool, String> {
+		self.network.unban_peer(&peer).map_err(|e| format!("{:?}", e))
+	}
+
+	fn clear_banned_peers(&self) {
+		self.network.clear_banned_peers();
+	}
+
+	fn set_ip_filter(&self, allow: String, deny: String) -> Result<(), String> {
+		let filter = IpFilter::parse(&allow, &deny).map_err(|e| format!("{:?}", e))?;
+		self.network.set_ip_filter(filter);
+		Ok(())
+	}
+
 	fn start_network(&self) {
 		self.start();
 	}
@@ -425,7 +474,138 @@ impl ManageNetwork for EthSync {
 	}
 
 	fn network_config(&self) -> NetworkConfiguration {
-		NetworkConfiguration::from(self.network.config().clone())
+		NetworkConfiguration::from(self.network.config())
+	}
+}
+
+/// Something which can dispatch requests over the light protocol.
+pub trait LightNetworkDispatcher {
+	/// Execute a closure with a protocol context.
+	/// Returns `None` if the network or the light protocol isn't available.
+	fn with_context<F, T>(&self, f: F) -> Option<T> where F: FnOnce(&BasicContext) -> T;
+}
+
+/// Configuration for the light sync service.
+pub struct LightSyncParams {
+	/// Network configuration.
+	pub network_config: BasicNetworkConfiguration,
+	/// Light data provider.
+	pub client: Arc<::light::Provider>,
+	/// Network ID.
+	pub network_id: u64,
+	/// Subprotocol name.
+	pub subprotocol_name: [u8; 3],
+	/// Other handlers to attach, e.g. the on-demand request service.
+	pub handlers: Vec<Arc<LightHandler>>,
+}
+
+/// Service for light synchronization.
+pub struct LightSync {
+	proto: Arc<LightProtocol>,
+	network: NetworkService,
+	subprotocol_name: [u8; 3],
+}
+
+impl LightSync {
+	/// Create a new light sync service.
+	pub fn new(params: LightSyncParams) -> Result<Self, NetworkError> {
+		let light_params = LightParams {
+			network_id: params.network_id,
+			flow_params: Default::default(),
+			capabilities: Capabilities {
+				serve_headers: false,
+				serve_chain_since: None,
+				serve_state_since: None,
+				tx_relay: false,
+			},
+		};
+
+		let mut network_config = params.network_config;
+		if network_config.discovery_v5 {
+			let genesis = params.client.chain_info().genesis_hash;
+			network_config.searched_topics.push(capability_topic(params.subprotocol_name, &genesis));
+		}
+
+		let mut light_proto = LightProtocol::new(params.client, light_params);
+		for handler in params.handlers {
+			light_proto.add_handler(handler);
+		}
+
+		let service = NetworkService::new(network_config)?;
+
+		Ok(LightSync {
+			proto: Arc::new(light_proto),
+			network: service,
+			subprotocol_name: params.subprotocol_name,
+		})
+	}
+}
+
+impl LightNetworkDispatcher for LightSync {
+	fn with_context<F, T>(&self, f: F) -> Option<T> where F: FnOnce(&BasicContext) -> T {
+		// the network service only accepts `Fn`, but will call it exactly once.
+		let f = ::std::cell::RefCell::new(Some(f));
+		self.network.with_context_eval(self.subprotocol_name, |ctx| {
+			f.borrow_mut().take().map(|f| self.proto.with_context(ctx, f))
+		}).and_then(|x| x)
+	}
+}
+
+impl ManageNetwork for LightSync {
+	fn accept_unreserved_peers(&self) {
+		self.network.set_non_reserved_mode(NonReservedPeerMode::Accept);
+	}
+
+	fn deny_unreserved_peers(&self) {
+		self.network.set_non_reserved_mode(NonReservedPeerMode::Deny);
+	}
+
+	fn remove_reserved_peer(&self, peer: String) -> Result<(), String> {
+		self.network.remove_reserved_peer(&peer).map_err(|e| format!("{:?}", e))
+	}
+
+	fn add_reserved_peer(&self, peer: String) -> Result<(), String> {
+		self.network.add_reserved_peer(&peer).map_err(|e| format!("{:?}", e))
+	}
+
+	fn banned_peers(&self) -> Vec<BannedPeer> {
+		self.network.banned_peers().into_iter().map(|(id, until)| BannedPeer { id: id.hex(), until: until }).collect()
+	}
+
+	fn unban_peer(&self, peer: String) -> Result<bool, String> {
+		self.network.unban_peer(&peer).map_err(|e| format!("{:?}", e))
+	}
+
+	fn clear_banned_peers(&self) {
+		self.network.clear_banned_peers();
+	}
+
+	fn set_ip_filter(&self, allow: String, deny: String) -> Result<(), String> {
+		let filter = IpFilter::parse(&allow, &deny).map_err(|e| format!("{:?}", e))?;
+		self.network.set_ip_filter(filter);
+		Ok(())
+	}
+
+	fn start_network(&self) {
+		match self.network.start() {
+			Err(NetworkError::StdIo(ref e)) if  e.kind() == io::ErrorKind::AddrInUse => warn!("Network port {:?} is already in use, make sure that another instance of an Ethereum client is not running or change the port using the --port option.", self.network.config().listen_address.expect("Listen address is not set.")),
+			Err(err) => warn!("Error starting network: {}", err),
+			_ => {},
+		}
+
+		self.network.register_protocol(self.proto.clone(), self.subprotocol_name, ::light::net::PACKET_COUNT, ::light::net::PROTOCOL_VERSIONS)
+			.unwrap_or_else(|e| warn!("Error registering light client protocol: {:?}", e));
+	}
+
+	fn stop_network(&self) {
+		self.proto.abort();
+		if let Err(e) = self.network.stop() {
+			warn!("Error stopping network: {}", e);
+		}
+	}
+
+	fn network_config(&self) -> NetworkConfiguration {
+		NetworkConfiguration::from(self.network.config())
 	}
 }
 
@@ -439,6 +619,19 @@ pub enum AllowIP {
 	Private,
 	/// Connect to public network only
 	Public,
+	/// Connect only to explicitly allowed networks
+	None,
+}
+
+impl From<NetworkAllowIP> for AllowIP {
+	fn from(other: NetworkAllowIP) -> Self {
+		match other {
+			NetworkAllowIP::All => AllowIP::All,
+			NetworkAllowIP::Private => AllowIP::Private,
+			NetworkAllowIP::Public => AllowIP::Public,
+			NetworkAllowIP::None => AllowIP::None,
+		}
+	}
 }
 
 impl AllowIP {
@@ -448,6 +641,7 @@ impl AllowIP {
 			"all" => Some(AllowIP::All),
 			"private" => Some(AllowIP::Private),
 			"public" => Some(AllowIP::Public),
+			"none" => Some(AllowIP::None),
 			_ => None,
 		}
 	}
@@ -471,6 +665,8 @@ pub struct NetworkConfiguration {
 	pub nat_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Enable discovery v5 topics. Nodes advertise and look for peers serving this chain.
+	pub discovery_v5: bool,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -489,6 +685,12 @@ pub struct NetworkConfiguration {
 	pub allow_non_reserved: bool,
 	/// IP Filtering
 	pub allow_ips: AllowIP,
+	/// Networks in CIDR notation allowed in addition to `allow_ips`
+	pub allow_networks: Vec<String>,
+	/// Networks in CIDR notation never connected to
+	pub deny_networks: Vec<String>,
+	/// Number of seconds misbehaving peers stay banned
+	pub ban_duration: u64,
 }
 
 impl NetworkConfiguration {
@@ -502,8 +704,17 @@ impl NetworkConfiguration {
 		From::from(BasicNetworkConfiguration::new_local())
 	}
 
+	/// Parse `allow` and `deny` IP filter lists and apply them to this config.
+	pub fn set_ip_filter(&mut self, allow: &str, deny: &str) -> Result<(), NetworkError> {
+		let filter = IpFilter::parse(allow, deny)?;
+		self.allow_ips = filter.predefined.into();
+		self.allow_networks = filter.custom_allow.iter().map(|n| n.to_string()).collect();
+		self.deny_networks = filter.custom_block.iter().map(|n| n.to_string()).collect();
+		Ok(())
+	}
+
 	/// Attempt to convert this config into a BasicNetworkConfiguration.
-	pub fn into_basic(self) -> Result<BasicNetworkConfiguration, AddrParseError> {
+	pub fn into_basic(self) -> Result<BasicNetworkConfiguration, NetworkError> {
 		Ok(BasicNetworkConfiguration {
 			config_path: self.config_path,
 			net_config_path: self.net_config_path,
@@ -512,6 +723,9 @@ impl NetworkConfiguration {
 			udp_port: self.udp_port,
 			nat_enabled: self.nat_enabled,
 			discovery_enabled: self.discovery_enabled,
+			discovery_v5: self.discovery_v5,
+			advertised_topics: Vec::new(),
+			searched_topics: Vec::new(),
 			boot_nodes: self.boot_nodes,
 			use_secret: self.use_secret,
 			max_peers: self.max_peers,
@@ -519,12 +733,18 @@ impl NetworkConfiguration {
 			max_handshakes: self.max_pending_peers,
 			reserved_protocols: hash_map![WARP_SYNC_PROTOCOL_ID => self.snapshot_peers],
 			reserved_nodes: self.reserved_nodes,
-			allow_ips: match self.allow_ips {
-				AllowIP::All => NetworkAllowIP::All,
-				AllowIP::Private => NetworkAllowIP::Private,
-				AllowIP::Public => NetworkAllowIP::Public,
+			ip_filter: IpFilter {
+				predefined: match self.allow_ips {
+					AllowIP::All => NetworkAllowIP::All,
+					AllowIP::Private => NetworkAllowIP::Private,
+					AllowIP::Public => NetworkAllowIP::Public,
+					AllowIP::None => NetworkAllowIP::None,
+				},
+				custom_allow: self.allow_networks.iter().map(|n| n.parse()).collect::<Result<_, _>>()?,
+				custom_block: self.deny_networks.iter().map(|n| n.parse()).collect::<Result<_, _>>()?,
 			},
 			non_reserved_mode: if self.allow_non_reserved { NonReservedPeerMode::Accept } else { NonReservedPeerMode::Deny },
+			ban_duration: self.ban_duration,
 		})
 	}
 }
@@ -539,6 +759,7 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			udp_port: other.udp_port,
 			nat_enabled: other.nat_enabled,
 			discovery_enabled: other.discovery_enabled,
+			discovery_v5: other.discovery_v5,
 			boot_nodes: other.boot_nodes,
 			use_secret: other.use_secret,
 			max_peers: other.max_peers,
@@ -546,12 +767,11 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			max_pending_peers: other.max_handshakes,
 			snapshot_peers: *other.reserved_protocols.get(&WARP_SYNC_PROTOCOL_ID).unwrap_or(&0),
 			reserved_nodes: other.reserved_nodes,
-			allow_ips: match other.allow_ips {
-				NetworkAllowIP::All => AllowIP::All,
-				NetworkAllowIP::Private => AllowIP::Private,
-				NetworkAllowIP::Public => AllowIP::Public,
-			},
+			allow_ips: other.ip_filter.predefined.into(),
+			allow_networks: other.ip_filter.custom_allow.iter().map(|n| n.to_string()).collect(),
+			deny_networks: other.ip_filter.custom_block.iter().map(|n| n.to_string()).collect(),
 			allow_non_reserved: match other.non_reserved_mode { NonReservedPeerMode::Accept => true, _ => false } ,
+			ban_duration: other.ban_duration,
 		}
 	}
 }
@@ -567,3 +787,39 @@ pub struct ServiceConfiguration {
 	/// IPC path.
 	pub io_path: String,
 }
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use devtools::RandomTempPath;
+	use ethcore::spec::Spec;
+	use io::IoChannel;
+	use light::client::Client as LightClient;
+	use network::{NetworkConfiguration as BasicNetworkConfiguration, capability_topic};
+	use util::Database;
+	use super::{LightSync, LightSyncParams, LES_PROTOCOL};
+
+	#[test]
+	fn light_sync_searches_for_les_topic() {
+		let spec = Spec::new_test();
+		let path = RandomTempPath::create_dir();
+		let db = Arc::new(Database::open_default(path.as_str()).unwrap());
+		let client = LightClient::new(Default::default(), db, &spec, IoChannel::disconnected()).unwrap();
+		let genesis = spec.genesis_header().hash();
+
+		let mut network_config = BasicNetworkConfiguration::new_local();
+		network_config.discovery_v5 = true;
+
+		let sync = LightSync::new(LightSyncParams {
+			network_config: network_config,
+			client: Arc::new(client),
+			network_id: 1,
+			subprotocol_name: LES_PROTOCOL,
+			handlers: Vec::new(),
+		}).unwrap();
+
+		let config = sync.network.config();
+		assert_eq!(config.searched_topics, vec![capability_topic(LES_PROTOCOL, &genesis)]);
+		assert!(config.advertised_topics.is_empty());
+	}
+}
@@ -101,6 +101,7 @@ use time;
 use super::SyncConfig;
 use block_sync::{BlockDownloader, BlockRequest, BlockDownloaderImportError as DownloaderImportError, DownloadAction};
 use snapshot::{Snapshot, ChunkType};
+use state_download::StateDownload;
 use rand::{thread_rng, Rng};
 use api::{PeerInfo as PeerInfoDigest, WARP_SYNC_PROTOCOL_ID};
 use transactions_stats::{TransactionsStats, Stats as TransactionStats};
@@ -128,6 +129,9 @@ const MAX_TRANSACTION_SIZE: usize = 300*1024;
 // Min number of blocks to be behind for a snapshot sync
 const SNAPSHOT_RESTORE_THRESHOLD: BlockNumber = 100000;
 const SNAPSHOT_MIN_PEERS: usize = 3;
+// Chains shorter than this are fully synced rather than state downloaded
+const STATE_DOWNLOAD_THRESHOLD: BlockNumber = 1000;
+const MAX_NODE_DATA_TO_REQUEST: usize = 384;
 
 const STATUS_PACKET: u8 = 0x00;
 const NEW_BLOCK_HASHES_PACKET: u8 = 0x01;
@@ -163,6 +167,16 @@ const RECEIPTS_TIMEOUT_SEC: u64 = 10;
 const FORK_HEADER_TIMEOUT_SEC: u64 = 3;
 const SNAPSHOT_MANIFEST_TIMEOUT_SEC: u64 = 3;
 const SNAPSHOT_DATA_TIMEOUT_SEC: u64 = 60;
+const PIVOT_BLOCK_TIMEOUT_SEC: u64 = 10;
+const NODE_DATA_TIMEOUT_SEC: u64 = 10;
+
+// Reputation weights reported to the network host. A peer is banned once it accumulates -100.
+const REPUTATION_BAD_BLOCK: i32 = -100;
+const REPUTATION_INVALID_DATA: i32 = -50;
+const REPUTATION_MALFORMED_PACKET: i32 = -20;
+const REPUTATION_ANCIENT_BLOCK: i32 = -10;
+const REPUTATION_OVERSIZED_TRANSACTION: i32 = -10;
+const REPUTATION_TIMEOUT: i32 = -10;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Sync state
@@ -175,6 +189,10 @@ pub enum SyncState {
 	SnapshotData,
 	/// Waiting for snapshot restoration progress.
 	SnapshotWaiting,
+	/// Downloading the pivot block for state download
+	StatePivot,
+	/// Downloading state trie nodes
+	StateData,
 	/// Downloading new blocks
 	Blocks,
 	/// Initial chain sync complete. Waiting for new packets
@@ -224,6 +242,8 @@ impl SyncStatus {
 		self.state == SyncState::SnapshotManifest
 			|| self.state == SyncState::SnapshotData
 			|| self.state == SyncState::SnapshotWaiting
+			|| self.state == SyncState::StatePivot
+			|| self.state == SyncState::StateData
 	}
 
 	/// Returns max no of peers to display in informants
@@ -253,6 +273,8 @@ enum PeerAsking {
 	BlockReceipts,
 	SnapshotManifest,
 	SnapshotData,
+	PivotBlock,
+	NodeData,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -288,7 +310,7 @@ struct PeerInfo {
 	difficulty: Option<U256>,
 	/// Type of data currenty being requested from peer.
 	asking: PeerAsking,
-	/// A set of block numbers being requested
+	/// A set of block or state node hashes being requested
 	asking_blocks: Vec<H256>,
 	/// Holds requested header hash if currently requesting block header by hash
 	asking_hash: Option<H256>,
@@ -363,6 +385,12 @@ pub struct ChainSync {
 	transactions_stats: TransactionsStats,
 	/// Enable ancient block downloading
 	download_old_blocks: bool,
+	/// State downloader.
+	state_download: StateDownload,
+	/// Enable snapshot sync
+	warp_sync: bool,
+	/// Enable state download when no snapshot is available
+	fast_sync: bool,
 }
 
 type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
@@ -373,7 +401,7 @@ impl ChainSync {
 		let chain_info = chain.chain_info();
 		let pruning = chain.pruning_info();
 		let mut sync = ChainSync {
-			state: if config.warp_sync { SyncState::WaitingPeers } else { SyncState::Idle },
+			state: if config.warp_sync || config.fast_sync { SyncState::WaitingPeers } else { SyncState::Idle },
 			starting_block: chain.chain_info().best_block_number,
 			highest_block: None,
 			peers: HashMap::new(),
@@ -386,6 +414,9 @@ impl ChainSync {
 			fork_block: config.fork_block,
 			download_old_blocks: config.download_old_blocks,
 			snapshot: Snapshot::new(),
+			state_download: StateDownload::new(),
+			warp_sync: config.warp_sync,
+			fast_sync: config.fast_sync,
 			sync_start_time: None,
 			transactions_stats: TransactionsStats::default(),
 		};
@@ -464,6 +495,11 @@ impl ChainSync {
 			debug!(target:"sync", "Aborting snapshot restore");
 			io.snapshot_service().abort_restore();
 		}
+		if self.state == SyncState::StateData {
+			debug!(target:"sync", "Aborting state download");
+			io.snapshot_service().abort_restore();
+		}
+		self.state_download.clear();
 		for (_, ref mut p) in &mut self.peers {
 			if p.block_set != Some(BlockSet::OldBlocks) {
 				p.reset_asking();
@@ -505,7 +541,7 @@ impl ChainSync {
 		let (best_hash, max_peers, snapshot_peers) = {
 			//collect snapshot infos from peers
 			let snapshots = self.peers.iter()
-				.filter(|&(_, p)| p.is_allowed() && p.snapshot_number.map_or(false, |sn|
+				.filter(|&(_, p)| self.warp_sync && p.is_allowed() && p.snapshot_number.map_or(false, |sn|
 					our_best_block < sn && (sn - our_best_block) > SNAPSHOT_RESTORE_THRESHOLD &&
 					sn > fork_block &&
 					self.highest_block.map_or(true, |highest| highest >= sn && (highest - sn) <= SNAPSHOT_RESTORE_THRESHOLD)
@@ -536,6 +572,9 @@ impl ChainSync {
 				trace!(target: "sync", "Starting unconfirmed snapshot sync {:?} with {:?}", hash, peers);
 				self.start_snapshot_sync(io, peers);
 			}
+		} else if self.fast_sync && (timeout || !self.warp_sync) && self.state_download_peer(io).is_some() {
+			trace!(target: "sync", "No snapshots found, starting state download");
+			self.start_state_download(io);
 		} else if timeout {
 			trace!(target: "sync", "No snapshots found, starting full sync");
 			self.state = SyncState::Idle;
@@ -553,6 +592,39 @@ impl ChainSync {
 		self.state = SyncState::SnapshotManifest;
 	}
 
+	/// Find an idle peer with the highest total difficulty above ours, along with its best block hash.
+	fn state_download_peer(&self, io: &SyncIo) -> Option<(PeerId, U256, H256)> {
+		let our_difficulty = io.chain().chain_info().total_difficulty;
+		self.peers.iter()
+			.filter(|&(_, p)| p.can_sync() && p.asking == PeerAsking::Nothing && p.protocol_version != PROTOCOL_VERSION_62)
+			.filter_map(|(id, p)| p.difficulty.map(|d| (*id, d, p.latest_hash)))
+			.filter(|&(_, d, _)| d > our_difficulty)
+			.max_by_key(|&(_, d, _)| d)
+	}
+
+	/// Pick the best block of the best peer as the pivot and start downloading its state.
+	/// Nodes already fetched for a previous pivot are kept by the snapshot service.
+	fn start_state_download(&mut self, io: &mut SyncIo) {
+		self.active_peers = self.peers.keys().cloned().collect();
+		match self.state_download_peer(io) {
+			Some((peer_id, difficulty, hash)) => {
+				trace!(target: "sync", "Starting state download at {:?} with {}", hash, peer_id);
+				self.state_download.reset_to(&hash, difficulty);
+				self.state = SyncState::StatePivot;
+				self.request_pivot_block(io, peer_id);
+			},
+			None => {
+				trace!(target: "sync", "No peers to download state from, starting full sync");
+				if self.state == SyncState::StateData {
+					io.snapshot_service().abort_restore();
+				}
+				self.state_download.clear();
+				self.state = SyncState::Idle;
+				self.continue_sync(io);
+			}
+		}
+	}
+
 	/// Restart sync disregarding the block queue status. May end up re-downloading up to QUEUE_SIZE blocks
 	pub fn restart(&mut self, io: &mut SyncIo) {
 		self.update_targets(io.chain());
@@ -682,6 +754,9 @@ impl ChainSync {
 			self.sync_peer(io, peer_id, false);
 			return Ok(());
 		}
+		if self.peers.get(&peer_id).map_or(false, |p| p.asking == PeerAsking::PivotBlock) {
+			return self.on_peer_pivot_block(io, peer_id, BLOCK_HEADERS_PACKET, r);
+		}
 
 		self.clear_peer_download(peer_id);
 		let expected_hash = self.peers.get(&peer_id).and_then(|p| p.asking_hash);
@@ -727,6 +802,7 @@ impl ChainSync {
 				self.deactivate_peer(io, peer_id);
 			},
 			Err(DownloaderImportError::Invalid) => {
+				io.report_peer(peer_id, REPUTATION_INVALID_DATA);
 				io.disable_peer(peer_id);
 				self.deactivate_peer(io, peer_id);
 				self.continue_sync(io);
@@ -753,6 +829,9 @@ impl ChainSync {
 
 	/// Called by peer once it has new block bodies
 	fn on_peer_block_bodies(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		if self.peers.get(&peer_id).map_or(false, |p| p.asking == PeerAsking::PivotBlock) {
+			return self.on_peer_pivot_block(io, peer_id, BLOCK_BODIES_PACKET, r);
+		}
 		self.clear_peer_download(peer_id);
 		let block_set = self.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
 		if !self.reset_peer_asking(peer_id, PeerAsking::BlockBodies) {
@@ -787,6 +866,7 @@ impl ChainSync {
 
 			match result {
 				Err(DownloaderImportError::Invalid) => {
+					io.report_peer(peer_id, REPUTATION_INVALID_DATA);
 					io.disable_peer(peer_id);
 					self.deactivate_peer(io, peer_id);
 					self.continue_sync(io);
@@ -807,6 +887,9 @@ impl ChainSync {
 
 	/// Called by peer once it has new block receipts
 	fn on_peer_block_receipts(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		if self.peers.get(&peer_id).map_or(false, |p| p.asking == PeerAsking::PivotBlock) {
+			return self.on_peer_pivot_block(io, peer_id, RECEIPTS_PACKET, r);
+		}
 		self.clear_peer_download(peer_id);
 		let block_set = self.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
 		if !self.reset_peer_asking(peer_id, PeerAsking::BlockReceipts) {
@@ -841,6 +924,7 @@ impl ChainSync {
 
 			match result {
 				Err(DownloaderImportError::Invalid) => {
+					io.report_peer(peer_id, REPUTATION_INVALID_DATA);
 					io.disable_peer(peer_id);
 					self.deactivate_peer(io, peer_id);
 					self.continue_sync(io);
@@ -859,6 +943,117 @@ impl ChainSync {
 		Ok(())
 	}
 
+	/// Called by peer once it has the header, body or receipts of the state download pivot block
+	fn on_peer_pivot_block(&mut self, io: &mut SyncIo, peer_id: PeerId, packet_id: PacketId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.reset_peer_asking(peer_id, PeerAsking::PivotBlock);
+		let item_count = r.item_count();
+		trace!(target: "sync", "{} -> PivotBlock packet {} ({} entries)", peer_id, packet_id, item_count);
+		if self.state != SyncState::StatePivot {
+			trace!(target: "sync", "{}: Ignored unexpected pivot block data", peer_id);
+			self.continue_sync(io);
+			return Ok(());
+		}
+		if item_count == 0 {
+			self.deactivate_peer(io, peer_id);
+			self.continue_sync(io);
+			return Ok(());
+		}
+
+		let data = r.at(0)?;
+		let result = match packet_id {
+			BLOCK_HEADERS_PACKET => self.state_download.import_header(data.as_raw()),
+			BLOCK_BODIES_PACKET => self.state_download.import_body(data.as_raw()),
+			_ => self.state_download.import_receipts(data.as_raw()),
+		};
+		match result {
+			Err(DownloaderImportError::Invalid) => {
+				io.report_peer(peer_id, REPUTATION_INVALID_DATA);
+				io.disable_peer(peer_id);
+				self.deactivate_peer(io, peer_id);
+				self.continue_sync(io);
+				return Ok(());
+			},
+			Err(DownloaderImportError::Useless) => {
+				self.deactivate_peer(io, peer_id);
+			},
+			Ok(()) => (),
+		}
+
+		let pivot_number = self.state_download.pivot_header().map(|h| h.number());
+		if pivot_number.map_or(false, |n| n <= io.chain().chain_info().best_block_number + STATE_DOWNLOAD_THRESHOLD) {
+			trace!(target: "sync", "Pivot block is too close to our best block, starting full sync");
+			self.state_download.clear();
+			self.state = SyncState::Idle;
+			self.continue_sync(io);
+			return Ok(());
+		}
+		if let Some((block, receipts, total_difficulty)) = self.state_download.pivot_block() {
+			trace!(target: "sync", "Downloading state at #{:?}", pivot_number);
+			io.snapshot_service().begin_state_download(block, receipts, total_difficulty);
+			self.state = SyncState::StateData;
+			self.check_state_download(io);
+		}
+		self.sync_peer(io, peer_id, false);
+		self.continue_sync(io);
+		Ok(())
+	}
+
+	/// Called by peer once it has state trie nodes
+	fn on_peer_node_data(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.clear_peer_download(peer_id);
+		let requested = self.peers.get(&peer_id).map_or_else(Vec::new, |p| p.asking_blocks.clone());
+		if !self.reset_peer_asking(peer_id, PeerAsking::NodeData) || self.state != SyncState::StateData {
+			trace!(target: "sync", "{}: Ignored unexpected node data", peer_id);
+			self.continue_sync(io);
+			return Ok(());
+		}
+		trace!(target: "sync", "{} -> NodeData ({} entries)", peer_id, r.item_count());
+
+		match self.state_download.validate_nodes(&requested, r) {
+			Err(_) => {
+				io.report_peer(peer_id, REPUTATION_INVALID_DATA);
+				io.disable_peer(peer_id);
+				self.deactivate_peer(io, peer_id);
+				self.continue_sync(io);
+				return Ok(());
+			},
+			Ok(ref nodes) if nodes.is_empty() => {
+				self.deactivate_peer(io, peer_id);
+			},
+			Ok(nodes) => {
+				io.snapshot_service().restore_state_nodes(nodes);
+			},
+		}
+
+		if self.state_download.is_stale() {
+			trace!(target: "sync", "Pivot state is no longer served, picking a new pivot");
+			self.start_state_download(io);
+			return Ok(());
+		}
+		self.check_state_download(io);
+		self.sync_peer(io, peer_id, false);
+		self.continue_sync(io);
+		Ok(())
+	}
+
+	/// Check the snapshot service for the state download outcome.
+	fn check_state_download(&mut self, io: &mut SyncIo) {
+		match io.snapshot_service().status() {
+			RestorationStatus::Inactive if io.snapshot_service().state_download_complete() => {
+				trace!(target: "sync", "State download is complete");
+				self.state_download.clear();
+				self.state = SyncState::Idle;
+				self.restart(io);
+			},
+			RestorationStatus::Inactive | RestorationStatus::Failed => {
+				trace!(target: "sync", "State download failed or was aborted");
+				self.state_download.clear();
+				self.state = SyncState::WaitingPeers;
+			},
+			RestorationStatus::Ongoing { .. } => (),
+		}
+	}
+
 	/// Called by peer once it has new block bodies
 	#[cfg_attr(feature="dev", allow(cyclomatic_complexity))]
 	fn on_peer_new_block(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
@@ -889,6 +1084,7 @@ impl ChainSync {
 		let last_imported_number = self.new_blocks.last_imported_block_number();
 		if last_imported_number > header.number() && last_imported_number - header.number() > MAX_NEW_BLOCK_AGE {
 			trace!(target: "sync", "Ignored ancient new block {:?}", h);
+			io.report_peer(peer_id, REPUTATION_ANCIENT_BLOCK);
 			io.disable_peer(peer_id);
 			return Ok(());
 		}
@@ -911,6 +1107,7 @@ impl ChainSync {
 			},
 			Err(e) => {
 				debug!(target: "sync", "Bad new block {:?} : {:?}", h, e);
+				io.report_peer(peer_id, REPUTATION_BAD_BLOCK);
 				io.disable_peer(peer_id);
 			}
 		};
@@ -965,6 +1162,7 @@ impl ChainSync {
 			}
 			if last_imported_number > number && last_imported_number - number > MAX_NEW_BLOCK_AGE {
 				trace!(target: "sync", "Ignored ancient new block hash {:?}", hash);
+				io.report_peer(peer_id, REPUTATION_ANCIENT_BLOCK);
 				io.disable_peer(peer_id);
 				continue;
 			}
@@ -987,6 +1185,7 @@ impl ChainSync {
 				},
 				BlockStatus::Bad => {
 					debug!(target: "sync", "Bad new block hash {:?}", hash);
+					io.report_peer(peer_id, REPUTATION_BAD_BLOCK);
 					io.disable_peer(peer_id);
 					return Ok(());
 				}
@@ -1019,6 +1218,7 @@ impl ChainSync {
 		let manifest = match ManifestData::from_rlp(manifest_rlp.as_raw()) {
 			Err(e) => {
 				trace!(target: "sync", "{}: Ignored bad manifest: {:?}", peer_id, e);
+				io.report_peer(peer_id, REPUTATION_INVALID_DATA);
 				io.disconnect_peer(peer_id);
 				self.continue_sync(io);
 				return Ok(());
@@ -1075,6 +1275,7 @@ impl ChainSync {
 			}
 			Err(()) => {
 				trace!(target: "sync", "{}: Got bad snapshot chunk", peer_id);
+				io.report_peer(peer_id, REPUTATION_INVALID_DATA);
 				io.disconnect_peer(peer_id);
 				self.continue_sync(io);
 				return Ok(());
@@ -1129,6 +1330,16 @@ impl ChainSync {
 				self.sync_peer(io, p, false);
 			}
 		}
+		if (self.state == SyncState::StatePivot || self.state == SyncState::StateData)
+			&& !self.peers.values().any(|p| p.asking != PeerAsking::Nothing && p.can_sync()) {
+
+			// nodes still queued for import are not a reason to move the pivot.
+			if self.state == SyncState::StatePivot || !io.snapshot_service().missing_state_nodes(1).is_empty() {
+				// nobody is able to serve the current pivot
+				self.start_state_download(io);
+			}
+			return;
+		}
 		if (self.state != SyncState::WaitingPeers && self.state != SyncState::SnapshotWaiting && self.state != SyncState::Waiting && self.state != SyncState::Idle)
 			&& !self.peers.values().any(|p| p.asking != PeerAsking::Nothing && p.block_set != Some(BlockSet::OldBlocks) && p.can_sync()) {
 
@@ -1155,7 +1366,7 @@ impl ChainSync {
 			trace!(target: "sync", "Skipping deactivated peer {}", peer_id);
 			return;
 		}
-		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash) = {
+		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash, peer_protocol) = {
 			if let Some(peer) = self.peers.get_mut(&peer_id) {
 				if peer.asking != PeerAsking::Nothing || !peer.can_sync() {
 					trace!(target: "sync", "Skipping busy peer {}", peer_id);
@@ -1169,7 +1380,7 @@ impl ChainSync {
 					trace!(target: "sync", "Waiting for the snapshot restoration");
 					return;
 				}
-				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned())
+				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned(), peer.protocol_version)
 			} else {
 				return;
 			}
@@ -1222,6 +1433,16 @@ impl ChainSync {
 						self.request_snapshot_data(io, peer_id);
 					}
 				},
+				SyncState::StatePivot => {
+					if peer_protocol != PROTOCOL_VERSION_62 && !self.peers.values().any(|p| p.asking == PeerAsking::PivotBlock) {
+						self.request_pivot_block(io, peer_id);
+					}
+				},
+				SyncState::StateData => {
+					if peer_protocol != PROTOCOL_VERSION_62 {
+						self.request_node_data(io, peer_id);
+					}
+				},
 				SyncState::SnapshotManifest | //already downloading from other peer
 					SyncState::Waiting | SyncState::SnapshotWaiting => ()
 			}
@@ -1257,6 +1478,50 @@ impl ChainSync {
 		}
 	}
 
+	/// Request the next missing part of the pivot block from a peer.
+	fn request_pivot_block(&mut self, io: &mut SyncIo, peer_id: PeerId) {
+		let hash = match self.state_download.pivot_hash() {
+			Some(hash) => hash,
+			None => return,
+		};
+		if self.state_download.pivot_header().is_none() {
+			trace!(target: "sync", "{} <- GetBlockHeaders: pivot {}", peer_id, hash);
+			let mut rlp = RlpStream::new_list(4);
+			rlp.append(&hash);
+			rlp.append(&1u32);
+			rlp.append(&0u32);
+			rlp.append(&0u32);
+			self.send_request(io, peer_id, PeerAsking::PivotBlock, GET_BLOCK_HEADERS_PACKET, rlp.out());
+		} else if self.state_download.needs_body() {
+			trace!(target: "sync", "{} <- GetBlockBodies: pivot {}", peer_id, hash);
+			let mut rlp = RlpStream::new_list(1);
+			rlp.append(&hash);
+			self.send_request(io, peer_id, PeerAsking::PivotBlock, GET_BLOCK_BODIES_PACKET, rlp.out());
+		} else if self.state_download.needs_receipts() {
+			trace!(target: "sync", "{} <- GetReceipts: pivot {}", peer_id, hash);
+			let mut rlp = RlpStream::new_list(1);
+			rlp.append(&hash);
+			self.send_request(io, peer_id, PeerAsking::PivotBlock, GET_RECEIPTS_PACKET, rlp.out());
+		}
+	}
+
+	/// Request missing state trie nodes from a peer.
+	fn request_node_data(&mut self, io: &mut SyncIo, peer_id: PeerId) {
+		self.clear_peer_download(peer_id);
+		let hashes = self.state_download.needed_nodes(io.snapshot_service(), MAX_NODE_DATA_TO_REQUEST);
+		if hashes.is_empty() {
+			return;
+		}
+		trace!(target: "sync", "{} <- GetNodeData: {} entries starting from {:?}", peer_id, hashes.len(), hashes.first());
+		let mut rlp = RlpStream::new_list(hashes.len());
+		for h in &hashes {
+			rlp.append(h);
+		}
+		self.send_request(io, peer_id, PeerAsking::NodeData, GET_NODE_DATA_PACKET, rlp.out());
+		let peer = self.peers.get_mut(&peer_id).expect("peer_id may originate either from on_packet, where it is already validated or from enumerating self.peers. qed");
+		peer.asking_blocks = hashes;
+	}
+
 	/// Clear all blocks/headers marked as being downloaded by a peer.
 	fn clear_peer_download(&mut self, peer_id: PeerId) {
 		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
@@ -1286,6 +1551,9 @@ impl ChainSync {
 						self.snapshot.clear_chunk_download(&hash);
 					}
 				},
+				PeerAsking::NodeData => {
+					self.state_download.clear_node_download(&peer.asking_blocks);
+				},
 				_ => (),
 			}
 		}
@@ -1443,6 +1711,7 @@ impl ChainSync {
 			let rlp = r.at(i)?;
 			if rlp.as_raw().len() > MAX_TRANSACTION_SIZE {
 				debug!("Skipped oversized transaction of {} bytes", rlp.as_raw().len());
+				io.report_peer(peer_id, REPUTATION_OVERSIZED_TRANSACTION);
 				continue;
 			}
 			let tx = rlp.as_raw().to_vec();
@@ -1714,6 +1983,7 @@ impl ChainSync {
 		};
 		result.unwrap_or_else(|e| {
 			debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
+			io.report_peer(peer, REPUTATION_MALFORMED_PACKET);
 		})
 	}
 
@@ -1733,6 +2003,7 @@ impl ChainSync {
 			NEW_BLOCK_HASHES_PACKET => self.on_peer_new_hashes(io, peer, &rlp),
 			SNAPSHOT_MANIFEST_PACKET => self.on_snapshot_manifest(io, peer, &rlp),
 			SNAPSHOT_DATA_PACKET => self.on_snapshot_data(io, peer, &rlp),
+			NODE_DATA_PACKET => self.on_peer_node_data(io, peer, &rlp),
 			_ => {
 				debug!(target: "sync", "{}: Unknown packet {}", peer, packet_id);
 				Ok(())
@@ -1740,6 +2011,7 @@ impl ChainSync {
 		};
 		result.unwrap_or_else(|e| {
 			debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
+			io.report_peer(peer, REPUTATION_MALFORMED_PACKET);
 		})
 	}
 
@@ -1757,9 +2029,12 @@ impl ChainSync {
 				PeerAsking::ForkHeader => elapsed > FORK_HEADER_TIMEOUT_SEC,
 				PeerAsking::SnapshotManifest => elapsed > SNAPSHOT_MANIFEST_TIMEOUT_SEC,
 				PeerAsking::SnapshotData => elapsed > SNAPSHOT_DATA_TIMEOUT_SEC,
+				PeerAsking::PivotBlock => elapsed > PIVOT_BLOCK_TIMEOUT_SEC,
+				PeerAsking::NodeData => elapsed > NODE_DATA_TIMEOUT_SEC,
 			};
 			if timeout {
 				trace!(target:"sync", "Timeout {}", peer_id);
+				io.report_peer(*peer_id, REPUTATION_TIMEOUT);
 				io.disconnect_peer(*peer_id);
 				aborting.push(*peer_id);
 			}
@@ -1782,6 +2057,12 @@ impl ChainSync {
 		if self.state == SyncState::Waiting && !io.chain().queue_info().is_full() && self.state == SyncState::Waiting {
 			self.state = SyncState::Blocks;
 			self.continue_sync(io);
+		} else if self.state == SyncState::StateData {
+			// state nodes are imported asynchronously, so the outcome may only show up here.
+			self.check_state_download(io);
+			if self.state != SyncState::StateData {
+				self.continue_sync(io);
+			}
 		} else if self.state == SyncState::SnapshotWaiting {
 			match io.snapshot_service().status() {
 				RestorationStatus::Inactive => {
@@ -2597,6 +2878,23 @@ mod tests {
 		assert!(result.is_err());
 	}
 
+	#[test]
+	fn reports_malformed_packet() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+
+		let block_data = get_dummy_block(11, client.chain_info().best_block_hash);
+
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		sync.on_packet(&mut io, 0, NEW_BLOCK_PACKET, &block_data);
+
+		assert_eq!(io.reputations.get(&0), Some(&REPUTATION_MALFORMED_PACKET));
+	}
+
 	#[test]
 	fn handles_peer_new_block() {
 		let mut client = TestBlockChainClient::new();
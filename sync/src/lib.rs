@@ -56,6 +56,7 @@ mod blocks;
 mod block_sync;
 mod sync_io;
 mod snapshot;
+mod state_download;
 mod transactions_stats;
 
 #[cfg(test)]
@@ -71,7 +72,8 @@ mod api {
 mod api;
 
 pub use api::{EthSync, Params, SyncProvider, ManageNetwork, SyncConfig,
-	ServiceConfiguration, NetworkConfiguration, PeerInfo, AllowIP, TransactionStats};
+	ServiceConfiguration, NetworkConfiguration, PeerInfo, BannedPeer, AllowIP, TransactionStats,
+	LightSync, LightSyncParams, LightNetworkDispatcher};
 pub use chain::{SyncStatus, SyncState};
 pub use network::{is_valid_node_url, NonReservedPeerMode, NetworkError};
 
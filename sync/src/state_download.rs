@@ -0,0 +1,287 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State download: an alternative to snapshot sync for networks without snapshots.
+//!
+//! A recent block announced by a peer is picked as the pivot. Its header, body and
+//! receipts are fetched and checked against each other, after which the state trie
+//! at the pivot is fetched node by node with `GET_NODE_DATA` and handed to the
+//! snapshot service to be written into a fresh database.
+
+use std::collections::HashSet;
+use util::{Bytes, H256, U256, Hashable};
+use util::sha3::{SHA3_NULL_RLP, SHA3_EMPTY_LIST_RLP};
+use util::triehash::ordered_trie_root;
+use rlp::{self, RlpStream, Stream, UntrustedRlp, View};
+use ethcore::header::Header as BlockHeader;
+use ethcore::snapshot::SnapshotService;
+use block_sync::BlockDownloaderImportError as DownloaderImportError;
+
+/// Number of consecutive node requests answered with nothing useful before
+/// the pivot is considered pruned away by the network.
+const MAX_STALE_RESPONSES: usize = 16;
+
+pub struct StateDownload {
+	pivot_hash: Option<H256>,
+	total_difficulty: U256,
+	header: Option<BlockHeader>,
+	header_rlp: Bytes,
+	body: Option<Bytes>,
+	receipts: Option<Bytes>,
+	downloading_nodes: HashSet<H256>,
+	stale_responses: usize,
+}
+
+impl StateDownload {
+	/// Create a new instance.
+	pub fn new() -> StateDownload {
+		StateDownload {
+			pivot_hash: None,
+			total_difficulty: U256::zero(),
+			header: None,
+			header_rlp: Bytes::new(),
+			body: None,
+			receipts: None,
+			downloading_nodes: HashSet::new(),
+			stale_responses: 0,
+		}
+	}
+
+	/// Clear everything.
+	pub fn clear(&mut self) {
+		*self = StateDownload::new();
+	}
+
+	/// Reset to download the state at the block with given hash and total difficulty.
+	pub fn reset_to(&mut self, hash: &H256, total_difficulty: U256) {
+		self.clear();
+		self.pivot_hash = Some(hash.clone());
+		self.total_difficulty = total_difficulty;
+	}
+
+	/// Pivot block hash, if any.
+	pub fn pivot_hash(&self) -> Option<H256> {
+		self.pivot_hash
+	}
+
+	/// Pivot block header, once downloaded.
+	pub fn pivot_header(&self) -> Option<&BlockHeader> {
+		self.header.as_ref()
+	}
+
+	/// Validate the pivot header and keep it. Blocks with nothing in the body or no
+	/// receipts don't need them downloaded.
+	pub fn import_header(&mut self, header: &[u8]) -> Result<(), DownloaderImportError> {
+		if self.pivot_hash != Some(header.sha3()) {
+			return Err(DownloaderImportError::Invalid);
+		}
+		let info: BlockHeader = UntrustedRlp::new(header).as_val().map_err(|_| DownloaderImportError::Invalid)?;
+		if self.total_difficulty < *info.difficulty() {
+			return Err(DownloaderImportError::Invalid);
+		}
+
+		if *info.transactions_root() == SHA3_NULL_RLP && *info.uncles_hash() == SHA3_EMPTY_LIST_RLP {
+			let mut body_stream = RlpStream::new_list(2);
+			body_stream.append_raw(&rlp::EMPTY_LIST_RLP, 1);
+			body_stream.append_raw(&rlp::EMPTY_LIST_RLP, 1);
+			self.body = Some(body_stream.out());
+		}
+		if *info.receipts_root() == SHA3_NULL_RLP {
+			self.receipts = Some(RlpStream::new_list(0).out());
+		}
+		self.header_rlp = header.to_vec();
+		self.header = Some(info);
+		Ok(())
+	}
+
+	/// Validate the pivot body against the header and keep it.
+	pub fn import_body(&mut self, body: &[u8]) -> Result<(), DownloaderImportError> {
+		{
+			let header = match self.header {
+				Some(ref header) if self.body.is_none() => header,
+				_ => return Err(DownloaderImportError::Useless),
+			};
+			let rlp = UntrustedRlp::new(body);
+			let tx = rlp.at(0).map_err(|_| DownloaderImportError::Invalid)?;
+			let uncles = rlp.at(1).map_err(|_| DownloaderImportError::Invalid)?;
+			let tx_root = ordered_trie_root(tx.iter().map(|r| r.as_raw().to_vec()));
+			if tx_root != *header.transactions_root() || uncles.as_raw().sha3() != *header.uncles_hash() {
+				return Err(DownloaderImportError::Invalid);
+			}
+		}
+		self.body = Some(body.to_vec());
+		Ok(())
+	}
+
+	/// Validate the pivot receipts against the header and keep them.
+	pub fn import_receipts(&mut self, receipts: &[u8]) -> Result<(), DownloaderImportError> {
+		match self.header {
+			Some(ref header) if self.receipts.is_none() => {
+				let receipts_root = ordered_trie_root(UntrustedRlp::new(receipts).iter().map(|r| r.as_raw().to_vec()));
+				if receipts_root != *header.receipts_root() {
+					return Err(DownloaderImportError::Invalid);
+				}
+			}
+			_ => return Err(DownloaderImportError::Useless),
+		}
+		self.receipts = Some(receipts.to_vec());
+		Ok(())
+	}
+
+	/// Whether the pivot body still has to be downloaded.
+	pub fn needs_body(&self) -> bool {
+		self.header.is_some() && self.body.is_none()
+	}
+
+	/// Whether the pivot receipts still have to be downloaded.
+	pub fn needs_receipts(&self) -> bool {
+		self.header.is_some() && self.receipts.is_none()
+	}
+
+	/// Get the complete pivot block, its receipts and total difficulty once all have been downloaded.
+	pub fn pivot_block(&self) -> Option<(Bytes, Bytes, U256)> {
+		match (&self.body, &self.receipts) {
+			(&Some(ref body), &Some(ref receipts)) if self.header.is_some() => {
+				let body = UntrustedRlp::new(body);
+				let mut block = RlpStream::new_list(3);
+				block.append_raw(&self.header_rlp, 1);
+				block.append_raw(body.at(0).expect("body validated on import; qed").as_raw(), 1);
+				block.append_raw(body.at(1).expect("body validated on import; qed").as_raw(), 1);
+				Some((block.out(), receipts.clone(), self.total_difficulty))
+			}
+			_ => None,
+		}
+	}
+
+	/// Find state nodes to download, skipping those already being downloaded.
+	pub fn needed_nodes(&mut self, snapshot_service: &SnapshotService, count: usize) -> Vec<H256> {
+		let nodes: Vec<H256> = snapshot_service.missing_state_nodes(self.downloading_nodes.len() + count).into_iter()
+			.filter(|h| !self.downloading_nodes.contains(h))
+			.take(count)
+			.collect();
+		self.downloading_nodes.extend(nodes.iter().cloned());
+		nodes
+	}
+
+	/// Unmark nodes as being downloaded.
+	pub fn clear_node_download(&mut self, hashes: &[H256]) {
+		for h in hashes {
+			self.downloading_nodes.remove(h);
+		}
+	}
+
+	/// Check a `NODE_DATA` response only contains nodes from the request, and note whether
+	/// it had anything in it.
+	pub fn validate_nodes(&mut self, requested: &[H256], nodes: &UntrustedRlp) -> Result<Vec<Bytes>, DownloaderImportError> {
+		let mut valid = Vec::with_capacity(nodes.item_count());
+		for node in nodes.iter() {
+			let data: Bytes = node.as_val().map_err(|_| DownloaderImportError::Invalid)?;
+			if !requested.contains(&data.sha3()) {
+				return Err(DownloaderImportError::Invalid);
+			}
+			valid.push(data);
+		}
+
+		match valid.is_empty() {
+			true => self.stale_responses += 1,
+			false => self.stale_responses = 0,
+		}
+		Ok(valid)
+	}
+
+	/// Whether peers seem to no longer have the state at the pivot.
+	pub fn is_stale(&self) -> bool {
+		self.stale_responses > MAX_STALE_RESPONSES
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use util::*;
+	use rlp::*;
+	use super::*;
+	use ethcore::header::Header as BlockHeader;
+	use ethcore::views::BlockView;
+
+	fn test_header(receipts_root: H256) -> (Bytes, H256) {
+		let mut header = BlockHeader::new();
+		header.set_number(42);
+		header.set_difficulty(10.into());
+		header.set_receipts_root(receipts_root);
+		let rlp = ::rlp::encode(&header).to_vec();
+		let hash = rlp.sha3();
+		(rlp, hash)
+	}
+
+	#[test]
+	fn empty_block_needs_no_body_or_receipts() {
+		let (header, hash) = test_header(SHA3_NULL_RLP);
+		let mut download = StateDownload::new();
+		download.reset_to(&hash, 100.into());
+		assert!(download.pivot_block().is_none());
+		assert_eq!(download.import_header(&H256::random().to_vec()), Err(DownloaderImportError::Invalid));
+
+		download.import_header(&header).unwrap();
+		assert!(!download.needs_body());
+		assert!(!download.needs_receipts());
+
+		let (block, receipts, td) = download.pivot_block().unwrap();
+		assert_eq!(BlockView::new(&block).header_view().sha3(), hash);
+		assert_eq!(receipts, vec![0xc0]);
+		assert_eq!(td, 100.into());
+	}
+
+	#[test]
+	fn validates_receipts() {
+		let receipts = {
+			let mut s = RlpStream::new_list(1);
+			s.append_raw(&encode(&H256::random()), 1);
+			s.out()
+		};
+		let root = ordered_trie_root(UntrustedRlp::new(&receipts).iter().map(|r| r.as_raw().to_vec()));
+		let (header, hash) = test_header(root);
+		let mut download = StateDownload::new();
+		download.reset_to(&hash, 100.into());
+		download.import_header(&header).unwrap();
+		assert!(download.needs_receipts());
+		assert_eq!(download.import_receipts(&::rlp::EMPTY_LIST_RLP), Err(DownloaderImportError::Invalid));
+		download.import_receipts(&receipts).unwrap();
+		assert!(!download.needs_receipts());
+		assert!(download.pivot_block().is_some());
+	}
+
+	#[test]
+	fn validates_nodes() {
+		let nodes: Vec<Bytes> = (0..4).map(|_| H256::random().to_vec()).collect();
+		let requested: Vec<H256> = nodes.iter().map(|n| n.sha3()).collect();
+		let mut download = StateDownload::new();
+
+		let mut response = RlpStream::new_list(2);
+		response.append(&nodes[0]).append(&nodes[3]);
+		let response = response.out();
+		assert_eq!(download.validate_nodes(&requested, &UntrustedRlp::new(&response)).unwrap(), vec![nodes[0].clone(), nodes[3].clone()]);
+
+		let mut unrequested = RlpStream::new_list(1);
+		unrequested.append(&H256::random().to_vec());
+		assert!(download.validate_nodes(&requested, &UntrustedRlp::new(&unrequested.out())).is_err());
+
+		for _ in 0..super::MAX_STALE_RESPONSES + 1 {
+			assert!(!download.is_stale());
+			download.validate_nodes(&requested, &UntrustedRlp::new(&::rlp::EMPTY_LIST_RLP)).unwrap();
+		}
+		assert!(download.is_stale());
+	}
+}
@@ -24,10 +24,11 @@ use parking_lot::RwLock;
 
 /// IO interface for the syncing handler.
 /// Provides peer connection management and an interface to the blockchain client.
-// TODO: ratings
 pub trait SyncIo {
 	/// Disable a peer
 	fn disable_peer(&mut self, peer_id: PeerId);
+	/// Adjust peer reputation. Peers are banned once it drops below the network threshold.
+	fn report_peer(&mut self, peer_id: PeerId, weight: i32);
 	/// Disconnect peer
 	fn disconnect_peer(&mut self, peer_id: PeerId);
 	/// Respond to current request with a packet. Can be called from an IO handler for incoming packet.
@@ -88,6 +89,10 @@ impl<'s, 'h> SyncIo for NetSyncIo<'s, 'h> {
 		self.network.disable_peer(peer_id);
 	}
 
+	fn report_peer(&mut self, peer_id: PeerId, weight: i32) {
+		self.network.report_peer(peer_id, weight);
+	}
+
 	fn disconnect_peer(&mut self, peer_id: PeerId) {
 		self.network.disconnect_peer(peer_id);
 	}
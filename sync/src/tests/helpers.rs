@@ -48,6 +48,7 @@ pub struct TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	pub queue: &'p RwLock<VecDeque<TestPacket>>,
 	pub sender: Option<PeerId>,
 	pub to_disconnect: HashSet<PeerId>,
+	pub reputations: HashMap<PeerId, i32>,
 	pub packets: Vec<TestPacket>,
 	overlay: RwLock<HashMap<BlockNumber, Bytes>>,
 }
@@ -60,6 +61,7 @@ impl<'p, C> TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 			queue: queue,
 			sender: sender,
 			to_disconnect: HashSet::new(),
+			reputations: HashMap::new(),
 			overlay: RwLock::new(HashMap::new()),
 			packets: Vec::new(),
 		}
@@ -81,6 +83,10 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 		self.to_disconnect.insert(peer_id);
 	}
 
+	fn report_peer(&mut self, peer_id: PeerId, weight: i32) {
+		*self.reputations.entry(peer_id).or_insert(0) += weight;
+	}
+
 	fn is_expired(&self) -> bool {
 		false
 	}
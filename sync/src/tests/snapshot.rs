@@ -29,6 +29,10 @@ pub struct TestSnapshotService {
 	restoration_manifest: Mutex<Option<ManifestData>>,
 	state_restoration_chunks: Mutex<HashMap<H256, Bytes>>,
 	block_restoration_chunks: Mutex<HashMap<H256, Bytes>>,
+
+	state_nodes: HashMap<H256, Bytes>,
+	state_download_block: Mutex<Option<Bytes>>,
+	state_restoration_nodes: Mutex<HashMap<H256, Bytes>>,
 }
 
 impl TestSnapshotService {
@@ -40,6 +44,9 @@ impl TestSnapshotService {
 			restoration_manifest: Mutex::new(None),
 			state_restoration_chunks: Mutex::new(HashMap::new()),
 			block_restoration_chunks: Mutex::new(HashMap::new()),
+			state_nodes: HashMap::new(),
+			state_download_block: Mutex::new(None),
+			state_restoration_nodes: Mutex::new(HashMap::new()),
 		}
 	}
 
@@ -64,8 +71,17 @@ impl TestSnapshotService {
 			restoration_manifest: Mutex::new(None),
 			state_restoration_chunks: Mutex::new(HashMap::new()),
 			block_restoration_chunks: Mutex::new(HashMap::new()),
+			state_nodes: HashMap::new(),
+			state_download_block: Mutex::new(None),
+			state_restoration_nodes: Mutex::new(HashMap::new()),
 		}
 	}
+
+	pub fn new_with_state_nodes(num_nodes: usize) -> TestSnapshotService {
+		let mut service = TestSnapshotService::new();
+		service.state_nodes = (0..num_nodes).map(|_| H256::random().to_vec()).map(|data| (data.sha3(), data)).collect();
+		service
+	}
 }
 
 impl SnapshotService for TestSnapshotService {
@@ -78,6 +94,18 @@ impl SnapshotService for TestSnapshotService {
 	}
 
 	fn status(&self) -> RestorationStatus {
+		if self.state_download_block.lock().is_some() {
+			let done = self.state_restoration_nodes.lock().len();
+			return match done == self.state_nodes.len() {
+				true => RestorationStatus::Inactive,
+				false => RestorationStatus::Ongoing {
+					state_chunks: self.state_nodes.len() as u32,
+					block_chunks: 1,
+					state_chunks_done: done as u32,
+					block_chunks_done: 0,
+				},
+			};
+		}
 		match *self.restoration_manifest.lock() {
 			Some(ref manifest) if self.state_restoration_chunks.lock().len() == manifest.state_hashes.len() &&
 				self.block_restoration_chunks.lock().len() == manifest.block_hashes.len() => RestorationStatus::Inactive,
@@ -99,6 +127,8 @@ impl SnapshotService for TestSnapshotService {
 
 	fn abort_restore(&self) {
 		*self.restoration_manifest.lock() = None;
+		*self.state_download_block.lock() = None;
+		self.state_restoration_nodes.lock().clear();
 		self.state_restoration_chunks.lock().clear();
 		self.block_restoration_chunks.lock().clear();
 	}
@@ -118,6 +148,32 @@ impl SnapshotService for TestSnapshotService {
 	fn provide_canon_hashes(&self, hashes: &[(u64, H256)]) {
 		self.canon_hashes.lock().extend(hashes.iter().cloned());
 	}
+
+	fn begin_state_download(&self, block: Bytes, _receipts: Bytes, _total_difficulty: U256) {
+		*self.restoration_manifest.lock() = None;
+		*self.state_download_block.lock() = Some(block);
+	}
+
+	fn missing_state_nodes(&self, max: usize) -> Vec<H256> {
+		let restored = self.state_restoration_nodes.lock();
+		self.state_nodes.keys().filter(|h| !restored.contains_key(h)).take(max).cloned().collect()
+	}
+
+	fn restore_state_nodes(&self, nodes: Vec<Bytes>) {
+		if self.state_download_block.lock().is_some() {
+			let mut restored = self.state_restoration_nodes.lock();
+			for node in nodes {
+				let hash = node.sha3();
+				if self.state_nodes.contains_key(&hash) {
+					restored.insert(hash, node);
+				}
+			}
+		}
+	}
+
+	fn state_download_complete(&self) -> bool {
+		self.state_download_block.lock().is_some() && self.state_restoration_nodes.lock().len() == self.state_nodes.len()
+	}
 }
 
 #[test]
@@ -136,3 +192,18 @@ fn snapshot_sync() {
 	assert_eq!(net.peer(4).snapshot_service.block_restoration_chunks.lock().len(), net.peer(0).snapshot_service.manifest.as_ref().unwrap().block_hashes.len());
 }
 
+#[test]
+fn state_download_sync() {
+	::env_logger::init().ok();
+	let mut config = SyncConfig::default();
+	config.fast_sync = true;
+	let mut net = TestNet::new_with_config(3, config);
+	let snapshot_service = Arc::new(TestSnapshotService::new_with_state_nodes(1000));
+	for i in 0..2 {
+		net.peer(i).chain.add_blocks(1100, EachBlockWith::Nothing);
+		net.peer(i).chain.state_nodes.write().extend(snapshot_service.state_nodes.clone());
+	}
+	net.peer_mut(2).snapshot_service = snapshot_service.clone();
+	net.sync_steps(50);
+	assert_eq!(net.peer(2).snapshot_service.state_restoration_nodes.lock().len(), net.peer(2).snapshot_service.state_nodes.len());
+}
@@ -30,10 +30,11 @@ use node_table::*;
 use error::NetworkError;
 use io::{StreamToken, IoContext};
 use ethkey::{Secret, KeyPair, sign, recover};
-use AllowIP;
+use host::ProtocolId;
+use IpFilter;
 
-use PROTOCOL_VERSION;
 
+const PROTOCOL_VERSION: u32 = 4;							// Discovery version, independent of the RLPx one.
 const ADDRESS_BYTES_SIZE: u32 = 32;							// Size of address type in bytes.
 const ADDRESS_BITS: u32 = 8 * ADDRESS_BYTES_SIZE;			// Denoted by n in [Kademlia].
 const NODE_BINS: u32 = ADDRESS_BITS - 1;					// Size of m_state (excludes root, which is us).
@@ -46,10 +47,20 @@ const PACKET_PING: u8 = 1;
 const PACKET_PONG: u8 = 2;
 const PACKET_FIND_NODE: u8 = 3;
 const PACKET_NEIGHBOURS: u8 = 4;
+// Discovery v5 topic packets. v4 nodes drop them as unknown.
+const PACKET_TOPIC_REGISTER: u8 = 5;
+const PACKET_TOPIC_QUERY: u8 = 6;
+const PACKET_TOPIC_NODES: u8 = 7;
 
 const PING_TIMEOUT_MS: u64 = 300;
 const MAX_NODES_PING: usize = 32; // Max nodes to add/ping at once
 
+const TOPIC_REFRESH_MS: u64 = 60_000;		// How often topics are re-advertised and searched for.
+const TOPIC_AD_EXPIRY_SEC: u64 = 15 * 60;	// Advertisements not refreshed within this time are dropped.
+const MAX_TOPIC_LENGTH: usize = 128;
+const MAX_TOPICS: usize = 256;				// Max distinct topics we store advertisements for.
+const MAX_TOPIC_ADS: usize = 64;			// Max advertisements stored per topic, most recent first.
+
 #[derive(Clone, Debug)]
 pub struct NodeEntry {
 	pub id: NodeId,
@@ -85,6 +96,21 @@ struct Datagramm {
 	address: SocketAddr,
 }
 
+/// Node advertising a topic, stored on behalf of the network.
+struct TopicAd {
+	entry: NodeEntry,
+	expires: u64,
+}
+
+/// Discovery v5 topic name for a capability on a given chain, e.g. `LES@<genesis>`.
+pub fn capability_topic(protocol: ProtocolId, genesis: &H256) -> String {
+	format!("{}@{}", String::from_utf8_lossy(&protocol).to_uppercase(), genesis.hex())
+}
+
+fn topic_hash(topic: &str) -> H256 {
+	topic.as_bytes().sha3()
+}
+
 pub struct Discovery {
 	id: NodeId,
 	id_hash: H256,
@@ -99,16 +125,23 @@ pub struct Discovery {
 	send_queue: VecDeque<Datagramm>,
 	check_timestamps: bool,
 	adding_nodes: Vec<NodeEntry>,
-	allow_ips: AllowIP,
+	ip_filter: IpFilter,
+	topics_enabled: bool,
+	advertised_topics: Vec<String>,
+	searched_topics: Vec<String>,
+	topic_ads: HashMap<H256, VecDeque<TopicAd>>,
+	topics_refreshed: Option<u64>,
 }
 
 pub struct TableUpdates {
 	pub added: HashMap<NodeId, NodeEntry>,
 	pub removed: HashSet<NodeId>,
+	/// Nodes found advertising one of the searched topics.
+	pub topic_nodes: HashSet<NodeId>,
 }
 
 impl Discovery {
-	pub fn new(key: &KeyPair, listen: SocketAddr, public: NodeEndpoint, token: StreamToken, allow_ips: AllowIP) -> Discovery {
+	pub fn new(key: &KeyPair, listen: SocketAddr, public: NodeEndpoint, token: StreamToken, ip_filter: IpFilter) -> Discovery {
 		let socket = UdpSocket::bind(&listen).expect("Error binding UDP socket");
 		Discovery {
 			id: key.public().clone(),
@@ -124,8 +157,30 @@ impl Discovery {
 			send_queue: VecDeque::new(),
 			check_timestamps: true,
 			adding_nodes: Vec::new(),
-			allow_ips: allow_ips,
+			ip_filter: ip_filter,
+			topics_enabled: false,
+			advertised_topics: Vec::new(),
+			searched_topics: Vec::new(),
+			topic_ads: HashMap::new(),
+			topics_refreshed: None,
+		}
+	}
+
+	/// Enable discovery v5 topics. Advertises `advertised` topics to the nodes closest to each topic hash,
+	/// queries those nodes for `searched` topics and stores advertisements made by other nodes.
+	pub fn enable_topics(&mut self, advertised: Vec<String>, searched: Vec<String>) {
+		self.topics_enabled = true;
+		self.advertised_topics = advertised.into_iter().filter(|t| t.len() <= MAX_TOPIC_LENGTH).collect();
+		self.searched_topics = searched.into_iter().filter(|t| t.len() <= MAX_TOPIC_LENGTH).collect();
+		self.topics_refreshed = None;
+	}
+
+	/// Replace the IP filter. Nodes no longer allowed are dropped from the routing table.
+	pub fn set_ip_filter(&mut self, ip_filter: IpFilter) {
+		for bucket in &mut self.node_buckets {
+			bucket.nodes.retain(|n| ip_filter.is_allowed(&n.address.endpoint.address.ip()));
 		}
+		self.ip_filter = ip_filter;
 	}
 
 	/// Add a new node to discovery table. Pings the node.
@@ -281,16 +336,19 @@ impl Discovery {
 		self.send_to(packet, address.clone());
 	}
 
-	#[cfg_attr(feature="dev", allow(map_clone))]
 	fn nearest_node_entries(target: &NodeId, buckets: &[NodeBucket]) -> Vec<NodeEntry> {
+		Discovery::nearest_node_entries_by_hash(&target.sha3(), buckets)
+	}
+
+	#[cfg_attr(feature="dev", allow(map_clone))]
+	fn nearest_node_entries_by_hash(target_hash: &H256, buckets: &[NodeBucket]) -> Vec<NodeEntry> {
 		let mut found: BTreeMap<u32, Vec<&NodeEntry>> = BTreeMap::new();
 		let mut count = 0;
-		let target_hash = target.sha3();
 
 		// Sort nodes by distance to target
 		for bucket in buckets {
 			for node in &bucket.nodes {
-				let distance = Discovery::distance(&target_hash, &node.id_hash);
+				let distance = Discovery::distance(target_hash, &node.id_hash);
 				found.entry(distance).or_insert_with(Vec::new).push(&node.address);
 				if count == BUCKET_SIZE {
 					// delete the most distant element
@@ -363,6 +421,10 @@ impl Discovery {
 	}
 
 	fn on_packet(&mut self, packet: &[u8], from: SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		if !self.ip_filter.is_allowed(&from.ip()) {
+			trace!(target: "discovery", "Ignored packet from filtered address {:?}", from);
+			return Ok(None);
+		}
 		// validate packet
 		if packet.len() < 32 + 65 + 4 + 1 {
 			return Err(NetworkError::BadProtocol);
@@ -384,6 +446,9 @@ impl Discovery {
 			PACKET_PONG => self.on_pong(&rlp, &node_id, &from),
 			PACKET_FIND_NODE => self.on_find_node(&rlp, &node_id, &from),
 			PACKET_NEIGHBOURS => self.on_neighbours(&rlp, &node_id, &from),
+			PACKET_TOPIC_REGISTER if self.topics_enabled => self.on_topic_register(&rlp, &node_id, &from),
+			PACKET_TOPIC_QUERY if self.topics_enabled => self.on_topic_query(&rlp, &node_id, &from),
+			PACKET_TOPIC_NODES if self.topics_enabled => self.on_topic_nodes(&rlp, &node_id, &from),
 			_ => {
 				debug!("Unknown UDP packet: {}", packet_id);
 				Ok(None)
@@ -400,7 +465,7 @@ impl Discovery {
 	}
 
 	fn is_allowed(&self, entry: &NodeEntry) -> bool {
-		entry.endpoint.is_allowed(self.allow_ips) && entry.id != self.id
+		entry.endpoint.is_allowed(&self.ip_filter) && entry.id != self.id
 	}
 
 	fn on_ping(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
@@ -425,7 +490,7 @@ impl Discovery {
 		response.append(&hash);
 		self.send_packet(PACKET_PONG, from, &response.drain());
 
-		Ok(Some(TableUpdates { added: added_map, removed: HashSet::new() }))
+		Ok(Some(TableUpdates { added: added_map, removed: HashSet::new(), topic_nodes: HashSet::new() }))
 	}
 
 	fn on_pong(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
@@ -501,7 +566,155 @@ impl Discovery {
 			self.ping(&entry.endpoint);
 			self.update_node(entry);
 		}
-		Ok(Some(TableUpdates { added: added, removed: HashSet::new() }))
+		Ok(Some(TableUpdates { added: added, removed: HashSet::new(), topic_nodes: HashSet::new() }))
+	}
+
+	fn on_topic_register(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		let source = NodeEndpoint::from_rlp(&rlp.at(0)?)?;
+		let topic: String = rlp.val_at(1)?;
+		let timestamp: u64 = rlp.val_at(2)?;
+		self.check_timestamp(timestamp)?;
+		trace!(target: "discovery", "Got TopicRegister {} from {:?}", topic, &from);
+		if topic.len() > MAX_TOPIC_LENGTH {
+			return Err(NetworkError::BadProtocol);
+		}
+		let entry = NodeEntry { id: node.clone(), endpoint: source };
+		if !entry.endpoint.is_valid() || !self.is_allowed(&entry) {
+			debug!(target: "discovery", "Topic advertisement from bad address: {:?}", entry);
+			return Ok(None);
+		}
+		let hash = topic_hash(&topic);
+		if !self.topic_ads.contains_key(&hash) && self.topic_ads.len() >= MAX_TOPICS {
+			debug!(target: "discovery", "Too many topics, ignoring {}", topic);
+			return Ok(None);
+		}
+		let ads = self.topic_ads.entry(hash).or_insert_with(VecDeque::new);
+		ads.retain(|ad| ad.entry.id != entry.id);
+		ads.push_front(TopicAd { entry: entry, expires: time::get_time().sec as u64 + TOPIC_AD_EXPIRY_SEC });
+		ads.truncate(MAX_TOPIC_ADS);
+		Ok(None)
+	}
+
+	fn on_topic_query(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		let topic: String = rlp.val_at(0)?;
+		let timestamp: u64 = rlp.val_at(1)?;
+		self.check_timestamp(timestamp)?;
+		trace!(target: "discovery", "Got TopicQuery {} from {:?}", topic, &from);
+		if topic.len() > MAX_TOPIC_LENGTH {
+			return Err(NetworkError::BadProtocol);
+		}
+		let now = time::get_time().sec as u64;
+		let mut nodes: Vec<NodeEntry> = self.topic_ads.get(&topic_hash(&topic))
+			.map_or_else(Vec::new, |ads| ads.iter().filter(|ad| ad.expires > now).map(|ad| ad.entry.clone()).collect());
+		if self.advertised_topics.contains(&topic) {
+			nodes.push(NodeEntry { id: self.id.clone(), endpoint: self.public_endpoint.clone() });
+		}
+		if nodes.is_empty() {
+			return Ok(None);
+		}
+		let mut packets = Discovery::prepare_topic_nodes_packets(&topic, &nodes);
+		for p in packets.drain(..) {
+			self.send_packet(PACKET_TOPIC_NODES, from, &p);
+		}
+		trace!(target: "discovery", "Sent {} TopicNodes to {:?}", nodes.len(), &from);
+		Ok(None)
+	}
+
+	fn prepare_topic_nodes_packets(topic: &str, nodes: &[NodeEntry]) -> Vec<Bytes> {
+		let limit = (MAX_DATAGRAM_SIZE - 109 - MAX_TOPIC_LENGTH - 2) / 90;
+		nodes.chunks(limit).map(|c| {
+			let mut rlp = RlpStream::new_list(2);
+			rlp.append(&topic);
+			rlp.begin_list(c.len());
+			for n in c {
+				rlp.begin_list(4);
+				n.endpoint.to_rlp(&mut rlp);
+				rlp.append(&n.id);
+			}
+			rlp.out()
+		}).collect()
+	}
+
+	fn on_topic_nodes(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		let topic: String = rlp.val_at(0)?;
+		let timestamp: u64 = rlp.val_at(2)?;
+		self.check_timestamp(timestamp)?;
+		if !self.searched_topics.contains(&topic) {
+			debug!(target: "discovery", "Unexpected TopicNodes {} from {:?}", topic, &from);
+			return Ok(None);
+		}
+		trace!(target: "discovery", "Got {} TopicNodes {} from {:?}", rlp.at(1)?.item_count(), topic, &from);
+		let mut added = HashMap::new();
+		let mut topic_nodes = HashSet::new();
+		for r in rlp.at(1)?.iter() {
+			let endpoint = NodeEndpoint::from_rlp(&r)?;
+			if !endpoint.is_valid() {
+				debug!(target: "discovery", "Bad address: {:?}", endpoint);
+				continue;
+			}
+			let node_id: NodeId = r.val_at(3)?;
+			if node_id == self.id {
+				continue;
+			}
+			let entry = NodeEntry { id: node_id.clone(), endpoint: endpoint };
+			if !self.is_allowed(&entry) {
+				debug!(target: "discovery", "Address not allowed: {:?}", entry);
+				continue;
+			}
+			topic_nodes.insert(node_id.clone());
+			added.insert(node_id, entry.clone());
+			self.ping(&entry.endpoint);
+			self.update_node(entry);
+		}
+		Ok(Some(TableUpdates { added: added, removed: HashSet::new(), topic_nodes: topic_nodes }))
+	}
+
+	/// Advertise and search for topics with the nodes closest to each topic hash.
+	fn update_topics(&mut self) {
+		if !self.topics_enabled {
+			return;
+		}
+		let now = time::precise_time_ns();
+		if self.topics_refreshed.map_or(false, |t| now - t < TOPIC_REFRESH_MS * 1000_000) {
+			return;
+		}
+
+		let now_sec = time::get_time().sec as u64;
+		let mut empty = Vec::new();
+		for (hash, ads) in &mut self.topic_ads {
+			ads.retain(|ad| ad.expires > now_sec);
+			if ads.is_empty() {
+				empty.push(hash.clone());
+			}
+		}
+		for hash in empty {
+			self.topic_ads.remove(&hash);
+		}
+
+		let mut sent = false;
+		for topic in self.advertised_topics.clone() {
+			let nearest = Discovery::nearest_node_entries_by_hash(&topic_hash(&topic), &self.node_buckets);
+			for n in nearest.into_iter().take(ALPHA) {
+				let mut rlp = RlpStream::new_list(2);
+				self.public_endpoint.to_rlp_list(&mut rlp);
+				rlp.append(&topic);
+				self.send_packet(PACKET_TOPIC_REGISTER, &n.endpoint.udp_address(), &rlp.drain());
+				sent = true;
+			}
+		}
+		for topic in self.searched_topics.clone() {
+			let nearest = Discovery::nearest_node_entries_by_hash(&topic_hash(&topic), &self.node_buckets);
+			for n in nearest.into_iter().take(ALPHA) {
+				let mut rlp = RlpStream::new_list(1);
+				rlp.append(&topic);
+				self.send_packet(PACKET_TOPIC_QUERY, &n.endpoint.udp_address(), &rlp.drain());
+				sent = true;
+			}
+		}
+		// Retry on the next round while the table is still empty.
+		if sent {
+			self.topics_refreshed = Some(now);
+		}
 	}
 
 	fn check_expired(&mut self, force: bool) -> HashSet<NodeId> {
@@ -527,8 +740,9 @@ impl Discovery {
 	pub fn round(&mut self) -> Option<TableUpdates> {
 		let removed = self.check_expired(false);
 		self.discover();
+		self.update_topics();
 		if !removed.is_empty() {
-			Some(TableUpdates { added: HashMap::new(), removed: removed })
+			Some(TableUpdates { added: HashMap::new(), removed: removed, topic_nodes: HashSet::new() })
 		} else { None }
 	}
 
@@ -558,12 +772,13 @@ mod tests {
 	use std::net::{SocketAddr};
 	use util::sha3::Hashable;
 	use util::FixedHash;
+	use util::hash::H256;
 	use node_table::{Node, NodeId, NodeEndpoint};
 
 	use std::str::FromStr;
 	use rustc_serialize::hex::FromHex;
 	use ethkey::{Random, Generator};
-	use AllowIP;
+	use IpFilter;
 
 	#[test]
 	fn find_node() {
@@ -588,8 +803,8 @@ mod tests {
 		let key2 = Random.generate().unwrap();
 		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40444").unwrap(), udp_port: 40444 };
 		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40445").unwrap(), udp_port: 40445 };
-		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, AllowIP::All);
-		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, AllowIP::All);
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default());
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default());
 
 		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7770").unwrap();
 		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7771").unwrap();
@@ -617,11 +832,60 @@ mod tests {
 		assert_eq!(Discovery::nearest_node_entries(&NodeId::new(), &discovery2.node_buckets).len(), 3)
 	}
 
+	fn deliver(from: &mut Discovery, from_address: &SocketAddr, to: &mut Discovery, to_address: &SocketAddr) -> Vec<TableUpdates> {
+		let mut updates = Vec::new();
+		while let Some(datagramm) = from.send_queue.pop_front() {
+			if datagramm.address == *to_address {
+				if let Ok(Some(update)) = to.on_packet(&datagramm.payload, from_address.clone()) {
+					updates.push(update);
+				}
+			}
+		}
+		updates
+	}
+
+	#[test]
+	fn topics() {
+		let topic = capability_topic(*b"les", &H256::from(1));
+		assert_eq!(topic, "LES@0000000000000000000000000000000000000000000000000000000000000001");
+
+		let (key1, key2, key3) = (Random.generate().unwrap(), Random.generate().unwrap(), Random.generate().unwrap());
+		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40450").unwrap(), udp_port: 40450 };
+		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40451").unwrap(), udp_port: 40451 };
+		let ep3 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40452").unwrap(), udp_port: 40452 };
+		let mut registry = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default());
+		let mut server = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default());
+		let mut client = Discovery::new(&key3, ep3.address.clone(), ep3.clone(), 0, IpFilter::default());
+		registry.enable_topics(vec![], vec![]);
+		server.enable_topics(vec![topic.clone()], vec![]);
+		client.enable_topics(vec![], vec![topic.clone()]);
+
+		// the server advertises with the only node it knows.
+		server.add_node(NodeEntry { id: key1.public().clone(), endpoint: ep1.clone() });
+		server.round();
+		deliver(&mut server, &ep2.address, &mut registry, &ep1.address);
+		assert_eq!(registry.topic_ads.len(), 1);
+
+		// the client finds the server through the registry.
+		client.add_node(NodeEntry { id: key1.public().clone(), endpoint: ep1.clone() });
+		client.round();
+		deliver(&mut client, &ep3.address, &mut registry, &ep1.address);
+		let updates = deliver(&mut registry, &ep1.address, &mut client, &ep3.address);
+		assert!(updates.iter().any(|u| u.topic_nodes.contains(key2.public()) && u.added.contains_key(key2.public())));
+
+		// nodes without topics enabled ignore advertisements.
+		let mut plain = Discovery::new(&Random.generate().unwrap(), SocketAddr::from_str("127.0.0.1:40453").unwrap(), ep1.clone(), 0, IpFilter::default());
+		server.topics_refreshed = None;
+		server.round();
+		deliver(&mut server, &ep2.address, &mut plain, &ep1.address);
+		assert!(plain.topic_ads.is_empty());
+	}
+
 	#[test]
 	fn removes_expired() {
 		let key = Random.generate().unwrap();
 		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40446").unwrap(), udp_port: 40447 };
-		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, AllowIP::All);
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default());
 		for _ in 0..1200 {
 			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone() });
 		}
@@ -650,7 +914,7 @@ mod tests {
 	fn packets() {
 		let key = Random.generate().unwrap();
 		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40447").unwrap(), udp_port: 40447 };
-		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, AllowIP::All);
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default());
 		discovery.check_timestamps = false;
 		let from = SocketAddr::from_str("99.99.99.99:40445").unwrap();
 
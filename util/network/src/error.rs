@@ -17,6 +17,7 @@
 use io::IoError;
 use rlp::*;
 use util::UtilError;
+use util::snappy;
 use std::fmt;
 use ethkey::Error as KeyError;
 use crypto::Error as CryptoError;
@@ -106,6 +107,10 @@ pub enum NetworkError {
 	AddressResolve(Option<::std::io::Error>),
 	/// Error concerning the Rust standard library's IO subsystem.
 	StdIo(::std::io::Error),
+	/// Packet size is over the protocol limit.
+	OversizedPacket,
+	/// Invalid IP filter specification.
+	InvalidIpFilter(String),
 }
 
 impl fmt::Display for NetworkError {
@@ -124,6 +129,8 @@ impl fmt::Display for NetworkError {
 			AddressResolve(_) => "Failed to resolve network address.".into(),
 			StdIo(ref err) => format!("{}", err),
 			Util(ref err) => format!("{}", err),
+			OversizedPacket => "Packet is too large".into(),
+			InvalidIpFilter(ref filter) => format!("Invalid IP filter: {}", filter),
 		};
 
 		f.write_fmt(format_args!("Network error ({})", msg))
@@ -166,6 +173,12 @@ impl From<CryptoError> for NetworkError {
 	}
 }
 
+impl From<snappy::InvalidInput> for NetworkError {
+	fn from(_err: snappy::InvalidInput) -> NetworkError {
+		NetworkError::BadProtocol
+	}
+}
+
 impl From<::std::net::AddrParseError> for NetworkError {
 	fn from(err: ::std::net::AddrParseError) -> NetworkError {
 		NetworkError::AddressParse(err)
@@ -35,7 +35,7 @@ use rlp::*;
 use session::{Session, SessionInfo, SessionData};
 use error::*;
 use io::*;
-use {NetworkProtocolHandler, NonReservedPeerMode, AllowIP, PROTOCOL_VERSION};
+use {NetworkProtocolHandler, NonReservedPeerMode, IpFilter, PROTOCOL_VERSION};
 use node_table::*;
 use stats::NetworkStats;
 use discovery::{Discovery, TableUpdates, NodeEntry};
@@ -85,6 +85,12 @@ pub struct NetworkConfiguration {
 	pub nat_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Enable discovery v5 topic advertisement and search on top of the v4 node table
+	pub discovery_v5: bool,
+	/// Topics advertised through discovery v5, e.g. "LES@<genesis>"
+	pub advertised_topics: Vec<String>,
+	/// Topics to find peers by through discovery v5. Nodes advertising them are connected to first
+	pub searched_topics: Vec<String>,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -101,8 +107,10 @@ pub struct NetworkConfiguration {
 	pub reserved_nodes: Vec<String>,
 	/// The non-reserved peer mode.
 	pub non_reserved_mode: NonReservedPeerMode,
-	/// IP filter
-	pub allow_ips: AllowIP,
+	/// IP filter applied to TCP connections and discovery
+	pub ip_filter: IpFilter,
+	/// Number of seconds a peer stays banned once its reputation drops below the threshold
+	pub ban_duration: u64,
 }
 
 impl Default for NetworkConfiguration {
@@ -122,15 +130,19 @@ impl NetworkConfiguration {
 			udp_port: None,
 			nat_enabled: true,
 			discovery_enabled: true,
+			discovery_v5: false,
+			advertised_topics: Vec::new(),
+			searched_topics: Vec::new(),
 			boot_nodes: Vec::new(),
 			use_secret: None,
 			min_peers: 25,
 			max_peers: 50,
 			max_handshakes: 64,
 			reserved_protocols: HashMap::new(),
-			allow_ips: AllowIP::All,
+			ip_filter: IpFilter::default(),
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			ban_duration: 3600,
 		}
 	}
 
@@ -184,6 +196,8 @@ pub enum NetworkIoMessage {
 	Disconnect(PeerId),
 	/// Disconnect and temporary disable peer.
 	DisablePeer(PeerId),
+	/// Adjust peer reputation by the given weight, banning it once below the threshold.
+	ReportPeer(PeerId, i32),
 	/// Network has been started with the host as the given enode.
 	NetworkStarted(String),
 }
@@ -275,6 +289,13 @@ impl<'s> NetworkContext<'s> {
 			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
 	}
 
+	/// Report peer behaviour. Negative weights penalize the peer, which gets
+	/// disconnected and banned once its reputation drops below the threshold.
+	pub fn report_peer(&self, peer: PeerId, weight: i32) {
+		self.io.message(NetworkIoMessage::ReportPeer(peer, weight))
+			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
+	}
+
 	/// Disconnect peer. Reconnect can be attempted later.
 	pub fn disconnect_peer(&self, peer: PeerId) {
 		self.io.message(NetworkIoMessage::Disconnect(peer))
@@ -505,6 +526,28 @@ impl Host {
 		}
 	}
 
+	/// Replace the IP filter and disconnect peers whose addresses are no longer allowed.
+	pub fn set_ip_filter(&self, filter: IpFilter, io: &IoContext<NetworkIoMessage>) {
+		self.info.write().config.ip_filter = filter.clone();
+		if let Some(ref mut discovery) = *self.discovery.lock() {
+			discovery.set_ip_filter(filter.clone());
+		}
+
+		let mut to_kill = Vec::new();
+		for e in self.sessions.write().iter_mut() {
+			let mut s = e.lock();
+			if s.remote_addr().map_or(true, |a| filter.is_allowed(&a.ip())) {
+				continue;
+			}
+			s.disconnect(io, DisconnectReason::ClientQuit);
+			to_kill.push(s.token());
+		}
+		for p in to_kill {
+			trace!(target: "network", "Disconnecting filtered peer: {}", p);
+			self.kill_connection(p, io, false);
+		}
+	}
+
 	pub fn remove_reserved_node(&self, id: &str) -> Result<(), NetworkError> {
 		let n = Node::from_str(id)?;
 		self.reserved_nodes.write().remove(&n.id);
@@ -512,6 +555,29 @@ impl Host {
 		Ok(())
 	}
 
+	/// Currently banned node ids along with the unix timestamp each ban expires at.
+	pub fn banned_nodes(&self) -> Vec<(NodeId, u64)> {
+		self.nodes.read().banned()
+	}
+
+	/// Lift the ban on a node. Accepts either a node id or an enode URL.
+	pub fn unban_node(&self, id: &str) -> Result<bool, NetworkError> {
+		let id = if id.starts_with("enode://") {
+			Node::from_str(id)?.id
+		} else {
+			id.trim_left_matches("0x").parse().map_err(::util::UtilError::from)?
+		};
+		let unbanned = self.nodes.write().unban(&id);
+		self.nodes.read().save();
+		Ok(unbanned)
+	}
+
+	/// Lift all bans.
+	pub fn clear_bans(&self) {
+		self.nodes.write().clear_bans();
+		self.nodes.read().save();
+	}
+
 	pub fn client_version() -> String {
 		version()
 	}
@@ -548,7 +614,7 @@ impl Host {
 		}
 		let local_endpoint = self.info.read().local_endpoint.clone();
 		let public_address = self.info.read().config.public_address.clone();
-		let allow_ips = self.info.read().config.allow_ips;
+		let ip_filter = self.info.read().config.ip_filter.clone();
 		let public_endpoint = match public_address {
 			None => {
 				let public_address = select_public_address(local_endpoint.address.port());
@@ -580,7 +646,11 @@ impl Host {
 			if info.config.discovery_enabled && info.config.non_reserved_mode == NonReservedPeerMode::Accept {
 				let mut udp_addr = local_endpoint.address.clone();
 				udp_addr.set_port(local_endpoint.udp_port);
-				Some(Discovery::new(&info.keys, udp_addr, public_endpoint, DISCOVERY, allow_ips))
+				let mut discovery = Discovery::new(&info.keys, udp_addr, public_endpoint, DISCOVERY, ip_filter);
+				if info.config.discovery_v5 {
+					discovery.enable_topics(info.config.advertised_topics.clone(), info.config.searched_topics.clone());
+				}
+				Some(discovery)
 			} else { None }
 		};
 
@@ -635,14 +705,14 @@ impl Host {
 	}
 
 	fn connect_peers(&self, io: &IoContext<NetworkIoMessage>) {
-		let (min_peers, mut pin, max_handshakes, allow_ips, self_id) = {
+		let (min_peers, mut pin, max_handshakes, ip_filter, self_id) = {
 			let info = self.info.read();
 			if info.capabilities.is_empty() {
 				return;
 			}
 			let config = &info.config;
 
-			(config.min_peers, config.non_reserved_mode == NonReservedPeerMode::Deny, config.max_handshakes as usize, config.allow_ips, info.id().clone())
+			(config.min_peers, config.non_reserved_mode == NonReservedPeerMode::Deny, config.max_handshakes as usize, config.ip_filter.clone(), info.id().clone())
 		};
 
 		let session_count = self.session_count();
@@ -666,7 +736,7 @@ impl Host {
 		// iterate over all nodes, reserved ones coming first.
 		// if we are pinned to only reserved nodes, ignore all others.
 		let nodes = reserved_nodes.iter().cloned().chain(if !pin {
-			self.nodes.read().nodes(allow_ips)
+			self.nodes.read().nodes(&ip_filter)
 		} else {
 			Vec::new()
 		});
@@ -705,6 +775,10 @@ impl Host {
 					return;
 				}
 			};
+			if !self.info.read().config.ip_filter.is_allowed(&address.ip()) {
+				debug!(target: "network", "Connection to filtered address {:?} aborted", address);
+				return;
+			}
 			match TcpStream::connect(&address) {
 				Ok(socket) => {
 					trace!(target: "network", "Connecting to {:?}", address);
@@ -749,7 +823,13 @@ impl Host {
 		trace!(target: "network", "Accepting incoming connection");
 		loop {
 			let socket = match self.tcp_listener.lock().accept() {
-				Ok((sock, _addr)) => sock,
+				Ok((sock, addr)) => {
+					if !self.info.read().config.ip_filter.is_allowed(&addr.ip()) {
+						debug!(target: "network", "Rejected incoming connection from filtered address {:?}", addr);
+						continue;
+					}
+					sock
+				},
 				Err(e) => {
 					if e.kind() != ErrorKind::WouldBlock {
 						debug!(target: "network", "Error accepting connection: {:?}", e);
@@ -820,6 +900,13 @@ impl Host {
 							(info.config.min_peers as usize, max_peers as usize, info.config.non_reserved_mode == NonReservedPeerMode::Deny)
 						};
 
+						let id = s.id().expect("Ready session always has id").clone();
+						if !self.reserved_nodes.read().contains(&id) && self.nodes.read().is_banned(&id) {
+							debug!(target: "network", "Rejecting banned peer {}", id);
+							s.disconnect(io, DisconnectReason::UselessPeer);
+							return;
+						}
+
 						// Check for the session limit. session_counts accounts for the new session.
 						if reserved_only ||
 							(s.info.originated && session_count > min_peers) ||
@@ -1035,6 +1122,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 			NODE_TABLE => {
 				trace!(target: "network", "Refreshing node table");
 				self.nodes.write().clear_useless();
+				self.nodes.write().decay_reputations();
 				self.nodes.write().save();
 			},
 			_ => match self.timers.read().get(&token).cloned() {
@@ -1104,6 +1192,21 @@ impl IoHandler<NetworkIoMessage> for Host {
 				trace!(target: "network", "Disabling peer {}", peer);
 				self.kill_connection(*peer, io, false);
 			},
+			NetworkIoMessage::ReportPeer(ref peer, weight) => {
+				let session = { self.sessions.read().get(*peer).cloned() };
+				let id = session.as_ref().and_then(|s| s.lock().id().cloned());
+				if let (Some(session), Some(id)) = (session, id) {
+					if self.reserved_nodes.read().contains(&id) {
+						return;
+					}
+					let ban_duration = self.info.read().config.ban_duration;
+					if self.nodes.write().report(&id, weight, ban_duration) {
+						debug!(target: "network", "Banning peer {} for {} seconds", id, ban_duration);
+						session.lock().disconnect(io, DisconnectReason::UselessPeer);
+						self.kill_connection(*peer, io, false);
+					}
+				}
+			},
 			NetworkIoMessage::InitPublicInterface =>
 				self.init_public_interface(io).unwrap_or_else(|e| warn!("Error initializing public interface: {:?}", e)),
 			_ => {}	// ignore others.
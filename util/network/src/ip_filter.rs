@@ -0,0 +1,259 @@
+// Copyright 2015, 2016 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! IP address filtering for peer connections.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+use std::str::FromStr;
+use error::NetworkError;
+use ip_utils::SocketAddrExt;
+use AllowIP;
+
+/// IPv4 or IPv6 network in CIDR notation, e.g. `10.0.0.0/8`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpNetwork {
+	address: IpAddr,
+	prefix: u8,
+}
+
+impl IpNetwork {
+	/// Check if the network contains the given address.
+	/// IPv4-mapped IPv6 addresses are matched as IPv4.
+	pub fn contains(&self, ip: &IpAddr) -> bool {
+		match (&self.address, &unmap(ip)) {
+			(&IpAddr::V4(ref net), &IpAddr::V4(ref ip)) => prefix_matches(&net.octets(), &ip.octets(), self.prefix),
+			(&IpAddr::V6(ref net), &IpAddr::V6(ref ip)) => prefix_matches(&net.octets(), &ip.octets(), self.prefix),
+			_ => false,
+		}
+	}
+}
+
+// convert IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`) to IPv4.
+fn unmap(ip: &IpAddr) -> IpAddr {
+	if let IpAddr::V6(ref v6) = *ip {
+		let s = v6.segments();
+		if s[..5] == [0; 5] && s[5] == 0xffff {
+			return IpAddr::V4(Ipv4Addr::new((s[6] >> 8) as u8, s[6] as u8, (s[7] >> 8) as u8, s[7] as u8));
+		}
+	}
+	*ip
+}
+
+fn prefix_matches(net: &[u8], ip: &[u8], prefix: u8) -> bool {
+	let bytes = (prefix / 8) as usize;
+	let bits = prefix % 8;
+	if net[..bytes] != ip[..bytes] {
+		return false;
+	}
+	bits == 0 || {
+		let mask = 0xffu8 << (8 - bits);
+		net[bytes] & mask == ip[bytes] & mask
+	}
+}
+
+impl FromStr for IpNetwork {
+	type Err = NetworkError;
+
+	/// Parse a network in CIDR notation. A plain address is treated as a single host network.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(2, '/');
+		let address: IpAddr = parts.next().unwrap_or("").parse()?;
+		let max_prefix = match address {
+			IpAddr::V4(_) => 32,
+			IpAddr::V6(_) => 128,
+		};
+		let prefix = match parts.next() {
+			Some(prefix) => match prefix.parse::<u8>() {
+				Ok(prefix) if prefix <= max_prefix => prefix,
+				_ => return Err(NetworkError::InvalidIpFilter(s.to_owned())),
+			},
+			None => max_prefix,
+		};
+		Ok(IpNetwork {
+			address: address,
+			prefix: prefix,
+		})
+	}
+}
+
+impl fmt::Display for IpNetwork {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}/{}", self.address, self.prefix)
+	}
+}
+
+/// Filter applied to peer addresses, for both TCP connections and discovery.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpFilter {
+	/// Predefined class of allowed addresses.
+	pub predefined: AllowIP,
+	/// Networks allowed in addition to the predefined class.
+	pub custom_allow: Vec<IpNetwork>,
+	/// Networks always denied, even if allowed otherwise.
+	pub custom_block: Vec<IpNetwork>,
+}
+
+impl Default for IpFilter {
+	fn default() -> Self {
+		IpFilter {
+			predefined: AllowIP::All,
+			custom_allow: Vec::new(),
+			custom_block: Vec::new(),
+		}
+	}
+}
+
+impl IpFilter {
+	/// Parse allow and deny lists. Entries are separated by commas or whitespace.
+	/// The allow list may contain at most one of `all`, `public`, `private` or `none`
+	/// along with any number of CIDR networks. When only networks are given,
+	/// nothing outside of them is allowed. The deny list contains networks only.
+	pub fn parse(allow: &str, deny: &str) -> Result<IpFilter, NetworkError> {
+		let mut predefined = None;
+		let mut custom_allow = Vec::new();
+		for entry in split_list(allow) {
+			let class = match entry {
+				"all" => AllowIP::All,
+				"public" => AllowIP::Public,
+				"private" => AllowIP::Private,
+				"none" => AllowIP::None,
+				network => {
+					custom_allow.push(network.parse()?);
+					continue;
+				}
+			};
+			if predefined.is_some() {
+				return Err(NetworkError::InvalidIpFilter(allow.to_owned()));
+			}
+			predefined = Some(class);
+		}
+		let mut custom_block = Vec::new();
+		for entry in split_list(deny) {
+			if entry != "none" {
+				custom_block.push(entry.parse()?);
+			}
+		}
+		Ok(IpFilter {
+			predefined: predefined.unwrap_or(if custom_allow.is_empty() { AllowIP::All } else { AllowIP::None }),
+			custom_allow: custom_allow,
+			custom_block: custom_block,
+		})
+	}
+
+	/// Check if connections with the given address are allowed.
+	pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+		let ip = &unmap(ip);
+		if self.custom_block.iter().any(|n| n.contains(ip)) {
+			return false;
+		}
+		let predefined = match self.predefined {
+			AllowIP::All => true,
+			AllowIP::Private => !ip.is_global_s(),
+			AllowIP::Public => ip.is_global_s(),
+			AllowIP::None => false,
+		};
+		predefined || self.custom_allow.iter().any(|n| n.contains(ip))
+	}
+}
+
+fn split_list(list: &str) -> Vec<&str> {
+	list.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::IpAddr;
+	use std::str::FromStr;
+	use super::*;
+	use AllowIP;
+
+	fn ip(s: &str) -> IpAddr {
+		IpAddr::from_str(s).unwrap()
+	}
+
+	#[test]
+	fn network_contains() {
+		let net = IpNetwork::from_str("10.1.0.0/16").unwrap();
+		assert!(net.contains(&ip("10.1.200.3")));
+		assert!(!net.contains(&ip("10.2.0.1")));
+		assert!(!net.contains(&ip("::1")));
+
+		let net = IpNetwork::from_str("192.168.1.128/25").unwrap();
+		assert!(net.contains(&ip("192.168.1.200")));
+		assert!(!net.contains(&ip("192.168.1.100")));
+
+		let net = IpNetwork::from_str("fd00::/8").unwrap();
+		assert!(net.contains(&ip("fd12:3456::1")));
+		assert!(!net.contains(&ip("fe80::1")));
+
+		let net = IpNetwork::from_str("0.0.0.0/0").unwrap();
+		assert!(net.contains(&ip("8.8.8.8")));
+
+		let host = IpNetwork::from_str("1.2.3.4").unwrap();
+		assert_eq!(host.to_string(), "1.2.3.4/32");
+		assert!(host.contains(&ip("1.2.3.4")));
+		assert!(!host.contains(&ip("1.2.3.5")));
+	}
+
+	#[test]
+	fn network_parse_errors() {
+		assert!(IpNetwork::from_str("10.0.0.0/33").is_err());
+		assert!(IpNetwork::from_str("10.0.0/8").is_err());
+		assert!(IpNetwork::from_str("::/129").is_err());
+		assert!(IpNetwork::from_str("10.0.0.0/x").is_err());
+	}
+
+	#[test]
+	fn filter_parse() {
+		assert_eq!(IpFilter::parse("", "").unwrap(), IpFilter::default());
+		assert_eq!(IpFilter::parse("private", "").unwrap().predefined, AllowIP::Private);
+		assert_eq!(IpFilter::parse("10.0.0.0/8", "").unwrap().predefined, AllowIP::None);
+		assert_eq!(IpFilter::parse("public, 10.0.0.0/8", "").unwrap().predefined, AllowIP::Public);
+		assert!(IpFilter::parse("everything", "").is_err());
+		assert!(IpFilter::parse("public,private", "").is_err());
+		assert!(IpFilter::parse("all", "public").is_err());
+	}
+
+	#[test]
+	fn filter_allows() {
+		let filter = IpFilter::parse("10.0.0.0/8 192.168.0.0/16", "10.10.0.0/16").unwrap();
+		assert!(filter.is_allowed(&ip("10.1.1.1")));
+		assert!(filter.is_allowed(&ip("192.168.5.5")));
+		assert!(!filter.is_allowed(&ip("10.10.1.1")));
+		assert!(!filter.is_allowed(&ip("8.8.8.8")));
+
+		let filter = IpFilter::parse("public", "8.8.0.0/16").unwrap();
+		assert!(filter.is_allowed(&ip("1.1.1.1")));
+		assert!(!filter.is_allowed(&ip("8.8.8.8")));
+		assert!(!filter.is_allowed(&ip("10.0.0.1")));
+	}
+
+	#[test]
+	fn filter_matches_mapped_addresses_as_ipv4() {
+		let filter = IpFilter::parse("all", "8.8.0.0/16").unwrap();
+		assert!(!filter.is_allowed(&ip("::ffff:8.8.8.8")));
+		assert!(filter.is_allowed(&ip("::ffff:1.1.1.1")));
+
+		let filter = IpFilter::parse("10.0.0.0/8", "").unwrap();
+		assert!(filter.is_allowed(&ip("::ffff:10.1.2.3")));
+		assert!(!filter.is_allowed(&ip("::ffff:11.1.2.3")));
+
+		let filter = IpFilter::parse("public", "").unwrap();
+		assert!(!filter.is_allowed(&ip("::ffff:192.168.0.1")));
+	}
+}
@@ -88,6 +88,7 @@ mod error;
 mod node_table;
 mod stats;
 mod ip_utils;
+mod ip_filter;
 
 #[cfg(test)]
 mod tests;
@@ -97,11 +98,13 @@ pub use service::NetworkService;
 pub use error::NetworkError;
 pub use stats::NetworkStats;
 pub use session::SessionInfo;
+pub use discovery::capability_topic;
+pub use ip_filter::{IpFilter, IpNetwork};
 
 use io::TimerToken;
 pub use node_table::{is_valid_node_url, NodeId};
 
-const PROTOCOL_VERSION: u32 = 4;
+const PROTOCOL_VERSION: u32 = 5;
 
 /// Network IO protocol handler. This needs to be implemented for each new subprotocol.
 /// All the handler function are called from within IO event loop.
@@ -148,5 +151,7 @@ pub enum AllowIP {
 	Private,
 	/// Connect to public network only
 	Public,
+	/// Connect only to explicitly allowed networks
+	None,
 }
 
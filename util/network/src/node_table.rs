@@ -30,7 +30,7 @@ use util::UtilError;
 use rlp::*;
 use time::Tm;
 use error::NetworkError;
-use AllowIP;
+use IpFilter;
 use discovery::{TableUpdates, NodeEntry};
 use ip_utils::*;
 pub use rustc_serialize::json::Json;
@@ -38,6 +38,9 @@ pub use rustc_serialize::json::Json;
 /// Node public key
 pub type NodeId = H512;
 
+/// Reputation at or below which a node gets banned.
+pub const BAN_THRESHOLD: i32 = -100;
+
 #[derive(Debug, Clone)]
 /// Node address info
 pub struct NodeEndpoint {
@@ -55,12 +58,8 @@ impl NodeEndpoint {
 		}
 	}
 
-	pub fn is_allowed(&self, filter: AllowIP) -> bool {
-		match filter {
-			AllowIP::All => true,
-			AllowIP::Private => !self.address.ip().is_global_s(),
-			AllowIP::Public => self.address.ip().is_global_s(),
-		}
+	pub fn is_allowed(&self, filter: &IpFilter) -> bool {
+		filter.is_allowed(&self.address.ip())
 	}
 
 	pub fn from_rlp(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
@@ -198,15 +197,25 @@ impl Hash for Node {
 pub struct NodeTable {
 	nodes: HashMap<NodeId, Node>,
 	useless_nodes: HashSet<NodeId>,
+	/// Nodes advertising a topic we search for. Tried before any other node.
+	topic_nodes: HashSet<NodeId>,
+	/// Accumulated reputation of misbehaving nodes. Decays towards zero over time.
+	reputations: HashMap<NodeId, i32>,
+	/// Banned nodes mapped to the unix timestamp the ban expires at.
+	banned: HashMap<NodeId, u64>,
 	path: Option<String>,
 }
 
 impl NodeTable {
 	pub fn new(path: Option<String>) -> NodeTable {
+		let (nodes, banned) = NodeTable::load(path.clone());
 		NodeTable {
-			path: path.clone(),
-			nodes: NodeTable::load(path),
+			path: path,
+			nodes: nodes,
 			useless_nodes: HashSet::new(),
+			topic_nodes: HashSet::new(),
+			reputations: HashMap::new(),
+			banned: banned,
 		}
 	}
 
@@ -219,9 +228,12 @@ impl NodeTable {
 	}
 
 	/// Returns node ids sorted by number of failures
-	pub fn nodes(&self, filter: AllowIP) -> Vec<NodeId> {
-		let mut refs: Vec<&Node> = self.nodes.values().filter(|n| !self.useless_nodes.contains(&n.id) && n.endpoint.is_allowed(filter)).collect();
-		refs.sort_by(|a, b| a.failures.cmp(&b.failures));
+	pub fn nodes(&self, filter: &IpFilter) -> Vec<NodeId> {
+		let now = unix_now();
+		let mut refs: Vec<&Node> = self.nodes.values()
+			.filter(|n| !self.useless_nodes.contains(&n.id) && !self.banned.get(&n.id).map_or(false, |until| *until > now) && n.endpoint.is_allowed(filter))
+			.collect();
+		refs.sort_by_key(|n| (!self.topic_nodes.contains(&n.id), n.failures));
 		refs.iter().map(|n| n.id.clone()).collect()
 	}
 
@@ -245,8 +257,11 @@ impl NodeTable {
 		for r in update.removed {
 			if !reserved.contains(&r) {
 				self.nodes.remove(&r);
+				self.topic_nodes.remove(&r);
 			}
 		}
+		let nodes = &self.nodes;
+		self.topic_nodes.extend(update.topic_nodes.into_iter().filter(|n| nodes.contains_key(n)));
 	}
 
 	/// Increase failure counte for a node
@@ -266,6 +281,59 @@ impl NodeTable {
 		self.useless_nodes.clear();
 	}
 
+	/// Adjust node reputation by `weight`. Once the reputation drops to `BAN_THRESHOLD`
+	/// the node is banned for `ban_duration` seconds. Returns `true` if the node got banned.
+	pub fn report(&mut self, id: &NodeId, weight: i32, ban_duration: u64) -> bool {
+		let reputation = {
+			let reputation = self.reputations.entry(id.clone()).or_insert(0);
+			*reputation = reputation.saturating_add(weight);
+			*reputation
+		};
+		if reputation > BAN_THRESHOLD {
+			return false;
+		}
+		self.reputations.remove(id);
+		self.banned.insert(id.clone(), unix_now() + ban_duration);
+		true
+	}
+
+	/// Current reputation of a node.
+	pub fn reputation(&self, id: &NodeId) -> i32 {
+		self.reputations.get(id).cloned().unwrap_or(0)
+	}
+
+	/// Check if the node is currently banned.
+	pub fn is_banned(&self, id: &NodeId) -> bool {
+		self.banned.get(id).map_or(false, |until| *until > unix_now())
+	}
+
+	/// Currently banned nodes along with the unix timestamp each ban expires at.
+	pub fn banned(&self) -> Vec<(NodeId, u64)> {
+		let now = unix_now();
+		self.banned.iter().filter(|&(_, until)| *until > now).map(|(id, until)| (id.clone(), *until)).collect()
+	}
+
+	/// Lift the ban on a node and reset its reputation. Returns `false` if the node was not banned.
+	pub fn unban(&mut self, id: &NodeId) -> bool {
+		self.reputations.remove(id);
+		self.banned.remove(id).is_some()
+	}
+
+	/// Lift all bans.
+	pub fn clear_bans(&mut self) {
+		self.banned.clear();
+		self.reputations.clear();
+	}
+
+	/// Halve all reputations and drop expired bans.
+	pub fn decay_reputations(&mut self) {
+		let now = unix_now();
+		let reputations = mem::replace(&mut self.reputations, HashMap::new());
+		self.reputations = reputations.into_iter().map(|(id, r)| (id, r / 2)).filter(|&(_, r)| r != 0).collect();
+		let banned = mem::replace(&mut self.banned, HashMap::new());
+		self.banned = banned.into_iter().filter(|&(_, until)| until > now).collect();
+	}
+
 	/// Save the nodes.json file.
 	pub fn save(&self) {
 		if let Some(ref path) = self.path {
@@ -278,10 +346,18 @@ impl NodeTable {
 			let mut json = String::new();
 			json.push_str("{\n");
 			json.push_str("\"nodes\": [\n");
-			let node_ids = self.nodes(AllowIP::All);
-			for i in 0 .. node_ids.len() {
-				let node = self.nodes.get(&node_ids[i]).expect("self.nodes() only returns node IDs from self.nodes");
-				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {} }}{}\n", node, node.failures, if i == node_ids.len() - 1 {""} else {","}))
+			// banned nodes are kept so they can be used again once their ban expires.
+			let mut nodes: Vec<&Node> = self.nodes.values().filter(|n| !self.useless_nodes.contains(&n.id)).collect();
+			nodes.sort_by_key(|n| n.failures);
+			for i in 0 .. nodes.len() {
+				let node = nodes[i];
+				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {} }}{}\n", node, node.failures, if i == nodes.len() - 1 {""} else {","}))
+			}
+			json.push_str("],\n");
+			json.push_str("\"banned\": [\n");
+			let banned = self.banned();
+			for i in 0 .. banned.len() {
+				json.push_str(&format!("\t{{ \"id\": \"{}\", \"until\": {} }}{}\n", banned[i].0.hex(), banned[i].1, if i == banned.len() - 1 {""} else {","}))
 			}
 			json.push_str("]\n");
 			json.push_str("}");
@@ -298,8 +374,9 @@ impl NodeTable {
 		}
 	}
 
-	fn load(path: Option<String>) -> HashMap<NodeId, Node> {
+	fn load(path: Option<String>) -> (HashMap<NodeId, Node>, HashMap<NodeId, u64>) {
 		let mut nodes: HashMap<NodeId, Node> = HashMap::new();
+		let mut banned: HashMap<NodeId, u64> = HashMap::new();
 		if let Some(path) = path {
 			let mut path_buf = PathBuf::from(path);
 			path_buf.push("nodes.json");
@@ -307,7 +384,7 @@ impl NodeTable {
 				Ok(file) => file,
 				Err(e) => {
 					debug!("Error opening node table file: {:?}", e);
-					return nodes;
+					return (nodes, banned);
 				}
 			};
 			let mut buf = String::new();
@@ -315,14 +392,14 @@ impl NodeTable {
 				Ok(_) => {},
 				Err(e) => {
 					warn!("Error reading node table file: {:?}", e);
-					return nodes;
+					return (nodes, banned);
 				}
 			}
 			let json = match Json::from_str(&buf) {
 				Ok(json) => json,
 				Err(e) => {
 					warn!("Error parsing node table file: {:?}", e);
-					return nodes;
+					return (nodes, banned);
 				}
 			};
 			if let Some(list) = json.as_object().and_then(|o| o.get("nodes")).and_then(|n| n.as_array()) {
@@ -337,11 +414,27 @@ impl NodeTable {
 					}
 				}
 			}
+			let now = unix_now();
+			if let Some(list) = json.as_object().and_then(|o| o.get("banned")).and_then(|n| n.as_array()) {
+				for n in list.iter().filter_map(|n| n.as_object()) {
+					let id = n.get("id").and_then(|i| i.as_string()).and_then(|i| NodeId::from_str(i).ok());
+					let until = n.get("until").and_then(|u| u.as_u64());
+					if let (Some(id), Some(until)) = (id, until) {
+						if until > now {
+							banned.insert(id, until);
+						}
+					}
+				}
+			}
 		}
-		nodes
+		(nodes, banned)
 	}
 }
 
+fn unix_now() -> u64 {
+	::time::get_time().sec as u64
+}
+
 impl Drop for NodeTable {
 	fn drop(&mut self) {
 		self.save();
@@ -358,10 +451,12 @@ pub fn is_valid_node_url(url: &str) -> bool {
 mod tests {
 	use super::*;
 	use std::net::{SocketAddr, SocketAddrV4, Ipv4Addr};
+	use std::collections::{HashMap, HashSet};
+	use discovery::TableUpdates;
 	use util::H512;
 	use std::str::FromStr;
 	use devtools::*;
-	use AllowIP;
+	use IpFilter;
 
 	#[test]
 	fn endpoint_parse() {
@@ -407,10 +502,21 @@ mod tests {
 		table.note_failure(&id1);
 		table.note_failure(&id2);
 
-		let r = table.nodes(AllowIP::All);
+		let r = table.nodes(&IpFilter::default());
 		assert_eq!(r[0][..], id3[..]);
 		assert_eq!(r[1][..], id2[..]);
 		assert_eq!(r[2][..], id1[..]);
+
+		// nodes found by topic come first.
+		table.update(TableUpdates {
+			added: HashMap::new(),
+			removed: HashSet::new(),
+			topic_nodes: vec![id1.clone()].into_iter().collect(),
+		}, &HashSet::new());
+		let r = table.nodes(&IpFilter::default());
+		assert_eq!(r[0][..], id1[..]);
+		assert_eq!(r[1][..], id3[..]);
+		assert_eq!(r[2][..], id2[..]);
 	}
 
 	#[test]
@@ -429,9 +535,65 @@ mod tests {
 
 		{
 			let table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
-			let r = table.nodes(AllowIP::All);
+			let r = table.nodes(&IpFilter::default());
 			assert_eq!(r[0][..], id1[..]);
 			assert_eq!(r[1][..], id2[..]);
 		}
 	}
+
+	#[test]
+	fn table_ban() {
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let id1 = node1.id.clone();
+		let id2 = node2.id.clone();
+		let mut table = NodeTable::new(None);
+		table.add_node(node1);
+		table.add_node(node2);
+
+		assert!(!table.report(&id1, -60, 3600));
+		assert_eq!(table.reputation(&id1), -60);
+		table.decay_reputations();
+		assert_eq!(table.reputation(&id1), -30);
+		assert!(!table.report(&id1, -60, 3600));
+		assert!(table.report(&id1, -10, 3600));
+		assert!(table.is_banned(&id1));
+		assert_eq!(table.reputation(&id1), 0);
+		assert_eq!(table.nodes(&IpFilter::default()), vec![id2.clone()]);
+		assert_eq!(table.banned().len(), 1);
+
+		assert!(table.unban(&id1));
+		assert!(!table.unban(&id1));
+		assert_eq!(table.nodes(&IpFilter::default()).len(), 2);
+
+		// zero duration bans expire immediately.
+		assert!(table.report(&id2, BAN_THRESHOLD, 0));
+		assert!(!table.is_banned(&id2));
+		table.decay_reputations();
+		assert!(table.banned().is_empty());
+	}
+
+	#[test]
+	fn table_save_load_bans() {
+		let temp_path = RandomTempPath::create_dir();
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let id1 = node1.id.clone();
+		{
+			let mut table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			table.add_node(node1);
+			assert!(table.report(&id1, BAN_THRESHOLD, 3600));
+		}
+
+		{
+			let mut table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			assert!(table.is_banned(&id1));
+			table.clear_bans();
+		}
+
+		{
+			let table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			assert!(!table.is_banned(&id1));
+			assert_eq!(table.nodes(&IpFilter::default()), vec![id1.clone()]);
+		}
+	}
 }
@@ -14,10 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use {NetworkProtocolHandler, NetworkConfiguration, NonReservedPeerMode};
+use {NetworkProtocolHandler, NetworkConfiguration, NonReservedPeerMode, IpFilter};
 use error::NetworkError;
 use host::{Host, NetworkContext, NetworkIoMessage, ProtocolId};
 use stats::NetworkStats;
+use node_table::NodeId;
 use io::*;
 use parking_lot::RwLock;
 use std::sync::Arc;
@@ -48,7 +49,7 @@ pub struct NetworkService {
 	stats: Arc<NetworkStats>,
 	panic_handler: Arc<PanicHandler>,
 	host_handler: Arc<HostHandler>,
-	config: NetworkConfiguration,
+	config: RwLock<NetworkConfiguration>,
 }
 
 impl NetworkService {
@@ -67,7 +68,7 @@ impl NetworkService {
 			stats: stats,
 			panic_handler: panic_handler,
 			host: RwLock::new(None),
-			config: config,
+			config: RwLock::new(config),
 			host_handler: host_handler,
 		})
 	}
@@ -99,8 +100,8 @@ impl NetworkService {
 	}
 
 	/// Returns network configuration.
-	pub fn config(&self) -> &NetworkConfiguration {
-		&self.config
+	pub fn config(&self) -> NetworkConfiguration {
+		self.config.read().clone()
 	}
 
 	/// Returns external url if available.
@@ -119,7 +120,7 @@ impl NetworkService {
 	pub fn start(&self) -> Result<(), NetworkError> {
 		let mut host = self.host.write();
 		if host.is_none() {
-			let h = Arc::new(Host::new(self.config.clone(), self.stats.clone())?);
+			let h = Arc::new(Host::new(self.config.read().clone(), self.stats.clone())?);
 			self.io_service.register_handler(h.clone())?;
 			*host = Some(h);
 		}
@@ -162,6 +163,34 @@ impl NetworkService {
 		}
 	}
 
+	/// Currently banned peers along with the unix timestamp each ban expires at.
+	pub fn banned_peers(&self) -> Vec<(NodeId, u64)> {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			host.banned_nodes()
+		} else {
+			Vec::new()
+		}
+	}
+
+	/// Lift the ban on a peer. Returns `false` if the peer was not banned.
+	pub fn unban_peer(&self, peer: &str) -> Result<bool, NetworkError> {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			host.unban_node(peer)
+		} else {
+			Ok(false)
+		}
+	}
+
+	/// Lift all peer bans.
+	pub fn clear_banned_peers(&self) {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			host.clear_bans();
+		}
+	}
+
 	/// Set the non-reserved peer mode.
 	pub fn set_non_reserved_mode(&self, mode: NonReservedPeerMode) {
 		let host = self.host.read();
@@ -171,6 +200,17 @@ impl NetworkService {
 		}
 	}
 
+	/// Replace the IP filter applied to peer connections and discovery.
+	/// The filter is kept across network restarts.
+	pub fn set_ip_filter(&self, filter: IpFilter) {
+		self.config.write().ip_filter = filter.clone();
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			let io_ctxt = IoContext::new(self.io_service.channel(), 0);
+			host.set_ip_filter(filter, &io_ctxt);
+		}
+	}
+
 	/// Executes action in the network context
 	pub fn with_context<F>(&self, protocol: ProtocolId, action: F) where F: Fn(&NetworkContext) {
 		let io = IoContext::new(self.io_service.channel(), 0);
@@ -24,8 +24,9 @@ use mio::*;
 use mio::deprecated::{Handler, EventLoop};
 use mio::tcp::*;
 use util::hash::*;
+use util::snappy;
 use rlp::*;
-use connection::{EncryptedConnection, Packet, Connection};
+use connection::{EncryptedConnection, Packet, Connection, MAX_PAYLOAD_SIZE};
 use handshake::Handshake;
 use io::{IoContext, StreamToken};
 use error::{NetworkError, DisconnectReason};
@@ -38,6 +39,11 @@ use time;
 const PING_TIMEOUT_SEC: u64 = 15;
 const PING_INTERVAL_SEC: u64 = 30;
 
+// Oldest RLPx version we still talk to.
+const MIN_PROTOCOL_VERSION: u32 = 4;
+// Payloads are snappy-compressed once both peers announce this version in hello.
+const MIN_COMPRESSION_PROTOCOL_VERSION: u32 = 5;
+
 #[derive(Debug, Clone)]
 enum ProtocolState {
 	// Packets pending protocol on_connect event return.
@@ -56,6 +62,8 @@ pub struct Session {
 	had_hello: bool,
 	/// Session is no longer active flag.
 	expired: bool,
+	/// Packet payloads are snappy-compressed. Set after Hello exchange with a v5 peer.
+	compression: bool,
 	ping_time_ns: u64,
 	pong_time_ns: Option<u64>,
 	state: State,
@@ -198,6 +206,7 @@ impl Session {
 			ping_time_ns: 0,
 			pong_time_ns: None,
 			expired: false,
+			compression: false,
 			protocol_states: HashMap::new(),			
 		})
 	}
@@ -212,7 +221,9 @@ impl Session {
 		};
 		self.state = State::Session(connection);
 		self.write_hello(io, host)?;
-		self.send_ping(io)?;
+		// The first ping goes out once hello settles compression; until then
+		// a missing hello times out just like a missing pong.
+		self.ping_time_ns = time::precise_time_ns();
 		Ok(())
 	}
 
@@ -345,10 +356,7 @@ impl Session {
 			}
 		}
 		let pid = self.info.capabilities[i].id_offset + packet_id;
-		let mut rlp = RlpStream::new();
-		rlp.append(&(pid as u32));
-		rlp.append_raw(data, 1);
-		self.send(io, rlp)
+		self.send_payload(io, pid, data)
 	}
 
 	/// Keep this session alive. Returns false if ping timeout happened
@@ -397,14 +405,21 @@ impl Session {
 		if packet_id != PACKET_HELLO && packet_id != PACKET_DISCONNECT && !self.had_hello {
 			return Err(From::from(NetworkError::BadProtocol));
 		}
+		let data = if self.compression {
+			let mut data = vec![packet_id];
+			data.extend(decompress_payload(&packet.data[1..])?);
+			data
+		} else {
+			packet.data
+		};
 		match packet_id {
 			PACKET_HELLO => {
-				let rlp = UntrustedRlp::new(&packet.data[1..]); //TODO: validate rlp expected size
+				let rlp = UntrustedRlp::new(&data[1..]); //TODO: validate rlp expected size
 				self.read_hello(io, &rlp, host)?;
 				Ok(SessionData::Ready)
 			},
 			PACKET_DISCONNECT => {
-				let rlp = UntrustedRlp::new(&packet.data[1..]);
+				let rlp = UntrustedRlp::new(&data[1..]);
 				let reason: u8 = rlp.val_at(0)?;
 				if self.had_hello {
 					debug!("Disconnected: {}: {:?}", self.token(), DisconnectReason::from_u8(reason));
@@ -440,11 +455,11 @@ impl Session {
 				match *self.protocol_states.entry(protocol).or_insert_with(|| ProtocolState::Pending(Vec::new())) {
 					ProtocolState::Connected => {
 						trace!(target: "network", "Packet {} mapped to {:?}:{}, i={}, capabilities={:?}", packet_id, protocol, protocol_packet_id, i, self.info.capabilities);
-						Ok(SessionData::Packet { data: packet.data, protocol: protocol, packet_id: protocol_packet_id } )
+						Ok(SessionData::Packet { data: data, protocol: protocol, packet_id: protocol_packet_id } )
 					}
 					ProtocolState::Pending(ref mut pending) => {
 						trace!(target: "network", "Packet {} deferred until protocol connection event completion", packet_id);
-						pending.push((packet.data, protocol_packet_id));
+						pending.push((data, protocol_packet_id));
 
 						Ok(SessionData::Continue)
 					}
@@ -520,43 +535,52 @@ impl Session {
 			trace!(target: "network", "No common capabilities with peer.");
 			return Err(From::from(self.disconnect(io, DisconnectReason::UselessPeer)));
 		}
-		if protocol != host.protocol_version {
+		if protocol < MIN_PROTOCOL_VERSION {
 			trace!(target: "network", "Peer protocol version mismatch: {}", protocol);
 			return Err(From::from(self.disconnect(io, DisconnectReason::UselessPeer)));
 		}
+		self.compression = protocol >= MIN_COMPRESSION_PROTOCOL_VERSION && host.protocol_version >= MIN_COMPRESSION_PROTOCOL_VERSION;
+		self.send_ping(io)?;
 		self.had_hello = true;
 		Ok(())
 	}
 
 	/// Senf ping packet
 	pub fn send_ping<Message>(&mut self, io: &IoContext<Message>) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
-		self.send(io, Session::prepare(PACKET_PING)?)?;
+		self.send_payload(io, PACKET_PING, &EMPTY_LIST_RLP)?;
 		self.ping_time_ns = time::precise_time_ns();
 		self.pong_time_ns = None;
 		Ok(())
 	}
 
 	fn send_pong<Message>(&mut self, io: &IoContext<Message>) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
-		self.send(io, Session::prepare(PACKET_PONG)?)
+		self.send_payload(io, PACKET_PONG, &EMPTY_LIST_RLP)
 	}
 
 	/// Disconnect this session
 	pub fn disconnect<Message>(&mut self, io: &IoContext<Message>, reason: DisconnectReason) -> NetworkError where Message: Send + Sync + Clone {
 		if let State::Session(_) = self.state {
 			let mut rlp = RlpStream::new();
-			rlp.append(&(PACKET_DISCONNECT as u32));
 			rlp.begin_list(1);
 			rlp.append(&(reason as u32));
-			self.send(io, rlp).ok();
+			self.send_payload(io, PACKET_DISCONNECT, &rlp.out()).ok();
 		}
 		NetworkError::Disconnect(reason)
 	}
 
-	fn prepare(packet_id: u8) -> Result<RlpStream, NetworkError> {
+	/// Frame packet id and payload, compressing the payload if negotiated.
+	fn send_payload<Message>(&mut self, io: &IoContext<Message>, packet_id: u8, payload: &[u8]) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
+		let compressed;
+		let payload = if self.compression {
+			compressed = compress_payload(payload)?;
+			&compressed[..]
+		} else {
+			payload
+		};
 		let mut rlp = RlpStream::new();
 		rlp.append(&(packet_id as u32));
-		rlp.begin_list(0);
-		Ok(rlp)
+		rlp.append_raw(payload, 1);
+		self.send(io, rlp)
 	}
 
 	fn send<Message>(&mut self, io: &IoContext<Message>, rlp: RlpStream) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
@@ -572,3 +596,58 @@ impl Session {
 	}
 }
 
+/// Snappy-compress a packet payload.
+fn compress_payload(payload: &[u8]) -> Result<Vec<u8>, NetworkError> {
+	if payload.len() > MAX_PAYLOAD_SIZE {
+		return Err(NetworkError::OversizedPacket);
+	}
+	let mut compressed = Vec::new();
+	let len = snappy::compress_into(payload, &mut compressed);
+	compressed.truncate(len);
+	Ok(compressed)
+}
+
+/// Decompress a packet payload. The announced size is checked before anything is allocated.
+fn decompress_payload(compressed: &[u8]) -> Result<Vec<u8>, NetworkError> {
+	if snappy::decompressed_len(compressed)? > MAX_PAYLOAD_SIZE {
+		return Err(NetworkError::OversizedPacket);
+	}
+	Ok(snappy::decompress(compressed)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use error::NetworkError;
+	use connection::MAX_PAYLOAD_SIZE;
+	use super::{compress_payload, decompress_payload};
+
+	#[test]
+	fn compressed_payload_roundtrip() {
+		let payload = vec![42u8; 4096];
+		let compressed = compress_payload(&payload).unwrap();
+		assert!(compressed.len() < payload.len());
+		assert_eq!(decompress_payload(&compressed).unwrap(), payload);
+	}
+
+	#[test]
+	fn rejects_oversized_payload() {
+		match compress_payload(&vec![0u8; MAX_PAYLOAD_SIZE + 1]) {
+			Err(NetworkError::OversizedPacket) => {},
+			_ => panic!("Unexpected result"),
+		}
+
+		// varint length prefix announcing 2^24 bytes, with no data behind it.
+		match decompress_payload(&[0x80, 0x80, 0x80, 0x08]) {
+			Err(NetworkError::OversizedPacket) => {},
+			_ => panic!("Unexpected result"),
+		}
+	}
+
+	#[test]
+	fn rejects_invalid_payload() {
+		match decompress_payload(&[0xc0]) {
+			Err(NetworkError::BadProtocol) => {},
+			_ => panic!("Unexpected result"),
+		}
+	}
+}
@@ -128,6 +128,18 @@ fn net_start_stop() {
 	service.start().unwrap();
 }
 
+#[test]
+fn net_ip_filter_survives_restart() {
+	let service = NetworkService::new(NetworkConfiguration::new_local()).unwrap();
+	let filter = IpFilter::parse("private", "").unwrap();
+	service.set_ip_filter(filter.clone());
+	assert_eq!(service.config().ip_filter, filter);
+	service.start().unwrap();
+	service.stop().unwrap();
+	service.start().unwrap();
+	assert_eq!(service.config().ip_filter, filter);
+}
+
 #[test]
 fn net_disconnect() {
 	let key1 = Random.generate().unwrap();
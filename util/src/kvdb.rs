@@ -577,6 +577,14 @@ impl Database {
 		self.flushing.write().clear();
 	}
 
+	/// Flush and close the database, then open it again as a new handle.
+	/// Reads and writes through `self` fail afterwards.
+	pub fn reopen(&self) -> Result<Database, String> {
+		self.flush()?;
+		self.close();
+		Database::open(&self.config, &self.path)
+	}
+
 	/// Restore the database from a copy at given path.
 	pub fn restore(&self, new_db: &str) -> Result<(), UtilError> {
 		self.close();